@@ -6,9 +6,10 @@ use crate::{
     accept_type::AcceptType,
     metrics,
     response::{
-        bcs_api_disabled, block_not_found_by_height, block_not_found_by_version,
+        api_disabled, bcs_api_disabled, block_not_found_by_height, block_not_found_by_version,
         block_pruned_by_height, json_api_disabled, version_not_found, version_pruned,
-        ForbiddenError, InternalError, NotFoundError, ServiceUnavailableError, StdApiError,
+        ForbiddenError, GoneError, InternalError, NotFoundError, ServiceUnavailableError,
+        StdApiError,
     },
 };
 use anyhow::{anyhow, bail, ensure, format_err, Context as AnyhowContext, Result};
@@ -751,6 +752,76 @@ impl Context {
             .map_err(|err| E::internal_with_code(err, AptosErrorCode::InternalError, ledger_info))
     }
 
+    fn account_activity_reader<E: ForbiddenError>(&self) -> Result<&Arc<dyn IndexerReader>, E> {
+        self.indexer_reader
+            .as_ref()
+            .filter(|_| self.node_config.indexer_db_config.enable_account_activity())
+            .ok_or_else(|| api_disabled("Get account activity"))
+    }
+
+    /// Returns the first version covered by the account activity index, which only holds the
+    /// transactions committed after it was enabled.
+    pub fn get_account_activity_start_version<E: ForbiddenError + InternalError>(
+        &self,
+        ledger_info: &LedgerInfo,
+    ) -> Result<u64, E> {
+        self.account_activity_reader()?
+            .get_account_activity_start_version()
+            .context("Failed to retrieve account activity start version")
+            .map_err(|err| E::internal_with_code(err, AptosErrorCode::InternalError, ledger_info))
+    }
+
+    /// Returns up to `limit` transactions touching `address`, starting at `start_version`, or the
+    /// latest ones if it is not given, in ascending order. Versions older than the oldest one of
+    /// `ledger_info` are gone.
+    pub fn get_account_activity<E: ForbiddenError + GoneError + InternalError>(
+        &self,
+        address: AccountAddress,
+        start_version: Option<u64>,
+        limit: u16,
+        ledger_info: &LedgerInfo,
+    ) -> Result<Vec<TransactionOnChainData>, E> {
+        let indexer_reader = self.account_activity_reader()?;
+
+        let ledger_version = ledger_info.version();
+        let (start_version, order) = match start_version {
+            Some(start_version) => {
+                if start_version < ledger_info.oldest_ledger_version.0 {
+                    return Err(E::gone_with_code(
+                        format!(
+                            "Account activity before ledger version({}) is not available",
+                            ledger_info.oldest_ledger_version
+                        ),
+                        AptosErrorCode::VersionPruned,
+                        ledger_info,
+                    ));
+                }
+                (start_version, Order::Ascending)
+            },
+            None => (ledger_version, Order::Descending),
+        };
+        let mut txns = indexer_reader
+            .get_account_activity(
+                address,
+                start_version,
+                order,
+                limit as u64,
+                true,
+                ledger_version,
+            )
+            .context("Failed to retrieve account activity")
+            .map_err(|err| E::internal_with_code(err, AptosErrorCode::InternalError, ledger_info))?
+            .into_inner();
+        if order == Order::Descending {
+            txns.reverse();
+        }
+        txns.into_iter()
+            .map(|t| self.convert_into_transaction_on_chain_data(t))
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to parse account activity")
+            .map_err(|err| E::internal_with_code(err, AptosErrorCode::InternalError, ledger_info))
+    }
+
     pub fn get_transaction_by_hash(
         &self,
         hash: HashValue,
@@ -17,11 +17,13 @@ mod failpoint;
 mod index;
 mod log;
 pub mod metrics;
+mod objects;
 mod page;
 mod response;
 mod runtime;
 mod set_failpoints;
 mod state;
+mod state_overrides;
 #[cfg(test)]
 pub mod tests;
 mod transactions;
@@ -0,0 +1,306 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    accept_type::AcceptType,
+    context::{api_spawn_blocking, Context},
+    failpoint::fail_point_poem,
+    page::{determine_limit, Page},
+    response::{
+        api_disabled, BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus,
+        BasicResultWith404, GoneError, InternalError,
+    },
+    ApiTags,
+};
+use anyhow::Context as AnyhowContext;
+use aptos_api_types::{
+    Address, AptosErrorCode, LedgerInfo, MoveStructTag, ObjectOwnerChange, OwnedObject,
+    StateKeyWrapper, U64,
+};
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::ObjectGroupResource,
+    indexer::indexer_db_reader::IndexerReader,
+    state_store::state_key::{inner::StateKeyInner, StateKey},
+};
+use move_core_types::{language_storage::StructTag, move_resource::MoveStructType};
+use poem_openapi::{
+    param::{Path, Query},
+    OpenApi,
+};
+use std::{cmp::max, convert::TryInto, sync::Arc};
+
+/// API for objects and their owners
+///
+/// These are served by the object ownership index of the internal indexer.
+#[derive(Clone)]
+pub struct ObjectsApi {
+    pub context: Arc<Context>,
+}
+
+#[OpenApi]
+impl ObjectsApi {
+    /// Get account owned objects
+    ///
+    /// Retrieves the objects owned by an account at the latest ledger version,
+    /// in order of object address. If a resource type is given, only the objects
+    /// holding a resource of that type are returned. As only a bounded number
+    /// of objects is looked at per request, a page may then hold fewer objects
+    /// than the limit, or none, while the cursor still points to the next one.
+    ///
+    /// This requires the object ownership index of the internal indexer to be
+    /// enabled on the node. It only knows the objects whose owner changed since
+    /// the index was enabled, so the `X-Aptos-Ledger-Oldest-Version` header
+    /// reports the first version it covers.
+    #[oai(
+        path = "/accounts/:address/objects",
+        method = "get",
+        operation_id = "get_account_owned_objects",
+        tag = "ApiTags::Accounts"
+    )]
+    async fn get_account_owned_objects(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Type of a resource the objects must hold, e.g. `0x4::token::Token`
+        resource_type: Query<Option<MoveStructTag>>,
+        /// Cursor specifying where to start for pagination
+        ///
+        /// This cursor cannot be derived manually client-side. Instead, you must
+        /// call this endpoint once without this query parameter specified, and
+        /// then use the cursor returned in the X-Aptos-Cursor header in the
+        /// response.
+        start: Query<Option<StateKeyWrapper>>,
+        /// Max number of objects to retrieve
+        ///
+        /// If not provided, defaults to default page size.
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<OwnedObject>> {
+        fail_point_poem("endpoint_get_account_owned_objects")?;
+        self.context
+            .check_api_output_enabled("Get account owned objects", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.owned_objects(
+                &accept_type,
+                address.0,
+                resource_type.0,
+                start.0.map(StateKey::from),
+                limit.0,
+            )
+        })
+        .await
+    }
+
+    /// Get object owner history
+    ///
+    /// Retrieves the changes of the owner of an object in order of ledger
+    /// version: its creation, its transfers and its deletion.
+    ///
+    /// This requires the object ownership index of the internal indexer to be
+    /// enabled on the node. It only covers the transactions committed since the
+    /// index was enabled, so the `X-Aptos-Ledger-Oldest-Version` header reports
+    /// the first version it covers, and starting before it is an error.
+    #[oai(
+        path = "/objects/:address/owners",
+        method = "get",
+        operation_id = "get_object_owner_history",
+        tag = "ApiTags::Accounts"
+    )]
+    async fn get_object_owner_history(
+        &self,
+        accept_type: AcceptType,
+        /// Address of object with or without a `0x` prefix
+        address: Path<Address>,
+        /// Ledger version to start list of owner changes
+        ///
+        /// If not provided, defaults to the first version covered by the index
+        start: Query<Option<U64>>,
+        /// Max number of owner changes to retrieve
+        ///
+        /// If not provided, defaults to default page size
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<ObjectOwnerChange>> {
+        fail_point_poem("endpoint_get_object_owner_history")?;
+        self.context
+            .check_api_output_enabled("Get object owner history", &accept_type)?;
+        let page = Page::new(
+            start.0.map(|v| v.0),
+            limit.0,
+            self.context.max_transactions_page_size(),
+        );
+
+        let api = self.clone();
+        api_spawn_blocking(move || api.owner_history(&accept_type, address.0, page)).await
+    }
+}
+
+impl ObjectsApi {
+    /// Returns the indexer reader if the object ownership index is enabled
+    fn indexer_reader(&self, api_name: &str) -> Result<&Arc<dyn IndexerReader>, BasicErrorWith404> {
+        self.context
+            .indexer_reader
+            .as_ref()
+            .filter(|_| {
+                self.context
+                    .node_config
+                    .indexer_db_config
+                    .enable_object_ownership()
+            })
+            .ok_or_else(|| api_disabled(api_name))
+    }
+
+    /// Returns the latest ledger info, reporting the versions before the object ownership index
+    /// was enabled as unavailable since the index doesn't cover them
+    fn latest_ledger_info(
+        &self,
+        indexer_reader: &Arc<dyn IndexerReader>,
+    ) -> Result<LedgerInfo, BasicErrorWith404> {
+        let mut latest_ledger_info = self.context.get_latest_ledger_info()?;
+        let start_version = indexer_reader
+            .get_object_ownership_start_version()
+            .context("Failed to retrieve object ownership start version")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &latest_ledger_info,
+                )
+            })?;
+        latest_ledger_info.oldest_ledger_version =
+            max(latest_ledger_info.oldest_ledger_version.0, start_version).into();
+        Ok(latest_ledger_info)
+    }
+
+    fn owned_objects(
+        &self,
+        accept_type: &AcceptType,
+        owner: Address,
+        resource_type: Option<MoveStructTag>,
+        start: Option<StateKey>,
+        limit: Option<u16>,
+    ) -> BasicResultWith404<Vec<OwnedObject>> {
+        let indexer_reader = self.indexer_reader("Get account owned objects")?;
+        let resource_type: Option<StructTag> = resource_type
+            .map(|resource_type| resource_type.try_into())
+            .transpose()
+            .context("Failed to parse given resource type")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        // The cursor is the object group of the first object of the page
+        let start_object = match start.as_ref().map(StateKey::inner) {
+            Some(StateKeyInner::AccessPath(access_path)) => access_path.address,
+            Some(_) => {
+                return Err(BasicErrorWith404::bad_request_with_code_no_info(
+                    "Invalid cursor",
+                    AptosErrorCode::InvalidInput,
+                ))
+            },
+            None => AccountAddress::ZERO,
+        };
+
+        let latest_ledger_info = self.latest_ledger_info(indexer_reader)?;
+        let max_page_size = self.context.max_account_resources_page_size();
+        let limit = determine_limit(limit, max_page_size, max_page_size, &latest_ledger_info)?;
+        let (objects, next_object) = indexer_reader
+            .get_owned_objects(
+                owner.into(),
+                resource_type.as_ref(),
+                start_object,
+                limit as u64,
+                latest_ledger_info.version(),
+            )
+            .context("Failed to retrieve owned objects")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &latest_ledger_info,
+                )
+            })?;
+        let next_cursor = next_object
+            .map(|object| StateKey::resource_group(&object, &ObjectGroupResource::struct_tag()));
+
+        match accept_type {
+            AcceptType::Json => {
+                let objects: Vec<_> = objects
+                    .into_iter()
+                    .map(|(object, version)| OwnedObject {
+                        address: object.into(),
+                        owned_since_version: version.into(),
+                    })
+                    .collect();
+                BasicResponse::try_from_json((
+                    objects,
+                    &latest_ledger_info,
+                    BasicResponseStatus::Ok,
+                ))
+                .map(|v| v.with_cursor(next_cursor))
+            },
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((objects, &latest_ledger_info, BasicResponseStatus::Ok))
+                    .map(|v| v.with_cursor(next_cursor))
+            },
+        }
+    }
+
+    fn owner_history(
+        &self,
+        accept_type: &AcceptType,
+        object: Address,
+        page: Page,
+    ) -> BasicResultWith404<Vec<ObjectOwnerChange>> {
+        let indexer_reader = self.indexer_reader("Get object owner history")?;
+        let latest_ledger_info = self.latest_ledger_info(indexer_reader)?;
+        let oldest_version = latest_ledger_info.oldest_ledger_version.0;
+        let start_version = page.start_option().unwrap_or(oldest_version);
+        if start_version < oldest_version {
+            return Err(BasicErrorWith404::gone_with_code(
+                format!(
+                    "Object owner history before ledger version({}) is not available",
+                    oldest_version
+                ),
+                AptosErrorCode::VersionPruned,
+                &latest_ledger_info,
+            ));
+        }
+        let history = indexer_reader
+            .get_object_owner_history(
+                object.into(),
+                start_version,
+                page.limit(&latest_ledger_info)? as u64,
+                latest_ledger_info.version(),
+            )
+            .context("Failed to retrieve object owner history")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &latest_ledger_info,
+                )
+            })?;
+
+        match accept_type {
+            AcceptType::Json => {
+                let history: Vec<_> = history
+                    .into_iter()
+                    .map(|(version, owner)| ObjectOwnerChange {
+                        version: version.into(),
+                        owner: owner.map(Into::into),
+                    })
+                    .collect();
+                BasicResponse::try_from_json((
+                    history,
+                    &latest_ledger_info,
+                    BasicResponseStatus::Ok,
+                ))
+            },
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((history, &latest_ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
+}
@@ -5,8 +5,8 @@
 use crate::{
     accounts::AccountsApi, basic::BasicApi, blocks::BlocksApi, check_size::PostSizeLimit,
     context::Context, error_converter::convert_error, events::EventsApi, index::IndexApi,
-    log::middleware_log, set_failpoints, state::StateApi, transactions::TransactionsApi,
-    view_function::ViewFunctionApi,
+    log::middleware_log, objects::ObjectsApi, set_failpoints, state::StateApi,
+    transactions::TransactionsApi, view_function::ViewFunctionApi,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_config::config::{ApiConfig, NodeConfig};
@@ -104,6 +104,7 @@ pub fn get_api_service(
         BlocksApi,
         EventsApi,
         IndexApi,
+        ObjectsApi,
         StateApi,
         TransactionsApi,
         ViewFunctionApi,
@@ -127,6 +128,9 @@ pub fn get_api_service(
         IndexApi {
             context: context.clone(),
         },
+        ObjectsApi {
+            context: context.clone(),
+        },
         StateApi {
             context: context.clone(),
         },
@@ -0,0 +1,328 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, format_err, Result};
+use aptos_api_types::{AsConverter, MoveConverter, ResourceGroup, StateOverrides};
+use aptos_storage_interface::{state_view::DbStateView, DbReader};
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::{
+        fungible_store::{primary_store, FungibleStoreResource},
+        AccountResource, CoinStoreResource, ObjectCoreResource, ObjectGroupResource,
+    },
+    event::{EventHandle, EventKey},
+    indexer::indexer_db_reader::IndexerReader,
+    on_chain_config::{CurrentTimeMicroseconds, FeatureFlag, Features, OnChainConfig},
+    state_store::{
+        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+        Result as StateViewResult, TStateView,
+    },
+    write_set::{TransactionWrite, WriteSet},
+};
+use move_binary_format::CompiledModule;
+use move_core_types::{
+    language_storage::{StructTag, TypeTag},
+    move_resource::MoveStructType,
+};
+use serde_json::{json, Value};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+
+/// The creation number of the first GUID of an object, as in `0x1::object`
+const INIT_GUID_CREATION_NUM: u64 = 0x4000000000000;
+
+/// The state seen by a simulation: the latest state with the state overrides and the outputs of
+/// the previously simulated transactions applied on top of it.
+///
+/// Nothing is ever written to the DB, the changes only live as long as the simulation.
+pub struct SimulationStateView {
+    base: DbStateView,
+    changes: HashMap<StateKey, Option<StateValue>>,
+    db: Arc<dyn DbReader>,
+    indexer_reader: Option<Arc<dyn IndexerReader>>,
+}
+
+impl TStateView for SimulationStateView {
+    type Key = StateKey;
+
+    fn get_state_value(&self, state_key: &StateKey) -> StateViewResult<Option<StateValue>> {
+        match self.changes.get(state_key) {
+            Some(value) => Ok(value.clone()),
+            None => self.base.get_state_value(state_key),
+        }
+    }
+
+    fn get_usage(&self) -> StateViewResult<StateStorageUsage> {
+        Ok(StateStorageUsage::new_untracked())
+    }
+}
+
+impl SimulationStateView {
+    pub fn new(
+        base: DbStateView,
+        db: Arc<dyn DbReader>,
+        indexer_reader: Option<Arc<dyn IndexerReader>>,
+    ) -> Self {
+        Self {
+            base,
+            changes: HashMap::new(),
+            db,
+            indexer_reader,
+        }
+    }
+
+    /// A converter rendering the types and values of the simulated state, including the ones of
+    /// overridden modules
+    pub fn converter(&self) -> MoveConverter<Self> {
+        self.as_converter(self.db.clone(), self.indexer_reader.clone())
+    }
+
+    /// Applies the write set of a simulated transaction, so the next transactions see its outputs
+    pub fn apply_write_set(&mut self, write_set: &WriteSet) {
+        for (state_key, write_op) in write_set.iter() {
+            self.changes
+                .insert(state_key.clone(), write_op.as_state_value());
+        }
+    }
+
+    /// Applies the state overrides, in the order documented on `StateOverrides`
+    pub fn apply_overrides(&mut self, overrides: &StateOverrides) -> Result<()> {
+        if overrides.enabled_features.is_some() || overrides.disabled_features.is_some() {
+            let mut features = Features::fetch_config(self).unwrap_or_default();
+            for name in overrides.enabled_features.iter().flatten() {
+                features.enable(parse_feature_flag(name)?);
+            }
+            for name in overrides.disabled_features.iter().flatten() {
+                features.disable(parse_feature_flag(name)?);
+            }
+            self.insert(
+                StateKey::on_chain_config::<Features>()?,
+                bcs::to_bytes(&features)?,
+            );
+        }
+
+        for balance in overrides.balances.iter().flatten() {
+            let address = balance.address.into();
+            let amount = balance.amount.0;
+            let uses_primary_store = self.uses_primary_apt_store(address)?;
+            if uses_primary_store {
+                self.set_primary_apt_store_balance(address, amount)?;
+            }
+            // With a primary store the balance is the sum of the coins and the fungible asset, so
+            // any coins are dropped
+            let coins = if uses_primary_store { 0 } else { amount };
+            let coin_store_tag = CoinStoreResource::struct_tag();
+            if !uses_primary_store
+                || self
+                    .converter()
+                    .find_resource(self, address.into(), &coin_store_tag)?
+                    .is_some()
+            {
+                self.update_resource(
+                    address,
+                    &coin_store_tag,
+                    || {
+                        CoinStoreResource::new(
+                            coins,
+                            false,
+                            EventHandle::new(EventKey::new(2, address), 0),
+                            EventHandle::new(EventKey::new(3, address), 0),
+                        )
+                    },
+                    |data| data["coin"]["value"] = json!(coins.to_string()),
+                )?;
+            }
+        }
+
+        for sequence_number in overrides.sequence_numbers.iter().flatten() {
+            let address = sequence_number.address.into();
+            let sequence_number = sequence_number.sequence_number.0;
+            self.update_resource(
+                address,
+                &AccountResource::struct_tag(),
+                || {
+                    AccountResource::new(
+                        sequence_number,
+                        address.to_vec(),
+                        EventHandle::new(EventKey::new(0, address), 0),
+                        EventHandle::new(EventKey::new(1, address), 0),
+                    )
+                },
+                |data| {
+                    data["sequence_number"] = json!(sequence_number.to_string());
+                    // Account the creation numbers of the two event handles of new accounts
+                    if data["guid_creation_num"] == json!("0") {
+                        data["guid_creation_num"] = json!("2");
+                    }
+                },
+            )?;
+        }
+
+        for resource in overrides.resources.iter().flatten() {
+            let tag: StructTag = resource.typ.clone().try_into()?;
+            self.write_resource(
+                resource.address.into(),
+                &tag,
+                serde_json::to_value(&resource.data)?,
+            )?;
+        }
+
+        for module in overrides.modules.iter().flatten() {
+            let compiled_module = CompiledModule::deserialize(module.bytecode.inner())
+                .map_err(|err| format_err!("Invalid module bytecode: {}", err))?;
+            let module_id = compiled_module.self_id();
+            if module_id.address() != module.address.inner() {
+                bail!(
+                    "Module {} cannot be published under address {}",
+                    module_id,
+                    module.address
+                );
+            }
+            self.insert(
+                StateKey::module_id(&module_id),
+                module.bytecode.inner().to_vec(),
+            );
+        }
+
+        if let Some(timestamp_usecs) = overrides.timestamp_usecs {
+            let timestamp = CurrentTimeMicroseconds {
+                microseconds: timestamp_usecs.0,
+            };
+            self.insert(
+                StateKey::on_chain_config::<CurrentTimeMicroseconds>()?,
+                bcs::to_bytes(&timestamp)?,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Whether the APT of the account is kept in its primary fungible store: the account has
+    /// one, or new accounts or all operations default to it.
+    fn uses_primary_apt_store(&self, address: AccountAddress) -> Result<bool> {
+        let features = Features::fetch_config(self).unwrap_or_default();
+        Ok(
+            features.is_enabled(FeatureFlag::NEW_ACCOUNTS_DEFAULT_TO_FA_APT_STORE)
+                || features.is_enabled(FeatureFlag::OPERATIONS_DEFAULT_TO_FA_APT_STORE)
+                || self
+                    .primary_apt_store_group(address)?
+                    .contains_key(&FungibleStoreResource::struct_tag()),
+        )
+    }
+
+    fn primary_apt_store_group(&self, address: AccountAddress) -> Result<ResourceGroup> {
+        let state_key =
+            StateKey::resource_group(&primary_store(&address), &ObjectGroupResource::struct_tag());
+        Ok(match self.get_state_value_bytes(&state_key)? {
+            Some(group_bytes) => bcs::from_bytes(&group_bytes)?,
+            None => ResourceGroup::new(),
+        })
+    }
+
+    /// Sets the balance of the primary APT store of the account, creating the store if it
+    /// doesn't exist. The store is a member of the `ObjectGroup` of its object.
+    fn set_primary_apt_store_balance(
+        &mut self,
+        address: AccountAddress,
+        amount: u64,
+    ) -> Result<()> {
+        let store_address = primary_store(&address);
+        let mut group = self.primary_apt_store_group(address)?;
+        if !group.contains_key(&ObjectCoreResource::struct_tag()) {
+            // Primary stores are owned by the account and cannot be transferred
+            let object_core = ObjectCoreResource::new(
+                INIT_GUID_CREATION_NUM + 1,
+                address,
+                false,
+                EventHandle::new(EventKey::new(INIT_GUID_CREATION_NUM, store_address), 0),
+            );
+            group.insert(
+                ObjectCoreResource::struct_tag(),
+                bcs::to_bytes(&object_core)?,
+            );
+        }
+
+        // With a concurrent balance, the balance of the store itself is not used
+        let concurrent_balance_tag =
+            StructTag::from_str("0x1::fungible_asset::ConcurrentFungibleBalance")?;
+        let store_balance = match group.get_mut(&concurrent_balance_tag) {
+            Some(bytes) => {
+                // An `Aggregator<u64>` with its value and maximal value
+                let (_, max_value): (u64, u64) = bcs::from_bytes(bytes)?;
+                *bytes = bcs::to_bytes(&(amount, max_value))?;
+                0
+            },
+            None => amount,
+        };
+        let store = match group.get(&FungibleStoreResource::struct_tag()) {
+            Some(bytes) => {
+                let store: FungibleStoreResource = bcs::from_bytes(bytes)?;
+                FungibleStoreResource::new(store.metadata(), store_balance, store.frozen())
+            },
+            None => FungibleStoreResource::new(AccountAddress::TEN, store_balance, false),
+        };
+        group.insert(FungibleStoreResource::struct_tag(), bcs::to_bytes(&store)?);
+
+        self.insert(
+            StateKey::resource_group(&store_address, &ObjectGroupResource::struct_tag()),
+            bcs::to_bytes(&group)?,
+        );
+        Ok(())
+    }
+
+    fn insert(&mut self, state_key: StateKey, bytes: Vec<u8>) {
+        self.changes
+            .insert(state_key, Some(StateValue::new_legacy(bytes.into())));
+    }
+
+    /// Reads a resource as JSON, creating it from `default` if it doesn't exist, and writes it
+    /// back after `update`
+    fn update_resource<T: serde::Serialize>(
+        &mut self,
+        address: AccountAddress,
+        tag: &StructTag,
+        default: impl FnOnce() -> T,
+        update: impl FnOnce(&mut Value),
+    ) -> Result<()> {
+        let converter = self.converter();
+        let bytes = match converter.find_resource(self, address.into(), tag)? {
+            Some(bytes) => bytes.to_vec(),
+            None => bcs::to_bytes(&default())?,
+        };
+        let mut data = serde_json::to_value(converter.try_into_resource(tag, &bytes)?.data)?;
+        update(&mut data);
+        self.write_resource(address, tag, data)
+    }
+
+    fn write_resource(
+        &mut self,
+        address: AccountAddress,
+        tag: &StructTag,
+        data: Value,
+    ) -> Result<()> {
+        let converter = self.converter();
+        let bytes = converter
+            .try_into_vm_value(&TypeTag::Struct(Box::new(tag.clone())), data)?
+            .undecorate()
+            .simple_serialize()
+            .ok_or_else(|| format_err!("Failed to serialize resource {}", tag))?;
+
+        match converter.resource_group_of(tag) {
+            Some(group_tag) => {
+                let state_key = StateKey::resource_group(&address, &group_tag);
+                let mut group: ResourceGroup = match self.get_state_value_bytes(&state_key)? {
+                    Some(group_bytes) => bcs::from_bytes(&group_bytes)?,
+                    None => ResourceGroup::new(),
+                };
+                group.insert(tag.clone(), bytes);
+                let group_bytes = bcs::to_bytes(&group)?;
+                self.insert(state_key, group_bytes);
+            },
+            None => self.insert(StateKey::resource(&address, tag)?, bytes),
+        }
+        Ok(())
+    }
+}
+
+fn parse_feature_flag(name: &str) -> Result<FeatureFlag> {
+    FeatureFlag::from_str(name).map_err(|_| format_err!("Unknown feature flag: {}", name))
+}
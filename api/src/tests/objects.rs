@@ -1,8 +1,9 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use super::new_test_context;
+use super::{new_test_context, new_test_context_with_config};
 use aptos_api_test_context::{current_function_name, TestContext};
+use aptos_config::config::NodeConfig;
 use aptos_types::{
     account_address::{self, AccountAddress},
     event::EventKey,
@@ -109,6 +110,118 @@ async fn test_gen_object() {
     assert_eq!(handle.as_array().unwrap().len(), 1);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_object_ownership_without_index() {
+    let mut context = new_test_context(current_function_name!());
+    let user = context.create_account().await;
+
+    let resp = context
+        .expect_status_code(403)
+        .get(&format!("/accounts/{}/objects", user.address()))
+        .await;
+    assert_eq!(resp["error_code"], "api_disabled");
+
+    let resp = context
+        .expect_status_code(403)
+        .get(&format!("/objects/{}/owners", user.address()))
+        .await;
+    assert_eq!(resp["error_code"], "api_disabled");
+}
+
+// Creates, transfers and burns a token, checking its owners at every step.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_object_ownership() {
+    let mut node_config = NodeConfig::default();
+    node_config.indexer_db_config.enable_object_ownership = true;
+    let mut context = new_test_context_with_config(current_function_name!(), node_config);
+    let mut creator = context.create_account().await;
+    let receiver = context.gen_account().address();
+    let owned_tokens = |owner: AccountAddress| {
+        format!(
+            "/accounts/{}/objects?resource_type=0x4::aptos_token::AptosToken",
+            owner
+        )
+    };
+
+    context
+        .api_execute_entry_function(
+            &mut creator,
+            "0x4::aptos_token::create_collection",
+            json!([]),
+            json!([
+                "description",
+                "10",
+                "collection",
+                "uri",
+                false,
+                false,
+                false,
+                false,
+                false,
+                false,
+                false,
+                true,
+                false,
+                "0",
+                "1"
+            ]),
+        )
+        .await;
+    context
+        .api_execute_entry_function(
+            &mut creator,
+            "0x4::aptos_token::mint",
+            json!([]),
+            json!(["collection", "description", "token", "uri", [], [], []]),
+        )
+        .await;
+    let objects = context.get(&owned_tokens(creator.address())).await;
+    let objects = objects.as_array().unwrap();
+    assert_eq!(objects.len(), 1);
+    let token = address(&objects[0]["address"]);
+    let create_version = objects[0]["owned_since_version"].clone();
+
+    context
+        .api_execute_entry_function(
+            &mut creator,
+            "0x1::object::transfer_call",
+            json!([]),
+            json!([token, receiver]),
+        )
+        .await;
+    assert_eq!(
+        context.get(&owned_tokens(creator.address())).await,
+        json!([])
+    );
+    let objects = context.get(&owned_tokens(receiver)).await;
+    assert_eq!(objects.as_array().unwrap().len(), 1);
+    assert_eq!(address(&objects[0]["address"]), token);
+    let transfer_version = objects[0]["owned_since_version"].clone();
+
+    context
+        .api_execute_entry_function(
+            &mut creator,
+            "0x4::aptos_token::burn",
+            json!(["0x4::aptos_token::AptosToken"]),
+            json!([token]),
+        )
+        .await;
+    assert_eq!(context.get(&owned_tokens(receiver)).await, json!([]));
+
+    let history = context.get(&format!("/objects/{}/owners", token)).await;
+    let history = history.as_array().unwrap();
+    assert_eq!(history.len(), 3);
+    assert_eq!(history[0]["version"], create_version);
+    assert_eq!(address(&history[0]["owner"]), creator.address());
+    assert_eq!(history[1]["version"], transfer_version);
+    assert_eq!(address(&history[1]["owner"]), receiver);
+    assert_eq!(history[2]["owner"], Value::Null);
+}
+
+fn address(value: &Value) -> AccountAddress {
+    value.as_str().unwrap().parse().unwrap()
+}
+
 fn to_object(value: Value) -> BTreeMap<String, Value> {
     value
         .as_array()
@@ -4,12 +4,16 @@
 use super::new_test_context;
 use aptos_api_test_context::{current_function_name, TestContext};
 use aptos_crypto::ed25519::Ed25519Signature;
-use aptos_types::transaction::{
-    authenticator::TransactionAuthenticator, EntryFunction, TransactionPayload,
+use aptos_types::{
+    account_config::fungible_store::primary_store,
+    transaction::{
+        authenticator::TransactionAuthenticator, EntryFunction, SignedTransaction,
+        TransactionPayload,
+    },
 };
-use move_core_types::{ident_str, language_storage::ModuleId};
-use serde_json::json;
-use std::path::PathBuf;
+use move_core_types::{account_address::AccountAddress, ident_str, language_storage::ModuleId};
+use serde_json::{json, Value};
+use std::{path::PathBuf, str::FromStr};
 
 async fn simulate_aptos_transfer(
     context: &mut TestContext,
@@ -153,3 +157,497 @@ async fn test_simulate_txn_with_aggregator() {
         unreachable!("Simulation uses Ed25519 authenticator.");
     }
 }
+
+fn simulation_request(txn: &SignedTransaction, function: &str, arguments: Vec<String>) -> Value {
+    let public_key = match txn.authenticator_ref() {
+        TransactionAuthenticator::Ed25519 { public_key, .. } => public_key.to_string(),
+        _ => unreachable!("Simulation uses Ed25519 authenticator."),
+    };
+    json!({
+        "sender": txn.sender().to_string(),
+        "sequence_number": txn.sequence_number().to_string(),
+        "max_gas_amount": txn.max_gas_amount().to_string(),
+        "gas_unit_price": txn.gas_unit_price().to_string(),
+        "expiration_timestamp_secs": txn.expiration_timestamp_secs().to_string(),
+        "payload": {
+            "type": "entry_function_payload",
+            "function": function,
+            "type_arguments": [],
+            "arguments": arguments,
+        },
+        "signature": {
+            "type": "ed25519_signature",
+            "public_key": public_key,
+            "signature": Ed25519Signature::dummy_signature().to_string(),
+        }
+    })
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_bundle_with_state_overrides() {
+    let mut context = new_test_context(current_function_name!());
+    // The sender only exists in the simulation, through the state overrides
+    let sender = &mut context.gen_account();
+    let receiver = context.gen_account();
+    let arguments = vec![
+        receiver.address().to_standard_string(),
+        SMALL_TRANSFER_AMOUNT.to_string(),
+    ];
+    let transactions: Vec<_> = (0..2)
+        .map(|_| {
+            let txn =
+                context.account_transfer_to(sender, receiver.address(), SMALL_TRANSFER_AMOUNT);
+            simulation_request(&txn, "0x1::aptos_account::transfer", arguments.clone())
+        })
+        .collect();
+
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/transactions/simulate_bundle",
+            json!({
+                "transactions": transactions,
+                "state_overrides": {
+                    "balances": [{
+                        "address": sender.address().to_standard_string(),
+                        "amount": LARGE_TRANSFER_AMOUNT.to_string(),
+                    }],
+                    "sequence_numbers": [{
+                        "address": sender.address().to_standard_string(),
+                        "sequence_number": "0",
+                    }],
+                },
+            }),
+        )
+        .await;
+
+    // The second transfer sees the sequence number and balance left by the first one
+    let simulated = resp.as_array().unwrap();
+    assert_eq!(simulated.len(), 2);
+    for txn in simulated {
+        assert!(txn["success"].as_bool().is_some_and(|v| v), "{}", txn);
+    }
+    assert_eq!(simulated[1]["sequence_number"], json!("1"));
+
+    // Nothing was committed
+    context
+        .expect_status_code(404)
+        .get(&format!(
+            "/accounts/{}",
+            sender.address().to_standard_string()
+        ))
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_bundle_with_primary_fungible_store() {
+    let mut context = new_test_context(current_function_name!());
+    let sender = &mut context.gen_account();
+    let receiver = context.gen_account();
+    let txn = context.account_transfer_to(sender, receiver.address(), SMALL_TRANSFER_AMOUNT);
+    let request = simulation_request(&txn, "0x1::aptos_account::transfer", vec![
+        receiver.address().to_standard_string(),
+        SMALL_TRANSFER_AMOUNT.to_string(),
+    ]);
+
+    // With the APT of the sender in its primary store, the balance override funds that store
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/transactions/simulate_bundle",
+            json!({
+                "transactions": [request],
+                "state_overrides": {
+                    "enabled_features": [
+                        "NEW_ACCOUNTS_DEFAULT_TO_FA_APT_STORE",
+                        "OPERATIONS_DEFAULT_TO_FA_APT_STORE",
+                    ],
+                    "balances": [{
+                        "address": sender.address().to_standard_string(),
+                        "amount": LARGE_TRANSFER_AMOUNT.to_string(),
+                    }],
+                    "sequence_numbers": [{
+                        "address": sender.address().to_standard_string(),
+                        "sequence_number": "0",
+                    }],
+                },
+            }),
+        )
+        .await;
+
+    let simulated = &resp.as_array().unwrap()[0];
+    assert!(
+        simulated["success"].as_bool().is_some_and(|v| v),
+        "{}",
+        simulated
+    );
+    let sender_store = primary_store(&sender.address());
+    let store_written = simulated["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|change| {
+            change["type"] == json!("write_resource")
+                && change["data"]["type"] == json!("0x1::fungible_asset::FungibleStore")
+                && AccountAddress::from_str(change["address"].as_str().unwrap()).unwrap()
+                    == sender_store
+        });
+    assert!(store_written, "{}", simulated["changes"]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_bundle_with_unknown_feature_flag() {
+    let mut context = new_test_context(current_function_name!());
+    let sender = &mut context.gen_account();
+    let receiver = context.gen_account();
+    let txn = context.account_transfer_to(sender, receiver.address(), SMALL_TRANSFER_AMOUNT);
+    let request = simulation_request(&txn, "0x1::aptos_account::transfer", vec![
+        receiver.address().to_standard_string(),
+        SMALL_TRANSFER_AMOUNT.to_string(),
+    ]);
+
+    context
+        .expect_status_code(400)
+        .post(
+            "/transactions/simulate_bundle",
+            json!({
+                "transactions": [request],
+                "state_overrides": { "enabled_features": ["NOT_A_FEATURE"] },
+            }),
+        )
+        .await;
+}
+
+/// Returns the written resource whose type ends with the name, under the address
+fn written_resource<'a>(
+    simulated: &'a Value,
+    address: AccountAddress,
+    type_name: &str,
+) -> Option<&'a Value> {
+    simulated["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|change| {
+            change["type"] == json!("write_resource")
+                && change["data"]["type"]
+                    .as_str()
+                    .unwrap()
+                    .ends_with(type_name)
+                && AccountAddress::from_str(change["address"].as_str().unwrap()).unwrap() == address
+        })
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_bundle_with_resource_overrides() {
+    let mut context = new_test_context(current_function_name!());
+    let sender = &mut context.gen_account();
+    let receiver = context.gen_account();
+    // An object owned by the sender, whose `ObjectCore` is a member of the `ObjectGroup`
+    let object = context.gen_account().address();
+    sender.set_sequence_number(5);
+    let txn = context.account_transfer_to(sender, receiver.address(), SMALL_TRANSFER_AMOUNT);
+    let request = simulation_request(&txn, "0x1::object::transfer_call", vec![
+        object.to_standard_string(),
+        receiver.address().to_standard_string(),
+    ]);
+
+    let event_handle = |address: AccountAddress, creation_num: u64| {
+        json!({
+            "counter": "0",
+            "guid": {
+                "id": {
+                    "addr": address.to_standard_string(),
+                    "creation_num": creation_num.to_string(),
+                },
+            },
+        })
+    };
+    let authentication_key = format!("0x{}", hex::encode(sender.authentication_key().to_vec()));
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/transactions/simulate_bundle",
+            json!({
+                "transactions": [request],
+                "state_overrides": {
+                    "balances": [{
+                        "address": sender.address().to_standard_string(),
+                        "amount": LARGE_TRANSFER_AMOUNT.to_string(),
+                    }],
+                    "resources": [
+                        {
+                            "address": sender.address().to_standard_string(),
+                            "type": "0x1::account::Account",
+                            "data": {
+                                "authentication_key": authentication_key,
+                                "sequence_number": "5",
+                                "guid_creation_num": "4",
+                                "coin_register_events": event_handle(sender.address(), 0),
+                                "key_rotation_events": event_handle(sender.address(), 1),
+                                "rotation_capability_offer": { "for": { "vec": [] } },
+                                "signer_capability_offer": { "for": { "vec": [] } },
+                            },
+                        },
+                        {
+                            "address": object.to_standard_string(),
+                            "type": "0x1::object::ObjectCore",
+                            "data": {
+                                "guid_creation_num": "1125899906842625",
+                                "owner": sender.address().to_standard_string(),
+                                "allow_ungated_transfer": true,
+                                "transfer_events": event_handle(object, 1125899906842624),
+                            },
+                        },
+                    ],
+                },
+            }),
+        )
+        .await;
+
+    // The account resource let the transaction with sequence number 5 through, and the object
+    // was found in its resource group
+    let simulated = &resp.as_array().unwrap()[0];
+    assert!(
+        simulated["success"].as_bool().is_some_and(|v| v),
+        "{}",
+        simulated
+    );
+    assert_eq!(simulated["sequence_number"], json!("5"));
+    let object_core = written_resource(simulated, object, "::object::ObjectCore")
+        .unwrap_or_else(|| panic!("{}", simulated["changes"]));
+    assert_eq!(
+        AccountAddress::from_str(object_core["data"]["data"]["owner"].as_str().unwrap()).unwrap(),
+        receiver.address()
+    );
+}
+
+/// Returns the bytecode of the `message` module of the `pack_string_resource` package, published
+/// under the address
+fn message_module(address: AccountAddress) -> Vec<u8> {
+    let path =
+        PathBuf::from(std::env!("CARGO_MANIFEST_DIR")).join("src/tests/move/pack_string_resource");
+    match TestContext::build_package(path, vec![("addr".to_string(), address)]) {
+        // `0x1::code::publish_package_txn(metadata, code)`
+        TransactionPayload::EntryFunction(entry_function) => {
+            let code: Vec<Vec<u8>> = bcs::from_bytes(&entry_function.args()[1]).unwrap();
+            code.into_iter().next().unwrap()
+        },
+        _ => unreachable!("Packages are published with an entry function."),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_bundle_with_module_overrides() {
+    let mut context = new_test_context(current_function_name!());
+    let sender = &mut context.gen_account();
+    let receiver = context.gen_account();
+    let txn = context.account_transfer_to(sender, receiver.address(), SMALL_TRANSFER_AMOUNT);
+    let function = format!(
+        "{}::message::set_message",
+        sender.address().to_standard_string()
+    );
+    let request = simulation_request(&txn, &function, vec![format!("0x{}", hex::encode("hello"))]);
+    let bundle = |module_address: AccountAddress| {
+        json!({
+            "transactions": [request],
+            "state_overrides": {
+                "balances": [{
+                    "address": sender.address().to_standard_string(),
+                    "amount": LARGE_TRANSFER_AMOUNT.to_string(),
+                }],
+                "sequence_numbers": [{
+                    "address": sender.address().to_standard_string(),
+                    "sequence_number": "0",
+                }],
+                "modules": [{
+                    "address": module_address.to_standard_string(),
+                    "bytecode": format!("0x{}", hex::encode(message_module(sender.address()))),
+                }],
+            },
+        })
+    };
+
+    // The module only exists in the simulation, and its resources are rendered
+    let resp = context
+        .expect_status_code(200)
+        .post("/transactions/simulate_bundle", bundle(sender.address()))
+        .await;
+    let simulated = &resp.as_array().unwrap()[0];
+    assert!(
+        simulated["success"].as_bool().is_some_and(|v| v),
+        "{}",
+        simulated
+    );
+    let holder = written_resource(simulated, sender.address(), "::message::MessageHolder")
+        .unwrap_or_else(|| panic!("{}", simulated["changes"]));
+    assert_eq!(holder["data"]["data"]["message"], json!("hello"));
+
+    // A module can only be published under its own address
+    context
+        .expect_status_code(400)
+        .post("/transactions/simulate_bundle", bundle(receiver.address()))
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_bundle_with_timestamp_override() {
+    let mut context = new_test_context(current_function_name!());
+    let sender = &mut context.gen_account();
+    let receiver = context.gen_account();
+    let txn = context.account_transfer_to(sender, receiver.address(), SMALL_TRANSFER_AMOUNT);
+    let request = simulation_request(&txn, "0x1::aptos_account::transfer", vec![
+        receiver.address().to_standard_string(),
+        SMALL_TRANSFER_AMOUNT.to_string(),
+    ]);
+    // The on-chain time is past the expiration of the transaction
+    let timestamp_usecs = (txn.expiration_timestamp_secs() + 1) * 1_000_000;
+
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/transactions/simulate_bundle",
+            json!({
+                "transactions": [request],
+                "state_overrides": {
+                    "balances": [{
+                        "address": sender.address().to_standard_string(),
+                        "amount": LARGE_TRANSFER_AMOUNT.to_string(),
+                    }],
+                    "sequence_numbers": [{
+                        "address": sender.address().to_standard_string(),
+                        "sequence_number": "0",
+                    }],
+                    "timestamp_usecs": timestamp_usecs.to_string(),
+                },
+            }),
+        )
+        .await;
+
+    let simulated = &resp.as_array().unwrap()[0];
+    assert!(
+        !simulated["success"].as_bool().is_some_and(|v| v),
+        "{}",
+        simulated
+    );
+    assert!(
+        simulated["vm_status"]
+            .as_str()
+            .unwrap()
+            .starts_with("TRANSACTION_EXPIRED"),
+        "{}",
+        simulated
+    );
+    assert_eq!(simulated["timestamp"], json!(timestamp_usecs.to_string()));
+}
+
+/// Returns the names of all the functions called in a call trace, in call order
+fn traced_functions(call_trace: &Value) -> Vec<String> {
+    let mut functions = vec![call_trace["function"].as_str().unwrap().to_string()];
+    for call in call_trace["calls"].as_array().unwrap() {
+        functions.extend(traced_functions(call));
+    }
+    functions
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_trace() {
+    let mut context = new_test_context(current_function_name!());
+    let sender = &mut context.gen_account();
+    let receiver = context.gen_account();
+    let txn = context.mint_user_account(sender).await;
+    context.commit_block(&vec![txn]).await;
+
+    let txn = context.account_transfer_to(sender, receiver.address(), SMALL_TRANSFER_AMOUNT);
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/transactions/simulate/trace",
+            simulation_request(&txn, "0x1::aptos_account::transfer", vec![
+                receiver.address().to_standard_string(),
+                SMALL_TRANSFER_AMOUNT.to_string(),
+            ]),
+        )
+        .await;
+
+    assert!(resp["success"].as_bool().is_some_and(|v| v), "{}", resp);
+    let call_trace = &resp["call_trace"];
+    assert_eq!(
+        call_trace["function"],
+        json!("0x1::aptos_account::transfer")
+    );
+    assert_eq!(call_trace["return_values"], json!([]));
+    assert!(traced_functions(call_trace).contains(&"0x1::account::exists_at".to_string()));
+    assert!(
+        call_trace["internal_gas_used"]
+            .as_str()
+            .unwrap()
+            .parse::<u64>()
+            .unwrap()
+            > 0
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_trace_abort() {
+    let mut context = new_test_context(current_function_name!());
+    let sender = &mut context.gen_account();
+    let receiver = context.gen_account();
+    let txn = context.mint_user_account(sender).await;
+    context.commit_block(&vec![txn]).await;
+
+    let txn = context.account_transfer_to(sender, receiver.address(), LARGE_TRANSFER_AMOUNT);
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/transactions/simulate/trace",
+            simulation_request(&txn, "0x1::aptos_account::transfer", vec![
+                receiver.address().to_standard_string(),
+                LARGE_TRANSFER_AMOUNT.to_string(),
+            ]),
+        )
+        .await;
+
+    // The abort propagates through every open call, up to the entry function
+    assert!(!resp["success"].as_bool().is_some_and(|v| v), "{}", resp);
+    let call_trace = &resp["call_trace"];
+    assert!(call_trace["abort_code"].is_string(), "{}", call_trace);
+    assert_eq!(call_trace["return_values"], Value::Null);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transaction_trace_by_version() {
+    let mut context = new_test_context(current_function_name!());
+    let sender = &mut context.gen_account();
+    let receiver = context.gen_account();
+    let txn = context.mint_user_account(sender).await;
+    context.commit_block(&vec![txn]).await;
+    let txn = context.account_transfer_to(sender, receiver.address(), SMALL_TRANSFER_AMOUNT);
+    context.commit_block(&vec![txn.clone()]).await;
+
+    let committed = context
+        .get(&format!(
+            "/transactions/by_hash/{}",
+            txn.committed_hash().to_hex_literal()
+        ))
+        .await;
+    let resp = context
+        .get(&format!(
+            "/transactions/by_version/{}/trace",
+            committed["version"].as_str().unwrap()
+        ))
+        .await;
+
+    assert_eq!(resp["hash"], committed["hash"]);
+    assert_eq!(
+        resp["call_trace"]["function"],
+        json!("0x1::aptos_account::transfer")
+    );
+    assert!(traced_functions(&resp["call_trace"]).contains(&"0x1::account::exists_at".to_string()));
+
+    // Block metadata transactions have no calls to trace
+    context
+        .expect_status_code(400)
+        .get("/transactions/by_version/1/trace")
+        .await;
+}
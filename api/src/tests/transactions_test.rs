@@ -771,6 +771,54 @@ async fn test_get_account_transactions() {
     assert_json(txns, expected_txns);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_activity_without_index() {
+    let mut context = new_test_context(current_function_name!());
+    let resp = context
+        .expect_status_code(403)
+        .get(
+            format!(
+                "/accounts/{}/activity",
+                context.root_account().await.address()
+            )
+            .as_str(),
+        )
+        .await;
+    assert_eq!(resp["error_code"], "api_disabled");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_activity() {
+    let mut node_config = NodeConfig::default();
+    node_config.indexer_db_config.enable_account_activity = true;
+    let mut context = new_test_context_with_config(current_function_name!(), node_config);
+    let mut account = context.create_account().await;
+    let receiver = context.gen_account();
+    let txn = context.account_transfer(&mut account, &receiver, 1000);
+    context.commit_block(&vec![txn.clone()]).await;
+
+    // The receiver never sent a transaction, but the transfer touched it.
+    let activity = context
+        .get(format!("/accounts/{}/activity", receiver.address()).as_str())
+        .await;
+    let activity = activity.as_array().unwrap();
+    assert_eq!(1, activity.len());
+    assert_eq!(activity[0]["hash"], txn.committed_hash().to_hex_literal());
+
+    // The index was enabled from genesis, so it covers the whole ledger.
+    let req = warp::test::request().method("GET").path(&format!(
+        "/v1/accounts/{}/activity?start=0",
+        account.address()
+    ));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["X-Aptos-Ledger-Oldest-Version"], "0");
+    let activity: Vec<serde_json::Value> = serde_json::from_slice(resp.body()).unwrap();
+    // The account was created by a transfer from the root account before sending its own.
+    assert_eq!(2, activity.len());
+    assert_eq!(activity[1]["hash"], txn.committed_hash().to_hex_literal());
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_transactions_filter_transactions_by_start_sequence_number() {
     let mut context = new_test_context(current_function_name!());
@@ -3,9 +3,14 @@
 
 use super::{new_test_context, new_test_context_with_config};
 use aptos_api_test_context::{current_function_name, TestContext};
+use aptos_api_types::{ViewFunction, ViewFunctionResultBcs};
 use aptos_cached_packages::aptos_stdlib;
 use aptos_config::config::{NodeConfig, ViewFilter, ViewFunctionId};
 use aptos_types::account_address::AccountAddress;
+use move_core_types::{
+    ident_str,
+    language_storage::{ModuleId, TypeTag},
+};
 use serde_json::{json, Value};
 use std::{path::PathBuf, str::FromStr};
 
@@ -252,3 +257,137 @@ async fn test_view_aggregator() {
         .await;
     context.check_golden_output_no_prune(resp);
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_batch() {
+    let mut context = new_test_context(current_function_name!());
+    let creator = &mut context.gen_account();
+    let owner = &mut context.gen_account();
+    let txn1 = context.mint_user_account(creator).await;
+    let txn2 = context.account_transfer(creator, owner, 100_000);
+
+    context.commit_block(&vec![txn1, txn2]).await;
+
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/view/batch",
+            json!([
+                build_coin_balance_request(&owner.address()),
+                {
+                    "function":"0x1::aptos_account::assert_account_exists",
+                    "arguments": vec![owner.address().to_string()],
+                    "type_arguments": [],
+                },
+                build_coin_decimals_request(),
+            ]),
+        )
+        .await;
+
+    // A failing view function doesn't fail the others
+    let results = resp.as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0]["values"], json!(["100000"]));
+    assert!(results[1]["error"].is_object(), "{}", results[1]);
+    assert_eq!(results[1]["values"], Value::Null);
+    assert_eq!(results[2]["values"], json!([8]));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_batch_bcs() {
+    let mut context = new_test_context(current_function_name!());
+    let creator = &mut context.gen_account();
+    let owner = &mut context.gen_account();
+    let txn1 = context.mint_user_account(creator).await;
+    let txn2 = context.account_transfer(creator, owner, 100_000);
+
+    context.commit_block(&vec![txn1, txn2]).await;
+
+    let coin = ModuleId::new(AccountAddress::ONE, ident_str!("coin").to_owned());
+    let aptos_coin = TypeTag::from_str("0x1::aptos_coin::AptosCoin").unwrap();
+    let view_functions = vec![
+        ViewFunction {
+            module: coin.clone(),
+            function: ident_str!("balance").to_owned(),
+            ty_args: vec![aptos_coin.clone()],
+            args: vec![bcs::to_bytes(&owner.address()).unwrap()],
+        },
+        ViewFunction {
+            module: ModuleId::new(AccountAddress::ONE, ident_str!("aptos_account").to_owned()),
+            function: ident_str!("assert_account_exists").to_owned(),
+            ty_args: vec![],
+            args: vec![bcs::to_bytes(&AccountAddress::random()).unwrap()],
+        },
+        ViewFunction {
+            module: coin,
+            function: ident_str!("decimals").to_owned(),
+            ty_args: vec![aptos_coin],
+            args: vec![],
+        },
+    ];
+
+    // The request is a BCS encoded `Vec<ViewFunction>`, and the response a BCS encoded
+    // `Vec<ViewFunctionResultBcs>`
+    let req = warp::test::request()
+        .method("POST")
+        .path("/v1/view/batch")
+        .header(
+            "Content-Type",
+            "application/x.aptos.view_function_batch+bcs",
+        )
+        .header("Accept", "application/x-bcs")
+        .body(bcs::to_bytes(&view_functions).unwrap());
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+
+    let results: Vec<ViewFunctionResultBcs> = bcs::from_bytes(resp.body()).unwrap();
+    assert_eq!(results.len(), 3);
+    let balance = bcs::to_bytes(&100_000u64).unwrap();
+    assert_eq!(results[0].values.as_ref().unwrap(), &vec![balance]);
+    assert!(results[0].gas_used > 0);
+    assert!(results[1].values.is_err());
+    let decimals = bcs::to_bytes(&8u8).unwrap();
+    assert_eq!(results[2].values.as_ref().unwrap(), &vec![decimals]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_batch_gas_budget() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.max_gas_view_function_batch = 1;
+    let mut context = new_test_context_with_config(current_function_name!(), node_config);
+    let owner = context.gen_account();
+
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/view/batch",
+            json!([
+                build_coin_decimals_request(),
+                build_coin_balance_request(&owner.address())
+            ]),
+        )
+        .await;
+
+    // The first view function runs out of the budget, and the second isn't executed
+    let results = resp.as_array().unwrap();
+    assert!(results[0]["error"].is_object(), "{}", results[0]);
+    assert!(results[1]["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("gas budget"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_batch_too_large() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.max_view_function_batch_size = 1;
+    let mut context = new_test_context_with_config(current_function_name!(), node_config);
+
+    context
+        .expect_status_code(400)
+        .post(
+            "/view/batch",
+            json!([build_coin_decimals_request(), build_coin_decimals_request()]),
+        )
+        .await;
+}
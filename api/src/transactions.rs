@@ -17,36 +17,46 @@ use crate::{
         BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResult, BasicResultWith404,
         ForbiddenError, InsufficientStorageError, InternalError,
     },
+    state_overrides::SimulationStateView,
     ApiTags,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
     verify_function_identifier, verify_module_identifier, Address, AptosError, AptosErrorCode,
-    AsConverter, EncodeSubmissionRequest, GasEstimation, GasEstimationBcs, HashValue,
-    HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction, SubmitTransactionRequest,
+    AsConverter, CallTrace, EncodeSubmissionRequest, GasEstimation, GasEstimationBcs, HashValue,
+    HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction, ResourceAccess,
+    SimulateBundleRequest, StateOverrides, SubmitTransactionRequest, TracedUserTransaction,
     Transaction, TransactionData, TransactionOnChainData, TransactionsBatchSingleSubmissionFailure,
     TransactionsBatchSubmissionResult, UserTransaction, VerifyInput, VerifyInputWithRecursion,
     MAX_RECURSIVE_TYPES_ALLOWED, U64,
 };
 use aptos_crypto::{hash::CryptoHash, signing_message};
+use aptos_gas_profiling::{CallOutcome, CallTracer, FrameName};
 use aptos_types::{
     account_address::AccountAddress,
     mempool_status::MempoolStatusCode,
+    state_store::TStateView,
     transaction::{
         EntryFunction, ExecutionStatus, MultisigTransactionPayload, RawTransaction,
-        RawTransactionWithData, SignedTransaction, TransactionPayload,
+        RawTransactionWithData, SignedTransaction, TransactionOutput, TransactionPayload,
+        TransactionStatus,
     },
     vm_status::StatusCode,
     APTOS_COIN_TYPE,
 };
-use aptos_vm::{AptosSimulationVM, AptosVM};
-use move_core_types::{ident_str, language_storage::ModuleId, vm_status::VMStatus};
+use aptos_vm::{data_cache::AsMoveResolver, gas::ProdGasMeter, AptosSimulationVM, AptosVM};
+use aptos_vm_logging::log_schema::AdapterLogSchema;
+use move_core_types::{
+    ident_str,
+    language_storage::{ModuleId, TypeTag},
+    vm_status::VMStatus,
+};
 use poem_openapi::{
     param::{Path, Query},
     payload::Json,
     ApiRequest, OpenApi,
 };
-use std::{sync::Arc, time::Duration};
+use std::{cmp::max, collections::BTreeSet, sync::Arc, time::Duration};
 
 generate_success_response!(SubmitTransactionResponse, (202, Accepted));
 
@@ -344,6 +354,51 @@ impl TransactionsApi {
         api_spawn_blocking(move || api.list_by_account(&accept_type, page, address.0)).await
     }
 
+    /// Get account activity
+    ///
+    /// Retrieves on-chain committed transactions touching an account: the ones
+    /// it sent, signed as a secondary signer, paid the fees of as fee payer, or
+    /// was the multisig account of, and the ones that changed its resources or
+    /// objects, or emitted events from it. The framework changes the resources of
+    /// the reserved addresses (0x0 to 0xf) in nearly every transaction, so these
+    /// changes alone don't make a transaction part of their activity.
+    ///
+    /// This requires the account activity index of the internal indexer to be
+    /// enabled on the node. It only covers the transactions committed since the
+    /// index was enabled, so the `X-Aptos-Ledger-Oldest-Version` header reports
+    /// the first version it covers, and starting before it is an error.
+    #[oai(
+        path = "/accounts/:address/activity",
+        method = "get",
+        operation_id = "get_account_activity",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn get_account_activity(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Ledger version to start list of transactions
+        ///
+        /// If not provided, defaults to showing the latest transactions
+        start: Query<Option<U64>>,
+        /// Max number of transactions to retrieve.
+        ///
+        /// If not provided, defaults to default page size
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<Transaction>> {
+        fail_point_poem("endpoint_get_account_activity")?;
+        self.context
+            .check_api_output_enabled("Get account activity", &accept_type)?;
+        let page = Page::new(
+            start.0.map(|v| v.0),
+            limit.0,
+            self.context.max_transactions_page_size(),
+        );
+        let api = self.clone();
+        api_spawn_blocking(move || api.list_account_activity(&accept_type, page, address.0)).await
+    }
+
     /// Submit transaction
     ///
     /// This endpoint accepts transaction submissions in two formats.
@@ -471,6 +526,10 @@ impl TransactionsApi {
     ///
     /// To use this endpoint with BCS, you must submit a SignedTransaction
     /// encoded as BCS. See SignedTransaction in types/src/transaction/mod.rs.
+    ///
+    /// The request body is the transaction itself, so state overrides can't be given here. To
+    /// simulate a transaction against overridden state, use /transactions/simulate_bundle with a
+    /// bundle of that single transaction.
     #[oai(
         path = "/transactions/simulate",
         method = "post",
@@ -622,6 +681,194 @@ impl TransactionsApi {
         .await
     }
 
+    /// Simulate a bundle of transactions
+    ///
+    /// Simulates an ordered bundle of transactions, where each transaction sees the outputs of
+    /// the previous ones, and returns the write set, events and gas used of every transaction.
+    /// Nothing is committed.
+    ///
+    /// Before the simulation, the state can be overridden: account balances, sequence numbers,
+    /// resources, module bytecode, the on-chain timestamp and feature flags. A bundle of a single
+    /// transaction simulates it against the overridden state.
+    ///
+    /// Like with /transactions/simulate, the transactions must have zero-padded signatures.
+    #[oai(
+        path = "/transactions/simulate_bundle",
+        method = "post",
+        operation_id = "simulate_transaction_bundle",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn simulate_transaction_bundle(
+        &self,
+        accept_type: AcceptType,
+        data: Json<SimulateBundleRequest>,
+    ) -> SimulateTransactionResult<Vec<UserTransaction>> {
+        data.0
+            .verify()
+            .context("Simulated transaction bundle invalid")
+            .map_err(|err| {
+                SubmitTransactionError::bad_request_with_code_no_info(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+        fail_point_poem("endpoint_simulate_transaction_bundle")?;
+        if !self.context.node_config.api.transaction_simulation_enabled {
+            return Err(api_disabled("Simulate transaction bundle"));
+        }
+        self.context
+            .check_api_output_enabled("Simulate transaction bundle", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            let ledger_info = api.context.get_latest_ledger_info()?;
+            let SimulateBundleRequest {
+                transactions,
+                state_overrides,
+            } = data.0;
+            if api.context.max_submit_transaction_batch_size() < transactions.len() {
+                return Err(SubmitTransactionError::bad_request_with_code(
+                    format!(
+                        "Simulated too many transactions: {}, while limit is {}",
+                        transactions.len(),
+                        api.context.max_submit_transaction_batch_size(),
+                    ),
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                ));
+            }
+            let signed_transactions = transactions
+                .into_iter()
+                .map(|request| {
+                    api.get_signed_transaction(
+                        &ledger_info,
+                        SubmitTransactionPost::Json(Json(request)),
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            for signed_transaction in signed_transactions.iter() {
+                if !api.context.node_config.api.simulation_filter.allows(
+                    aptos_crypto::HashValue::zero(),
+                    ledger_info.timestamp(),
+                    signed_transaction,
+                ) {
+                    return Err(SubmitTransactionError::forbidden_with_code(
+                        "Transaction not allowed by simulation filter",
+                        AptosErrorCode::InvalidInput,
+                        &ledger_info,
+                    ));
+                }
+            }
+
+            api.simulate_bundle(
+                &accept_type,
+                ledger_info,
+                signed_transactions,
+                state_overrides,
+            )
+        })
+        .await
+    }
+
+    /// Simulate transaction with a call trace
+    ///
+    /// Simulates a transaction like /transactions/simulate, and also returns the call tree of
+    /// its execution: every function called, with its type arguments, arguments, return values
+    /// or abort code, the gas it used and the resources it read and wrote.
+    ///
+    /// Only transactions with an entry function or script payload can be traced, and the trace
+    /// is only available as JSON.
+    #[oai(
+        path = "/transactions/simulate/trace",
+        method = "post",
+        operation_id = "simulate_transaction_with_trace",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn simulate_transaction_with_trace(
+        &self,
+        accept_type: AcceptType,
+        data: SubmitTransactionPost,
+    ) -> SimulateTransactionResult<TracedUserTransaction> {
+        data.verify()
+            .context("Simulated transaction invalid")
+            .map_err(|err| {
+                SubmitTransactionError::bad_request_with_code_no_info(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+        fail_point_poem("endpoint_simulate_transaction_with_trace")?;
+        if !self.context.node_config.api.transaction_simulation_enabled {
+            return Err(api_disabled("Simulate transaction with trace"));
+        }
+        if accept_type == AcceptType::Bcs {
+            return Err(SubmitTransactionError::bad_request_with_code_no_info(
+                "BCS is not supported for call traces",
+                AptosErrorCode::BcsNotSupported,
+            ));
+        }
+        self.context
+            .check_api_output_enabled("Simulate transaction with trace", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            let ledger_info = api.context.get_latest_ledger_info()?;
+            let signed_transaction = api.get_signed_transaction(&ledger_info, data)?;
+            if !api.context.node_config.api.simulation_filter.allows(
+                aptos_crypto::HashValue::zero(),
+                ledger_info.timestamp(),
+                &signed_transaction,
+            ) {
+                return Err(SubmitTransactionError::forbidden_with_code(
+                    "Transaction not allowed by simulation filter",
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                ));
+            }
+
+            api.simulate_with_trace(ledger_info, signed_transaction)
+        })
+        .await
+    }
+
+    /// Get the call trace of a transaction by version
+    ///
+    /// Re-executes a committed user transaction against the state right before it, and returns
+    /// the transaction along with the call tree of its execution. See
+    /// /transactions/simulate/trace for the content of the call tree.
+    ///
+    /// The state before the transaction must not have been pruned. Only transactions with an
+    /// entry function or script payload can be traced, and the trace is only available as JSON.
+    #[oai(
+        path = "/transactions/by_version/:txn_version/trace",
+        method = "get",
+        operation_id = "get_transaction_trace_by_version",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn get_transaction_trace_by_version(
+        &self,
+        accept_type: AcceptType,
+        /// Version of the transaction to trace
+        txn_version: Path<U64>,
+    ) -> BasicResultWith404<TracedUserTransaction> {
+        fail_point_poem("endpoint_transaction_trace_by_version")?;
+        // Re-executing a transaction costs as much as simulating it
+        if !self.context.node_config.api.transaction_simulation_enabled {
+            return Err(api_disabled("Get transaction trace by version"));
+        }
+        if accept_type == AcceptType::Bcs {
+            return Err(BasicErrorWith404::bad_request_with_code_no_info(
+                "BCS is not supported for call traces",
+                AptosErrorCode::BcsNotSupported,
+            ));
+        }
+        self.context
+            .check_api_output_enabled("Get transaction trace by version", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || api.get_transaction_trace_by_version_inner(txn_version.0)).await
+    }
+
     /// Encode submission
     ///
     /// This endpoint accepts an EncodeSubmissionRequest, which internally is a
@@ -881,6 +1128,148 @@ impl TransactionsApi {
         }
     }
 
+    fn get_transaction_trace_by_version_inner(
+        &self,
+        version: U64,
+    ) -> BasicResultWith404<TracedUserTransaction> {
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let txn_data = self
+            .get_by_version(version.0, &ledger_info)
+            .context(format!("Failed to get transaction by version {}", version))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+        let txn = match txn_data {
+            GetByVersionResponse::Found(TransactionData::OnChain(txn)) => txn,
+            GetByVersionResponse::Found(TransactionData::Pending(_)) => {
+                return Err(BasicErrorWith404::internal_with_code(
+                    "Transaction by version is pending",
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                ))
+            },
+            GetByVersionResponse::VersionTooNew => {
+                return Err(transaction_not_found_by_version(version.0, &ledger_info))
+            },
+            GetByVersionResponse::VersionTooOld => {
+                return Err(version_pruned(version.0, &ledger_info))
+            },
+        };
+        let signed_txn = match &txn.transaction {
+            aptos_types::transaction::Transaction::UserTransaction(signed_txn) => {
+                signed_txn.clone()
+            },
+            _ => {
+                return Err(BasicErrorWith404::bad_request_with_code(
+                    format!("Transaction {} is not a user transaction", version),
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                ))
+            },
+        };
+        if !is_traceable(signed_txn.payload()) {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                "Only entry function and script payloads can be traced",
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+
+        // The genesis transaction is never a user transaction, so there is always a state before
+        let state_view = self
+            .context
+            .state_view_at_version(version.0 - 1)
+            .context(format!(
+                "Failed to read the state before version {}",
+                version
+            ))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+        let resolver = state_view.as_move_resolver();
+        // The state is not the latest one, so the VM must not share the warm code cache
+        let vm = AptosVM::new_without_warm_vm_cache(&resolver, None);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        let (vm_status, output, tracer) = vm
+            .execute_user_transaction_with_modified_gas_meter(
+                &resolver,
+                &signed_txn,
+                &log_context,
+                |gas_meter| new_call_tracer(signed_txn.payload(), gas_meter),
+            )
+            .map_err(|vm_status| {
+                BasicErrorWith404::internal_with_code(
+                    format!(
+                        "Transaction {} was discarded on replay: {:?}",
+                        version, vm_status
+                    ),
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+        // A trace is only meaningful if the replay behaved like the committed execution
+        match output.status() {
+            TransactionStatus::Keep(status) if status == txn.info.status() => {},
+            replayed_status => {
+                return Err(BasicErrorWith404::internal_with_code(
+                    format!(
+                        "Replay of transaction {} ended with status {:?} ({:?}), but it was \
+                         committed with status {:?}",
+                        version,
+                        replayed_status,
+                        vm_status,
+                        txn.info.status()
+                    ),
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                ))
+            },
+        }
+        let call_trace = render_call_trace(tracer.finish(&vm_status));
+
+        let timestamp = self
+            .context
+            .get_block_timestamp(&ledger_info, txn.version)?;
+        let state_view = self.context.latest_state_view_poem(&ledger_info)?;
+        let transaction = match state_view
+            .as_converter(self.context.db.clone(), self.context.indexer_reader.clone())
+            .try_into_onchain_transaction(timestamp, txn)
+            .context("Failed to convert on chain transaction to Transaction")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })? {
+            Transaction::UserTransaction(user_txn) => *user_txn,
+            _ => {
+                return Err(BasicErrorWith404::internal_with_code(
+                    "User transaction resulted in a non-UserTransaction",
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                ))
+            },
+        };
+
+        BasicResponse::try_from_json((
+            TracedUserTransaction {
+                transaction,
+                call_trace,
+            },
+            &ledger_info,
+            BasicResponseStatus::Ok,
+        ))
+    }
+
     /// Converts a transaction into the outgoing type
     fn get_transaction_inner(
         &self,
@@ -1011,6 +1400,39 @@ impl TransactionsApi {
         }
     }
 
+    /// List all transactions touching an account
+    fn list_account_activity(
+        &self,
+        accept_type: &AcceptType,
+        page: Page,
+        address: Address,
+    ) -> BasicResultWith404<Vec<Transaction>> {
+        let mut latest_ledger_info = self.context.get_latest_ledger_info()?;
+        // The index doesn't cover the versions before it was enabled, report them as unavailable.
+        let start_version = self
+            .context
+            .get_account_activity_start_version(&latest_ledger_info)?;
+        latest_ledger_info.oldest_ledger_version =
+            max(latest_ledger_info.oldest_ledger_version.0, start_version).into();
+        let data = self.context.get_account_activity(
+            address.into(),
+            page.start_option(),
+            page.limit(&latest_ledger_info)?,
+            &latest_ledger_info,
+        )?;
+        match accept_type {
+            AcceptType::Json => BasicResponse::try_from_json((
+                self.context
+                    .render_transactions_non_sequential(&latest_ledger_info, data)?,
+                &latest_ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((data, &latest_ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
+
     /// Parses a single signed transaction
     fn get_signed_transaction(
         &self,
@@ -1367,6 +1789,229 @@ impl TransactionsApi {
         let state_view = self.context.latest_state_view_poem(&ledger_info)?;
         let (vm_status, output) =
             AptosSimulationVM::create_vm_and_simulate_signed_transaction(&txn, &state_view);
+        let simulated_txn = self.to_simulated_transaction(&ledger_info, txn, &vm_status, &output);
+
+        let result = match accept_type {
+            AcceptType::Json => {
+                let transactions = self
+                    .context
+                    .render_transactions_non_sequential(&ledger_info, vec![simulated_txn])?;
+
+                // Users can only make requests to simulate UserTransactions, so unpack
+                // the Vec<Transaction> into Vec<UserTransaction>.
+                let mut user_transactions = Vec::new();
+                for transaction in transactions.into_iter() {
+                    match transaction {
+                        Transaction::UserTransaction(user_txn) => {
+                            let mut txn = *user_txn;
+                            append_vm_status_message(&mut txn, &vm_status);
+                            user_transactions.push(txn);
+                        },
+                        _ => {
+                            return Err(SubmitTransactionError::internal_with_code(
+                                "Simulation transaction resulted in a non-UserTransaction",
+                                AptosErrorCode::InternalError,
+                                &ledger_info,
+                            ))
+                        },
+                    }
+                }
+                BasicResponse::try_from_json((
+                    user_transactions,
+                    &ledger_info,
+                    BasicResponseStatus::Ok,
+                ))
+            },
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((simulated_txn, &ledger_info, BasicResponseStatus::Ok))
+            },
+        };
+
+        result.map(|r| r.with_gas_used(Some(output.gas_used())))
+    }
+
+    /// Simulate an ordered bundle of transactions in the VM, on top of the state overrides
+    ///
+    /// The simulation runs in a VM outside of the cache shared with the rest of the node, so
+    /// overridden modules never leak into the execution of committed transactions.
+    fn simulate_bundle(
+        &self,
+        accept_type: &AcceptType,
+        ledger_info: LedgerInfo,
+        txns: Vec<SignedTransaction>,
+        state_overrides: Option<StateOverrides>,
+    ) -> SimulateTransactionResult<Vec<UserTransaction>> {
+        // The caller must ensure that the signatures are not valid, as otherwise
+        // a malicious actor could execute the transactions without their knowledge
+        if txns.iter().any(|txn| txn.verify_signature().is_ok()) {
+            return Err(SubmitTransactionError::bad_request_with_code(
+                "Simulated transactions must not have a valid signature",
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+
+        let mut state_view = SimulationStateView::new(
+            self.context.latest_state_view_poem(&ledger_info)?,
+            self.context.db.clone(),
+            self.context.indexer_reader.clone(),
+        );
+        let timestamp = match &state_overrides {
+            Some(state_overrides) => {
+                state_view
+                    .apply_overrides(state_overrides)
+                    .context("Failed to apply the state overrides")
+                    .map_err(|err| {
+                        SubmitTransactionError::bad_request_with_code(
+                            err,
+                            AptosErrorCode::InvalidInput,
+                            &ledger_info,
+                        )
+                    })?;
+                state_overrides.timestamp_usecs.map(|timestamp| timestamp.0)
+            },
+            None => None,
+        }
+        .unwrap_or_else(|| ledger_info.timestamp());
+
+        // A single VM for the whole bundle, created after the overrides so that it sees the
+        // overridden feature flags
+        let vm = AptosSimulationVM::new_isolated(&state_view.as_move_resolver());
+        let mut gas_used = 0;
+        let mut simulated_txns = Vec::with_capacity(txns.len());
+        for txn in txns {
+            let (vm_status, output) = vm.simulate(&txn, &state_view);
+            state_view.apply_write_set(output.write_set());
+            gas_used += output.gas_used();
+            simulated_txns.push((
+                self.to_simulated_transaction(&ledger_info, txn, &vm_status, &output),
+                vm_status,
+            ));
+        }
+
+        let result = match accept_type {
+            AcceptType::Json => {
+                // Render with the simulated state, so resources of overridden modules are decoded
+                let converter = state_view.converter();
+                let user_transactions = simulated_txns
+                    .into_iter()
+                    .map(|(simulated_txn, vm_status)| {
+                        match converter.try_into_onchain_transaction(timestamp, simulated_txn) {
+                            Ok(Transaction::UserTransaction(user_txn)) => {
+                                let mut txn = *user_txn;
+                                append_vm_status_message(&mut txn, &vm_status);
+                                Ok(txn)
+                            },
+                            Ok(_) => Err(SubmitTransactionError::internal_with_code(
+                                "Simulation transaction resulted in a non-UserTransaction",
+                                AptosErrorCode::InternalError,
+                                &ledger_info,
+                            )),
+                            Err(err) => Err(SubmitTransactionError::internal_with_code(
+                                err,
+                                AptosErrorCode::InternalError,
+                                &ledger_info,
+                            )),
+                        }
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                BasicResponse::try_from_json((
+                    user_transactions,
+                    &ledger_info,
+                    BasicResponseStatus::Ok,
+                ))
+            },
+            AcceptType::Bcs => {
+                let simulated_txns: Vec<_> = simulated_txns
+                    .into_iter()
+                    .map(|(simulated_txn, _)| simulated_txn)
+                    .collect();
+                BasicResponse::try_from_bcs((simulated_txns, &ledger_info, BasicResponseStatus::Ok))
+            },
+        };
+
+        result.map(|r| r.with_gas_used(Some(gas_used)))
+    }
+
+    /// Simulate a transaction in the VM, tracing its calls
+    fn simulate_with_trace(
+        &self,
+        ledger_info: LedgerInfo,
+        txn: SignedTransaction,
+    ) -> SimulateTransactionResult<TracedUserTransaction> {
+        // The caller must ensure that the signature is not valid, as otherwise
+        // a malicious actor could execute the transaction without their knowledge
+        if txn.verify_signature().is_ok() {
+            return Err(SubmitTransactionError::bad_request_with_code(
+                "Simulated transactions must not have a valid signature",
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+        if !is_traceable(txn.payload()) {
+            return Err(SubmitTransactionError::bad_request_with_code(
+                "Only entry function and script payloads can be traced",
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+
+        let state_view = self.context.latest_state_view_poem(&ledger_info)?;
+        let (vm_status, output, tracer) =
+            AptosSimulationVM::create_isolated_vm_and_simulate_signed_transaction_with_modified_gas_meter(
+                &txn,
+                &state_view,
+                |gas_meter| new_call_tracer(txn.payload(), gas_meter),
+            )
+            .map_err(|vm_status| {
+                SubmitTransactionError::bad_request_with_code(
+                    format!("Transaction was discarded: {:?}", vm_status),
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                )
+            })?;
+        let call_trace = render_call_trace(tracer.finish(&vm_status));
+        let simulated_txn = self.to_simulated_transaction(&ledger_info, txn, &vm_status, &output);
+
+        let mut transactions = self
+            .context
+            .render_transactions_non_sequential(&ledger_info, vec![simulated_txn])?;
+        let transaction = match transactions.pop() {
+            Some(Transaction::UserTransaction(user_txn)) => {
+                let mut txn = *user_txn;
+                append_vm_status_message(&mut txn, &vm_status);
+                txn
+            },
+            _ => {
+                return Err(SubmitTransactionError::internal_with_code(
+                    "Simulation transaction resulted in a non-UserTransaction",
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                ))
+            },
+        };
+
+        BasicResponse::try_from_json((
+            TracedUserTransaction {
+                transaction,
+                call_trace,
+            },
+            &ledger_info,
+            BasicResponseStatus::Ok,
+        ))
+        .map(|r| r.with_gas_used(Some(output.gas_used())))
+    }
+
+    /// Builds up a transaction from the outputs of a simulation, and records its stats
+    ///
+    /// All state hashes are invalid, and will be filled with 0s
+    fn to_simulated_transaction(
+        &self,
+        ledger_info: &LedgerInfo,
+        txn: SignedTransaction,
+        vm_status: &VMStatus,
+        output: &TransactionOutput,
+    ) -> TransactionOnChainData {
         let version = ledger_info.version();
 
         // Ensure that all known statuses return their values in the output (even if they aren't supposed to)
@@ -1400,8 +2045,6 @@ impl TransactionsApi {
             .simulate_txn_stats()
             .increment(stats_key, output.gas_used());
 
-        // Build up a transaction from the outputs
-        // All state hashes are invalid, and will be filled with 0s
         let txn = aptos_types::transaction::Transaction::UserTransaction(txn);
         let zero_hash = aptos_crypto::HashValue::zero();
         let info = aptos_types::transaction::TransactionInfo::new(
@@ -1412,64 +2055,14 @@ impl TransactionsApi {
             output.gas_used(),
             exe_status,
         );
-        let simulated_txn = TransactionOnChainData {
+        TransactionOnChainData {
             version,
             transaction: txn,
             info,
             events: output.events().to_vec(),
             accumulator_root_hash: zero_hash,
             changes: output.write_set().clone(),
-        };
-
-        let result = match accept_type {
-            AcceptType::Json => {
-                let transactions = self
-                    .context
-                    .render_transactions_non_sequential(&ledger_info, vec![simulated_txn])?;
-
-                // Users can only make requests to simulate UserTransactions, so unpack
-                // the Vec<Transaction> into Vec<UserTransaction>.
-                let mut user_transactions = Vec::new();
-                for transaction in transactions.into_iter() {
-                    match transaction {
-                        Transaction::UserTransaction(user_txn) => {
-                            let mut txn = *user_txn;
-                            match &vm_status {
-                                VMStatus::Error {
-                                    message: Some(msg), ..
-                                }
-                                | VMStatus::ExecutionFailure {
-                                    message: Some(msg), ..
-                                } => {
-                                    txn.info.vm_status +=
-                                        format!("\nExecution failed with message: {}", msg)
-                                            .as_str();
-                                },
-                                _ => (),
-                            }
-                            user_transactions.push(txn);
-                        },
-                        _ => {
-                            return Err(SubmitTransactionError::internal_with_code(
-                                "Simulation transaction resulted in a non-UserTransaction",
-                                AptosErrorCode::InternalError,
-                                &ledger_info,
-                            ))
-                        },
-                    }
-                }
-                BasicResponse::try_from_json((
-                    user_transactions,
-                    &ledger_info,
-                    BasicResponseStatus::Ok,
-                ))
-            },
-            AcceptType::Bcs => {
-                BasicResponse::try_from_bcs((simulated_txn, &ledger_info, BasicResponseStatus::Ok))
-            },
-        };
-
-        result.map(|r| r.with_gas_used(Some(output.gas_used())))
+        }
     }
 
     /// Encode message as BCS
@@ -1556,3 +2149,87 @@ enum GetByVersionResponse {
     VersionTooOld,
     Found(TransactionData),
 }
+
+/// Appends the message of a failed simulation to the VM status of the transaction
+fn append_vm_status_message(txn: &mut UserTransaction, vm_status: &VMStatus) {
+    match vm_status {
+        VMStatus::Error {
+            message: Some(msg), ..
+        }
+        | VMStatus::ExecutionFailure {
+            message: Some(msg), ..
+        } => {
+            txn.info.vm_status += format!("\nExecution failed with message: {}", msg).as_str();
+        },
+        _ => (),
+    }
+}
+
+/// Whether the calls of a transaction can be traced, i.e. whether it has a single top-level call
+fn is_traceable(payload: &TransactionPayload) -> bool {
+    matches!(
+        payload,
+        TransactionPayload::EntryFunction(_) | TransactionPayload::Script(_)
+    )
+}
+
+fn new_call_tracer(
+    payload: &TransactionPayload,
+    gas_meter: ProdGasMeter,
+) -> CallTracer<ProdGasMeter> {
+    match payload {
+        TransactionPayload::EntryFunction(entry_func) => CallTracer::new_function(
+            gas_meter,
+            entry_func.module().clone(),
+            entry_func.function().to_owned(),
+            entry_func.ty_args().to_vec(),
+        ),
+        _ => CallTracer::new_script(gas_meter),
+    }
+}
+
+fn render_call_trace(trace: aptos_gas_profiling::CallTrace) -> CallTrace {
+    let (function, type_arguments) = match trace.name {
+        FrameName::Script => ("script".to_string(), vec![]),
+        FrameName::Function {
+            module_id,
+            name,
+            ty_args,
+        } => (
+            format!("{}::{}", module_id.short_str_lossless(), name),
+            ty_args.iter().map(MoveType::from).collect(),
+        ),
+    };
+    let (return_values, abort_code, error) = match trace.outcome {
+        CallOutcome::Returned(values) => (Some(values), None, None),
+        CallOutcome::Aborted(code) => (None, Some(code.into()), None),
+        CallOutcome::Failed(status_code) => (None, None, Some(format!("{:?}", status_code))),
+        CallOutcome::Unfinished => (None, None, None),
+    };
+    let render_accesses = |accesses: BTreeSet<aptos_gas_profiling::ResourceAccess>| {
+        accesses
+            .into_iter()
+            .filter_map(|access| match access.ty {
+                TypeTag::Struct(tag) => Some(ResourceAccess {
+                    address: access.addr.into(),
+                    typ: (*tag).into(),
+                }),
+                _ => None,
+            })
+            .collect()
+    };
+
+    CallTrace {
+        function,
+        type_arguments,
+        arguments: trace.args,
+        is_native: trace.is_native,
+        return_values,
+        abort_code,
+        error,
+        internal_gas_used: u64::from(trace.gas_used).into(),
+        resources_read: render_accesses(trace.resources_read),
+        resources_written: render_accesses(trace.resources_written),
+        calls: trace.calls.into_iter().map(render_call_trace).collect(),
+    }
+}
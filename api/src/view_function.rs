@@ -14,15 +14,16 @@ use crate::{
 };
 use anyhow::Context as anyhowContext;
 use aptos_api_types::{
-    AptosErrorCode, AsConverter, MoveValue, ViewFunction, ViewRequest, MAX_RECURSIVE_TYPES_ALLOWED,
-    U64,
+    AptosError, AptosErrorCode, AsConverter, MoveValue, ViewFunction, ViewFunctionResult,
+    ViewFunctionResultBcs, ViewRequest, MAX_RECURSIVE_TYPES_ALLOWED, U64,
 };
 use aptos_bcs_utils::serialize_uleb128;
+use aptos_storage_interface::state_view::DbStateView;
 use aptos_vm::AptosVM;
 use itertools::Itertools;
 use move_core_types::language_storage::TypeTag;
 use poem_openapi::{param::Query, payload::Json, ApiRequest, OpenApi};
-use std::sync::Arc;
+use std::{cmp::min, sync::Arc};
 
 /// API for executing Move view function.
 #[derive(Clone)]
@@ -39,6 +40,16 @@ pub enum ViewFunctionRequest {
     Bcs(Bcs),
 }
 
+#[derive(ApiRequest, Debug)]
+pub enum ViewFunctionBatchRequest {
+    #[oai(content_type = "application/json")]
+    Json(Json<Vec<ViewRequest>>),
+
+    // A BCS encoded `Vec<ViewFunction>`
+    #[oai(content_type = "application/x.aptos.view_function_batch+bcs")]
+    Bcs(Bcs),
+}
+
 #[OpenApi]
 impl ViewFunctionApi {
     /// Execute view function of a module
@@ -71,6 +82,50 @@ impl ViewFunctionApi {
         api_spawn_blocking(move || view_request(context, accept_type, request, ledger_version))
             .await
     }
+
+    /// Execute a batch of view functions
+    ///
+    /// Execute the given Move view functions against the same ledger version, and return the
+    /// execution result of each of them, in order. A failing view function doesn't fail the
+    /// batch, its result holds the error instead.
+    ///
+    /// Each view function has the same gas limit as with /view, and the whole batch has an
+    /// aggregate gas budget. Once the budget is used up, the remaining view functions fail
+    /// without being executed.
+    ///
+    /// To use this endpoint with BCS, you must submit a `Vec<ViewFunction>` encoded as BCS,
+    /// see ViewFunction in api/types/src/view.rs. The BCS response is a
+    /// `Vec<ViewFunctionResultBcs>`, where each result holds either the BCS encoded return
+    /// values or the error, and the gas used.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/view/batch",
+        method = "post",
+        operation_id = "view_batch",
+        tag = "ApiTags::View"
+    )]
+    async fn view_function_batch(
+        &self,
+        accept_type: AcceptType,
+        /// View function requests with type and position arguments
+        request: ViewFunctionBatchRequest,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<Vec<ViewFunctionResult>> {
+        fail_point_poem("endpoint_view_function_batch")?;
+        self.context
+            .check_api_output_enabled("View function batch", &accept_type)?;
+
+        let context = self.context.clone();
+        api_spawn_blocking(move || {
+            view_batch_request(context, accept_type, request, ledger_version)
+        })
+        .await
+    }
 }
 
 fn view_request(
@@ -118,11 +173,7 @@ fn view_request(
     };
 
     // Reject the request if it's not allowed by the filter.
-    if !context.node_config.api.view_filter.allows(
-        view_function.module.address(),
-        view_function.module.name().as_str(),
-        view_function.function.as_str(),
-    ) {
+    if !is_allowed(&context, &view_function) {
         return Err(BasicErrorWith404::forbidden_with_code_no_info(
             format!(
                 "Function {}::{} is not allowed",
@@ -166,31 +217,7 @@ fn view_request(
             BasicResponse::try_from_encoded((ret, &ledger_info, BasicResponseStatus::Ok))
         },
         AcceptType::Json => {
-            let return_types = state_view
-                .as_converter(context.db.clone(), context.indexer_reader.clone())
-                .function_return_types(&view_function)
-                .and_then(|tys| {
-                    tys.into_iter()
-                        .map(TypeTag::try_from)
-                        .collect::<anyhow::Result<Vec<_>>>()
-                })
-                .map_err(|err| {
-                    BasicErrorWith404::bad_request_with_code(
-                        err,
-                        AptosErrorCode::InternalError,
-                        &ledger_info,
-                    )
-                })?;
-
-            let move_vals = values
-                .into_iter()
-                .zip(return_types.into_iter())
-                .map(|(v, ty)| {
-                    state_view
-                        .as_converter(context.db.clone(), context.indexer_reader.clone())
-                        .try_into_move_value(&ty, &v)
-                })
-                .collect::<anyhow::Result<Vec<_>>>()
+            let move_vals = convert_return_values(&context, &state_view, &view_function, values)
                 .map_err(|err| {
                     BasicErrorWith404::bad_request_with_code(
                         err,
@@ -208,3 +235,188 @@ fn view_request(
     );
     result.map(|r| r.with_gas_used(Some(output.gas_used)))
 }
+
+fn view_batch_request(
+    context: Arc<Context>,
+    accept_type: AcceptType,
+    request: ViewFunctionBatchRequest,
+    ledger_version: Query<Option<U64>>,
+) -> BasicResultWith404<Vec<ViewFunctionResult>> {
+    // Retrieve the current state of the chain, all the view functions see the same version
+    let (ledger_info, requested_version) = context
+        .get_latest_ledger_info_and_verify_lookup_version(ledger_version.map(|inner| inner.0))?;
+
+    let state_view = context
+        .state_view_at_version(requested_version)
+        .map_err(|err| {
+            BasicErrorWith404::bad_request_with_code(
+                err,
+                AptosErrorCode::InternalError,
+                &ledger_info,
+            )
+        })?;
+
+    let max_batch_size = context.node_config.api.max_view_function_batch_size;
+    let check_batch_size = |batch_size: usize| {
+        if batch_size > max_batch_size {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "Too many view functions: {}, while limit is {}",
+                    batch_size, max_batch_size
+                ),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+        Ok(())
+    };
+
+    // A view function which can't be converted fails on its own, like one which can't be executed
+    let view_functions: Vec<Result<ViewFunction, AptosError>> = match request {
+        ViewFunctionBatchRequest::Json(data) => {
+            check_batch_size(data.0.len())?;
+            let converter =
+                state_view.as_converter(context.db.clone(), context.indexer_reader.clone());
+            data.0
+                .into_iter()
+                .map(|request| {
+                    converter.convert_view_function(request).map_err(|err| {
+                        AptosError::new_with_error_code(err, AptosErrorCode::InvalidInput)
+                    })
+                })
+                .collect()
+        },
+        ViewFunctionBatchRequest::Bcs(data) => {
+            let view_functions: Vec<ViewFunction> =
+                bcs::from_bytes_with_limit(data.0.as_slice(), MAX_RECURSIVE_TYPES_ALLOWED as usize)
+                    .context("Failed to deserialize input into a list of ViewFunction")
+                    .map_err(|err| {
+                        BasicErrorWith404::bad_request_with_code(
+                            err,
+                            AptosErrorCode::InvalidInput,
+                            &ledger_info,
+                        )
+                    })?;
+            check_batch_size(view_functions.len())?;
+            view_functions.into_iter().map(Ok).collect()
+        },
+    };
+
+    let max_gas_per_function = context.node_config.api.max_gas_view_function;
+    let mut remaining_gas = context.node_config.api.max_gas_view_function_batch;
+    let mut outputs = Vec::with_capacity(view_functions.len());
+    for view_function in view_functions {
+        let view_function = match view_function {
+            Ok(view_function) => view_function,
+            Err(err) => {
+                outputs.push((None, Err(err), 0));
+                continue;
+            },
+        };
+        if !is_allowed(&context, &view_function) {
+            let err = AptosError::new_with_error_code(
+                format!(
+                    "Function {}::{} is not allowed",
+                    view_function.module, view_function.function
+                ),
+                AptosErrorCode::InvalidInput,
+            );
+            outputs.push((None, Err(err), 0));
+            continue;
+        }
+        if remaining_gas == 0 {
+            let err = AptosError::new_with_error_code(
+                "The gas budget of the batch is used up",
+                AptosErrorCode::InvalidInput,
+            );
+            outputs.push((None, Err(err), 0));
+            continue;
+        }
+
+        let output = AptosVM::execute_view_function(
+            &state_view,
+            view_function.module.clone(),
+            view_function.function.clone(),
+            view_function.ty_args.clone(),
+            view_function.args.clone(),
+            min(max_gas_per_function, remaining_gas),
+        );
+        remaining_gas = remaining_gas.saturating_sub(output.gas_used);
+        context.view_function_stats().increment(
+            FunctionStats::function_to_key(&view_function.module, &view_function.function),
+            output.gas_used,
+        );
+        let values = output
+            .values
+            .map_err(|err| AptosError::new_with_error_code(err, AptosErrorCode::InvalidInput));
+        outputs.push((Some(view_function), values, output.gas_used));
+    }
+    let total_gas_used = outputs.iter().map(|(_, _, gas_used)| gas_used).sum();
+
+    let result = match accept_type {
+        AcceptType::Bcs => {
+            let results: Vec<_> = outputs
+                .into_iter()
+                .map(|(_, values, gas_used)| ViewFunctionResultBcs { values, gas_used })
+                .collect();
+            BasicResponse::try_from_bcs((results, &ledger_info, BasicResponseStatus::Ok))
+        },
+        AcceptType::Json => {
+            let results: Vec<_> = outputs
+                .into_iter()
+                .map(|(view_function, values, gas_used)| {
+                    let values = values.and_then(|values| {
+                        let view_function = view_function.expect("executed view function");
+                        convert_return_values(&context, &state_view, &view_function, values)
+                            .map_err(|err| {
+                                AptosError::new_with_error_code(err, AptosErrorCode::InternalError)
+                            })
+                    });
+                    let (values, error) = match values {
+                        Ok(values) => (Some(values), None),
+                        Err(err) => (None, Some(err)),
+                    };
+                    ViewFunctionResult {
+                        values,
+                        error,
+                        gas_used: gas_used.into(),
+                    }
+                })
+                .collect();
+            BasicResponse::try_from_json((results, &ledger_info, BasicResponseStatus::Ok))
+        },
+    };
+    result.map(|r| r.with_gas_used(Some(total_gas_used)))
+}
+
+/// Checks the view function against the view filter of the node
+fn is_allowed(context: &Context, view_function: &ViewFunction) -> bool {
+    context.node_config.api.view_filter.allows(
+        view_function.module.address(),
+        view_function.module.name().as_str(),
+        view_function.function.as_str(),
+    )
+}
+
+/// Converts the BCS encoded return values of a view function into JSON values
+fn convert_return_values(
+    context: &Context,
+    state_view: &DbStateView,
+    view_function: &ViewFunction,
+    values: Vec<Vec<u8>>,
+) -> anyhow::Result<Vec<MoveValue>> {
+    let converter = state_view.as_converter(context.db.clone(), context.indexer_reader.clone());
+    let return_types = converter
+        .function_return_types(view_function)
+        .and_then(|tys| {
+            tys.into_iter()
+                .map(TypeTag::try_from)
+                .collect::<anyhow::Result<Vec<_>>>()
+        })?;
+
+    values
+        .into_iter()
+        .zip(return_types.into_iter())
+        .map(|(v, ty)| converter.try_into_move_value(&ty, &v))
+        .collect()
+}
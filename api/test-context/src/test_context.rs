@@ -17,6 +17,9 @@ use aptos_config::{
 };
 use aptos_crypto::{ed25519::Ed25519PrivateKey, hash::HashValue, SigningKey};
 use aptos_db::AptosDB;
+use aptos_db_indexer::{
+    db_indexer::DBIndexer, db_ops::open_internal_indexer_db, indexer_reader::IndexerReaders,
+};
 use aptos_executor::{block_executor::BlockExecutor, db_bootstrapper};
 use aptos_executor_types::BlockExecutorTrait;
 use aptos_framework::BuiltPackage;
@@ -30,7 +33,7 @@ use aptos_sdk::{
         transaction::SignedTransaction, AccountKey, LocalAccount,
     },
 };
-use aptos_storage_interface::{state_view::DbStateView, DbReaderWriter};
+use aptos_storage_interface::{state_view::DbStateView, DbReader, DbReaderWriter};
 use aptos_temppath::TempPath;
 use aptos_types::{
     account_address::{create_multisig_account_address, AccountAddress},
@@ -39,6 +42,7 @@ use aptos_types::{
     block_info::BlockInfo,
     block_metadata::BlockMetadata,
     chain_id::ChainId,
+    indexer::indexer_db_reader::IndexerReader,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     transaction::{
         signature_verified_transaction::into_signature_verified_block, Transaction,
@@ -140,12 +144,34 @@ pub fn new_test_context(
 
     let mempool = MockSharedMempool::new_in_runtime(&db_rw, VMValidator::new(db.clone()));
 
+    let indexer_db_config = node_config.indexer_db_config;
+    let db_indexer = if indexer_db_config.enable_transaction()
+        || indexer_db_config.enable_event()
+        || indexer_db_config.enable_account_activity()
+        || indexer_db_config.enable_object_ownership()
+    {
+        let indexer_db = open_internal_indexer_db(
+            tmp_dir.path().join("internal_indexer_db"),
+            &node_config.storage.rocksdb_configs.index_db_config,
+        )
+        .unwrap();
+        Some(Arc::new(DBIndexer::new(
+            Arc::new(indexer_db),
+            db.clone(),
+            &indexer_db_config,
+        )))
+    } else {
+        None
+    };
+    let indexer_reader = IndexerReaders::new(None, db_indexer.clone())
+        .map(|reader| Arc::new(reader) as Arc<dyn IndexerReader>);
+
     let context = Context::new(
         ChainId::test(),
         db.clone(),
         mempool.ac_client.clone(),
         node_config.clone(),
-        None, /* table info reader */
+        indexer_reader,
     );
 
     // Configure the testing depending on which API version we're testing.
@@ -162,6 +188,7 @@ pub fn new_test_context(
         Box::new(BlockExecutor::<AptosVM>::new(db_rw)),
         mempool,
         db,
+        db_indexer,
         test_name,
         api_specific_config,
     )
@@ -173,6 +200,7 @@ pub struct TestContext {
     pub validator_owner: AccountAddress,
     pub mempool: Arc<MockSharedMempool>,
     pub db: Arc<AptosDB>,
+    db_indexer: Option<Arc<DBIndexer>>,
     rng: rand::rngs::StdRng,
     root_key: ConfigKey<Ed25519PrivateKey>,
     executor: Arc<dyn BlockExecutorTrait>,
@@ -192,6 +220,7 @@ impl TestContext {
         executor: Box<dyn BlockExecutorTrait>,
         mempool: MockSharedMempool,
         db: Arc<AptosDB>,
+        db_indexer: Option<Arc<DBIndexer>>,
         test_name: String,
         api_specific_config: ApiSpecificConfig,
     ) -> Self {
@@ -204,6 +233,7 @@ impl TestContext {
             mempool: Arc::new(mempool),
             expect_status_code: 200,
             db,
+            db_indexer,
             test_name,
             golden_output: None,
             fake_time_usecs: 0,
@@ -636,6 +666,7 @@ impl TestContext {
                 self.new_ledger_info(&metadata, result.root_hash(), txns.len() + 1),
             )
             .unwrap();
+        self.wait_for_db_indexer();
 
         self.mempool
             .mempool_notifier
@@ -644,6 +675,21 @@ impl TestContext {
             .unwrap();
     }
 
+    /// Indexes the committed transactions if the internal indexer is enabled, and waits for them
+    /// to be persisted.
+    fn wait_for_db_indexer(&self) {
+        if let Some(db_indexer) = &self.db_indexer {
+            let synced_version = self.db.get_synced_version().unwrap();
+            let mut version = db_indexer.get_persisted_version().unwrap();
+            while version <= synced_version {
+                version = db_indexer.process_a_batch(Some(version)).unwrap();
+            }
+            while db_indexer.get_persisted_version().unwrap() < synced_version {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        }
+    }
+
     pub async fn get_sequence_number(&self, account: AccountAddress) -> u64 {
         let account_resource = self
             .gen_resource(&account, "0x1::account::Account")
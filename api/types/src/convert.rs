@@ -109,6 +109,11 @@ impl<'a, S: StateView> MoveConverter<'a, S> {
         false
     }
 
+    /// The resource group the resource is stored in, if it is a member of one
+    pub fn resource_group_of(&self, tag: &StructTag) -> Option<StructTag> {
+        self.inner.view_resource_group_member(tag)
+    }
+
     pub fn find_resource(
         &self,
         state_view: &impl StateView,
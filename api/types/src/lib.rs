@@ -17,8 +17,10 @@ mod index;
 mod ledger_info;
 pub mod mime_types;
 mod move_types;
+mod object;
 mod state;
 mod table;
+mod trace;
 pub mod transaction;
 mod view;
 mod wrappers;
@@ -40,24 +42,27 @@ pub use move_types::{
     MoveScriptBytecode, MoveStruct, MoveStructField, MoveStructTag, MoveType, MoveValue,
     ResourceGroup, MAX_RECURSIVE_TYPES_ALLOWED, U128, U256, U64,
 };
+pub use object::{ObjectOwnerChange, OwnedObject};
 use serde::{Deserialize, Deserializer};
 pub use state::RawStateValueRequest;
 use std::str::FromStr;
 pub use table::{RawTableItemRequest, TableItemRequest};
+pub use trace::{CallTrace, ResourceAccess, TracedUserTransaction};
 pub use transaction::{
-    AccountSignature, BlockMetadataTransaction, DeleteModule, DeleteResource, DeleteTableItem,
-    DirectWriteSet, Ed25519Signature, EncodeSubmissionRequest, EntryFunctionPayload, Event,
-    FeePayerSignature, GasEstimation, GasEstimationBcs, GenesisPayload, GenesisTransaction,
-    MultiAgentSignature, MultiEd25519Signature, MultiKeySignature, MultisigPayload,
-    MultisigTransactionPayload, PendingTransaction, PublicKey, ScriptPayload, ScriptWriteSet,
-    Signature, SingleKeySignature, SubmitTransactionRequest, Transaction, TransactionData,
-    TransactionId, TransactionInfo, TransactionOnChainData, TransactionPayload,
-    TransactionSignature, TransactionSigningMessage, TransactionsBatchSingleSubmissionFailure,
-    TransactionsBatchSubmissionResult, UserCreateSigningMessageRequest, UserTransaction,
-    UserTransactionRequest, VersionedEvent, WriteModule, WriteResource, WriteSet, WriteSetChange,
-    WriteSetPayload, WriteTableItem,
+    AccountSignature, BalanceOverride, BlockMetadataTransaction, DeleteModule, DeleteResource,
+    DeleteTableItem, DirectWriteSet, Ed25519Signature, EncodeSubmissionRequest,
+    EntryFunctionPayload, Event, FeePayerSignature, GasEstimation, GasEstimationBcs,
+    GenesisPayload, GenesisTransaction, ModuleOverride, MultiAgentSignature, MultiEd25519Signature,
+    MultiKeySignature, MultisigPayload, MultisigTransactionPayload, PendingTransaction, PublicKey,
+    ResourceOverride, ScriptPayload, ScriptWriteSet, SequenceNumberOverride, Signature,
+    SimulateBundleRequest, SingleKeySignature, StateOverrides, SubmitTransactionRequest,
+    Transaction, TransactionData, TransactionId, TransactionInfo, TransactionOnChainData,
+    TransactionPayload, TransactionSignature, TransactionSigningMessage,
+    TransactionsBatchSingleSubmissionFailure, TransactionsBatchSubmissionResult,
+    UserCreateSigningMessageRequest, UserTransaction, UserTransactionRequest, VersionedEvent,
+    WriteModule, WriteResource, WriteSet, WriteSetChange, WriteSetPayload, WriteTableItem,
 };
-pub use view::{ViewFunction, ViewRequest};
+pub use view::{ViewFunction, ViewFunctionResult, ViewFunctionResultBcs, ViewRequest};
 pub use wrappers::{EventGuid, IdentifierWrapper, StateKeyWrapper};
 
 pub fn deserialize_from_string<'de, D, T>(deserializer: D) -> Result<T, D::Error>
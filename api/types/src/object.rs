@@ -0,0 +1,24 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Address, U64};
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+
+/// An object owned by an account
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct OwnedObject {
+    /// Address of the object
+    pub address: Address,
+    /// Ledger version of the transaction that made the account the owner of the object
+    pub owned_since_version: U64,
+}
+
+/// A change of the owner of an object
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ObjectOwnerChange {
+    /// Ledger version of the transaction that changed the owner
+    pub version: U64,
+    /// The new owner of the object, or null if the object was deleted
+    pub owner: Option<Address>,
+}
@@ -0,0 +1,59 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Address, MoveStructTag, MoveType, UserTransaction, U64};
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+
+/// A user transaction along with the call tree of its execution
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TracedUserTransaction {
+    #[serde(flatten)]
+    #[oai(flatten)]
+    pub transaction: UserTransaction,
+    pub call_trace: CallTrace,
+}
+
+/// A call made during the execution of a transaction
+///
+/// The outermost call is the entry function or script of the transaction, whose arguments are
+/// part of the transaction payload. Values are rendered as text, with the fields of structs in
+/// declaration order, e.g. `{0x1, [1, 2]}`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct CallTrace {
+    /// The called function, e.g. `0x1::coin::transfer`, or `script` for a script
+    pub function: String,
+    /// Type arguments of the function
+    pub type_arguments: Vec<MoveType>,
+    /// Arguments of the function
+    pub arguments: Vec<String>,
+    /// Whether the function is implemented natively
+    pub is_native: bool,
+    /// The returned values, if the call returned
+    pub return_values: Option<Vec<String>>,
+    /// The abort code, if the call or one of the calls it made aborted
+    pub abort_code: Option<U64>,
+    /// The VM status, if the call or one of the calls it made failed other than by aborting,
+    /// e.g. by running out of gas
+    pub error: Option<String>,
+    /// Gas used by the call, including the calls it made
+    ///
+    /// This is in internal gas units, which are gas units multiplied by the gas unit scaling
+    /// factor of the gas schedule.
+    pub internal_gas_used: U64,
+    /// Resources read by the call, excluding the ones read by the calls it made
+    pub resources_read: Vec<ResourceAccess>,
+    /// Resources written by the call, excluding the ones written by the calls it made
+    pub resources_written: Vec<ResourceAccess>,
+    /// Calls made by the call, in order
+    pub calls: Vec<CallTrace>,
+}
+
+/// A resource accessed by a call
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ResourceAccess {
+    pub address: Address,
+    #[serde(rename = "type")]
+    #[oai(rename = "type")]
+    pub typ: MoveStructTag,
+}
@@ -3,9 +3,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    Address, AptosError, EntryFunctionId, EventGuid, HashValue, HexEncodedBytes,
-    MoveModuleBytecode, MoveModuleId, MoveResource, MoveScriptBytecode, MoveStructTag, MoveType,
-    MoveValue, VerifyInput, VerifyInputWithRecursion, U64,
+    move_types::MoveStructValue, Address, AptosError, EntryFunctionId, EventGuid, HashValue,
+    HexEncodedBytes, MoveModuleBytecode, MoveModuleId, MoveResource, MoveScriptBytecode,
+    MoveStructTag, MoveType, MoveValue, VerifyInput, VerifyInputWithRecursion, U64,
 };
 use anyhow::{bail, Context as AnyhowContext};
 use aptos_crypto::{
@@ -467,6 +467,97 @@ impl VerifyInput for SubmitTransactionRequest {
     }
 }
 
+/// A request to simulate an ordered bundle of transactions
+///
+/// Each transaction is simulated on top of the outputs of the previous ones, starting from the
+/// latest state with the state overrides applied.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct SimulateBundleRequest {
+    pub transactions: Vec<SubmitTransactionRequest>,
+    pub state_overrides: Option<StateOverrides>,
+}
+
+impl VerifyInput for SimulateBundleRequest {
+    fn verify(&self) -> anyhow::Result<()> {
+        if self.transactions.is_empty() {
+            bail!("At least one transaction must be simulated")
+        }
+        for transaction in self.transactions.iter() {
+            transaction.verify()?;
+        }
+        if let Some(state_overrides) = &self.state_overrides {
+            state_overrides.verify()?;
+        }
+        Ok(())
+    }
+}
+
+/// Changes to the state before a simulation
+///
+/// Overrides are applied in order: feature flags, balances, sequence numbers, resources, modules
+/// and finally the timestamp.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct StateOverrides {
+    /// APT balances of accounts, stored in their `0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>`,
+    /// or in their primary fungible store if they have one or the FA APT store features are
+    /// enabled
+    pub balances: Option<Vec<BalanceOverride>>,
+    /// Sequence numbers of accounts, the account is created if it does not exist
+    pub sequence_numbers: Option<Vec<SequenceNumberOverride>>,
+    /// Resources replacing the ones stored under accounts
+    pub resources: Option<Vec<ResourceOverride>>,
+    /// Modules replacing or added to the ones published under accounts
+    pub modules: Option<Vec<ModuleOverride>>,
+    /// The on-chain time, in microseconds
+    pub timestamp_usecs: Option<U64>,
+    /// Names of feature flags to enable, e.g. `KEYLESS_ACCOUNTS`
+    pub enabled_features: Option<Vec<String>>,
+    /// Names of feature flags to disable
+    pub disabled_features: Option<Vec<String>>,
+}
+
+impl VerifyInput for StateOverrides {
+    fn verify(&self) -> anyhow::Result<()> {
+        for resource in self.resources.iter().flatten() {
+            resource.typ.verify(0)?;
+        }
+        for module in self.modules.iter().flatten() {
+            if module.bytecode.inner().is_empty() {
+                bail!("Module bytecode must not be empty")
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct BalanceOverride {
+    pub address: Address,
+    /// Balance in octas
+    pub amount: U64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct SequenceNumberOverride {
+    pub address: Address,
+    pub sequence_number: U64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ResourceOverride {
+    pub address: Address,
+    #[serde(rename = "type")]
+    #[oai(rename = "type")]
+    pub typ: MoveStructTag,
+    pub data: MoveStructValue,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ModuleOverride {
+    pub address: Address,
+    pub bytecode: HexEncodedBytes,
+}
+
 /// Batch transaction submission result
 ///
 /// Tells which transactions failed
@@ -1,7 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{EntryFunctionId, MoveType};
+use crate::{AptosError, EntryFunctionId, MoveType, MoveValue, U64};
 use aptos_types::serde_helper::vec_bytes;
 use move_core_types::{
     identifier::Identifier,
@@ -28,3 +28,23 @@ pub struct ViewFunction {
     #[serde(with = "vec_bytes")]
     pub args: Vec<Vec<u8>>,
 }
+
+/// The result of a view function of a batch view request
+///
+/// Exactly one of `values` and `error` is set.
+#[derive(Clone, Debug, Serialize, Deserialize, Object)]
+pub struct ViewFunctionResult {
+    /// Values returned by the function, if it succeeded
+    pub values: Option<Vec<MoveValue>>,
+    /// Error the function failed with, if it failed
+    pub error: Option<AptosError>,
+    /// Gas units used by the function
+    pub gas_used: U64,
+}
+
+/// The result of a view function of a batch view request, with BCS encoded return values
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ViewFunctionResultBcs {
+    pub values: Result<Vec<Vec<u8>>, AptosError>,
+    pub gas_used: u64,
+}
@@ -465,6 +465,24 @@ where
         Ok(())
     }
 
+    #[inline]
+    fn charge_return_values(
+        &mut self,
+        _ret_vals: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn charge_resource_access(
+        &mut self,
+        _addr: AccountAddress,
+        _ty: impl TypeView,
+        _is_write: bool,
+    ) -> PartialVMResult<()> {
+        Ok(())
+    }
+
     #[inline]
     fn charge_create_ty(&mut self, num_nodes: NumTypeNodes) -> PartialVMResult<()> {
         if self.feature_version() < 14 {
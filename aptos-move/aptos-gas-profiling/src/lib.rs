@@ -9,6 +9,8 @@ mod misc;
 mod profiler;
 mod render;
 mod report;
+mod trace;
 
 pub use log::{FrameName, TransactionGasLog};
 pub use profiler::GasProfiler;
+pub use trace::{CallOutcome, CallTrace, CallTracer, ResourceAccess};
@@ -177,6 +177,20 @@ where
             &mut self,
             locals: impl Iterator<Item = impl ValueView> + Clone,
         ) -> PartialVMResult<()>;
+
+        // Note: we don't use this to charge gas so no need to record anything.
+        fn charge_return_values(
+            &mut self,
+            ret_vals: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+
+        // Note: we don't use this to charge gas so no need to record anything.
+        fn charge_resource_access(
+            &mut self,
+            addr: AccountAddress,
+            ty: impl TypeView,
+            is_write: bool,
+        ) -> PartialVMResult<()>;
     }
 
     record_bytecode! {
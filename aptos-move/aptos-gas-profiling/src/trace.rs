@@ -0,0 +1,705 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::log::FrameName;
+use aptos_gas_algebra::{Fee, FeePerGasUnit, InternalGas, NumArgs, NumBytes, NumTypeNodes};
+use aptos_gas_meter::AptosGasMeter;
+use aptos_types::{
+    contract_event::ContractEvent, state_store::state_key::StateKey, write_set::WriteOpSize,
+};
+use move_binary_format::{
+    errors::{PartialVMResult, VMResult},
+    file_format::CodeOffset,
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    identifier::{IdentStr, Identifier},
+    language_storage::{ModuleId, TypeTag},
+    u256::U256,
+    vm_status::{StatusCode, VMStatus},
+};
+use move_vm_types::{
+    delayed_values::delayed_field_id::DelayedFieldID,
+    gas::{GasMeter, SimpleInstruction},
+    views::{TypeView, ValueView, ValueVisitor},
+};
+use std::collections::BTreeSet;
+
+/// Rendered values longer than this are truncated.
+const MAX_RENDERED_VALUE_LENGTH: usize = 1024;
+
+/// How a traced call ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallOutcome {
+    /// The call returned the given values.
+    Returned(Vec<String>),
+    /// The call, or one of the calls it made, aborted with the given code.
+    Aborted(u64),
+    /// The call, or one of the calls it made, failed with an error other than an abort, e.g.
+    /// running out of gas.
+    Failed(StatusCode),
+    /// The execution finished without the call returning.
+    Unfinished,
+}
+
+/// A resource accessed by a call.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ResourceAccess {
+    pub addr: AccountAddress,
+    pub ty: TypeTag,
+}
+
+/// A node of the call tree of a transaction.
+#[derive(Debug, Clone)]
+pub struct CallTrace {
+    pub name: FrameName,
+    pub is_native: bool,
+    /// The arguments of the call, rendered as text. Struct fields are not named, since values
+    /// don't carry their types.
+    pub args: Vec<String>,
+    pub outcome: CallOutcome,
+    /// The gas used by the call, including the calls it made.
+    pub gas_used: InternalGas,
+    pub resources_read: BTreeSet<ResourceAccess>,
+    pub resources_written: BTreeSet<ResourceAccess>,
+    pub calls: Vec<CallTrace>,
+}
+
+struct OpenFrame {
+    trace: CallTrace,
+    /// The balance and the gas charged outside of execution when the frame was entered
+    balance: InternalGas,
+    non_execution_gas: InternalGas,
+}
+
+/// A gas meter adapter that records the call tree of a transaction, along with the arguments,
+/// results, gas usage and resource accesses of every call.
+///
+/// Unlike the `GasProfiler`, which records every instruction to attribute costs precisely, the
+/// tracer only records calls, so it is cheap enough to trace transactions on demand.
+pub struct CallTracer<G> {
+    base: G,
+
+    /// Gas charged outside of the execution of Move code, e.g. the intrinsic gas or the IO gas
+    /// and storage fees of the outputs
+    non_execution_gas: InternalGas,
+    frames: Vec<OpenFrame>,
+}
+
+// TODO: consider switching to a library like https://docs.rs/delegate/latest/delegate/.
+macro_rules! delegate {
+    ($(
+        fn $fn: ident $(<$($lt: lifetime),*>)? (&self $(, $arg: ident : $ty: ty)* $(,)?) -> $ret_ty: ty;
+    )*) => {
+        $(fn $fn $(<$($lt)*>)? (&self, $($arg: $ty),*) -> $ret_ty {
+            self.base.$fn($($arg),*)
+        })*
+    };
+}
+
+macro_rules! delegate_mut {
+    ($(
+        fn $fn: ident $(<$($lt: lifetime),*>)? (&mut self $(, $arg: ident : $ty: ty)* $(,)?) -> $ret_ty: ty;
+    )*) => {
+        $(fn $fn $(<$($lt)*>)? (&mut self, $($arg: $ty),*) -> $ret_ty {
+            self.base.$fn($($arg),*)
+        })*
+    };
+}
+
+macro_rules! record_non_execution_gas {
+    ($(
+        fn $fn: ident (&mut self $(, $arg: ident : $ty: ty)* $(,)?) -> $ret_ty: ty;
+    )*) => {
+        $(fn $fn(&mut self, $($arg: $ty),*) -> $ret_ty {
+            let old = self.base.balance_internal();
+            let res = self.base.$fn($($arg),*);
+            let cost = old
+                .checked_sub(self.base.balance_internal())
+                .expect("gas cost must be non-negative");
+            self.non_execution_gas += cost;
+            res
+        })*
+    };
+}
+
+impl<G> CallTracer<G>
+where
+    G: AptosGasMeter,
+{
+    pub fn new_script(base: G) -> Self {
+        Self::new(base, FrameName::Script)
+    }
+
+    pub fn new_function(
+        base: G,
+        module_id: ModuleId,
+        func_name: Identifier,
+        ty_args: Vec<TypeTag>,
+    ) -> Self {
+        Self::new(base, FrameName::Function {
+            module_id,
+            name: func_name,
+            ty_args,
+        })
+    }
+
+    fn new(base: G, name: FrameName) -> Self {
+        let mut tracer = Self {
+            base,
+            non_execution_gas: 0.into(),
+            frames: vec![],
+        };
+        tracer.enter_frame(name, vec![]);
+        tracer
+    }
+
+    fn enter_frame(&mut self, name: FrameName, args: Vec<String>) {
+        self.frames.push(OpenFrame {
+            trace: CallTrace {
+                name,
+                is_native: false,
+                args,
+                outcome: CallOutcome::Unfinished,
+                gas_used: 0.into(),
+                resources_read: BTreeSet::new(),
+                resources_written: BTreeSet::new(),
+                calls: vec![],
+            },
+            balance: self.base.balance_internal(),
+            non_execution_gas: self.non_execution_gas,
+        });
+    }
+
+    fn close_frame(&self, frame: OpenFrame, outcome: CallOutcome) -> CallTrace {
+        let OpenFrame {
+            mut trace,
+            balance,
+            non_execution_gas,
+        } = frame;
+        let cost = balance
+            .checked_sub(self.base.balance_internal())
+            .expect("gas cost must be non-negative");
+        let non_execution_cost = self
+            .non_execution_gas
+            .checked_sub(non_execution_gas)
+            .expect("non-execution gas only grows");
+        trace.gas_used = cost
+            .checked_sub(non_execution_cost)
+            .expect("non-execution gas is part of the cost");
+        trace.outcome = outcome;
+        trace
+    }
+
+    /// Closes the current call, which becomes one of the calls of its caller. The outermost frame
+    /// is only closed by `finish`.
+    fn return_from_frame(&mut self, ret_vals: Vec<String>) {
+        if self.frames.len() > 1 {
+            let frame = self.frames.pop().expect("frame must exist");
+            let trace = self.close_frame(frame, CallOutcome::Returned(ret_vals));
+            self.current_frame().calls.push(trace);
+        } else {
+            self.current_frame().outcome = CallOutcome::Returned(ret_vals);
+        }
+    }
+
+    fn current_frame(&mut self) -> &mut CallTrace {
+        &mut self.frames.last_mut().expect("frame must exist").trace
+    }
+
+    /// Returns the call tree. The calls still open when the execution finished are the ones the
+    /// error of the transaction, if any, propagated through.
+    pub fn finish(mut self, status: &VMStatus) -> CallTrace {
+        let outcome = match status {
+            VMStatus::Executed => CallOutcome::Unfinished,
+            VMStatus::MoveAbort(_, code) => CallOutcome::Aborted(*code),
+            VMStatus::ExecutionFailure {
+                status_code: StatusCode::ABORTED,
+                sub_status: Some(code),
+                ..
+            } => CallOutcome::Aborted(*code),
+            status => CallOutcome::Failed(status.status_code()),
+        };
+
+        while self.frames.len() > 1 {
+            let frame = self.frames.pop().expect("frame must exist");
+            let trace = self.close_frame(frame, outcome.clone());
+            self.current_frame().calls.push(trace);
+        }
+        let frame = self.frames.pop().expect("frame must exist");
+        let root_outcome = match &frame.trace.outcome {
+            CallOutcome::Returned(_) if *status == VMStatus::Executed => {
+                frame.trace.outcome.clone()
+            },
+            _ => outcome,
+        };
+        self.close_frame(frame, root_outcome)
+    }
+}
+
+impl<G> GasMeter for CallTracer<G>
+where
+    G: AptosGasMeter,
+{
+    delegate_mut! {
+        fn charge_simple_instr(&mut self, instr: SimpleInstruction) -> PartialVMResult<()>;
+
+        fn charge_br_true(&mut self, target_offset: Option<CodeOffset>) -> PartialVMResult<()>;
+
+        fn charge_br_false(&mut self, target_offset: Option<CodeOffset>) -> PartialVMResult<()>;
+
+        fn charge_branch(&mut self, target_offset: CodeOffset) -> PartialVMResult<()>;
+
+        fn charge_pop(&mut self, popped_val: impl ValueView) -> PartialVMResult<()>;
+
+        fn charge_ld_const(&mut self, size: NumBytes) -> PartialVMResult<()>;
+
+        fn charge_ld_const_after_deserialization(&mut self, val: impl ValueView)
+            -> PartialVMResult<()>;
+
+        fn charge_copy_loc(&mut self, val: impl ValueView) -> PartialVMResult<()>;
+
+        fn charge_move_loc(&mut self, val: impl ValueView) -> PartialVMResult<()>;
+
+        fn charge_store_loc(&mut self, val: impl ValueView) -> PartialVMResult<()>;
+
+        fn charge_pack(
+            &mut self,
+            is_generic: bool,
+            args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+
+        fn charge_unpack(
+            &mut self,
+            is_generic: bool,
+            args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+
+        fn charge_read_ref(&mut self, val: impl ValueView) -> PartialVMResult<()>;
+
+        fn charge_write_ref(
+            &mut self,
+            new_val: impl ValueView,
+            old_val: impl ValueView,
+        ) -> PartialVMResult<()>;
+
+        fn charge_eq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()>;
+
+        fn charge_neq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()>;
+
+        fn charge_borrow_global(
+            &mut self,
+            is_mut: bool,
+            is_generic: bool,
+            ty: impl TypeView,
+            is_success: bool,
+        ) -> PartialVMResult<()>;
+
+        fn charge_exists(
+            &mut self,
+            is_generic: bool,
+            ty: impl TypeView,
+            exists: bool,
+        ) -> PartialVMResult<()>;
+
+        fn charge_move_from(
+            &mut self,
+            is_generic: bool,
+            ty: impl TypeView,
+            val: Option<impl ValueView>,
+        ) -> PartialVMResult<()>;
+
+        fn charge_move_to(
+            &mut self,
+            is_generic: bool,
+            ty: impl TypeView,
+            val: impl ValueView,
+            is_success: bool,
+        ) -> PartialVMResult<()>;
+
+        fn charge_vec_pack<'a>(
+            &mut self,
+            ty: impl TypeView + 'a,
+            args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+
+        fn charge_vec_len(&mut self, ty: impl TypeView) -> PartialVMResult<()>;
+
+        fn charge_vec_borrow(
+            &mut self,
+            is_mut: bool,
+            ty: impl TypeView,
+            is_success: bool,
+        ) -> PartialVMResult<()>;
+
+        fn charge_vec_push_back(
+            &mut self,
+            ty: impl TypeView,
+            val: impl ValueView,
+        ) -> PartialVMResult<()>;
+
+        fn charge_vec_pop_back(
+            &mut self,
+            ty: impl TypeView,
+            val: Option<impl ValueView>,
+        ) -> PartialVMResult<()>;
+
+        fn charge_vec_unpack(
+            &mut self,
+            ty: impl TypeView,
+            expect_num_elements: NumArgs,
+            elems: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+
+        fn charge_vec_swap(&mut self, ty: impl TypeView) -> PartialVMResult<()>;
+
+        fn charge_load_resource(
+            &mut self,
+            addr: AccountAddress,
+            ty: impl TypeView,
+            val: Option<impl ValueView>,
+            bytes_loaded: NumBytes,
+        ) -> PartialVMResult<()>;
+
+        fn charge_native_function_before_execution(
+            &mut self,
+            ty_args: impl ExactSizeIterator<Item = impl TypeView> + Clone,
+            args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+
+        fn charge_drop_frame(
+            &mut self,
+            locals: impl Iterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+
+        fn charge_create_ty(&mut self, num_nodes: NumTypeNodes) -> PartialVMResult<()>;
+
+        fn charge_dependency(
+            &mut self,
+            is_new: bool,
+            addr: &AccountAddress,
+            name: &IdentStr,
+            size: NumBytes,
+        ) -> PartialVMResult<()>;
+    }
+
+    fn balance_internal(&self) -> InternalGas {
+        self.base.balance_internal()
+    }
+
+    fn charge_call(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        let rendered_args = args.clone().map(|arg| render_value(&arg)).collect();
+
+        let res = self
+            .base
+            .charge_call(module_id, func_name, args, num_locals);
+
+        self.enter_frame(
+            FrameName::Function {
+                module_id: module_id.clone(),
+                name: Identifier::new(func_name).unwrap(),
+                ty_args: vec![],
+            },
+            rendered_args,
+        );
+
+        res
+    }
+
+    fn charge_call_generic(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView> + Clone,
+        args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        let ty_tags = ty_args
+            .clone()
+            .map(|ty| ty.to_type_tag())
+            .collect::<Vec<_>>();
+        let rendered_args = args.clone().map(|arg| render_value(&arg)).collect();
+
+        let res = self
+            .base
+            .charge_call_generic(module_id, func_name, ty_args, args, num_locals);
+
+        self.enter_frame(
+            FrameName::Function {
+                module_id: module_id.clone(),
+                name: Identifier::new(func_name).unwrap(),
+                ty_args: ty_tags,
+            },
+            rendered_args,
+        );
+
+        res
+    }
+
+    fn charge_native_function(
+        &mut self,
+        amount: InternalGas,
+        ret_vals: Option<impl ExactSizeIterator<Item = impl ValueView> + Clone>,
+    ) -> PartialVMResult<()> {
+        let rendered_ret_vals = ret_vals
+            .clone()
+            .map(|vals| vals.map(|val| render_value(&val)).collect());
+
+        let res = self.base.charge_native_function(amount, ret_vals);
+
+        // The frame was entered by `charge_call`, as the VM does not tell natives apart before.
+        // A native without return values either failed, or dispatched to a Move function whose
+        // return closes the frame.
+        self.current_frame().is_native = true;
+        if let Some(ret_vals) = rendered_ret_vals {
+            self.return_from_frame(ret_vals);
+        }
+
+        res
+    }
+
+    fn charge_return_values(
+        &mut self,
+        ret_vals: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+    ) -> PartialVMResult<()> {
+        let rendered_ret_vals = ret_vals.clone().map(|val| render_value(&val)).collect();
+
+        let res = self.base.charge_return_values(ret_vals);
+
+        self.return_from_frame(rendered_ret_vals);
+
+        res
+    }
+
+    fn charge_resource_access(
+        &mut self,
+        addr: AccountAddress,
+        ty: impl TypeView,
+        is_write: bool,
+    ) -> PartialVMResult<()> {
+        let access = ResourceAccess {
+            addr,
+            ty: ty.to_type_tag(),
+        };
+
+        let res = self.base.charge_resource_access(addr, ty, is_write);
+
+        let frame = self.current_frame();
+        if is_write {
+            frame.resources_written.insert(access);
+        } else {
+            frame.resources_read.insert(access);
+        }
+
+        res
+    }
+}
+
+impl<G> AptosGasMeter for CallTracer<G>
+where
+    G: AptosGasMeter,
+{
+    type Algebra = G::Algebra;
+
+    delegate! {
+        fn algebra(&self) -> &Self::Algebra;
+    }
+
+    delegate_mut! {
+        fn algebra_mut(&mut self) -> &mut Self::Algebra;
+    }
+
+    record_non_execution_gas! {
+        fn charge_storage_fee(
+            &mut self,
+            amount: Fee,
+            gas_unit_price: FeePerGasUnit,
+        ) -> PartialVMResult<()>;
+
+        fn charge_intrinsic_gas_for_transaction(&mut self, txn_size: NumBytes) -> VMResult<()>;
+
+        fn charge_keyless(&mut self) -> VMResult<()>;
+
+        fn charge_io_gas_for_transaction(&mut self, txn_size: NumBytes) -> VMResult<()>;
+
+        fn charge_io_gas_for_event(&mut self, event: &ContractEvent) -> VMResult<()>;
+
+        fn charge_io_gas_for_write(&mut self, key: &StateKey, op: &WriteOpSize) -> VMResult<()>;
+    }
+}
+
+/// Renders a value as text, e.g. `{0x1, [1, 2]}` for a struct holding an address and a vector.
+fn render_value(val: &impl ValueView) -> String {
+    let mut renderer = ValueRenderer {
+        output: String::new(),
+        open_containers: vec![],
+    };
+    val.visit(&mut renderer);
+
+    let mut output = renderer.output;
+    if output.len() > MAX_RENDERED_VALUE_LENGTH {
+        let mut end = MAX_RENDERED_VALUE_LENGTH;
+        while !output.is_char_boundary(end) {
+            end -= 1;
+        }
+        output.truncate(end);
+        output.push_str("..");
+    }
+    output
+}
+
+/// Builds the text of a value from the pre-order traversal of the value visitor. Once the text
+/// reaches `MAX_RENDERED_VALUE_LENGTH`, the rest of the value is not visited.
+struct ValueRenderer {
+    output: String,
+    /// The containers being rendered, with the number of items left in each and their closing
+    /// delimiter
+    open_containers: Vec<(usize, &'static str)>,
+}
+
+impl ValueRenderer {
+    fn is_full(&self) -> bool {
+        self.output.len() >= MAX_RENDERED_VALUE_LENGTH
+    }
+
+    fn leaf(&mut self, text: impl AsRef<str>) {
+        if self.is_full() {
+            return;
+        }
+        self.output.push_str(text.as_ref());
+        self.item_done();
+    }
+
+    /// Opens a container, returning whether its items should be visited.
+    fn container(&mut self, open: &str, close: &'static str, len: usize) -> bool {
+        if self.is_full() {
+            return false;
+        }
+        self.output.push_str(open);
+        if len == 0 {
+            self.output.push_str(close);
+            self.item_done();
+        } else {
+            self.open_containers.push((len, close));
+        }
+        true
+    }
+
+    fn vec<T>(&mut self, vals: &[T], render: impl Fn(&T) -> String) {
+        if !self.container("[", "]", vals.len()) {
+            return;
+        }
+        for val in vals {
+            if self.is_full() {
+                return;
+            }
+            self.leaf(render(val));
+        }
+    }
+
+    fn item_done(&mut self) {
+        while let Some((remaining, close)) = self.open_containers.last_mut() {
+            *remaining -= 1;
+            if *remaining > 0 {
+                self.output.push_str(", ");
+                return;
+            }
+            self.output.push_str(close);
+            self.open_containers.pop();
+        }
+    }
+}
+
+impl ValueVisitor for ValueRenderer {
+    fn visit_delayed(&mut self, _depth: usize, id: DelayedFieldID) {
+        self.leaf(format!("<delayed {}>", id.as_u64()))
+    }
+
+    fn visit_u8(&mut self, _depth: usize, val: u8) {
+        self.leaf(val.to_string())
+    }
+
+    fn visit_u16(&mut self, _depth: usize, val: u16) {
+        self.leaf(val.to_string())
+    }
+
+    fn visit_u32(&mut self, _depth: usize, val: u32) {
+        self.leaf(val.to_string())
+    }
+
+    fn visit_u64(&mut self, _depth: usize, val: u64) {
+        self.leaf(val.to_string())
+    }
+
+    fn visit_u128(&mut self, _depth: usize, val: u128) {
+        self.leaf(val.to_string())
+    }
+
+    fn visit_u256(&mut self, _depth: usize, val: U256) {
+        self.leaf(val.to_string())
+    }
+
+    fn visit_bool(&mut self, _depth: usize, val: bool) {
+        self.leaf(val.to_string())
+    }
+
+    fn visit_address(&mut self, _depth: usize, val: AccountAddress) {
+        self.leaf(val.to_hex_literal())
+    }
+
+    fn visit_struct(&mut self, _depth: usize, len: usize) -> bool {
+        self.container("{", "}", len)
+    }
+
+    fn visit_vec(&mut self, _depth: usize, len: usize) -> bool {
+        self.container("[", "]", len)
+    }
+
+    fn visit_ref(&mut self, _depth: usize, _is_global: bool) -> bool {
+        self.container("&", "", 1)
+    }
+
+    fn visit_vec_u8(&mut self, _depth: usize, vals: &[u8]) {
+        // Two hex digits per byte, the rest would be truncated
+        let hex: String = vals
+            .iter()
+            .take(MAX_RENDERED_VALUE_LENGTH / 2)
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        self.leaf(format!("0x{}", hex))
+    }
+
+    fn visit_vec_u16(&mut self, _depth: usize, vals: &[u16]) {
+        self.vec(vals, u16::to_string)
+    }
+
+    fn visit_vec_u32(&mut self, _depth: usize, vals: &[u32]) {
+        self.vec(vals, u32::to_string)
+    }
+
+    fn visit_vec_u64(&mut self, _depth: usize, vals: &[u64]) {
+        self.vec(vals, u64::to_string)
+    }
+
+    fn visit_vec_u128(&mut self, _depth: usize, vals: &[u128]) {
+        self.vec(vals, u128::to_string)
+    }
+
+    fn visit_vec_u256(&mut self, _depth: usize, vals: &[U256]) {
+        self.vec(vals, U256::to_string)
+    }
+
+    fn visit_vec_bool(&mut self, _depth: usize, vals: &[bool]) {
+        self.vec(vals, bool::to_string)
+    }
+
+    fn visit_vec_address(&mut self, _depth: usize, vals: &[AccountAddress]) {
+        self.vec(vals, AccountAddress::to_hex_literal)
+    }
+}
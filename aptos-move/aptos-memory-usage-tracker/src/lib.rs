@@ -166,6 +166,18 @@ where
         fn charge_create_ty(&mut self, num_nodes: NumTypeNodes) -> PartialVMResult<()>;
 
         fn charge_dependency(&mut self, is_new: bool, addr: &AccountAddress, name: &IdentStr, size: NumBytes) -> PartialVMResult<()>;
+
+        fn charge_return_values(
+            &mut self,
+            ret_vals: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+
+        fn charge_resource_access(
+            &mut self,
+            addr: AccountAddress,
+            ty: impl TypeView,
+            is_write: bool,
+        ) -> PartialVMResult<()>;
     }
 
     #[inline]
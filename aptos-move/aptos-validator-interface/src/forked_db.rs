@@ -0,0 +1,599 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{AptosValidatorInterface, DebuggerStateView, RestDebuggerInterface};
+use aptos_config::config::ForkConfig;
+use aptos_crypto::HashValue;
+use aptos_rest_client::Client;
+use aptos_storage_interface::{
+    cached_state_view::ShardedStateCache, state_delta::StateDelta, DbReader, DbReaderWriter,
+    DbWriter, Result, StateSnapshotReceiver, MAX_REQUEST_LIMIT,
+};
+use aptos_types::{
+    access_path::Path,
+    account_config::AccountResource,
+    ledger_info::LedgerInfoWithSignatures,
+    state_store::{
+        state_key::{inner::StateKeyInner, StateKey},
+        state_value::StateValue,
+        ShardedStateUpdates, TStateView,
+    },
+    transaction::{
+        authenticator::AuthenticationKey, TransactionOutputListWithProof, TransactionToCommit,
+        Version,
+    },
+    write_set::WriteSet,
+};
+use dashmap::DashMap;
+use move_core_types::move_resource::MoveStructType;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+/// A DB layered on top of the state of another network at a pinned version: state keys that were
+/// never written locally are read from the forked network, and cached in memory.
+pub struct ForkedDb {
+    local: DbReaderWriter,
+    remote: DebuggerStateView,
+    impersonation_authentication_key: Option<AuthenticationKey>,
+    /// The first local version each state key was written at. From that version on, the local DB
+    /// is authoritative for the key, including when the key was deleted.
+    local_write_versions: DashMap<StateKey, Version>,
+    // Serves the remote reads of `remote`
+    _runtime: Runtime,
+}
+
+impl ForkedDb {
+    pub fn new(local: DbReaderWriter, config: &ForkConfig) -> Result<Self> {
+        Self::new_with_remote(
+            local,
+            Arc::new(RestDebuggerInterface::new(Client::new(config.url.clone()))),
+            config,
+        )
+    }
+
+    fn new_with_remote(
+        local: DbReaderWriter,
+        remote: Arc<dyn AptosValidatorInterface + Send>,
+        config: &ForkConfig,
+    ) -> Result<Self> {
+        let runtime = aptos_runtimes::spawn_named_runtime("fork".into(), None);
+        let remote = {
+            let _guard = runtime.enter();
+            // The debugger reads the state before the transaction at a version
+            DebuggerStateView::new(remote, config.version + 1)
+        };
+        let db = Self {
+            local,
+            remote,
+            impersonation_authentication_key: config.impersonation_authentication_key,
+            local_write_versions: DashMap::new(),
+            _runtime: runtime,
+        };
+
+        // Local writes are only tracked in memory, so recover them from the ledger.
+        let num_versions = db.local.reader.get_synced_version()? + 1;
+        let mut start_version = 0;
+        while start_version < num_versions {
+            let limit = MAX_REQUEST_LIMIT.min(num_versions - start_version);
+            let write_sets = db
+                .local
+                .reader
+                .get_write_set_iterator(start_version, limit)?;
+            for (version, write_set) in (start_version..).zip(write_sets) {
+                db.record_local_writes(version, &write_set?);
+            }
+            start_version += limit;
+        }
+
+        Ok(db)
+    }
+
+    pub fn wrap(local: DbReaderWriter, config: &ForkConfig) -> Result<DbReaderWriter> {
+        Ok(DbReaderWriter::new(Self::new(local, config)?))
+    }
+
+    fn record_local_writes(&self, version: Version, write_set: &WriteSet) {
+        for (state_key, _) in write_set.iter() {
+            self.local_write_versions
+                .entry(state_key.clone())
+                .or_insert(version);
+        }
+    }
+
+    fn is_forked(&self, state_key: &StateKey, version: Version) -> bool {
+        self.local_write_versions
+            .get(state_key)
+            .map_or(true, |first_local_version| version < *first_local_version)
+    }
+
+    fn get_remote_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        let value_opt = self.remote.get_state_value(state_key)?;
+        match (value_opt, &self.impersonation_authentication_key) {
+            (Some(value), Some(authentication_key)) if is_account_resource(state_key) => {
+                let value = value.map_bytes(|bytes| {
+                    let mut account = bcs::from_bytes::<AccountResource>(&bytes)?;
+                    account.set_authentication_key(authentication_key.to_vec());
+                    Ok(bcs::to_bytes(&account)?.into())
+                })?;
+                Ok(Some(value))
+            },
+            (value_opt, _) => Ok(value_opt),
+        }
+    }
+}
+
+fn is_account_resource(state_key: &StateKey) -> bool {
+    matches!(
+        state_key.inner(),
+        StateKeyInner::AccessPath(access_path)
+            if access_path.get_path() == Path::Resource(AccountResource::struct_tag())
+    )
+}
+
+impl DbReader for ForkedDb {
+    fn get_read_delegatee(&self) -> &dyn DbReader {
+        self.local.reader.as_ref()
+    }
+
+    fn get_state_value_by_version(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+    ) -> Result<Option<StateValue>> {
+        match self
+            .local
+            .reader
+            .get_state_value_by_version(state_key, version)?
+        {
+            Some(value) => Ok(Some(value)),
+            None if self.is_forked(state_key, version) => self.get_remote_state_value(state_key),
+            None => Ok(None),
+        }
+    }
+
+    fn is_state_key_forked(&self, state_key: &StateKey, version: Version) -> Result<bool> {
+        Ok(self.is_forked(state_key, version))
+    }
+
+    fn get_forked_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        self.get_remote_state_value(state_key)
+    }
+}
+
+impl DbWriter for ForkedDb {
+    fn get_state_snapshot_receiver(
+        &self,
+        version: Version,
+        expected_root_hash: HashValue,
+    ) -> Result<Box<dyn StateSnapshotReceiver<StateKey, StateValue>>> {
+        self.local
+            .writer
+            .get_state_snapshot_receiver(version, expected_root_hash)
+    }
+
+    fn finalize_state_snapshot(
+        &self,
+        version: Version,
+        output_with_proof: TransactionOutputListWithProof,
+        ledger_infos: &[LedgerInfoWithSignatures],
+    ) -> Result<()> {
+        self.local
+            .writer
+            .finalize_state_snapshot(version, output_with_proof, ledger_infos)
+    }
+
+    fn save_transactions(
+        &self,
+        txns_to_commit: &[TransactionToCommit],
+        first_version: Version,
+        base_state_version: Option<Version>,
+        ledger_info_with_sigs: Option<&LedgerInfoWithSignatures>,
+        sync_commit: bool,
+        latest_in_memory_state: StateDelta,
+        state_updates_until_last_checkpoint: Option<ShardedStateUpdates>,
+        sharded_state_cache: Option<&ShardedStateCache>,
+    ) -> Result<()> {
+        for (version, txn_to_commit) in (first_version..).zip(txns_to_commit) {
+            self.record_local_writes(version, txn_to_commit.write_set());
+        }
+        self.local.writer.save_transactions(
+            txns_to_commit,
+            first_version,
+            base_state_version,
+            ledger_info_with_sigs,
+            sync_commit,
+            latest_in_memory_state,
+            state_updates_until_last_checkpoint,
+            sharded_state_cache,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FilterCondition;
+    use aptos_framework::natives::code::PackageMetadata;
+    use aptos_scratchpad::SparseMerkleTree;
+    use aptos_storage_interface::{
+        async_proof_fetcher::AsyncProofFetcher,
+        cached_state_view::{CachedStateView, UncommittedDeletions},
+    };
+    use aptos_types::{
+        account_address::AccountAddress,
+        event::{EventHandle, EventKey},
+        state_store::{create_empty_sharded_state_updates, StateViewId},
+        transaction::{Transaction, TransactionInfo},
+        write_set::{WriteOp, WriteSetMut},
+    };
+    use move_core_types::language_storage::ModuleId;
+    use std::{collections::HashMap, sync::RwLock};
+
+    const FORK_VERSION: Version = 100;
+
+    /// A forked network whose state is only known at the pinned version.
+    struct MockValidatorInterface(HashMap<StateKey, StateValue>);
+
+    #[async_trait::async_trait]
+    impl AptosValidatorInterface for MockValidatorInterface {
+        async fn get_state_value_by_version(
+            &self,
+            state_key: &StateKey,
+            version: Version,
+        ) -> anyhow::Result<Option<StateValue>> {
+            assert_eq!(version, FORK_VERSION);
+            Ok(self.0.get(state_key).cloned())
+        }
+
+        async fn get_committed_transactions(
+            &self,
+            _start: Version,
+            _limit: u64,
+        ) -> anyhow::Result<(Vec<Transaction>, Vec<TransactionInfo>)> {
+            unimplemented!()
+        }
+
+        async fn get_and_filter_committed_transactions(
+            &self,
+            _start: Version,
+            _limit: u64,
+            _filter_condition: FilterCondition,
+            _package_cache: &mut HashMap<
+                ModuleId,
+                (
+                    AccountAddress,
+                    String,
+                    HashMap<(AccountAddress, String), PackageMetadata>,
+                ),
+            >,
+        ) -> anyhow::Result<
+            Vec<(
+                u64,
+                Transaction,
+                Option<(
+                    AccountAddress,
+                    String,
+                    HashMap<(AccountAddress, String), PackageMetadata>,
+                )>,
+            )>,
+        > {
+            unimplemented!()
+        }
+
+        async fn get_latest_ledger_info_version(&self) -> anyhow::Result<Version> {
+            unimplemented!()
+        }
+
+        async fn get_version_by_account_sequence(
+            &self,
+            _account: AccountAddress,
+            _seq: u64,
+        ) -> anyhow::Result<Option<Version>> {
+            unimplemented!()
+        }
+    }
+
+    /// A local DB that only keeps the write set of every version, starting with an empty genesis.
+    struct MockLocalDb(RwLock<Vec<WriteSet>>);
+
+    impl MockLocalDb {
+        fn new() -> Arc<Self> {
+            Arc::new(Self(RwLock::new(vec![WriteSet::default()])))
+        }
+    }
+
+    impl DbReader for MockLocalDb {
+        fn get_synced_version(&self) -> Result<Version> {
+            Ok(self.0.read().unwrap().len() as Version - 1)
+        }
+
+        fn get_write_set_iterator(
+            &self,
+            start_version: Version,
+            limit: u64,
+        ) -> Result<Box<dyn Iterator<Item = Result<WriteSet>> + '_>> {
+            let write_sets = self.0.read().unwrap()
+                [start_version as usize..(start_version + limit) as usize]
+                .to_vec();
+            Ok(Box::new(write_sets.into_iter().map(Ok)))
+        }
+
+        fn get_state_value_by_version(
+            &self,
+            state_key: &StateKey,
+            version: Version,
+        ) -> Result<Option<StateValue>> {
+            Ok(self.0.read().unwrap()[..=version as usize]
+                .iter()
+                .rev()
+                .find_map(|write_set| write_set.get(state_key))
+                .and_then(|write_op| write_op.bytes())
+                .map(|bytes| StateValue::from(bytes.clone())))
+        }
+    }
+
+    impl DbWriter for MockLocalDb {
+        fn save_transactions(
+            &self,
+            txns_to_commit: &[TransactionToCommit],
+            first_version: Version,
+            _base_state_version: Option<Version>,
+            _ledger_info_with_sigs: Option<&LedgerInfoWithSignatures>,
+            _sync_commit: bool,
+            _latest_in_memory_state: StateDelta,
+            _state_updates_until_last_checkpoint: Option<ShardedStateUpdates>,
+            _sharded_state_cache: Option<&ShardedStateCache>,
+        ) -> Result<()> {
+            let mut write_sets = self.0.write().unwrap();
+            assert_eq!(first_version, write_sets.len() as Version);
+            write_sets.extend(
+                txns_to_commit
+                    .iter()
+                    .map(|txn_to_commit| txn_to_commit.write_set().clone()),
+            );
+            Ok(())
+        }
+    }
+
+    fn fork(
+        local: &Arc<MockLocalDb>,
+        remote_state: Vec<(StateKey, StateValue)>,
+        impersonation_authentication_key: Option<AuthenticationKey>,
+    ) -> ForkedDb {
+        let config = ForkConfig {
+            url: "http://localhost:8080".parse().unwrap(),
+            version: FORK_VERSION,
+            impersonation_authentication_key,
+        };
+        ForkedDb::new_with_remote(
+            DbReaderWriter::from_arc(local.clone()),
+            Arc::new(MockValidatorInterface(remote_state.into_iter().collect())),
+            &config,
+        )
+        .unwrap()
+    }
+
+    /// Commits a transaction with the given writes, at the next version.
+    fn commit(db: &ForkedDb, write_ops: Vec<(StateKey, WriteOp)>) -> Version {
+        let version = db.local.reader.get_synced_version().unwrap() + 1;
+        let txn_to_commit = TransactionToCommit {
+            write_set: WriteSetMut::new(write_ops).freeze().unwrap(),
+            ..TransactionToCommit::dummy()
+        };
+        db.save_transactions(
+            &[txn_to_commit],
+            version,
+            None,
+            None,
+            true,
+            StateDelta::new_empty(),
+            None,
+            None,
+        )
+        .unwrap();
+        version
+    }
+
+    fn value(bytes: &[u8]) -> StateValue {
+        StateValue::from(bytes.to_vec())
+    }
+
+    #[test]
+    fn test_remote_read() {
+        let key = StateKey::raw(b"key");
+        let db = fork(
+            &MockLocalDb::new(),
+            vec![(key.clone(), value(b"remote"))],
+            None,
+        );
+
+        assert!(db.is_state_key_forked(&key, 0).unwrap());
+        assert_eq!(
+            db.get_state_value_by_version(&key, 0).unwrap(),
+            Some(value(b"remote"))
+        );
+        assert_eq!(
+            db.get_forked_state_value(&key).unwrap(),
+            Some(value(b"remote"))
+        );
+
+        let missing_key = StateKey::raw(b"missing");
+        assert_eq!(
+            db.get_state_value_by_version(&missing_key, 0).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_local_write_wins() {
+        let key = StateKey::raw(b"key");
+        let db = fork(
+            &MockLocalDb::new(),
+            vec![(key.clone(), value(b"remote"))],
+            None,
+        );
+
+        let version = commit(&db, vec![(
+            key.clone(),
+            WriteOp::legacy_modification(b"local".to_vec().into()),
+        )]);
+
+        assert!(!db.is_state_key_forked(&key, version).unwrap());
+        assert_eq!(
+            db.get_state_value_by_version(&key, version).unwrap(),
+            Some(value(b"local"))
+        );
+        // Versions before the local write still read the forked network
+        assert_eq!(
+            db.get_state_value_by_version(&key, version - 1).unwrap(),
+            Some(value(b"remote"))
+        );
+    }
+
+    #[test]
+    fn test_local_deletion() {
+        let key = StateKey::raw(b"key");
+        let db = fork(
+            &MockLocalDb::new(),
+            vec![(key.clone(), value(b"remote"))],
+            None,
+        );
+
+        let deletion_version = commit(&db, vec![(key.clone(), WriteOp::legacy_deletion())]);
+        let later_version = commit(&db, vec![]);
+
+        for version in [deletion_version, later_version] {
+            assert!(!db.is_state_key_forked(&key, version).unwrap());
+            assert_eq!(db.get_state_value_by_version(&key, version).unwrap(), None);
+        }
+        assert!(db.is_state_key_forked(&key, deletion_version - 1).unwrap());
+        assert_eq!(
+            db.get_state_value_by_version(&key, deletion_version - 1)
+                .unwrap(),
+            Some(value(b"remote"))
+        );
+    }
+
+    #[test]
+    fn test_state_view_deletions() {
+        let key = StateKey::raw(b"key");
+        let db = Arc::new(fork(
+            &MockLocalDb::new(),
+            vec![(key.clone(), value(b"remote"))],
+            None,
+        ));
+        // The view blocks are executed on, with an empty local state.
+        let state_view = |next_version, uncommitted_deletions| {
+            let smt = SparseMerkleTree::new_empty();
+            CachedStateView::new_impl(
+                StateViewId::Miscellaneous,
+                next_version,
+                None,
+                smt.freeze(&smt),
+                uncommitted_deletions,
+                Arc::new(AsyncProofFetcher::new(db.clone())),
+            )
+        };
+        assert_eq!(
+            state_view(1, UncommittedDeletions::default())
+                .get_state_value(&key)
+                .unwrap(),
+            Some(value(b"remote"))
+        );
+
+        // A block deleting the key at version 1 is executed but not committed: the DB doesn't
+        // know about the deletion, yet the blocks on top of it must not read the forked network.
+        let mut updates = create_empty_sharded_state_updates();
+        updates[key.get_shard_id() as usize].insert(key.clone(), None);
+        let uncommitted_deletions = UncommittedDeletions::default().extend(&updates, 1, Some(0));
+        assert!(db.is_state_key_forked(&key, 1).unwrap());
+        assert_eq!(
+            state_view(2, uncommitted_deletions.clone())
+                .get_state_value(&key)
+                .unwrap(),
+            None
+        );
+
+        // Once committed, the deletion is left to the DB.
+        let deletion_version = commit(&db, vec![(key.clone(), WriteOp::legacy_deletion())]);
+        assert_eq!(deletion_version, 1);
+        let uncommitted_deletions = uncommitted_deletions.extend(
+            &create_empty_sharded_state_updates(),
+            2,
+            Some(deletion_version),
+        );
+        assert!(!uncommitted_deletions.contains(&key));
+        assert_eq!(
+            state_view(3, uncommitted_deletions)
+                .get_state_value(&key)
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_impersonation() {
+        let address = AccountAddress::random();
+        let key = StateKey::resource_typed::<AccountResource>(&address).unwrap();
+        let account = AccountResource::new(
+            7,
+            vec![1; 32],
+            EventHandle::new(EventKey::new(0, address), 0),
+            EventHandle::new(EventKey::new(1, address), 0),
+        );
+        let other_key = StateKey::raw(b"key");
+        let authentication_key = AuthenticationKey::new([2; 32]);
+        let db = fork(
+            &MockLocalDb::new(),
+            vec![
+                (key.clone(), value(&bcs::to_bytes(&account).unwrap())),
+                (other_key.clone(), value(b"remote")),
+            ],
+            Some(authentication_key),
+        );
+
+        let forked_account: AccountResource = bcs::from_bytes(
+            db.get_state_value_by_version(&key, 0)
+                .unwrap()
+                .unwrap()
+                .bytes(),
+        )
+        .unwrap();
+        assert_eq!(
+            forked_account.authentication_key(),
+            authentication_key.to_vec()
+        );
+        assert_eq!(forked_account.sequence_number(), 7);
+
+        // Other state is read as is
+        assert_eq!(
+            db.get_state_value_by_version(&other_key, 0).unwrap(),
+            Some(value(b"remote"))
+        );
+    }
+
+    #[test]
+    fn test_recover_local_writes() {
+        let local = MockLocalDb::new();
+        let key = StateKey::raw(b"key");
+        let remote_state = vec![(key.clone(), value(b"remote"))];
+        let deletion_version = {
+            let db = fork(&local, remote_state.clone(), None);
+            commit(&db, vec![(key.clone(), WriteOp::legacy_deletion())])
+        };
+
+        // The deletion stays authoritative after a restart.
+        let db = fork(&local, remote_state, None);
+        assert!(!db.is_state_key_forked(&key, deletion_version).unwrap());
+        assert_eq!(
+            db.get_state_value_by_version(&key, deletion_version)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            db.get_state_value_by_version(&key, deletion_version - 1)
+                .unwrap(),
+            Some(value(b"remote"))
+        );
+    }
+}
@@ -2,10 +2,14 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+mod forked_db;
 mod rest_interface;
 mod storage_interface;
 
-pub use crate::{rest_interface::RestDebuggerInterface, storage_interface::DBDebuggerInterface};
+pub use crate::{
+    forked_db::ForkedDb, rest_interface::RestDebuggerInterface,
+    storage_interface::DBDebuggerInterface,
+};
 use anyhow::Result;
 use aptos_framework::natives::code::PackageMetadata;
 use aptos_types::{
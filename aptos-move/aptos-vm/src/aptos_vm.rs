@@ -263,6 +263,31 @@ impl AptosVM {
     pub fn new(
         resolver: &impl AptosMoveResolver,
         override_is_delayed_field_optimization_capable: Option<bool>,
+    ) -> Self {
+        Self::new_impl(
+            resolver,
+            override_is_delayed_field_optimization_capable,
+            true,
+        )
+    }
+
+    /// Creates a VM with its own code cache instead of the process-wide warm cache, for executions
+    /// against states which may not be the latest one, e.g. when replaying historical transactions.
+    pub fn new_without_warm_vm_cache(
+        resolver: &impl AptosMoveResolver,
+        override_is_delayed_field_optimization_capable: Option<bool>,
+    ) -> Self {
+        Self::new_impl(
+            resolver,
+            override_is_delayed_field_optimization_capable,
+            false,
+        )
+    }
+
+    fn new_impl(
+        resolver: &impl AptosMoveResolver,
+        override_is_delayed_field_optimization_capable: Option<bool>,
+        use_warm_vm_cache: bool,
     ) -> Self {
         let _timer = TIMER.timer_with(&["AptosVM::new"]);
         let features = Features::fetch_config(resolver).unwrap_or_default();
@@ -291,15 +316,27 @@ impl AptosVM {
         let aggregator_v2_type_tagging = override_is_delayed_field_optimization_capable
             && features.is_aggregator_v2_delayed_fields_enabled();
 
-        let move_vm = MoveVmExt::new(
-            gas_feature_version,
-            gas_params.as_ref(),
-            chain_id.id(),
-            features,
-            timed_features.clone(),
-            resolver,
-            aggregator_v2_type_tagging,
-        )
+        let move_vm = if use_warm_vm_cache {
+            MoveVmExt::new(
+                gas_feature_version,
+                gas_params.as_ref(),
+                chain_id.id(),
+                features,
+                timed_features.clone(),
+                resolver,
+                aggregator_v2_type_tagging,
+            )
+        } else {
+            MoveVmExt::new_without_warm_vm_cache(
+                gas_feature_version,
+                gas_params.as_ref(),
+                chain_id.id(),
+                features,
+                timed_features.clone(),
+                resolver,
+                aggregator_v2_type_tagging,
+            )
+        }
         .expect("should be able to create Move VM; check if there are duplicated natives");
 
         // We use an `Option` to handle the VK not being set on-chain, or an incorrect VK being set
@@ -2780,10 +2817,83 @@ impl AptosSimulationVM {
 
         let resolver = state_view.as_move_resolver();
         let vm = Self::new(&resolver);
+        vm.simulate_signed_transaction(transaction, state_view)
+    }
+
+    /// Simulates a signed transaction on this VM.
+    /// *Precondition:* the transaction must **not** have a valid signature.
+    pub fn simulate(
+        &self,
+        transaction: &SignedTransaction,
+        state_view: &impl StateView,
+    ) -> (VMStatus, TransactionOutput) {
+        assert_err!(
+            transaction.verify_signature(),
+            "Simulated transaction should not have a valid signature"
+        );
+        self.simulate_signed_transaction(transaction, state_view)
+    }
+
+    /// Like `create_vm_and_simulate_signed_transaction`, but the VM has its own code cache, so
+    /// that modules which only exist in the given state view (e.g., overridden or published by a
+    /// previous simulated transaction) are never cached for other executions. Allows customizing
+    /// the production gas meter, e.g. to trace the execution.
+    /// *Precondition:* the transaction must **not** have a valid signature.
+    pub fn create_isolated_vm_and_simulate_signed_transaction_with_modified_gas_meter<G, F>(
+        transaction: &SignedTransaction,
+        state_view: &impl StateView,
+        modify_gas_meter: F,
+    ) -> Result<(VMStatus, TransactionOutput, G), VMStatus>
+    where
+        F: FnOnce(ProdGasMeter) -> G,
+        G: AptosGasMeter,
+    {
+        assert_err!(
+            transaction.verify_signature(),
+            "Simulated transaction should not have a valid signature"
+        );
+
+        let resolver = state_view.as_move_resolver();
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+
+        let (vm_status, vm_output, gas_meter) = Self::new_isolated(&resolver)
+            .0
+            .execute_user_transaction_with_modified_gas_meter(
+                &resolver,
+                transaction,
+                &log_context,
+                modify_gas_meter,
+            )?;
+        let txn_output = vm_output
+            .try_materialize_into_transaction_output(&resolver)
+            .expect("Materializing aggregator V1 deltas should never fail");
+        Ok((vm_status, txn_output, gas_meter))
+    }
+
+    /// Creates a VM with its own code cache, to simulate several transactions which build on each
+    /// other, e.g., a bundle where every transaction sees the outputs of the previous ones. A
+    /// module published by one of the transactions invalidates the cache of the VM, so later
+    /// transactions load the new version.
+    pub fn new_isolated(resolver: &impl AptosMoveResolver) -> Self {
+        let mut vm = AptosVM::new_without_warm_vm_cache(
+            resolver,
+            /*override_is_delayed_field_optimization_capable=*/ Some(false),
+        );
+        vm.is_simulation = true;
+        Self(vm)
+    }
+
+    fn simulate_signed_transaction(
+        &self,
+        transaction: &SignedTransaction,
+        state_view: &impl StateView,
+    ) -> (VMStatus, TransactionOutput) {
+        let resolver = state_view.as_move_resolver();
         let log_context = AdapterLogSchema::new(state_view.id(), 0);
 
         let (vm_status, vm_output) =
-            vm.0.execute_user_transaction(&resolver, transaction, &log_context);
+            self.0
+                .execute_user_transaction(&resolver, transaction, &log_context);
         let txn_output = vm_output
             .try_materialize_into_transaction_output(&resolver)
             .expect("Materializing aggregator V1 deltas should never fail");
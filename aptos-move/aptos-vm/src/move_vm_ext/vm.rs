@@ -4,6 +4,7 @@
 use crate::{
     aptos_vm::{aptos_default_ty_builder, aptos_prod_ty_builder},
     move_vm_ext::{warm_vm_cache::WarmVmCache, AptosMoveResolver, SessionExt, SessionId},
+    natives::aptos_natives_with_builder,
 };
 use aptos_framework::natives::{
     aggregator_natives::NativeAggregatorContext,
@@ -45,6 +46,7 @@ impl MoveVmExt {
         gas_hook: Option<F>,
         resolver: &impl AptosMoveResolver,
         aggregator_v2_type_tagging: bool,
+        use_warm_vm_cache: bool,
     ) -> VMResult<Self>
     where
         F: Fn(DynamicExpression) + Send + Sync + 'static,
@@ -92,8 +94,13 @@ impl MoveVmExt {
             paranoid_type_checks,
         );
 
+        let inner = if use_warm_vm_cache {
+            WarmVmCache::get_warm_vm(builder, vm_config, resolver)?
+        } else {
+            MoveVM::new_with_config(aptos_natives_with_builder(&mut builder), vm_config)?
+        };
         Ok(Self {
-            inner: WarmVmCache::get_warm_vm(builder, vm_config, resolver)?,
+            inner,
             chain_id,
             features,
         })
@@ -117,6 +124,31 @@ impl MoveVmExt {
             None,
             resolver,
             aggregator_v2_type_tagging,
+            true,
+        )
+    }
+
+    /// Creates a VM with its own code cache instead of one shared through the warm VM cache, for
+    /// executions on a state whose modules must not leak into other executions.
+    pub fn new_without_warm_vm_cache(
+        gas_feature_version: u64,
+        gas_params: Result<&AptosGasParameters, &String>,
+        chain_id: u8,
+        features: Features,
+        timed_features: TimedFeatures,
+        resolver: &impl AptosMoveResolver,
+        aggregator_v2_type_tagging: bool,
+    ) -> VMResult<Self> {
+        Self::new_impl::<fn(DynamicExpression)>(
+            gas_feature_version,
+            gas_params,
+            chain_id,
+            features,
+            timed_features,
+            None,
+            resolver,
+            aggregator_v2_type_tagging,
+            false,
         )
     }
 
@@ -142,6 +174,7 @@ impl MoveVmExt {
             gas_hook,
             resolver,
             aggregator_v2_type_tagging,
+            true,
         )
     }
 
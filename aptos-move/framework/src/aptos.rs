@@ -120,6 +120,8 @@ impl ReleaseTarget {
                     output_format: None,
                 }),
                 skip_fetch_latest_git_deps: true,
+                locked: false,
+                frozen: false,
                 bytecode_version: None,
                 compiler_version: None,
                 language_version: None,
@@ -0,0 +1,742 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generates typed Rust bindings for a Move package.
+//!
+//! Given the compiled modules of a package, the generator emits:
+//!
+//! * a serde struct for every struct of the package, plus the structs of dependencies which are
+//!   reachable from their fields, so values can be decoded from BCS;
+//! * a `fetch` function for every resource (struct with the `key` ability);
+//! * an async caller over `aptos_sdk::rest_client::Client` for every `#[view]` function;
+//! * a `PackageEvent` enum which decodes the events of the package keyed by their struct tag.
+//!
+//! The generated code depends on the `aptos-sdk`, `anyhow` and `serde` crates.
+
+use crate::get_metadata_from_compiled_module;
+use anyhow::{bail, Context};
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{
+        Ability, SignatureToken, StructDefinition, StructFieldInformation, StructHandleIndex,
+    },
+    CompiledModule,
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    identifier::{IdentStr, Identifier},
+    language_storage::{ModuleId, CORE_CODE_ADDRESS},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fmt::Write,
+};
+
+const ADDRESS: &str = "::aptos_sdk::move_types::account_address::AccountAddress";
+const CLIENT: &str = "::aptos_sdk::rest_client::Client";
+const TYPE_TAG: &str = "::aptos_sdk::move_types::language_storage::TypeTag";
+const STRUCT_TAG: &str = "::aptos_sdk::move_types::language_storage::StructTag";
+const MODULE_ID: &str = "::aptos_sdk::move_types::language_storage::ModuleId";
+const IDENT_STR: &str = "::aptos_sdk::move_types::ident_str!";
+const DERIVES: &str =
+    "#[derive(Clone, Debug, PartialEq, ::serde::Serialize, ::serde::Deserialize)]";
+
+/// Generates Rust bindings for the `package` modules.
+///
+/// `dependencies` must contain every module defining a struct which is reachable from the
+/// fields of the package structs, or from the signatures of its view functions.
+pub fn generate_rust_bindings(
+    package: &[CompiledModule],
+    dependencies: &[CompiledModule],
+) -> anyhow::Result<String> {
+    Generator::new(package, dependencies).generate()
+}
+
+/// Uniquely identifies a struct across modules.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct StructId {
+    module: ModuleId,
+    name: Identifier,
+}
+
+impl StructId {
+    fn is(&self, address: AccountAddress, module: &str, name: &str) -> bool {
+        self.module.address() == &address
+            && self.module.name().as_str() == module
+            && self.name.as_str() == name
+    }
+}
+
+struct Generator<'a> {
+    package: Vec<&'a CompiledModule>,
+    modules: BTreeMap<ModuleId, &'a CompiledModule>,
+    rust_names: BTreeMap<ModuleId, String>,
+}
+
+impl<'a> Generator<'a> {
+    fn new(package: &'a [CompiledModule], dependencies: &'a [CompiledModule]) -> Self {
+        let modules: BTreeMap<_, _> = dependencies
+            .iter()
+            .chain(package.iter())
+            .map(|module| (module.self_id(), module))
+            .collect();
+        // Modules are named after the Move module, unless the same name is used at several
+        // addresses, in which case the address is appended.
+        let mut name_counts = BTreeMap::<&IdentStr, usize>::new();
+        for id in modules.keys() {
+            *name_counts.entry(id.name()).or_default() += 1;
+        }
+        let rust_names = modules
+            .keys()
+            .map(|id| {
+                let name = if name_counts[id.name()] > 1 {
+                    format!("{}_{}", id.name(), id.address().short_str_lossless())
+                } else {
+                    id.name().to_string()
+                };
+                (id.clone(), rust_ident(&name))
+            })
+            .collect();
+        Self {
+            package: package.iter().collect(),
+            modules,
+            rust_names,
+        }
+    }
+
+    fn generate(&self) -> anyhow::Result<String> {
+        let package_ids: BTreeSet<_> = self.package.iter().map(|m| m.self_id()).collect();
+
+        // Render the package structs first, then every struct they transitively refer to.
+        let mut queue = VecDeque::new();
+        let mut events = BTreeSet::new();
+        for module in &self.package {
+            let metadata = get_metadata_from_compiled_module(module);
+            for def in module.struct_defs() {
+                let id = self.struct_id(module, def.struct_handle);
+                let is_event = metadata
+                    .as_ref()
+                    .and_then(|m| m.struct_attributes.get(id.name.as_str()))
+                    .map_or(false, |attrs| attrs.iter().any(|attr| attr.is_event()));
+                if is_event {
+                    events.insert(id.clone());
+                }
+                queue.push_back(id);
+            }
+        }
+        let mut views = BTreeMap::new();
+        for module in &self.package {
+            let mut referenced = vec![];
+            let code = self.render_view_functions(module, &mut referenced)?;
+            views.insert(module.self_id(), code);
+            queue.extend(referenced);
+        }
+        let mut structs = BTreeMap::<ModuleId, BTreeMap<usize, String>>::new();
+        let mut visited = BTreeSet::new();
+        while let Some(id) = queue.pop_front() {
+            if !visited.insert(id.clone()) {
+                continue;
+            }
+            let mut referenced = vec![];
+            let is_package = package_ids.contains(&id.module);
+            let (index, code) =
+                self.render_struct(&id, is_package, &mut referenced, &mut events)?;
+            structs
+                .entry(id.module.clone())
+                .or_default()
+                .insert(index, code);
+            queue.extend(referenced);
+        }
+
+        let mut out = String::new();
+        writeln!(
+            out,
+            "// Generated by `aptos move generate-bindings`. Do not edit by hand.\n"
+        )?;
+        writeln!(
+            out,
+            "#![allow(dead_code, non_camel_case_types, unused_variables, clippy::all)]\n"
+        )?;
+        self.render_view_helper(&mut out)?;
+        for id in self.modules.keys() {
+            let is_package = package_ids.contains(id);
+            if !is_package && !structs.contains_key(id) {
+                continue;
+            }
+            writeln!(
+                out,
+                "\n/// Bindings for the Move module `{}`.",
+                id.short_str_lossless()
+            )?;
+            writeln!(out, "pub mod {} {{", self.rust_names[id])?;
+            writeln!(
+                out,
+                "    pub const ADDRESS: {} = {}::new({:?});",
+                ADDRESS,
+                ADDRESS,
+                id.address().into_bytes()
+            )?;
+            writeln!(
+                out,
+                "    pub const NAME: &str = {:?};\n",
+                id.name().as_str()
+            )?;
+            writeln!(out, "    pub fn module_id() -> {} {{", MODULE_ID)?;
+            writeln!(
+                out,
+                "        {}::new(ADDRESS, {}({:?}).to_owned())",
+                MODULE_ID,
+                IDENT_STR,
+                id.name().as_str()
+            )?;
+            writeln!(out, "    }}")?;
+            for code in structs.get(id).into_iter().flat_map(|s| s.values()) {
+                writeln!(out)?;
+                out.push_str(code);
+            }
+            if let Some(code) = views.get(id) {
+                out.push_str(code);
+            }
+            writeln!(out, "}}")?;
+        }
+        self.render_events(&events, &mut out)?;
+        Ok(out)
+    }
+
+    fn struct_id(&self, module: &CompiledModule, idx: StructHandleIndex) -> StructId {
+        let handle = module.struct_handle_at(idx);
+        StructId {
+            module: module.module_id_for_handle(module.module_handle_at(handle.module)),
+            name: module.identifier_at(handle.name).to_owned(),
+        }
+    }
+
+    fn find_struct(&self, id: &StructId) -> anyhow::Result<(&'a CompiledModule, usize)> {
+        let module = self.modules.get(&id.module).with_context(|| {
+            format!(
+                "module `{}` is not available, it must be passed as a dependency",
+                id.module
+            )
+        })?;
+        let index = module
+            .struct_defs()
+            .iter()
+            .position(|def| {
+                module.identifier_at(module.struct_handle_at(def.struct_handle).name)
+                    == id.name.as_ident_str()
+            })
+            .with_context(|| format!("struct `{}::{}` not found", id.module, id.name))?;
+        Ok((module, index))
+    }
+
+    /// Renders the Rust struct for `id`, returning its definition index in the declaring module.
+    /// Event handles are only collected for structs of the package.
+    fn render_struct(
+        &self,
+        id: &StructId,
+        is_package: bool,
+        referenced: &mut Vec<StructId>,
+        events: &mut BTreeSet<StructId>,
+    ) -> anyhow::Result<(usize, String)> {
+        let (module, index) = self.find_struct(id)?;
+        let def: &StructDefinition = &module.struct_defs()[index];
+        let handle = module.struct_handle_at(def.struct_handle);
+        let fields = match &def.field_information {
+            StructFieldInformation::Declared(fields) => fields,
+            StructFieldInformation::Native => {
+                bail!(
+                    "native struct `{}::{}` is not supported",
+                    id.module,
+                    id.name
+                )
+            },
+        };
+        let is_generic = !handle.type_parameters.is_empty();
+        let generics = rust_generics(
+            handle
+                .type_parameters
+                .iter()
+                .enumerate()
+                .filter(|(_, param)| !param.is_phantom)
+                .map(|(i, _)| i as u16),
+            "",
+        );
+        let type_args = if is_generic {
+            format!("type_args: ::std::vec::Vec<{}>", TYPE_TAG)
+        } else {
+            String::new()
+        };
+
+        let mut out = String::new();
+        let kind = if handle.abilities.has_ability(Ability::Key) {
+            "resource"
+        } else if events.contains(id) {
+            "event"
+        } else {
+            "struct"
+        };
+        writeln!(
+            out,
+            "    /// Move {} `{}::{}`.",
+            kind,
+            id.module.short_str_lossless(),
+            id.name
+        )?;
+        writeln!(out, "    {}", DERIVES)?;
+        writeln!(out, "    pub struct {}{} {{", id.name, generics)?;
+        for field in fields {
+            let ty =
+                self.rust_type(module, &field.signature.0, &mut BTreeSet::new(), referenced)?;
+            if is_package {
+                self.collect_event_handles(module, &field.signature.0, referenced, events);
+            }
+            writeln!(
+                out,
+                "        pub {}: {},",
+                rust_ident(module.identifier_at(field.name).as_str()),
+                ty
+            )?;
+        }
+        writeln!(out, "    }}\n")?;
+
+        writeln!(out, "    impl{} {}{} {{", generics, id.name, generics)?;
+        writeln!(out, "        /// Returns the struct tag of this type.")?;
+        writeln!(
+            out,
+            "        pub fn struct_tag({}) -> {} {{",
+            type_args, STRUCT_TAG
+        )?;
+        writeln!(out, "            {} {{", STRUCT_TAG)?;
+        writeln!(out, "                address: ADDRESS,")?;
+        writeln!(
+            out,
+            "                module: {}(NAME).to_owned(),",
+            IDENT_STR
+        )?;
+        writeln!(
+            out,
+            "                name: {}({:?}).to_owned(),",
+            IDENT_STR,
+            id.name.as_str()
+        )?;
+        writeln!(
+            out,
+            "                type_args: {},",
+            if is_generic { "type_args" } else { "vec![]" }
+        )?;
+        writeln!(out, "            }}")?;
+        writeln!(out, "        }}")?;
+        writeln!(out, "    }}")?;
+
+        if handle.abilities.has_ability(Ability::Key) {
+            let bounded = rust_generics(
+                handle
+                    .type_parameters
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, param)| !param.is_phantom)
+                    .map(|(i, _)| i as u16),
+                ": ::serde::de::DeserializeOwned",
+            );
+            let separator = if is_generic { ", " } else { "" };
+            writeln!(out, "\n    impl{} {}{} {{", bounded, id.name, generics)?;
+            writeln!(
+                out,
+                "        /// Fetches the resource stored at `address`, or `None` if there is none."
+            )?;
+            writeln!(
+                out,
+                "        pub async fn fetch(client: &{}, address: {}{}{}) -> ::anyhow::Result<::std::option::Option<Self>> {{",
+                CLIENT, ADDRESS, separator, type_args
+            )?;
+            writeln!(
+                out,
+                "            let resource_type = Self::struct_tag({}).to_canonical_string();",
+                if is_generic { "type_args" } else { "" }
+            )?;
+            writeln!(
+                out,
+                "            match client.get_account_resource_bcs::<Self>(address, &resource_type).await {{"
+            )?;
+            writeln!(
+                out,
+                "                Ok(response) => Ok(Some(response.into_inner())),"
+            )?;
+            writeln!(
+                out,
+                "                Err(::aptos_sdk::rest_client::error::RestError::Api(err)) if matches!(err.error.error_code, ::aptos_sdk::rest_client::aptos_api_types::AptosErrorCode::ResourceNotFound) => Ok(None),"
+            )?;
+            writeln!(out, "                Err(err) => Err(err.into()),")?;
+            writeln!(out, "            }}")?;
+            writeln!(out, "        }}")?;
+            writeln!(out, "    }}")?;
+        }
+        Ok((index, out))
+    }
+
+    /// Legacy events are declared by storing an `0x1::event::EventHandle<T>` in a struct. As the
+    /// type parameter of the handle is phantom, the event struct is referenced explicitly.
+    fn collect_event_handles(
+        &self,
+        module: &CompiledModule,
+        token: &SignatureToken,
+        referenced: &mut Vec<StructId>,
+        events: &mut BTreeSet<StructId>,
+    ) {
+        if let SignatureToken::StructInstantiation(idx, args) = token {
+            if self
+                .struct_id(module, *idx)
+                .is(CORE_CODE_ADDRESS, "event", "EventHandle")
+            {
+                match args.first() {
+                    Some(SignatureToken::Struct(event))
+                    | Some(SignatureToken::StructInstantiation(event, _)) => {
+                        let id = self.struct_id(module, *event);
+                        referenced.push(id.clone());
+                        events.insert(id);
+                    },
+                    _ => {},
+                }
+            }
+        }
+    }
+
+    /// Maps a Move type to its Rust counterpart, recording the type parameters and the structs
+    /// it refers to.
+    fn rust_type(
+        &self,
+        module: &CompiledModule,
+        token: &SignatureToken,
+        type_params: &mut BTreeSet<u16>,
+        referenced: &mut Vec<StructId>,
+    ) -> anyhow::Result<String> {
+        use SignatureToken::*;
+        Ok(match token {
+            Bool => "bool".to_string(),
+            U8 => "u8".to_string(),
+            U16 => "u16".to_string(),
+            U32 => "u32".to_string(),
+            U64 => "u64".to_string(),
+            U128 => "u128".to_string(),
+            U256 => "::aptos_sdk::move_types::u256::U256".to_string(),
+            Address => ADDRESS.to_string(),
+            Vector(inner) => format!(
+                "::std::vec::Vec<{}>",
+                self.rust_type(module, inner, type_params, referenced)?
+            ),
+            TypeParameter(idx) => {
+                type_params.insert(*idx);
+                format!("T{}", idx)
+            },
+            Struct(idx) => self.rust_struct_type(module, *idx, &[], type_params, referenced)?,
+            StructInstantiation(idx, args) => {
+                self.rust_struct_type(module, *idx, args, type_params, referenced)?
+            },
+            Signer | Reference(_) | MutableReference(_) => {
+                bail!("type `{:?}` cannot be represented in bindings", token)
+            },
+        })
+    }
+
+    fn rust_struct_type(
+        &self,
+        module: &CompiledModule,
+        idx: StructHandleIndex,
+        args: &[SignatureToken],
+        type_params: &mut BTreeSet<u16>,
+        referenced: &mut Vec<StructId>,
+    ) -> anyhow::Result<String> {
+        let id = self.struct_id(module, idx);
+        if id.is(CORE_CODE_ADDRESS, "string", "String") {
+            return Ok("::std::string::String".to_string());
+        }
+        // A Move option is a vector of at most one element, which has the same BCS encoding as
+        // a Rust option.
+        if id.is(CORE_CODE_ADDRESS, "option", "Option") {
+            return Ok(format!(
+                "::std::option::Option<{}>",
+                self.rust_type(module, &args[0], type_params, referenced)?
+            ));
+        }
+        let rust_module = self.rust_names.get(&id.module).with_context(|| {
+            format!(
+                "module `{}` is not available, it must be passed as a dependency",
+                id.module
+            )
+        })?;
+        // Phantom type parameters do not contribute to the layout and are left out.
+        let handle = module.struct_handle_at(idx);
+        let mut rust_args = vec![];
+        for (arg, param) in args.iter().zip(handle.type_parameters.iter()) {
+            if !param.is_phantom {
+                rust_args.push(self.rust_type(module, arg, type_params, referenced)?);
+            }
+        }
+        let ty = if rust_args.is_empty() {
+            format!("super::{}::{}", rust_module, id.name)
+        } else {
+            format!(
+                "super::{}::{}<{}>",
+                rust_module,
+                id.name,
+                rust_args.join(", ")
+            )
+        };
+        referenced.push(id);
+        Ok(ty)
+    }
+
+    fn render_view_helper(&self, out: &mut String) -> anyhow::Result<()> {
+        writeln!(
+            out,
+            "/// View functions return the number of values, followed by the BCS encoded values."
+        )?;
+        writeln!(
+            out,
+            "/// Generated callers return less than 128 values, so the count fits in one byte."
+        )?;
+        writeln!(
+            out,
+            "async fn call_view<T: ::serde::de::DeserializeOwned>(client: &{}, request: &::aptos_sdk::rest_client::aptos_api_types::ViewFunction) -> ::anyhow::Result<T> {{",
+            CLIENT
+        )?;
+        writeln!(
+            out,
+            "    let (_, values): (u8, T) = client.view_bcs(request, None).await?.into_inner();"
+        )?;
+        writeln!(out, "    Ok(values)")?;
+        writeln!(out, "}}")?;
+        Ok(())
+    }
+
+    fn render_view_functions(
+        &self,
+        module: &CompiledModule,
+        referenced: &mut Vec<StructId>,
+    ) -> anyhow::Result<String> {
+        let mut out = String::new();
+        let metadata = match get_metadata_from_compiled_module(module) {
+            Some(metadata) => metadata,
+            None => return Ok(out),
+        };
+        for def in module.function_defs() {
+            let handle = module.function_handle_at(def.function);
+            let name = module.identifier_at(handle.name);
+            let is_view = metadata
+                .fun_attributes
+                .get(name.as_str())
+                .map_or(false, |attrs| {
+                    attrs.iter().any(|attr| attr.is_view_function())
+                });
+            if !is_view {
+                continue;
+            }
+
+            let mut type_params = BTreeSet::new();
+            let mut params = vec![];
+            for (i, token) in module.signature_at(handle.parameters).0.iter().enumerate() {
+                let ty = self.rust_type(module, token, &mut type_params, referenced)?;
+                params.push((format!("arg{}", i), ty));
+            }
+            let mut returns = vec![];
+            for token in &module.signature_at(handle.return_).0 {
+                returns.push(self.rust_type(module, token, &mut type_params, referenced)?);
+            }
+            let return_type = match returns.len() {
+                1 => returns.remove(0),
+                _ => format!("({})", returns.join(", ")),
+            };
+            let generics = rust_generics(
+                type_params.into_iter(),
+                ": ::serde::Serialize + ::serde::de::DeserializeOwned",
+            );
+
+            let mut signature = format!("client: &{}", CLIENT);
+            if !handle.type_parameters.is_empty() {
+                write!(signature, ", type_args: ::std::vec::Vec<{}>", TYPE_TAG)?;
+            }
+            for (name, ty) in &params {
+                write!(signature, ", {}: {}", name, ty)?;
+            }
+            writeln!(
+                out,
+                "\n    /// Calls the view function `{}::{}`.",
+                module.self_id().short_str_lossless(),
+                name
+            )?;
+            writeln!(
+                out,
+                "    pub async fn {}{}({}) -> ::anyhow::Result<{}> {{",
+                rust_ident(name.as_str()),
+                generics,
+                signature,
+                return_type
+            )?;
+            writeln!(
+                out,
+                "        let request = ::aptos_sdk::rest_client::aptos_api_types::ViewFunction {{"
+            )?;
+            writeln!(out, "            module: module_id(),")?;
+            writeln!(
+                out,
+                "            function: {}({:?}).to_owned(),",
+                IDENT_STR,
+                name.as_str()
+            )?;
+            writeln!(
+                out,
+                "            ty_args: {},",
+                if handle.type_parameters.is_empty() {
+                    "vec![]"
+                } else {
+                    "type_args"
+                }
+            )?;
+            let args = params
+                .iter()
+                .map(|(name, _)| format!("::aptos_sdk::bcs::to_bytes(&{})?", name))
+                .collect::<Vec<_>>();
+            writeln!(out, "            args: vec![{}],", args.join(", "))?;
+            writeln!(out, "        }};")?;
+            writeln!(out, "        super::call_view(client, &request).await")?;
+            writeln!(out, "    }}")?;
+        }
+        Ok(out)
+    }
+
+    fn render_events(&self, events: &BTreeSet<StructId>, out: &mut String) -> anyhow::Result<()> {
+        // Events with non-phantom type parameters cannot be decoded without knowing the
+        // instantiation, so they are left out.
+        let mut variants = vec![];
+        for id in events {
+            let (module, index) = self.find_struct(id)?;
+            let handle = module.struct_handle_at(module.struct_defs()[index].struct_handle);
+            if handle.type_parameters.iter().any(|param| !param.is_phantom) {
+                continue;
+            }
+            let rust_module = &self.rust_names[&id.module];
+            variants.push((
+                format!("{}{}", upper_camel_case(rust_module), id.name),
+                format!("{}::{}", rust_module, id.name),
+                id,
+            ));
+        }
+
+        writeln!(
+            out,
+            "\n/// Events of the package, decoded according to their struct tag."
+        )?;
+        writeln!(out, "#[derive(Clone, Debug, PartialEq)]")?;
+        writeln!(out, "pub enum PackageEvent {{")?;
+        for (variant, ty, _) in &variants {
+            writeln!(out, "    {}({}),", variant, ty)?;
+        }
+        writeln!(out, "}}\n")?;
+        writeln!(out, "impl PackageEvent {{")?;
+        writeln!(
+            out,
+            "    /// Decodes the BCS encoded `data` of an event of type `type_tag`, returning `None` if"
+        )?;
+        writeln!(out, "    /// the event is not one of the package.")?;
+        writeln!(
+            out,
+            "    pub fn decode(type_tag: &{}, data: &[u8]) -> ::anyhow::Result<::std::option::Option<Self>> {{",
+            TYPE_TAG
+        )?;
+        writeln!(
+            out,
+            "        let tag = match type_tag {{\n            {}::Struct(tag) => tag,\n            _ => return Ok(None),\n        }};",
+            TYPE_TAG
+        )?;
+        for (variant, ty, id) in &variants {
+            writeln!(
+                out,
+                "        if tag.address == {}::ADDRESS && tag.module.as_str() == {}::NAME && tag.name.as_str() == {:?} {{",
+                self.rust_names[&id.module],
+                self.rust_names[&id.module],
+                id.name.as_str()
+            )?;
+            writeln!(
+                out,
+                "            return Ok(Some(Self::{}(::aptos_sdk::bcs::from_bytes::<{}>(data)?)));",
+                variant, ty
+            )?;
+            writeln!(out, "        }}")?;
+        }
+        writeln!(out, "        Ok(None)")?;
+        writeln!(out, "    }}\n")?;
+        writeln!(out, "    /// Decodes a contract event emitted on chain.")?;
+        writeln!(
+            out,
+            "    pub fn from_contract_event(event: &::aptos_sdk::types::contract_event::ContractEvent) -> ::anyhow::Result<::std::option::Option<Self>> {{"
+        )?;
+        writeln!(
+            out,
+            "        Self::decode(event.type_tag(), event.event_data())"
+        )?;
+        writeln!(out, "    }}")?;
+        writeln!(out, "}}")?;
+        Ok(())
+    }
+}
+
+/// Renders `<T0, T2>` style generics, with each parameter followed by `bounds`.
+fn rust_generics(params: impl Iterator<Item = u16>, bounds: &str) -> String {
+    let params = params
+        .map(|idx| format!("T{}{}", idx, bounds))
+        .collect::<Vec<_>>();
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", params.join(", "))
+    }
+}
+
+/// Escapes Move identifiers which are reserved in Rust.
+fn rust_ident(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do",
+        "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let",
+        "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+        "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+        "virtual", "where", "while", "yield",
+    ];
+    match name {
+        // These cannot be used as raw identifiers.
+        "self" | "Self" | "super" | "crate" => format!("{}_", name),
+        _ if KEYWORDS.contains(&name) => format!("r#{}", name),
+        _ => name.to_string(),
+    }
+}
+
+fn upper_camel_case(name: &str) -> String {
+    name.trim_start_matches("r#")
+        .split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rust_ident() {
+        assert_eq!(rust_ident("coin"), "coin");
+        assert_eq!(rust_ident("type"), "r#type");
+        assert_eq!(rust_ident("self"), "self_");
+    }
+
+    #[test]
+    fn test_upper_camel_case() {
+        assert_eq!(upper_camel_case("fungible_asset"), "FungibleAsset");
+        assert_eq!(upper_camel_case("r#type"), "Type");
+        assert_eq!(upper_camel_case("coin_0x1"), "Coin0x1");
+    }
+}
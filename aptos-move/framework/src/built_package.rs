@@ -83,6 +83,12 @@ pub struct BuildOptions {
     pub docgen_options: Option<DocgenOptions>,
     #[clap(long)]
     pub skip_fetch_latest_git_deps: bool,
+    /// Fail instead of updating the Move.lock file if it is missing or out of date.
+    #[clap(long)]
+    pub locked: bool,
+    /// Like `--locked`, and additionally never fetch git dependencies from the network.
+    #[clap(long)]
+    pub frozen: bool,
     #[clap(long)]
     pub bytecode_version: Option<u32>,
     #[clap(long, value_parser = clap::value_parser!(CompilerVersion))]
@@ -115,6 +121,8 @@ impl Default for BuildOptions {
             // This is false by default, because it could accidentally pull new dependencies
             // while in a test (and cause some havoc)
             skip_fetch_latest_git_deps: false,
+            locked: false,
+            frozen: false,
             bytecode_version: None,
             compiler_version: None,
             language_version: None,
@@ -158,6 +166,9 @@ pub fn build_model(
         force_recompilation: false,
         fetch_deps_only: false,
         skip_fetch_latest_git_deps: true,
+        locked: false,
+        frozen: false,
+        update_lockfile: false,
         compiler_config: CompilerConfig {
             bytecode_version,
             compiler_version,
@@ -202,6 +213,9 @@ impl BuiltPackage {
             force_recompilation: false,
             fetch_deps_only: false,
             skip_fetch_latest_git_deps: options.skip_fetch_latest_git_deps,
+            locked: options.locked,
+            frozen: options.frozen,
+            update_lockfile: false,
             compiler_config: CompilerConfig {
                 bytecode_version,
                 compiler_version,
@@ -15,6 +15,7 @@ pub use built_package::*;
 mod module_metadata;
 pub use module_metadata::*;
 
+pub mod bindings;
 pub mod natives;
 mod release_builder;
 pub use release_builder::*;
@@ -11,7 +11,7 @@ use move_core_types::account_address::AccountAddress;
 use move_model::metadata::{CompilerVersion, LanguageVersion};
 use std::{
     collections::{BTreeMap, BTreeSet},
-    path::Path,
+    path::{Path, PathBuf},
     time::Instant,
 };
 use tempfile::TempDir;
@@ -84,6 +84,12 @@ pub struct ProverOptions {
     #[clap(long)]
     pub dump: bool,
 
+    /// Directory of the verification result cache. Verification conditions which verified
+    /// before and whose code, specs and dependencies did not change are not verified again.
+    /// The cache can be shared between runs, e.g. in CI.
+    #[clap(long)]
+    pub cache_dir: Option<PathBuf>,
+
     #[clap(skip)]
     pub for_test: bool,
 }
@@ -106,6 +112,7 @@ impl Default for ProverOptions {
             loop_unroll: None,
             stable_test_output: false,
             dump: false,
+            cache_dir: None,
             for_test: false,
         }
     }
@@ -217,6 +224,10 @@ impl ProverOptions {
                 },
                 custom_natives: None,
                 loop_unroll: self.loop_unroll,
+                verification_cache_dir: self
+                    .cache_dir
+                    .as_ref()
+                    .map(|dir| dir.display().to_string()),
                 ..Default::default()
             },
             ..Default::default()
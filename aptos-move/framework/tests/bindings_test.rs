@@ -0,0 +1,66 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_framework::{
+    bindings::generate_rust_bindings, path_in_crate, BuildOptions, BuiltPackage,
+};
+use move_core_types::account_address::AccountAddress;
+use std::collections::BTreeMap;
+use tempfile::tempdir;
+
+/// Set this env var to update the baselines instead of checking against them
+const UPDATE_BASELINE: &str = "UPBL";
+
+/// Builds the `hello_blockchain` example against the local framework, outside of the source
+/// tree so that no build artifacts or lockfile end up in the repository.
+fn build_hello_blockchain() -> BuiltPackage {
+    let package_dir = tempdir().unwrap();
+    let framework_path = path_in_crate("aptos-framework");
+    std::fs::write(
+        package_dir.path().join("Move.toml"),
+        format!(
+            "[package]\nname = \"Examples\"\nversion = \"0.0.0\"\n\n\
+             [addresses]\nhello_blockchain = \"_\"\n\n\
+             [dependencies]\nAptosFramework = {{ local = {:?} }}\n",
+            framework_path.display().to_string()
+        ),
+    )
+    .unwrap();
+    let sources_dir = package_dir.path().join("sources");
+    std::fs::create_dir(&sources_dir).unwrap();
+    std::fs::copy(
+        path_in_crate("../move-examples/hello_blockchain/sources/hello_blockchain.move"),
+        sources_dir.join("hello_blockchain.move"),
+    )
+    .unwrap();
+
+    let options = BuildOptions {
+        install_dir: Some(package_dir.path().join("build")),
+        named_addresses: BTreeMap::from([(
+            "hello_blockchain".to_string(),
+            AccountAddress::from_hex_literal("0xcafe").unwrap(),
+        )]),
+        ..BuildOptions::default()
+    };
+    BuiltPackage::build(package_dir.path().to_path_buf(), options).unwrap()
+}
+
+#[test]
+fn test_hello_blockchain_bindings() {
+    let package = build_hello_blockchain();
+    let modules: Vec<_> = package.modules().cloned().collect();
+    let dependencies: Vec<_> = package.all_modules().cloned().collect();
+    let bindings = generate_rust_bindings(&modules, &dependencies).unwrap();
+
+    let baseline_path = path_in_crate("tests/bindings/hello_blockchain.rs.exp");
+    if std::env::var(UPDATE_BASELINE).is_ok() {
+        std::fs::write(&baseline_path, bindings).unwrap();
+    } else {
+        let baseline = std::fs::read_to_string(&baseline_path).unwrap();
+        assert_eq!(
+            bindings, baseline,
+            "The generated bindings differ from the baseline, run with {}=1 to update it",
+            UPDATE_BASELINE
+        );
+    }
+}
@@ -35,6 +35,25 @@ pub(crate) fn maybe_apply_genesis(
     }
 }
 
+/// Layers the DB on top of the state of another network, if the config asks to fork one.
+/// Genesis is always applied to the local DB alone.
+#[cfg(not(feature = "consensus-only-perf-test"))]
+fn maybe_fork(db_rw: DbReaderWriter, node_config: &NodeConfig) -> Result<DbReaderWriter> {
+    match &node_config.storage.fork {
+        Some(fork_config) => {
+            info!(
+                "Forking the state of {} at version {}",
+                fork_config.url, fork_config.version
+            );
+            Ok(aptos_validator_interface::ForkedDb::wrap(
+                db_rw,
+                fork_config,
+            )?)
+        },
+        None => Ok(db_rw),
+    }
+}
+
 #[cfg(not(feature = "consensus-only-perf-test"))]
 pub(crate) fn bootstrap_db(
     node_config: &NodeConfig,
@@ -48,6 +67,7 @@ pub(crate) fn bootstrap_db(
                     db_arc.clone(),
                 );
                 maybe_apply_genesis(&db_rw, node_config)?;
+                let db_rw = maybe_fork(db_rw, node_config)?;
                 (db_arc as Arc<dyn DbReader>, db_rw, Some(db_backup_service))
             },
             Either::Right(fast_sync_db_wrapper) => {
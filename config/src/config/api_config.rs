@@ -65,6 +65,12 @@ pub struct ApiConfig {
     ///
     /// This limits the execution length of a view function to the given gas used.
     pub max_gas_view_function: u64,
+    /// Maximum number of view functions in a batch view request
+    pub max_view_function_batch_size: usize,
+    /// Maximum gas unit limit for all the view functions of a batch view request
+    ///
+    /// Each view function of the batch is also limited by `max_gas_view_function`.
+    pub max_gas_view_function_batch: u64,
     /// Optional: Maximum number of worker threads for the API.
     ///
     /// If not set, `runtime_worker_multiplier` will multiply times the number of CPU cores on the machine
@@ -99,6 +105,8 @@ pub const DEFAULT_MAX_PAGE_SIZE: u16 = 100;
 const DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE: u16 = 9999;
 const DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE: u16 = 9999;
 const DEFAULT_MAX_VIEW_GAS: u64 = 2_000_000; // We keep this value the same as the max number of gas allowed for one single transaction defined in aptos-gas.
+const DEFAULT_MAX_VIEW_FUNCTION_BATCH_SIZE: usize = 64;
+const DEFAULT_MAX_VIEW_BATCH_GAS: u64 = 10 * DEFAULT_MAX_VIEW_GAS;
 
 fn default_enabled() -> bool {
     true
@@ -131,6 +139,8 @@ impl Default for ApiConfig {
             max_account_resources_page_size: DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE,
             max_account_modules_page_size: DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE,
             max_gas_view_function: DEFAULT_MAX_VIEW_GAS,
+            max_view_function_batch_size: DEFAULT_MAX_VIEW_FUNCTION_BATCH_SIZE,
+            max_gas_view_function_batch: DEFAULT_MAX_VIEW_BATCH_GAS,
             max_runtime_workers: None,
             runtime_worker_multiplier: 2,
             gas_estimation: GasEstimationConfig::default(),
@@ -4,9 +4,10 @@
 use crate::config::{
     node_config_loader::NodeType,
     utils::{are_failpoints_enabled, get_config_name},
-    AdminServiceConfig, ApiConfig, BaseConfig, ConsensusConfig, DagConsensusConfig, Error,
-    ExecutionConfig, IndexerGrpcConfig, InspectionServiceConfig, LoggerConfig, MempoolConfig,
-    NetbenchConfig, NodeConfig, StateSyncConfig, StorageConfig,
+    AdminServiceConfig, ApiConfig, BaseConfig, ConsensusConfig, DagConsensusConfig,
+    DiscoveryMethod, Error, ExecutionConfig, IndexerGrpcConfig, InspectionServiceConfig,
+    LoggerConfig, MempoolConfig, NetbenchConfig, NetworkConfig, NodeConfig, StateSyncConfig,
+    StorageConfig,
 };
 use aptos_types::chain_id::ChainId;
 use std::collections::HashSet;
@@ -146,6 +147,9 @@ fn sanitize_fullnode_network_configs(
                 ),
             ));
         }
+
+        // Verify the discovery methods of the network
+        sanitize_discovery_methods(&sanitizer_name, fullnode_network_config)?;
     }
 
     Ok(())
@@ -193,6 +197,34 @@ fn sanitize_validator_network_config(
                 "Mutual authentication must be enabled for the validator network!".into(),
             ));
         }
+
+        // Verify the discovery methods of the network
+        sanitize_discovery_methods(&sanitizer_name, validator_network_config)?;
+    }
+
+    Ok(())
+}
+
+/// Sanitize the discovery methods of a network config
+fn sanitize_discovery_methods(
+    sanitizer_name: &str,
+    network_config: &NetworkConfig,
+) -> Result<(), Error> {
+    let discovery_methods =
+        std::iter::once(&network_config.discovery_method).chain(&network_config.discovery_methods);
+    for discovery_method in discovery_methods {
+        if let DiscoveryMethod::PeerExchange(peer_exchange) = discovery_method {
+            // Verify that the peer exchange intervals are not zero
+            if peer_exchange.interval_secs == 0 || peer_exchange.min_request_interval_secs == 0 {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name.to_string(),
+                    format!(
+                        "The peer exchange intervals must be positive! interval_secs: {}, min_request_interval_secs: {}",
+                        peer_exchange.interval_secs, peer_exchange.min_request_interval_secs
+                    ),
+                ));
+            }
+        }
     }
 
     Ok(())
@@ -202,7 +234,7 @@ fn sanitize_validator_network_config(
 mod tests {
     use super::*;
     use crate::{
-        config::{node_startup_config::NodeStartupConfig, NetworkConfig},
+        config::{node_startup_config::NodeStartupConfig, PeerExchangeDiscovery},
         network_id::NetworkId,
     };
 
@@ -402,4 +434,53 @@ mod tests {
         .unwrap_err();
         assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
     }
+
+    #[test]
+    fn test_sanitize_peer_exchange_intervals() {
+        for peer_exchange in [
+            PeerExchangeDiscovery {
+                interval_secs: 0,
+                ..Default::default()
+            },
+            PeerExchangeDiscovery {
+                min_request_interval_secs: 0,
+                ..Default::default()
+            },
+        ] {
+            // Create a fullnode config that discovers peers with a zero interval
+            let node_config = NodeConfig {
+                full_node_networks: vec![NetworkConfig {
+                    network_id: NetworkId::Public,
+                    discovery_methods: vec![DiscoveryMethod::PeerExchange(peer_exchange)],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            };
+
+            // Sanitize the config and verify that it fails
+            let error = sanitize_fullnode_network_configs(
+                &node_config,
+                NodeType::PublicFullnode,
+                Some(ChainId::testnet()),
+            )
+            .unwrap_err();
+            assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+        }
+
+        // Verify that the default intervals are accepted
+        let node_config = NodeConfig {
+            full_node_networks: vec![NetworkConfig {
+                network_id: NetworkId::Public,
+                discovery_methods: vec![DiscoveryMethod::PeerExchange(Default::default())],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        sanitize_fullnode_network_configs(
+            &node_config,
+            NodeType::PublicFullnode,
+            Some(ChainId::testnet()),
+        )
+        .unwrap();
+    }
 }
@@ -4,18 +4,33 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct InternalIndexerDBConfig {
     pub enable_transaction: bool,
     pub enable_event: bool,
+    pub enable_account_activity: bool,
+    pub enable_object_ownership: bool,
     pub batch_size: usize,
+    /// Max number of index entries a request for the objects of an owner goes through, as the
+    /// ones not holding the requested resource are skipped.
+    pub max_owned_objects_scanned: usize,
 }
 
 impl InternalIndexerDBConfig {
-    pub fn new(enable_transaction: bool, enable_event: bool, batch_size: usize) -> Self {
+    pub fn new(
+        enable_transaction: bool,
+        enable_event: bool,
+        enable_account_activity: bool,
+        enable_object_ownership: bool,
+        batch_size: usize,
+    ) -> Self {
         Self {
             enable_transaction,
             enable_event,
+            enable_account_activity,
+            enable_object_ownership,
             batch_size,
+            ..Self::default()
         }
     }
 
@@ -27,9 +42,21 @@ impl InternalIndexerDBConfig {
         self.enable_event
     }
 
+    pub fn enable_account_activity(&self) -> bool {
+        self.enable_account_activity
+    }
+
+    pub fn enable_object_ownership(&self) -> bool {
+        self.enable_object_ownership
+    }
+
     pub fn batch_size(&self) -> usize {
         self.batch_size
     }
+
+    pub fn max_owned_objects_scanned(&self) -> usize {
+        self.max_owned_objects_scanned
+    }
 }
 
 impl Default for InternalIndexerDBConfig {
@@ -37,7 +64,10 @@ impl Default for InternalIndexerDBConfig {
         Self {
             enable_transaction: false,
             enable_event: false,
+            enable_account_activity: false,
+            enable_object_ownership: false,
             batch_size: 10_000,
+            max_owned_objects_scanned: 10_000,
         }
     }
 }
@@ -26,6 +26,7 @@ use std::{
     collections::{HashMap, HashSet},
     convert::TryFrom,
     fmt,
+    net::SocketAddr,
     path::PathBuf,
     string::ToString,
 };
@@ -351,6 +352,8 @@ pub enum DiscoveryMethod {
     Onchain,
     File(FileDiscovery),
     Rest(RestDiscovery),
+    PeerExchange(PeerExchangeDiscovery),
+    Dns(DnsDiscovery),
     None,
 }
 
@@ -368,6 +371,72 @@ pub struct RestDiscovery {
     pub interval_secs: u64,
 }
 
+/// Discovery of seed peers through DNS. Each seed domain is periodically
+/// resolved: TXT records carry the peer IDs and x25519 public keys of the
+/// seeds, and A/AAAA records carry their IP addresses.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DnsDiscovery {
+    pub seed_domains: Vec<String>,
+    pub interval_secs: u64,
+    /// The port used for the seeds, unless a TXT record specifies otherwise
+    #[serde(default = "DnsDiscovery::default_port")]
+    pub port: u16,
+    /// The name servers to query. If empty, the system configuration is used.
+    #[serde(default)]
+    pub name_servers: Vec<SocketAddr>,
+}
+
+impl DnsDiscovery {
+    fn default_port() -> u16 {
+        6182
+    }
+}
+
+/// Gossip based discovery, where peers periodically exchange signed address
+/// records of the other peers they know about. Intended for public networks.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PeerExchangeDiscovery {
+    /// Addresses advertised for this node. If empty, the node only relays
+    /// records of other peers and never advertises itself.
+    pub advertised_addresses: Vec<NetworkAddress>,
+    /// Interval at which a connected peer is queried, and at which the
+    /// discovered peers are handed to the connectivity manager
+    pub interval_secs: u64,
+    /// Timeout for a single peer exchange request
+    pub rpc_timeout_ms: u64,
+    /// Maximum number of records sent in (or accepted from) a single response
+    pub max_records_per_response: usize,
+    /// Maximum number of records kept in the local record store
+    pub max_stored_records: usize,
+    /// Maximum number of discovered peers handed to the connectivity manager
+    pub max_discovered_peers: usize,
+    /// Records (including our own) older than this are discarded
+    pub record_ttl_secs: u64,
+    /// Minimum interval between two requests served to the same peer
+    pub min_request_interval_secs: u64,
+    /// File used to persist the record store across restarts. If not set,
+    /// the store is kept in memory only.
+    pub persistence_path: Option<PathBuf>,
+}
+
+impl Default for PeerExchangeDiscovery {
+    fn default() -> Self {
+        Self {
+            advertised_addresses: Vec::new(),
+            interval_secs: 30,
+            rpc_timeout_ms: 5_000,
+            max_records_per_response: 64,
+            max_stored_records: 1_024,
+            max_discovered_peers: 128,
+            record_ttl_secs: 6 * 60 * 60, // 6 hours
+            min_request_interval_secs: 10,
+            persistence_path: None,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct RateLimitConfig {
@@ -532,4 +601,52 @@ mod tests {
         network_config.configure_num_deserialization_tasks();
         assert_eq!(network_config.max_parallel_deserialization_tasks, Some(1));
     }
+
+    #[test]
+    fn test_peer_exchange_discovery_defaults() {
+        // Deserialize a peer exchange discovery method with only a few fields set
+        let discovery_method: DiscoveryMethod = serde_yaml::from_str(
+            r#"
+            peer_exchange:
+                interval_secs: 10
+                persistence_path: /opt/aptos/data/peer_exchange.bcs
+            "#,
+        )
+        .unwrap();
+
+        // Verify the unset fields fall back to the defaults
+        let expected_discovery = PeerExchangeDiscovery {
+            interval_secs: 10,
+            persistence_path: Some(PathBuf::from("/opt/aptos/data/peer_exchange.bcs")),
+            ..PeerExchangeDiscovery::default()
+        };
+        assert_eq!(
+            discovery_method,
+            DiscoveryMethod::PeerExchange(expected_discovery)
+        );
+    }
+
+    #[test]
+    fn test_dns_discovery_defaults() {
+        // Deserialize a DNS discovery method without a port or name servers
+        let discovery_method: DiscoveryMethod = serde_yaml::from_str(
+            r#"
+            dns:
+                seed_domains: [seeds.example.com]
+                interval_secs: 60
+            "#,
+        )
+        .unwrap();
+
+        // Verify the public network port and system name servers are used
+        assert_eq!(
+            discovery_method,
+            DiscoveryMethod::Dns(DnsDiscovery {
+                seed_domains: vec!["seeds.example.com".into()],
+                interval_secs: 60,
+                port: 6182,
+                name_servers: vec![],
+            })
+        );
+    }
 }
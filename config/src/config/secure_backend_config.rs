@@ -3,9 +3,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::config::Error;
-use aptos_secure_storage::{InMemoryStorage, Namespaced, OnDiskStorage, Storage, VaultStorage};
+use aptos_secure_storage::{
+    EncryptedOnDiskStorage, InMemoryStorage, Namespaced, OnDiskStorage, Storage, VaultStorage,
+};
 use serde::{Deserialize, Serialize};
 use std::{
+    env,
     fs::File,
     io::Read,
     path::{Path, PathBuf},
@@ -20,15 +23,18 @@ pub enum SecureBackend {
     InMemoryStorage,
     Vault(VaultConfig),
     OnDiskStorage(OnDiskStorageConfig),
+    EncryptedOnDiskStorage(EncryptedOnDiskStorageConfig),
 }
 
 impl SecureBackend {
     pub fn namespace(&self) -> Option<&str> {
         match self {
             SecureBackend::Vault(VaultConfig { namespace, .. })
-            | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. }) => {
-                namespace.as_deref()
-            },
+            | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. })
+            | SecureBackend::EncryptedOnDiskStorage(EncryptedOnDiskStorageConfig {
+                namespace,
+                ..
+            }) => namespace.as_deref(),
             SecureBackend::InMemoryStorage => None,
         }
     }
@@ -36,7 +42,11 @@ impl SecureBackend {
     pub fn clear_namespace(&mut self) {
         match self {
             SecureBackend::Vault(VaultConfig { namespace, .. })
-            | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. }) => {
+            | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. })
+            | SecureBackend::EncryptedOnDiskStorage(EncryptedOnDiskStorageConfig {
+                namespace,
+                ..
+            }) => {
                 *namespace = None;
             },
             SecureBackend::InMemoryStorage => {},
@@ -97,6 +107,48 @@ pub struct OnDiskStorageConfig {
     data_dir: PathBuf,
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EncryptedOnDiskStorageConfig {
+    // Required path for encrypted on disk storage
+    pub path: PathBuf,
+    /// A namespace is an optional portion of the path to a key stored within
+    /// EncryptedOnDiskStorage, as for OnDiskStorage.
+    pub namespace: Option<String>,
+    /// The passphrase the encryption key is derived from
+    pub passphrase: Passphrase,
+    #[serde(skip)]
+    data_dir: PathBuf,
+}
+
+/// Passphrases are never stored within this config: they are either read from a file or from an
+/// environment variable.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Passphrase {
+    /// This is an absolute path and not relative to data_dir
+    FromDisk(PathBuf),
+    /// The name of the environment variable holding the passphrase
+    FromEnv(String),
+}
+
+impl Passphrase {
+    pub fn read_passphrase(&self) -> Result<String, Error> {
+        match self {
+            // Ignore the trailing newline most editors add to the file
+            Passphrase::FromDisk(path) => {
+                read_file(path).map(|passphrase| passphrase.trim_end_matches(['\n', '\r']).into())
+            },
+            Passphrase::FromEnv(name) => env::var(name).map_err(|e| {
+                Error::Unexpected(format!(
+                    "Unable to read the passphrase from {}: {}",
+                    name, e
+                ))
+            }),
+        }
+    }
+}
+
 /// Tokens can either be directly within this config or stored somewhere on disk.
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -151,6 +203,29 @@ impl OnDiskStorageConfig {
     }
 }
 
+impl EncryptedOnDiskStorageConfig {
+    pub fn new(path: PathBuf, passphrase: Passphrase) -> Self {
+        Self {
+            path,
+            namespace: None,
+            passphrase,
+            data_dir: PathBuf::from("/opt/aptos/data"),
+        }
+    }
+
+    pub fn path(&self) -> PathBuf {
+        if self.path.is_relative() {
+            self.data_dir.join(&self.path)
+        } else {
+            self.path.clone()
+        }
+    }
+
+    pub fn set_data_dir(&mut self, data_dir: PathBuf) {
+        self.data_dir = data_dir;
+    }
+}
+
 fn read_file(path: &Path) -> Result<String, Error> {
     let mut file =
         File::open(path).map_err(|e| Error::IO(path.to_str().unwrap().to_string(), e))?;
@@ -172,6 +247,21 @@ impl From<&SecureBackend> for Storage {
                     storage
                 }
             },
+            SecureBackend::EncryptedOnDiskStorage(config) => {
+                let passphrase = config
+                    .passphrase
+                    .read_passphrase()
+                    .expect("Unable to read passphrase");
+                let storage = Storage::from(
+                    EncryptedOnDiskStorage::new(config.path(), passphrase.as_bytes())
+                        .expect("Unable to open encrypted on disk storage"),
+                );
+                if let Some(namespace) = &config.namespace {
+                    Storage::from(Namespaced::new(namespace, Box::new(storage)))
+                } else {
+                    storage
+                }
+            },
             SecureBackend::Vault(config) => {
                 let storage = Storage::from(VaultStorage::new(
                     config.server.clone(),
@@ -303,4 +393,40 @@ vault:
         let config = Token::FromConfig("config_token".to_string());
         assert_eq!("config_token", config.read_token().unwrap());
     }
+
+    #[test]
+    fn test_encrypted_on_disk_config_parsing() {
+        let text = r#"
+type: "encrypted_on_disk_storage"
+path: "secure_storage.enc"
+passphrase:
+    from_env: "SECURE_STORAGE_PASSPHRASE"
+        "#;
+
+        let backend: SecureBackend = serde_yaml::from_str(text).unwrap();
+        let config = match backend {
+            SecureBackend::EncryptedOnDiskStorage(config) => config,
+            backend => panic!("Unexpected backend: {:?}", backend),
+        };
+        assert_eq!(config.path, PathBuf::from("secure_storage.enc"));
+        assert_eq!(
+            config.passphrase,
+            Passphrase::FromEnv("SECURE_STORAGE_PASSPHRASE".to_string())
+        );
+        assert_eq!(config.namespace, None);
+    }
+
+    #[test]
+    fn test_passphrase_reading() {
+        let temppath = aptos_temppath::TempPath::new();
+        temppath.create_as_file().unwrap();
+        let mut file = File::create(temppath.path()).unwrap();
+        file.write_all(b"disk_passphrase\n").unwrap();
+
+        let disk = Passphrase::FromDisk(temppath.path().to_path_buf());
+        assert_eq!("disk_passphrase", disk.read_passphrase().unwrap());
+
+        let missing = Passphrase::FromEnv("APTOS_TEST_MISSING_PASSPHRASE".to_string());
+        assert!(missing.read_passphrase().is_err());
+    }
 }
@@ -8,7 +8,7 @@ use crate::{
 };
 use anyhow::{ensure, Result};
 use aptos_logger::warn;
-use aptos_types::chain_id::ChainId;
+use aptos_types::{chain_id::ChainId, transaction::authenticator::AuthenticationKey};
 use arr_macro::arr;
 use number_range::NumberRangeOptions;
 use serde::{Deserialize, Serialize};
@@ -17,6 +17,7 @@ use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
     path::{Path, PathBuf},
 };
+use url::Url;
 
 // Lru cache will consume about 2G RAM based on this default value.
 pub const DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD: usize = 1 << 13;
@@ -175,6 +176,23 @@ pub struct StorageConfig {
     /// If not specificed, will use `dir` as default.
     /// Only allowed when sharding is enabled.
     pub db_path_overrides: Option<DbPathConfig>,
+    /// If set, state missing from the local DB is read from another network. Only meant for
+    /// local test networks.
+    pub fork: Option<ForkConfig>,
+}
+
+/// The network a local DB is forked from. State that has never been written locally is lazily
+/// read from the network at the pinned version, and cached in memory.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ForkConfig {
+    /// REST endpoint of a node of the forked network
+    pub url: Url,
+    /// The version of the forked network to read the state at
+    pub version: u64,
+    /// If set, replaces the authentication key of every account read from the forked network,
+    /// so that the holder of the matching private key can send transactions as any account.
+    pub impersonation_authentication_key: Option<AuthenticationKey>,
 }
 
 pub const NO_OP_STORAGE_PRUNER_CONFIG: PrunerConfig = PrunerConfig {
@@ -322,6 +340,7 @@ impl Default for StorageConfig {
             rocksdb_configs: RocksdbConfigs::default(),
             enable_indexer: false,
             db_path_overrides: None,
+            fork: None,
             buffered_state_target_items: BUFFERED_STATE_TARGET_ITEMS,
             max_num_nodes_per_lru_cache_shard: DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
         }
@@ -475,11 +494,18 @@ impl ConfigSanitizer for StorageConfig {
     fn sanitize(
         node_config: &NodeConfig,
         _node_type: NodeType,
-        _chain_id: Option<ChainId>,
+        chain_id: Option<ChainId>,
     ) -> Result<(), Error> {
         let sanitizer_name = Self::get_sanitizer_name();
         let config = &node_config.storage;
 
+        if config.fork.is_some() && chain_id.map_or(false, |chain_id| chain_id != ChainId::test()) {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "fork is only allowed on test networks.".to_string(),
+            ));
+        }
+
         let ledger_prune_window = config
             .storage_pruner_config
             .ledger_pruner_config
@@ -576,7 +602,36 @@ impl ConfigSanitizer for StorageConfig {
 
 #[cfg(test)]
 mod test {
-    use crate::config::{PrunerConfig, ShardPathConfig, ShardedDbPathConfig};
+    use crate::config::{
+        config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, Error, ForkConfig,
+        NodeConfig, PrunerConfig, ShardPathConfig, ShardedDbPathConfig, StorageConfig,
+    };
+    use aptos_types::chain_id::ChainId;
+
+    #[test]
+    pub fn test_sanitize_fork() {
+        // Create a node config that forks another network
+        let node_config = NodeConfig {
+            storage: StorageConfig {
+                fork: Some(ForkConfig {
+                    url: "http://localhost:8080".parse().unwrap(),
+                    version: 100,
+                    impersonation_authentication_key: None,
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Verify that forking is allowed on test networks
+        StorageConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::test())).unwrap();
+
+        // Verify that forking is rejected on other networks
+        let error =
+            StorageConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::mainnet()))
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
 
     #[test]
     pub fn test_default_prune_window() {
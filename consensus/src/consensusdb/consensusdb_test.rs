@@ -4,7 +4,10 @@
 
 use self::schema::dag::NodeSchema;
 use super::*;
-use crate::dag::{CertifiedNode, Extensions, Node, Vote};
+use crate::{
+    dag::{CertifiedNode, Extensions, Node, Vote},
+    evidence::Evidence,
+};
 use aptos_consensus_types::{
     block::block_test_utils::certificate_for_genesis,
     common::{Author, Payload},
@@ -116,3 +119,37 @@ fn test_dag() {
     let vote = Vote::new(node.metadata().clone(), Signature::dummy_signature());
     test_dag_type::<DagVoteSchema, <DagVoteSchema as Schema>::Key>(node.id(), vote, &db);
 }
+
+#[test]
+fn test_evidence_dedup() {
+    let tmp_dir = TempPath::new();
+    let db = ConsensusDB::new(&tmp_dir);
+
+    let author = Author::random();
+    let certificate = |timestamp| {
+        let node = Node::new(
+            1,
+            1,
+            author,
+            timestamp,
+            vec![],
+            Payload::empty(false, true),
+            vec![],
+            Extensions::empty(),
+        );
+        Box::new(CertifiedNode::new(node, AggregateSignature::empty()).certificate())
+    };
+    let evidence = Evidence::ConflictingDagNodes {
+        first: certificate(1),
+        second: certificate(2),
+    };
+    assert!(db.save_evidence(&evidence).unwrap());
+
+    // only the first evidence of the author in the epoch is kept
+    let other_evidence = Evidence::ConflictingDagNodes {
+        first: certificate(1),
+        second: certificate(3),
+    };
+    assert!(!db.save_evidence(&other_evidence).unwrap());
+    assert_eq!(db.get_all_evidence().unwrap(), vec![evidence]);
+}
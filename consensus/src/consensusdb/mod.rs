@@ -6,7 +6,7 @@
 mod consensusdb_test;
 mod schema;
 
-use crate::error::DbError;
+use crate::{error::DbError, evidence::Evidence};
 use anyhow::Result;
 use aptos_consensus_types::{block::Block, quorum_cert::QuorumCert};
 use aptos_crypto::HashValue;
@@ -16,12 +16,13 @@ use aptos_storage_interface::AptosDbError;
 pub use schema::{
     block::BlockSchema,
     dag::{CertifiedNodeSchema, DagVoteSchema, NodeSchema},
+    evidence::EvidenceSchema,
     quorum_certificate::QCSchema,
 };
 use schema::{
     single_entry::{SingleEntryKey, SingleEntrySchema},
-    BLOCK_CF_NAME, CERTIFIED_NODE_CF_NAME, DAG_VOTE_CF_NAME, NODE_CF_NAME, QC_CF_NAME,
-    SINGLE_ENTRY_CF_NAME,
+    BLOCK_CF_NAME, CERTIFIED_NODE_CF_NAME, DAG_VOTE_CF_NAME, EVIDENCE_CF_NAME, NODE_CF_NAME,
+    QC_CF_NAME, SINGLE_ENTRY_CF_NAME,
 };
 use std::{iter::Iterator, path::Path, time::Instant};
 
@@ -59,6 +60,7 @@ impl ConsensusDB {
             CERTIFIED_NODE_CF_NAME,
             DAG_VOTE_CF_NAME,
             "ordered_anchor_id", // deprecated CF
+            EVIDENCE_CF_NAME,
         ];
 
         let path = db_root_path.as_ref().join(CONSENSUS_DB_NAME);
@@ -152,6 +154,25 @@ impl ConsensusDB {
         self.commit(batch)
     }
 
+    /// Saves the evidence unless evidence of the same kind was already saved for the author in
+    /// the epoch. Returns whether the evidence was saved.
+    pub fn save_evidence(&self, evidence: &Evidence) -> Result<bool, DbError> {
+        let key = evidence.key();
+        if self.db.get::<EvidenceSchema>(&key)?.is_some() {
+            return Ok(false);
+        }
+        self.put::<EvidenceSchema>(&key, evidence)?;
+        Ok(true)
+    }
+
+    pub fn get_all_evidence(&self) -> Result<Vec<Evidence>, DbError> {
+        Ok(self
+            .get_all::<EvidenceSchema>()?
+            .into_iter()
+            .map(|(_, evidence)| evidence)
+            .collect())
+    }
+
     /// Write the whole schema batch including all data necessary to mutate the ledger
     /// state of some transaction by leveraging rocksdb atomicity support.
     fn commit(&self, batch: SchemaBatch) -> Result<(), DbError> {
@@ -0,0 +1,45 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for equivocation evidence.
+//!
+//! Evidence is identified by the epoch, author and kind of the equivocation, so that only the
+//! first evidence is kept.
+//! ```text
+//! |<-----------key---------->|<--value-->|
+//! | epoch | author | kind    |  Evidence |
+//! ```
+
+use crate::{
+    define_schema,
+    evidence::{Evidence, EvidenceKey},
+};
+use anyhow::Result;
+use aptos_schemadb::{
+    schema::{KeyCodec, ValueCodec},
+    ColumnFamilyName,
+};
+
+pub const EVIDENCE_CF_NAME: ColumnFamilyName = "evidence";
+
+define_schema!(EvidenceSchema, EvidenceKey, Evidence, EVIDENCE_CF_NAME);
+
+impl KeyCodec<EvidenceSchema> for EvidenceKey {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(&self)?)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+impl ValueCodec<EvidenceSchema> for Evidence {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(&self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
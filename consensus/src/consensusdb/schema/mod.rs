@@ -4,6 +4,7 @@
 
 pub(crate) mod block;
 pub(crate) mod dag;
+pub(crate) mod evidence;
 pub(crate) mod quorum_certificate;
 pub(crate) mod single_entry;
 
@@ -37,5 +38,6 @@ macro_rules! define_schema {
 
 pub use block::BLOCK_CF_NAME;
 pub use dag::{CERTIFIED_NODE_CF_NAME, DAG_VOTE_CF_NAME, NODE_CF_NAME};
+pub use evidence::EVIDENCE_CF_NAME;
 pub use quorum_certificate::QC_CF_NAME;
 pub use single_entry::SINGLE_ENTRY_CF_NAME;
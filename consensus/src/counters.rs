@@ -91,6 +91,16 @@ pub static COMMITTED_TXNS_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Count of the equivocation evidence saved since last restart, by kind of evidence.
+pub static EQUIVOCATION_EVIDENCE_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_equivocation_evidence_count",
+        "Count of the equivocation evidence saved since last restart. kind is vote, proposal or dag_node",
+        &["kind"]
+    )
+    .unwrap()
+});
+
 //////////////////////
 // PROPOSAL VOTE COUNTERS
 //////////////////////
@@ -14,6 +14,7 @@ use crate::{
         storage::{CommitEvent, DAGStorage},
         CertifiedNode, Node, NodeId, Vote,
     },
+    evidence::Evidence,
     pipeline::buffer_manager::OrderedBlocks,
 };
 use anyhow::{anyhow, bail, format_err};
@@ -376,6 +377,10 @@ impl DAGStorage for StorageAdapter {
         Ok(self.consensus_db.delete::<CertifiedNodeSchema>(digests)?)
     }
 
+    fn save_evidence(&self, evidence: &Evidence) -> anyhow::Result<bool> {
+        Ok(self.consensus_db.save_evidence(evidence)?)
+    }
+
     fn get_latest_k_committed_events(&self, k: u64) -> anyhow::Result<Vec<CommitEvent>> {
         let timer = counters::FETCH_COMMIT_HISTORY_DURATION.start_timer();
         let version = self.aptos_db.get_latest_ledger_info_version()?;
@@ -10,6 +10,7 @@ use crate::{
         storage::DAGStorage,
         types::{CertifiedNode, NodeCertificate},
     },
+    evidence::{report_saved_evidence, Evidence},
     payload_manager::TPayloadManager,
 };
 use anyhow::{anyhow, ensure};
@@ -508,6 +509,19 @@ impl DagStore {
     }
 
     pub fn add_node(&self, node: CertifiedNode) -> anyhow::Result<()> {
+        let conflicting = self
+            .dag
+            .read()
+            .get_node_by_round_author(node.round(), node.author())
+            .filter(|existing| existing.digest() != node.digest())
+            .map(|existing| existing.certificate());
+        if let Some(existing) = conflicting {
+            let evidence = Evidence::ConflictingDagNodes {
+                first: Box::new(existing),
+                second: Box::new(node.certificate()),
+            };
+            report_saved_evidence(&evidence, self.storage.save_evidence(&evidence));
+        }
         self.dag.write().validate_new_node(&node)?;
 
         // Note on concurrency: it is possible that a prune operation kicks in here and
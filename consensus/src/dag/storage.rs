@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::{types::Vote, NodeId};
-use crate::dag::{CertifiedNode, Node};
+use crate::{
+    dag::{CertifiedNode, Node},
+    evidence::Evidence,
+};
 use aptos_consensus_types::common::{Author, Round};
 use aptos_crypto::HashValue;
 use aptos_types::ledger_info::LedgerInfoWithSignatures;
@@ -64,6 +67,8 @@ pub trait DAGStorage: Send + Sync {
 
     fn delete_certified_nodes(&self, digests: Vec<HashValue>) -> anyhow::Result<()>;
 
+    fn save_evidence(&self, evidence: &Evidence) -> anyhow::Result<bool>;
+
     fn get_latest_k_committed_events(&self, k: u64) -> anyhow::Result<Vec<CommitEvent>>;
 
     fn get_latest_ledger_info(&self) -> anyhow::Result<LedgerInfoWithSignatures>;
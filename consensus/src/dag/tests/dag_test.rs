@@ -2,12 +2,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::helpers::MockPayloadManager;
-use crate::dag::{
-    dag_store::DagStore,
-    storage::{CommitEvent, DAGStorage},
-    tests::helpers::{new_certified_node, TEST_DAG_WINDOW},
-    types::{CertifiedNode, DagSnapshotBitmask, Node},
-    NodeId, Vote,
+use crate::{
+    dag::{
+        dag_store::DagStore,
+        storage::{CommitEvent, DAGStorage},
+        tests::helpers::{new_certified_node, TEST_DAG_WINDOW},
+        types::{CertifiedNode, DagSnapshotBitmask, Node},
+        NodeId, Vote,
+    },
+    evidence::Evidence,
 };
 use aptos_consensus_types::common::Author;
 use aptos_crypto::HashValue;
@@ -105,6 +108,10 @@ impl DAGStorage for MockStorage {
         Ok(())
     }
 
+    fn save_evidence(&self, _evidence: &Evidence) -> anyhow::Result<bool> {
+        Ok(true)
+    }
+
     fn get_latest_k_committed_events(&self, _k: u64) -> anyhow::Result<Vec<CommitEvent>> {
         Ok(vec![])
     }
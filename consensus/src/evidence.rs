@@ -0,0 +1,299 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Evidence of equivocation: pairs of conflicting messages which are signed by the same
+//! validator(s). Evidence is persisted in the ConsensusDB when it is detected, and can be verified
+//! by anyone who knows the validator set of the epoch it was produced in.
+
+use crate::counters;
+pub use crate::dag::NodeCertificate;
+use anyhow::{ensure, Context};
+use aptos_consensus_types::{block::Block, common::Author, vote::Vote};
+use aptos_crypto::hash::CryptoHash;
+use aptos_logger::prelude::*;
+use aptos_types::validator_verifier::ValidatorVerifier;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fmt};
+
+/// The kind of misbehavior an evidence proves.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum EvidenceKind {
+    Vote,
+    Proposal,
+    DagNode,
+}
+
+impl EvidenceKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EvidenceKind::Vote => "vote",
+            EvidenceKind::Proposal => "proposal",
+            EvidenceKind::DagNode => "dag_node",
+        }
+    }
+}
+
+/// Evidence is deduplicated per epoch, author and kind: only the first evidence is kept.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct EvidenceKey {
+    pub epoch: u64,
+    pub author: Author,
+    pub kind: EvidenceKind,
+}
+
+impl fmt::Display for EvidenceKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[epoch: {}, author: {}, kind: {}]",
+            self.epoch,
+            self.author,
+            self.kind.as_str()
+        )
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum Evidence {
+    /// Two votes of the same author for different ledger infos in the same round.
+    ConflictingVotes { first: Box<Vote>, second: Box<Vote> },
+    /// Two different proposals of the same author for the same round.
+    ConflictingProposals {
+        first: Box<Block>,
+        second: Box<Block>,
+    },
+    /// Two different nodes of the same author and round which are both certified. DAG nodes are
+    /// not signed by their author, so the validators which signed both certificates are the ones
+    /// which misbehaved.
+    ConflictingDagNodes {
+        first: Box<NodeCertificate>,
+        second: Box<NodeCertificate>,
+    },
+}
+
+impl Evidence {
+    pub fn kind(&self) -> EvidenceKind {
+        match self {
+            Evidence::ConflictingVotes { .. } => EvidenceKind::Vote,
+            Evidence::ConflictingProposals { .. } => EvidenceKind::Proposal,
+            Evidence::ConflictingDagNodes { .. } => EvidenceKind::DagNode,
+        }
+    }
+
+    /// The author of the first message, for DAG nodes this is the author of the node.
+    pub fn author(&self) -> Author {
+        match self {
+            Evidence::ConflictingVotes { first, .. } => first.author(),
+            Evidence::ConflictingProposals { first, .. } => first.author().unwrap_or(Author::ZERO),
+            Evidence::ConflictingDagNodes { first, .. } => *first.metadata().author(),
+        }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        match self {
+            Evidence::ConflictingVotes { first, .. } => first.epoch(),
+            Evidence::ConflictingProposals { first, .. } => first.epoch(),
+            Evidence::ConflictingDagNodes { first, .. } => first.metadata().epoch(),
+        }
+    }
+
+    pub fn round(&self) -> u64 {
+        match self {
+            Evidence::ConflictingVotes { first, .. } => first.vote_data().proposed().round(),
+            Evidence::ConflictingProposals { first, .. } => first.round(),
+            Evidence::ConflictingDagNodes { first, .. } => first.metadata().round(),
+        }
+    }
+
+    pub fn key(&self) -> EvidenceKey {
+        EvidenceKey {
+            epoch: self.epoch(),
+            author: self.author(),
+            kind: self.kind(),
+        }
+    }
+
+    /// Verifies that both messages are validly signed for the same epoch and round and that they
+    /// conflict. The verifier must be the one of the epoch of the evidence.
+    pub fn verify(&self, verifier: &ValidatorVerifier) -> anyhow::Result<()> {
+        match self {
+            Evidence::ConflictingVotes { first, second } => {
+                ensure!(
+                    first.author() == second.author(),
+                    "Votes of different authors"
+                );
+                ensure!(
+                    (first.epoch(), first.vote_data().proposed().round())
+                        == (second.epoch(), second.vote_data().proposed().round()),
+                    "Votes for different (epoch, round)"
+                );
+                ensure!(
+                    first.ledger_info().hash() != second.ledger_info().hash(),
+                    "Votes for the same ledger info"
+                );
+                first.verify(verifier).context("Invalid first vote")?;
+                second.verify(verifier).context("Invalid second vote")?;
+            },
+            Evidence::ConflictingProposals { first, second } => {
+                ensure!(first.author().is_some(), "Proposal without author");
+                ensure!(
+                    first.author() == second.author(),
+                    "Proposals of different authors"
+                );
+                ensure!(
+                    (first.epoch(), first.round()) == (second.epoch(), second.round()),
+                    "Proposals for different (epoch, round)"
+                );
+                ensure!(first.id() != second.id(), "Proposals of the same block");
+                first
+                    .validate_signature(verifier)
+                    .context("Invalid first proposal")?;
+                second
+                    .validate_signature(verifier)
+                    .context("Invalid second proposal")?;
+            },
+            Evidence::ConflictingDagNodes { first, second } => {
+                let (first_metadata, second_metadata) = (first.metadata(), second.metadata());
+                ensure!(
+                    (
+                        first_metadata.epoch(),
+                        first_metadata.round(),
+                        first_metadata.author()
+                    ) == (
+                        second_metadata.epoch(),
+                        second_metadata.round(),
+                        second_metadata.author()
+                    ),
+                    "Nodes with different (epoch, round, author)"
+                );
+                ensure!(
+                    first_metadata.digest() != second_metadata.digest(),
+                    "Certificates of the same node"
+                );
+                first
+                    .verify(verifier)
+                    .context("Invalid first certificate")?;
+                second
+                    .verify(verifier)
+                    .context("Invalid second certificate")?;
+            },
+        }
+        Ok(())
+    }
+
+    /// The validators which provably misbehaved, assuming the evidence is valid.
+    pub fn equivocators(&self, verifier: &ValidatorVerifier) -> Vec<Author> {
+        match self {
+            Evidence::ConflictingVotes { .. } | Evidence::ConflictingProposals { .. } => {
+                vec![self.author()]
+            },
+            Evidence::ConflictingDagNodes { first, second } => {
+                let validators = verifier.get_ordered_account_addresses();
+                let first_signers: HashSet<_> = first.signers(&validators).into_iter().collect();
+                second
+                    .signers(&validators)
+                    .into_iter()
+                    .filter(|author| first_signers.contains(author))
+                    .collect()
+            },
+        }
+    }
+}
+
+impl fmt::Display for Evidence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Evidence {} at round {}", self.key(), self.round())
+    }
+}
+
+/// Logs the result of persisting the evidence and updates the metrics. Failing to persist the
+/// evidence must not affect consensus, so the error is only logged.
+pub(crate) fn report_saved_evidence(evidence: &Evidence, result: anyhow::Result<bool>) {
+    match result {
+        Ok(true) => {
+            counters::EQUIVOCATION_EVIDENCE_COUNT
+                .with_label_values(&[evidence.kind().as_str()])
+                .inc();
+            warn!(
+                SecurityEvent::ConsensusEquivocationEvidence,
+                "Saved {}", evidence
+            );
+        },
+        // Evidence of the same kind was already saved for the author in this epoch
+        Ok(false) => (),
+        Err(e) => error!(error = ?e, "Failed to save {}", evidence),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Evidence;
+    use aptos_consensus_types::{vote::Vote, vote_data::VoteData};
+    use aptos_crypto::HashValue;
+    use aptos_types::{
+        block_info::BlockInfo, ledger_info::LedgerInfo, validator_signer::ValidatorSigner,
+        validator_verifier::random_validator_verifier,
+    };
+
+    fn random_vote(signer: &ValidatorSigner, round: u64) -> Vote {
+        Vote::new(
+            VoteData::new(
+                BlockInfo::new(
+                    1,
+                    round,
+                    HashValue::random(),
+                    HashValue::random(),
+                    0,
+                    0,
+                    None,
+                ),
+                BlockInfo::random(round - 1),
+            ),
+            signer.author(),
+            LedgerInfo::new(BlockInfo::random(round - 1), HashValue::zero()),
+            signer,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_verify_conflicting_votes() {
+        let (signers, verifier) = random_validator_verifier(4, None, false);
+        let first = random_vote(&signers[0], 1);
+        let evidence = Evidence::ConflictingVotes {
+            first: Box::new(first.clone()),
+            second: Box::new(random_vote(&signers[0], 1)),
+        };
+        evidence.verify(&verifier).unwrap();
+        assert_eq!(evidence.equivocators(&verifier), vec![signers[0].author()]);
+
+        // the same vote twice
+        let evidence = Evidence::ConflictingVotes {
+            first: Box::new(first.clone()),
+            second: Box::new(first.clone()),
+        };
+        assert!(evidence.verify(&verifier).is_err());
+
+        // votes for different rounds
+        let evidence = Evidence::ConflictingVotes {
+            first: Box::new(first.clone()),
+            second: Box::new(random_vote(&signers[0], 2)),
+        };
+        assert!(evidence.verify(&verifier).is_err());
+
+        // votes of different authors
+        let evidence = Evidence::ConflictingVotes {
+            first: Box::new(first.clone()),
+            second: Box::new(random_vote(&signers[1], 1)),
+        };
+        assert!(evidence.verify(&verifier).is_err());
+
+        // votes of an author which is not part of the validator set
+        let other_signer = ValidatorSigner::random(None);
+        let evidence = Evidence::ConflictingVotes {
+            first: Box::new(random_vote(&other_signer, 1)),
+            second: Box::new(random_vote(&other_signer, 1)),
+        };
+        assert!(evidence.verify(&verifier).is_err());
+    }
+}
@@ -22,6 +22,7 @@ mod consensusdb;
 mod dag;
 mod epoch_manager;
 mod error;
+pub mod evidence;
 mod liveness;
 mod logging;
 mod metrics_safety_rules;
@@ -17,7 +17,8 @@ use std::{cmp::Ordering, sync::Arc};
 // the same leader proposes multiple blocks.
 pub struct UnequivocalProposerElection {
     proposer_election: Arc<dyn ProposerElection + Send + Sync>,
-    already_proposed: Mutex<(Round, HashValue)>,
+    // The first valid proposal of the highest round, kept as evidence for equivocations.
+    already_proposed: Mutex<Option<Block>>,
 }
 
 impl ProposerElection for UnequivocalProposerElection {
@@ -35,7 +36,7 @@ impl UnequivocalProposerElection {
     pub fn new(proposer_election: Arc<dyn ProposerElection + Send + Sync>) -> Self {
         Self {
             proposer_election,
-            already_proposed: Mutex::new((0, HashValue::zero())),
+            already_proposed: Mutex::new(None),
         }
     }
 
@@ -59,21 +60,25 @@ impl UnequivocalProposerElection {
                 return false;
             }
             let mut already_proposed = self.already_proposed.lock();
+            let (proposed_round, proposed_id) = already_proposed
+                .as_ref()
+                .map_or((0, HashValue::zero()), |proposal| {
+                    (proposal.round(), proposal.id())
+                });
             // detect if the leader proposes more than once in this round
-            match block.round().cmp(&already_proposed.0) {
+            match block.round().cmp(&proposed_round) {
                 Ordering::Greater => {
-                    already_proposed.0 = block.round();
-                    already_proposed.1 = block.id();
+                    *already_proposed = Some(block.clone());
                     true
                 },
                 Ordering::Equal => {
-                    if already_proposed.1 != block.id() {
+                    if proposed_id != block.id() {
                         error!(
                             SecurityEvent::InvalidConsensusProposal,
                             "Multiple proposals from {} for round {}: {} and {}",
                             author,
                             block.round(),
-                            already_proposed.1,
+                            proposed_id,
                             block.id()
                         );
                         false
@@ -85,4 +90,18 @@ impl UnequivocalProposerElection {
             }
         })
     }
+
+    // Return the first proposal of the author of the given block for the same round, if it is a
+    // different block, i.e. the author equivocated.
+    pub fn conflicting_proposal(&self, block: &Block) -> Option<Block> {
+        self.already_proposed
+            .lock()
+            .as_ref()
+            .filter(|proposal| {
+                proposal.round() == block.round()
+                    && proposal.author() == block.author()
+                    && proposal.id() != block.id()
+            })
+            .cloned()
+    }
 }
@@ -93,7 +93,13 @@ fn test_is_valid_proposal() {
     assert!(!pe.is_valid_proposal(&bad_author_proposal));
 
     // another proposal from the valid proposer should fail
+    assert_eq!(
+        pe.conflicting_proposal(&bad_duplicate_proposal),
+        Some(good_proposal.clone())
+    );
     assert!(!pe.is_valid_proposal(&bad_duplicate_proposal));
+    assert_eq!(pe.conflicting_proposal(&good_proposal), None);
+    assert_eq!(pe.conflicting_proposal(&bad_author_proposal), None);
     // good proposal still passes
     assert!(pe.is_valid_proposal(&good_proposal));
 
@@ -2,7 +2,10 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{consensusdb::ConsensusDB, epoch_manager::LivenessStorageData, error::DbError};
+use crate::{
+    consensusdb::ConsensusDB, epoch_manager::LivenessStorageData, error::DbError,
+    evidence::Evidence,
+};
 use anyhow::{format_err, Context, Result};
 use aptos_config::config::NodeConfig;
 use aptos_consensus_types::{
@@ -46,6 +49,10 @@ pub trait PersistentLivenessStorage: Send + Sync {
         highest_timeout_cert: &TwoChainTimeoutCertificate,
     ) -> Result<()>;
 
+    /// Persist the evidence of an equivocation, returns false if evidence of the same kind was
+    /// already persisted for the author in the epoch.
+    fn save_evidence(&self, evidence: &Evidence) -> Result<bool>;
+
     /// Retrieve a epoch change proof for SafetyRules so it can instantiate its
     /// ValidatorVerifier.
     fn retrieve_epoch_change_proof(&self, version: u64) -> Result<EpochChangeProof>;
@@ -458,6 +465,10 @@ impl PersistentLivenessStorage for StorageWriteProxy {
             .save_highest_2chain_timeout_certificate(bcs::to_bytes(highest_timeout_cert)?)?)
     }
 
+    fn save_evidence(&self, evidence: &Evidence) -> Result<bool> {
+        Ok(self.db.save_evidence(evidence)?)
+    }
+
     fn retrieve_epoch_change_proof(&self, version: u64) -> Result<EpochChangeProof> {
         let (_, proofs) = self
             .aptos_db
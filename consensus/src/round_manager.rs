@@ -14,6 +14,7 @@ use crate::{
         QC_AGGREGATED_FROM_VOTES, SYNC_INFO_RECEIVED_WITH_NEWER_CERT,
     },
     error::{error_kind, VerifyError},
+    evidence::{report_saved_evidence, Evidence},
     liveness::{
         proposal_generator::ProposalGenerator,
         proposer_election::ProposerElection,
@@ -790,6 +791,13 @@ impl RoundManager {
             self.local_config.max_receiving_block_bytes,
         );
 
+        if let Some(first) = self.proposer_election.conflicting_proposal(&proposal) {
+            let evidence = Evidence::ConflictingProposals {
+                first: Box::new(first),
+                second: Box::new(proposal.clone()),
+            };
+            report_saved_evidence(&evidence, self.storage.save_evidence(&evidence));
+        }
         ensure!(
             self.proposer_election.is_valid_proposal(&proposal),
             "[RoundManager] Proposer {} for block {} is not a valid proposer for this round or created duplicate proposal",
@@ -1161,6 +1169,14 @@ impl RoundManager {
                 PROPOSAL_VOTE_ADDED.inc();
                 Ok(())
             },
+            VoteReceptionResult::EquivocateVote(previous_vote) => {
+                let evidence = Evidence::ConflictingVotes {
+                    first: previous_vote,
+                    second: Box::new(vote.clone()),
+                };
+                report_saved_evidence(&evidence, self.storage.save_evidence(&evidence));
+                bail!("[RoundManager] Equivocating {}", vote)
+            },
             VoteReceptionResult::VoteAddedQCDelayed(_)
             | VoteReceptionResult::EchoTimeout(_)
             | VoteReceptionResult::DuplicateVote => Ok(()),
@@ -4,6 +4,7 @@
 
 use crate::{
     epoch_manager::LivenessStorageData,
+    evidence::{Evidence, EvidenceKey},
     persistent_liveness_storage::{
         LedgerRecoveryData, PersistentLivenessStorage, RecoveryData, RootMetadata,
     },
@@ -32,6 +33,7 @@ pub struct MockSharedStorage {
 
     // Liveness state
     pub highest_2chain_timeout_certificate: Mutex<Option<TwoChainTimeoutCertificate>>,
+    pub evidence: Mutex<HashMap<EvidenceKey, Evidence>>,
     pub validator_set: ValidatorSet,
 }
 
@@ -43,6 +45,7 @@ impl MockSharedStorage {
             lis: Mutex::new(HashMap::new()),
             last_vote: Mutex::new(None),
             highest_2chain_timeout_certificate: Mutex::new(None),
+            evidence: Mutex::new(HashMap::new()),
             validator_set,
         }
     }
@@ -221,6 +224,15 @@ impl PersistentLivenessStorage for MockStorage {
         Ok(())
     }
 
+    fn save_evidence(&self, evidence: &Evidence) -> Result<bool> {
+        let mut saved = self.shared_storage.evidence.lock();
+        if saved.contains_key(&evidence.key()) {
+            return Ok(false);
+        }
+        saved.insert(evidence.key(), evidence.clone());
+        Ok(true)
+    }
+
     fn retrieve_epoch_change_proof(&self, version: u64) -> Result<EpochChangeProof> {
         let lis = self
             .shared_storage
@@ -301,6 +313,10 @@ impl PersistentLivenessStorage for EmptyStorage {
         Ok(())
     }
 
+    fn save_evidence(&self, _: &Evidence) -> Result<bool> {
+        Ok(true)
+    }
+
     fn retrieve_epoch_change_proof(&self, _version: u64) -> Result<EpochChangeProof> {
         Ok(EpochChangeProof::new(vec![], false))
     }
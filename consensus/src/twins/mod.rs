@@ -3,4 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod basic_twins_test;
+mod random_twins_test;
+mod scenario;
+mod scenario_runner;
 mod twins_node;
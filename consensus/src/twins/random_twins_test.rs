@@ -0,0 +1,195 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::twins::{
+    scenario::{minimize, set_partitions, ScenarioGenerator, TwinsScenario},
+    scenario_runner::{CommittedBlock, CommittedChain, ScenarioRunner, TwinsViolation},
+};
+use aptos_consensus_types::common::Round;
+use aptos_crypto::HashValue;
+use rand::{rngs::StdRng, SeedableRng};
+use std::{collections::HashSet, env, time::Duration};
+
+/// The seed of the scenarios run by default, so that the test is deterministic unless a seed is
+/// given explicitly.
+const DEFAULT_SEED: u64 = 42;
+
+#[test]
+fn test_set_partitions() {
+    // S(4, 1) + S(4, 2), Stirling numbers of the second kind
+    assert_eq!(set_partitions(4, 2).len(), 8);
+    // B(5), the Bell number
+    assert_eq!(set_partitions(5, 5).len(), 52);
+
+    let partitions = set_partitions(5, 3);
+    for partition in &partitions {
+        let mut nodes: Vec<_> = partition.iter().flatten().copied().collect();
+        nodes.sort();
+        assert_eq!(nodes, vec![0, 1, 2, 3, 4]);
+        assert!(partition.iter().all(|set| !set.is_empty()));
+    }
+    assert_eq!(
+        partitions.iter().collect::<HashSet<_>>().len(),
+        partitions.len()
+    );
+}
+
+#[test]
+fn test_enumerate_scenarios() {
+    // 5 nodes in at most 2 partitions, S(5, 1) + S(5, 2) = 16, and 4 leaders for each of 2 rounds
+    let generator = ScenarioGenerator::new(4, 1, 2, 2);
+    assert_eq!(generator.num_scenarios(), 64 * 64);
+
+    let scenarios: Vec<_> = generator.enumerate().collect();
+    assert_eq!(scenarios.len(), 64 * 64);
+    let distinct: HashSet<_> = scenarios
+        .iter()
+        .map(|scenario| (scenario.leaders.clone(), scenario.partitions.clone()))
+        .collect();
+    assert_eq!(distinct.len(), scenarios.len());
+}
+
+#[test]
+fn test_sample_is_reproducible() {
+    let generator = ScenarioGenerator::new(4, 1, 10, 3);
+    let first = generator.sample(&mut StdRng::seed_from_u64(7));
+    let second = generator.sample(&mut StdRng::seed_from_u64(7));
+    assert_eq!(first, second);
+    assert_eq!(first.num_rounds(), 10);
+    assert!(first.leaders.iter().all(|leader| *leader < 4));
+}
+
+#[test]
+fn test_minimize() {
+    let all_nodes = vec![vec![0, 1, 2, 3, 4]];
+    let split = vec![vec![0, 1, 2], vec![3, 4]];
+    let scenario = TwinsScenario {
+        num_nodes: 4,
+        num_twins: 1,
+        leaders: vec![1, 0, 0],
+        partitions: vec![split.clone(), split.clone(), split.clone()],
+    };
+    // Fails in the first round led by the node with a twin in a split network
+    let run = |scenario: &TwinsScenario| match (1..)
+        .zip(scenario.leaders.iter().zip(&scenario.partitions))
+        .find(|(_, (leader, partitions))| **leader == 0 && partitions.len() > 1)
+    {
+        Some((round, _)) => Err(round),
+        None => Ok(()),
+    };
+
+    let (minimized, failure) = minimize(scenario, 2, run);
+    assert_eq!(minimized.leaders, vec![1, 0]);
+    assert_eq!(minimized.partitions, vec![all_nodes, split]);
+    assert_eq!(failure, 2);
+}
+
+fn block(round: Round, parent_round: Round) -> CommittedBlock {
+    block_on_fork(round, parent_round, 0)
+}
+
+/// A block whose parent is the block of the parent round on the given fork.
+fn block_on_fork(round: Round, parent_round: Round, fork: u8) -> CommittedBlock {
+    let id = |round: Round| {
+        let fork = if round == parent_round { fork } else { 0 };
+        HashValue::sha3_256_of(&[&round.to_le_bytes()[..], &[fork]].concat())
+    };
+    CommittedBlock {
+        round,
+        id: id(round),
+        parent_round,
+        parent_id: id(parent_round),
+    }
+}
+
+#[test]
+fn test_committed_chains_extend_each_other() {
+    let mut chain = CommittedChain::default();
+    for (node, block) in [
+        (0, block(1, 0)),
+        (0, block(2, 1)),
+        (1, block(1, 0)),
+        // Node 2 synced up to block 2, and no block was certified in round 3
+        (2, block(4, 2)),
+        (1, block(2, 1)),
+        (1, block(4, 2)),
+        (0, block(5, 4)),
+    ] {
+        assert_eq!(chain.add(node, block), Ok(()));
+    }
+}
+
+#[test]
+fn test_fork_committed_at_different_rounds() {
+    // Node 0 commits block 2 on top of block 1, node 1 commits block 3 on top of block 1
+    let mut chain = CommittedChain::default();
+    assert_eq!(chain.add(0, block(1, 0)), Ok(()));
+    assert_eq!(chain.add(0, block(2, 1)), Ok(()));
+    assert_eq!(chain.add(1, block(1, 0)), Ok(()));
+    let fork = block(3, 1);
+    assert_eq!(
+        chain.add(1, fork),
+        Err(TwinsViolation::ConflictingCommits {
+            first: (0, 2, block(2, 1).id),
+            second: (1, 3, fork.id),
+        })
+    );
+
+    // The same fork is found if the later block is committed first
+    let mut chain = CommittedChain::default();
+    assert_eq!(chain.add(1, fork), Ok(()));
+    assert_eq!(
+        chain.add(0, block(2, 1)),
+        Err(TwinsViolation::ConflictingCommits {
+            first: (1, 3, fork.id),
+            second: (0, 2, block(2, 1).id),
+        })
+    );
+
+    // A block whose parent differs from the block committed in the parent round
+    let mut chain = CommittedChain::default();
+    assert_eq!(chain.add(0, block(2, 1)), Ok(()));
+    let other_parent = block_on_fork(4, 2, 1);
+    assert_eq!(
+        chain.add(1, other_parent),
+        Err(TwinsViolation::ConflictingCommits {
+            first: (0, 2, block(2, 1).id),
+            second: (1, 4, other_parent.id),
+        })
+    );
+}
+
+#[test]
+/// This test runs randomly sampled Twins scenarios and checks that the honest nodes never commit
+/// conflicting blocks, and that they all commit after the last round of the scenario. A failing
+/// scenario is minimized and reported with the seed to replay it.
+///
+/// Setup:
+///
+/// 4 nodes and 1 twin, 4 rounds with random leaders and up to 2 partitions per round
+///
+/// By default, the scenario of a fixed seed is run. Run more scenarios, or replay a seed:
+/// TWINS_ITERATIONS=100 TWINS_SEED=<seed> cargo xtest -p consensus random_twins_test -- --nocapture
+fn random_twins_test() {
+    let seed: u64 = env::var("TWINS_SEED")
+        .map(|seed| seed.parse().expect("TWINS_SEED must be a u64"))
+        .unwrap_or(DEFAULT_SEED);
+    let iterations: u64 = env::var("TWINS_ITERATIONS")
+        .map(|iterations| iterations.parse().expect("TWINS_ITERATIONS must be a u64"))
+        .unwrap_or(1);
+
+    let generator = ScenarioGenerator::new(4, 1, 4, 2);
+    let runner = ScenarioRunner::new(1_000, Duration::from_secs(60));
+    for iteration in 0..iterations {
+        let scenario_seed = seed.wrapping_add(iteration);
+        let scenario = generator.sample(&mut StdRng::seed_from_u64(scenario_seed));
+        if let Err(violation) = runner.run(&scenario) {
+            let (scenario, violation) =
+                minimize(scenario, violation, |scenario| runner.run(scenario));
+            panic!(
+                "[TwinsTest] {} with TWINS_SEED={}, minimized scenario:\n{}",
+                violation, scenario_seed, scenario
+            );
+        }
+    }
+}
@@ -0,0 +1,220 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generation of Twins scenarios, as described in the Twins paper
+//! (https://arxiv.org/abs/2004.10617).
+//!
+//! A scenario runs a network of `num_nodes` validators, the first `num_twins` of which have a twin:
+//! a second node with the same identity and keys, which makes the validator byzantine. For each
+//! round the scenario fixes the leader and a partition of all nodes (twins included) which only
+//! exchange messages within their partition. Placing the twins on the first validators does not
+//! lose any scenario, as the leaders and partitions are generated over all validators.
+
+use aptos_consensus_types::common::Round;
+use itertools::Itertools;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+use std::{collections::HashMap, fmt};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TwinsScenario {
+    pub num_nodes: usize,
+    pub num_twins: usize,
+    /// The leader (validator index) of every round, starting at round 1
+    pub leaders: Vec<usize>,
+    /// The partitions of the nodes (node index, twin of validator `i` has index `num_nodes + i`)
+    /// of every round, starting at round 1
+    pub partitions: Vec<Vec<Vec<usize>>>,
+}
+
+impl TwinsScenario {
+    pub fn num_rounds(&self) -> Round {
+        self.leaders.len() as Round
+    }
+
+    /// Nodes without a twin, safety and liveness are only expected from them.
+    pub fn honest_nodes(&self) -> impl Iterator<Item = usize> {
+        self.num_twins..self.num_nodes
+    }
+
+    pub fn round_leaders(&self) -> HashMap<Round, usize> {
+        (1..).zip(self.leaders.iter().copied()).collect()
+    }
+
+    pub fn round_partitions(&self) -> HashMap<Round, Vec<Vec<usize>>> {
+        (1..).zip(self.partitions.iter().cloned()).collect()
+    }
+}
+
+impl fmt::Display for TwinsScenario {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} nodes, twins of nodes {:?}",
+            self.num_nodes,
+            (0..self.num_twins).collect::<Vec<_>>()
+        )?;
+        for (round, (leader, partitions)) in (1..).zip(self.leaders.iter().zip(&self.partitions)) {
+            writeln!(
+                f,
+                "round {}: leader {}, partitions {:?}",
+                round, leader, partitions
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Enumerates or samples the scenarios of a network.
+pub struct ScenarioGenerator {
+    num_nodes: usize,
+    num_twins: usize,
+    num_rounds: usize,
+    /// All partitions of the nodes into at most `max_partitions` sets
+    partitions: Vec<Vec<Vec<usize>>>,
+}
+
+impl ScenarioGenerator {
+    pub fn new(
+        num_nodes: usize,
+        num_twins: usize,
+        num_rounds: usize,
+        max_partitions: usize,
+    ) -> Self {
+        assert!(num_twins < num_nodes, "at least one node must be honest");
+        assert!(max_partitions > 0);
+        Self {
+            num_nodes,
+            num_twins,
+            num_rounds,
+            partitions: set_partitions(num_nodes + num_twins, max_partitions),
+        }
+    }
+
+    /// The number of (leader, partitions) choices per round
+    fn choices_per_round(&self) -> u128 {
+        (self.num_nodes * self.partitions.len()) as u128
+    }
+
+    /// The number of distinct scenarios, saturated to `u128::MAX`
+    pub fn num_scenarios(&self) -> u128 {
+        (0..self.num_rounds).fold(1u128, |total, _| {
+            total.saturating_mul(self.choices_per_round())
+        })
+    }
+
+    /// Returns the scenario at the given index, consecutive indices differ in the choices of the
+    /// first round.
+    pub fn scenario(&self, mut index: u128) -> TwinsScenario {
+        let mut leaders = vec![];
+        let mut partitions = vec![];
+        for _ in 0..self.num_rounds {
+            let choice = (index % self.choices_per_round()) as usize;
+            index /= self.choices_per_round();
+            leaders.push(choice % self.num_nodes);
+            partitions.push(self.partitions[choice / self.num_nodes].clone());
+        }
+        self.new_scenario(leaders, partitions)
+    }
+
+    pub fn enumerate(&self) -> impl Iterator<Item = TwinsScenario> + '_ {
+        (0..self.num_scenarios()).map(|index| self.scenario(index))
+    }
+
+    pub fn sample(&self, rng: &mut StdRng) -> TwinsScenario {
+        let leaders = (0..self.num_rounds)
+            .map(|_| rng.gen_range(0, self.num_nodes))
+            .collect();
+        let partitions = (0..self.num_rounds)
+            .map(|_| self.partitions.choose(rng).unwrap().clone())
+            .collect();
+        self.new_scenario(leaders, partitions)
+    }
+
+    fn new_scenario(&self, leaders: Vec<usize>, partitions: Vec<Vec<Vec<usize>>>) -> TwinsScenario {
+        TwinsScenario {
+            num_nodes: self.num_nodes,
+            num_twins: self.num_twins,
+            leaders,
+            partitions,
+        }
+    }
+}
+
+/// Returns all partitions of `0..n` into at most `max_sets` non-empty sets.
+pub fn set_partitions(n: usize, max_sets: usize) -> Vec<Vec<Vec<usize>>> {
+    let mut partitions: Vec<Vec<Vec<usize>>> = vec![vec![]];
+    for element in 0..n {
+        partitions = partitions
+            .into_iter()
+            .flat_map(|partition| {
+                let mut extended = vec![];
+                for i in 0..partition.len() {
+                    let mut with_element = partition.clone();
+                    with_element[i].push(element);
+                    extended.push(with_element);
+                }
+                if partition.len() < max_sets {
+                    let mut with_new_set = partition;
+                    with_new_set.push(vec![element]);
+                    extended.push(with_new_set);
+                }
+                extended
+            })
+            .collect();
+    }
+    partitions
+}
+
+/// Shrinks a failing scenario: drops trailing rounds, heals the partitions and moves the
+/// leadership to an honest node round by round, as long as the scenario still fails.
+pub fn minimize<E>(
+    mut scenario: TwinsScenario,
+    mut failure: E,
+    mut run: impl FnMut(&TwinsScenario) -> Result<(), E>,
+) -> (TwinsScenario, E) {
+    loop {
+        let candidates = simplifications(&scenario);
+        let mut simplified = false;
+        for candidate in candidates {
+            if let Err(candidate_failure) = run(&candidate) {
+                scenario = candidate;
+                failure = candidate_failure;
+                simplified = true;
+                break;
+            }
+        }
+        if !simplified {
+            return (scenario, failure);
+        }
+    }
+}
+
+fn simplifications(scenario: &TwinsScenario) -> Vec<TwinsScenario> {
+    let all_nodes = vec![(0..scenario.num_nodes + scenario.num_twins).collect_vec()];
+    let honest_leader = scenario.num_twins;
+    let mut candidates = vec![];
+    if scenario.num_rounds() > 1 {
+        let mut candidate = scenario.clone();
+        candidate.leaders.pop();
+        candidate.partitions.pop();
+        candidates.push(candidate);
+    }
+    for (round, (leader, partitions)) in scenario
+        .leaders
+        .iter()
+        .zip(&scenario.partitions)
+        .enumerate()
+    {
+        if *partitions != all_nodes {
+            let mut candidate = scenario.clone();
+            candidate.partitions[round] = all_nodes.clone();
+            candidates.push(candidate);
+        }
+        if *leader < scenario.num_twins {
+            let mut candidate = scenario.clone();
+            candidate.leaders[round] = honest_leader;
+            candidates.push(candidate);
+        }
+    }
+    candidates
+}
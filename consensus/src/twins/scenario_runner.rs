@@ -0,0 +1,214 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    network_tests::{NetworkPlayground, TwinId},
+    test_utils::consensus_runtime,
+    twins::{scenario::TwinsScenario, twins_node::SMRNode},
+};
+use aptos_consensus_types::{common::Round, pipelined_block::PipelinedBlock};
+use aptos_crypto::HashValue;
+use aptos_types::on_chain_config::ProposerElectionType::RoundProposer;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    time::{Duration, Instant},
+};
+use tokio::runtime::Runtime;
+
+/// Number of rounds after the scenario which are led by an honest node, so that the progress of
+/// the honest nodes is only prevented by the scenario itself.
+const HONEST_LEADER_ROUNDS: Round = 20;
+
+/// A violation of the safety or liveness of the honest nodes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TwinsViolation {
+    /// Two honest nodes committed blocks which are not on the same chain, given as the node,
+    /// round and id of each block
+    ConflictingCommits {
+        first: (usize, Round, HashValue),
+        second: (usize, Round, HashValue),
+    },
+    /// An honest node did not commit a block after the last round of the scenario in time
+    NoProgress {
+        node: usize,
+        highest_committed_round: Round,
+    },
+}
+
+impl fmt::Display for TwinsViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TwinsViolation::ConflictingCommits { first, second } => write!(
+                f,
+                "Safety violation: node {} committed {} in round {} and node {} committed {} in \
+                round {}, which are not on the same chain",
+                first.0, first.2, first.1, second.0, second.2, second.1
+            ),
+            TwinsViolation::NoProgress {
+                node,
+                highest_committed_round,
+            } => write!(
+                f,
+                "Liveness violation: node {} did not commit after round {}",
+                node, highest_committed_round
+            ),
+        }
+    }
+}
+
+/// A block committed by an honest node, with the round and id of its parent.
+#[derive(Clone, Copy, Debug)]
+pub struct CommittedBlock {
+    pub round: Round,
+    pub id: HashValue,
+    pub parent_round: Round,
+    pub parent_id: HashValue,
+}
+
+impl From<&PipelinedBlock> for CommittedBlock {
+    fn from(block: &PipelinedBlock) -> Self {
+        Self {
+            round: block.round(),
+            id: block.id(),
+            parent_round: block.quorum_cert().certified_block().round(),
+            parent_id: block.parent_id(),
+        }
+    }
+}
+
+/// The blocks committed by the honest nodes. As long as the chain committed by each node is a
+/// prefix of, or extends, the chains of the others, all blocks are on a single chain: there is
+/// at most one block per round, and no block between a block and its parent. A node which synced
+/// over some rounds does not commit the blocks of those rounds, so the chain can have gaps.
+#[derive(Default)]
+pub struct CommittedChain {
+    blocks: BTreeMap<Round, (usize, CommittedBlock)>,
+}
+
+impl CommittedChain {
+    /// Adds a block committed by the node, failing if it is not on the chain of the blocks
+    /// committed before.
+    pub fn add(&mut self, node: usize, block: CommittedBlock) -> Result<(), TwinsViolation> {
+        let conflict = |(other_node, other): &(usize, CommittedBlock)| {
+            Err(TwinsViolation::ConflictingCommits {
+                first: (*other_node, other.round, other.id),
+                second: (node, block.round, block.id),
+            })
+        };
+        if let Some(existing) = self.blocks.get(&block.round) {
+            return if existing.1.id == block.id {
+                Ok(())
+            } else {
+                conflict(existing)
+            };
+        }
+        // The closest block below is the parent or one of its ancestors
+        if let Some((_, below)) = self.blocks.range(..block.round).next_back() {
+            if below.1.round > block.parent_round
+                || (below.1.round == block.parent_round && below.1.id != block.parent_id)
+            {
+                return conflict(below);
+            }
+        }
+        // The closest block above is a descendant
+        if let Some((_, above)) = self.blocks.range(block.round + 1..).next() {
+            if above.1.parent_round < block.round
+                || (above.1.parent_round == block.round && above.1.parent_id != block.id)
+            {
+                return conflict(above);
+            }
+        }
+        self.blocks.insert(block.round, (node, block));
+        Ok(())
+    }
+}
+
+/// Runs scenarios against `SMRNode`s and checks the safety and liveness of the honest nodes.
+pub struct ScenarioRunner {
+    runtime: Runtime,
+    round_initial_timeout_ms: u64,
+    liveness_timeout: Duration,
+}
+
+impl ScenarioRunner {
+    pub fn new(round_initial_timeout_ms: u64, liveness_timeout: Duration) -> Self {
+        Self {
+            runtime: consensus_runtime(),
+            round_initial_timeout_ms,
+            liveness_timeout,
+        }
+    }
+
+    pub fn run(&self, scenario: &TwinsScenario) -> Result<(), TwinsViolation> {
+        let mut playground = NetworkPlayground::new(self.runtime.handle().clone());
+        let mut round_proposers = scenario.round_leaders();
+        for round in scenario.num_rounds() + 1..=scenario.num_rounds() + HONEST_LEADER_ROUNDS {
+            round_proposers.insert(round, scenario.num_twins);
+        }
+        let mut nodes = SMRNode::start_num_nodes_with_twins_and_round_timeout(
+            scenario.num_nodes,
+            scenario.num_twins,
+            &mut playground,
+            RoundProposer(HashMap::new()),
+            Some(round_proposers),
+            self.round_initial_timeout_ms,
+        );
+
+        let twin_ids: Vec<TwinId> = nodes.iter().map(|node| node.id).collect();
+        let round_partitions: HashMap<Round, Vec<Vec<TwinId>>> = scenario
+            .round_partitions()
+            .into_iter()
+            .map(|(round, partitions)| {
+                let partitions = partitions
+                    .iter()
+                    .map(|partition| partition.iter().map(|node| twin_ids[*node]).collect())
+                    .collect();
+                (round, partitions)
+            })
+            .collect();
+        playground.split_network_round(&round_partitions);
+        self.runtime.spawn(playground.start());
+
+        self.runtime.block_on(self.check(scenario, &mut nodes))
+    }
+
+    /// Collects the blocks committed by the honest nodes until all of them committed a block
+    /// after the last round of the scenario, failing on the first block which is not on the chain
+    /// committed before.
+    async fn check(
+        &self,
+        scenario: &TwinsScenario,
+        nodes: &mut [SMRNode],
+    ) -> Result<(), TwinsViolation> {
+        let honest_nodes: Vec<usize> = scenario.honest_nodes().collect();
+        let mut committed = CommittedChain::default();
+        let mut highest_committed_round = vec![0; nodes.len()];
+        let deadline = Instant::now() + self.liveness_timeout;
+        loop {
+            for node in &honest_nodes {
+                while let Ok(Some(blocks)) = nodes[*node].committed_blocks_receiver.try_next() {
+                    for block in &blocks {
+                        committed.add(*node, block.into())?;
+                        highest_committed_round[*node] =
+                            highest_committed_round[*node].max(block.round());
+                    }
+                }
+            }
+
+            let stalled = honest_nodes
+                .iter()
+                .find(|node| highest_committed_round[**node] <= scenario.num_rounds());
+            match stalled {
+                None => return Ok(()),
+                Some(node) if Instant::now() >= deadline => {
+                    return Err(TwinsViolation::NoProgress {
+                        node: *node,
+                        highest_committed_round: highest_committed_round[*node],
+                    });
+                },
+                Some(_) => tokio::time::sleep(Duration::from_millis(100)).await,
+            }
+        }
+    }
+}
@@ -22,7 +22,10 @@ use aptos_config::{
     generator::{self, ValidatorSwarm},
     network_id::{NetworkId, PeerNetworkId},
 };
-use aptos_consensus_types::common::{Author, Round};
+use aptos_consensus_types::{
+    common::{Author, Round},
+    pipelined_block::PipelinedBlock,
+};
 use aptos_event_notifications::{ReconfigNotification, ReconfigNotificationListener};
 use aptos_mempool::mocks::MockSharedMempool;
 use aptos_network::{
@@ -59,6 +62,8 @@ pub struct SMRNode {
     pub id: TwinId,
     pub storage: Arc<MockStorage>,
     pub commit_cb_receiver: mpsc::UnboundedReceiver<LedgerInfoWithSignatures>,
+    /// The blocks ordered by each commit, in the order of their rounds
+    pub committed_blocks_receiver: mpsc::UnboundedReceiver<Vec<PipelinedBlock>>,
     _runtime: Runtime,
     _shared_mempool: MockSharedMempool,
     _state_sync: mpsc::UnboundedReceiver<Vec<SignedTransaction>>,
@@ -173,16 +178,20 @@ impl SMRNode {
         runtime.spawn(epoch_mgr.start(timeout_receiver, network_receiver));
 
         let (commit_cb_sender, commit_cb_receiver) = mpsc::unbounded::<LedgerInfoWithSignatures>();
+        let (committed_blocks_sender, committed_blocks_receiver) =
+            mpsc::unbounded::<Vec<PipelinedBlock>>();
         runtime.spawn(async move {
             loop {
                 let ordered_blocks = ordered_blocks_events.next().await.unwrap();
                 let commit = ordered_blocks.ordered_proof.clone();
+                let blocks = ordered_blocks.ordered_blocks.clone();
                 execution_client
                     .commit_to_storage(ordered_blocks)
                     .await
                     .unwrap();
 
                 commit_cb_sender.unbounded_send(commit.clone()).unwrap();
+                committed_blocks_sender.unbounded_send(blocks).unwrap();
             }
         });
 
@@ -190,6 +199,7 @@ impl SMRNode {
             id: twin_id,
             _runtime: runtime,
             commit_cb_receiver,
+            committed_blocks_receiver,
             storage,
             _shared_mempool: shared_mempool,
             _state_sync: state_sync,
@@ -203,6 +213,26 @@ impl SMRNode {
         playground: &mut NetworkPlayground,
         proposer_type: ProposerElectionType,
         round_proposers_idx: Option<HashMap<Round, usize>>,
+    ) -> Vec<Self> {
+        // Disable timeout in twins test to avoid flakiness
+        Self::start_num_nodes_with_twins_and_round_timeout(
+            num_nodes,
+            num_twins,
+            playground,
+            proposer_type,
+            round_proposers_idx,
+            2_000_000,
+        )
+    }
+
+    /// Starts a given number of nodes and their twins, with the given initial round timeout
+    pub fn start_num_nodes_with_twins_and_round_timeout(
+        num_nodes: usize,
+        num_twins: usize,
+        playground: &mut NetworkPlayground,
+        proposer_type: ProposerElectionType,
+        round_proposers_idx: Option<HashMap<Round, usize>>,
+        round_initial_timeout_ms: u64,
     ) -> Vec<Self> {
         assert!(num_nodes >= num_twins);
         let ValidatorSwarm {
@@ -278,8 +308,7 @@ impl SMRNode {
                 .unwrap()
                 .waypoint = Some(waypoint);
             config.base.waypoint = WaypointConfig::FromConfig(waypoint);
-            // Disable timeout in twins test to avoid flakiness
-            config.consensus.round_initial_timeout_ms = 2_000_000;
+            config.consensus.round_initial_timeout_ms = round_initial_timeout_ms;
 
             let author = author_from_config(&config);
 
@@ -37,6 +37,46 @@ pub async fn handle_dump_consensus_db_request(
     }
 }
 
+pub async fn handle_dump_evidence_request(
+    req: Request<Body>,
+    consensus_db: Arc<dyn PersistentLivenessStorage>,
+) -> hyper::Result<Response<Body>> {
+    let query = req.uri().query().unwrap_or("");
+    let query_pairs: HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes()).collect();
+
+    let bcs: bool = match query_pairs.get("bcs") {
+        Some(val) => match val.parse() {
+            Ok(val) => val,
+            Err(err) => return Ok(reply_with_status(StatusCode::BAD_REQUEST, err.to_string())),
+        },
+        None => false,
+    };
+
+    info!("Dumping equivocation evidence.");
+
+    match spawn_blocking(move || {
+        if bcs {
+            dump_evidence_bcs(consensus_db.as_ref()).map(Into::<Body>::into)
+        } else {
+            dump_evidence(consensus_db.as_ref()).map(Into::into)
+        }
+    })
+    .await
+    {
+        Ok(result) => {
+            info!("Finished dumping equivocation evidence.");
+            Ok(reply_with(vec![], result))
+        },
+        Err(e) => {
+            info!("Failed to dump equivocation evidence: {e:?}");
+            Ok(reply_with_status(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                e.to_string(),
+            ))
+        },
+    }
+}
+
 pub async fn handle_dump_quorum_store_db_request(
     req: Request<Body>,
     quorum_store_db: Arc<dyn QuorumStoreStorage>,
@@ -155,6 +195,25 @@ fn dump_consensus_db(consensus_db: &dyn PersistentLivenessStorage) -> anyhow::Re
     Ok(body)
 }
 
+fn dump_evidence(consensus_db: &dyn PersistentLivenessStorage) -> anyhow::Result<String> {
+    let mut body = String::new();
+
+    for evidence in consensus_db.consensus_db().get_all_evidence()? {
+        body.push_str(&format!("{evidence}:\n{evidence:?}\n\n"));
+    }
+
+    if body.is_empty() {
+        body.push_str("Done, no evidence is found.");
+    }
+
+    Ok(body)
+}
+
+fn dump_evidence_bcs(consensus_db: &dyn PersistentLivenessStorage) -> anyhow::Result<Vec<u8>> {
+    let evidence = consensus_db.consensus_db().get_all_evidence()?;
+    bcs::to_bytes(&evidence).map_err(Error::msg)
+}
+
 fn dump_quorum_store_db(
     quorum_store_db: &dyn QuorumStoreStorage,
     digest: Option<HashValue>,
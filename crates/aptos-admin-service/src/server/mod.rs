@@ -182,6 +182,17 @@ impl AdminService {
                     ))
                 }
             },
+            (hyper::Method::GET, "/debug/consensus/evidence") => {
+                let consensus_db = context.consensus_db.read().clone();
+                if let Some(consensus_db) = consensus_db {
+                    consensus::handle_dump_evidence_request(req, consensus_db).await
+                } else {
+                    Ok(reply_with_status(
+                        StatusCode::NOT_FOUND,
+                        "Consensus db is not available.",
+                    ))
+                }
+            },
             (hyper::Method::GET, "/debug/consensus/quorumstoredb") => {
                 let quorum_store_db = context.quorum_store_db.read().clone();
                 if let Some(quorum_store_db) = quorum_store_db {
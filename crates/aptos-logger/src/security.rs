@@ -44,6 +44,9 @@ pub enum SecurityEvent {
     /// Consensus received an equivocating order vote
     ConsensusEquivocatingOrderVote,
 
+    /// Consensus saved evidence of an equivocation
+    ConsensusEquivocationEvidence,
+
     /// Consensus received an invalid proposal
     InvalidConsensusProposal,
 
@@ -80,6 +83,9 @@ pub enum SecurityEvent {
     /// A failed noise handshake that's either a clear bug or indicates some
     /// security issue.
     NoiseHandshake,
+
+    /// Peer exchange received an invalid or conflicting peer record
+    InvalidPeerExchangeRecord,
 }
 
 impl Schema for SecurityEvent {
@@ -1061,6 +1061,17 @@ pub struct MovePackageDir {
     #[clap(long)]
     pub(crate) skip_fetch_latest_git_deps: bool,
 
+    /// Fail instead of updating the Move.lock file if it is missing or out of date
+    ///
+    /// The Move.lock file pins the commits of git dependencies. Use this in CI to make sure
+    /// builds use exactly the dependencies recorded in it.
+    #[clap(long)]
+    pub(crate) locked: bool,
+
+    /// Like `--locked`, and additionally never fetch git dependencies from the network
+    #[clap(long)]
+    pub(crate) frozen: bool,
+
     /// Specify the version of the bytecode the compiler is going to emit.
     #[clap(long)]
     pub bytecode_version: Option<u32>,
@@ -1095,6 +1106,8 @@ impl MovePackageDir {
             named_addresses: Default::default(),
             override_std: None,
             skip_fetch_latest_git_deps: true,
+            locked: false,
+            frozen: false,
             bytecode_version: None,
             compiler_version: None,
             language_version: None,
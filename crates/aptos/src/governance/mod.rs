@@ -1000,6 +1000,8 @@ impl CliCommand<()> for GenerateUpgradeProposal {
         let options = included_artifacts.build_options(
             move_options.dev,
             move_options.skip_fetch_latest_git_deps,
+            move_options.locked,
+            move_options.frozen,
             move_options.named_addresses(),
             move_options.override_std,
             move_options.bytecode_version,
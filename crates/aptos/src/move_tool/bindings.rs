@@ -0,0 +1,151 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{CachedPackageRegistry, IncludedArtifacts};
+use crate::common::{
+    types::{
+        CliCommand, CliError, CliTypedResult, MovePackageDir, ProfileOptions, PromptOptions,
+        RestOptions,
+    },
+    utils::{check_if_file_exists, write_to_file},
+};
+use aptos_framework::{bindings::generate_rust_bindings, BuildOptions, BuiltPackage};
+use aptos_rest_client::Client;
+use aptos_types::account_address::AccountAddress;
+use async_trait::async_trait;
+use clap::Parser;
+use move_binary_format::{access::ModuleAccess, CompiledModule};
+use move_core_types::language_storage::ModuleId;
+use std::{collections::BTreeSet, path::PathBuf};
+
+/// Generates typed Rust bindings for a Move package
+///
+/// The bindings contain serde structs for the structs of the package, functions to fetch its
+/// resources and call its view functions, and a decoder for its events. The package is either
+/// compiled from a local directory, or fetched from chain with `--account` and `--package`.
+#[derive(Parser)]
+pub struct GenerateBindings {
+    /// Address of the account containing the package on chain
+    ///
+    /// If not provided, the package is compiled locally.
+    #[clap(long, value_parser = crate::common::types::load_account_arg, requires = "package")]
+    pub(crate) account: Option<AccountAddress>,
+
+    /// Name of the package on chain
+    #[clap(long, requires = "account")]
+    pub(crate) package: Option<String>,
+
+    /// File to write the generated Rust code to
+    #[clap(long, value_parser)]
+    pub(crate) output_file: PathBuf,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    #[clap(flatten)]
+    pub(crate) prompt_options: PromptOptions,
+}
+
+#[async_trait]
+impl CliCommand<String> for GenerateBindings {
+    fn command_name(&self) -> &'static str {
+        "GenerateBindings"
+    }
+
+    async fn execute(self) -> CliTypedResult<String> {
+        check_if_file_exists(self.output_file.as_path(), self.prompt_options)?;
+        let (package, dependencies) = match (&self.account, &self.package) {
+            (Some(account), Some(package)) => self.fetch_package(*account, package).await?,
+            _ => self.build_package()?,
+        };
+        let code = generate_rust_bindings(&package, &dependencies)
+            .map_err(|err| CliError::UnexpectedError(format!("{:#}", err)))?;
+        write_to_file(
+            self.output_file.as_path(),
+            &self.output_file.display().to_string(),
+            code.as_bytes(),
+        )?;
+        Ok(self.output_file.display().to_string())
+    }
+}
+
+impl GenerateBindings {
+    /// Compiles the local package, returning its modules and the modules of its dependencies.
+    fn build_package(&self) -> CliTypedResult<(Vec<CompiledModule>, Vec<CompiledModule>)> {
+        let move_options = &self.move_options;
+        let build_options = BuildOptions {
+            install_dir: move_options.output_dir.clone(),
+            ..IncludedArtifacts::None.build_options(
+                move_options.dev,
+                move_options.skip_fetch_latest_git_deps,
+                move_options.locked,
+                move_options.frozen,
+                move_options.named_addresses(),
+                move_options.override_std.clone(),
+                move_options.bytecode_version,
+                move_options.compiler_version,
+                move_options.language_version,
+                move_options.skip_attribute_checks,
+                move_options.check_test_code,
+            )
+        };
+        let package = BuiltPackage::build(move_options.get_package_path()?, build_options)
+            .map_err(|e| CliError::MoveCompilationError(format!("{:#}", e)))?;
+        Ok((
+            package.modules().cloned().collect(),
+            package.all_modules().cloned().collect(),
+        ))
+    }
+
+    /// Fetches the package from chain, along with the modules it transitively depends on.
+    async fn fetch_package(
+        &self,
+        account: AccountAddress,
+        package_name: &str,
+    ) -> CliTypedResult<(Vec<CompiledModule>, Vec<CompiledModule>)> {
+        let url = self.rest_options.url(&self.profile_options)?;
+        let registry = CachedPackageRegistry::create(url.clone(), account, true).await?;
+        let package = registry
+            .get_package(package_name)
+            .await
+            .map_err(|s| CliError::CommandArgumentError(s.to_string()))?;
+        let mut modules = vec![];
+        for name in package.module_names() {
+            let bytecode = registry.get_bytecode(name).await?.ok_or_else(|| {
+                CliError::UnexpectedError(format!("Bytecode of module `{}` not found", name))
+            })?;
+            modules.push(deserialize_module(bytecode)?);
+        }
+
+        let client = Client::new(url);
+        let mut known: BTreeSet<ModuleId> = modules.iter().map(|m| m.self_id()).collect();
+        let mut pending: Vec<ModuleId> = modules
+            .iter()
+            .flat_map(|m| m.immediate_dependencies())
+            .collect();
+        let mut dependencies = vec![];
+        while let Some(id) = pending.pop() {
+            if !known.insert(id.clone()) {
+                continue;
+            }
+            let bytecode = client
+                .get_account_module(*id.address(), id.name().as_str())
+                .await?
+                .into_inner()
+                .bytecode
+                .0;
+            let module = deserialize_module(&bytecode)?;
+            pending.extend(module.immediate_dependencies());
+            dependencies.push(module);
+        }
+        Ok((modules, dependencies))
+    }
+}
+
+fn deserialize_module(bytecode: &[u8]) -> CliTypedResult<CompiledModule> {
+    CompiledModule::deserialize(bytecode)
+        .map_err(|err| CliError::UnexpectedError(format!("Invalid module bytecode: {}", err)))
+}
@@ -0,0 +1,256 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::types::{
+        CliCommand, CliError, CliTypedResult, MovePackageDir, ProfileOptions, RestOptions,
+    },
+    move_tool::{CachedPackageRegistry, IncludedArtifacts},
+};
+use aptos_framework::{natives::code::UpgradePolicy, BuildOptions, BuiltPackage};
+use aptos_types::{
+    account_address::AccountAddress,
+    on_chain_config::{FeatureFlag, Features},
+};
+use async_trait::async_trait;
+use clap::Parser;
+use move_binary_format::{
+    access::ModuleAccess,
+    compatibility::{Compatibility, Incompatibility},
+    file_format::{FunctionDefinitionIndex, StructDefinitionIndex},
+    normalized, CompiledModule,
+};
+use move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
+use move_package::compilation::compiled_package::CompiledUnitWithSource;
+use std::{collections::BTreeMap, fmt, fs, path::Path};
+
+/// Checks whether a package can be upgraded to the local version
+///
+/// Compares the local package against the package of the same name published at the given
+/// address, and reports every change which would make publishing the upgrade fail: a weaker or
+/// immutable upgrade policy, removed modules, module name clashes with other packages, and
+/// incompatible structs, functions and friend declarations. The checks are the same as the ones
+/// done on chain when the package is published.
+#[derive(Parser)]
+pub struct CheckUpgrade {
+    /// Address of the account containing the published package
+    #[clap(long, value_parser = crate::common::types::load_account_arg)]
+    pub(crate) account: AccountAddress,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+/// A change of the package which prevents the upgrade.
+struct UpgradeIssue {
+    /// The source location of the change in the local package, as `file:line:column`
+    location: Option<String>,
+    message: String,
+}
+
+impl fmt::Display for UpgradeIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "{}: {}", location, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+#[async_trait]
+impl CliCommand<&'static str> for CheckUpgrade {
+    fn command_name(&self) -> &'static str {
+        "CheckUpgrade"
+    }
+
+    async fn execute(self) -> CliTypedResult<&'static str> {
+        let build_options = BuildOptions {
+            install_dir: self.move_options.output_dir.clone(),
+            ..IncludedArtifacts::None.build_options(
+                self.move_options.dev,
+                self.move_options.skip_fetch_latest_git_deps,
+                self.move_options.locked,
+                self.move_options.frozen,
+                self.move_options.named_addresses(),
+                self.move_options.override_std.clone(),
+                self.move_options.bytecode_version,
+                self.move_options.compiler_version,
+                self.move_options.language_version,
+                self.move_options.skip_attribute_checks,
+                self.move_options.check_test_code,
+            )
+        };
+        let pack = BuiltPackage::build(self.move_options.get_package_path()?, build_options)
+            .map_err(|e| CliError::MoveCompilationError(format!("{:#}", e)))?;
+        let new_metadata = pack.extract_metadata()?;
+
+        let client = self.rest_options.client(&self.profile_options)?;
+        let registry = CachedPackageRegistry::create(
+            self.rest_options.url(&self.profile_options)?,
+            self.account,
+            true,
+        )
+        .await?;
+        let old_package = registry
+            .get_package(pack.name())
+            .await
+            .map_err(|err| CliError::CommandArgumentError(format!("{:#}", err)))?;
+        // Friend functions are treated like private functions when this feature is enabled
+        let features = client
+            .get_account_resource_bcs::<Features>(AccountAddress::ONE, "0x1::features::Features")
+            .await?
+            .into_inner();
+        let compatibility = Compatibility::new(
+            true,
+            true,
+            !features.is_enabled(FeatureFlag::TREAT_FRIEND_AS_PRIVATE),
+        );
+
+        let mut issues = vec![];
+        let old_policy = old_package.upgrade_policy();
+        let new_policy = new_metadata.upgrade_policy;
+        if old_policy == UpgradePolicy::immutable() {
+            issues.push(UpgradeIssue {
+                location: None,
+                message: "the published package is immutable".to_string(),
+            });
+        }
+        if new_policy == UpgradePolicy::arbitrary() {
+            issues.push(UpgradeIssue {
+                location: None,
+                message: "packages can no longer be published with the upgrade policy `arbitrary`"
+                    .to_string(),
+            });
+        } else if new_policy.policy < old_policy.policy {
+            issues.push(UpgradeIssue {
+                location: None,
+                message: format!(
+                    "the upgrade policy cannot be weakened from `{}` to `{}`",
+                    old_policy, new_policy
+                ),
+            });
+        }
+
+        let new_modules: BTreeMap<_, _> = pack
+            .package
+            .root_modules()
+            .filter_map(|unit| match &unit.unit {
+                CompiledUnit::Module(module) => Some((module.name.to_string(), (unit, module))),
+                CompiledUnit::Script(_) => None,
+            })
+            .collect();
+        for name in old_package.module_names() {
+            let Some((unit, new_module)) = new_modules.get(name) else {
+                issues.push(UpgradeIssue {
+                    location: None,
+                    message: format!("module `{}` was removed", name),
+                });
+                continue;
+            };
+            let Some(bytecode) = registry.get_bytecode(name).await? else {
+                continue;
+            };
+            let old_module = CompiledModule::deserialize(bytecode).map_err(|err| {
+                CliError::UnexpectedError(format!(
+                    "Unable to deserialize published module `{}`: {}",
+                    name, err
+                ))
+            })?;
+            for incompatibility in compatibility.incompatibilities(
+                &normalized::Module::new(&old_module),
+                &normalized::Module::new(&new_module.module),
+            ) {
+                issues.push(UpgradeIssue {
+                    location: incompatibility_location(unit, new_module, &incompatibility),
+                    message: format!("module `{}`: {}", name, incompatibility),
+                })
+            }
+        }
+
+        // Modules of the package must not clash with the ones of other packages at the address
+        for other_name in registry.package_names() {
+            if other_name == pack.name() {
+                continue;
+            }
+            let other_package = registry.get_package(other_name).await?;
+            for name in other_package.module_names() {
+                if let Some((unit, module)) = new_modules.get(name) {
+                    issues.push(UpgradeIssue {
+                        location: source_location(
+                            &unit.source_path,
+                            module.source_map.definition_location,
+                        ),
+                        message: format!(
+                            "module `{}` is already published by package `{}`",
+                            name, other_name
+                        ),
+                    })
+                }
+            }
+        }
+
+        if issues.is_empty() {
+            return Ok("Package can be upgraded");
+        }
+        for issue in &issues {
+            eprintln!("{}", issue);
+        }
+        Err(CliError::UnexpectedError(format!(
+            "Package `{}` cannot be upgraded: found {} incompatible change(s)",
+            pack.name(),
+            issues.len()
+        )))
+    }
+}
+
+/// Returns the location of the changed struct or function in the new module, or of the module
+/// for changes which are not part of the new module.
+fn incompatibility_location(
+    unit: &CompiledUnitWithSource,
+    module: &NamedCompiledModule,
+    incompatibility: &Incompatibility,
+) -> Option<String> {
+    let compiled = &module.module;
+    let loc = match incompatibility {
+        Incompatibility::StructAbilitiesRemoved { name, .. }
+        | Incompatibility::StructTypeParametersChanged(name)
+        | Incompatibility::StructLayoutChanged(name) => {
+            let idx = compiled.struct_defs().iter().position(|def| {
+                compiled.identifier_at(compiled.struct_handle_at(def.struct_handle).name) == name
+            })?;
+            module
+                .source_map
+                .get_struct_source_map(StructDefinitionIndex(idx as u16))
+                .ok()?
+                .definition_location
+        },
+        Incompatibility::FunctionSignatureChanged(name, _) => {
+            let idx = compiled.function_defs().iter().position(|def| {
+                compiled.identifier_at(compiled.function_handle_at(def.function).name) == name
+            })?;
+            module
+                .source_map
+                .get_function_source_map(FunctionDefinitionIndex(idx as u16))
+                .ok()?
+                .definition_location
+        },
+        Incompatibility::ModuleIdChanged
+        | Incompatibility::StructRemoved(_)
+        | Incompatibility::FunctionRemoved(..)
+        | Incompatibility::FriendRemoved(_) => module.source_map.definition_location,
+    };
+    source_location(&unit.source_path, loc)
+}
+
+/// Formats the start of the location as `file:line:column`.
+fn source_location(path: &Path, loc: move_ir_types::location::Loc) -> Option<String> {
+    let source = fs::read_to_string(path).ok()?;
+    let before = source.get(..loc.start() as usize)?;
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |idx| idx + 1) + 1;
+    Some(format!("{}:{}:{}", path.display(), line, column))
+}
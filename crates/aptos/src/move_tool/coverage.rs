@@ -1,17 +1,21 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::common::types::{CliCommand, CliError, CliResult, CliTypedResult, MovePackageDir};
+use crate::common::{
+    types::{CliCommand, CliError, CliResult, CliTypedResult, MovePackageDir, PromptOptions},
+    utils::{check_if_file_exists, write_to_file},
+};
 use aptos_framework::extended_checks;
 use async_trait::async_trait;
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
 use move_coverage::{
-    coverage_map::CoverageMap, format_csv_summary, format_human_summary,
+    coverage_map::CoverageMap, export::CoverageReport, format_csv_summary, format_human_summary,
     source_coverage::SourceCoverageBuilder, summary::summarize_inst_cov,
 };
 use move_disassembler::disassembler::Disassembler;
 use move_package::{compilation::compiled_package::CompiledPackage, BuildConfig, CompilerConfig};
+use std::path::PathBuf;
 
 /// Display a coverage summary for all modules in a package
 ///
@@ -142,6 +146,115 @@ impl CliCommand<()> for BytecodeCoverage {
     }
 }
 
+/// The format to export coverage in
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum CoverageFormat {
+    Lcov,
+    Cobertura,
+}
+
+/// Export line and branch coverage of the source code in the LCOV or Cobertura XML format
+///
+/// Coverage of other test runs can be merged in with `--coverage-maps`, and other packages can be
+/// included with `--additional-package-dirs`. Source paths are reported relative to the current
+/// directory.
+#[derive(Debug, Parser)]
+pub struct ExportCoverage {
+    /// The format to export coverage in
+    #[clap(long, value_enum, default_value_t = CoverageFormat::Lcov)]
+    pub format: CoverageFormat,
+    /// The file to write the coverage to
+    #[clap(long, value_parser)]
+    pub output_file: PathBuf,
+    /// Coverage maps of other test runs to merge, as written by `aptos move test --coverage`
+    #[clap(long, value_parser, num_args = 0..)]
+    pub coverage_maps: Vec<PathBuf>,
+    /// Directories of other packages whose coverage is included in the report
+    ///
+    /// Each package must have been tested with `--coverage`. The packages are compiled with the
+    /// same options as the main package.
+    #[clap(long, value_parser, num_args = 0..)]
+    pub additional_package_dirs: Vec<PathBuf>,
+    #[clap(flatten)]
+    pub move_options: MovePackageDir,
+    #[clap(flatten)]
+    pub prompt_options: PromptOptions,
+}
+
+#[async_trait]
+impl CliCommand<String> for ExportCoverage {
+    fn command_name(&self) -> &'static str {
+        "ExportCoverage"
+    }
+
+    async fn execute(self) -> CliTypedResult<String> {
+        check_if_file_exists(self.output_file.as_path(), self.prompt_options)?;
+        let mut package_dirs = vec![self.move_options.get_package_path()?];
+        package_dirs.extend(self.additional_package_dirs.iter().cloned());
+
+        // A test run of one package may also cover modules of the others, so the coverage of all
+        // runs is merged before computing the coverage of each package.
+        let mut coverage_map = CoverageMap {
+            exec_maps: Default::default(),
+        };
+        let mut packages = vec![];
+        for package_dir in package_dirs {
+            let (package_coverage_map, package) = compile_coverage(MovePackageDir {
+                package_dir: Some(package_dir),
+                ..self.move_options.clone()
+            })?;
+            coverage_map.merge(package_coverage_map);
+            packages.push(package);
+        }
+        for path in &self.coverage_maps {
+            coverage_map.merge(CoverageMap::from_binary_file(path).map_err(|err| {
+                CliError::UnexpectedError(format!("Failed to retrieve coverage map {}", err))
+            })?);
+        }
+        let coverage_map = coverage_map.to_unified_exec_map();
+
+        let current_dir =
+            std::env::current_dir().map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        let mut report = CoverageReport::new();
+        for package in &packages {
+            let package_name = package.compiled_package_info.package_name.as_str();
+            for unit in package.root_modules() {
+                if let CompiledUnit::Module(NamedCompiledModule {
+                    module, source_map, ..
+                }) = &unit.unit
+                {
+                    let source_path = unit
+                        .source_path
+                        .strip_prefix(&current_dir)
+                        .unwrap_or(&unit.source_path);
+                    report
+                        .add_module(package_name, module, source_map, source_path, &coverage_map)
+                        .map_err(|err| {
+                            CliError::UnexpectedError(format!(
+                                "Failed to compute coverage of {}: {:#}",
+                                source_path.display(),
+                                err
+                            ))
+                        })?;
+                }
+            }
+        }
+
+        let mut output = vec![];
+        match self.format {
+            CoverageFormat::Lcov => report.write_lcov(&mut output),
+            CoverageFormat::Cobertura => report.write_cobertura(&mut output),
+        }
+        .map_err(|err| CliError::UnexpectedError(format!("Failed to export coverage {}", err)))?;
+        write_to_file(
+            self.output_file.as_path(),
+            &self.output_file.display().to_string(),
+            &output,
+        )?;
+        Ok(self.output_file.display().to_string())
+    }
+}
+
 fn compile_coverage(
     move_options: MovePackageDir,
 ) -> CliTypedResult<(CoverageMap, CompiledPackage)> {
@@ -178,6 +291,7 @@ pub enum CoveragePackage {
     Summary(SummaryCoverage),
     Source(SourceCoverage),
     Bytecode(BytecodeCoverage),
+    Export(ExportCoverage),
 }
 
 impl CoveragePackage {
@@ -186,6 +300,7 @@ impl CoveragePackage {
             Self::Summary(tool) => tool.execute_serialized_success().await,
             Self::Source(tool) => tool.execute_serialized_success().await,
             Self::Bytecode(tool) => tool.execute_serialized_success().await,
+            Self::Export(tool) => tool.execute_serialized().await,
         }
     }
 }
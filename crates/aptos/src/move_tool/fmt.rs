@@ -0,0 +1,81 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{
+    types::{CliCommand, CliError, CliTypedResult, MovePackageDir},
+    utils::read_from_file,
+};
+use async_trait::async_trait;
+use clap::Parser;
+use move_command_line_common::files::find_move_filenames;
+use move_formatter::{format_source, FormatConfig, CONFIG_FILE_NAME};
+use move_package::source_package::layout::SourcePackageLayout;
+use std::{fs, path::PathBuf};
+
+/// Format the Move source files of a package
+///
+/// Formats all Move files in the `sources`, `specs`, `tests`, `scripts` and `examples`
+/// directories of the package. Comments are preserved. The formatter is configured by a
+/// `movefmt.toml` file in the package root, e.g. `max_width = 100`, `indent_size = 4` and
+/// `reorder_imports = true`.
+#[derive(Parser)]
+pub struct FormatPackage {
+    /// Only check whether the files are formatted, failing if any isn't, instead of formatting
+    /// them
+    #[clap(long)]
+    pub check: bool,
+
+    #[clap(flatten)]
+    pub move_options: MovePackageDir,
+}
+
+#[async_trait]
+impl CliCommand<Vec<String>> for FormatPackage {
+    fn command_name(&self) -> &'static str {
+        "FormatPackage"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<String>> {
+        let package_path = self.move_options.get_package_path()?;
+        let config = FormatConfig::load(&package_path)
+            .map_err(|err| CliError::UnableToParse(CONFIG_FILE_NAME, format!("{:#}", err)))?;
+        let dirs = [
+            SourcePackageLayout::Sources,
+            SourcePackageLayout::Specifications,
+            SourcePackageLayout::Tests,
+            SourcePackageLayout::Scripts,
+            SourcePackageLayout::Examples,
+        ]
+        .iter()
+        .map(|layout| package_path.join(layout.path()))
+        .filter(|dir| dir.is_dir())
+        .collect::<Vec<_>>();
+        let files = find_move_filenames(&dirs, false)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+
+        // The files which are (or, when checking, would be) changed by formatting
+        let mut changed = vec![];
+        for file in files {
+            let path = PathBuf::from(&file);
+            let source = String::from_utf8(read_from_file(&path)?)
+                .map_err(|err| CliError::UnableToReadFile(file.clone(), err.to_string()))?;
+            let formatted = format_source(&file, &source, &config)
+                .map_err(|err| CliError::MoveCompilationError(format!("{:#}", err)))?;
+            if formatted == source {
+                continue;
+            }
+            if !self.check {
+                fs::write(&path, formatted).map_err(|err| CliError::IO(file.clone(), err))?;
+            }
+            changed.push(file);
+        }
+
+        if self.check && !changed.is_empty() {
+            return Err(CliError::UnexpectedError(format!(
+                "The following files are not formatted, run `aptos move fmt` to format them:\n{}",
+                changed.join("\n")
+            )));
+        }
+        Ok(changed)
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{CliCommand, CliError, CliTypedResult, MovePackageDir};
+use aptos_framework::extended_checks;
+use async_trait::async_trait;
+use clap::Parser;
+use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
+use move_compiler_v2::{
+    lint::{all_lints, LintConfig, LintLevel},
+    run_move_compiler, Experiment,
+};
+use move_model::metadata::CompilerVersion;
+use move_package::{
+    compilation::model_builder::ModelBuilder, BuildConfig, CompilerConfig, ModelConfig,
+};
+use serde::Serialize;
+
+/// Run the lints on a Move package
+///
+/// The lints look for common pitfalls in Aptos contracts, such as precision loss in fee
+/// arithmetic, mutable global borrows in view functions, or public functions handing out signer
+/// capabilities. Lints can be suppressed for a function or module with `#[lint::skip(<lint>)]`.
+/// Use `--list` to show all lints. The result lists the lints with the level they ran at.
+#[derive(Parser)]
+pub struct LintPackage {
+    /// Lints to turn off
+    #[clap(long, num_args = 0..)]
+    pub allow: Vec<String>,
+
+    /// Lints to report as warnings
+    #[clap(long, num_args = 0..)]
+    pub warn: Vec<String>,
+
+    /// Lints to report as errors, failing the command
+    #[clap(long, num_args = 0..)]
+    pub deny: Vec<String>,
+
+    /// Show the available lints and their default levels instead of running them
+    #[clap(long)]
+    pub list: bool,
+
+    #[clap(flatten)]
+    pub move_options: MovePackageDir,
+}
+
+/// A lint and the level it is reported at
+#[derive(Debug, Serialize)]
+pub struct LintSummary {
+    pub name: &'static str,
+    pub level: String,
+    pub description: &'static str,
+}
+
+#[async_trait]
+impl CliCommand<Vec<LintSummary>> for LintPackage {
+    fn command_name(&self) -> &'static str {
+        "LintPackage"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<LintSummary>> {
+        let mut config = LintConfig::default();
+        for (names, level) in [
+            (&self.allow, LintLevel::Allow),
+            (&self.warn, LintLevel::Warn),
+            (&self.deny, LintLevel::Deny),
+        ] {
+            for name in names {
+                config
+                    .set_level(name, level)
+                    .map_err(|err| CliError::CommandArgumentError(err.to_string()))?;
+            }
+        }
+        let lints = all_lints()
+            .into_iter()
+            .map(|(name, description, level)| LintSummary {
+                name,
+                level: config.level(name, level).to_string(),
+                description,
+            })
+            .collect();
+        if self.list {
+            return Ok(lints);
+        }
+
+        // Lints run as part of the v2 compiler, which reports their diagnostics.
+        let move_options = self.move_options;
+        let build_config = BuildConfig {
+            dev_mode: move_options.dev,
+            additional_named_addresses: move_options.named_addresses(),
+            override_std: move_options.override_std.clone(),
+            skip_fetch_latest_git_deps: move_options.skip_fetch_latest_git_deps,
+            locked: move_options.locked,
+            frozen: move_options.frozen,
+            compiler_config: CompilerConfig {
+                bytecode_version: move_options.bytecode_version,
+                compiler_version: Some(CompilerVersion::V2_0),
+                language_version: move_options.language_version,
+                skip_attribute_checks: move_options.skip_attribute_checks,
+                known_attributes: extended_checks::get_all_attribute_names().clone(),
+            },
+            ..Default::default()
+        };
+        let mut options = build_config
+            .resolution_graph_for_package(&move_options.get_package_path()?, &mut Vec::new())
+            .and_then(|resolved_graph| {
+                ModelBuilder::create(resolved_graph, ModelConfig {
+                    target_filter: None,
+                    all_files_as_targets: false,
+                    compiler_version: CompilerVersion::V2_0,
+                    language_version: move_options.language_version.unwrap_or_default(),
+                })
+                .compiler_v2_options()
+            })
+            .map_err(|err| CliError::MoveCompilationError(format!("{:#}", err)))?
+            .set_experiment(Experiment::LINT_CHECKS, true);
+        options.lint_config = config;
+
+        let mut error_writer = StandardStream::stderr(ColorChoice::Auto);
+        run_move_compiler(&mut error_writer, options)
+            .map_err(|err| CliError::MoveCompilationError(format!("{:#}", err)))?;
+        Ok(lints)
+    }
+}
@@ -0,0 +1,66 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{AccountAddressWrapper, CliCommand, CliError, CliTypedResult};
+use aptos_framework::extended_checks;
+use async_trait::async_trait;
+use clap::Parser;
+use move_model::metadata::{CompilerVersion, LanguageVersion};
+use move_package::{BuildConfig, CompilerConfig};
+use std::collections::BTreeMap;
+
+/// Run the Move language server
+///
+/// The server communicates over stdin and stdout using the Language Server Protocol, and is
+/// meant to be started by an editor. It provides diagnostics from the v2 compiler, go to
+/// definition, find references, hover and completion for the packages of the opened files.
+/// Dependencies are resolved without fetching the latest versions of git dependencies.
+#[derive(Parser)]
+pub struct LanguageServer {
+    /// Enables dev mode, which uses all dev-addresses and dev-dependencies
+    #[clap(long)]
+    pub dev: bool,
+
+    /// Named addresses for the move binary
+    ///
+    /// Example: alice=0x1234, bob=0x5678
+    #[clap(long, value_parser = crate::common::utils::parse_map::<String, AccountAddressWrapper>, default_value = "")]
+    pub(crate) named_addresses: BTreeMap<String, AccountAddressWrapper>,
+
+    /// Specify the language version to be supported.
+    #[clap(long, value_parser = clap::value_parser!(LanguageVersion))]
+    pub language_version: Option<LanguageVersion>,
+
+    /// Do not complain about unknown attributes in Move code.
+    #[clap(long)]
+    pub skip_attribute_checks: bool,
+}
+
+#[async_trait]
+impl CliCommand<&'static str> for LanguageServer {
+    fn command_name(&self) -> &'static str {
+        "LanguageServer"
+    }
+
+    async fn execute(self) -> CliTypedResult<&'static str> {
+        let build_config = BuildConfig {
+            dev_mode: self.dev,
+            additional_named_addresses: self
+                .named_addresses
+                .into_iter()
+                .map(|(name, address)| (name, address.account_address))
+                .collect(),
+            compiler_config: CompilerConfig {
+                compiler_version: Some(CompilerVersion::V2_0),
+                language_version: self.language_version,
+                skip_attribute_checks: self.skip_attribute_checks,
+                known_attributes: extended_checks::get_all_attribute_names().clone(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        move_language_server::run(build_config)
+            .map_err(|err| CliError::UnexpectedError(format!("{:#}", err)))?;
+        Ok("succeeded")
+    }
+}
@@ -19,9 +19,16 @@ use crate::{
     },
     governance::CompileScriptFunction,
     move_tool::{
+        bindings::GenerateBindings,
         bytecode::{Decompile, Disassemble},
+        check_upgrade::CheckUpgrade,
         coverage::SummaryCoverage,
+        fmt::FormatPackage,
+        lint::LintPackage,
+        lsp::LanguageServer,
         manifest::{Dependency, ManifestNamedAddress, MovePackageManifest, PackageInfo},
+        repl::Repl,
+        test_gas_meter::AptosGasMeterFactory,
     },
     CliCommand, CliResult,
 };
@@ -54,7 +61,7 @@ use move_package::{
     source_package::{layout::SourcePackageLayout, std_lib::StdVersion},
     BuildConfig, CompilerConfig,
 };
-use move_unit_test::UnitTestingConfig;
+use move_unit_test::{fuzzer::DEFAULT_FUZZ_ITERATIONS, UnitTestingConfig};
 pub use package_hooks::*;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -69,12 +76,19 @@ use tokio::task;
 use url::Url;
 
 mod aptos_debug_natives;
+mod bindings;
 mod bytecode;
+mod check_upgrade;
 pub mod coverage;
+mod fmt;
+mod lint;
+mod lsp;
 mod manifest;
 pub mod package_hooks;
+mod repl;
 mod show;
 pub mod stored_package;
+mod test_gas_meter;
 
 /// Tool for Move related operations
 ///
@@ -84,6 +98,7 @@ pub mod stored_package;
 #[derive(Subcommand)]
 pub enum MoveTool {
     BuildPublishPayload(BuildPublishPayload),
+    CheckUpgrade(CheckUpgrade),
     Clean(CleanPackage),
     Compile(CompilePackage),
     CompileScript(CompileScript),
@@ -96,15 +111,21 @@ pub enum MoveTool {
     Decompile(Decompile),
     Document(DocumentPackage),
     Download(DownloadPackage),
+    Fmt(FormatPackage),
+    GenerateBindings(GenerateBindings),
     Init(InitPackage),
+    Lint(LintPackage),
     List(ListPackage),
+    Lsp(LanguageServer),
     Prove(ProvePackage),
     Publish(PublishPackage),
+    Repl(Repl),
     Run(RunFunction),
     RunScript(RunScript),
     #[clap(subcommand, hide = true)]
     Show(show::ShowTool),
     Test(TestPackage),
+    UpdateDeps(UpdateDeps),
     VerifyPackage(VerifyPackage),
     View(ViewFunction),
     Replay(Replay),
@@ -114,6 +135,7 @@ impl MoveTool {
     pub async fn execute(self) -> CliResult {
         match self {
             MoveTool::BuildPublishPayload(tool) => tool.execute_serialized().await,
+            MoveTool::CheckUpgrade(tool) => tool.execute_serialized().await,
             MoveTool::Clean(tool) => tool.execute_serialized().await,
             MoveTool::Compile(tool) => tool.execute_serialized().await,
             MoveTool::CompileScript(tool) => tool.execute_serialized().await,
@@ -129,14 +151,20 @@ impl MoveTool {
             MoveTool::Decompile(tool) => tool.execute_serialized().await,
             MoveTool::Document(tool) => tool.execute_serialized().await,
             MoveTool::Download(tool) => tool.execute_serialized().await,
+            MoveTool::Fmt(tool) => tool.execute_serialized().await,
+            MoveTool::GenerateBindings(tool) => tool.execute_serialized().await,
             MoveTool::Init(tool) => tool.execute_serialized_success().await,
+            MoveTool::Lint(tool) => tool.execute_serialized().await,
             MoveTool::List(tool) => tool.execute_serialized().await,
+            MoveTool::Lsp(tool) => tool.execute_serialized_without_logger().await,
             MoveTool::Prove(tool) => tool.execute_serialized().await,
             MoveTool::Publish(tool) => tool.execute_serialized().await,
+            MoveTool::Repl(tool) => tool.execute_serialized_without_logger().await,
             MoveTool::Run(tool) => tool.execute_serialized().await,
             MoveTool::RunScript(tool) => tool.execute_serialized().await,
             MoveTool::Show(tool) => tool.execute_serialized().await,
             MoveTool::Test(tool) => tool.execute_serialized().await,
+            MoveTool::UpdateDeps(tool) => tool.execute_serialized().await,
             MoveTool::VerifyPackage(tool) => tool.execute_serialized().await,
             MoveTool::View(tool) => tool.execute_serialized().await,
             MoveTool::Replay(tool) => tool.execute_serialized().await,
@@ -333,6 +361,8 @@ impl CliCommand<Vec<String>> for CompilePackage {
                 .build_options(
                     self.move_options.dev,
                     self.move_options.skip_fetch_latest_git_deps,
+                    self.move_options.locked,
+                    self.move_options.frozen,
                     self.move_options.named_addresses(),
                     self.move_options.override_std.clone(),
                     self.move_options.bytecode_version,
@@ -397,6 +427,8 @@ impl CompileScript {
             ..IncludedArtifacts::None.build_options(
                 self.move_options.dev,
                 self.move_options.skip_fetch_latest_git_deps,
+                self.move_options.locked,
+                self.move_options.frozen,
                 self.move_options.named_addresses(),
                 self.move_options.override_std.clone(),
                 self.move_options.bytecode_version,
@@ -467,6 +499,37 @@ pub struct TestPackage {
     /// Dump storage state on failure.
     #[clap(long = "dump")]
     pub dump_state: bool,
+
+    /// The number of times each `#[test(fuzz)]` test is run with random arguments
+    #[clap(long, default_value_t = DEFAULT_FUZZ_ITERATIONS)]
+    pub fuzz_iterations: u64,
+
+    /// The seed for generating the arguments of `#[test(fuzz)]` tests
+    ///
+    /// A failing fuzz test reports the seed it was run with, pass it here together with
+    /// `--filter` to replay the failure.
+    #[clap(long)]
+    pub fuzz_seed: Option<u64>,
+
+    /// Compare the gas used by each test against the snapshot in this file
+    ///
+    /// The snapshot is created if it does not exist, a relative path is relative to the package
+    /// directory. Tests are metered with the latest Aptos gas schedule instead of being bounded by
+    /// `--instructions`, and their gas usage is recorded in internal gas units.
+    #[clap(long, value_parser)]
+    pub gas_snapshot: Option<PathBuf>,
+
+    /// Write the gas used by each test to the gas snapshot instead of comparing against it
+    #[clap(long, requires = "gas_snapshot")]
+    pub update_gas_snapshot: bool,
+
+    /// The change of the gas used by a test, in percent, up to which it matches the gas snapshot
+    #[clap(long, default_value_t = 0.0)]
+    pub gas_snapshot_threshold: f64,
+
+    /// Only warn about gas snapshot mismatches instead of failing
+    #[clap(long)]
+    pub gas_snapshot_warn_only: bool,
 }
 
 #[async_trait]
@@ -484,6 +547,8 @@ impl CliCommand<&'static str> for TestPackage {
             full_model_generation: self.move_options.check_test_code,
             install_dir: self.move_options.output_dir.clone(),
             skip_fetch_latest_git_deps: self.move_options.skip_fetch_latest_git_deps,
+            locked: self.move_options.locked,
+            frozen: self.move_options.frozen,
             compiler_config: CompilerConfig {
                 known_attributes: known_attributes.clone(),
                 skip_attribute_checks: self.move_options.skip_attribute_checks,
@@ -494,26 +559,55 @@ impl CliCommand<&'static str> for TestPackage {
         };
 
         let path = self.move_options.get_package_path()?;
-        let result = move_cli::base::test::run_move_unit_tests(
-            path.as_path(),
-            config.clone(),
-            UnitTestingConfig {
-                filter: self.filter.clone(),
-                report_stacktrace_on_abort: true,
-                report_storage_on_error: self.dump_state,
-                ignore_compile_warnings: self.ignore_compile_warnings,
-                ..UnitTestingConfig::default_with_bound(None)
-            },
-            // TODO(Gas): we may want to switch to non-zero costs in the future
-            aptos_debug_natives::aptos_debug_natives(
-                NativeGasParameters::zeros(),
-                MiscGasParameters::zeros(),
-            ),
-            aptos_test_feature_flags_genesis(),
-            None,
-            self.compute_coverage,
-            &mut std::io::stdout(),
-        )
+        let unit_test_config = UnitTestingConfig {
+            filter: self.filter.clone(),
+            report_stacktrace_on_abort: true,
+            report_storage_on_error: self.dump_state,
+            ignore_compile_warnings: self.ignore_compile_warnings,
+            fuzz_iterations: self.fuzz_iterations,
+            fuzz_seed: self.fuzz_seed,
+            gas_snapshot: self
+                .gas_snapshot
+                .as_ref()
+                .map(|snapshot| path.join(snapshot)),
+            update_gas_snapshot: self.update_gas_snapshot,
+            gas_snapshot_threshold: self.gas_snapshot_threshold,
+            gas_snapshot_warn_only: self.gas_snapshot_warn_only,
+            ..UnitTestingConfig::default_with_bound(None)
+        };
+        let result = if unit_test_config.gas_snapshot.is_some() {
+            // Gas snapshots are only meaningful with the costs charged on chain
+            let gas_meter_factory = AptosGasMeterFactory::latest();
+            let natives = aptos_debug_natives::aptos_debug_natives(
+                gas_meter_factory.gas_params().natives.clone(),
+                gas_meter_factory.gas_params().vm.misc.clone(),
+            );
+            move_cli::base::test::run_move_unit_tests_with_gas_meter(
+                path.as_path(),
+                config.clone(),
+                unit_test_config,
+                natives,
+                aptos_test_feature_flags_genesis(),
+                gas_meter_factory,
+                self.compute_coverage,
+                &mut std::io::stdout(),
+            )
+        } else {
+            move_cli::base::test::run_move_unit_tests(
+                path.as_path(),
+                config.clone(),
+                unit_test_config,
+                // TODO(Gas): we may want to switch to non-zero costs in the future
+                aptos_debug_natives::aptos_debug_natives(
+                    NativeGasParameters::zeros(),
+                    MiscGasParameters::zeros(),
+                ),
+                aptos_test_feature_flags_genesis(),
+                None,
+                self.compute_coverage,
+                &mut std::io::stdout(),
+            )
+        }
         .map_err(|err| CliError::UnexpectedError(format!("Failed to run tests: {:#}", err)))?;
 
         // Print coverage summary if --coverage is set
@@ -619,6 +713,8 @@ impl CliCommand<&'static str> for DocumentPackage {
             override_std: move_options.override_std.clone(),
             docgen_options: Some(docgen_options),
             skip_fetch_latest_git_deps: move_options.skip_fetch_latest_git_deps,
+            locked: move_options.locked,
+            frozen: move_options.frozen,
             bytecode_version: move_options.bytecode_version,
             compiler_version: move_options.compiler_version,
             language_version: move_options.language_version,
@@ -687,6 +783,8 @@ impl TryInto<PackagePublicationData> for &PublishPackage {
             .build_options(
                 self.move_options.dev,
                 self.move_options.skip_fetch_latest_git_deps,
+                self.move_options.locked,
+                self.move_options.frozen,
                 self.move_options.named_addresses(),
                 self.move_options.override_std.clone(),
                 self.move_options.bytecode_version,
@@ -759,6 +857,8 @@ impl IncludedArtifacts {
         self,
         dev: bool,
         skip_fetch_latest_git_deps: bool,
+        locked: bool,
+        frozen: bool,
         named_addresses: BTreeMap<String, AccountAddress>,
         override_std: Option<StdVersion>,
         bytecode_version: Option<u32>,
@@ -779,6 +879,8 @@ impl IncludedArtifacts {
                 named_addresses,
                 override_std,
                 skip_fetch_latest_git_deps,
+                locked,
+                frozen,
                 bytecode_version,
                 compiler_version,
                 language_version,
@@ -796,6 +898,8 @@ impl IncludedArtifacts {
                 named_addresses,
                 override_std,
                 skip_fetch_latest_git_deps,
+                locked,
+                frozen,
                 bytecode_version,
                 compiler_version,
                 language_version,
@@ -813,6 +917,8 @@ impl IncludedArtifacts {
                 named_addresses,
                 override_std,
                 skip_fetch_latest_git_deps,
+                locked,
+                frozen,
                 bytecode_version,
                 compiler_version,
                 language_version,
@@ -933,6 +1039,8 @@ impl CliCommand<TransactionSummary> for CreateObjectAndPublishPackage {
             .build_options(
                 self.move_options.dev,
                 self.move_options.skip_fetch_latest_git_deps,
+                self.move_options.locked,
+                self.move_options.frozen,
                 self.move_options.named_addresses(),
                 self.move_options.override_std.clone(),
                 self.move_options.bytecode_version,
@@ -1011,6 +1119,8 @@ impl CliCommand<TransactionSummary> for UpgradeObjectPackage {
             .build_options(
                 self.move_options.dev,
                 self.move_options.skip_fetch_latest_git_deps,
+                self.move_options.locked,
+                self.move_options.frozen,
                 self.move_options.named_addresses(),
                 self.move_options.override_std.clone(),
                 self.move_options.bytecode_version,
@@ -1137,6 +1247,8 @@ impl CliCommand<TransactionSummary> for CreateResourceAccountAndPublishPackage {
         let options = included_artifacts_args.included_artifacts.build_options(
             move_options.dev,
             move_options.skip_fetch_latest_git_deps,
+            move_options.locked,
+            move_options.frozen,
             move_options.named_addresses(),
             move_options.override_std,
             move_options.bytecode_version,
@@ -1290,6 +1402,8 @@ impl CliCommand<&'static str> for VerifyPackage {
             ..self.included_artifacts.build_options(
                 self.move_options.dev,
                 self.move_options.skip_fetch_latest_git_deps,
+                self.move_options.locked,
+                self.move_options.frozen,
                 self.move_options.named_addresses(),
                 self.move_options.override_std.clone(),
                 self.move_options.bytecode_version,
@@ -1437,6 +1551,44 @@ impl CliCommand<&'static str> for CleanPackage {
     }
 }
 
+/// Updates the Move.lock file of a package
+///
+/// Git dependencies are resolved to the latest commit of the branch or tag given in Move.toml,
+/// and the resolved commits, digests and named addresses of all transitive dependencies are
+/// written to Move.lock. Subsequent builds use the commits recorded there.
+#[derive(Parser)]
+pub struct UpdateDeps {
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+}
+
+#[async_trait]
+impl CliCommand<String> for UpdateDeps {
+    fn command_name(&self) -> &'static str {
+        "UpdateDeps"
+    }
+
+    async fn execute(self) -> CliTypedResult<String> {
+        let path = self.move_options.get_package_path()?;
+        let config = BuildConfig {
+            // Resolve in dev mode, so that dev dependencies are locked as well
+            dev_mode: true,
+            additional_named_addresses: self.move_options.named_addresses(),
+            override_std: self.move_options.override_std.clone(),
+            update_lockfile: true,
+            ..Default::default()
+        };
+        let resolved_graph = config
+            .resolution_graph_for_package(&path, &mut std::io::stderr())
+            .map_err(|err| CliError::MoveCompilationError(format!("{:#}", err)))?;
+        Ok(resolved_graph
+            .root_package_path
+            .join(SourcePackageLayout::Lockfile.path())
+            .display()
+            .to_string())
+    }
+}
+
 /// Run a Move function
 #[derive(Parser)]
 pub struct RunFunction {
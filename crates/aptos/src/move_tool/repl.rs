@@ -0,0 +1,670 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::types::{
+        load_account_arg, CliCommand, CliError, CliTypedResult, EntryFunctionArguments,
+        MovePackageDir,
+    },
+    move_tool::{IncludedArtifacts, ReplayNetworkSelection},
+};
+use aptos_framework::{BuildOptions, BuiltPackage};
+use aptos_gas_schedule::{AptosGasParameters, InitialGasSchedule, LATEST_GAS_FEATURE_VERSION};
+use aptos_move_debugger::aptos_debugger::AptosDebugger;
+use aptos_resource_viewer::AptosValueAnnotator;
+use aptos_rest_client::Client;
+use aptos_types::{
+    chain_id::ChainId,
+    contract_event::ContractEvent,
+    on_chain_config::{Features, OnChainConfig, TimedFeaturesBuilder},
+    state_store::{
+        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+        Result as StateViewResult, TStateView,
+    },
+    transaction::{ChangeSet, EntryFunction},
+    write_set::TransactionWrite,
+};
+use aptos_vm::{
+    data_cache::AsMoveResolver,
+    move_vm_ext::{MoveVmExt, SessionExt, SessionId},
+};
+use aptos_vm_types::storage::change_set_configs::ChangeSetConfigs;
+use async_trait::async_trait;
+use clap::Parser;
+use move_binary_format::{
+    access::ModuleAccess,
+    errors::{VMError, VMResult},
+    CompiledModule,
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    ident_str,
+    language_storage::{ModuleId, StructTag},
+    value::MoveValue,
+};
+use move_vm_runtime::module_traversal::{TraversalContext, TraversalStorage};
+use move_vm_types::gas::UnmeteredGasMeter;
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, BufRead, Write},
+    path::PathBuf,
+};
+use url::Url;
+
+/// Start an interactive Move session
+///
+/// The session starts from a fresh genesis, or from the state of a network at a version with
+/// `--fork`, which is fetched on demand. If the package directory contains a package, it is
+/// published first. Functions can then be called as arbitrary signers, resources inspected and
+/// time advanced. All changes are kept in memory and dropped at the end of the session; nothing
+/// is submitted to the network.
+///
+/// Type `help` in the session for the list of commands.
+#[derive(Parser)]
+pub struct Repl {
+    /// Network to fork the state from, instead of starting from a fresh genesis
+    ///
+    /// Possible values:
+    ///     mainnet, testnet, devnet, <REST_ENDPOINT_URL>
+    #[clap(long)]
+    pub(crate) fork: Option<ReplayNetworkSelection>,
+
+    /// Version of the forked network to start from, defaults to the latest version
+    #[clap(long, requires = "fork")]
+    pub(crate) fork_version: Option<u64>,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+}
+
+/// The commands of a session, entered one per line.
+#[derive(Parser)]
+#[clap(multicall = true)]
+enum ReplCommand {
+    /// Call a function and keep its changes
+    Run {
+        /// Addresses of the signers, passed before the other arguments
+        #[clap(long, value_parser = load_account_arg, num_args = 1..)]
+        signers: Vec<AccountAddress>,
+        #[clap(flatten)]
+        function: EntryFunctionArguments,
+    },
+    /// Call a function and drop its changes
+    View {
+        /// Addresses of the signers, passed before the other arguments
+        #[clap(long, value_parser = load_account_arg, num_args = 1..)]
+        signers: Vec<AccountAddress>,
+        #[clap(flatten)]
+        function: EntryFunctionArguments,
+    },
+    /// Show a resource of an account
+    Resource {
+        #[clap(long, value_parser = load_account_arg)]
+        account: AccountAddress,
+        /// Type of the resource, e.g. `0x1::account::Account`
+        #[clap(long)]
+        resource_type: StructTag,
+    },
+    /// Advance the on-chain time
+    AdvanceTime {
+        #[clap(long)]
+        seconds: u64,
+    },
+    /// Rebuild the package and publish it again
+    Publish,
+    /// End the session
+    Exit,
+}
+
+#[async_trait]
+impl CliCommand<&'static str> for Repl {
+    fn command_name(&self) -> &'static str {
+        "Repl"
+    }
+
+    async fn execute(self) -> CliTypedResult<&'static str> {
+        let mut state = match &self.fork {
+            Some(network) => {
+                let client = Client::new(network_url(network)?);
+                let version = match self.fork_version {
+                    Some(version) => version,
+                    None => client.get_ledger_information().await?.into_inner().version,
+                };
+                println!("Forked network at version {}", version);
+                // The debugger reads the state before the transaction at a version
+                ReplState::new(Some(Box::new(
+                    AptosDebugger::rest_client(client)?.state_view_at_version(version + 1),
+                )))
+            },
+            None => ReplState::genesis(),
+        };
+
+        let package_path = self.move_options.get_package_path()?;
+        let has_package = package_path.join("Move.toml").exists();
+        if has_package {
+            self.publish_package(&mut state, package_path.clone())?;
+        }
+
+        let stdin = io::stdin();
+        let mut lines = stdin.lock().lines();
+        loop {
+            print!("> ");
+            io::stdout()
+                .flush()
+                .map_err(|err| CliError::IO("stdout".to_string(), err))?;
+            let Some(line) = lines
+                .next()
+                .transpose()
+                .map_err(|err| CliError::IO("stdin".to_string(), err))?
+            else {
+                break;
+            };
+            let words = match shell_words::split(&line) {
+                Ok(words) if words.is_empty() => continue,
+                Ok(words) => words,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    continue;
+                },
+            };
+            let command = match ReplCommand::try_parse_from(words) {
+                Ok(command) => command,
+                Err(err) => {
+                    // Includes the output of `help`
+                    eprintln!("{}", err);
+                    continue;
+                },
+            };
+            let result = match command {
+                ReplCommand::Run { signers, function } => {
+                    TryInto::<EntryFunction>::try_into(function)
+                        .and_then(|function| state.call(signers, function, true))
+                },
+                ReplCommand::View { signers, function } => {
+                    TryInto::<EntryFunction>::try_into(function)
+                        .and_then(|function| state.call(signers, function, false))
+                },
+                ReplCommand::Resource {
+                    account,
+                    resource_type,
+                } => state.show_resource(account, &resource_type),
+                ReplCommand::AdvanceTime { seconds } => state.advance_time(seconds),
+                ReplCommand::Publish if has_package => {
+                    self.publish_package(&mut state, package_path.clone())
+                },
+                ReplCommand::Publish => Err(CliError::CommandArgumentError(format!(
+                    "No package found in {}",
+                    package_path.display()
+                ))),
+                ReplCommand::Exit => break,
+            };
+            if let Err(err) = result {
+                eprintln!("{}", err);
+            }
+        }
+        Ok("Session ended")
+    }
+}
+
+impl Repl {
+    /// Builds the package and publishes its modules under their addresses, calling `init_module`
+    /// of newly published modules.
+    fn publish_package(&self, state: &mut ReplState, package_path: PathBuf) -> CliTypedResult<()> {
+        let build_options = BuildOptions {
+            install_dir: self.move_options.output_dir.clone(),
+            ..IncludedArtifacts::None.build_options(
+                self.move_options.dev,
+                self.move_options.skip_fetch_latest_git_deps,
+                self.move_options.locked,
+                self.move_options.frozen,
+                self.move_options.named_addresses(),
+                self.move_options.override_std.clone(),
+                self.move_options.bytecode_version,
+                self.move_options.compiler_version,
+                self.move_options.language_version,
+                self.move_options.skip_attribute_checks,
+                self.move_options.check_test_code,
+            )
+        };
+        let pack = BuiltPackage::build(package_path, build_options)
+            .map_err(|e| CliError::MoveCompilationError(format!("{:#}", e)))?;
+
+        // Modules are published in dependency order, grouped by their address
+        let mut bundles: BTreeMap<AccountAddress, Vec<(&CompiledModule, Vec<u8>)>> =
+            BTreeMap::new();
+        for (module, code) in pack.modules().zip(pack.extract_code()) {
+            bundles
+                .entry(*module.self_id().address())
+                .or_default()
+                .push((module, code));
+        }
+        let mut published = vec![];
+        for (address, bundle) in bundles {
+            // Like on chain, `init_module` only runs when a module is first published
+            let initialized: Vec<_> = bundle
+                .iter()
+                .map(|(module, _)| module)
+                .filter(|module| !state.module_exists(&module.self_id()) && has_init(module))
+                .map(|module| module.self_id())
+                .collect();
+            let ids: Vec<_> = bundle
+                .iter()
+                .map(|(module, _)| module.self_id().to_string())
+                .collect();
+            let codes = bundle.into_iter().map(|(_, code)| code).collect();
+            let (_, change_set) = state.run(|session, traversal_context| {
+                session.publish_module_bundle_relax_compatibility(
+                    codes,
+                    address,
+                    &mut UnmeteredGasMeter,
+                )?;
+                for id in &initialized {
+                    session.execute_function_bypass_visibility(
+                        id,
+                        ident_str!("init_module"),
+                        vec![],
+                        vec![MoveValue::Signer(address).simple_serialize().unwrap()],
+                        &mut UnmeteredGasMeter,
+                        traversal_context,
+                    )?;
+                }
+                Ok(())
+            })?;
+            state.commit(&change_set);
+            state.invalidate_loader_cache = true;
+            published.extend(ids);
+        }
+        println!("Published {}", published.join(", "));
+        Ok(())
+    }
+}
+
+fn has_init(module: &CompiledModule) -> bool {
+    module.function_defs().iter().any(|def| {
+        module.identifier_at(module.function_handle_at(def.function).name)
+            == ident_str!("init_module")
+    })
+}
+
+fn network_url(network: &ReplayNetworkSelection) -> CliTypedResult<Url> {
+    let url = match network {
+        ReplayNetworkSelection::Mainnet => "https://fullnode.mainnet.aptoslabs.com",
+        ReplayNetworkSelection::Testnet => "https://fullnode.testnet.aptoslabs.com",
+        ReplayNetworkSelection::Devnet => "https://fullnode.devnet.aptoslabs.com",
+        ReplayNetworkSelection::RestEndpoint(url) => url,
+    };
+    Url::parse(url).map_err(|_| CliError::UnableToParse("url", url.to_string()))
+}
+
+/// The state of a session: the changes made in the session on top of the forked network, or
+/// of an empty state for sessions starting from genesis.
+struct ReplState {
+    base: Option<Box<dyn TStateView<Key = StateKey> + Send + Sync>>,
+    changes: HashMap<StateKey, Option<StateValue>>,
+    /// The number of sessions run so far, which makes the session ids, and with them the
+    /// addresses of created objects, unique
+    num_sessions: u64,
+    /// Whether modules were republished since the VM was last used
+    invalidate_loader_cache: bool,
+}
+
+impl TStateView for ReplState {
+    type Key = StateKey;
+
+    fn get_state_value(&self, state_key: &StateKey) -> StateViewResult<Option<StateValue>> {
+        match (self.changes.get(state_key), &self.base) {
+            (Some(value), _) => Ok(value.clone()),
+            (None, Some(base)) => base.get_state_value(state_key),
+            (None, None) => Ok(None),
+        }
+    }
+
+    fn get_usage(&self) -> StateViewResult<StateStorageUsage> {
+        Ok(StateStorageUsage::new_untracked())
+    }
+}
+
+impl ReplState {
+    fn new(base: Option<Box<dyn TStateView<Key = StateKey> + Send + Sync>>) -> Self {
+        Self {
+            base,
+            changes: HashMap::new(),
+            num_sessions: 0,
+            invalidate_loader_cache: false,
+        }
+    }
+
+    /// Creates the state of a fresh genesis.
+    fn genesis() -> Self {
+        let mut state = Self::new(None);
+        let (genesis, _) = aptos_vm_genesis::generate_test_genesis(
+            aptos_cached_packages::head_release_bundle(),
+            Some(1),
+        );
+        state.commit(&genesis);
+        state
+    }
+
+    /// Runs `f` in a new VM session, returning its result and the changes of the session.
+    fn run<T>(
+        &mut self,
+        f: impl FnOnce(&mut SessionExt, &mut TraversalContext) -> VMResult<T>,
+    ) -> CliTypedResult<(T, ChangeSet)> {
+        let vm_error = |err: VMError| CliError::UnexpectedError(err.into_vm_status().to_string());
+        let session_id = SessionId::Txn {
+            sender: AccountAddress::ZERO,
+            sequence_number: self.num_sessions,
+            script_hash: vec![],
+        };
+        let (result, change_set) = {
+            let state = &*self;
+            let features = Features::fetch_config(state).unwrap_or_default();
+            let chain_id = ChainId::fetch_config(state).unwrap_or_else(ChainId::test);
+            let resolver = state.as_move_resolver();
+            let vm = MoveVmExt::new(
+                LATEST_GAS_FEATURE_VERSION,
+                Ok(&AptosGasParameters::initial()),
+                chain_id.id(),
+                features,
+                TimedFeaturesBuilder::enable_all().build(),
+                &resolver,
+                false,
+            )
+            .map_err(vm_error)?;
+            if self.invalidate_loader_cache {
+                vm.mark_loader_cache_as_invalid();
+            }
+            let mut session = vm.new_session(&resolver, session_id, None);
+            let storage = TraversalStorage::new();
+            let result = f(&mut session, &mut TraversalContext::new(&storage)).map_err(vm_error)?;
+            let change_set = session
+                .finish(&ChangeSetConfigs::unlimited_at_gas_feature_version(
+                    LATEST_GAS_FEATURE_VERSION,
+                ))
+                .map_err(vm_error)?
+                .try_into_storage_change_set()
+                .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+            (result, change_set)
+        };
+        self.num_sessions += 1;
+        self.invalidate_loader_cache = false;
+        Ok((result, change_set))
+    }
+
+    fn commit(&mut self, change_set: &ChangeSet) {
+        for (state_key, write_op) in change_set.write_set() {
+            self.changes
+                .insert(state_key.clone(), write_op.as_state_value());
+        }
+    }
+
+    fn module_exists(&self, id: &ModuleId) -> bool {
+        matches!(self.get_state_value(&StateKey::module_id(id)), Ok(Some(_)))
+    }
+
+    /// Calls the function with the signers passed before its arguments, and prints the return
+    /// values and events. The changes are kept if `commit` is set.
+    fn call(
+        &mut self,
+        signers: Vec<AccountAddress>,
+        function: EntryFunction,
+        commit: bool,
+    ) -> CliTypedResult<()> {
+        let (module, function, ty_args, args) = function.into_inner();
+        let args: Vec<_> = signers
+            .into_iter()
+            .map(|signer| MoveValue::Signer(signer).simple_serialize().unwrap())
+            .chain(args)
+            .collect();
+        let (return_values, change_set) = self.run(|session, traversal_context| {
+            session.execute_function_bypass_visibility(
+                &module,
+                &function,
+                ty_args,
+                args,
+                &mut UnmeteredGasMeter,
+                traversal_context,
+            )
+        })?;
+
+        for (bytes, layout) in &return_values.return_values {
+            match MoveValue::simple_deserialize(bytes, layout) {
+                Ok(value) => println!("{}", value),
+                Err(_) => println!("0x{}", hex::encode(bytes)),
+            }
+        }
+        self.print_events(change_set.events());
+        if commit {
+            println!("{} state changes", change_set.write_set().iter().count());
+            self.commit(&change_set);
+        }
+        Ok(())
+    }
+
+    fn print_events(&self, events: &[ContractEvent]) {
+        let annotator = AptosValueAnnotator::new(self);
+        for event in events {
+            match annotator.view_value(event.type_tag(), event.event_data()) {
+                Ok(value) => println!("event {}: {}", event.type_tag(), value),
+                Err(_) => println!(
+                    "event {}: 0x{}",
+                    event.type_tag(),
+                    hex::encode(event.event_data())
+                ),
+            }
+        }
+    }
+
+    fn show_resource(&self, account: AccountAddress, tag: &StructTag) -> CliTypedResult<()> {
+        let annotator = AptosValueAnnotator::new(self);
+        let state_error = |err: aptos_types::state_store::errors::StateviewError| {
+            CliError::UnexpectedError(err.to_string())
+        };
+        // Members of resource groups are stored together with the other members of the group
+        let bytes = match annotator.view_resource_group_member(tag) {
+            Some(group) => self
+                .get_state_value_bytes(&StateKey::resource_group(&account, &group))
+                .map_err(state_error)?
+                .map(|bytes| bcs::from_bytes::<BTreeMap<StructTag, Vec<u8>>>(&bytes))
+                .transpose()?
+                .and_then(|mut members| members.remove(tag)),
+            None => self
+                .get_state_value_bytes(&StateKey::resource(&account, tag)?)
+                .map_err(state_error)?
+                .map(|bytes| bytes.to_vec()),
+        };
+        match bytes {
+            Some(bytes) => println!("{}", annotator.view_resource(tag, &bytes)?),
+            None => println!("Resource {} not found at {}", tag, account),
+        }
+        Ok(())
+    }
+
+    fn advance_time(&mut self, seconds: u64) -> CliTypedResult<()> {
+        if seconds == 0 {
+            return Err(CliError::CommandArgumentError(
+                "Time can only be advanced by a positive number of seconds".to_string(),
+            ));
+        }
+        let now = self.now_microseconds()?;
+        let new_time = seconds
+            .checked_mul(1_000_000)
+            .and_then(|micros| now.checked_add(micros))
+            .ok_or_else(|| {
+                CliError::CommandArgumentError(format!(
+                    "Advancing the time by {} seconds overflows",
+                    seconds
+                ))
+            })?;
+        // The time can only be updated by the VM, on behalf of a block proposer
+        let (_, change_set) = self.run(|session, traversal_context| {
+            session.execute_function_bypass_visibility(
+                &timestamp_module(),
+                ident_str!("update_global_time"),
+                vec![],
+                vec![
+                    MoveValue::Signer(AccountAddress::ZERO)
+                        .simple_serialize()
+                        .unwrap(),
+                    MoveValue::Address(AccountAddress::ONE)
+                        .simple_serialize()
+                        .unwrap(),
+                    MoveValue::U64(new_time).simple_serialize().unwrap(),
+                ],
+                &mut UnmeteredGasMeter,
+                traversal_context,
+            )
+        })?;
+        self.commit(&change_set);
+        println!("Time is now {} microseconds", new_time);
+        Ok(())
+    }
+
+    fn now_microseconds(&mut self) -> CliTypedResult<u64> {
+        let (now, _) = self.run(|session, traversal_context| {
+            session.execute_function_bypass_visibility(
+                &timestamp_module(),
+                ident_str!("now_microseconds"),
+                vec![],
+                Vec::<Vec<u8>>::new(),
+                &mut UnmeteredGasMeter,
+                traversal_context,
+            )
+        })?;
+        Ok(bcs::from_bytes(&now.return_values[0].0)?)
+    }
+}
+
+fn timestamp_module() -> ModuleId {
+    ModuleId::new(AccountAddress::ONE, ident_str!("timestamp").to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_types::write_set::{WriteOp, WriteSetMut};
+    use std::{fs, str::FromStr};
+
+    fn change_set(writes: Vec<(StateKey, WriteOp)>) -> ChangeSet {
+        ChangeSet::new(WriteSetMut::new(writes).freeze().unwrap(), vec![])
+    }
+
+    fn value(state: &ReplState, key: &StateKey) -> Option<Vec<u8>> {
+        state
+            .get_state_value_bytes(key)
+            .unwrap()
+            .map(|bytes| bytes.to_vec())
+    }
+
+    #[test]
+    fn test_changes_overlay_base() {
+        let (a, b, c) = (
+            StateKey::raw(b"a"),
+            StateKey::raw(b"b"),
+            StateKey::raw(b"c"),
+        );
+        let mut base = ReplState::new(None);
+        base.commit(&change_set(vec![
+            (
+                a.clone(),
+                WriteOp::legacy_creation(b"base a".to_vec().into()),
+            ),
+            (
+                b.clone(),
+                WriteOp::legacy_creation(b"base b".to_vec().into()),
+            ),
+        ]));
+        let mut state = ReplState::new(Some(Box::new(base)));
+        state.commit(&change_set(vec![
+            (
+                a.clone(),
+                WriteOp::legacy_modification(b"new a".to_vec().into()),
+            ),
+            (
+                c.clone(),
+                WriteOp::legacy_creation(b"new c".to_vec().into()),
+            ),
+        ]));
+        assert_eq!(value(&state, &a), Some(b"new a".to_vec()));
+        assert_eq!(value(&state, &b), Some(b"base b".to_vec()));
+        assert_eq!(value(&state, &c), Some(b"new c".to_vec()));
+
+        // Deletions shadow the values of the base
+        state.commit(&change_set(vec![(b.clone(), WriteOp::legacy_deletion())]));
+        assert_eq!(value(&state, &b), None);
+        assert_eq!(value(&state, &a), Some(b"new a".to_vec()));
+    }
+
+    #[test]
+    fn test_publish_runs_init_module_once() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("Move.toml"),
+            "[package]\nname = \"ReplTest\"\nversion = \"0.0.0\"\n\n\
+             [addresses]\nrepl_test = \"0xcafe\"\n",
+        )
+        .unwrap();
+        fs::create_dir(dir.path().join("sources")).unwrap();
+        fs::write(
+            dir.path().join("sources").join("counter.move"),
+            r#"
+module repl_test::counter {
+    struct Counter has key { value: u64 }
+
+    fun init_module(account: &signer) {
+        move_to(account, Counter { value: 1 })
+    }
+
+    public entry fun increment(_account: &signer) acquires Counter {
+        let counter = borrow_global_mut<Counter>(@repl_test);
+        counter.value = counter.value + 1
+    }
+}
+"#,
+        )
+        .unwrap();
+        let repl = Repl {
+            fork: None,
+            fork_version: None,
+            move_options: MovePackageDir::new(dir.path().to_path_buf()),
+        };
+        let address = AccountAddress::from_hex_literal("0xcafe").unwrap();
+        let counter = StateKey::resource(
+            &address,
+            &StructTag::from_str("0xcafe::counter::Counter").unwrap(),
+        )
+        .unwrap();
+
+        let mut state = ReplState::genesis();
+        repl.publish_package(&mut state, dir.path().to_path_buf())
+            .unwrap();
+        assert_eq!(value(&state, &counter), Some(bcs::to_bytes(&1u64).unwrap()));
+
+        let increment = EntryFunction::new(
+            ModuleId::new(address, ident_str!("counter").to_owned()),
+            ident_str!("increment").to_owned(),
+            vec![],
+            vec![],
+        );
+        state.call(vec![address], increment, true).unwrap();
+        assert_eq!(value(&state, &counter), Some(bcs::to_bytes(&2u64).unwrap()));
+
+        // Republishing does not run `init_module` again
+        repl.publish_package(&mut state, dir.path().to_path_buf())
+            .unwrap();
+        assert_eq!(value(&state, &counter), Some(bcs::to_bytes(&2u64).unwrap()));
+    }
+
+    #[test]
+    fn test_advance_time() {
+        let mut state = ReplState::genesis();
+        let start = state.now_microseconds().unwrap();
+        state.advance_time(10).unwrap();
+        assert_eq!(state.now_microseconds().unwrap(), start + 10_000_000);
+
+        assert!(state.advance_time(0).is_err());
+        assert!(state.advance_time(u64::MAX).is_err());
+        assert!(state.advance_time(u64::MAX / 1_000_000).is_err());
+        assert_eq!(state.now_microseconds().unwrap(), start + 10_000_000);
+    }
+}
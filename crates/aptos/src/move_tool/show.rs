@@ -62,6 +62,8 @@ impl CliCommand<Vec<EntryABI>> for ShowAbi {
                 .build_options(
                     self.move_options.dev,
                     self.move_options.skip_fetch_latest_git_deps,
+                    self.move_options.locked,
+                    self.move_options.frozen,
                     self.move_options.named_addresses(),
                     self.move_options.override_std.clone(),
                     self.move_options.bytecode_version,
@@ -0,0 +1,46 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_gas_meter::{AptosGasMeter, GasAlgebra, StandardGasAlgebra, StandardGasMeter};
+use aptos_gas_schedule::{AptosGasParameters, InitialGasSchedule, LATEST_GAS_FEATURE_VERSION};
+use aptos_vm_types::storage::StorageGasParameters;
+use move_unit_test::test_runner::TestGasMeterFactory;
+
+/// Meters Move unit tests with the latest Aptos gas schedule, so the gas used by a test is the
+/// execution gas, in internal gas units, it would be charged on chain. A test is bounded by the
+/// execution gas limit of a transaction.
+pub struct AptosGasMeterFactory {
+    gas_params: AptosGasParameters,
+    storage_gas_params: StorageGasParameters,
+}
+
+impl AptosGasMeterFactory {
+    pub fn latest() -> Self {
+        Self {
+            gas_params: AptosGasParameters::initial(),
+            storage_gas_params: StorageGasParameters::latest(),
+        }
+    }
+
+    pub fn gas_params(&self) -> &AptosGasParameters {
+        &self.gas_params
+    }
+}
+
+impl TestGasMeterFactory for AptosGasMeterFactory {
+    type GasMeter<'a> = StandardGasMeter<StandardGasAlgebra>;
+
+    fn new_gas_meter(&self) -> Self::GasMeter<'_> {
+        StandardGasMeter::new(StandardGasAlgebra::new(
+            LATEST_GAS_FEATURE_VERSION,
+            self.gas_params.vm.clone(),
+            self.storage_gas_params.clone(),
+            false,
+            self.gas_params.vm.txn.maximum_number_of_gas_units,
+        ))
+    }
+
+    fn gas_used(&self, gas_meter: &Self::GasMeter<'_>) -> u64 {
+        gas_meter.algebra().execution_gas_used().into()
+    }
+}
@@ -37,6 +37,7 @@ use crate::{
 };
 use anyhow::{Context, Result};
 use aptos_indexer_grpc_server_framework::setup_logging;
+use aptos_rest_client::Client;
 use async_trait::async_trait;
 use clap::Parser;
 use std::{
@@ -248,6 +249,23 @@ impl CliCommand<()> for RunLocalnet {
 
         let mut managers: Vec<Box<dyn ServiceManager>> = Vec::new();
 
+        // Pin the version of the forked network, so that all of the state is read at the
+        // same version.
+        if let Some(fork_url) = &self.node_args.fork_url {
+            let version = match self.node_args.fork_version {
+                Some(version) => version,
+                None => {
+                    Client::new(fork_url.clone())
+                        .get_ledger_information()
+                        .await?
+                        .into_inner()
+                        .version
+                },
+            };
+            info!("Forking {} at version {}", fork_url, version);
+            self.node_args.fork_version = Some(version);
+        }
+
         // Build the node manager. We do this unconditionally.
         let node_manager = NodeManager::new(&self, bind_to, test_dir.clone())
             .context("Failed to build node service manager")?;
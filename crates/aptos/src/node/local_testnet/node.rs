@@ -4,8 +4,10 @@
 use super::{health_checker::HealthChecker, traits::ServiceManager, RunLocalnet};
 use crate::node::local_testnet::utils::socket_addr_to_url;
 use anyhow::{anyhow, Context, Result};
-use aptos_config::config::{NodeConfig, DEFAULT_GRPC_STREAM_PORT};
+use aptos_config::config::{ForkConfig, NodeConfig, DEFAULT_GRPC_STREAM_PORT};
+use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey};
 use aptos_node::{load_node_config, start_test_environment_node};
+use aptos_types::transaction::authenticator::AuthenticationKey;
 use async_trait::async_trait;
 use clap::Parser;
 use maplit::hashset;
@@ -13,8 +15,9 @@ use rand::{rngs::StdRng, SeedableRng};
 use reqwest::Url;
 use std::{
     collections::HashSet,
+    fs,
     net::{IpAddr, Ipv4Addr},
-    path::PathBuf,
+    path::{Path, PathBuf},
     thread,
     time::Duration,
 };
@@ -61,6 +64,24 @@ pub struct NodeArgs {
     #[clap(long, default_value_t = DEFAULT_GRPC_STREAM_PORT)]
     txn_stream_port: u16,
 
+    /// REST endpoint of a network to fork the state from
+    ///
+    /// State that the localnet has never written is read from the network on demand, at
+    /// `--fork-version`, and cached in memory only, so it is read from the network again after
+    /// the localnet restarts. The root key of the localnet (`mint.key` in the test dir) can send
+    /// transactions as any account read from the network, and the faucet can fund them.
+    ///
+    /// Genesis and the framework are still those of the localnet. Listing the resources of an
+    /// account only returns those written locally.
+    #[clap(long)]
+    pub fork_url: Option<Url>,
+
+    /// Version of the forked network to read the state at
+    ///
+    /// Defaults to the latest version of the network when the localnet starts.
+    #[clap(long, requires = "fork_url")]
+    pub fork_version: Option<u64>,
+
     /// If set we won't run the node at all.
     //
     // Note: I decided that since running multiple partial localnets is a rare
@@ -134,6 +155,21 @@ impl NodeManager {
         node_config.admin_service.address = bind_to.to_string();
         node_config.inspection_service.address = bind_to.to_string();
 
+        if let Some(fork_url) = &args.node_args.fork_url {
+            let version = args
+                .node_args
+                .fork_version
+                .context("The version of the forked network must be resolved")?;
+            let root_key = load_root_key(&test_dir)?;
+            node_config.storage.fork = Some(ForkConfig {
+                url: fork_url.clone(),
+                version,
+                impersonation_authentication_key: Some(AuthenticationKey::ed25519(
+                    &root_key.public_key(),
+                )),
+            });
+        }
+
         Ok(NodeManager {
             config: node_config,
             test_dir,
@@ -150,6 +186,14 @@ impl NodeManager {
     }
 }
 
+/// Load the root key that `load_node_config` wrote to the test dir.
+fn load_root_key(test_dir: &Path) -> Result<Ed25519PrivateKey> {
+    let root_key_path = test_dir.join("mint.key");
+    let bytes = fs::read(&root_key_path)
+        .with_context(|| format!("Failed to read root key at {}", root_key_path.display()))?;
+    bcs::from_bytes(&bytes).context("Failed to deserialize root key")
+}
+
 #[async_trait]
 impl ServiceManager for NodeManager {
     fn get_name(&self) -> String {
@@ -25,6 +25,8 @@ use aptos_backup_cli::{
     utils::GlobalRestoreOpt,
 };
 use aptos_cached_packages::aptos_stdlib;
+use aptos_config::config::Passphrase;
+use aptos_consensus::evidence::Evidence;
 use aptos_crypto::{bls12381, bls12381::PublicKey, x25519, ValidCryptoMaterialStringExt};
 use aptos_genesis::config::{HostAndPort, OperatorConfiguration};
 use aptos_logger::Level;
@@ -32,6 +34,7 @@ use aptos_network_checker::args::{
     validate_address, CheckEndpointArgs, HandshakeArgs, NodeAddressArgs,
 };
 use aptos_rest_client::{aptos_api_types::VersionedEvent, Client, State};
+use aptos_secure_storage::{EncryptedOnDiskStorage, KeyVersion};
 use aptos_types::{
     account_address::AccountAddress,
     account_config::{BlockResource, CORE_CODE_ADDRESS},
@@ -42,6 +45,7 @@ use aptos_types::{
     staking_contract::StakingContractStore,
     validator_info::ValidatorInfo,
     validator_performances::ValidatorPerformances,
+    validator_verifier::ValidatorVerifier,
     vesting::VestingAdminStore,
 };
 use async_trait::async_trait;
@@ -78,8 +82,10 @@ pub enum NodeTool {
     ShowValidatorStake(ShowValidatorStake),
     #[clap(aliases = &["run-local-testnet"])]
     RunLocalnet(RunLocalnet),
+    RotateStorageEncryptionKey(RotateStorageEncryptionKey),
     UpdateConsensusKey(UpdateConsensusKey),
     UpdateValidatorNetworkAddresses(UpdateValidatorNetworkAddresses),
+    VerifyEvidence(VerifyEvidence),
 }
 
 impl NodeTool {
@@ -105,8 +111,10 @@ impl NodeTool {
                 .execute_serialized_without_logger()
                 .await
                 .map(|_| "".to_string()),
+            RotateStorageEncryptionKey(tool) => tool.execute_serialized().await,
             UpdateConsensusKey(tool) => tool.execute_serialized().await,
             UpdateValidatorNetworkAddresses(tool) => tool.execute_serialized().await,
+            VerifyEvidence(tool) => tool.execute_serialized().await,
         }
     }
 }
@@ -1486,6 +1494,168 @@ impl Time {
     }
 }
 
+/// Verify exported equivocation evidence
+///
+/// Verifies the evidence exported by a validator's admin service, from
+/// `/debug/consensus/evidence?bcs=true`, against the validator set of the epoch of the evidence.
+/// Evidence is valid if both of its messages are signed by the validator(s) and conflict with
+/// each other.
+#[derive(Parser)]
+pub struct VerifyEvidence {
+    /// Path to the BCS encoded evidence
+    #[clap(long, value_parser)]
+    pub(crate) evidence_file: PathBuf,
+
+    /// Ledger version at which to read the validator set
+    ///
+    /// It must be in the epoch of the evidence. Defaults to the latest version.
+    #[clap(long)]
+    pub(crate) ledger_version: Option<u64>,
+
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EvidenceSummary {
+    pub kind: &'static str,
+    pub epoch: u64,
+    pub round: u64,
+    pub author: AccountAddress,
+    /// The validators which provably misbehaved
+    pub equivocators: Vec<AccountAddress>,
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[async_trait]
+impl CliCommand<Vec<EvidenceSummary>> for VerifyEvidence {
+    fn command_name(&self) -> &'static str {
+        "VerifyEvidence"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<EvidenceSummary>> {
+        let evidence: Vec<Evidence> = bcs::from_bytes(&read_from_file(&self.evidence_file)?)
+            .map_err(|err| CliError::BCS("Evidence", err))?;
+
+        let client = self.rest_options.client(&self.profile_options)?;
+        let version = match self.ledger_version {
+            Some(version) => version,
+            None => client.get_ledger_information().await?.into_inner().version,
+        };
+        let epoch = client
+            .get_account_resource_at_version_bcs::<ConfigurationResource>(
+                CORE_CODE_ADDRESS,
+                "0x1::reconfiguration::Configuration",
+                version,
+            )
+            .await?
+            .into_inner()
+            .epoch();
+        let validator_set: ValidatorSet = client
+            .get_account_resource_at_version_bcs(
+                CORE_CODE_ADDRESS,
+                "0x1::stake::ValidatorSet",
+                version,
+            )
+            .await?
+            .into_inner();
+        let verifier = ValidatorVerifier::from(&validator_set);
+
+        Ok(evidence
+            .iter()
+            .map(|evidence| {
+                let result = if evidence.epoch() != epoch {
+                    Err(format!(
+                        "Evidence is of epoch {}, but version {} is in epoch {}",
+                        evidence.epoch(),
+                        version,
+                        epoch
+                    ))
+                } else {
+                    evidence
+                        .verify(&verifier)
+                        .map_err(|err| format!("{:#}", err))
+                };
+                EvidenceSummary {
+                    kind: evidence.kind().as_str(),
+                    epoch: evidence.epoch(),
+                    round: evidence.round(),
+                    author: evidence.author(),
+                    equivocators: if result.is_ok() {
+                        evidence.equivocators(&verifier)
+                    } else {
+                        vec![]
+                    },
+                    valid: result.is_ok(),
+                    error: result.err(),
+                }
+            })
+            .collect())
+    }
+}
+
+/// Rotate the encryption key of an encrypted on-disk secure storage
+///
+/// Re-encrypts the storage under a key derived from the new passphrase. Stop the node before
+/// rotating the key, and update the passphrase in its configuration before restarting it: the
+/// current passphrase no longer opens the storage afterwards.
+#[derive(Parser)]
+pub struct RotateStorageEncryptionKey {
+    /// Path to the encrypted storage file
+    #[clap(long, value_parser)]
+    pub(crate) storage_file: PathBuf,
+
+    /// Path to a file containing the current passphrase
+    #[clap(long, value_parser)]
+    pub(crate) passphrase_file: PathBuf,
+
+    /// Path to a file containing the new passphrase
+    #[clap(long, value_parser)]
+    pub(crate) new_passphrase_file: PathBuf,
+}
+
+#[async_trait]
+impl CliCommand<KeyVersion> for RotateStorageEncryptionKey {
+    fn command_name(&self) -> &'static str {
+        "RotateStorageEncryptionKey"
+    }
+
+    async fn execute(self) -> CliTypedResult<KeyVersion> {
+        // Opening a missing file would create a new storage
+        if !self.storage_file.exists() {
+            return Err(CliError::UnableToReadFile(
+                self.storage_file.display().to_string(),
+                "File not found".to_string(),
+            ));
+        }
+        let read_passphrase = |path: &PathBuf| {
+            Passphrase::FromDisk(path.clone())
+                .read_passphrase()
+                .map_err(|err| CliError::UnexpectedError(err.to_string()))
+        };
+        let passphrase = read_passphrase(&self.passphrase_file)?;
+        let new_passphrase = read_passphrase(&self.new_passphrase_file)?;
+        if new_passphrase.is_empty() {
+            return Err(CliError::CommandArgumentError(
+                "The new passphrase must not be empty".to_string(),
+            ));
+        }
+        let storage_error = |err: aptos_secure_storage::Error| {
+            CliError::UnexpectedError(format!("Unable to rotate the encryption key: {}", err))
+        };
+        let mut storage =
+            EncryptedOnDiskStorage::new(self.storage_file.clone(), passphrase.as_bytes())
+                .map_err(storage_error)?;
+        storage
+            .rotate_encryption_key(new_passphrase.as_bytes())
+            .map_err(storage_error)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{CliResult, Tool};
@@ -61,6 +61,7 @@ use aptos_sdk::move_types::{account_address::AccountAddress, language_storage::M
 use aptos_temppath::TempPath;
 use aptos_types::on_chain_config::ValidatorSet;
 use move_core_types::ident_str;
+use move_unit_test::fuzzer::DEFAULT_FUZZ_ITERATIONS;
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -861,6 +862,12 @@ impl CliTestFramework {
             ignore_compile_warnings: false,
             compute_coverage: false,
             dump_state: false,
+            fuzz_iterations: DEFAULT_FUZZ_ITERATIONS,
+            fuzz_seed: None,
+            gas_snapshot: None,
+            update_gas_snapshot: false,
+            gas_snapshot_threshold: 0.0,
+            gas_snapshot_warn_only: false,
         }
         .execute()
         .await
@@ -1092,6 +1099,8 @@ impl CliTestFramework {
             named_addresses: Self::named_addresses(account_strs),
             override_std: None,
             skip_fetch_latest_git_deps: true,
+            locked: false,
+            frozen: false,
             bytecode_version: None,
             compiler_version: None,
             language_version: None,
@@ -73,6 +73,7 @@ async fn ensure_every_command_args_work() {
     assert_cmd_not_panic(&["aptos", "move", "list", "--help"]).await;
     assert_cmd_not_panic(&["aptos", "move", "prove", "--help"]).await;
     assert_cmd_not_panic(&["aptos", "move", "publish", "--help"]).await;
+    assert_cmd_not_panic(&["aptos", "move", "repl", "--help"]).await;
     assert_cmd_not_panic(&["aptos", "move", "run", "--help"]).await;
     assert_cmd_not_panic(&["aptos", "move", "run-script", "--help"]).await;
     assert_cmd_not_panic(&["aptos", "move", "test", "--help"]).await;
@@ -99,6 +100,7 @@ async fn ensure_every_command_args_work() {
         "--help",
     ])
     .await;
+    assert_cmd_not_panic(&["aptos", "node", "verify-evidence", "--help"]).await;
 
     assert_cmd_not_panic(&["aptos", "stake"]).await;
     assert_cmd_not_panic(&["aptos", "stake", "add-stake", "--help"]).await;
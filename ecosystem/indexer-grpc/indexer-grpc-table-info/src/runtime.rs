@@ -19,7 +19,11 @@ pub fn bootstrap_internal_indexer_db(
     config: &NodeConfig,
     db_rw: DbReaderWriter,
 ) -> Option<(Runtime, Arc<DBIndexer>)> {
-    if !(config.indexer_db_config.enable_event() || config.indexer_db_config.enable_transaction()) {
+    if !(config.indexer_db_config.enable_event()
+        || config.indexer_db_config.enable_transaction()
+        || config.indexer_db_config.enable_account_activity()
+        || config.indexer_db_config.enable_object_ownership())
+    {
         return None;
     }
     let runtime = aptos_runtimes::spawn_named_runtime("index-db".to_string(), None);
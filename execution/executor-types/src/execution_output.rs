@@ -4,7 +4,7 @@
 #![forbid(unsafe_code)]
 
 use crate::LedgerUpdateOutput;
-use aptos_storage_interface::state_delta::StateDelta;
+use aptos_storage_interface::{cached_state_view::UncommittedDeletions, state_delta::StateDelta};
 use aptos_types::epoch_state::EpochState;
 use once_cell::sync::OnceCell;
 
@@ -12,14 +12,21 @@ pub struct ExecutionOutput {
     state: StateDelta,
     /// If set, this is the new epoch info that should be changed to if this is committed.
     next_epoch_state: Option<EpochState>,
+    /// The keys deleted by this block and its ancestors that are not committed yet.
+    uncommitted_deletions: UncommittedDeletions,
     ledger_update_output: OnceCell<LedgerUpdateOutput>,
 }
 
 impl ExecutionOutput {
-    pub fn new(state: StateDelta, next_epoch_state: Option<EpochState>) -> Self {
+    pub fn new(
+        state: StateDelta,
+        next_epoch_state: Option<EpochState>,
+        uncommitted_deletions: UncommittedDeletions,
+    ) -> Self {
         Self {
             state,
             next_epoch_state,
+            uncommitted_deletions,
             ledger_update_output: OnceCell::new(),
         }
     }
@@ -34,6 +41,7 @@ impl ExecutionOutput {
         Self {
             state,
             next_epoch_state,
+            uncommitted_deletions: UncommittedDeletions::default(),
             ledger_update_output: ledger_update,
         }
     }
@@ -42,6 +50,10 @@ impl ExecutionOutput {
         &self.next_epoch_state
     }
 
+    pub fn uncommitted_deletions(&self) -> &UncommittedDeletions {
+        &self.uncommitted_deletions
+    }
+
     pub fn has_reconfiguration(&self) -> bool {
         self.next_epoch_state.is_some()
     }
@@ -98,6 +98,10 @@ impl StateCheckpointOutput {
         self.txns.to_commit.len()
     }
 
+    pub fn state_updates_before_last_checkpoint(&self) -> Option<&ShardedStateUpdates> {
+        self.state_updates_before_last_checkpoint.as_ref()
+    }
+
     pub fn into_inner(
         self,
     ) -> (
@@ -27,7 +27,9 @@ use aptos_infallible::RwLock;
 use aptos_logger::prelude::*;
 use aptos_scratchpad::SparseMerkleTree;
 use aptos_storage_interface::{
-    async_proof_fetcher::AsyncProofFetcher, cached_state_view::CachedStateView, DbReaderWriter,
+    async_proof_fetcher::AsyncProofFetcher,
+    cached_state_view::{CachedStateView, UncommittedDeletions},
+    DbReaderWriter,
 };
 use aptos_types::{
     block_executor::{
@@ -233,6 +235,7 @@ where
                         Arc::clone(&self.db.reader),
                         parent_output.next_version(),
                         parent_output.state().current.clone(),
+                        parent_output.uncommitted_deletions().clone(),
                         Arc::new(AsyncProofFetcher::new(self.db.reader.clone())),
                     )?
                 };
@@ -256,10 +259,23 @@ where
                 })?
             };
 
+        // The DB only learns about the deletions of a block once it is committed, keep track of
+        // them meanwhile for the blocks executed on top of it.
+        let uncommitted_deletions =
+            match state_checkpoint_output.state_updates_before_last_checkpoint() {
+                Some(updates) => parent_output.uncommitted_deletions().extend(
+                    updates,
+                    state
+                        .current_version
+                        .expect("Executed block must have a version."),
+                    self.block_tree.root_block().output.state().current_version,
+                ),
+                None => parent_output.uncommitted_deletions().clone(),
+            };
         let _ = self.block_tree.add_block(
             parent_block_id,
             block_id,
-            ExecutionOutput::new(state, epoch_state),
+            ExecutionOutput::new(state, epoch_state, uncommitted_deletions),
         )?;
         Ok(state_checkpoint_output)
     }
@@ -28,8 +28,10 @@ use aptos_infallible::{Mutex, RwLock};
 use aptos_logger::prelude::*;
 use aptos_metrics_core::TimerHelper;
 use aptos_storage_interface::{
-    async_proof_fetcher::AsyncProofFetcher, cached_state_view::CachedStateView,
-    state_delta::StateDelta, DbReaderWriter, ExecutedTrees,
+    async_proof_fetcher::AsyncProofFetcher,
+    cached_state_view::{CachedStateView, UncommittedDeletions},
+    state_delta::StateDelta,
+    DbReaderWriter, ExecutedTrees,
 };
 use aptos_types::{
     block_executor::config::BlockExecutorConfigFromOnchain,
@@ -162,6 +164,7 @@ impl<V: VMExecutor> ChunkExecutorInner<V> {
             self.db.reader.clone(),
             first_version,
             latest_state.current.clone(),
+            UncommittedDeletions::default(),
             Arc::new(AsyncProofFetcher::new(self.db.reader.clone())),
         )?)
     }
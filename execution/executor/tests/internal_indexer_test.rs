@@ -1,10 +1,11 @@
 // Copyright (c) Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_cached_packages::aptos_stdlib;
+use aptos_cached_packages::{aptos_stdlib, aptos_token_objects_sdk_builder};
 use aptos_config::config::{internal_indexer_db_config::InternalIndexerDBConfig, RocksdbConfig};
 use aptos_db::AptosDB;
 use aptos_db_indexer::{db_indexer::DBIndexer, db_ops::open_internal_indexer_db};
+use aptos_db_indexer_schemas::schema::object_by_owner::ObjectByOwnerSchema;
 use aptos_executor_test_helpers::{
     gen_block_id, gen_ledger_info_with_sigs, integration_test_impl::create_db_and_executor,
 };
@@ -16,17 +17,29 @@ use aptos_sdk::{
 use aptos_storage_interface::DbReader;
 use aptos_temppath::TempPath;
 use aptos_types::{
-    account_config::aptos_test_root_address,
+    access_path::Path,
+    account_config::{aptos_test_root_address, ObjectGroupResource},
     block_metadata::BlockMetadata,
     chain_id::ChainId,
+    indexer::indexer_db_reader::Order,
+    state_store::state_key::inner::StateKeyInner,
     test_helpers::transaction_test_helpers::TEST_BLOCK_EXECUTOR_ONCHAIN_CONFIG,
     transaction::{
-        signature_verified_transaction::into_signature_verified_block, Transaction,
-        Transaction::UserTransaction, WriteSetPayload,
+        signature_verified_transaction::into_signature_verified_block, EntryFunction,
+        SignedTransaction, Transaction, Transaction::UserTransaction, TransactionPayload, Version,
+        WriteSetPayload,
     },
+    utility_coin::APTOS_COIN_TYPE,
+    validator_signer::ValidatorSigner,
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    ident_str,
+    language_storage::{ModuleId, StructTag, TypeTag},
+    move_resource::MoveStructType,
 };
 use rand::SeedableRng;
-use std::sync::Arc;
+use std::{collections::BTreeMap, str::FromStr, sync::Arc, thread, time::Duration};
 
 const B: u64 = 1_000_000_000;
 
@@ -130,7 +143,6 @@ pub fn create_test_db() -> (Arc<AptosDB>, LocalAccount) {
 
 #[test]
 fn test_db_indexer_data() {
-    use std::{thread, time::Duration};
     // create test db
     let (aptos_db, core_account) = create_test_db();
     let total_version = aptos_db.get_synced_version().unwrap();
@@ -144,7 +156,7 @@ fn test_db_indexer_data() {
     let db_indexer = DBIndexer::new(
         db.clone(),
         aptos_db,
-        &InternalIndexerDBConfig::new(true, true, 2),
+        &InternalIndexerDBConfig::new(true, true, true, true, 2),
     );
     // assert the data matches the expected data
     let mut version = db_indexer.get_persisted_version().unwrap();
@@ -165,8 +177,504 @@ fn test_db_indexer_data() {
     // core account submitted 7 transactions including last reconfig txn, and the first transaction is version 2
     assert!(res.len() == 7);
     assert!(res[0].as_ref().unwrap().1 == 2);
+    let sent_versions: Vec<_> = res.into_iter().map(|r| r.unwrap().1).collect();
+
+    // every transaction sent by core account is part of its activity
+    let activity = db_indexer
+        .lookup_account_activity(
+            core_account.address(),
+            0,
+            Order::Ascending,
+            1000,
+            total_version,
+        )
+        .unwrap();
+    assert!(sent_versions.iter().all(|v| activity.contains(v)));
+    // the framework writes to the reserved addresses don't make them part of the activity
+    for reserved in [
+        AccountAddress::ONE,
+        AccountAddress::from_hex_literal("0xa").unwrap(),
+    ] {
+        let activity = db_indexer
+            .lookup_account_activity(reserved, 0, Order::Ascending, 1000, total_version)
+            .unwrap();
+        assert!(sent_versions.iter().all(|v| !activity.contains(v)));
+    }
+    let latest = db_indexer
+        .lookup_account_activity(
+            core_account.address(),
+            u64::MAX,
+            Order::Descending,
+            1,
+            total_version,
+        )
+        .unwrap();
+    assert_eq!(latest, vec![total_version]);
 
     let x = db_indexer.get_event_by_key_iter().unwrap();
     let res: Vec<_> = x.collect();
     assert!(res.len() == 14);
 }
+
+/// Executes and commits a block of user transactions on top of the committed one.
+fn execute_and_commit_block(
+    executor: &impl BlockExecutorTrait,
+    signer: &ValidatorSigner,
+    round: u8,
+    txns: Vec<SignedTransaction>,
+) {
+    let parent_block_id = executor.committed_block_id();
+    let block_id = gen_block_id(round);
+    let block_meta = Transaction::BlockMetadata(BlockMetadata::new(
+        block_id,
+        1,
+        round as u64,
+        signer.author(),
+        vec![0],
+        vec![],
+        round as u64,
+    ));
+    let block = into_signature_verified_block(
+        std::iter::once(block_meta)
+            .chain(txns.into_iter().map(UserTransaction))
+            .collect(),
+    );
+    let output = executor
+        .execute_block(
+            (block_id, block).into(),
+            parent_block_id,
+            TEST_BLOCK_EXECUTOR_ONCHAIN_CONFIG,
+        )
+        .unwrap();
+    let li = gen_ledger_info_with_sigs(1, &output, block_id, &[signer.clone()]);
+    executor.commit_blocks(vec![block_id], li).unwrap();
+}
+
+/// Indexes everything committed to `aptos_db` and waits for the indexer to persist it.
+fn index_all(db_indexer: &DBIndexer, aptos_db: &AptosDB) -> Version {
+    let synced_version = aptos_db.get_synced_version().unwrap();
+    let mut version = db_indexer.get_persisted_version().unwrap();
+    while version <= synced_version {
+        version = db_indexer.process_a_batch(Some(version)).unwrap();
+    }
+    while db_indexer.get_persisted_version().unwrap() < synced_version {
+        thread::sleep(Duration::from_millis(10));
+    }
+    synced_version
+}
+
+/// Returns the version of the transaction `sender` sent with `seq_num`.
+fn txn_version(db_indexer: &DBIndexer, sender: &LocalAccount, seq_num: u64) -> Version {
+    db_indexer
+        .get_account_transaction_version_iter(sender.address(), seq_num, 1, Version::MAX)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .1
+}
+
+/// Returns the object holding a resource of type `resource` created at `version`.
+fn created_object(aptos_db: &AptosDB, version: Version, resource: &StructTag) -> AccountAddress {
+    let write_set = aptos_db
+        .get_write_set_iterator(version, 1)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    for (state_key, write_op) in &write_set {
+        if let (StateKeyInner::AccessPath(access_path), Some(bytes)) =
+            (state_key.inner(), write_op.bytes())
+        {
+            if access_path.get_path() == Path::ResourceGroup(ObjectGroupResource::struct_tag()) {
+                let members = bcs::from_bytes::<BTreeMap<StructTag, Vec<u8>>>(bytes).unwrap();
+                if members.contains_key(resource) {
+                    return access_path.address;
+                }
+            }
+        }
+    }
+    panic!(
+        "No object holding {} created at version {}",
+        resource, version
+    );
+}
+
+/// Creates a db with only the genesis committed in `path`, and an internal indexer db with the
+/// given config in `indexer_path`. Returns them along with an executor, the signer of the
+/// validator and the core resources account.
+fn create_test_chain(
+    path: &TempPath,
+    indexer_path: &TempPath,
+    config: &InternalIndexerDBConfig,
+) -> (
+    Arc<AptosDB>,
+    impl BlockExecutorTrait,
+    ValidatorSigner,
+    LocalAccount,
+    DBIndexer,
+) {
+    let (genesis, validators) = aptos_vm_genesis::test_genesis_change_set_and_validators(Some(1));
+    let genesis_txn = Transaction::GenesisTransaction(WriteSetPayload::Direct(genesis));
+    let core_account = LocalAccount::new(
+        aptos_test_root_address(),
+        AccountKey::from_private_key(aptos_vm_genesis::GENESIS_KEYPAIR.0.clone()),
+        0,
+    );
+    let (aptos_db, _db, executor, _waypoint) =
+        create_db_and_executor(path.path(), &genesis_txn, true);
+    let signer = ValidatorSigner::new(
+        validators[0].data.owner_address,
+        validators[0].consensus_key.clone(),
+    );
+    let db = Arc::new(
+        open_internal_indexer_db(indexer_path.as_ref(), &RocksdbConfig::default())
+            .expect("Failed to open up indexer db initially"),
+    );
+    let db_indexer = DBIndexer::new(db, aptos_db.clone(), config);
+    (aptos_db, executor, signer, core_account, db_indexer)
+}
+
+#[test]
+fn test_db_indexer_account_activity_roles() {
+    let (path, indexer_path) = (TempPath::new(), TempPath::new());
+    let (aptos_db, executor, signer, core_account, db_indexer) = create_test_chain(
+        &path,
+        &indexer_path,
+        &InternalIndexerDBConfig::new(true, false, true, false, 2),
+    );
+
+    let mut rng = ::rand::rngs::StdRng::from_seed([5u8; 32]);
+    let sender = LocalAccount::generate(&mut rng);
+    let fee_payer = LocalAccount::generate(&mut rng);
+    let secondary_signer = LocalAccount::generate(&mut rng);
+    let coin_receiver = LocalAccount::generate(&mut rng).address();
+    let fa_receiver = LocalAccount::generate(&mut rng).address();
+    let object_receiver = LocalAccount::generate(&mut rng).address();
+    let txn_factory = TransactionFactory::new(ChainId::test());
+
+    let mut txns = vec![];
+    for account in [&sender, &fee_payer, &secondary_signer] {
+        txns.push(
+            core_account.sign_with_transaction_builder(
+                txn_factory.create_user_account(account.public_key()),
+            ),
+        );
+    }
+    for account in [&sender, &fee_payer] {
+        txns.push(
+            core_account
+                .sign_with_transaction_builder(txn_factory.mint(account.address(), 1_000 * B)),
+        );
+    }
+    // Transfer to an account holding coins in a CoinStore.
+    txns.push(sender.sign_with_transaction_builder(txn_factory.account_transfer(coin_receiver, B)));
+    // Transfer to an account holding fungible assets in a primary store, which is only touched
+    // through the store object it owns.
+    txns.push(sender.sign_with_transaction_builder(txn_factory.payload(
+        aptos_stdlib::coin_migrate_to_fungible_store(APTOS_COIN_TYPE.clone()),
+    )));
+    txns.push(sender.sign_with_transaction_builder(txn_factory.payload(
+        TransactionPayload::EntryFunction(EntryFunction::new(
+            ModuleId::new(
+                AccountAddress::ONE,
+                ident_str!("primary_fungible_store").to_owned(),
+            ),
+            ident_str!("transfer").to_owned(),
+            vec![TypeTag::from_str("0x1::fungible_asset::Metadata").unwrap()],
+            vec![
+                bcs::to_bytes(&AccountAddress::TEN).unwrap(),
+                bcs::to_bytes(&fa_receiver).unwrap(),
+                bcs::to_bytes(&B).unwrap(),
+            ],
+        )),
+    )));
+    let fee_payer_seq_num = sender.sequence_number();
+    txns.push(sender.sign_fee_payer_with_transaction_builder(
+        vec![],
+        &fee_payer,
+        txn_factory.payload(aptos_stdlib::aptos_account_create_account(
+            LocalAccount::generate(&mut rng).address(),
+        )),
+    ));
+    let multi_agent_seq_num = sender.sequence_number();
+    txns.push(sender.sign_multi_agent_with_transaction_builder(
+        vec![&secondary_signer],
+        txn_factory.payload(aptos_stdlib::aptos_account_create_account(
+            LocalAccount::generate(&mut rng).address(),
+        )),
+    ));
+    txns.push(sender.sign_with_transaction_builder(txn_factory.payload(
+        aptos_token_objects_sdk_builder::aptos_token_create_collection(
+            b"description".to_vec(),
+            10,
+            b"collection".to_vec(),
+            b"uri".to_vec(),
+            false,
+            false,
+            false,
+            false,
+            false,
+            false,
+            false,
+            false,
+            false,
+            0,
+            1,
+        ),
+    )));
+    let mint_seq_num = sender.sequence_number();
+    txns.push(sender.sign_with_transaction_builder(txn_factory.payload(
+        aptos_token_objects_sdk_builder::aptos_token_mint(
+            b"collection".to_vec(),
+            b"description".to_vec(),
+            b"token".to_vec(),
+            b"uri".to_vec(),
+            vec![],
+            vec![],
+            vec![],
+        ),
+    )));
+    execute_and_commit_block(&executor, &signer, 1, txns);
+    index_all(&db_indexer, &aptos_db);
+
+    // The token address is only known once minted, so it is transferred in a second block.
+    let token = created_object(
+        &aptos_db,
+        txn_version(&db_indexer, &sender, mint_seq_num),
+        &StructTag::from_str("0x4::aptos_token::AptosToken").unwrap(),
+    );
+    let object_transfer_seq_num = sender.sequence_number();
+    let txn = sender.sign_with_transaction_builder(
+        txn_factory.payload(aptos_stdlib::object_transfer_call(token, object_receiver)),
+    );
+    execute_and_commit_block(&executor, &signer, 2, vec![txn]);
+    let ledger_version = index_all(&db_indexer, &aptos_db);
+
+    let activity = |address| {
+        db_indexer
+            .lookup_account_activity(address, 0, Order::Ascending, 1000, ledger_version)
+            .unwrap()
+    };
+    // Every transaction is part of its sender's activity.
+    let sent_versions: Vec<_> = (0..sender.sequence_number())
+        .map(|seq_num| txn_version(&db_indexer, &sender, seq_num))
+        .collect();
+    let sender_activity = activity(sender.address());
+    assert!(sent_versions.iter().all(|v| sender_activity.contains(v)));
+    // A transfer is part of its receiver's activity, whether it holds coins or fungible assets.
+    assert_eq!(activity(coin_receiver), vec![sent_versions[0]]);
+    assert_eq!(activity(fa_receiver), vec![sent_versions[2]]);
+    // As is a transaction of its fee payer and secondary signers.
+    assert!(activity(fee_payer.address()).contains(&sent_versions[fee_payer_seq_num as usize]));
+    assert!(
+        activity(secondary_signer.address()).contains(&sent_versions[multi_agent_seq_num as usize])
+    );
+    // And an object transfer of its receiver.
+    assert_eq!(activity(object_receiver), vec![
+        sent_versions[object_transfer_seq_num as usize]
+    ]);
+}
+
+#[test]
+fn test_db_indexer_object_ownership() {
+    let (path, indexer_path) = (TempPath::new(), TempPath::new());
+    // Go through a single entry per request, so listing the tokens takes a page per object.
+    let mut config = InternalIndexerDBConfig::new(true, false, false, true, 2);
+    config.max_owned_objects_scanned = 1;
+    let (aptos_db, executor, signer, core_account, db_indexer) =
+        create_test_chain(&path, &indexer_path, &config);
+    let mut rng = ::rand::rngs::StdRng::from_seed([6u8; 32]);
+    let creator = LocalAccount::generate(&mut rng);
+    let receiver = LocalAccount::generate(&mut rng).address();
+    let txn_factory = TransactionFactory::new(ChainId::test());
+    let token_type = StructTag::from_str("0x4::aptos_token::AptosToken").unwrap();
+
+    let create_txns = vec![
+        core_account
+            .sign_with_transaction_builder(txn_factory.create_user_account(creator.public_key())),
+        core_account.sign_with_transaction_builder(txn_factory.mint(creator.address(), 1_000 * B)),
+        creator.sign_with_transaction_builder(txn_factory.payload(
+            aptos_token_objects_sdk_builder::aptos_token_create_collection(
+                b"description".to_vec(),
+                10,
+                b"collection".to_vec(),
+                b"uri".to_vec(),
+                false,
+                false,
+                false,
+                false,
+                false,
+                false,
+                false,
+                true, // tokens burnable by creator
+                false,
+                0,
+                1,
+            ),
+        )),
+        creator.sign_with_transaction_builder(txn_factory.payload(
+            aptos_token_objects_sdk_builder::aptos_token_mint(
+                b"collection".to_vec(),
+                b"description".to_vec(),
+                b"token".to_vec(),
+                b"uri".to_vec(),
+                vec![],
+                vec![],
+                vec![],
+            ),
+        )),
+    ];
+    execute_and_commit_block(&executor, &signer, 1, create_txns);
+    let ledger_version = index_all(&db_indexer, &aptos_db);
+    let create_version = txn_version(&db_indexer, &creator, 1);
+    let token = created_object(&aptos_db, create_version, &token_type);
+    let owned_tokens = |owner, ledger_version| {
+        let mut tokens = Vec::new();
+        let mut start_object = Some(AccountAddress::ZERO);
+        while let Some(object) = start_object {
+            let (page, next_object) = db_indexer
+                .get_owned_objects(owner, Some(&token_type), object, 100, ledger_version)
+                .unwrap();
+            tokens.extend(page);
+            start_object = next_object;
+        }
+        tokens
+    };
+    let indexed_owner = |owner| {
+        db_indexer
+            .db
+            .get::<ObjectByOwnerSchema>(&(owner, token))
+            .unwrap()
+    };
+    assert_eq!(owned_tokens(creator.address(), ledger_version), vec![(
+        token,
+        create_version
+    )]);
+    assert!(indexed_owner(creator.address()).is_some());
+    // The creator also owns the collection, so the first page stops short of the last object.
+    let (_, next_object) = db_indexer
+        .get_owned_objects(
+            creator.address(),
+            Some(&token_type),
+            AccountAddress::ZERO,
+            100,
+            ledger_version,
+        )
+        .unwrap();
+    assert!(next_object.is_some());
+
+    let txn = creator.sign_with_transaction_builder(
+        txn_factory.payload(aptos_stdlib::object_transfer_call(token, receiver)),
+    );
+    execute_and_commit_block(&executor, &signer, 2, vec![txn]);
+    let ledger_version = index_all(&db_indexer, &aptos_db);
+    let transfer_version = txn_version(&db_indexer, &creator, 2);
+    assert!(owned_tokens(creator.address(), ledger_version).is_empty());
+    assert_eq!(owned_tokens(receiver, ledger_version), vec![(
+        token,
+        transfer_version
+    )]);
+    // The previous owner is dropped from the index.
+    assert!(indexed_owner(creator.address()).is_none());
+    assert!(indexed_owner(receiver).is_some());
+
+    let txn = creator.sign_with_transaction_builder(txn_factory.payload(
+        TransactionPayload::EntryFunction(EntryFunction::new(
+            ModuleId::new(AccountAddress::FOUR, ident_str!("aptos_token").to_owned()),
+            ident_str!("burn").to_owned(),
+            vec![TypeTag::Struct(Box::new(token_type.clone()))],
+            vec![bcs::to_bytes(&token).unwrap()],
+        )),
+    ));
+    execute_and_commit_block(&executor, &signer, 3, vec![txn]);
+    let ledger_version = index_all(&db_indexer, &aptos_db);
+    let burn_version = txn_version(&db_indexer, &creator, 3);
+    assert!(owned_tokens(receiver, ledger_version).is_empty());
+    assert!(indexed_owner(receiver).is_none());
+
+    // The history holds every change, and so does the owner as of each version.
+    assert_eq!(
+        db_indexer
+            .get_object_owner_history(token, 0, 100, ledger_version)
+            .unwrap(),
+        vec![
+            (create_version, Some(creator.address())),
+            (transfer_version, Some(receiver)),
+            (burn_version, None),
+        ]
+    );
+    assert_eq!(
+        db_indexer
+            .get_object_owner(token, transfer_version - 1)
+            .unwrap(),
+        Some((create_version, Some(creator.address())))
+    );
+    assert_eq!(
+        db_indexer.get_object_owner(token, ledger_version).unwrap(),
+        Some((burn_version, None))
+    );
+}
+
+#[test]
+fn test_db_indexer_start_versions() {
+    let (aptos_db, core_account) = create_test_db();
+    let total_version = aptos_db.get_synced_version().unwrap();
+    let temp_path = TempPath::new();
+    let db = Arc::new(
+        open_internal_indexer_db(temp_path.as_ref(), &RocksdbConfig::default())
+            .expect("Failed to open up indexer db initially"),
+    );
+
+    // Enabled from the start, the indices cover every transaction.
+    let config = InternalIndexerDBConfig::new(true, true, true, true, 2);
+    let db_indexer = DBIndexer::new(db.clone(), aptos_db.clone(), &config);
+    assert_eq!(db_indexer.get_account_activity_start_version().unwrap(), 0);
+    assert_eq!(db_indexer.get_object_ownership_start_version().unwrap(), 0);
+    index_all(&db_indexer, &aptos_db);
+
+    // Disabling them drops the start versions, as the transactions indexed meanwhile would be
+    // missing.
+    drop(db_indexer);
+    let db_indexer = DBIndexer::new(
+        db.clone(),
+        aptos_db.clone(),
+        &InternalIndexerDBConfig::new(true, true, false, false, 2),
+    );
+    assert!(db_indexer.get_account_activity_start_version().is_err());
+    assert!(db_indexer.get_object_ownership_start_version().is_err());
+
+    // Enabled again, they only cover the transactions indexed from then on.
+    drop(db_indexer);
+    let db_indexer = DBIndexer::new(db, aptos_db.clone(), &config);
+    assert_eq!(
+        db_indexer.get_account_activity_start_version().unwrap(),
+        total_version + 1
+    );
+    assert_eq!(
+        db_indexer.get_object_ownership_start_version().unwrap(),
+        total_version + 1
+    );
+    assert!(db_indexer
+        .lookup_account_activity(
+            core_account.address(),
+            0,
+            Order::Ascending,
+            1000,
+            total_version
+        )
+        .unwrap()
+        .is_empty());
+    // Listing the latest activity stops at the start version too, rather than going through the
+    // entries left from before.
+    assert!(db_indexer
+        .lookup_account_activity(
+            core_account.address(),
+            u64::MAX,
+            Order::Descending,
+            1000,
+            total_version
+        )
+        .unwrap()
+        .is_empty());
+}
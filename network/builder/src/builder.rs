@@ -23,7 +23,7 @@ use aptos_event_notifications::{DbBackedOnChainConfig, EventSubscriptionService}
 use aptos_logger::prelude::*;
 use aptos_netcore::transport::tcp::TCPBufferCfg;
 use aptos_network::{
-    application::storage::PeersAndMetadata,
+    application::{interface::NetworkClient, storage::PeersAndMetadata},
     connectivity_manager::{builder::ConnectivityManagerBuilder, ConnectivityRequest},
     constants::MAX_MESSAGE_SIZE,
     logging::NetworkSchema,
@@ -38,10 +38,14 @@ use aptos_network::{
             NewNetworkSender,
         },
     },
+    ProtocolId,
+};
+use aptos_network_discovery::{
+    dns::HickoryDnsResolver, peer_exchange::peer_exchange_network_config, DiscoveryChangeListener,
 };
-use aptos_network_discovery::DiscoveryChangeListener;
 use aptos_time_service::TimeService;
 use aptos_types::{chain_id::ChainId, network_address::NetworkAddress};
+use maplit::hashmap;
 use std::{clone::Clone, collections::HashSet, sync::Arc, time::Duration};
 use tokio::runtime::Handle;
 
@@ -389,6 +393,41 @@ impl NetworkBuilder {
                     Duration::from_secs(rest_discovery.interval_secs),
                     self.time_service.clone(),
                 ),
+                DiscoveryMethod::Dns(dns_discovery) => {
+                    let resolver = HickoryDnsResolver::new(&dns_discovery.name_servers)
+                        .expect("Unable to create the DNS resolver for DNS discovery!");
+                    DiscoveryChangeListener::dns(
+                        self.network_context,
+                        conn_mgr_reqs_tx.clone(),
+                        dns_discovery.seed_domains.clone(),
+                        dns_discovery.port,
+                        Arc::new(resolver),
+                        Duration::from_secs(dns_discovery.interval_secs),
+                        self.time_service.clone(),
+                    )
+                },
+                DiscoveryMethod::PeerExchange(peer_exchange_discovery) => {
+                    let (network_sender, network_events) = self.add_client_and_service(
+                        &peer_exchange_network_config(),
+                        config.max_parallel_deserialization_tasks,
+                        true, /* allow_out_of_order_delivery */
+                    );
+                    let network_client = NetworkClient::new(
+                        vec![],
+                        vec![ProtocolId::PeerExchangeRpc],
+                        hashmap! {self.network_context.network_id() => network_sender},
+                        self.peers_and_metadata.clone(),
+                    );
+                    DiscoveryChangeListener::peer_exchange(
+                        self.network_context,
+                        conn_mgr_reqs_tx.clone(),
+                        peer_exchange_discovery.clone(),
+                        &config.identity_key(),
+                        network_client,
+                        network_events,
+                        self.time_service.clone(),
+                    )
+                },
                 DiscoveryMethod::None => {
                     continue;
                 },
@@ -36,3 +36,12 @@ pub static NETWORK_KEY_MISMATCH: Lazy<IntGaugeVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static PENDING_PEER_EXCHANGE_NETWORK_EVENTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_pending_peer_exchange_events",
+        "Number of pending peer exchange events by state",
+        &["state"]
+    )
+    .unwrap()
+});
@@ -0,0 +1,529 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::DiscoveryError;
+use aptos_config::{
+    config::{Peer, PeerRole, PeerSet, HANDSHAKE_VERSION},
+    network_id::NetworkContext,
+};
+use aptos_crypto::{x25519, ValidCryptoMaterialStringExt};
+use aptos_logger::prelude::*;
+use aptos_network::logging::NetworkSchema;
+use aptos_time_service::{Interval, TimeService, TimeServiceTrait};
+use aptos_types::{
+    account_address::from_identity_public_key, network_address::NetworkAddress, PeerId,
+};
+use futures::{future::BoxFuture, Future, FutureExt, Stream};
+use hickory_resolver::{
+    config::{NameServerConfigGroup, ResolverConfig, ResolverOpts},
+    TokioAsyncResolver,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    net::{IpAddr, SocketAddr},
+    pin::Pin,
+    str::FromStr,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// The prefix identifying TXT records that describe seed peers
+const TXT_RECORD_PREFIX: &str = "aptos-seed";
+
+/// A minimal DNS resolver interface, allowing the DNS stream to be tested
+/// against a local stand-in instead of real name servers.
+pub trait DnsResolver: Send + Sync + 'static {
+    /// Returns the IP addresses of the A and AAAA records of the given name
+    fn lookup_ips(&self, name: String) -> BoxFuture<'static, Result<Vec<IpAddr>, DiscoveryError>>;
+
+    /// Returns the contents of the TXT records of the given name
+    fn lookup_txt(&self, name: String) -> BoxFuture<'static, Result<Vec<String>, DiscoveryError>>;
+}
+
+/// A `DnsResolver` backed by a hickory async resolver
+pub struct HickoryDnsResolver {
+    resolver: TokioAsyncResolver,
+}
+
+impl HickoryDnsResolver {
+    /// Creates a resolver that queries the given name servers, or the name
+    /// servers of the system configuration if none are given.
+    pub fn new(name_servers: &[SocketAddr]) -> Result<Self, DiscoveryError> {
+        let resolver = if name_servers.is_empty() {
+            TokioAsyncResolver::tokio_from_system_conf()
+                .map_err(|error| DiscoveryError::Dns(error.to_string()))?
+        } else {
+            let mut name_server_group = NameServerConfigGroup::new();
+            for name_server in name_servers {
+                name_server_group.merge(NameServerConfigGroup::from_ips_clear(
+                    &[name_server.ip()],
+                    name_server.port(),
+                    true,
+                ));
+            }
+            let config = ResolverConfig::from_parts(None, vec![], name_server_group);
+            TokioAsyncResolver::tokio(config, ResolverOpts::default())
+        };
+        Ok(Self { resolver })
+    }
+}
+
+impl DnsResolver for HickoryDnsResolver {
+    fn lookup_ips(&self, name: String) -> BoxFuture<'static, Result<Vec<IpAddr>, DiscoveryError>> {
+        let resolver = self.resolver.clone();
+        async move {
+            let lookup = resolver
+                .lookup_ip(name.as_str())
+                .await
+                .map_err(|error| DiscoveryError::Dns(error.to_string()))?;
+            Ok(lookup.iter().collect())
+        }
+        .boxed()
+    }
+
+    fn lookup_txt(&self, name: String) -> BoxFuture<'static, Result<Vec<String>, DiscoveryError>> {
+        let resolver = self.resolver.clone();
+        async move {
+            let lookup = resolver
+                .txt_lookup(name.as_str())
+                .await
+                .map_err(|error| DiscoveryError::Dns(error.to_string()))?;
+            Ok(lookup.iter().map(|txt| txt.to_string()).collect())
+        }
+        .boxed()
+    }
+}
+
+/// A seed peer described by a TXT record of the form:
+/// `aptos-seed peer_id=<hex> key=<hex> [host=<domain>] [port=<port>]`.
+///
+/// The `peer_id` is optional, and defaults to the ID derived from the key. The
+/// addresses of the seed are resolved from `host`, which defaults to the seed
+/// domain the TXT record belongs to.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct SeedRecord {
+    peer_id: PeerId,
+    key: x25519::PublicKey,
+    host: Option<String>,
+    port: Option<u16>,
+}
+
+impl FromStr for SeedRecord {
+    type Err = DiscoveryError;
+
+    fn from_str(txt: &str) -> Result<Self, Self::Err> {
+        let mut fields = txt.split_whitespace();
+        if fields.next() != Some(TXT_RECORD_PREFIX) {
+            return Err(DiscoveryError::Parsing(format!(
+                "Not a seed record: {:?}",
+                txt
+            )));
+        }
+
+        let mut values = HashMap::new();
+        for field in fields {
+            let (name, value) = field.split_once('=').ok_or_else(|| {
+                DiscoveryError::Parsing(format!("Malformed seed record field: {:?}", field))
+            })?;
+            values.insert(name, value);
+        }
+
+        let key = values
+            .get("key")
+            .ok_or_else(|| DiscoveryError::Parsing(format!("Seed record has no key: {:?}", txt)))
+            .and_then(|key| {
+                x25519::PublicKey::from_encoded_string(key)
+                    .map_err(|error| DiscoveryError::Parsing(error.to_string()))
+            })?;
+        let peer_id = match values.get("peer_id") {
+            Some(peer_id) => PeerId::from_str(peer_id)
+                .map_err(|error| DiscoveryError::Parsing(error.to_string()))?,
+            None => from_identity_public_key(key),
+        };
+        let port = values
+            .get("port")
+            .map(|port| {
+                port.parse::<u16>()
+                    .map_err(|error| DiscoveryError::Parsing(error.to_string()))
+            })
+            .transpose()?;
+
+        Ok(SeedRecord {
+            peer_id,
+            key,
+            host: values.get("host").map(|host| host.to_string()),
+            port,
+        })
+    }
+}
+
+/// A discovery stream that periodically resolves the seed domains through DNS
+pub struct DnsStream {
+    network_context: NetworkContext,
+    seed_domains: Vec<String>,
+    default_port: u16,
+    resolver: Arc<dyn DnsResolver>,
+    interval: Pin<Box<Interval>>,
+    pending_resolution: Option<BoxFuture<'static, Result<PeerSet, DiscoveryError>>>,
+}
+
+impl DnsStream {
+    pub(crate) fn new(
+        network_context: NetworkContext,
+        seed_domains: Vec<String>,
+        default_port: u16,
+        resolver: Arc<dyn DnsResolver>,
+        interval_duration: Duration,
+        time_service: TimeService,
+    ) -> Self {
+        DnsStream {
+            network_context,
+            seed_domains,
+            default_port,
+            resolver,
+            interval: Box::pin(time_service.interval(interval_duration)),
+            pending_resolution: None,
+        }
+    }
+}
+
+impl Stream for DnsStream {
+    type Item = Result<PeerSet, DiscoveryError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // Wait for delay before starting a new resolution
+        if self.pending_resolution.is_none() {
+            futures::ready!(self.interval.as_mut().poll_next(cx));
+            let resolution = resolve_seeds(
+                self.network_context,
+                self.seed_domains.clone(),
+                self.default_port,
+                self.resolver.clone(),
+            );
+            self.pending_resolution = Some(resolution.boxed());
+        }
+
+        let result = futures::ready!(self
+            .pending_resolution
+            .as_mut()
+            .expect("The resolution must be pending!")
+            .as_mut()
+            .poll(cx));
+        self.pending_resolution = None;
+        Poll::Ready(Some(result))
+    }
+}
+
+/// Resolves all seed domains into a peer set. Domains that fail to resolve
+/// are skipped, unless all of them fail.
+async fn resolve_seeds(
+    network_context: NetworkContext,
+    seed_domains: Vec<String>,
+    default_port: u16,
+    resolver: Arc<dyn DnsResolver>,
+) -> Result<PeerSet, DiscoveryError> {
+    let mut peer_set = PeerSet::new();
+    let mut last_error = None;
+    for seed_domain in seed_domains.iter() {
+        match resolve_seed_domain(
+            &network_context,
+            seed_domain,
+            default_port,
+            resolver.as_ref(),
+        )
+        .await
+        {
+            Ok(peers) => {
+                for (peer_id, peer) in peers {
+                    peer_set
+                        .entry(peer_id)
+                        .and_modify(|existing_peer: &mut Peer| {
+                            existing_peer.addresses.extend(peer.addresses.clone());
+                            existing_peer.keys.extend(peer.keys.clone());
+                        })
+                        .or_insert(peer);
+                }
+            },
+            Err(error) => {
+                warn!(
+                    NetworkSchema::new(&network_context),
+                    "{} Failed to resolve seed domain {}: {:?}",
+                    network_context,
+                    seed_domain,
+                    error
+                );
+                last_error = Some(error);
+            },
+        }
+    }
+
+    match last_error {
+        Some(error) if peer_set.is_empty() => Err(error),
+        _ => Ok(peer_set),
+    }
+}
+
+/// Resolves the seeds described by the TXT records of a single domain. Seed
+/// records that are malformed or whose host fails to resolve are skipped,
+/// unless none of the seeds resolve.
+async fn resolve_seed_domain(
+    network_context: &NetworkContext,
+    seed_domain: &str,
+    default_port: u16,
+    resolver: &dyn DnsResolver,
+) -> Result<PeerSet, DiscoveryError> {
+    let txts = resolver.lookup_txt(seed_domain.to_string()).await?;
+
+    let mut peer_set = PeerSet::new();
+    let mut last_error = None;
+    for txt in txts
+        .iter()
+        .filter(|txt| txt.split_whitespace().next() == Some(TXT_RECORD_PREFIX))
+    {
+        let seed_record = match SeedRecord::from_str(txt) {
+            Ok(seed_record) => seed_record,
+            Err(error) => {
+                warn!(
+                    NetworkSchema::new(network_context),
+                    "{} Skipping invalid seed record of {}: {:?}",
+                    network_context,
+                    seed_domain,
+                    error
+                );
+                last_error = Some(error);
+                continue;
+            },
+        };
+        let host = seed_record.host.as_deref().unwrap_or(seed_domain);
+        let port = seed_record.port.unwrap_or(default_port);
+        let ips = match resolver.lookup_ips(host.to_string()).await {
+            Ok(ips) => ips,
+            Err(error) => {
+                warn!(
+                    NetworkSchema::new(network_context),
+                    "{} Skipping seed {} of {}, failed to resolve host {}: {:?}",
+                    network_context,
+                    seed_record.peer_id,
+                    seed_domain,
+                    host,
+                    error
+                );
+                last_error = Some(error);
+                continue;
+            },
+        };
+        let addresses: Vec<NetworkAddress> = ips
+            .into_iter()
+            .map(|ip| {
+                NetworkAddress::from(SocketAddr::new(ip, port))
+                    .append_prod_protos(seed_record.key, HANDSHAKE_VERSION)
+            })
+            .collect();
+        if addresses.is_empty() {
+            continue;
+        }
+        let keys: HashSet<_> = [seed_record.key].into_iter().collect();
+        peer_set.insert(
+            seed_record.peer_id,
+            Peer::new(addresses, keys, PeerRole::Upstream),
+        );
+    }
+
+    match last_error {
+        Some(error) if peer_set.is_empty() => Err(error),
+        _ => Ok(peer_set),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_crypto::Uniform;
+    use futures::StreamExt;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    /// A local stand-in for a DNS server, serving records from memory
+    #[derive(Default)]
+    struct LocalDnsResolver {
+        ips: HashMap<String, Vec<IpAddr>>,
+        txts: HashMap<String, Vec<String>>,
+    }
+
+    impl DnsResolver for LocalDnsResolver {
+        fn lookup_ips(
+            &self,
+            name: String,
+        ) -> BoxFuture<'static, Result<Vec<IpAddr>, DiscoveryError>> {
+            let result = self
+                .ips
+                .get(&name)
+                .cloned()
+                .ok_or_else(|| DiscoveryError::Dns(format!("No A/AAAA records: {}", name)));
+            async move { result }.boxed()
+        }
+
+        fn lookup_txt(
+            &self,
+            name: String,
+        ) -> BoxFuture<'static, Result<Vec<String>, DiscoveryError>> {
+            let result = self
+                .txts
+                .get(&name)
+                .cloned()
+                .ok_or_else(|| DiscoveryError::Dns(format!("No TXT records: {}", name)));
+            async move { result }.boxed()
+        }
+    }
+
+    fn create_dns_stream(resolver: LocalDnsResolver, seed_domains: Vec<&str>) -> DnsStream {
+        DnsStream::new(
+            NetworkContext::mock(),
+            seed_domains.into_iter().map(str::to_string).collect(),
+            6182,
+            Arc::new(resolver),
+            Duration::from_millis(5),
+            TimeService::real(),
+        )
+    }
+
+    #[test]
+    fn test_parse_seed_record() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let key = x25519::PrivateKey::generate(&mut rng).public_key();
+        let peer_id = PeerId::random();
+
+        // Parse a full record
+        let txt = format!(
+            "aptos-seed peer_id={} key={} host=seed0.example.com port=6190",
+            peer_id.to_hex(),
+            key.to_encoded_string().unwrap()
+        );
+        assert_eq!(SeedRecord::from_str(&txt).unwrap(), SeedRecord {
+            peer_id,
+            key,
+            host: Some("seed0.example.com".into()),
+            port: Some(6190),
+        });
+
+        // The peer ID defaults to the one derived from the key
+        let txt = format!("aptos-seed key={}", key.to_encoded_string().unwrap());
+        assert_eq!(
+            SeedRecord::from_str(&txt).unwrap().peer_id,
+            from_identity_public_key(key)
+        );
+
+        // Invalid records are rejected
+        for txt in [
+            "v=spf1 -all",
+            "aptos-seed peer_id=0x1",
+            "aptos-seed key=zz",
+            "aptos-seed key",
+        ] {
+            assert!(SeedRecord::from_str(txt).is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dns_stream() {
+        let mut rng = StdRng::from_seed([1u8; 32]);
+        let key_0 = x25519::PrivateKey::generate(&mut rng).public_key();
+        let key_1 = x25519::PrivateKey::generate(&mut rng).public_key();
+        let ip_0 = IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4));
+        let ip_1 = IpAddr::V6(Ipv6Addr::LOCALHOST);
+
+        // Create a domain with one seed on the domain itself, and one on another host
+        let mut resolver = LocalDnsResolver::default();
+        resolver.txts.insert("seeds.example.com".into(), vec![
+            format!("aptos-seed key={}", key_0.to_encoded_string().unwrap()),
+            format!(
+                "aptos-seed key={} host=seed1.example.com port=6190",
+                key_1.to_encoded_string().unwrap()
+            ),
+            "unrelated txt record".into(),
+        ]);
+        resolver.ips.insert("seeds.example.com".into(), vec![ip_0]);
+        resolver.ips.insert("seed1.example.com".into(), vec![ip_1]);
+
+        // A domain that fails to resolve shouldn't prevent discovery
+        let mut stream =
+            create_dns_stream(resolver, vec!["seeds.example.com", "missing.example.com"]);
+        let peer_set = stream.next().await.unwrap().unwrap();
+
+        let mut expected_peer_set = PeerSet::new();
+        let address_0 = NetworkAddress::from(SocketAddr::new(ip_0, 6182))
+            .append_prod_protos(key_0, HANDSHAKE_VERSION);
+        let address_1 = NetworkAddress::from(SocketAddr::new(ip_1, 6190))
+            .append_prod_protos(key_1, HANDSHAKE_VERSION);
+        expected_peer_set.insert(
+            from_identity_public_key(key_0),
+            Peer::from_addrs(PeerRole::Upstream, vec![address_0]),
+        );
+        expected_peer_set.insert(
+            from_identity_public_key(key_1),
+            Peer::from_addrs(PeerRole::Upstream, vec![address_1]),
+        );
+        assert_eq!(peer_set, expected_peer_set);
+    }
+
+    #[tokio::test]
+    async fn test_dns_stream_skips_bad_seeds() {
+        let mut rng = StdRng::from_seed([2u8; 32]);
+        let key = x25519::PrivateKey::generate(&mut rng).public_key();
+        let ip = IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4));
+
+        // A domain with a valid seed, a malformed seed, and a seed on a missing host
+        let mut resolver = LocalDnsResolver::default();
+        resolver.txts.insert("seeds.example.com".into(), vec![
+            "aptos-seed key=zz".into(),
+            format!("aptos-seed key={}", key.to_encoded_string().unwrap()),
+            format!(
+                "aptos-seed key={} host=missing.example.com",
+                x25519::PrivateKey::generate(&mut rng)
+                    .public_key()
+                    .to_encoded_string()
+                    .unwrap()
+            ),
+        ]);
+        resolver.ips.insert("seeds.example.com".into(), vec![ip]);
+
+        // Only the valid seed is discovered
+        let mut stream = create_dns_stream(resolver, vec!["seeds.example.com"]);
+        let peer_set = stream.next().await.unwrap().unwrap();
+        let address = NetworkAddress::from(SocketAddr::new(ip, 6182))
+            .append_prod_protos(key, HANDSHAKE_VERSION);
+        let mut expected_peer_set = PeerSet::new();
+        expected_peer_set.insert(
+            from_identity_public_key(key),
+            Peer::from_addrs(PeerRole::Upstream, vec![address]),
+        );
+        assert_eq!(peer_set, expected_peer_set);
+
+        // If none of the seeds resolve, the domain fails to resolve
+        let mut resolver = LocalDnsResolver::default();
+        resolver.txts.insert("seeds.example.com".into(), vec![
+            "aptos-seed key=zz".into(),
+            format!(
+                "aptos-seed key={} host=missing.example.com",
+                key.to_encoded_string().unwrap()
+            ),
+        ]);
+        let mut stream = create_dns_stream(resolver, vec!["seeds.example.com"]);
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(DiscoveryError::Dns(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_dns_stream_failure() {
+        // If no domain resolves, the stream returns an error
+        let mut stream =
+            create_dns_stream(LocalDnsResolver::default(), vec!["missing.example.com"]);
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(DiscoveryError::Dns(_)))
+        ));
+    }
+}
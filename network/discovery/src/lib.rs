@@ -3,14 +3,22 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    counters::DISCOVERY_COUNTS, file::FileStream, rest::RestStream,
+    counters::DISCOVERY_COUNTS,
+    dns::{DnsResolver, DnsStream},
+    file::FileStream,
+    peer_exchange::{PeerExchangeMsg, PeerExchangeNetworkEvents, PeerExchangeStream},
+    rest::RestStream,
     validator_set::ValidatorSetStream,
 };
-use aptos_config::{config::PeerSet, network_id::NetworkContext};
+use aptos_config::{
+    config::{PeerExchangeDiscovery, PeerSet},
+    network_id::NetworkContext,
+};
 use aptos_crypto::x25519;
 use aptos_event_notifications::ReconfigNotificationListener;
 use aptos_logger::prelude::*;
 use aptos_network::{
+    application::interface::NetworkClient,
     connectivity_manager::{ConnectivityRequest, DiscoverySource},
     counters::inc_by_with_context,
     logging::NetworkSchema,
@@ -21,13 +29,16 @@ use futures::{Stream, StreamExt};
 use std::{
     path::Path,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
     time::Duration,
 };
 use tokio::runtime::Handle;
 
 mod counters;
+pub mod dns;
 mod file;
+pub mod peer_exchange;
 mod rest;
 mod validator_set;
 
@@ -36,6 +47,7 @@ pub enum DiscoveryError {
     IO(std::io::Error),
     Parsing(String),
     Rest(aptos_rest_client::error::RestError),
+    Dns(String),
 }
 
 /// A union type for all implementations of `DiscoveryChangeListenerTrait`
@@ -50,6 +62,8 @@ enum DiscoveryChangeStream<P: OnChainConfigProvider> {
     ValidatorSet(ValidatorSetStream<P>),
     File(FileStream),
     Rest(RestStream),
+    PeerExchange(PeerExchangeStream),
+    Dns(DnsStream),
 }
 
 impl<P: OnChainConfigProvider> Stream for DiscoveryChangeStream<P> {
@@ -60,6 +74,8 @@ impl<P: OnChainConfigProvider> Stream for DiscoveryChangeStream<P> {
             Self::ValidatorSet(stream) => Pin::new(stream).poll_next(cx),
             Self::File(stream) => Pin::new(stream).poll_next(cx),
             Self::Rest(stream) => Pin::new(stream).poll_next(cx),
+            Self::PeerExchange(stream) => Pin::new(stream).poll_next(cx),
+            Self::Dns(stream) => Pin::new(stream).poll_next(cx),
         }
     }
 }
@@ -125,7 +141,61 @@ impl<P: OnChainConfigProvider> DiscoveryChangeListener<P> {
         }
     }
 
-    pub fn start(self, executor: &Handle) {
+    pub fn peer_exchange(
+        network_context: NetworkContext,
+        update_channel: aptos_channels::Sender<ConnectivityRequest>,
+        config: PeerExchangeDiscovery,
+        identity_key: &x25519::PrivateKey,
+        network_client: NetworkClient<PeerExchangeMsg>,
+        network_events: PeerExchangeNetworkEvents,
+        time_service: TimeService,
+    ) -> Self {
+        let source_stream = DiscoveryChangeStream::PeerExchange(PeerExchangeStream::new(
+            network_context,
+            config,
+            identity_key,
+            network_client,
+            network_events,
+            time_service,
+        ));
+        DiscoveryChangeListener {
+            discovery_source: DiscoverySource::PeerExchange,
+            network_context,
+            update_channel,
+            source_stream,
+        }
+    }
+
+    pub fn dns(
+        network_context: NetworkContext,
+        update_channel: aptos_channels::Sender<ConnectivityRequest>,
+        seed_domains: Vec<String>,
+        default_port: u16,
+        resolver: Arc<dyn DnsResolver>,
+        interval_duration: Duration,
+        time_service: TimeService,
+    ) -> Self {
+        let source_stream = DiscoveryChangeStream::Dns(DnsStream::new(
+            network_context,
+            seed_domains,
+            default_port,
+            resolver,
+            interval_duration,
+            time_service,
+        ));
+        DiscoveryChangeListener {
+            discovery_source: DiscoverySource::Dns,
+            network_context,
+            update_channel,
+            source_stream,
+        }
+    }
+
+    pub fn start(mut self, executor: &Handle) {
+        // Peer exchange relies on a separate service to talk to other peers
+        if let DiscoveryChangeStream::PeerExchange(stream) = &mut self.source_stream {
+            stream.start_service(executor);
+        }
         spawn_named!("DiscoveryChangeListener", executor, Box::pin(self).run());
     }
 
@@ -0,0 +1,745 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Gossip based peer discovery for public networks
+//!
+//! Each node periodically asks a random connected peer for the address records
+//! it knows about. Responses contain the responder's own record, along with its
+//! highest scored records of other peers. Every record is signed by the peer it
+//! describes, so relays cannot alter addresses they forward.
+//!
+//! Records are signed with the node's network identity key (see
+//! `RecordSigningKey`), and every address in a record must carry the noise key
+//! the peer id is derived from. A record can thus only be created by the peer
+//! it describes.
+//!
+//! Records are scored (see `PeerRecordStore`), aged out after a configurable
+//! TTL, and optionally persisted across restarts. The highest scored peers are
+//! periodically handed to the connectivity manager.
+
+use crate::{
+    counters::{DISCOVERY_COUNTS, PENDING_PEER_EXCHANGE_NETWORK_EVENTS},
+    DiscoveryError,
+};
+use aptos_channels::{aptos_channel, message_queues::QueueStyle};
+use aptos_config::{
+    config::{PeerExchangeDiscovery, PeerSet, HANDSHAKE_VERSION},
+    network_id::{NetworkContext, PeerNetworkId},
+};
+use aptos_crypto::x25519;
+use aptos_logger::prelude::*;
+use aptos_network::{
+    application::interface::{NetworkClient, NetworkClientInterface},
+    constants::NETWORK_CHANNEL_SIZE,
+    counters::inc_by_with_context,
+    logging::NetworkSchema,
+    protocols::{
+        network::{
+            Event, NetworkApplicationConfig, NetworkClientConfig, NetworkEvents,
+            NetworkServiceConfig,
+        },
+        rpc::error::RpcError,
+    },
+    ProtocolId,
+};
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use aptos_types::{network_address::NetworkAddress, PeerId};
+use bytes::Bytes;
+use futures::{
+    channel::oneshot,
+    stream::{FuturesUnordered, StreamExt},
+    Stream,
+};
+use rand::{rngs::SmallRng, seq::SliceRandom, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tokio::runtime::Handle;
+
+mod record;
+mod store;
+
+pub use record::{PeerRecord, RecordError, RecordSigningKey, SignedPeerRecord};
+pub use store::PeerRecordStore;
+
+/// The interface from the network to the peer exchange service
+pub type PeerExchangeNetworkEvents = NetworkEvents<PeerExchangeMsg>;
+
+/// Returns a network application config for the peer exchange client and service
+pub fn peer_exchange_network_config() -> NetworkApplicationConfig {
+    let direct_send_protocols = vec![]; // Peer exchange doesn't use direct send
+    let rpc_protocols = vec![ProtocolId::PeerExchangeRpc];
+
+    let network_client_config =
+        NetworkClientConfig::new(direct_send_protocols.clone(), rpc_protocols.clone());
+    let network_service_config = NetworkServiceConfig::new(
+        direct_send_protocols,
+        rpc_protocols,
+        aptos_channel::Config::new(NETWORK_CHANNEL_SIZE)
+            .queue_style(QueueStyle::LIFO)
+            .counters(&PENDING_PEER_EXCHANGE_NETWORK_EVENTS),
+    );
+    NetworkApplicationConfig::new(network_client_config, network_service_config)
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum PeerExchangeMsg {
+    Request(PeerExchangeRequest),
+    Response(PeerExchangeResponse),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PeerExchangeRequest {
+    /// The maximum number of records the requester wants to receive
+    pub max_records: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum PeerExchangeResponse {
+    Records(Vec<SignedPeerRecord>),
+    /// The requester sent requests too frequently
+    RateLimited,
+}
+
+/// A discovery stream of the peers learned through peer exchange. The peer
+/// exchange service must be started (see `start_service`) for the stream to
+/// produce any updates.
+pub struct PeerExchangeStream {
+    service: Option<PeerExchangeService<NetworkClient<PeerExchangeMsg>>>,
+    peer_set_updates: aptos_channel::Receiver<(), PeerSet>,
+}
+
+impl PeerExchangeStream {
+    pub(crate) fn new(
+        network_context: NetworkContext,
+        config: PeerExchangeDiscovery,
+        identity_key: &x25519::PrivateKey,
+        network_client: NetworkClient<PeerExchangeMsg>,
+        network_events: PeerExchangeNetworkEvents,
+        time_service: TimeService,
+    ) -> Self {
+        let (peer_set_sender, peer_set_updates) = aptos_channel::new(QueueStyle::KLAST, 1, None);
+        let service = PeerExchangeService::new(
+            network_context,
+            config,
+            identity_key,
+            network_client,
+            network_events,
+            peer_set_sender,
+            time_service,
+        );
+        Self {
+            service: Some(service),
+            peer_set_updates,
+        }
+    }
+
+    pub(crate) fn start_service(&mut self, executor: &Handle) {
+        if let Some(service) = self.service.take() {
+            spawn_named!("[Network] PeerExchange", executor, service.start());
+        }
+    }
+}
+
+impl Stream for PeerExchangeStream {
+    type Item = Result<PeerSet, DiscoveryError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.peer_set_updates)
+            .poll_next(cx)
+            .map(|maybe_peer_set| maybe_peer_set.map(Ok))
+    }
+}
+
+/// The actor serving peer exchange requests and periodically querying peers
+pub struct PeerExchangeService<NetworkClient> {
+    network_context: NetworkContext,
+    config: PeerExchangeDiscovery,
+    time_service: TimeService,
+    network_client: NetworkClient,
+    network_events: PeerExchangeNetworkEvents,
+    /// The key used to sign our own record
+    signing_key: RecordSigningKey,
+    /// The addresses advertised in our own record
+    advertised_addresses: Vec<NetworkAddress>,
+    /// Our own record, refreshed before it gets too old
+    own_record: Option<SignedPeerRecord>,
+    store: PeerRecordStore,
+    /// The last time we served a request for each peer (used for rate limiting)
+    last_served_requests: HashMap<PeerId, Instant>,
+    peer_set_sender: aptos_channel::Sender<(), PeerSet>,
+    rng: SmallRng,
+}
+
+impl<NetworkClient: NetworkClientInterface<PeerExchangeMsg>> PeerExchangeService<NetworkClient> {
+    pub fn new(
+        network_context: NetworkContext,
+        config: PeerExchangeDiscovery,
+        identity_key: &x25519::PrivateKey,
+        network_client: NetworkClient,
+        network_events: PeerExchangeNetworkEvents,
+        peer_set_sender: aptos_channel::Sender<(), PeerSet>,
+        time_service: TimeService,
+    ) -> Self {
+        // Ensure all advertised addresses can be dialed with our network key
+        let identity_pubkey = identity_key.public_key();
+        let advertised_addresses = config
+            .advertised_addresses
+            .iter()
+            .map(|address| {
+                if address.is_aptosnet_addr() {
+                    address.clone()
+                } else {
+                    address
+                        .clone()
+                        .append_prod_protos(identity_pubkey, HANDSHAKE_VERSION)
+                }
+            })
+            .collect();
+
+        let store = PeerRecordStore::new(
+            network_context.peer_id(),
+            config.max_stored_records,
+            Duration::from_secs(config.record_ttl_secs).as_micros() as u64,
+        );
+
+        Self {
+            network_context,
+            config,
+            time_service,
+            network_client,
+            network_events,
+            signing_key: RecordSigningKey::from_identity_key(identity_key),
+            advertised_addresses,
+            own_record: None,
+            store,
+            last_served_requests: HashMap::new(),
+            peer_set_sender,
+            rng: SmallRng::from_entropy(),
+        }
+    }
+
+    pub async fn start(mut self) {
+        info!(
+            NetworkSchema::new(&self.network_context),
+            "{} Peer exchange service started", self.network_context
+        );
+        self.load_store();
+
+        let ticker = self
+            .time_service
+            .interval(Duration::from_secs(self.config.interval_secs));
+        tokio::pin!(ticker);
+        let mut pending_requests = FuturesUnordered::new();
+
+        loop {
+            futures::select! {
+                maybe_event = self.network_events.next() => {
+                    // Shutdown the service when this network instance shuts down
+                    let event = match maybe_event {
+                        Some(event) => event,
+                        None => break,
+                    };
+                    self.handle_network_event(event);
+                }
+                _ = ticker.select_next_some() => {
+                    self.refresh_store();
+                    if let Some(peer_network_id) = self.select_peer_to_query() {
+                        pending_requests.push(Self::query_peer(
+                            self.network_client.clone(),
+                            peer_network_id,
+                            self.config.max_records_per_response as u64,
+                            Duration::from_millis(self.config.rpc_timeout_ms),
+                        ));
+                    }
+                }
+                (peer_id, result) = pending_requests.select_next_some() => {
+                    self.handle_query_response(peer_id, result);
+                }
+            }
+        }
+        warn!(
+            NetworkSchema::new(&self.network_context),
+            "{} Peer exchange service terminated", self.network_context
+        );
+    }
+
+    fn handle_network_event(&mut self, event: Event<PeerExchangeMsg>) {
+        match event {
+            Event::RpcRequest(peer_id, PeerExchangeMsg::Request(request), protocol, res_tx) => {
+                self.handle_request(peer_id, request, protocol, res_tx)
+            },
+            Event::RpcRequest(peer_id, msg, _, _) => {
+                warn!(
+                    SecurityEvent::InvalidNetworkEvent,
+                    NetworkSchema::new(&self.network_context).remote_peer(&peer_id),
+                    "{} Unexpected peer exchange RPC from {}: {:?}",
+                    self.network_context,
+                    peer_id,
+                    msg
+                );
+            },
+            Event::Message(peer_id, msg) => {
+                warn!(
+                    SecurityEvent::InvalidNetworkEvent,
+                    NetworkSchema::new(&self.network_context).remote_peer(&peer_id),
+                    "{} Unexpected peer exchange direct send from {}: {:?}",
+                    self.network_context,
+                    peer_id,
+                    msg
+                );
+            },
+            Event::LostPeer(metadata) => {
+                self.last_served_requests.remove(&metadata.remote_peer_id);
+            },
+            Event::NewPeer(_) => {},
+        }
+    }
+
+    fn handle_request(
+        &mut self,
+        peer_id: PeerId,
+        request: PeerExchangeRequest,
+        protocol: ProtocolId,
+        res_tx: oneshot::Sender<Result<Bytes, RpcError>>,
+    ) {
+        // Rate limit the requests served to each peer
+        let now = self.time_service.now();
+        let min_request_interval = Duration::from_secs(self.config.min_request_interval_secs);
+        let is_rate_limited = self
+            .last_served_requests
+            .get(&peer_id)
+            .map_or(false, |last_served| {
+                now.duration_since(*last_served) < min_request_interval
+            });
+
+        let response = if is_rate_limited {
+            inc_by_with_context(
+                &DISCOVERY_COUNTS,
+                &self.network_context,
+                "peer_exchange_rate_limited",
+                1,
+            );
+            PeerExchangeResponse::RateLimited
+        } else {
+            self.last_served_requests.insert(peer_id, now);
+            let max_records =
+                (request.max_records as usize).min(self.config.max_records_per_response);
+            let now_usecs = self.now_usecs();
+            let mut records = Vec::with_capacity(max_records);
+            records.extend(self.own_record.clone());
+            records.extend(
+                self.store
+                    .best_records(max_records.saturating_sub(records.len()), now_usecs)
+                    .into_iter()
+                    .filter(|record| record.peer_id() != peer_id),
+            );
+            PeerExchangeResponse::Records(records)
+        };
+
+        match protocol.to_bytes(&PeerExchangeMsg::Response(response)) {
+            Ok(bytes) => {
+                let _ = res_tx.send(Ok(bytes.into()));
+            },
+            Err(error) => {
+                warn!(
+                    NetworkSchema::new(&self.network_context),
+                    error = ?error,
+                    "{} Unable to serialize peer exchange response: {}", self.network_context, error
+                );
+            },
+        }
+    }
+
+    fn handle_query_response(
+        &mut self,
+        peer_id: PeerId,
+        result: Result<PeerExchangeResponse, RpcError>,
+    ) {
+        let records = match result {
+            Ok(PeerExchangeResponse::Records(records)) => records,
+            Ok(PeerExchangeResponse::RateLimited) => {
+                debug!(
+                    NetworkSchema::new(&self.network_context).remote_peer(&peer_id),
+                    "{} Peer exchange request was rate limited by {}",
+                    self.network_context,
+                    peer_id
+                );
+                return;
+            },
+            Err(error) => {
+                inc_by_with_context(
+                    &DISCOVERY_COUNTS,
+                    &self.network_context,
+                    "peer_exchange_request_failure",
+                    1,
+                );
+                warn!(
+                    NetworkSchema::new(&self.network_context).remote_peer(&peer_id),
+                    error = ?error,
+                    "{} Peer exchange request to {} failed: {:?}", self.network_context, peer_id, error
+                );
+                return;
+            },
+        };
+
+        // Insert the (bounded number of) records into the store
+        let now_usecs = self.now_usecs();
+        let mut store_updated = false;
+        for record in records
+            .into_iter()
+            .take(self.config.max_records_per_response)
+        {
+            match self.store.insert(record, peer_id, now_usecs) {
+                Ok(updated) => store_updated |= updated,
+                Err(error) => {
+                    inc_by_with_context(
+                        &DISCOVERY_COUNTS,
+                        &self.network_context,
+                        "peer_exchange_invalid_record",
+                        1,
+                    );
+                    warn!(
+                        SecurityEvent::InvalidPeerExchangeRecord,
+                        NetworkSchema::new(&self.network_context).remote_peer(&peer_id),
+                        "{} Rejected peer record from {}: {}",
+                        self.network_context,
+                        peer_id,
+                        error
+                    );
+                },
+            }
+        }
+
+        if store_updated {
+            self.publish_peer_set();
+            self.save_store();
+        }
+    }
+
+    /// Refreshes our own record, drops expired records and publishes the discovered peers
+    fn refresh_store(&mut self) {
+        let now_usecs = self.now_usecs();
+        let record_ttl_usecs = Duration::from_secs(self.config.record_ttl_secs).as_micros() as u64;
+        let needs_refresh = self.own_record.as_ref().map_or(true, |record| {
+            now_usecs.saturating_sub(record.timestamp_usecs()) > record_ttl_usecs / 2
+        });
+        if needs_refresh && !self.advertised_addresses.is_empty() {
+            let record = PeerRecord {
+                peer_id: self.network_context.peer_id(),
+                addresses: self.advertised_addresses.clone(),
+                timestamp_usecs: now_usecs,
+            };
+            self.own_record = Some(SignedPeerRecord::sign(record, &self.signing_key));
+        }
+
+        let num_expired = self.store.remove_expired(now_usecs);
+        if num_expired > 0 {
+            self.save_store();
+        }
+        self.publish_peer_set();
+    }
+
+    /// Selects a random connected peer that supports peer exchange
+    fn select_peer_to_query(&mut self) -> Option<PeerNetworkId> {
+        let available_peers = match self.network_client.get_available_peers() {
+            Ok(available_peers) => available_peers,
+            Err(error) => {
+                warn!(
+                    NetworkSchema::new(&self.network_context),
+                    error = ?error,
+                    "{} Failed to get the available peers: {:?}", self.network_context, error
+                );
+                return None;
+            },
+        };
+        let network_id = self.network_context.network_id();
+        let peers: Vec<_> = available_peers
+            .into_iter()
+            .filter(|peer_network_id| peer_network_id.network_id() == network_id)
+            .collect();
+        peers.choose(&mut self.rng).cloned()
+    }
+
+    async fn query_peer(
+        network_client: NetworkClient,
+        peer_network_id: PeerNetworkId,
+        max_records: u64,
+        rpc_timeout: Duration,
+    ) -> (PeerId, Result<PeerExchangeResponse, RpcError>) {
+        let result = network_client
+            .send_to_peer_rpc(
+                PeerExchangeMsg::Request(PeerExchangeRequest { max_records }),
+                rpc_timeout,
+                peer_network_id,
+            )
+            .await
+            .map_err(|error| RpcError::Error(error.into()))
+            .and_then(|msg| match msg {
+                PeerExchangeMsg::Response(response) => Ok(response),
+                _ => Err(RpcError::InvalidRpcResponse),
+            });
+        (peer_network_id.peer_id(), result)
+    }
+
+    fn publish_peer_set(&mut self) {
+        let peer_set = self
+            .store
+            .peer_set(self.config.max_discovered_peers, self.now_usecs());
+        if let Err(error) = self.peer_set_sender.push((), peer_set) {
+            warn!(
+                NetworkSchema::new(&self.network_context),
+                "{} Failed to publish the discovered peers: {:?}", self.network_context, error
+            );
+        }
+    }
+
+    fn persistence_path(&self) -> Option<&PathBuf> {
+        self.config.persistence_path.as_ref()
+    }
+
+    fn load_store(&mut self) {
+        let now_usecs = self.now_usecs();
+        if let Some(path) = self.config.persistence_path.clone() {
+            match self.store.load(&path, now_usecs) {
+                Ok(num_loaded) => info!(
+                    NetworkSchema::new(&self.network_context),
+                    "{} Loaded {} peer records from {:?}", self.network_context, num_loaded, path
+                ),
+                Err(error) => warn!(
+                    NetworkSchema::new(&self.network_context),
+                    "{} Failed to load peer records from {:?}: {:?}",
+                    self.network_context,
+                    path,
+                    error
+                ),
+            }
+        }
+    }
+
+    fn save_store(&self) {
+        if let Some(path) = self.persistence_path() {
+            if let Err(error) = self.store.save(path) {
+                warn!(
+                    NetworkSchema::new(&self.network_context),
+                    "{} Failed to persist peer records to {:?}: {:?}",
+                    self.network_context,
+                    path,
+                    error
+                );
+            }
+        }
+    }
+
+    fn now_usecs(&self) -> u64 {
+        self.time_service.now_unix_time().as_micros() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_config::network_id::NetworkId;
+    use aptos_crypto::Uniform;
+    use aptos_network::{
+        application::storage::PeersAndMetadata,
+        peer_manager::{conn_notifs_channel, ConnectionRequestSender, PeerManagerRequestSender},
+        protocols::network::{NetworkSender, NewNetworkEvents, NewNetworkSender},
+    };
+    use aptos_time_service::MockTimeService;
+    use aptos_types::account_address::from_identity_public_key;
+    use futures::FutureExt;
+    use rand::rngs::StdRng;
+    use std::str::FromStr;
+
+    struct TestHarness {
+        mock_time: MockTimeService,
+        peer_set_updates: aptos_channel::Receiver<(), PeerSet>,
+    }
+
+    impl TestHarness {
+        fn new(
+            identity_key: &x25519::PrivateKey,
+            config: PeerExchangeDiscovery,
+        ) -> (Self, PeerExchangeService<NetworkClient<PeerExchangeMsg>>) {
+            let time_service = TimeService::mock();
+            let peer_id = from_identity_public_key(identity_key.public_key());
+            let network_context = NetworkContext::mock_with_peer_id(peer_id);
+
+            let (peer_mgr_reqs_tx, _) = aptos_channel::new(QueueStyle::FIFO, 1, None);
+            let (connection_reqs_tx, _) = aptos_channel::new(QueueStyle::FIFO, 1, None);
+            let (_, peer_mgr_notifs_rx) = aptos_channel::new(QueueStyle::FIFO, 1, None);
+            let (_, connection_notifs_rx) = conn_notifs_channel::new();
+            let network_sender = NetworkSender::new(
+                PeerManagerRequestSender::new(peer_mgr_reqs_tx),
+                ConnectionRequestSender::new(connection_reqs_tx),
+            );
+            let network_client = NetworkClient::new(
+                vec![],
+                vec![ProtocolId::PeerExchangeRpc],
+                [(NetworkId::Validator, network_sender)]
+                    .into_iter()
+                    .collect(),
+                PeersAndMetadata::new(&[NetworkId::Validator]),
+            );
+            let network_events = PeerExchangeNetworkEvents::new(
+                peer_mgr_notifs_rx,
+                connection_notifs_rx,
+                None,
+                true,
+            );
+
+            let (peer_set_sender, peer_set_updates) =
+                aptos_channel::new(QueueStyle::KLAST, 1, None);
+            let service = PeerExchangeService::new(
+                network_context,
+                config,
+                identity_key,
+                network_client,
+                network_events,
+                peer_set_sender,
+                time_service.clone(),
+            );
+            let harness = Self {
+                mock_time: time_service.into_mock(),
+                peer_set_updates,
+            };
+            (harness, service)
+        }
+
+        fn next_peer_set(&mut self) -> Option<PeerSet> {
+            self.peer_set_updates.next().now_or_never().flatten()
+        }
+    }
+
+    fn create_record(identity_key: &x25519::PrivateKey, timestamp_usecs: u64) -> SignedPeerRecord {
+        let address = NetworkAddress::from_str("/ip4/1.2.3.4/tcp/6180")
+            .unwrap()
+            .append_prod_protos(identity_key.public_key(), HANDSHAKE_VERSION);
+        let record = PeerRecord {
+            peer_id: from_identity_public_key(identity_key.public_key()),
+            addresses: vec![address],
+            timestamp_usecs,
+        };
+        SignedPeerRecord::sign(record, &RecordSigningKey::from_identity_key(identity_key))
+    }
+
+    fn serve_request(
+        service: &mut PeerExchangeService<NetworkClient<PeerExchangeMsg>>,
+        peer_id: PeerId,
+        max_records: u64,
+    ) -> PeerExchangeResponse {
+        let (res_tx, mut res_rx) = oneshot::channel();
+        let request = PeerExchangeRequest { max_records };
+        service.handle_request(peer_id, request, ProtocolId::PeerExchangeRpc, res_tx);
+        let bytes = res_rx.try_recv().unwrap().unwrap().unwrap();
+        match ProtocolId::PeerExchangeRpc.from_bytes(&bytes).unwrap() {
+            PeerExchangeMsg::Response(response) => response,
+            msg => panic!("Unexpected peer exchange message: {:?}", msg),
+        }
+    }
+
+    #[test]
+    fn test_serve_own_record() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let identity_key = x25519::PrivateKey::generate(&mut rng);
+        let config = PeerExchangeDiscovery {
+            advertised_addresses: vec![NetworkAddress::from_str("/ip4/1.2.3.4/tcp/6182").unwrap()],
+            ..Default::default()
+        };
+        let (_harness, mut service) = TestHarness::new(&identity_key, config);
+
+        // Our own record is signed with the identity key, and advertises it in every address
+        service.refresh_store();
+        let records = match serve_request(&mut service, PeerId::random(), 10) {
+            PeerExchangeResponse::Records(records) => records,
+            response => panic!("Unexpected response: {:?}", response),
+        };
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.verify(), Ok(()));
+        assert_eq!(record.peer_id(), service.network_context.peer_id());
+        assert_eq!(
+            record.record().addresses[0].find_noise_proto(),
+            Some(identity_key.public_key())
+        );
+    }
+
+    #[test]
+    fn test_rate_limiting() {
+        let mut rng = StdRng::from_seed([1u8; 32]);
+        let identity_key = x25519::PrivateKey::generate(&mut rng);
+        let config = PeerExchangeDiscovery::default();
+        let min_request_interval = Duration::from_secs(config.min_request_interval_secs);
+        let (harness, mut service) = TestHarness::new(&identity_key, config);
+
+        // Repeated requests from the same peer are rate limited, but other peers are served
+        let peer_id = PeerId::random();
+        assert!(matches!(
+            serve_request(&mut service, peer_id, 10),
+            PeerExchangeResponse::Records(_)
+        ));
+        assert!(matches!(
+            serve_request(&mut service, peer_id, 10),
+            PeerExchangeResponse::RateLimited
+        ));
+        assert!(matches!(
+            serve_request(&mut service, PeerId::random(), 10),
+            PeerExchangeResponse::Records(_)
+        ));
+
+        // Once the interval has passed, the peer is served again
+        harness.mock_time.advance(min_request_interval);
+        assert!(matches!(
+            serve_request(&mut service, peer_id, 10),
+            PeerExchangeResponse::Records(_)
+        ));
+    }
+
+    #[test]
+    fn test_handle_query_response() {
+        let mut rng = StdRng::from_seed([2u8; 32]);
+        let identity_key = x25519::PrivateKey::generate(&mut rng);
+        let (mut harness, mut service) =
+            TestHarness::new(&identity_key, PeerExchangeDiscovery::default());
+
+        // A peer responds with its own record, a relayed record and a forged record
+        let peer_key = x25519::PrivateKey::generate(&mut rng);
+        let peer_record = create_record(&peer_key, 0);
+        let peer_id = peer_record.peer_id();
+        let relayed_record = create_record(&x25519::PrivateKey::generate(&mut rng), 0);
+        let forged_record = SignedPeerRecord::sign(
+            relayed_record.record().clone(),
+            &RecordSigningKey::from_identity_key(&peer_key),
+        );
+        let records = vec![peer_record.clone(), forged_record, relayed_record.clone()];
+        service.handle_query_response(peer_id, Ok(PeerExchangeResponse::Records(records)));
+
+        // Only the valid records are stored and published
+        let peer_set = harness.next_peer_set().unwrap();
+        assert_eq!(peer_set.len(), 2);
+        for record in [&peer_record, &relayed_record] {
+            assert_eq!(
+                peer_set[&record.peer_id()].addresses,
+                record.record().addresses
+            );
+        }
+
+        // The stored records are relayed to other peers, but never back to the peer they describe
+        let records = match serve_request(&mut service, peer_id, 10) {
+            PeerExchangeResponse::Records(records) => records,
+            response => panic!("Unexpected response: {:?}", response),
+        };
+        assert_eq!(records, vec![relayed_record]);
+
+        // Failed requests leave the store untouched
+        service.handle_query_response(peer_id, Err(RpcError::TimedOut));
+        assert!(harness.next_peer_set().is_none());
+        assert_eq!(service.store.len(), 2);
+    }
+}
@@ -0,0 +1,165 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_crypto::{
+    ed25519::{Ed25519PublicKey, Ed25519Signature},
+    hash::HashValue,
+    signing_message, x25519, Signature, ValidCryptoMaterial,
+};
+use aptos_crypto_derive::{BCSCryptoHash, CryptoHasher};
+use aptos_types::{
+    account_address::from_identity_public_key, network_address::NetworkAddress, PeerId,
+};
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, fmt};
+
+/// Domain separator used to derive the signing nonce key from the network identity key
+const NONCE_KEY_DERIVATION_SALT: &[u8] = b"APTOS::PeerExchange::NonceKey";
+
+/// The (unsigned) address record of a single peer
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, CryptoHasher, BCSCryptoHash)]
+pub struct PeerRecord {
+    pub peer_id: PeerId,
+    pub addresses: Vec<NetworkAddress>,
+    /// The time at which the record was created by its owner
+    pub timestamp_usecs: u64,
+}
+
+/// A peer record signed by the peer it describes
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SignedPeerRecord {
+    record: PeerRecord,
+    public_key: Ed25519PublicKey,
+    signature: Ed25519Signature,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum RecordError {
+    /// The record does not contain any dialable addresses
+    NoAddresses,
+    /// An address is not a valid aptosnet address (e.g., missing noise or handshake protocols)
+    InvalidAddress(NetworkAddress),
+    /// The noise key of an address doesn't derive the peer id of the record
+    PeerIdMismatch(NetworkAddress),
+    /// The record was not signed by the identity key advertised in its addresses
+    SigningKeyMismatch,
+    /// The signature doesn't match the record and public key
+    InvalidSignature,
+    /// The record is older than the allowed record TTL
+    Expired,
+    /// The record was created (too far) in the future
+    FromTheFuture,
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl SignedPeerRecord {
+    pub fn sign(record: PeerRecord, signing_key: &RecordSigningKey) -> Self {
+        let signature = signing_key.sign(&record);
+        Self {
+            record,
+            public_key: signing_key.public_key().clone(),
+            signature,
+        }
+    }
+
+    pub fn record(&self) -> &PeerRecord {
+        &self.record
+    }
+
+    pub fn peer_id(&self) -> PeerId {
+        self.record.peer_id
+    }
+
+    pub fn public_key(&self) -> &Ed25519PublicKey {
+        &self.public_key
+    }
+
+    pub fn timestamp_usecs(&self) -> u64 {
+        self.record.timestamp_usecs
+    }
+
+    /// Verifies that the record is well formed and signed by the peer it describes,
+    /// i.e., that the noise key of every address derives the peer id, and that the
+    /// record was signed by that same key (see `RecordSigningKey`).
+    pub fn verify(&self) -> Result<(), RecordError> {
+        if self.record.addresses.is_empty() {
+            return Err(RecordError::NoAddresses);
+        }
+        let signing_key = x25519::PublicKey::from_ed25519_public_bytes(&self.public_key.to_bytes())
+            .map_err(|_| RecordError::SigningKeyMismatch)?;
+        for address in &self.record.addresses {
+            let identity_key = match address.find_noise_proto() {
+                Some(identity_key) if address.is_aptosnet_addr() => identity_key,
+                _ => return Err(RecordError::InvalidAddress(address.clone())),
+            };
+            if from_identity_public_key(identity_key) != self.record.peer_id {
+                return Err(RecordError::PeerIdMismatch(address.clone()));
+            }
+            if identity_key != signing_key {
+                return Err(RecordError::SigningKeyMismatch);
+            }
+        }
+        self.signature
+            .verify(&self.record, &self.public_key)
+            .map_err(|_| RecordError::InvalidSignature)
+    }
+}
+
+/// The key used to sign our own peer records.
+///
+/// The network identity key is an x25519 key that cannot sign directly, so records
+/// are signed XEdDSA style: the identity scalar is used as an Ed25519 scalar. The
+/// Montgomery form of the resulting Ed25519 public key is the identity public key,
+/// which lets anyone check that a record was signed by the peer whose noise key
+/// (and thus peer id) it advertises.
+pub struct RecordSigningKey {
+    expanded_key: ed25519_dalek::ExpandedSecretKey,
+    public_key: Ed25519PublicKey,
+}
+
+impl RecordSigningKey {
+    pub fn from_identity_key(identity_key: &x25519::PrivateKey) -> Self {
+        // Clamp the scalar exactly like x25519 does, so both keys share the same point
+        let mut scalar = [0u8; 32];
+        scalar.copy_from_slice(&identity_key.to_bytes());
+        scalar[0] &= 248;
+        scalar[31] &= 127;
+        scalar[31] |= 64;
+
+        // The nonce key only needs to be secret and stable, so it's derived from the scalar
+        let mut nonce_seed = NONCE_KEY_DERIVATION_SALT.to_vec();
+        nonce_seed.extend(scalar);
+        let nonce_key = HashValue::sha3_256_of(&nonce_seed);
+
+        let mut expanded_bytes = [0u8; 64];
+        expanded_bytes[..32].copy_from_slice(&scalar);
+        expanded_bytes[32..].copy_from_slice(nonce_key.as_ref());
+        let expanded_key = ed25519_dalek::ExpandedSecretKey::from_bytes(&expanded_bytes)
+            .expect("Any 64 bytes are a valid expanded Ed25519 key!");
+        let public_key = ed25519_dalek::PublicKey::from(&expanded_key);
+        let public_key = Ed25519PublicKey::try_from(&public_key.to_bytes()[..])
+            .expect("The identity public key should be a valid Ed25519 point!");
+        Self {
+            expanded_key,
+            public_key,
+        }
+    }
+
+    pub fn public_key(&self) -> &Ed25519PublicKey {
+        &self.public_key
+    }
+
+    fn sign(&self, record: &PeerRecord) -> Ed25519Signature {
+        let message =
+            signing_message(record).expect("Serializing a peer record should never fail!");
+        let public_key = ed25519_dalek::PublicKey::from(&self.expanded_key);
+        let signature = self.expanded_key.sign(&message, &public_key);
+        Ed25519Signature::try_from(&signature.to_bytes()[..])
+            .expect("Ed25519 signatures should always be canonical!")
+    }
+}
@@ -0,0 +1,420 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    peer_exchange::record::{RecordError, SignedPeerRecord},
+    DiscoveryError,
+};
+use aptos_config::config::{Peer, PeerRole, PeerSet};
+use aptos_types::PeerId;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeSet, HashMap},
+    path::Path,
+};
+
+/// Maximum clock skew tolerated for records created in the future
+const MAX_CLOCK_SKEW_USECS: u64 = 60_000_000; // 1 minute
+/// Maximum number of distinct reporters tracked per record
+const MAX_TRACKED_REPORTERS: usize = 10;
+
+/// Score components (see `StoredRecord::score`)
+const FIRST_HAND_SCORE: u64 = 100;
+const MAX_FRESHNESS_SCORE: u64 = 100;
+const REPORTER_SCORE: u64 = 10;
+
+/// A peer record, along with the information used to score it
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+struct StoredRecord {
+    signed_record: SignedPeerRecord,
+    /// Whether the record was received directly from the peer it describes
+    first_hand: bool,
+    /// The distinct peers that reported the record (capped at `MAX_TRACKED_REPORTERS`)
+    reporters: BTreeSet<PeerId>,
+}
+
+impl StoredRecord {
+    fn new(signed_record: SignedPeerRecord, reporter: PeerId) -> Self {
+        let first_hand = signed_record.peer_id() == reporter;
+        let mut stored_record = Self {
+            signed_record,
+            first_hand,
+            reporters: BTreeSet::new(),
+        };
+        stored_record.add_reporter(reporter);
+        stored_record
+    }
+
+    fn add_reporter(&mut self, reporter: PeerId) {
+        if self.reporters.len() < MAX_TRACKED_REPORTERS {
+            self.reporters.insert(reporter);
+        }
+    }
+
+    fn age_usecs(&self, now_usecs: u64) -> u64 {
+        now_usecs.saturating_sub(self.signed_record.timestamp_usecs())
+    }
+
+    /// Records are scored by freshness, by whether they were received first
+    /// hand, and by how many distinct peers vouched for them.
+    fn score(&self, now_usecs: u64, record_ttl_usecs: u64) -> u64 {
+        let record_ttl_usecs = record_ttl_usecs.max(1);
+        let remaining_usecs = record_ttl_usecs.saturating_sub(self.age_usecs(now_usecs));
+        let freshness_score = (remaining_usecs as u128 * MAX_FRESHNESS_SCORE as u128
+            / record_ttl_usecs as u128) as u64;
+        let first_hand_score = if self.first_hand { FIRST_HAND_SCORE } else { 0 };
+        let reporter_score = self.reporters.len() as u64 * REPORTER_SCORE;
+        freshness_score + first_hand_score + reporter_score
+    }
+}
+
+/// A bounded store of the peer records learned through peer exchange.
+///
+/// Only records signed by the peer they describe are stored (see
+/// `SignedPeerRecord::verify`), so relays can't alter or forge records.
+pub struct PeerRecordStore {
+    own_peer_id: PeerId,
+    records: HashMap<PeerId, StoredRecord>,
+    max_records: usize,
+    record_ttl_usecs: u64,
+}
+
+impl PeerRecordStore {
+    pub fn new(own_peer_id: PeerId, max_records: usize, record_ttl_usecs: u64) -> Self {
+        Self {
+            own_peer_id,
+            records: HashMap::new(),
+            max_records,
+            record_ttl_usecs,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Inserts the given record reported by `reporter`. Returns true iff the
+    /// store changed in a way that affects the discovered peers.
+    pub fn insert(
+        &mut self,
+        signed_record: SignedPeerRecord,
+        reporter: PeerId,
+        now_usecs: u64,
+    ) -> Result<bool, RecordError> {
+        signed_record.verify()?;
+        let timestamp_usecs = signed_record.timestamp_usecs();
+        if timestamp_usecs > now_usecs.saturating_add(MAX_CLOCK_SKEW_USECS) {
+            return Err(RecordError::FromTheFuture);
+        }
+        if now_usecs.saturating_sub(timestamp_usecs) > self.record_ttl_usecs {
+            return Err(RecordError::Expired);
+        }
+
+        // We never store records about ourselves
+        let peer_id = signed_record.peer_id();
+        if peer_id == self.own_peer_id {
+            return Ok(false);
+        }
+
+        let first_hand = peer_id == reporter;
+        if let Some(stored_record) = self.records.get_mut(&peer_id) {
+            stored_record.add_reporter(reporter);
+            stored_record.first_hand |= first_hand;
+            if timestamp_usecs > stored_record.signed_record.timestamp_usecs() {
+                let addresses_changed = stored_record.signed_record.record().addresses
+                    != signed_record.record().addresses;
+                stored_record.signed_record = signed_record;
+                return Ok(addresses_changed);
+            }
+            return Ok(false);
+        }
+
+        // Make room for the new record by evicting the lowest scored record (if it scores lower)
+        let stored_record = StoredRecord::new(signed_record, reporter);
+        if self.records.len() >= self.max_records {
+            let new_score = stored_record.score(now_usecs, self.record_ttl_usecs);
+            let lowest_scored = self
+                .records
+                .iter()
+                .map(|(peer_id, record)| (record.score(now_usecs, self.record_ttl_usecs), *peer_id))
+                .min();
+            match lowest_scored {
+                Some((lowest_score, lowest_peer_id)) if lowest_score < new_score => {
+                    self.records.remove(&lowest_peer_id);
+                },
+                _ => return Ok(false),
+            }
+        }
+        self.records.insert(peer_id, stored_record);
+        Ok(true)
+    }
+
+    /// Removes all records older than the record TTL. Returns the number of removed records.
+    pub fn remove_expired(&mut self, now_usecs: u64) -> usize {
+        let num_records = self.records.len();
+        let record_ttl_usecs = self.record_ttl_usecs;
+        self.records
+            .retain(|_, record| record.age_usecs(now_usecs) <= record_ttl_usecs);
+        num_records - self.records.len()
+    }
+
+    /// Returns the (at most) `max_records` highest scored records
+    pub fn best_records(&self, max_records: usize, now_usecs: u64) -> Vec<SignedPeerRecord> {
+        let mut records: Vec<_> = self.records.values().collect();
+        records.sort_by_key(|record| {
+            (
+                Reverse(record.score(now_usecs, self.record_ttl_usecs)),
+                record.signed_record.peer_id(),
+            )
+        });
+        records
+            .into_iter()
+            .take(max_records)
+            .map(|record| record.signed_record.clone())
+            .collect()
+    }
+
+    /// Returns the (at most) `max_peers` highest scored peers as a discovered peer set
+    pub fn peer_set(&self, max_peers: usize, now_usecs: u64) -> PeerSet {
+        self.best_records(max_peers, now_usecs)
+            .into_iter()
+            .map(|signed_record| {
+                let record = signed_record.record();
+                (
+                    record.peer_id,
+                    Peer::from_addrs(PeerRole::Upstream, record.addresses.clone()),
+                )
+            })
+            .collect()
+    }
+
+    /// Atomically writes the store to the given file
+    pub fn save(&self, path: &Path) -> Result<(), DiscoveryError> {
+        let records: Vec<&StoredRecord> = self.records.values().collect();
+        let bytes =
+            bcs::to_bytes(&records).map_err(|error| DiscoveryError::Parsing(error.to_string()))?;
+        let temp_path = path.with_extension("tmp");
+        std::fs::write(&temp_path, bytes).map_err(DiscoveryError::IO)?;
+        std::fs::rename(&temp_path, path).map_err(DiscoveryError::IO)
+    }
+
+    /// Loads the records persisted in the given file (if it exists). Records
+    /// that are invalid or expired are dropped. Returns the number of loaded records.
+    pub fn load(&mut self, path: &Path, now_usecs: u64) -> Result<usize, DiscoveryError> {
+        if !path.exists() {
+            return Ok(0);
+        }
+        let bytes = std::fs::read(path).map_err(DiscoveryError::IO)?;
+        let records: Vec<StoredRecord> =
+            bcs::from_bytes(&bytes).map_err(|error| DiscoveryError::Parsing(error.to_string()))?;
+
+        let mut num_loaded = 0;
+        for record in records {
+            let peer_id = record.signed_record.peer_id();
+            let is_valid = record.signed_record.verify().is_ok()
+                && record.age_usecs(now_usecs) <= self.record_ttl_usecs
+                && peer_id != self.own_peer_id;
+            if is_valid && self.records.len() < self.max_records {
+                self.records.insert(peer_id, record);
+                num_loaded += 1;
+            }
+        }
+        Ok(num_loaded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::peer_exchange::record::{PeerRecord, RecordSigningKey};
+    use aptos_crypto::{x25519, Uniform};
+    use aptos_temppath::TempPath;
+    use aptos_types::{account_address::from_identity_public_key, network_address::NetworkAddress};
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::str::FromStr;
+
+    const RECORD_TTL_USECS: u64 = 1_000_000_000;
+
+    fn ip_tcp_address() -> NetworkAddress {
+        NetworkAddress::from_str("/ip4/1.2.3.4/tcp/6180").unwrap()
+    }
+
+    fn aptosnet_address(identity_key: &x25519::PrivateKey, port: u16) -> NetworkAddress {
+        NetworkAddress::from_str(&format!("/ip4/1.2.3.4/tcp/{}", port))
+            .unwrap()
+            .append_prod_protos(identity_key.public_key(), 0)
+    }
+
+    fn create_record(identity_key: &x25519::PrivateKey, timestamp_usecs: u64) -> SignedPeerRecord {
+        let record = PeerRecord {
+            peer_id: from_identity_public_key(identity_key.public_key()),
+            addresses: vec![aptosnet_address(identity_key, 6180)],
+            timestamp_usecs,
+        };
+        SignedPeerRecord::sign(record, &RecordSigningKey::from_identity_key(identity_key))
+    }
+
+    #[test]
+    fn test_insert_and_update() {
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let mut store = PeerRecordStore::new(PeerId::random(), 10, RECORD_TTL_USECS);
+
+        // Insert a relayed record and verify the store updates
+        let identity_key = x25519::PrivateKey::generate(&mut rng);
+        let reporter = PeerId::random();
+        let record = create_record(&identity_key, 100);
+        let peer_id = record.peer_id();
+        assert_eq!(store.insert(record, reporter, 200), Ok(true));
+
+        // A newer record with the same addresses doesn't change the discovered peers
+        assert_eq!(
+            store.insert(create_record(&identity_key, 150), reporter, 200),
+            Ok(false)
+        );
+
+        // A newer record with other addresses does
+        let record = PeerRecord {
+            peer_id,
+            addresses: vec![aptosnet_address(&identity_key, 6190)],
+            timestamp_usecs: 160,
+        };
+        let signing_key = RecordSigningKey::from_identity_key(&identity_key);
+        let record = SignedPeerRecord::sign(record, &signing_key);
+        assert_eq!(store.insert(record.clone(), peer_id, 200), Ok(true));
+
+        // Older records are ignored
+        assert_eq!(
+            store.insert(create_record(&identity_key, 120), reporter, 200),
+            Ok(false)
+        );
+        assert_eq!(store.best_records(10, 200), vec![record]);
+    }
+
+    #[test]
+    fn test_insert_forged_records() {
+        let mut rng = StdRng::from_seed([1u8; 32]);
+        let mut store = PeerRecordStore::new(PeerId::random(), 10, RECORD_TTL_USECS);
+        let identity_key = x25519::PrivateKey::generate(&mut rng);
+        let peer_id = from_identity_public_key(identity_key.public_key());
+        let other_identity_key = x25519::PrivateKey::generate(&mut rng);
+        let other_signing_key = RecordSigningKey::from_identity_key(&other_identity_key);
+
+        // A record advertising the peer's addresses, but signed by another key, is rejected
+        let record = PeerRecord {
+            peer_id,
+            addresses: vec![aptosnet_address(&identity_key, 6180)],
+            timestamp_usecs: 100,
+        };
+        let record = SignedPeerRecord::sign(record, &other_signing_key);
+        assert_eq!(
+            store.insert(record, peer_id, 200),
+            Err(RecordError::SigningKeyMismatch)
+        );
+
+        // A record advertising other addresses for the peer is rejected
+        let address = aptosnet_address(&other_identity_key, 6180);
+        let record = PeerRecord {
+            peer_id,
+            addresses: vec![address.clone()],
+            timestamp_usecs: 100,
+        };
+        let record = SignedPeerRecord::sign(record, &other_signing_key);
+        assert_eq!(
+            store.insert(record, peer_id, 200),
+            Err(RecordError::PeerIdMismatch(address))
+        );
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_insert_invalid_records() {
+        let mut rng = StdRng::from_seed([2u8; 32]);
+        let mut store = PeerRecordStore::new(PeerId::random(), 10, RECORD_TTL_USECS);
+        let identity_key = x25519::PrivateKey::generate(&mut rng);
+
+        // Expired records and records from the future are rejected
+        let record = create_record(&identity_key, 0);
+        assert_eq!(
+            store.insert(record, PeerId::random(), RECORD_TTL_USECS + 1),
+            Err(RecordError::Expired)
+        );
+        let record = create_record(&identity_key, u64::MAX);
+        assert_eq!(
+            store.insert(record, PeerId::random(), 0),
+            Err(RecordError::FromTheFuture)
+        );
+
+        // Records without aptosnet addresses are rejected
+        let record = PeerRecord {
+            peer_id: from_identity_public_key(identity_key.public_key()),
+            addresses: vec![ip_tcp_address()],
+            timestamp_usecs: 0,
+        };
+        let record =
+            SignedPeerRecord::sign(record, &RecordSigningKey::from_identity_key(&identity_key));
+        assert_eq!(
+            store.insert(record, PeerId::random(), 0),
+            Err(RecordError::InvalidAddress(ip_tcp_address()))
+        );
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_eviction_and_expiration() {
+        let mut rng = StdRng::from_seed([3u8; 32]);
+        let mut store = PeerRecordStore::new(PeerId::random(), 2, RECORD_TTL_USECS);
+
+        // Fill the store with two relayed records
+        for timestamp_usecs in [10, 20] {
+            let record = create_record(&x25519::PrivateKey::generate(&mut rng), timestamp_usecs);
+            assert_eq!(store.insert(record, PeerId::random(), 100), Ok(true));
+        }
+
+        // An older relayed record doesn't evict anything
+        let record = create_record(&x25519::PrivateKey::generate(&mut rng), 0);
+        assert_eq!(store.insert(record, PeerId::random(), 100), Ok(false));
+
+        // A first hand record evicts the lowest scored record
+        let record = create_record(&x25519::PrivateKey::generate(&mut rng), 0);
+        let peer_id = record.peer_id();
+        assert_eq!(store.insert(record, peer_id, 100), Ok(true));
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.best_records(1, 100)[0].peer_id(), peer_id);
+
+        // Once the TTL has passed, all records expire
+        assert_eq!(store.remove_expired(RECORD_TTL_USECS + 100), 2);
+        assert!(store.peer_set(10, RECORD_TTL_USECS + 100).is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let mut rng = StdRng::from_seed([4u8; 32]);
+        let own_peer_id = PeerId::random();
+        let mut store = PeerRecordStore::new(own_peer_id, 10, RECORD_TTL_USECS);
+        for _ in 0..5 {
+            let record = create_record(&x25519::PrivateKey::generate(&mut rng), 10);
+            store.insert(record, PeerId::random(), 100).unwrap();
+        }
+
+        // Persist the store and load it into a new store
+        let path = TempPath::new();
+        store.save(path.path()).unwrap();
+        let mut loaded_store = PeerRecordStore::new(own_peer_id, 10, RECORD_TTL_USECS);
+        assert_eq!(loaded_store.load(path.path(), 100).unwrap(), 5);
+        assert_eq!(loaded_store.peer_set(10, 100), store.peer_set(10, 100));
+
+        // Expired records are dropped when loading
+        let mut loaded_store = PeerRecordStore::new(own_peer_id, 10, RECORD_TTL_USECS);
+        assert_eq!(
+            loaded_store
+                .load(path.path(), RECORD_TTL_USECS + 100)
+                .unwrap(),
+            0
+        );
+    }
+}
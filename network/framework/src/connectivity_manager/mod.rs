@@ -133,14 +133,16 @@ pub struct ConnectivityManager<TBackoff> {
 }
 
 /// Different sources for peer addresses, ordered by priority (Onchain=highest,
-/// Config=lowest).
+/// PeerExchange=lowest).
 #[repr(u8)]
 #[derive(Copy, Clone, Eq, Hash, PartialEq, Ord, PartialOrd, NumVariants, Serialize)]
 pub enum DiscoverySource {
     OnChainValidatorSet,
     File,
     Rest,
+    Dns,
     Config,
+    PeerExchange,
 }
 
 impl fmt::Debug for DiscoverySource {
@@ -156,6 +158,8 @@ impl fmt::Display for DiscoverySource {
             DiscoverySource::File => "File",
             DiscoverySource::Config => "Config",
             DiscoverySource::Rest => "Rest",
+            DiscoverySource::Dns => "Dns",
+            DiscoverySource::PeerExchange => "PeerExchange",
         })
     }
 }
@@ -73,6 +73,7 @@ pub enum ProtocolId {
     JWKConsensusRpcJson = 26,
     ConsensusObserver = 27,
     ConsensusObserverRpc = 28,
+    PeerExchangeRpc = 29,
 }
 
 /// The encoding types for Protocols
@@ -115,6 +116,7 @@ impl ProtocolId {
             JWKConsensusRpcJson => "JWKConsensusRpcJson",
             ConsensusObserver => "ConsensusObserver",
             ConsensusObserverRpc => "ConsensusObserverRpc",
+            PeerExchangeRpc => "PeerExchangeRpc",
         }
     }
 
@@ -150,6 +152,7 @@ impl ProtocolId {
             ProtocolId::JWKConsensusRpcJson,
             ProtocolId::ConsensusObserver,
             ProtocolId::ConsensusObserverRpc,
+            ProtocolId::PeerExchangeRpc,
         ]
     }
 
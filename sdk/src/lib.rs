@@ -11,6 +11,7 @@
 //! * `crypto` - Types used for signing and verifying
 //! * `move_types` - Includes types used when interacting with the Move VM
 //! * `rest_client` - The Aptos API Client, used for sending requests to the Aptos Blockchain.
+//! * `submission_manager` - Pipelined submission of many transactions, managing sequence numbers and resubmissions
 //! * `transaction_builder` - Includes helpers for constructing transactions
 //! * `types` - Includes types for Aptos on-chain data structures
 //!
@@ -36,6 +37,8 @@ pub mod rest_client {
     pub use aptos_rest_client::*;
 }
 
+pub mod submission_manager;
+
 pub mod transaction_builder;
 
 pub mod types;
@@ -0,0 +1,956 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A transaction submission manager for high-throughput senders.
+//!
+//! The manager keeps a local window of sequence numbers per account, signs the payloads it is
+//! given into that window and pipelines them to a node through batch submission. Commits are
+//! tracked by polling the on-chain sequence number of the account and fetching the committed
+//! transactions. Transactions that are rejected with a retriable error (i.e., a stale sequence
+//! number, an expired transaction or a full mempool) or that expire before being committed are
+//! signed again and resubmitted, after resyncing the window with the chain. The sequence number
+//! of a transaction rejected with any other error is reused by the next payload, or filled with a
+//! transfer of 0 coins from the account to itself once no payload is left, so that the
+//! transactions after it don't wait until they expire.
+
+use crate::{
+    move_types::vm_status::StatusCode,
+    rest_client::{
+        aptos_api_types::{AptosError, AptosErrorCode, TransactionOnChainData},
+        error::RestError,
+        Client,
+    },
+    transaction_builder::{aptos_stdlib, TransactionFactory},
+    types::{
+        account_address::AccountAddress,
+        transaction::{SignedTransaction, Transaction, TransactionPayload},
+        LocalAccount,
+    },
+};
+use anyhow::{ensure, Result};
+use futures::future::try_join_all;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    time::Duration,
+};
+
+/// The maximum number of committed transactions fetched per request
+const COMMITTED_TRANSACTIONS_PAGE_SIZE: u16 = 100;
+
+#[derive(Clone, Debug)]
+pub struct SubmissionManagerConfig {
+    /// The maximum number of submitted but not yet committed transactions per account
+    pub max_in_flight_per_account: usize,
+    /// The maximum number of transactions per batch submission request
+    pub max_batch_size: usize,
+    /// The maximum number of times a payload is signed and submitted before it is dropped
+    pub max_attempts: usize,
+    /// The interval at which the sequence numbers of the accounts are polled
+    pub poll_interval: Duration,
+    /// The time to back off for once the mempool of the node is full
+    pub mempool_full_backoff: Duration,
+}
+
+impl Default for SubmissionManagerConfig {
+    fn default() -> Self {
+        Self {
+            max_in_flight_per_account: 100,
+            max_batch_size: 100,
+            max_attempts: 3,
+            poll_interval: Duration::from_millis(500),
+            mempool_full_backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+impl SubmissionManagerConfig {
+    /// Checks that the config allows the manager to make progress
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            self.max_in_flight_per_account > 0,
+            "max_in_flight_per_account must be greater than 0"
+        );
+        ensure!(
+            self.max_batch_size > 0,
+            "max_batch_size must be greater than 0"
+        );
+        ensure!(self.max_attempts > 0, "max_attempts must be greater than 0");
+        Ok(())
+    }
+}
+
+/// The outcome of the submission of a single payload
+#[derive(Debug)]
+pub enum SubmissionOutcome {
+    /// The transaction was committed. Note: this doesn't mean it executed successfully, see
+    /// the status of `info`.
+    Committed(Box<TransactionOnChainData>),
+    /// The transaction was rejected on submission with a non-retriable error
+    Rejected(AptosError),
+    /// The transaction was not committed within the maximum number of attempts
+    Dropped,
+}
+
+impl SubmissionOutcome {
+    pub fn is_committed(&self) -> bool {
+        matches!(self, SubmissionOutcome::Committed(_))
+    }
+}
+
+/// Signs and submits transactions for any number of accounts, managing their sequence numbers
+#[derive(Clone, Debug)]
+pub struct SubmissionManager {
+    client: Client,
+    transaction_factory: TransactionFactory,
+    config: SubmissionManagerConfig,
+}
+
+impl SubmissionManager {
+    pub fn new(
+        client: Client,
+        transaction_factory: TransactionFactory,
+        config: SubmissionManagerConfig,
+    ) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            client,
+            transaction_factory,
+            config,
+        })
+    }
+
+    /// Submits all `payloads` on behalf of `account` and waits for them to be committed. The
+    /// outcomes are returned in the order of the payloads. Note: to fill sequence numbers left
+    /// unused by rejected transactions, payloads may be committed out of order, and filler
+    /// transactions may be committed in addition to the payloads.
+    ///
+    /// The manager takes over the sequence number of `account` for the duration of the call,
+    /// so the account must not be used to sign other transactions concurrently.
+    pub async fn submit_and_wait(
+        &self,
+        account: &LocalAccount,
+        payloads: Vec<TransactionPayload>,
+    ) -> Result<Vec<SubmissionOutcome>> {
+        let (sequence_number, _) = self.get_sequence_number(account.address()).await?;
+        let mut window = AccountWindow::new(payloads.len(), sequence_number, &self.config);
+
+        while !window.is_done() {
+            let batch = window.next_batch();
+            let submitted_any = !batch.is_empty();
+            for chunk in batch.chunks(self.config.max_batch_size) {
+                let txns: Vec<_> = chunk
+                    .iter()
+                    .map(|(index, sequence_number)| {
+                        let payload = match index {
+                            Some(index) => payloads[*index].clone(),
+                            None => aptos_stdlib::aptos_account_transfer(account.address(), 0),
+                        };
+                        let raw_txn = self
+                            .transaction_factory
+                            .payload(payload)
+                            .sender(account.address())
+                            .sequence_number(*sequence_number)
+                            .build();
+                        account.sign_transaction(raw_txn)
+                    })
+                    .collect();
+                if window.submit(&self.client, chunk, txns).await {
+                    tokio::time::sleep(self.config.mempool_full_backoff).await;
+                }
+            }
+
+            // Keep the window full as long as there are payloads left, and only wait for
+            // commits once nothing else can be submitted
+            if !submitted_any {
+                tokio::time::sleep(self.config.poll_interval).await;
+            }
+            let (sequence_number, timestamp_secs) =
+                self.get_sequence_number(account.address()).await?;
+            if let Some(start) = window.first_unconfirmed(sequence_number) {
+                let committed = self
+                    .get_committed_transactions(account.address(), start, sequence_number)
+                    .await?;
+                window.confirm(sequence_number, committed);
+            }
+            window.sync(sequence_number, timestamp_secs);
+        }
+
+        account.set_sequence_number(window.next_sequence_number());
+        Ok(window.into_outcomes())
+    }
+
+    /// Runs `submit_and_wait` for multiple accounts concurrently
+    pub async fn submit_and_wait_many(
+        &self,
+        requests: Vec<(&LocalAccount, Vec<TransactionPayload>)>,
+    ) -> Result<Vec<Vec<SubmissionOutcome>>> {
+        try_join_all(
+            requests
+                .into_iter()
+                .map(|(account, payloads)| self.submit_and_wait(account, payloads)),
+        )
+        .await
+    }
+
+    /// Returns the on-chain sequence number of the account, along with the ledger timestamp
+    async fn get_sequence_number(&self, address: AccountAddress) -> Result<(u64, u64)> {
+        match self.client.get_account_bcs(address).await {
+            Ok(response) => Ok((
+                response.inner().sequence_number(),
+                Duration::from_micros(response.state().timestamp_usecs).as_secs(),
+            )),
+            Err(RestError::Api(error))
+                if matches!(error.error.error_code, AptosErrorCode::AccountNotFound) =>
+            {
+                // An account that doesn't exist yet has a sequence number of 0
+                let timestamp_usecs = error.state.map_or(0, |state| state.timestamp_usecs);
+                Ok((0, Duration::from_micros(timestamp_usecs).as_secs()))
+            },
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Returns the transactions of the account with sequence numbers in `[start, end)`
+    async fn get_committed_transactions(
+        &self,
+        address: AccountAddress,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<TransactionOnChainData>> {
+        let mut transactions = vec![];
+        let mut next = start;
+        while next < end {
+            let limit = (end - next).min(COMMITTED_TRANSACTIONS_PAGE_SIZE as u64) as u16;
+            let page = self
+                .client
+                .get_account_transactions_bcs(address, Some(next), Some(limit))
+                .await?
+                .into_inner();
+            if page.is_empty() {
+                break;
+            }
+            next += page.len() as u64;
+            transactions.extend(page);
+        }
+        Ok(transactions)
+    }
+}
+
+/// How a rejected submission is handled
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Rejection {
+    /// The sequence number was already used on chain
+    SequenceNumberTooOld,
+    /// The transaction expired before it was submitted
+    Expired,
+    /// The mempool of the node is full
+    MempoolFull,
+    /// Any other error, which is returned to the caller
+    Other,
+}
+
+impl From<&AptosError> for Rejection {
+    fn from(error: &AptosError) -> Self {
+        match error.error_code {
+            AptosErrorCode::SequenceNumberTooOld => return Rejection::SequenceNumberTooOld,
+            AptosErrorCode::MempoolIsFull => return Rejection::MempoolFull,
+            _ => {},
+        }
+        match error
+            .vm_error_code
+            .and_then(|code| StatusCode::try_from(code).ok())
+        {
+            Some(StatusCode::SEQUENCE_NUMBER_TOO_OLD) => Rejection::SequenceNumberTooOld,
+            Some(StatusCode::TRANSACTION_EXPIRED) => Rejection::Expired,
+            _ => Rejection::Other,
+        }
+    }
+}
+
+/// The local sequence number window of a single account. Transactions are identified by the
+/// index of their payload, or by `None` for the fillers of holes.
+struct AccountWindow {
+    max_in_flight: usize,
+    max_attempts: usize,
+    /// The payloads (by index) that are waiting to be signed
+    pending: VecDeque<usize>,
+    /// The submitted and not yet committed transactions, by sequence number
+    in_flight: BTreeMap<u64, (Option<usize>, SignedTransaction)>,
+    /// Sequence numbers below `next_sequence_number` that are not used by any transaction,
+    /// e.g., because the submission was rejected. These are filled first.
+    holes: BTreeSet<u64>,
+    /// Holes whose filler was rejected with a non-retriable error, which are not filled again
+    unfillable: BTreeSet<u64>,
+    next_sequence_number: u64,
+    attempts: Vec<usize>,
+    outcomes: Vec<Option<SubmissionOutcome>>,
+}
+
+impl AccountWindow {
+    fn new(num_payloads: usize, sequence_number: u64, config: &SubmissionManagerConfig) -> Self {
+        Self {
+            max_in_flight: config.max_in_flight_per_account,
+            max_attempts: config.max_attempts,
+            pending: (0..num_payloads).collect(),
+            in_flight: BTreeMap::new(),
+            holes: BTreeSet::new(),
+            unfillable: BTreeSet::new(),
+            next_sequence_number: sequence_number,
+            attempts: vec![0; num_payloads],
+            outcomes: (0..num_payloads).map(|_| None).collect(),
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.pending.is_empty() && self.in_flight.is_empty()
+    }
+
+    /// The sequence number the account should use once the window is done
+    fn next_sequence_number(&self) -> u64 {
+        self.holes
+            .first()
+            .copied()
+            .unwrap_or(self.next_sequence_number)
+    }
+
+    /// Assigns sequence numbers to as many pending payloads as the window allows. Once no
+    /// payload is left, the holes below in flight transactions get a filler, as these
+    /// transactions can't be committed otherwise.
+    fn next_batch(&mut self) -> Vec<(Option<usize>, u64)> {
+        let mut batch = vec![];
+        while self.in_flight.len() + batch.len() < self.max_in_flight {
+            let Some(index) = self.pending.pop_front() else {
+                break;
+            };
+            let sequence_number = self.holes.pop_first().unwrap_or_else(|| {
+                self.next_sequence_number += 1;
+                self.next_sequence_number - 1
+            });
+            self.attempts[index] += 1;
+            batch.push((Some(index), sequence_number));
+        }
+        if self.pending.is_empty() {
+            let last_in_flight = self.in_flight.keys().next_back().copied().unwrap_or(0);
+            let fillable: Vec<_> = self
+                .holes
+                .range(..last_in_flight)
+                .filter(|sequence_number| !self.unfillable.contains(sequence_number))
+                .copied()
+                .collect();
+            for sequence_number in fillable {
+                self.holes.remove(&sequence_number);
+                batch.push((None, sequence_number));
+            }
+        }
+        batch
+    }
+
+    /// Submits the signed transactions of a batch, returning true if the mempool is full
+    async fn submit(
+        &mut self,
+        client: &Client,
+        batch: &[(Option<usize>, u64)],
+        txns: Vec<SignedTransaction>,
+    ) -> bool {
+        let failures: HashMap<_, _> = match client.submit_batch_bcs(&txns).await {
+            Ok(response) => response
+                .into_inner()
+                .transaction_failures
+                .into_iter()
+                .map(|failure| (failure.transaction_index, failure.error))
+                .collect(),
+            // The transactions may or may not have reached the mempool, so they are treated
+            // as in flight: if they never commit, they are resubmitted once expired.
+            Err(_) => HashMap::new(),
+        };
+
+        let mut mempool_full = false;
+        for (position, (&(index, sequence_number), txn)) in batch.iter().zip(txns).enumerate() {
+            match failures.get(&position) {
+                Some(error) => {
+                    mempool_full |=
+                        self.on_rejected(index, sequence_number, error) == Rejection::MempoolFull
+                },
+                None => {
+                    self.in_flight.insert(sequence_number, (index, txn));
+                },
+            }
+        }
+        mempool_full
+    }
+
+    fn on_rejected(
+        &mut self,
+        index: Option<usize>,
+        sequence_number: u64,
+        error: &AptosError,
+    ) -> Rejection {
+        let rejection = Rejection::from(error);
+        if rejection != Rejection::SequenceNumberTooOld {
+            self.holes.insert(sequence_number);
+        }
+        match (rejection, index) {
+            (Rejection::Other, Some(index)) => {
+                self.outcomes[index] = Some(SubmissionOutcome::Rejected(error.clone()))
+            },
+            (Rejection::Other, None) => {
+                self.unfillable.insert(sequence_number);
+            },
+            _ => self.retry(index),
+        }
+        rejection
+    }
+
+    /// Schedules the payload of a transaction that wasn't committed to be signed again. Fillers
+    /// are not retried, holes get a new one if still needed.
+    fn retry(&mut self, index: Option<usize>) {
+        let Some(index) = index else {
+            return;
+        };
+        if self.attempts[index] < self.max_attempts {
+            self.pending.push_front(index);
+        } else {
+            self.outcomes[index] = Some(SubmissionOutcome::Dropped);
+        }
+    }
+
+    /// Returns the lowest in flight sequence number that was used on chain, if any
+    fn first_unconfirmed(&self, onchain_sequence_number: u64) -> Option<u64> {
+        self.in_flight
+            .keys()
+            .next()
+            .copied()
+            .filter(|sequence_number| *sequence_number < onchain_sequence_number)
+    }
+
+    /// Resolves the in flight transactions whose sequence numbers were used on chain
+    fn confirm(&mut self, onchain_sequence_number: u64, committed: Vec<TransactionOnChainData>) {
+        for data in committed {
+            let Transaction::UserTransaction(txn) = &data.transaction else {
+                continue;
+            };
+            if let Some((index, submitted)) = self.in_flight.remove(&txn.sequence_number()) {
+                if submitted.committed_hash() == data.info.transaction_hash() {
+                    if let Some(index) = index {
+                        self.outcomes[index] = Some(SubmissionOutcome::Committed(Box::new(data)));
+                    }
+                } else {
+                    // The sequence number was used by a transaction not sent by this manager
+                    self.retry(index);
+                }
+            }
+        }
+
+        // Anything else below the on-chain sequence number can't be committed anymore
+        let stale: Vec<_> = self
+            .in_flight
+            .range(..onchain_sequence_number)
+            .map(|(sequence_number, _)| *sequence_number)
+            .collect();
+        for sequence_number in stale {
+            let (index, _) = self.in_flight.remove(&sequence_number).unwrap();
+            self.retry(index);
+        }
+    }
+
+    /// Resyncs the window with the on-chain sequence number of the account
+    fn sync(&mut self, onchain_sequence_number: u64, ledger_timestamp_secs: u64) {
+        // Once all outstanding transactions have expired, none of them can be committed and
+        // the window restarts from the on-chain sequence number
+        if self
+            .in_flight
+            .values()
+            .all(|(_, txn)| txn.expiration_timestamp_secs() <= ledger_timestamp_secs)
+        {
+            let expired = std::mem::take(&mut self.in_flight);
+            for (index, _) in expired.into_values().rev() {
+                self.retry(index);
+            }
+            self.next_sequence_number = onchain_sequence_number;
+        }
+
+        self.next_sequence_number = self.next_sequence_number.max(onchain_sequence_number);
+        self.holes = (onchain_sequence_number..self.next_sequence_number)
+            .filter(|sequence_number| !self.in_flight.contains_key(sequence_number))
+            .collect();
+    }
+
+    fn into_outcomes(self) -> Vec<SubmissionOutcome> {
+        self.outcomes
+            .into_iter()
+            .map(|outcome| outcome.unwrap_or(SubmissionOutcome::Dropped))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        rest_client::aptos_api_types::{
+            TransactionsBatchSingleSubmissionFailure, TransactionsBatchSubmissionResult,
+            X_APTOS_BLOCK_HEIGHT, X_APTOS_CHAIN_ID, X_APTOS_EPOCH, X_APTOS_LEDGER_OLDEST_VERSION,
+            X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION, X_APTOS_OLDEST_BLOCK_HEIGHT,
+        },
+        transaction_builder::aptos_stdlib,
+        types::{
+            account_config::AccountResource,
+            chain_id::ChainId,
+            event::{EventHandle, EventKey},
+            transaction::{ExecutionStatus, TransactionInfo},
+            write_set::WriteSet,
+        },
+    };
+    use aptos_crypto::HashValue;
+    use std::{
+        net::SocketAddr,
+        sync::{Arc, Mutex},
+    };
+    use warp::{http::Response, hyper::body::Bytes, Filter};
+
+    /// A minimal node that commits submitted transactions as soon as the sequence number of
+    /// their sender allows it. The first `mempool_full_count` submissions are rejected as if
+    /// the mempool was full, and transactions with a payload in `invalid_payloads` are always
+    /// rejected.
+    #[derive(Default)]
+    struct MockNode {
+        committed: Vec<SignedTransaction>,
+        parked: BTreeMap<u64, SignedTransaction>,
+        mempool_full_count: usize,
+        invalid_payloads: Vec<TransactionPayload>,
+    }
+
+    impl MockNode {
+        fn submit(&mut self, txns: Vec<SignedTransaction>) -> TransactionsBatchSubmissionResult {
+            let mut transaction_failures = vec![];
+            for (transaction_index, txn) in txns.into_iter().enumerate() {
+                if self.mempool_full_count > 0 {
+                    self.mempool_full_count -= 1;
+                    transaction_failures.push(TransactionsBatchSingleSubmissionFailure {
+                        error: AptosError::new_with_error_code(
+                            "Mempool is full",
+                            AptosErrorCode::MempoolIsFull,
+                        ),
+                        transaction_index,
+                    });
+                } else if self.invalid_payloads.contains(txn.payload()) {
+                    transaction_failures.push(TransactionsBatchSingleSubmissionFailure {
+                        error: error(
+                            AptosErrorCode::VmError,
+                            Some(StatusCode::FUNCTION_NOT_FOUND),
+                        ),
+                        transaction_index,
+                    });
+                } else if txn.sequence_number() >= self.committed.len() as u64 {
+                    self.parked.insert(txn.sequence_number(), txn);
+                }
+            }
+            while let Some(txn) = self.parked.remove(&(self.committed.len() as u64)) {
+                self.committed.push(txn);
+            }
+            TransactionsBatchSubmissionResult {
+                transaction_failures,
+            }
+        }
+
+        fn account(&self, address: AccountAddress) -> AccountResource {
+            AccountResource::new(
+                self.committed.len() as u64,
+                address.to_vec(),
+                EventHandle::new(EventKey::new(0, address), 0),
+                EventHandle::new(EventKey::new(1, address), 0),
+            )
+        }
+
+        fn transactions(&self, start: usize, limit: usize) -> Vec<TransactionOnChainData> {
+            self.committed
+                .iter()
+                .enumerate()
+                .skip(start)
+                .take(limit)
+                .map(|(version, txn)| TransactionOnChainData {
+                    version: version as u64,
+                    transaction: Transaction::UserTransaction(txn.clone()),
+                    info: TransactionInfo::new(
+                        txn.committed_hash(),
+                        HashValue::zero(),
+                        HashValue::zero(),
+                        None,
+                        0,
+                        ExecutionStatus::Success,
+                    ),
+                    events: vec![],
+                    accumulator_root_hash: HashValue::zero(),
+                    changes: WriteSet::default(),
+                })
+                .collect()
+        }
+
+        fn response(&self, body: Vec<u8>) -> Response<Vec<u8>> {
+            let version = self.committed.len().to_string();
+            Response::builder()
+                .header(X_APTOS_CHAIN_ID, ChainId::test().id().to_string())
+                .header(X_APTOS_LEDGER_VERSION, version.as_str())
+                .header(X_APTOS_LEDGER_OLDEST_VERSION, "0")
+                .header(X_APTOS_LEDGER_TIMESTAMP, "0")
+                .header(X_APTOS_EPOCH, "1")
+                .header(X_APTOS_BLOCK_HEIGHT, version.as_str())
+                .header(X_APTOS_OLDEST_BLOCK_HEIGHT, "0")
+                .body(body)
+                .unwrap()
+        }
+    }
+
+    /// Serves the REST endpoints used by the manager on a local port
+    fn start_mock_node(node: Arc<Mutex<MockNode>>) -> SocketAddr {
+        let with_node = warp::any().map(move || node.clone());
+        let account = warp::path!("v1" / "accounts" / AccountAddress)
+            .and(warp::get())
+            .and(with_node.clone())
+            .map(|address, node: Arc<Mutex<MockNode>>| {
+                let node = node.lock().unwrap();
+                node.response(bcs::to_bytes(&node.account(address)).unwrap())
+            });
+        let transactions = warp::path!("v1" / "accounts" / AccountAddress / "transactions")
+            .and(warp::get())
+            .and(warp::query::<HashMap<String, usize>>())
+            .and(with_node.clone())
+            .map(
+                |_, query: HashMap<String, usize>, node: Arc<Mutex<MockNode>>| {
+                    let node = node.lock().unwrap();
+                    let start = query.get("start").copied().unwrap_or(0);
+                    let limit = query.get("limit").copied().unwrap_or(usize::MAX);
+                    node.response(bcs::to_bytes(&node.transactions(start, limit)).unwrap())
+                },
+            );
+        let submit = warp::path!("v1" / "transactions" / "batch")
+            .and(warp::post())
+            .and(warp::body::bytes())
+            .and(with_node)
+            .map(|body: Bytes, node: Arc<Mutex<MockNode>>| {
+                let mut node = node.lock().unwrap();
+                let result = node.submit(bcs::from_bytes(&body).unwrap());
+                node.response(bcs::to_bytes(&result).unwrap())
+            });
+
+        let (address, server) = warp::serve(account.or(transactions).or(submit))
+            .bind_ephemeral(SocketAddr::from(([127, 0, 0, 1], 0)));
+        tokio::spawn(server);
+        address
+    }
+
+    fn window(num_payloads: usize, max_in_flight: usize) -> AccountWindow {
+        let config = SubmissionManagerConfig {
+            max_in_flight_per_account: max_in_flight,
+            ..SubmissionManagerConfig::default()
+        };
+        AccountWindow::new(num_payloads, 10, &config)
+    }
+
+    fn sign(
+        account: &LocalAccount,
+        sequence_number: u64,
+        expiration_secs: u64,
+    ) -> SignedTransaction {
+        let raw_txn = TransactionFactory::new(ChainId::test())
+            .payload(aptos_stdlib::aptos_account_transfer(AccountAddress::ONE, 1))
+            .sender(account.address())
+            .sequence_number(sequence_number)
+            .expiration_timestamp_secs(expiration_secs)
+            .build();
+        account.sign_transaction(raw_txn)
+    }
+
+    fn error(error_code: AptosErrorCode, vm_error_code: Option<StatusCode>) -> AptosError {
+        AptosError {
+            message: "".to_string(),
+            error_code,
+            vm_error_code: vm_error_code.map(|code| code as u64),
+        }
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(SubmissionManagerConfig::default().validate().is_ok());
+        for config in [
+            SubmissionManagerConfig {
+                max_in_flight_per_account: 0,
+                ..SubmissionManagerConfig::default()
+            },
+            SubmissionManagerConfig {
+                max_batch_size: 0,
+                ..SubmissionManagerConfig::default()
+            },
+            SubmissionManagerConfig {
+                max_attempts: 0,
+                ..SubmissionManagerConfig::default()
+            },
+        ] {
+            let client = Client::new("http://localhost:8080".parse().unwrap());
+            let transaction_factory = TransactionFactory::new(ChainId::test());
+            assert!(SubmissionManager::new(client, transaction_factory, config).is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_submit_and_wait() {
+        let node = Arc::new(Mutex::new(MockNode {
+            mempool_full_count: 1,
+            ..MockNode::default()
+        }));
+        let address = start_mock_node(node.clone());
+        let client = Client::new(format!("http://{}", address).parse().unwrap());
+        let config = SubmissionManagerConfig {
+            max_in_flight_per_account: 2,
+            poll_interval: Duration::from_millis(10),
+            mempool_full_backoff: Duration::from_millis(10),
+            ..SubmissionManagerConfig::default()
+        };
+        let manager =
+            SubmissionManager::new(client, TransactionFactory::new(ChainId::test()), config)
+                .unwrap();
+
+        // All payloads are committed in order, including the one rejected by the full mempool
+        let account = LocalAccount::generate(&mut rand::rngs::OsRng);
+        let payloads: Vec<_> = (0..5)
+            .map(|amount| aptos_stdlib::aptos_account_transfer(AccountAddress::ONE, amount))
+            .collect();
+        let outcomes = manager
+            .submit_and_wait(&account, payloads.clone())
+            .await
+            .unwrap();
+        assert_eq!(outcomes.len(), 5);
+        for (outcome, payload) in outcomes.iter().zip(&payloads) {
+            let SubmissionOutcome::Committed(data) = outcome else {
+                panic!("Unexpected outcome: {:?}", outcome);
+            };
+            let Transaction::UserTransaction(txn) = &data.transaction else {
+                panic!("Unexpected transaction: {:?}", data.transaction);
+            };
+            assert_eq!(txn.payload(), payload);
+        }
+        assert_eq!(account.sequence_number(), 5);
+        assert_eq!(node.lock().unwrap().committed.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_submit_and_wait_fills_rejected_sequence_number() {
+        let account = LocalAccount::generate(&mut rand::rngs::OsRng);
+        let payloads: Vec<_> = (0..3)
+            .map(|amount| aptos_stdlib::aptos_account_transfer(AccountAddress::ONE, amount))
+            .collect();
+        let node = Arc::new(Mutex::new(MockNode {
+            invalid_payloads: vec![payloads[1].clone()],
+            ..MockNode::default()
+        }));
+        let address = start_mock_node(node.clone());
+        let client = Client::new(format!("http://{}", address).parse().unwrap());
+        let config = SubmissionManagerConfig {
+            max_in_flight_per_account: 3,
+            poll_interval: Duration::from_millis(10),
+            ..SubmissionManagerConfig::default()
+        };
+        let manager =
+            SubmissionManager::new(client, TransactionFactory::new(ChainId::test()), config)
+                .unwrap();
+
+        // The transaction after the rejected one is committed without waiting for it to expire
+        // (which never happens on the mock node), once a filler took the sequence number
+        let outcomes = tokio::time::timeout(
+            Duration::from_secs(10),
+            manager.submit_and_wait(&account, payloads.clone()),
+        )
+        .await
+        .expect("The transaction after the rejected one should be committed")
+        .unwrap();
+        assert!(outcomes[0].is_committed());
+        assert!(matches!(outcomes[1], SubmissionOutcome::Rejected(_)));
+        assert!(outcomes[2].is_committed());
+        assert_eq!(account.sequence_number(), 3);
+
+        let node = node.lock().unwrap();
+        let committed_payloads: Vec<_> = node.committed.iter().map(|txn| txn.payload()).collect();
+        assert_eq!(committed_payloads, vec![
+            &payloads[0],
+            &aptos_stdlib::aptos_account_transfer(account.address(), 0),
+            &payloads[2],
+        ]);
+    }
+
+    #[test]
+    fn test_rejection_classification() {
+        assert_eq!(
+            Rejection::from(&error(AptosErrorCode::SequenceNumberTooOld, None)),
+            Rejection::SequenceNumberTooOld
+        );
+        assert_eq!(
+            Rejection::from(&error(
+                AptosErrorCode::VmError,
+                Some(StatusCode::SEQUENCE_NUMBER_TOO_OLD)
+            )),
+            Rejection::SequenceNumberTooOld
+        );
+        assert_eq!(
+            Rejection::from(&error(
+                AptosErrorCode::VmError,
+                Some(StatusCode::TRANSACTION_EXPIRED)
+            )),
+            Rejection::Expired
+        );
+        assert_eq!(
+            Rejection::from(&error(AptosErrorCode::MempoolIsFull, None)),
+            Rejection::MempoolFull
+        );
+        assert_eq!(
+            Rejection::from(&error(
+                AptosErrorCode::VmError,
+                Some(StatusCode::INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE)
+            )),
+            Rejection::Other
+        );
+    }
+
+    #[test]
+    fn test_window_fills_holes_first() {
+        let account = LocalAccount::generate(&mut rand::rngs::OsRng);
+        let mut window = window(4, 3);
+
+        // The window is limited to 3 in flight transactions
+        let batch = window.next_batch();
+        assert_eq!(batch, vec![(0, 10), (1, 11), (2, 12)]);
+        window
+            .in_flight
+            .insert(10, (Some(0), sign(&account, 10, 100)));
+        window.on_rejected(
+            Some(1),
+            11,
+            &error(AptosErrorCode::VmError, Some(StatusCode::INVALID_SIGNATURE)),
+        );
+        window
+            .in_flight
+            .insert(12, (Some(2), sign(&account, 12, 100)));
+        assert!(matches!(
+            window.outcomes[1],
+            Some(SubmissionOutcome::Rejected(_))
+        ));
+
+        // The sequence number of the rejected transaction is reused by the next payload
+        assert_eq!(window.next_batch(), vec![(Some(3), 11)]);
+        assert_eq!(window.next_sequence_number, 13);
+    }
+
+    #[test]
+    fn test_window_fills_holes_without_payloads() {
+        let account = LocalAccount::generate(&mut rand::rngs::OsRng);
+        let invalid = error(AptosErrorCode::VmError, Some(StatusCode::INVALID_SIGNATURE));
+
+        // A hole above all in flight transactions doesn't block anything
+        let mut last_rejected = window(2, 10);
+        assert_eq!(last_rejected.next_batch(), vec![
+            (Some(0), 10),
+            (Some(1), 11)
+        ]);
+        last_rejected
+            .in_flight
+            .insert(10, (Some(0), sign(&account, 10, 100)));
+        last_rejected.on_rejected(Some(1), 11, &invalid);
+        assert!(last_rejected.next_batch().is_empty());
+
+        // A hole below an in flight transaction gets a filler once no payload is left
+        let mut window = window(2, 10);
+        assert_eq!(window.next_batch(), vec![(Some(0), 10), (Some(1), 11)]);
+        window.on_rejected(Some(0), 10, &invalid);
+        window
+            .in_flight
+            .insert(11, (Some(1), sign(&account, 11, 100)));
+        assert_eq!(window.next_batch(), vec![(None, 10)]);
+
+        // The filler isn't retried once it is rejected as well
+        window.on_rejected(None, 10, &invalid);
+        assert!(window.next_batch().is_empty());
+        assert_eq!(window.holes, BTreeSet::from([10]));
+        assert!(matches!(
+            window.outcomes[0],
+            Some(SubmissionOutcome::Rejected(_))
+        ));
+    }
+
+    #[test]
+    fn test_window_retries_mempool_full() {
+        let mut window = window(2, 10);
+        assert_eq!(window.next_batch(), vec![(Some(0), 10), (Some(1), 11)]);
+        for _ in 0..window.max_attempts {
+            assert_eq!(
+                window.on_rejected(Some(0), 10, &error(AptosErrorCode::MempoolIsFull, None)),
+                Rejection::MempoolFull
+            );
+            if window.outcomes[0].is_none() {
+                assert_eq!(window.next_batch(), vec![(Some(0), 10)]);
+            }
+        }
+        assert!(matches!(
+            window.outcomes[0],
+            Some(SubmissionOutcome::Dropped)
+        ));
+    }
+
+    #[test]
+    fn test_window_resyncs_on_expiration() {
+        let account = LocalAccount::generate(&mut rand::rngs::OsRng);
+        let mut window = window(2, 10);
+        assert_eq!(window.next_batch(), vec![(Some(0), 10), (Some(1), 11)]);
+        window
+            .in_flight
+            .insert(10, (Some(0), sign(&account, 10, 100)));
+        window
+            .in_flight
+            .insert(11, (Some(1), sign(&account, 11, 200)));
+
+        // Nothing is resubmitted while some transactions could still be committed
+        window.sync(10, 150);
+        assert_eq!(window.in_flight.len(), 2);
+        assert!(window.pending.is_empty());
+
+        // Once everything expired, the payloads are resubmitted in order
+        window.sync(10, 200);
+        assert!(window.in_flight.is_empty());
+        assert_eq!(window.pending, vec![0, 1]);
+        assert_eq!(window.next_batch(), vec![(Some(0), 10), (Some(1), 11)]);
+    }
+
+    #[test]
+    fn test_window_resyncs_on_stale_sequence_number() {
+        let account = LocalAccount::generate(&mut rand::rngs::OsRng);
+        let mut window = window(2, 10);
+        assert_eq!(window.next_batch(), vec![(Some(0), 10), (Some(1), 11)]);
+
+        // Another sender used sequence numbers 10 to 14
+        window.on_rejected(
+            Some(0),
+            10,
+            &error(AptosErrorCode::SequenceNumberTooOld, None),
+        );
+        window.on_rejected(
+            Some(1),
+            11,
+            &error(AptosErrorCode::SequenceNumberTooOld, None),
+        );
+        window.sync(15, 0);
+        assert!(window.holes.is_empty());
+        assert_eq!(window.next_batch(), vec![(Some(1), 15), (Some(0), 16)]);
+
+        // A transaction of another sender committed at a sequence number of the window
+        window
+            .in_flight
+            .insert(15, (Some(1), sign(&account, 15, 100)));
+        window
+            .in_flight
+            .insert(16, (Some(0), sign(&account, 16, 100)));
+        assert_eq!(window.first_unconfirmed(16), Some(15));
+        window.confirm(16, vec![]);
+        assert_eq!(window.pending, vec![1]);
+        assert_eq!(window.in_flight.keys().copied().collect::<Vec<_>>(), vec![
+            16
+        ]);
+    }
+}
@@ -5,40 +5,68 @@
 use crate::{
     crypto::{
         ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
-        signing_message,
+        hash::CryptoHash,
+        multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
+        secp256k1_ecdsa, secp256r1_ecdsa, signing_message,
         traits::Uniform,
+        HashValue,
     },
-    transaction_builder::TransactionBuilder,
+    transaction_builder::{aptos_stdlib, TransactionBuilder},
     types::{
         account_address::AccountAddress,
-        transaction::{authenticator::AuthenticationKey, RawTransaction, SignedTransaction},
+        transaction::{
+            authenticator::{AccountAuthenticator, AuthenticationKey, Scheme},
+            RawTransaction, RawTransactionWithData, SignedTransaction, TransactionPayload,
+        },
     },
 };
-use anyhow::Result;
+use anyhow::{bail, ensure, Result};
 use aptos_crypto::{ed25519::Ed25519Signature, PrivateKey, SigningKey};
 use aptos_ledger::AptosLedgerError;
 pub use aptos_types::*;
 use aptos_types::{
+    account_config::{RotationProofChallenge, CORE_CODE_ADDRESS},
     event::EventKey,
     keyless::{
         Claims, Configuration, EphemeralCertificate, IdCommitment, KeylessPublicKey,
         KeylessSignature, OpenIdSig, Pepper, TransactionAndProof, ZeroKnowledgeSig,
     },
-    transaction::authenticator::{AnyPublicKey, EphemeralPublicKey, EphemeralSignature},
+    transaction::{
+        authenticator::{
+            AnyPublicKey, AnySignature, EphemeralPublicKey, EphemeralSignature, MultiKey,
+            MultiKeyAuthenticator, SingleKeyAuthenticator,
+        },
+        webauthn::{AssertionSignature, PartialAuthenticatorAssertionResponse},
+    },
 };
 use bip39::{Language, Mnemonic, Seed};
 use ed25519_dalek_bip32::{DerivationPath, ExtendedSecretKey};
+use p256::ecdsa::signature::Signer;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
 use std::{
     str::FromStr,
     sync::atomic::{AtomicU64, Ordering},
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+/// Relying party of the WebAuthn assertions produced for local Secp256r1 keys
+const LOCAL_WEBAUTHN_RP_ID: &str = "localhost";
+/// Origin recorded in the client data of the WebAuthn assertions produced for local Secp256r1 keys
+const LOCAL_WEBAUTHN_ORIGIN: &str = "http://localhost";
+
+/// The signer backing a `LocalAccount`
 #[derive(Debug)]
-enum LocalAccountAuthenticator {
+pub enum LocalAccountAuthenticator {
+    /// A legacy Ed25519 account
     PrivateKey(AccountKey),
+    /// A `SingleKey` account, i.e., an Ed25519, Secp256k1 or Secp256r1 key
+    SingleKey(SingleKeyAccount),
+    /// A k-of-n `MultiKey` account
+    MultiKey(MultiKeyAccount),
+    /// A legacy k-of-n MultiEd25519 account
+    MultiEd25519(MultiEd25519Account),
     Keyless(KeylessAccount),
-    // TODO: Add support for keyless authentication
 }
 
 impl LocalAccountAuthenticator {
@@ -48,16 +76,49 @@ impl LocalAccountAuthenticator {
                 .sign(key.private_key(), key.public_key().clone())
                 .expect("Signing a txn can't fail")
                 .into_inner(),
+            LocalAccountAuthenticator::MultiEd25519(account) => {
+                let signature = account.sign(&txn).expect("Signing a txn can't fail");
+                SignedTransaction::new_multisig(txn, account.public_key().clone(), signature)
+            },
+            _ => {
+                let authenticator = self.sign_message(&txn).expect("Signing a txn can't fail");
+                SignedTransaction::new_single_sender(txn, authenticator)
+            },
+        }
+    }
+
+    /// Signs `message` (e.g., a `RawTransaction` or a `RawTransactionWithData`) and wraps the
+    /// signature into the `AccountAuthenticator` matching the account's scheme.
+    pub fn sign_message<T: CryptoHash + Serialize>(
+        &self,
+        message: &T,
+    ) -> Result<AccountAuthenticator> {
+        let authenticator = match self {
+            LocalAccountAuthenticator::PrivateKey(key) => AccountAuthenticator::ed25519(
+                key.public_key().clone(),
+                key.private_key().sign(message)?,
+            ),
+            LocalAccountAuthenticator::SingleKey(account) => {
+                AccountAuthenticator::single_key(account.sign(message)?)
+            },
+            LocalAccountAuthenticator::MultiKey(account) => {
+                AccountAuthenticator::multi_key(account.sign(message)?)
+            },
+            LocalAccountAuthenticator::MultiEd25519(account) => {
+                AccountAuthenticator::multi_ed25519(
+                    account.public_key().clone(),
+                    account.sign(message)?,
+                )
+            },
             LocalAccountAuthenticator::Keyless(keyless_account) => {
                 let proof = keyless_account.zk_sig.proof;
                 let txn_and_zkp = TransactionAndProof {
-                    message: txn.clone(),
+                    message,
                     proof: Some(proof),
                 };
 
                 let esk = &keyless_account.ephemeral_key_pair.private_key;
-                let ephemeral_signature =
-                    EphemeralSignature::ed25519(esk.sign(&txn_and_zkp).unwrap());
+                let ephemeral_signature = EphemeralSignature::ed25519(esk.sign(&txn_and_zkp)?);
 
                 let sig = KeylessSignature {
                     cert: EphemeralCertificate::ZeroKnowledgeSig(keyless_account.zk_sig.clone()),
@@ -67,11 +128,64 @@ impl LocalAccountAuthenticator {
                     ephemeral_signature,
                 };
 
-                SignedTransaction::new_keyless(txn, keyless_account.public_key.clone(), sig)
+                AccountAuthenticator::single_key(SingleKeyAuthenticator::new(
+                    AnyPublicKey::keyless(keyless_account.public_key.clone()),
+                    AnySignature::keyless(sig),
+                ))
+            },
+        };
+        Ok(authenticator)
+    }
+
+    pub fn authentication_key(&self) -> AuthenticationKey {
+        match self {
+            LocalAccountAuthenticator::PrivateKey(key) => key.authentication_key(),
+            LocalAccountAuthenticator::SingleKey(account) => account.authentication_key(),
+            LocalAccountAuthenticator::MultiKey(account) => account.authentication_key(),
+            LocalAccountAuthenticator::MultiEd25519(account) => account.authentication_key(),
+            LocalAccountAuthenticator::Keyless(keyless_account) => {
+                keyless_account.authentication_key()
+            },
+        }
+    }
+
+    /// Returns the scheme and public key used by `0x1::account::rotate_authentication_key`,
+    /// along with a signature over the (unprefixed) `message`. Only Ed25519 and MultiEd25519
+    /// accounts can take part in a rotation proof.
+    fn sign_rotation_proof(&self, message: &[u8]) -> Result<(u8, Vec<u8>, Vec<u8>)> {
+        match self {
+            LocalAccountAuthenticator::PrivateKey(key) => Ok((
+                Scheme::Ed25519 as u8,
+                key.public_key().to_bytes().to_vec(),
+                sign_ed25519_arbitrary_message(key.private_key(), message)?
+                    .to_bytes()
+                    .to_vec(),
+            )),
+            LocalAccountAuthenticator::MultiEd25519(account) => {
+                let signatures = account
+                    .private_keys
+                    .iter()
+                    .take(*account.public_key.threshold() as usize)
+                    .map(|(index, private_key)| {
+                        Ok((
+                            sign_ed25519_arbitrary_message(private_key, message)?,
+                            *index,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok((
+                    Scheme::MultiEd25519 as u8,
+                    account.public_key().to_bytes(),
+                    MultiEd25519Signature::new(signatures)?.to_bytes(),
+                ))
+            },
+            _ => {
+                bail!("Key rotation proofs can only be signed by Ed25519 or MultiEd25519 accounts")
             },
         }
     }
 }
+
 impl<T: Into<AccountKey>> From<T> for LocalAccountAuthenticator {
     fn from(key: T) -> Self {
         Self::PrivateKey(key.into())
@@ -115,14 +229,83 @@ impl LocalAccount {
         address: AccountAddress,
         keyless_account: KeylessAccount,
         sequence_number: u64,
+    ) -> Self {
+        Self::new_with_authenticator(
+            address,
+            LocalAccountAuthenticator::Keyless(keyless_account),
+            sequence_number,
+        )
+    }
+
+    pub fn new_single_key(
+        address: AccountAddress,
+        account: SingleKeyAccount,
+        sequence_number: u64,
+    ) -> Self {
+        Self::new_with_authenticator(
+            address,
+            LocalAccountAuthenticator::SingleKey(account),
+            sequence_number,
+        )
+    }
+
+    pub fn new_multi_key(
+        address: AccountAddress,
+        account: MultiKeyAccount,
+        sequence_number: u64,
+    ) -> Self {
+        Self::new_with_authenticator(
+            address,
+            LocalAccountAuthenticator::MultiKey(account),
+            sequence_number,
+        )
+    }
+
+    pub fn new_multi_ed25519(
+        address: AccountAddress,
+        account: MultiEd25519Account,
+        sequence_number: u64,
+    ) -> Self {
+        Self::new_with_authenticator(
+            address,
+            LocalAccountAuthenticator::MultiEd25519(account),
+            sequence_number,
+        )
+    }
+
+    pub fn new_with_authenticator(
+        address: AccountAddress,
+        auth: LocalAccountAuthenticator,
+        sequence_number: u64,
     ) -> Self {
         Self {
             address,
-            auth: LocalAccountAuthenticator::Keyless(keyless_account),
+            auth,
             sequence_number: AtomicU64::new(sequence_number),
         }
     }
 
+    /// Create a local account for a freshly generated `SingleKey` account, whose address is
+    /// derived from its authentication key.
+    pub fn from_single_key(account: SingleKeyAccount, sequence_number: u64) -> Self {
+        let address = account.authentication_key().account_address();
+        Self::new_single_key(address, account, sequence_number)
+    }
+
+    /// Create a local account for a freshly generated `MultiKey` account, whose address is
+    /// derived from its authentication key.
+    pub fn from_multi_key(account: MultiKeyAccount, sequence_number: u64) -> Self {
+        let address = account.authentication_key().account_address();
+        Self::new_multi_key(address, account, sequence_number)
+    }
+
+    /// Create a local account for a freshly generated `MultiEd25519` account, whose address is
+    /// derived from its authentication key.
+    pub fn from_multi_ed25519(account: MultiEd25519Account, sequence_number: u64) -> Self {
+        let address = account.authentication_key().account_address();
+        Self::new_multi_ed25519(address, account, sequence_number)
+    }
+
     /// Recover an account from derive path (e.g. m/44'/637'/0'/0'/0') and mnemonic phrase,
     pub fn from_derive_path(
         derive_path: &str,
@@ -182,26 +365,33 @@ impl LocalAccount {
         secondary_signers: Vec<&Self>,
         builder: TransactionBuilder,
     ) -> SignedTransaction {
-        let secondary_signer_addresses = secondary_signers
+        let secondary_signer_addresses: Vec<_> = secondary_signers
             .iter()
             .map(|signer| signer.address())
             .collect();
-        let secondary_signer_privkeys = secondary_signers
-            .iter()
-            .map(|signer| signer.private_key())
-            .collect();
         let raw_txn = builder
             .sender(self.address())
             .sequence_number(self.increment_sequence_number())
             .build();
-        raw_txn
-            .sign_multi_agent(
-                self.private_key(),
-                secondary_signer_addresses,
-                secondary_signer_privkeys,
-            )
-            .expect("Signing multi agent txn failed")
-            .into_inner()
+        let message = RawTransactionWithData::new_multi_agent(
+            raw_txn.clone(),
+            secondary_signer_addresses.clone(),
+        );
+        let sender_authenticator = self
+            .auth
+            .sign_message(&message)
+            .expect("Signing multi agent txn failed");
+        let secondary_authenticators = secondary_signers
+            .iter()
+            .map(|signer| signer.auth.sign_message(&message))
+            .collect::<Result<_>>()
+            .expect("Signing multi agent txn failed");
+        SignedTransaction::new_multi_agent(
+            raw_txn,
+            sender_authenticator,
+            secondary_signer_addresses,
+            secondary_authenticators,
+        )
     }
 
     pub fn sign_fee_payer_with_transaction_builder(
@@ -210,28 +400,40 @@ impl LocalAccount {
         fee_payer_signer: &Self,
         builder: TransactionBuilder,
     ) -> SignedTransaction {
-        let secondary_signer_addresses = secondary_signers
+        let secondary_signer_addresses: Vec<_> = secondary_signers
             .iter()
             .map(|signer| signer.address())
             .collect();
-        let secondary_signer_privkeys = secondary_signers
-            .iter()
-            .map(|signer| signer.private_key())
-            .collect();
         let raw_txn = builder
             .sender(self.address())
             .sequence_number(self.increment_sequence_number())
             .build();
-        raw_txn
-            .sign_fee_payer(
-                self.private_key(),
-                secondary_signer_addresses,
-                secondary_signer_privkeys,
-                fee_payer_signer.address(),
-                fee_payer_signer.private_key(),
-            )
-            .expect("Signing multi agent txn failed")
-            .into_inner()
+        let message = RawTransactionWithData::new_fee_payer(
+            raw_txn.clone(),
+            secondary_signer_addresses.clone(),
+            fee_payer_signer.address(),
+        );
+        let sender_authenticator = self
+            .auth
+            .sign_message(&message)
+            .expect("Signing fee payer txn failed");
+        let secondary_authenticators = secondary_signers
+            .iter()
+            .map(|signer| signer.auth.sign_message(&message))
+            .collect::<Result<_>>()
+            .expect("Signing fee payer txn failed");
+        let fee_payer_authenticator = fee_payer_signer
+            .auth
+            .sign_message(&message)
+            .expect("Signing fee payer txn failed");
+        SignedTransaction::new_fee_payer(
+            raw_txn,
+            sender_authenticator,
+            secondary_signer_addresses,
+            secondary_authenticators,
+            fee_payer_signer.address(),
+            fee_payer_authenticator,
+        )
     }
 
     pub fn address(&self) -> AccountAddress {
@@ -242,6 +444,7 @@ impl LocalAccount {
         match &self.auth {
             LocalAccountAuthenticator::PrivateKey(key) => key.private_key(),
             LocalAccountAuthenticator::Keyless(_) => todo!(),
+            _ => panic!("Account is not backed by a single Ed25519 private key"),
         }
     }
 
@@ -249,16 +452,16 @@ impl LocalAccount {
         match &self.auth {
             LocalAccountAuthenticator::PrivateKey(key) => key.public_key(),
             LocalAccountAuthenticator::Keyless(_) => todo!(),
+            _ => panic!("Account is not backed by a single Ed25519 private key"),
         }
     }
 
+    pub fn authenticator(&self) -> &LocalAccountAuthenticator {
+        &self.auth
+    }
+
     pub fn authentication_key(&self) -> AuthenticationKey {
-        match &self.auth {
-            LocalAccountAuthenticator::PrivateKey(key) => key.authentication_key(),
-            LocalAccountAuthenticator::Keyless(keyless_account) => {
-                keyless_account.authentication_key()
-            },
-        }
+        self.auth.authentication_key()
     }
 
     pub fn sequence_number(&self) -> u64 {
@@ -282,9 +485,59 @@ impl LocalAccount {
         match &mut self.auth {
             LocalAccountAuthenticator::PrivateKey(key) => std::mem::replace(key, new_key.into()),
             LocalAccountAuthenticator::Keyless(_) => todo!(),
+            _ => panic!("Account is not backed by a single Ed25519 private key"),
         }
     }
 
+    /// Replaces the signer of the account, e.g., once a key rotation has been committed on
+    /// chain, and returns the previous one. The address of the account is left unchanged.
+    pub fn rotate_authenticator(
+        &mut self,
+        new_auth: LocalAccountAuthenticator,
+    ) -> LocalAccountAuthenticator {
+        std::mem::replace(&mut self.auth, new_auth)
+    }
+
+    /// Builds the `0x1::account::rotate_authentication_key` payload that rotates the
+    /// authentication key of this account to the one of `new_auth`. The rotation proof is signed
+    /// by both the current and the new signer, so both must be Ed25519 or MultiEd25519 backed.
+    ///
+    /// The proof commits to the current sequence number, so the payload must be submitted as the
+    /// next transaction of the account. Once committed, call `rotate_authenticator`.
+    pub fn rotate_authentication_key_payload(
+        &self,
+        new_auth: &LocalAccountAuthenticator,
+    ) -> Result<TransactionPayload> {
+        let new_public_key = match new_auth {
+            LocalAccountAuthenticator::PrivateKey(key) => key.public_key().to_bytes().to_vec(),
+            LocalAccountAuthenticator::MultiEd25519(account) => account.public_key().to_bytes(),
+            _ => bail!("Accounts can only be rotated to Ed25519 or MultiEd25519 keys"),
+        };
+        let rotation_proof = RotationProofChallenge {
+            account_address: CORE_CODE_ADDRESS,
+            module_name: "account".to_string(),
+            struct_name: "RotationProofChallenge".to_string(),
+            sequence_number: self.sequence_number(),
+            originator: self.address(),
+            current_auth_key: self.authentication_key().account_address(),
+            new_public_key,
+        };
+        let rotation_msg = bcs::to_bytes(&rotation_proof)?;
+
+        let (from_scheme, from_public_key, cap_rotate_key) =
+            self.auth.sign_rotation_proof(&rotation_msg)?;
+        let (to_scheme, to_public_key, cap_update_table) =
+            new_auth.sign_rotation_proof(&rotation_msg)?;
+        Ok(aptos_stdlib::account_rotate_authentication_key(
+            from_scheme,
+            from_public_key,
+            to_scheme,
+            to_public_key,
+            cap_rotate_key,
+            cap_update_table,
+        ))
+    }
+
     pub fn received_event_key(&self) -> EventKey {
         EventKey::new(2, self.address)
     }
@@ -468,6 +721,314 @@ impl From<Ed25519PrivateKey> for AccountKey {
     }
 }
 
+/// A private key of any of the schemes supported by `SingleKey` and `MultiKey` accounts
+#[derive(Debug)]
+pub enum AnyPrivateKey {
+    Ed25519(Ed25519PrivateKey),
+    Secp256k1Ecdsa(secp256k1_ecdsa::PrivateKey),
+    /// Secp256r1 keys sign through (locally produced) WebAuthn assertions, as this is the only
+    /// form in which Secp256r1 signatures are accepted on chain.
+    Secp256r1Ecdsa(secp256r1_ecdsa::PrivateKey),
+}
+
+impl AnyPrivateKey {
+    pub fn generate_ed25519<R>(rng: &mut R) -> Self
+    where
+        R: ::rand_core::RngCore + ::rand_core::CryptoRng,
+    {
+        Self::Ed25519(Ed25519PrivateKey::generate(rng))
+    }
+
+    pub fn generate_secp256k1_ecdsa<R>(rng: &mut R) -> Self
+    where
+        R: ::rand_core::RngCore + ::rand_core::CryptoRng,
+    {
+        Self::Secp256k1Ecdsa(secp256k1_ecdsa::PrivateKey::generate(rng))
+    }
+
+    pub fn generate_secp256r1_ecdsa<R>(rng: &mut R) -> Self
+    where
+        R: ::rand_core::RngCore + ::rand_core::CryptoRng,
+    {
+        Self::Secp256r1Ecdsa(secp256r1_ecdsa::PrivateKey::generate(rng))
+    }
+
+    pub fn public_key(&self) -> AnyPublicKey {
+        match self {
+            Self::Ed25519(private_key) => AnyPublicKey::ed25519(private_key.public_key()),
+            Self::Secp256k1Ecdsa(private_key) => {
+                AnyPublicKey::secp256k1_ecdsa(private_key.public_key())
+            },
+            Self::Secp256r1Ecdsa(private_key) => {
+                AnyPublicKey::secp256r1_ecdsa(private_key.public_key())
+            },
+        }
+    }
+
+    pub fn sign<T: CryptoHash + Serialize>(&self, message: &T) -> Result<AnySignature> {
+        let signature = match self {
+            Self::Ed25519(private_key) => AnySignature::ed25519(private_key.sign(message)?),
+            Self::Secp256k1Ecdsa(private_key) => {
+                AnySignature::secp256k1_ecdsa(private_key.sign(message)?)
+            },
+            Self::Secp256r1Ecdsa(private_key) => {
+                AnySignature::webauthn(sign_secp256r1_webauthn(private_key, message)?)
+            },
+        };
+        Ok(signature)
+    }
+}
+
+impl From<Ed25519PrivateKey> for AnyPrivateKey {
+    fn from(private_key: Ed25519PrivateKey) -> Self {
+        Self::Ed25519(private_key)
+    }
+}
+
+impl From<secp256k1_ecdsa::PrivateKey> for AnyPrivateKey {
+    fn from(private_key: secp256k1_ecdsa::PrivateKey) -> Self {
+        Self::Secp256k1Ecdsa(private_key)
+    }
+}
+
+impl From<secp256r1_ecdsa::PrivateKey> for AnyPrivateKey {
+    fn from(private_key: secp256r1_ecdsa::PrivateKey) -> Self {
+        Self::Secp256r1Ecdsa(private_key)
+    }
+}
+
+/// The key of a `SingleKey` account
+#[derive(Debug)]
+pub struct SingleKeyAccount {
+    private_key: AnyPrivateKey,
+    public_key: AnyPublicKey,
+    authentication_key: AuthenticationKey,
+}
+
+impl SingleKeyAccount {
+    pub fn new<T: Into<AnyPrivateKey>>(private_key: T) -> Self {
+        let private_key = private_key.into();
+        let public_key = private_key.public_key();
+        let authentication_key = AuthenticationKey::any_key(public_key.clone());
+
+        Self {
+            private_key,
+            public_key,
+            authentication_key,
+        }
+    }
+
+    pub fn private_key(&self) -> &AnyPrivateKey {
+        &self.private_key
+    }
+
+    pub fn public_key(&self) -> &AnyPublicKey {
+        &self.public_key
+    }
+
+    pub fn authentication_key(&self) -> AuthenticationKey {
+        self.authentication_key
+    }
+
+    pub fn sign<T: CryptoHash + Serialize>(&self, message: &T) -> Result<SingleKeyAuthenticator> {
+        Ok(SingleKeyAuthenticator::new(
+            self.public_key.clone(),
+            self.private_key.sign(message)?,
+        ))
+    }
+}
+
+/// The keys of a k-of-n `MultiKey` account. Only the private keys held locally are known, and
+/// at least k of them are required to sign.
+#[derive(Debug)]
+pub struct MultiKeyAccount {
+    public_keys: MultiKey,
+    /// The local private keys along with the index of their public key, sorted by index
+    private_keys: Vec<(u8, AnyPrivateKey)>,
+    authentication_key: AuthenticationKey,
+}
+
+impl MultiKeyAccount {
+    pub fn new(public_keys: MultiKey, mut private_keys: Vec<(u8, AnyPrivateKey)>) -> Result<Self> {
+        private_keys.sort_by_key(|(index, _)| *index);
+        ensure!(
+            private_keys.len() >= public_keys.signatures_required() as usize,
+            "Not enough private keys to sign, {} < {}",
+            private_keys.len(),
+            public_keys.signatures_required(),
+        );
+        ensure!(
+            private_keys.windows(2).all(|keys| keys[0].0 != keys[1].0),
+            "Duplicate private key index"
+        );
+        for (index, private_key) in &private_keys {
+            ensure!(
+                public_keys.public_keys().get(*index as usize) == Some(&private_key.public_key()),
+                "Private key does not match the public key at index {}",
+                index,
+            );
+        }
+        let authentication_key = AuthenticationKey::multi_key(public_keys.clone());
+
+        Ok(Self {
+            public_keys,
+            private_keys,
+            authentication_key,
+        })
+    }
+
+    /// Create a k-of-n account for which all private keys are held locally
+    pub fn from_private_keys(
+        private_keys: Vec<AnyPrivateKey>,
+        signatures_required: u8,
+    ) -> Result<Self> {
+        let public_keys = MultiKey::new(
+            private_keys.iter().map(AnyPrivateKey::public_key).collect(),
+            signatures_required,
+        )?;
+        let private_keys = (0u8..).zip(private_keys).collect();
+        Self::new(public_keys, private_keys)
+    }
+
+    pub fn public_keys(&self) -> &MultiKey {
+        &self.public_keys
+    }
+
+    pub fn authentication_key(&self) -> AuthenticationKey {
+        self.authentication_key
+    }
+
+    /// Signs `message` with the first k local private keys
+    pub fn sign<T: CryptoHash + Serialize>(&self, message: &T) -> Result<MultiKeyAuthenticator> {
+        let signatures = self
+            .private_keys
+            .iter()
+            .take(self.public_keys.signatures_required() as usize)
+            .map(|(index, private_key)| Ok((*index, private_key.sign(message)?)))
+            .collect::<Result<Vec<_>>>()?;
+        MultiKeyAuthenticator::new(self.public_keys.clone(), signatures)
+    }
+}
+
+/// The keys of a legacy k-of-n MultiEd25519 account. As with `MultiKeyAccount`, only the
+/// private keys held locally are known.
+#[derive(Debug)]
+pub struct MultiEd25519Account {
+    public_key: MultiEd25519PublicKey,
+    /// The local private keys along with the index of their public key, sorted by index
+    private_keys: Vec<(u8, Ed25519PrivateKey)>,
+    authentication_key: AuthenticationKey,
+}
+
+impl MultiEd25519Account {
+    pub fn new(
+        public_key: MultiEd25519PublicKey,
+        mut private_keys: Vec<(u8, Ed25519PrivateKey)>,
+    ) -> Result<Self> {
+        private_keys.sort_by_key(|(index, _)| *index);
+        ensure!(
+            private_keys.len() >= *public_key.threshold() as usize,
+            "Not enough private keys to sign, {} < {}",
+            private_keys.len(),
+            public_key.threshold(),
+        );
+        ensure!(
+            private_keys.windows(2).all(|keys| keys[0].0 != keys[1].0),
+            "Duplicate private key index"
+        );
+        for (index, private_key) in &private_keys {
+            ensure!(
+                public_key.public_keys().get(*index as usize) == Some(&private_key.public_key()),
+                "Private key does not match the public key at index {}",
+                index,
+            );
+        }
+        let authentication_key = AuthenticationKey::multi_ed25519(&public_key);
+
+        Ok(Self {
+            public_key,
+            private_keys,
+            authentication_key,
+        })
+    }
+
+    /// Create a k-of-n account for which all private keys are held locally
+    pub fn from_private_keys(private_keys: Vec<Ed25519PrivateKey>, threshold: u8) -> Result<Self> {
+        let public_key = MultiEd25519PublicKey::new(
+            private_keys.iter().map(Ed25519PublicKey::from).collect(),
+            threshold,
+        )?;
+        let private_keys = (0u8..).zip(private_keys).collect();
+        Self::new(public_key, private_keys)
+    }
+
+    pub fn public_key(&self) -> &MultiEd25519PublicKey {
+        &self.public_key
+    }
+
+    pub fn authentication_key(&self) -> AuthenticationKey {
+        self.authentication_key
+    }
+
+    /// Signs `message` with the first k local private keys
+    pub fn sign<T: CryptoHash + Serialize>(&self, message: &T) -> Result<MultiEd25519Signature> {
+        let signatures = self
+            .private_keys
+            .iter()
+            .take(*self.public_key.threshold() as usize)
+            .map(|(index, private_key)| Ok((private_key.sign(message)?, *index)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(MultiEd25519Signature::new(signatures)?)
+    }
+}
+
+/// Signs a message that is not prefixed by a `CryptoHasher` seed, as required for the proofs
+/// checked by `0x1::account`.
+fn sign_ed25519_arbitrary_message(
+    private_key: &Ed25519PrivateKey,
+    message: &[u8],
+) -> Result<Ed25519Signature> {
+    let secret_key = ed25519_dalek::SecretKey::from_bytes(&private_key.to_bytes())?;
+    let public_key = ed25519_dalek::PublicKey::from(&secret_key);
+    let signature = ed25519_dalek::ExpandedSecretKey::from(&secret_key).sign(message, &public_key);
+    Ok(Ed25519Signature::try_from(&signature.to_bytes()[..])?)
+}
+
+/// Produces the WebAuthn assertion an authenticator holding `private_key` would return for
+/// `message`. The challenge is the SHA3-256 digest of the signing message, see
+/// `PartialAuthenticatorAssertionResponse::verify`.
+fn sign_secp256r1_webauthn<T: CryptoHash + Serialize>(
+    private_key: &secp256r1_ecdsa::PrivateKey,
+    message: &T,
+) -> Result<PartialAuthenticatorAssertionResponse> {
+    let challenge = HashValue::sha3_256_of(&signing_message(message)?);
+    let client_data_json = serde_json::to_vec(&serde_json::json!({
+        "type": "webauthn.get",
+        "challenge": base64::encode_config(challenge.to_vec(), base64::URL_SAFE_NO_PAD),
+        "origin": LOCAL_WEBAUTHN_ORIGIN,
+        "crossOrigin": false,
+    }))?;
+
+    // rpIdHash || flags (user present and verified) || signCount
+    let mut authenticator_data = Sha256::digest(LOCAL_WEBAUTHN_RP_ID.as_bytes()).to_vec();
+    authenticator_data.push(0b0000_0101);
+    authenticator_data.extend(0u32.to_be_bytes());
+
+    let mut verification_data = authenticator_data.clone();
+    verification_data.extend(Sha256::digest(&client_data_json));
+    let signing_key = p256::ecdsa::SigningKey::from_slice(&private_key.to_bytes())?;
+    let signature: p256::ecdsa::Signature = signing_key.sign(&verification_data);
+    // Only signatures with a low S are accepted on chain
+    let signature = signature.normalize_s().unwrap_or(signature);
+    let signature = secp256r1_ecdsa::Signature::try_from(&signature.to_bytes()[..])?;
+
+    Ok(PartialAuthenticatorAssertionResponse::new(
+        AssertionSignature::Secp256r1Ecdsa { signature },
+        authenticator_data,
+        client_data_json,
+    ))
+}
+
 #[derive(Debug)]
 pub struct EphemeralKeyPair {
     private_key: Ed25519PrivateKey,
@@ -565,6 +1126,7 @@ impl KeylessAccount {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{transaction_builder::TransactionFactory, types::chain_id::ChainId};
 
     #[test]
     fn test_recover_account_from_derive_path() {
@@ -606,4 +1168,139 @@ mod tests {
         // Test invalid private key hex literal.
         assert!(LocalAccount::from_private_key("invalid_private_key", 0).is_err());
     }
+
+    #[test]
+    fn test_sign_single_key_transactions() {
+        let mut rng = rand::rngs::OsRng;
+        let factory = TransactionFactory::new(ChainId::test());
+        for private_key in [
+            AnyPrivateKey::generate_ed25519(&mut rng),
+            AnyPrivateKey::generate_secp256k1_ecdsa(&mut rng),
+            AnyPrivateKey::generate_secp256r1_ecdsa(&mut rng),
+        ] {
+            let key = SingleKeyAccount::new(private_key);
+            let expected_address =
+                AuthenticationKey::any_key(key.public_key().clone()).account_address();
+            let account = LocalAccount::from_single_key(key, 0);
+            assert_eq!(account.address(), expected_address);
+
+            let txn =
+                account.sign_with_transaction_builder(factory.transfer(AccountAddress::ONE, 1));
+            txn.verify_signature().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_sign_multi_key_transactions() {
+        let mut rng = rand::rngs::OsRng;
+        let private_keys = vec![
+            AnyPrivateKey::generate_ed25519(&mut rng),
+            AnyPrivateKey::generate_secp256k1_ecdsa(&mut rng),
+            AnyPrivateKey::generate_secp256r1_ecdsa(&mut rng),
+        ];
+        let public_keys = MultiKey::new(
+            private_keys.iter().map(AnyPrivateKey::public_key).collect(),
+            2,
+        )
+        .unwrap();
+
+        // A single local key is not enough for a 2-of-3 account
+        let mut private_keys: Vec<_> = (0u8..).zip(private_keys).collect();
+        let last_key = private_keys.pop().unwrap();
+        let first_key = private_keys.remove(0);
+        assert!(MultiKeyAccount::new(public_keys.clone(), vec![first_key]).is_err());
+
+        // Private keys must match the public key at their index
+        let (_, middle_key) = private_keys.pop().unwrap();
+        assert!(
+            MultiKeyAccount::new(public_keys.clone(), vec![(0, middle_key), last_key,]).is_err()
+        );
+
+        let private_keys = vec![
+            AnyPrivateKey::generate_secp256r1_ecdsa(&mut rng),
+            AnyPrivateKey::generate_ed25519(&mut rng),
+            AnyPrivateKey::generate_secp256k1_ecdsa(&mut rng),
+        ];
+        let key = MultiKeyAccount::from_private_keys(private_keys, 2).unwrap();
+        assert_eq!(
+            key.authentication_key(),
+            AuthenticationKey::multi_key(key.public_keys().clone())
+        );
+        let account = LocalAccount::from_multi_key(key, 0);
+        let factory = TransactionFactory::new(ChainId::test());
+        let txn = account.sign_with_transaction_builder(factory.transfer(AccountAddress::ONE, 1));
+        txn.verify_signature().unwrap();
+    }
+
+    #[test]
+    fn test_sign_multi_agent_and_fee_payer_transactions() {
+        let mut rng = rand::rngs::OsRng;
+        let sender = LocalAccount::generate(&mut rng);
+        let secondary_signer = LocalAccount::from_single_key(
+            SingleKeyAccount::new(AnyPrivateKey::generate_secp256k1_ecdsa(&mut rng)),
+            0,
+        );
+        let fee_payer = LocalAccount::from_multi_ed25519(
+            MultiEd25519Account::from_private_keys(
+                vec![
+                    Ed25519PrivateKey::generate(&mut rng),
+                    Ed25519PrivateKey::generate(&mut rng),
+                ],
+                1,
+            )
+            .unwrap(),
+            0,
+        );
+        let factory = TransactionFactory::new(ChainId::test());
+
+        let txn = sender.sign_multi_agent_with_transaction_builder(
+            vec![&secondary_signer],
+            factory.transfer(AccountAddress::ONE, 1),
+        );
+        txn.verify_signature().unwrap();
+
+        let txn = sender.sign_fee_payer_with_transaction_builder(
+            vec![&secondary_signer],
+            &fee_payer,
+            factory.transfer(AccountAddress::ONE, 1),
+        );
+        txn.verify_signature().unwrap();
+        assert_eq!(
+            txn.authenticator().fee_payer_address(),
+            Some(fee_payer.address())
+        );
+    }
+
+    #[test]
+    fn test_rotate_authentication_key() {
+        let mut rng = rand::rngs::OsRng;
+        let mut account = LocalAccount::generate(&mut rng);
+        let address = account.address();
+        let new_auth = LocalAccountAuthenticator::MultiEd25519(
+            MultiEd25519Account::from_private_keys(
+                vec![
+                    Ed25519PrivateKey::generate(&mut rng),
+                    Ed25519PrivateKey::generate(&mut rng),
+                ],
+                2,
+            )
+            .unwrap(),
+        );
+        let new_auth_key = new_auth.authentication_key();
+        account
+            .rotate_authentication_key_payload(&new_auth)
+            .unwrap();
+
+        // Rotation proofs can't be signed by SingleKey accounts
+        let single_key = LocalAccountAuthenticator::SingleKey(SingleKeyAccount::new(
+            AnyPrivateKey::generate_secp256k1_ecdsa(&mut rng),
+        ));
+        assert!(account
+            .rotate_authentication_key_payload(&single_key)
+            .is_err());
+
+        account.rotate_authenticator(new_auth);
+        assert_eq!(account.address(), address);
+        assert_eq!(account.authentication_key(), new_auth_key);
+    }
 }
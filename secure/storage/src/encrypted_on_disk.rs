@@ -0,0 +1,357 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{from_base64, to_base64, CryptoKVStorage, Error, GetResponse, KVStorage};
+use aes_gcm::{
+    aead::{Aead, OsRng as AeadOsRng, Payload},
+    AeadCore, Aes256Gcm, Key, KeyInit, Nonce,
+};
+use aptos_temppath::TempPath;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::{rngs::OsRng, RngCore};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+/// The version of the file format, stored in (and authenticated with) every file
+const FORMAT_VERSION: u32 = 1;
+const KEY_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 12;
+const SALT_LENGTH: usize = 16;
+
+/// Upper bounds of the key derivation parameters. The parameters of a file are read before it is
+/// authenticated, so they are bounded to keep a tampered file from exhausting memory or time.
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+const MAX_ITERATIONS: u32 = 64;
+const MAX_PARALLELISM: u32 = 16;
+
+/// The argon2id parameters used to derive the encryption key from the passphrase. The parameters
+/// are stored in the file, so changing them only affects the keys derived by later rotations.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct KdfParams {
+    /// Memory cost, in KiB
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    /// The OWASP recommended parameters for argon2id
+    fn default() -> Self {
+        Self {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+impl KdfParams {
+    /// The parameters bounded by the maximal values. Lower bounds are checked by argon2.
+    fn clamped(self) -> Self {
+        Self {
+            memory_kib: self.memory_kib.min(MAX_MEMORY_KIB),
+            iterations: self.iterations.min(MAX_ITERATIONS),
+            parallelism: self.parallelism.min(MAX_PARALLELISM),
+        }
+    }
+}
+
+/// A version of the encryption key. The version is bumped by every rotation of the key.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct KeyVersion {
+    pub version: u32,
+    pub created_secs: u64,
+}
+
+/// Everything needed to derive the key of the file. The header is authenticated as the associated
+/// data of the ciphertext, so it cannot be modified without failing the decryption.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+struct Header {
+    format_version: u32,
+    kdf_params: KdfParams,
+    #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
+    salt: Vec<u8>,
+    /// All the versions of the encryption key, the last one is the current key
+    key_history: Vec<KeyVersion>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct EncryptedFile {
+    header: Header,
+    #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
+    nonce: Vec<u8>,
+    #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
+    ciphertext: Vec<u8>,
+}
+
+/// EncryptedOnDiskStorage is a key value store persisted to a single file on the local
+/// filesystem, like OnDiskStorage, except that the data is encrypted with AES-256-GCM under a key
+/// derived from a passphrase with argon2id. Any modification of the file, including its header,
+/// fails the decryption. Writes go to a temporary file which is atomically renamed over the
+/// storage file, so a crash never leaves a partially written file behind.
+///
+/// Like OnDiskStorage it is intended for single threads (or must be wrapped by a Arc<RwLock<>>),
+/// and it makes copies of the key material in memory when reading and writing the file.
+pub struct EncryptedOnDiskStorage {
+    file_path: PathBuf,
+    temp_path: TempPath,
+    time_service: TimeService,
+    /// The parameters used to derive the keys of new files and rotated keys
+    kdf_params: KdfParams,
+    header: Header,
+    cipher: Aes256Gcm,
+}
+
+impl EncryptedOnDiskStorage {
+    /// Opens the storage at the given path, or creates it if the file does not exist or is empty.
+    /// Fails if the passphrase does not decrypt an existing file.
+    pub fn new(file_path: PathBuf, passphrase: &[u8]) -> Result<Self, Error> {
+        Self::new_with_kdf_params(file_path, passphrase, KdfParams::default())
+    }
+
+    pub fn new_with_kdf_params(
+        file_path: PathBuf,
+        passphrase: &[u8],
+        kdf_params: KdfParams,
+    ) -> Result<Self, Error> {
+        Self::new_with_time_service(file_path, passphrase, kdf_params, TimeService::real())
+    }
+
+    fn new_with_time_service(
+        file_path: PathBuf,
+        passphrase: &[u8],
+        kdf_params: KdfParams,
+        time_service: TimeService,
+    ) -> Result<Self, Error> {
+        let kdf_params = kdf_params.clamped();
+        // The parent will be one when only a filename is supplied. Therefore use the current
+        // working directory provided by PathBuf::new().
+        let file_dir = file_path
+            .parent()
+            .map_or(PathBuf::new(), |p| p.to_path_buf());
+        let temp_path = TempPath::new_with_temp_dir(file_dir);
+
+        match read_file(&file_path)? {
+            Some(file) => {
+                let cipher = derive_cipher(passphrase, &file.header)?;
+                // Check the passphrase and the integrity of the file early
+                decrypt(&cipher, &file)?;
+                Ok(Self {
+                    file_path,
+                    temp_path,
+                    time_service,
+                    kdf_params,
+                    header: file.header,
+                    cipher,
+                })
+            },
+            None => {
+                let header = new_header(kdf_params, vec![KeyVersion {
+                    version: 1,
+                    created_secs: time_service.now_secs(),
+                }]);
+                let storage = Self {
+                    cipher: derive_cipher(passphrase, &header)?,
+                    file_path,
+                    temp_path,
+                    time_service,
+                    kdf_params,
+                    header,
+                };
+                storage.write(&HashMap::new())?;
+                Ok(storage)
+            },
+        }
+    }
+
+    /// The versions of the encryption key, the last one is the current key.
+    pub fn key_history(&self) -> &[KeyVersion] {
+        &self.header.key_history
+    }
+
+    /// Re-encrypts all data under a new key derived from the new passphrase and a fresh salt, and
+    /// returns the new key version. The previous passphrase no longer opens the storage.
+    pub fn rotate_encryption_key(&mut self, new_passphrase: &[u8]) -> Result<KeyVersion, Error> {
+        let data = self.read()?;
+
+        let current_version = self
+            .header
+            .key_history
+            .last()
+            .map_or(0, |key_version| key_version.version);
+        let key_version = KeyVersion {
+            version: current_version + 1,
+            created_secs: self.time_service.now_secs(),
+        };
+        let mut key_history = self.header.key_history.clone();
+        key_history.push(key_version);
+        let header = new_header(self.kdf_params, key_history);
+        let cipher = derive_cipher(new_passphrase, &header)?;
+
+        write_file(&self.temp_path, &self.file_path, &cipher, &header, &data)?;
+        self.header = header;
+        self.cipher = cipher;
+        Ok(key_version)
+    }
+
+    fn read(&self) -> Result<HashMap<String, Value>, Error> {
+        let file = read_file(&self.file_path)?
+            .ok_or_else(|| Error::InternalError(format!("{:?} is empty", self.file_path)))?;
+        if file.header != self.header {
+            return Err(Error::InternalError(format!(
+                "The encryption key of {:?} was changed by another process",
+                self.file_path
+            )));
+        }
+        decrypt(&self.cipher, &file)
+    }
+
+    fn write(&self, data: &HashMap<String, Value>) -> Result<(), Error> {
+        write_file(
+            &self.temp_path,
+            &self.file_path,
+            &self.cipher,
+            &self.header,
+            data,
+        )
+    }
+}
+
+fn new_header(kdf_params: KdfParams, key_history: Vec<KeyVersion>) -> Header {
+    let mut salt = vec![0; SALT_LENGTH];
+    OsRng.fill_bytes(&mut salt);
+    Header {
+        format_version: FORMAT_VERSION,
+        kdf_params,
+        salt,
+        key_history,
+    }
+}
+
+fn derive_cipher(passphrase: &[u8], header: &Header) -> Result<Aes256Gcm, Error> {
+    let KdfParams {
+        memory_kib,
+        iterations,
+        parallelism,
+    } = header.kdf_params.clamped();
+    let params = Params::new(memory_kib, iterations, parallelism, Some(KEY_LENGTH))
+        .map_err(|e| Error::InternalError(format!("Invalid key derivation parameters: {}", e)))?;
+    let mut key = [0u8; KEY_LENGTH];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase, &header.salt, &mut key)
+        .map_err(|e| Error::InternalError(format!("Unable to derive the key: {}", e)))?;
+    Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+}
+
+/// Returns None if the file does not exist or is empty.
+fn read_file(file_path: &Path) -> Result<Option<EncryptedFile>, Error> {
+    if !file_path.exists() {
+        return Ok(None);
+    }
+    let mut file = File::open(file_path)?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+    if contents.is_empty() {
+        return Ok(None);
+    }
+    let file: EncryptedFile = serde_json::from_str(&contents)?;
+    if file.header.format_version != FORMAT_VERSION {
+        return Err(Error::SerializationError(format!(
+            "Unsupported format version: {}",
+            file.header.format_version
+        )));
+    }
+    Ok(Some(file))
+}
+
+fn decrypt(cipher: &Aes256Gcm, file: &EncryptedFile) -> Result<HashMap<String, Value>, Error> {
+    if file.nonce.len() != NONCE_LENGTH {
+        return Err(Error::SerializationError(format!(
+            "Invalid nonce length: {}",
+            file.nonce.len()
+        )));
+    }
+    let aad = serde_json::to_vec(&file.header)?;
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&file.nonce), Payload {
+            msg: &file.ciphertext,
+            aad: &aad,
+        })
+        .map_err(|_| {
+            Error::DecryptionError("wrong passphrase or corrupted storage file".to_string())
+        })?;
+    Ok(serde_json::from_slice(&plaintext)?)
+}
+
+fn write_file(
+    temp_path: &TempPath,
+    file_path: &Path,
+    cipher: &Aes256Gcm,
+    header: &Header,
+    data: &HashMap<String, Value>,
+) -> Result<(), Error> {
+    let plaintext = serde_json::to_vec(data)?;
+    let aad = serde_json::to_vec(header)?;
+    let nonce = Aes256Gcm::generate_nonce(&mut AeadOsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, Payload {
+            msg: &plaintext,
+            aad: &aad,
+        })
+        .map_err(|e| Error::InternalError(format!("Unable to encrypt the storage: {}", e)))?;
+    let contents = serde_json::to_vec(&EncryptedFile {
+        header: header.clone(),
+        nonce: nonce.to_vec(),
+        ciphertext,
+    })?;
+
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(temp_path.path())?;
+    file.write_all(&contents)?;
+    file.sync_all()?;
+    fs::rename(temp_path, file_path)?;
+    Ok(())
+}
+
+impl KVStorage for EncryptedOnDiskStorage {
+    fn available(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn get<V: DeserializeOwned>(&self, key: &str) -> Result<GetResponse<V>, Error> {
+        let mut data = self.read()?;
+        data.remove(key)
+            .ok_or_else(|| Error::KeyNotSet(key.to_string()))
+            .and_then(|value| serde_json::from_value(value).map_err(|e| e.into()))
+    }
+
+    fn set<V: Serialize>(&mut self, key: &str, value: V) -> Result<(), Error> {
+        let now = self.time_service.now_secs();
+        let mut data = self.read()?;
+        data.insert(
+            key.to_string(),
+            serde_json::to_value(&GetResponse::new(value, now))?,
+        );
+        self.write(&data)
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    fn reset_and_clear(&mut self) -> Result<(), Error> {
+        self.write(&HashMap::new())
+    }
+}
+
+impl CryptoKVStorage for EncryptedOnDiskStorage {}
@@ -0,0 +1,131 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    tests::suite, CryptoStorage, EncryptedOnDiskStorage, Error, KVStorage, KdfParams, Storage,
+};
+use aptos_temppath::TempPath;
+use std::{fs, path::Path};
+
+const PASSPHRASE: &[u8] = b"correct horse battery staple";
+
+// Cheap parameters to keep the tests fast
+const TEST_KDF_PARAMS: KdfParams = KdfParams {
+    memory_kib: 64,
+    iterations: 1,
+    parallelism: 1,
+};
+
+fn open(path: &Path, passphrase: &[u8]) -> Result<EncryptedOnDiskStorage, Error> {
+    EncryptedOnDiskStorage::new_with_kdf_params(path.to_path_buf(), passphrase, TEST_KDF_PARAMS)
+}
+
+#[test]
+fn encrypted_on_disk() {
+    let path_buf = TempPath::new().path().to_path_buf();
+    let mut storage = Storage::from(open(&path_buf, PASSPHRASE).unwrap());
+    suite::execute_all_storage_tests(&mut storage);
+}
+
+#[test]
+fn encrypted_on_disk_reopen() {
+    let temp_path = TempPath::new();
+    let path_buf = temp_path.path().to_path_buf();
+    let mut storage = open(&path_buf, PASSPHRASE).unwrap();
+    storage.set("plaintext", "not on disk").unwrap();
+    let public_key = storage.create_key("consensus").unwrap();
+    drop(storage);
+
+    let contents = fs::read_to_string(&path_buf).unwrap();
+    assert!(!contents.contains("not on disk"));
+
+    let storage = open(&path_buf, PASSPHRASE).unwrap();
+    assert_eq!(
+        storage.get::<String>("plaintext").unwrap().value,
+        "not on disk"
+    );
+    assert_eq!(
+        storage.get_public_key("consensus").unwrap().public_key,
+        public_key
+    );
+
+    assert!(matches!(
+        open(&path_buf, b"wrong passphrase"),
+        Err(Error::DecryptionError(_))
+    ));
+}
+
+#[test]
+fn encrypted_on_disk_tampered() {
+    let temp_path = TempPath::new();
+    let path_buf = temp_path.path().to_path_buf();
+    let mut storage = open(&path_buf, PASSPHRASE).unwrap();
+    storage.set("key", 1u64).unwrap();
+    drop(storage);
+
+    // Modify the authenticated header
+    let contents = fs::read_to_string(&path_buf).unwrap();
+    let mut file: serde_json::Value = serde_json::from_str(&contents).unwrap();
+    file["header"]["key_history"][0]["created_secs"] = serde_json::json!(0);
+    fs::write(&path_buf, serde_json::to_vec(&file).unwrap()).unwrap();
+    assert!(matches!(
+        open(&path_buf, PASSPHRASE),
+        Err(Error::DecryptionError(_))
+    ));
+
+    // Modify the key derivation parameters. They are bounded, so deriving the key is still cheap.
+    let mut file: serde_json::Value = serde_json::from_str(&contents).unwrap();
+    file["header"]["kdf_params"]["iterations"] = serde_json::json!(u32::MAX);
+    fs::write(&path_buf, serde_json::to_vec(&file).unwrap()).unwrap();
+    assert!(matches!(
+        open(&path_buf, PASSPHRASE),
+        Err(Error::DecryptionError(_))
+    ));
+
+    // Modify the ciphertext
+    let mut file: serde_json::Value = serde_json::from_str(&contents).unwrap();
+    let mut ciphertext = base64::decode(file["ciphertext"].as_str().unwrap()).unwrap();
+    ciphertext[0] ^= 1;
+    file["ciphertext"] = serde_json::json!(base64::encode(ciphertext));
+    fs::write(&path_buf, serde_json::to_vec(&file).unwrap()).unwrap();
+    assert!(matches!(
+        open(&path_buf, PASSPHRASE),
+        Err(Error::DecryptionError(_))
+    ));
+}
+
+#[test]
+fn encrypted_on_disk_rotate_encryption_key() {
+    let temp_path = TempPath::new();
+    let path_buf = temp_path.path().to_path_buf();
+    let mut storage = open(&path_buf, PASSPHRASE).unwrap();
+    let public_key = storage.create_key("consensus").unwrap();
+    let rotated_public_key = storage.rotate_key("consensus").unwrap();
+
+    let new_passphrase = b"new passphrase";
+    let key_version = storage.rotate_encryption_key(new_passphrase).unwrap();
+    assert_eq!(key_version.version, 2);
+    assert_eq!(
+        storage.get_public_key("consensus").unwrap().public_key,
+        rotated_public_key
+    );
+    drop(storage);
+
+    assert!(matches!(
+        open(&path_buf, PASSPHRASE),
+        Err(Error::DecryptionError(_))
+    ));
+    let storage = open(&path_buf, new_passphrase).unwrap();
+    let versions: Vec<_> = storage
+        .key_history()
+        .iter()
+        .map(|key_version| key_version.version)
+        .collect();
+    assert_eq!(versions, vec![1, 2]);
+    assert_eq!(
+        storage
+            .get_public_key_previous_version("consensus")
+            .unwrap(),
+        public_key
+    );
+}
@@ -45,7 +45,7 @@ use aptos_schemadb::SchemaBatch;
 use aptos_scratchpad::{SmtAncestors, SparseMerkleTree};
 use aptos_storage_interface::{
     async_proof_fetcher::AsyncProofFetcher,
-    cached_state_view::{CachedStateView, ShardedStateCache},
+    cached_state_view::{CachedStateView, ShardedStateCache, UncommittedDeletions},
     db_ensure as ensure,
     state_delta::StateDelta,
     AptosDbError, DbReader, Result, StateSnapshotReceiver,
@@ -496,8 +496,10 @@ impl StateStore {
                 .freeze(&buffered_state.current_state().base);
             let latest_snapshot_state_view = CachedStateView::new_impl(
                 StateViewId::Miscellaneous,
+                num_transactions,
                 snapshot,
                 speculative_state,
+                UncommittedDeletions::default(),
                 Arc::new(AsyncProofFetcher::new(state_db.clone())),
             );
             let write_sets = state_db
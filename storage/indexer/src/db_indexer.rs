@@ -5,8 +5,9 @@ use aptos_config::config::internal_indexer_db_config::InternalIndexerDBConfig;
 use aptos_db_indexer_schemas::{
     metadata::{MetadataKey, MetadataValue},
     schema::{
-        event_by_key::EventByKeySchema, event_by_version::EventByVersionSchema,
-        indexer_metadata::InternalIndexerMetadataSchema,
+        account_activity::AccountActivitySchema, event_by_key::EventByKeySchema,
+        event_by_version::EventByVersionSchema, indexer_metadata::InternalIndexerMetadataSchema,
+        object_by_owner::ObjectByOwnerSchema, object_owner_history::ObjectOwnerHistorySchema,
         transaction_by_account::TransactionByAccountSchema,
     },
     utils::{
@@ -19,14 +20,22 @@ use aptos_storage_interface::{
     db_ensure as ensure, db_other_bail as bail, AptosDbError, DbReader, Result,
 };
 use aptos_types::{
+    access_path::Path,
     account_address::AccountAddress,
+    account_config::{ObjectCoreResource, ObjectGroupResource, ObjectTransferEvent},
     contract_event::{ContractEvent, EventWithVersion},
     event::EventKey,
     indexer::indexer_db_reader::Order,
-    transaction::{AccountTransactionsWithProof, Transaction, Version},
+    state_store::state_key::{inner::StateKeyInner, StateKey},
+    transaction::{
+        AccountTransactionsWithProof, SignedTransaction, Transaction, TransactionPayload, Version,
+    },
+    write_set::{WriteOp, WriteSet},
 };
+use move_core_types::{language_storage::StructTag, move_resource::MoveStructType};
 use std::{
-    cmp::min,
+    cmp::{max, min},
+    collections::{BTreeMap, BTreeSet},
     sync::{
         mpsc::{self, Receiver, Sender},
         Arc,
@@ -88,6 +97,39 @@ impl DBIndexer {
         db_reader: Arc<dyn DbReader>,
         config: &InternalIndexerDBConfig,
     ) -> Self {
+        // The account activity and object ownership indices only cover the transactions
+        // processed while they are enabled, so record the version each starts at, and drop it
+        // once disabled as that leaves a gap.
+        for (enabled, start_version_key) in [
+            (
+                config.enable_account_activity,
+                MetadataKey::AccountActivityStartVersion,
+            ),
+            (
+                config.enable_object_ownership,
+                MetadataKey::ObjectOwnershipStartVersion,
+            ),
+        ] {
+            if enabled {
+                let metadata = |key| {
+                    db.get::<InternalIndexerMetadataSchema>(key)
+                        .expect("Failed to read indexer metadata")
+                };
+                if metadata(&start_version_key).is_none() {
+                    let start_version = metadata(&MetadataKey::LatestVersion)
+                        .map_or(0, |metavalue| metavalue.expect_version() + 1);
+                    db.put::<InternalIndexerMetadataSchema>(
+                        &start_version_key,
+                        &MetadataValue::Version(start_version),
+                    )
+                    .expect("Failed to write indexer metadata");
+                }
+            } else {
+                db.delete::<InternalIndexerMetadataSchema>(&start_version_key)
+                    .expect("Failed to write indexer metadata");
+            }
+        }
+
         let (sender, reciver) = mpsc::channel();
 
         let db_clone = db.clone();
@@ -120,6 +162,34 @@ impl DBIndexer {
         self.config.enable_transaction
     }
 
+    pub fn account_activity_enabled(&self) -> bool {
+        self.config.enable_account_activity
+    }
+
+    pub fn object_ownership_enabled(&self) -> bool {
+        self.config.enable_object_ownership
+    }
+
+    /// Returns the first version covered by the account activity index.
+    pub fn get_account_activity_start_version(&self) -> Result<Version> {
+        self.db
+            .get::<InternalIndexerMetadataSchema>(&MetadataKey::AccountActivityStartVersion)?
+            .map(|metavalue| metavalue.expect_version())
+            .ok_or_else(|| {
+                AptosDbError::Other("Account activity start version is missing".to_string())
+            })
+    }
+
+    /// Returns the first version covered by the object ownership index.
+    pub fn get_object_ownership_start_version(&self) -> Result<Version> {
+        self.db
+            .get::<InternalIndexerMetadataSchema>(&MetadataKey::ObjectOwnershipStartVersion)?
+            .map(|metavalue| metavalue.expect_version())
+            .ok_or_else(|| {
+                AptosDbError::Other("Object ownership start version is missing".to_string())
+            })
+    }
+
     fn get_main_db_iter(
         &self,
         start_version: Version,
@@ -153,9 +223,26 @@ impl DBIndexer {
 
         let num_transactions = self.get_num_of_transactions(version)?;
         let mut db_iter = self.get_main_db_iter(version, num_transactions)?;
+        let mut write_set_iter =
+            if self.config.enable_account_activity || self.config.enable_object_ownership {
+                Some(
+                    self.main_db_reader
+                        .get_write_set_iterator(version, num_transactions)?,
+                )
+            } else {
+                None
+            };
         let batch = SchemaBatch::new();
         db_iter.try_for_each(|res| {
             let (txn, events) = res?;
+            let write_set = write_set_iter
+                .as_mut()
+                .map(|iter| {
+                    iter.next().ok_or_else(|| {
+                        AptosDbError::Other(format!("Write set missing at version {}", version))
+                    })?
+                })
+                .transpose()?;
             if let Some(txn) = txn.try_as_signed_user_txn() {
                 if self.config.enable_transaction {
                     batch.put::<TransactionByAccountSchema>(
@@ -182,6 +269,32 @@ impl DBIndexer {
                         }
                     });
                 }
+
+                if self.config.enable_account_activity {
+                    if let Some(write_set) = &write_set {
+                        for address in touched_accounts(txn, &events, write_set) {
+                            batch.put::<AccountActivitySchema>(&(address, version), &())?;
+                        }
+                    }
+                }
+            }
+
+            if self.config.enable_object_ownership {
+                if let Some(write_set) = &write_set {
+                    for (object, owner) in object_ownership_changes(&events, write_set) {
+                        // Only keep the current owner in the index, so listing the objects of an
+                        // owner doesn't go through the ones it no longer has.
+                        if let Some(previous_owner) = self.previous_object_owner(object, version)? {
+                            if Some(previous_owner) != owner {
+                                batch.delete::<ObjectByOwnerSchema>(&(previous_owner, object))?;
+                            }
+                        }
+                        if let Some(owner) = owner {
+                            batch.put::<ObjectByOwnerSchema>(&(owner, object), &())?;
+                        }
+                        batch.put::<ObjectOwnerHistorySchema>(&(object, version), &owner)?;
+                    }
+                }
             }
             version += 1;
             Ok::<(), AptosDbError>(())
@@ -197,6 +310,24 @@ impl DBIndexer {
         Ok(version)
     }
 
+    /// Returns the owner of `object` before the transaction at `version`, read from the main db as
+    /// the batches of the indexer are committed asynchronously.
+    fn previous_object_owner(
+        &self,
+        object: AccountAddress,
+        version: Version,
+    ) -> Result<Option<AccountAddress>> {
+        if version == 0 {
+            return Ok(None);
+        }
+        let group = ObjectGroupResource::struct_tag();
+        let group_key = StateKey::resource_group(&object, &group);
+        Ok(self
+            .main_db_reader
+            .get_state_value_by_version(&group_key, version - 1)?
+            .and_then(|group_value| object_owner(&group, group_value.bytes())))
+    }
+
     pub fn get_account_transaction_version_iter(
         &self,
         address: AccountAddress,
@@ -384,4 +515,309 @@ impl DBIndexer {
 
         Ok(AccountTransactionsWithProof::new(txns_with_proofs))
     }
+
+    /// Returns the first version the account activity can be served from: the index doesn't cover
+    /// the versions before it was enabled, and the transactions pruned from the main db can't be
+    /// read anymore.
+    fn account_activity_lower_bound(&self) -> Result<Version> {
+        let start_version = self.get_account_activity_start_version()?;
+        let first_txn_version = self.main_db_reader.get_first_txn_version()?.unwrap_or(0);
+        Ok(max(start_version, first_txn_version))
+    }
+
+    /// Returns the versions of up to `limit` transactions touching `address`, starting at
+    /// `start_version` and going in the given `order`. Result won't contain versions >
+    /// `ledger_version`, nor the ones before the index started or pruned from the main db.
+    pub fn lookup_account_activity(
+        &self,
+        address: AccountAddress,
+        start_version: Version,
+        order: Order,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<Version>> {
+        let lower_bound = self.account_activity_lower_bound()?;
+        let mut versions = Vec::new();
+        match order {
+            Order::Ascending => {
+                let mut iter = self.db.iter::<AccountActivitySchema>()?;
+                iter.seek(&(address, max(start_version, lower_bound)))?;
+                for res in iter.take(limit as usize) {
+                    let ((addr, version), ()) = res?;
+                    if addr != address || version > ledger_version {
+                        break;
+                    }
+                    versions.push(version);
+                }
+            },
+            Order::Descending => {
+                let mut iter = self.db.rev_iter::<AccountActivitySchema>()?;
+                iter.seek_for_prev(&(address, min(start_version, ledger_version)))?;
+                for res in iter.take(limit as usize) {
+                    let ((addr, version), ()) = res?;
+                    // Stop at the lower bound rather than walk the older entries of the account,
+                    // which can't be served.
+                    if addr != address || version < lower_bound {
+                        break;
+                    }
+                    versions.push(version);
+                }
+            },
+        }
+        Ok(versions)
+    }
+
+    pub fn get_account_activity(
+        &self,
+        address: AccountAddress,
+        start_version: Version,
+        order: Order,
+        limit: u64,
+        include_events: bool,
+        ledger_version: Version,
+    ) -> anyhow::Result<AccountTransactionsWithProof> {
+        error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
+
+        let txns_with_proofs = self
+            .lookup_account_activity(address, start_version, order, limit, ledger_version)?
+            .into_iter()
+            .map(|txn_version| {
+                self.main_db_reader.get_transaction_by_version(
+                    txn_version,
+                    ledger_version,
+                    include_events,
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(AccountTransactionsWithProof::new(txns_with_proofs))
+    }
+
+    /// Returns the owner of `object` as of `ledger_version` along with the version it has been
+    /// owned since, `None` as the owner if the object was deleted, or `None` if it is unknown.
+    pub fn get_object_owner(
+        &self,
+        object: AccountAddress,
+        ledger_version: Version,
+    ) -> Result<Option<(Version, Option<AccountAddress>)>> {
+        let mut iter = self.db.rev_iter::<ObjectOwnerHistorySchema>()?;
+        iter.seek_for_prev(&(object, ledger_version))?;
+
+        Ok(iter
+            .next()
+            .transpose()?
+            .and_then(|((key, version), owner)| {
+                if key == object {
+                    Some((version, owner))
+                } else {
+                    None
+                }
+            }))
+    }
+
+    /// Returns up to `limit` objects owned by `owner` as of `ledger_version`, in ascending order
+    /// of address starting at `start_object`, each with the version it has been owned since. If
+    /// `resource_type` is given, only the objects holding a resource of that type are returned.
+    /// As the index only holds the latest owners, the objects `owner` has transferred away since
+    /// `ledger_version` are missing.
+    ///
+    /// Goes through at most `max_owned_objects_scanned` entries of the index, so fewer objects may
+    /// be returned when filtering by `resource_type`. Also returns the object to start at for the
+    /// next page, if there is one.
+    pub fn get_owned_objects(
+        &self,
+        owner: AccountAddress,
+        resource_type: Option<&StructTag>,
+        start_object: AccountAddress,
+        limit: u64,
+        ledger_version: Version,
+    ) -> anyhow::Result<(Vec<(AccountAddress, Version)>, Option<AccountAddress>)> {
+        error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
+
+        let mut iter = self.db.iter::<ObjectByOwnerSchema>()?;
+        iter.seek(&(owner, start_object))?;
+
+        let mut objects = Vec::new();
+        for (scanned, res) in iter.enumerate() {
+            let ((key, object), ()) = res?;
+            if key != owner {
+                break;
+            }
+            if objects.len() as u64 >= limit || scanned >= self.config.max_owned_objects_scanned {
+                return Ok((objects, Some(object)));
+            }
+            // The index holds the latest owners, check the owner had the object at the ledger
+            // version.
+            if let Some((version, Some(current_owner))) =
+                self.get_object_owner(object, ledger_version)?
+            {
+                if current_owner != owner {
+                    continue;
+                }
+                if let Some(resource_type) = resource_type {
+                    if !self.object_has_resource(object, resource_type, ledger_version)? {
+                        continue;
+                    }
+                }
+                objects.push((object, version));
+            }
+        }
+        Ok((objects, None))
+    }
+
+    fn object_has_resource(
+        &self,
+        object: AccountAddress,
+        resource_type: &StructTag,
+        ledger_version: Version,
+    ) -> Result<bool> {
+        let group_key = StateKey::resource_group(&object, &ObjectGroupResource::struct_tag());
+        if let Some(group) = self
+            .main_db_reader
+            .get_state_value_by_version(&group_key, ledger_version)?
+        {
+            let members = bcs::from_bytes::<BTreeMap<StructTag, Vec<u8>>>(group.bytes())?;
+            if members.contains_key(resource_type) {
+                return Ok(true);
+            }
+        }
+        let resource_key = StateKey::resource(&object, resource_type)?;
+        Ok(self
+            .main_db_reader
+            .get_state_value_by_version(&resource_key, ledger_version)?
+            .is_some())
+    }
+
+    /// Returns up to `limit` owner changes of `object`, in ascending order starting at
+    /// `start_version`. Result won't contain changes made after `ledger_version`.
+    pub fn get_object_owner_history(
+        &self,
+        object: AccountAddress,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> anyhow::Result<Vec<(Version, Option<AccountAddress>)>> {
+        error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
+
+        let mut iter = self.db.iter::<ObjectOwnerHistorySchema>()?;
+        iter.seek(&(object, start_version))?;
+
+        let mut history = Vec::new();
+        for res in iter.take(limit as usize) {
+            let ((key, version), owner) = res?;
+            if key != object || version > ledger_version {
+                break;
+            }
+            history.push((version, owner));
+        }
+        Ok(history)
+    }
+}
+
+/// Returns the objects whose owner a transaction changed along with their new owner, or `None`
+/// for the ones it deleted. Those are the objects it created, the ones it emitted transfer events
+/// for, and the ones whose object group it deleted.
+fn object_ownership_changes(
+    events: &[ContractEvent],
+    write_set: &WriteSet,
+) -> BTreeMap<AccountAddress, Option<AccountAddress>> {
+    let mut changes = BTreeMap::new();
+    let mut written_owners = BTreeMap::new();
+    for (state_key, write_op) in write_set {
+        let access_path = match state_key.inner() {
+            StateKeyInner::AccessPath(access_path) => access_path,
+            _ => continue,
+        };
+        let group = match access_path.get_path() {
+            Path::ResourceGroup(group) => group,
+            _ => continue,
+        };
+        match write_op {
+            WriteOp::Creation { data, .. } => {
+                if let Some(owner) = object_owner(&group, data) {
+                    changes.insert(access_path.address, Some(owner));
+                }
+            },
+            WriteOp::Modification { data, .. } => {
+                if let Some(owner) = object_owner(&group, data) {
+                    written_owners.insert(access_path.address, owner);
+                }
+            },
+            WriteOp::Deletion { .. } => {
+                if group == ObjectGroupResource::struct_tag() {
+                    changes.insert(access_path.address, None);
+                }
+            },
+        }
+    }
+
+    // An object may be transferred several times in a transaction, the ObjectCore it writes
+    // holds the final owner.
+    for event in events {
+        if let Ok(transfer) = ObjectTransferEvent::try_from(event) {
+            let owner = written_owners
+                .get(&transfer.object())
+                .copied()
+                .unwrap_or_else(|| transfer.to());
+            changes.entry(transfer.object()).or_insert(Some(owner));
+        }
+    }
+    changes
+}
+
+/// Returns the accounts a user transaction touched: its sender, secondary signers, fee payer and
+/// multisig account, the accounts that emitted its events, and the owners of the resources and
+/// objects it changed other than the reserved framework addresses.
+fn touched_accounts(
+    txn: &SignedTransaction,
+    events: &[ContractEvent],
+    write_set: &WriteSet,
+) -> BTreeSet<AccountAddress> {
+    let mut accounts = BTreeSet::new();
+    accounts.insert(txn.sender());
+    let authenticator = txn.authenticator_ref();
+    accounts.extend(authenticator.secondary_signer_addresses());
+    accounts.extend(authenticator.fee_payer_address());
+    if let TransactionPayload::Multisig(multisig) = txn.payload() {
+        accounts.insert(multisig.multisig_address);
+    }
+
+    accounts.extend(events.iter().filter_map(|event| match event {
+        ContractEvent::V1(v1) => Some(v1.key().get_creator_address()),
+        ContractEvent::V2(_) => None,
+    }));
+
+    for (state_key, write_op) in write_set {
+        let access_path = match state_key.inner() {
+            StateKeyInner::AccessPath(access_path) => access_path,
+            _ => continue,
+        };
+        // The framework writes to the reserved addresses (0x1, 0xa, ...) in nearly every
+        // transaction, e.g. for the gas fees or the coin supply, so indexing them would grow the
+        // activity of these addresses with the whole chain. They still count when sending or
+        // signing.
+        if !access_path.address.is_special() {
+            accounts.insert(access_path.address);
+        }
+        if let (Path::ResourceGroup(group), Some(bytes)) =
+            (access_path.get_path(), write_op.bytes())
+        {
+            if let Some(owner) = object_owner(&group, bytes) {
+                accounts.insert(owner);
+            }
+        }
+    }
+    accounts
+}
+
+/// Returns the owner recorded in the `ObjectCore` of an object group, if there is one.
+fn object_owner(group: &StructTag, bytes: &[u8]) -> Option<AccountAddress> {
+    if *group != ObjectGroupResource::struct_tag() {
+        return None;
+    }
+    let members = bcs::from_bytes::<BTreeMap<StructTag, Vec<u8>>>(bytes).ok()?;
+    let object_core = members.get(&ObjectCoreResource::struct_tag())?;
+    bcs::from_bytes::<ObjectCoreResource>(object_core)
+        .ok()
+        .map(|object_core| object_core.owner())
 }
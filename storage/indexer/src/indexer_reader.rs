@@ -11,6 +11,7 @@ use aptos_types::{
     state_store::table::{TableHandle, TableInfo},
     transaction::{AccountTransactionsWithProof, Version},
 };
+use move_core_types::language_storage::StructTag;
 use std::sync::Arc;
 
 pub struct IndexerReaders {
@@ -113,4 +114,98 @@ impl IndexerReader for IndexerReaders {
         }
         bail!("DB indexer reader is not available")
     }
+
+    fn get_account_activity_start_version(&self) -> Result<Version> {
+        if let Some(db_indexer_reader) = &self.db_indexer_reader {
+            if db_indexer_reader.account_activity_enabled() {
+                return Ok(db_indexer_reader.get_account_activity_start_version()?);
+            } else {
+                bail!("Account activity index is not enabled")
+            }
+        }
+        bail!("DB indexer reader is not available")
+    }
+
+    fn get_account_activity(
+        &self,
+        address: AccountAddress,
+        start_version: Version,
+        order: Order,
+        limit: u64,
+        include_events: bool,
+        ledger_version: Version,
+    ) -> Result<AccountTransactionsWithProof> {
+        if let Some(db_indexer_reader) = &self.db_indexer_reader {
+            if db_indexer_reader.account_activity_enabled() {
+                return db_indexer_reader.get_account_activity(
+                    address,
+                    start_version,
+                    order,
+                    limit,
+                    include_events,
+                    ledger_version,
+                );
+            } else {
+                bail!("Account activity index is not enabled")
+            }
+        }
+        bail!("DB indexer reader is not available")
+    }
+
+    fn get_object_ownership_start_version(&self) -> Result<Version> {
+        if let Some(db_indexer_reader) = &self.db_indexer_reader {
+            if db_indexer_reader.object_ownership_enabled() {
+                return Ok(db_indexer_reader.get_object_ownership_start_version()?);
+            } else {
+                bail!("Object ownership index is not enabled")
+            }
+        }
+        bail!("DB indexer reader is not available")
+    }
+
+    fn get_owned_objects(
+        &self,
+        owner: AccountAddress,
+        resource_type: Option<&StructTag>,
+        start_object: AccountAddress,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<(Vec<(AccountAddress, Version)>, Option<AccountAddress>)> {
+        if let Some(db_indexer_reader) = &self.db_indexer_reader {
+            if db_indexer_reader.object_ownership_enabled() {
+                return db_indexer_reader.get_owned_objects(
+                    owner,
+                    resource_type,
+                    start_object,
+                    limit,
+                    ledger_version,
+                );
+            } else {
+                bail!("Object ownership index is not enabled")
+            }
+        }
+        bail!("DB indexer reader is not available")
+    }
+
+    fn get_object_owner_history(
+        &self,
+        object: AccountAddress,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<(Version, Option<AccountAddress>)>> {
+        if let Some(db_indexer_reader) = &self.db_indexer_reader {
+            if db_indexer_reader.object_ownership_enabled() {
+                return db_indexer_reader.get_object_owner_history(
+                    object,
+                    start_version,
+                    limit,
+                    ledger_version,
+                );
+            } else {
+                bail!("Object ownership index is not enabled")
+            }
+        }
+        bail!("DB indexer reader is not available")
+    }
 }
@@ -0,0 +1,60 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for an index of the transactions touching an
+//! account, via which the versions of all transactions that `address` took part in can be found,
+//! whether as sender, secondary signer, fee payer, multisig account, owner of a changed resource
+//! or object, or event emitter.
+//!
+//! ```text
+//! |<-------key------->|<-value->|
+//! | address | txn_ver |  empty  |
+//! ```
+
+use crate::{schema::ACCOUNT_ACTIVITY_CF_NAME, utils::ensure_slice_len_eq};
+use anyhow::Result;
+use aptos_schemadb::{
+    define_pub_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use aptos_types::{account_address::AccountAddress, transaction::Version};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::{convert::TryFrom, mem::size_of};
+
+define_pub_schema!(AccountActivitySchema, Key, (), ACCOUNT_ACTIVITY_CF_NAME);
+
+type Key = (AccountAddress, Version);
+
+impl KeyCodec<AccountActivitySchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (ref address, version) = *self;
+
+        let mut encoded = address.to_vec();
+        encoded.write_u64::<BigEndian>(version)?;
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Self>())?;
+
+        let address = AccountAddress::try_from(&data[..AccountAddress::LENGTH])?;
+        let version = (&data[AccountAddress::LENGTH..]).read_u64::<BigEndian>()?;
+
+        Ok((address, version))
+    }
+}
+
+impl ValueCodec<AccountActivitySchema> for () {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, 0)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test;
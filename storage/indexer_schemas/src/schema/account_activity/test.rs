@@ -0,0 +1,18 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use aptos_schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn test_encode_decode(
+        address in any::<AccountAddress>(),
+        version in any::<Version>(),
+    ) {
+        assert_encode_decode::<AccountActivitySchema>(&(address, version), &());
+    }
+}
+
+test_no_panic_decoding!(AccountActivitySchema);
@@ -6,9 +6,12 @@
 //!
 //! All schemas are `pub(crate)` so not shown in rustdoc, refer to the source code to see details.
 
+pub mod account_activity;
 pub mod event_by_key;
 pub mod event_by_version;
 pub mod indexer_metadata;
+pub mod object_by_owner;
+pub mod object_owner_history;
 pub mod table_info;
 pub mod transaction_by_account;
 use aptos_schemadb::ColumnFamilyName;
@@ -20,6 +23,9 @@ pub const TABLE_INFO_CF_NAME: ColumnFamilyName = "table_info";
 pub const EVENT_BY_KEY_CF_NAME: ColumnFamilyName = "event_by_key";
 pub const EVENT_BY_VERSION_CF_NAME: ColumnFamilyName = "event_by_version";
 pub const TRANSACTION_BY_ACCOUNT_CF_NAME: ColumnFamilyName = "transaction_by_account";
+pub const ACCOUNT_ACTIVITY_CF_NAME: ColumnFamilyName = "account_activity";
+pub const OBJECT_BY_OWNER_CF_NAME: ColumnFamilyName = "object_by_owner";
+pub const OBJECT_OWNER_HISTORY_CF_NAME: ColumnFamilyName = "object_owner_history";
 
 pub fn column_families() -> Vec<ColumnFamilyName> {
     vec![
@@ -36,5 +42,8 @@ pub fn internal_indexer_column_families() -> Vec<ColumnFamilyName> {
         EVENT_BY_KEY_CF_NAME,
         EVENT_BY_VERSION_CF_NAME,
         TRANSACTION_BY_ACCOUNT_CF_NAME,
+        ACCOUNT_ACTIVITY_CF_NAME,
+        OBJECT_BY_OWNER_CF_NAME,
+        OBJECT_OWNER_HISTORY_CF_NAME,
     ]
 }
@@ -0,0 +1,60 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for an index via which the objects that `owner`
+//! owns or has owned can be found. Whether `owner` owns an object at a given version is answered
+//! by `ObjectOwnerHistorySchema`.
+//!
+//! ```text
+//! |<------key------>|<-value->|
+//! | owner | object  |  empty  |
+//! ```
+
+use crate::{schema::OBJECT_BY_OWNER_CF_NAME, utils::ensure_slice_len_eq};
+use anyhow::Result;
+use aptos_schemadb::{
+    define_pub_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use aptos_types::account_address::AccountAddress;
+use std::{convert::TryFrom, mem::size_of};
+
+define_pub_schema!(ObjectByOwnerSchema, Key, (), OBJECT_BY_OWNER_CF_NAME);
+
+type Owner = AccountAddress;
+type Object = AccountAddress;
+type Key = (Owner, Object);
+
+impl KeyCodec<ObjectByOwnerSchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (ref owner, ref object) = *self;
+
+        let mut encoded = owner.to_vec();
+        encoded.extend_from_slice(object.as_ref());
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Self>())?;
+
+        let owner = AccountAddress::try_from(&data[..AccountAddress::LENGTH])?;
+        let object = AccountAddress::try_from(&data[AccountAddress::LENGTH..])?;
+
+        Ok((owner, object))
+    }
+}
+
+impl ValueCodec<ObjectByOwnerSchema> for () {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, 0)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test;
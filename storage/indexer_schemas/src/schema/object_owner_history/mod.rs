@@ -0,0 +1,68 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the ownership history of objects, recording
+//! the owner an object has from `txn_ver` on. An empty owner means the object was deleted.
+//!
+//! ```text
+//! |<-------key------->|<-value->|
+//! | object  | txn_ver |  owner  |
+//! ```
+
+use crate::{schema::OBJECT_OWNER_HISTORY_CF_NAME, utils::ensure_slice_len_eq};
+use anyhow::Result;
+use aptos_schemadb::{
+    define_pub_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use aptos_types::{account_address::AccountAddress, transaction::Version};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::{convert::TryFrom, mem::size_of};
+
+define_pub_schema!(
+    ObjectOwnerHistorySchema,
+    Key,
+    Value,
+    OBJECT_OWNER_HISTORY_CF_NAME
+);
+
+type Key = (AccountAddress, Version);
+type Value = Option<AccountAddress>;
+
+impl KeyCodec<ObjectOwnerHistorySchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (ref object, version) = *self;
+
+        let mut encoded = object.to_vec();
+        encoded.write_u64::<BigEndian>(version)?;
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Self>())?;
+
+        let object = AccountAddress::try_from(&data[..AccountAddress::LENGTH])?;
+        let version = (&data[AccountAddress::LENGTH..]).read_u64::<BigEndian>()?;
+
+        Ok((object, version))
+    }
+}
+
+impl ValueCodec<ObjectOwnerHistorySchema> for Value {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(self.map_or_else(Vec::new, |owner| owner.to_vec()))
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        if data.is_empty() {
+            return Ok(None);
+        }
+        ensure_slice_len_eq(data, AccountAddress::LENGTH)?;
+
+        Ok(Some(AccountAddress::try_from(data)?))
+    }
+}
+
+#[cfg(test)]
+mod test;
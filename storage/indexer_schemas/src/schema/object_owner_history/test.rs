@@ -0,0 +1,19 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use aptos_schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn test_encode_decode(
+        object in any::<AccountAddress>(),
+        version in any::<Version>(),
+        owner in any::<Option<AccountAddress>>(),
+    ) {
+        assert_encode_decode::<ObjectOwnerHistorySchema>(&(object, version), &owner);
+    }
+}
+
+test_no_panic_decoding!(ObjectOwnerHistorySchema);
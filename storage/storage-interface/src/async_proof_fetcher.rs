@@ -55,6 +55,14 @@ impl AsyncProofFetcher {
         }
     }
 
+    pub fn is_state_key_forked(&self, state_key: &StateKey, version: Version) -> Result<bool> {
+        Ok(self.reader.is_state_key_forked(state_key, version)?)
+    }
+
+    pub fn fetch_forked_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        Ok(self.reader.get_forked_state_value(state_key)?)
+    }
+
     pub fn fetch_state_value(
         &self,
         state_key: &StateKey,
@@ -11,7 +11,7 @@ use aptos_types::{
     proof::SparseMerkleProofExt,
     state_store::{
         errors::StateviewError, state_key::StateKey, state_storage_usage::StateStorageUsage,
-        state_value::StateValue, StateViewId, TStateView,
+        state_value::StateValue, ShardedStateUpdates, StateViewId, TStateView,
     },
     transaction::Version,
     write_set::WriteSet,
@@ -80,18 +80,71 @@ impl ShardedStateCache {
     }
 }
 
+/// The state keys deleted by the executed blocks that are not committed yet, each with the last
+/// version of the block that deleted it. Deleting a key that only exists on the network the DB is
+/// forked from leaves no trace in the state trees, and the DB only learns about it once committed.
+#[derive(Clone, Debug, Default)]
+pub struct UncommittedDeletions(Arc<HashMap<StateKey, Version>>);
+
+impl UncommittedDeletions {
+    /// Returns the deletions once a block ending at `version` with the given `updates` is executed
+    /// on top of these, leaving out the ones of the blocks committed as of `committed_version`.
+    pub fn extend(
+        &self,
+        updates: &ShardedStateUpdates,
+        version: Version,
+        committed_version: Option<Version>,
+    ) -> Self {
+        let mut deletions: HashMap<_, _> = self
+            .0
+            .iter()
+            .filter(|(_, deletion_version)| Some(**deletion_version) > committed_version)
+            .map(|(state_key, deletion_version)| (state_key.clone(), *deletion_version))
+            .collect();
+        for (state_key, value) in updates.iter().flatten() {
+            match value {
+                Some(_) => deletions.remove(state_key),
+                None => deletions.insert(state_key.clone(), version),
+            };
+        }
+        Self(Arc::new(deletions))
+    }
+
+    pub fn contains(&self, state_key: &StateKey) -> bool {
+        self.0.contains_key(state_key)
+    }
+}
+
+/// The value of a state key in the local state, that is without the network the DB may be forked
+/// from.
+enum LocalStateValue {
+    /// The key has a value locally.
+    Exists(StateValue),
+    /// The key has no value locally, and the local state is authoritative for it: it was deleted
+    /// locally, or the DB is not forked.
+    Deleted,
+    /// The key has never been written locally, so its value is that of the forked network.
+    Absent,
+}
+
 /// `CachedStateView` is like a snapshot of the global state comprised of state view at two
 /// levels, persistent storage and memory.
 pub struct CachedStateView {
     /// For logging and debugging purpose, identifies what this view is for.
     id: StateViewId,
 
+    /// The version of the first transaction to be executed on top of this view.
+    next_version: Version,
+
     /// A readable snapshot in the persistent storage.
     snapshot: Option<(Version, HashValue)>,
 
     /// The in-memory state on top of the snapshot.
     speculative_state: FrozenSparseMerkleTree<StateValue>,
 
+    /// The keys deleted by the uncommitted blocks `speculative_state` includes.
+    uncommitted_deletions: UncommittedDeletions,
+
     /// The cache of verified account states from `reader` and `speculative_state_view`,
     /// represented by a hashmap with an account address as key and a pair of an ordered
     /// account state map and an an optional account state proof as value. When the VM queries an
@@ -146,12 +199,14 @@ impl Debug for CachedStateView {
 impl CachedStateView {
     /// Constructs a [`CachedStateView`] with persistent state view in the DB and the in-memory
     /// speculative state represented by `speculative_state`. The persistent state view is the
-    /// latest one preceding `next_version`
+    /// latest one preceding `next_version`. `uncommitted_deletions` holds the keys deleted by the
+    /// uncommitted blocks `speculative_state` includes.
     pub fn new(
         id: StateViewId,
         reader: Arc<dyn DbReader>,
         next_version: Version,
         speculative_state: SparseMerkleTree<StateValue>,
+        uncommitted_deletions: UncommittedDeletions,
         proof_fetcher: Arc<AsyncProofFetcher>,
     ) -> Result<Self> {
         // n.b. Freeze the state before getting the state snapshot, otherwise it's possible that
@@ -165,22 +220,28 @@ impl CachedStateView {
 
         Ok(Self::new_impl(
             id,
+            next_version,
             snapshot,
             speculative_state,
+            uncommitted_deletions,
             proof_fetcher,
         ))
     }
 
     pub fn new_impl(
         id: StateViewId,
+        next_version: Version,
         snapshot: Option<(Version, HashValue)>,
         speculative_state: FrozenSparseMerkleTree<StateValue>,
+        uncommitted_deletions: UncommittedDeletions,
         proof_fetcher: Arc<AsyncProofFetcher>,
     ) -> Self {
         Self {
             id,
+            next_version,
             snapshot,
             speculative_state,
+            uncommitted_deletions,
             sharded_state_cache: ShardedStateCache::default(),
             proof_fetcher,
         }
@@ -214,6 +275,47 @@ impl CachedStateView {
         }
     }
 
+    fn get_local_state_value(&self, state_key: &StateKey) -> Result<LocalStateValue> {
+        if let Some(value) = self.get_cached_state_value(state_key)? {
+            return Ok(LocalStateValue::Exists(value));
+        }
+        // Deletions of keys that only exist on the forked network leave no trace in the state
+        // trees, so whether the key was ever written locally is tracked by the executed blocks
+        // until they are committed, and by the DB from then on.
+        if self.uncommitted_deletions.contains(state_key) {
+            return Ok(LocalStateValue::Deleted);
+        }
+        match self.next_version.checked_sub(1) {
+            Some(version) if self.proof_fetcher.is_state_key_forked(state_key, version)? => {
+                Ok(LocalStateValue::Absent)
+            },
+            _ => Ok(LocalStateValue::Deleted),
+        }
+    }
+
+    fn get_cached_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        // First check if the cache has the state value.
+        if let Some(version_and_value_opt) = self
+            .sharded_state_cache
+            .shard(state_key.get_shard_id())
+            .get(state_key)
+        {
+            // This can return None, which means the value has been deleted from the DB.
+            let value_opt = &version_and_value_opt.1;
+            return Ok(value_opt.clone());
+        }
+        let version_and_state_value_option =
+            self.get_version_and_state_value_internal(state_key)?;
+        // Update the cache if still empty
+        let new_version_and_value = self
+            .sharded_state_cache
+            .shard(state_key.get_shard_id())
+            .entry(state_key.clone())
+            .or_insert(version_and_state_value_option);
+        let value_opt = &new_version_and_value.1;
+        Ok(value_opt.clone())
+    }
+
     fn get_version_and_state_value_internal(
         &self,
         state_key: &StateKey,
@@ -280,26 +382,15 @@ impl TStateView for CachedStateView {
 
     fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
         let _timer = TIMER.with_label_values(&["get_state_value"]).start_timer();
-        // First check if the cache has the state value.
-        if let Some(version_and_value_opt) = self
-            .sharded_state_cache
-            .shard(state_key.get_shard_id())
-            .get(state_key)
-        {
-            // This can return None, which means the value has been deleted from the DB.
-            let value_opt = &version_and_value_opt.1;
-            return Ok(value_opt.clone());
+        // Values of a forked network are not cached here: the cache only reflects the local
+        // state, which is what the state storage usage and stale indices are derived from.
+        match self.get_local_state_value(state_key)? {
+            LocalStateValue::Exists(value) => Ok(Some(value)),
+            LocalStateValue::Deleted => Ok(None),
+            LocalStateValue::Absent => {
+                Ok(self.proof_fetcher.fetch_forked_state_value(state_key)?)
+            },
         }
-        let version_and_state_value_option =
-            self.get_version_and_state_value_internal(state_key)?;
-        // Update the cache if still empty
-        let new_version_and_value = self
-            .sharded_state_cache
-            .shard(state_key.get_shard_id())
-            .entry(state_key.clone())
-            .or_insert(version_and_state_value_option);
-        let value_opt = &new_version_and_value.1;
-        Ok(value_opt.clone())
     }
 
     fn get_usage(&self) -> Result<StateStorageUsage> {
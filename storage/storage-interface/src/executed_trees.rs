@@ -2,8 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    async_proof_fetcher::AsyncProofFetcher, cached_state_view::CachedStateView,
-    state_delta::StateDelta, DbReader,
+    async_proof_fetcher::AsyncProofFetcher,
+    cached_state_view::{CachedStateView, UncommittedDeletions},
+    state_delta::StateDelta,
+    DbReader,
 };
 use aptos_crypto::HashValue;
 use aptos_types::{
@@ -104,6 +106,7 @@ impl ExecutedTrees {
             reader,
             self.transaction_accumulator.num_leaves(),
             self.state.current.clone(),
+            UncommittedDeletions::default(),
             proof_fetcher,
         )
     }
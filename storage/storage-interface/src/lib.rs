@@ -487,6 +487,19 @@ pub trait DbReader: Send + Sync {
         self.get_state_value_with_proof_by_version_ext(state_key, version, 0)
             .map(|(value, proof_ext)| (value, proof_ext.into()))
     }
+
+    /// Returns whether the value of a state key at `version` is that of the network this DB was
+    /// forked from, because the key had not been written locally by then. Always `false` for DBs
+    /// that are not forked.
+    fn is_state_key_forked(&self, state_key: &StateKey, version: Version) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// Returns the value of a state key on the network this DB was forked from. Always `None` for
+    /// DBs that are not forked.
+    fn get_forked_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        Ok(None)
+    }
 }
 
 /// Trait that is implemented by a DB that supports certain public (to client) write APIs
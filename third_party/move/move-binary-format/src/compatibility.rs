@@ -8,8 +8,8 @@ use crate::{
     file_format_common::VERSION_5,
     normalized::Module,
 };
-use move_core_types::vm_status::StatusCode;
-use std::collections::BTreeSet;
+use move_core_types::{identifier::Identifier, language_storage::ModuleId, vm_status::StatusCode};
+use std::{collections::BTreeSet, fmt};
 
 /// The result of a linking and layout compatibility check. Here is what the different combinations. NOTE that if `check_struct_layout` is false, type safety over a series of upgrades cannot be guaranteed.
 /// mean:
@@ -71,123 +71,16 @@ impl Compatibility {
 
     /// Check compatibility for `new_module` relative to old module `old_module`.
     pub fn check(&self, old_module: &Module, new_module: &Module) -> PartialVMResult<()> {
-        let mut struct_and_pub_function_linking = true;
-        let mut struct_layout = true;
-        let mut friend_linking = true;
-
-        // module's name and address are unchanged
-        if old_module.address != new_module.address || old_module.name != new_module.name {
-            struct_and_pub_function_linking = false;
-        }
-
-        // old module's structs are a subset of the new module's structs
-        for (name, old_struct) in &old_module.structs {
-            let new_struct = match new_module.structs.get(name) {
-                Some(new_struct) => new_struct,
-                None => {
-                    // Struct not present in new . Existing modules that depend on this struct will fail to link with the new version of the module.
-                    // Also, struct layout cannot be guaranteed transitively, because after
-                    // removing the struct, it could be re-added later with a different layout.
-                    struct_and_pub_function_linking = false;
-                    struct_layout = false;
-                    break;
-                },
-            };
-
-            if !struct_abilities_compatibile(old_struct.abilities, new_struct.abilities)
-                || !struct_type_parameters_compatibile(
-                    &old_struct.type_parameters,
-                    &new_struct.type_parameters,
-                )
-            {
-                struct_and_pub_function_linking = false;
-            }
-            if new_struct.fields != old_struct.fields {
-                // Fields changed. Code in this module will fail at runtime if it tries to
-                // read a previously published struct value
-                // TODO: this is a stricter definition than required. We could in principle
-                // choose that changing the name (but not position or type) of a field is
-                // compatible. The VM does not care about the name of a field
-                // (it's purely informational), but clients presumably do.
-                struct_layout = false
-            }
-        }
-
-        // The modules are considered as compatible function-wise when all the conditions are met:
-        //
-        // - old module's public functions are a subset of the new module's public functions
-        //   (i.e. we cannot remove or change public functions)
-        // - old module's script functions are a subset of the new module's script functions
-        //   (i.e. we cannot remove or change script functions)
-        // - for any friend function that is removed or changed in the old module
-        //   - if the function visibility is upgraded to public, it is OK
-        //   - otherwise, it is considered as incompatible.
-        //
-        // NOTE: it is possible to relax the compatibility checking for a friend function, i.e.,
-        // we can remove/change a friend function if the function is not used by any module in the
-        // friend list. But for simplicity, we decided to go to the more restrictive form now and
-        // we may revisit this in the future.
-        for (name, old_func) in &old_module.exposed_functions {
-            let new_func = match new_module.exposed_functions.get(name) {
-                Some(new_func) => new_func,
-                None => {
-                    if matches!(old_func.visibility, Visibility::Friend) {
-                        friend_linking = false;
-                    } else {
-                        struct_and_pub_function_linking = false;
-                    }
-                    continue;
-                },
-            };
-            let is_vis_compatible = match (old_func.visibility, new_func.visibility) {
-                // public must remain public
-                (Visibility::Public, Visibility::Public) => true,
-                (Visibility::Public, _) => false,
-                // friend can become public or remain friend
-                (Visibility::Friend, Visibility::Public)
-                | (Visibility::Friend, Visibility::Friend) => true,
-                (Visibility::Friend, _) => false,
-                // private can become public or friend, or stay private
-                (Visibility::Private, _) => true,
-            };
-            let is_entry_compatible = if old_module.file_format_version < VERSION_5
-                && new_module.file_format_version < VERSION_5
-            {
-                // if it was public(script), it must remain pubic(script)
-                // if it was not public(script), it _cannot_ become public(script)
-                old_func.is_entry == new_func.is_entry
-            } else {
-                // If it was an entry function, it must remain one.
-                // If it was not an entry function, it is allowed to become one.
-                !old_func.is_entry || new_func.is_entry
-            };
-            if !is_vis_compatible
-                || !is_entry_compatible
-                || old_func.parameters != new_func.parameters
-                || old_func.return_ != new_func.return_
-                || !fun_type_parameters_compatibile(
-                    &old_func.type_parameters,
-                    &new_func.type_parameters,
-                )
-            {
-                if matches!(old_func.visibility, Visibility::Friend) {
-                    friend_linking = false;
-                } else {
-                    struct_and_pub_function_linking = false;
-                }
-            }
-        }
-
-        // check friend declarations compatibility
-        //
-        // - additions to the list are allowed
-        // - removals are not allowed
-        //
-        let old_friend_module_ids: BTreeSet<_> = old_module.friends.iter().cloned().collect();
-        let new_friend_module_ids: BTreeSet<_> = new_module.friends.iter().cloned().collect();
-        if !old_friend_module_ids.is_subset(&new_friend_module_ids) {
-            friend_linking = false;
-        }
+        let incompatibilities = find_incompatibilities(old_module, new_module);
+        let struct_and_pub_function_linking = !incompatibilities
+            .iter()
+            .any(Incompatibility::breaks_struct_and_pub_function_linking);
+        let struct_layout = !incompatibilities
+            .iter()
+            .any(Incompatibility::breaks_struct_layout);
+        let friend_linking = !incompatibilities
+            .iter()
+            .any(Incompatibility::breaks_friend_linking);
 
         if self.check_struct_and_pub_function_linking && !struct_and_pub_function_linking {
             return Err(PartialVMError::new(
@@ -207,6 +100,254 @@ impl Compatibility {
 
         Ok(())
     }
+
+    /// Returns all changes of `new_module` relative to old module `old_module` which violate
+    /// this compatibility. `check` fails if and only if there is at least one.
+    pub fn incompatibilities(
+        &self,
+        old_module: &Module,
+        new_module: &Module,
+    ) -> Vec<Incompatibility> {
+        find_incompatibilities(old_module, new_module)
+            .into_iter()
+            .filter(|incompatibility| {
+                (self.check_struct_and_pub_function_linking
+                    && incompatibility.breaks_struct_and_pub_function_linking())
+                    || (self.check_struct_layout && incompatibility.breaks_struct_layout())
+                    || (self.check_friend_linking && incompatibility.breaks_friend_linking())
+            })
+            .collect()
+    }
+}
+
+/// A change of a module which is not backward compatible with the old version of the module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Incompatibility {
+    /// The address or name of the module changed
+    ModuleIdChanged,
+    /// The struct was removed
+    StructRemoved(Identifier),
+    /// Abilities of the struct were removed
+    StructAbilitiesRemoved {
+        name: Identifier,
+        old: AbilitySet,
+        new: AbilitySet,
+    },
+    /// The type parameters of the struct changed in number, constraints or phantom declarations
+    StructTypeParametersChanged(Identifier),
+    /// The fields of the struct changed
+    StructLayoutChanged(Identifier),
+    /// The public, friend or entry function with the old visibility was removed
+    FunctionRemoved(Identifier, Visibility),
+    /// The visibility, entry modifier, parameters, return types or type parameters of the public,
+    /// friend or entry function with the old visibility changed
+    FunctionSignatureChanged(Identifier, Visibility),
+    /// The module was removed from the friends of the module
+    FriendRemoved(ModuleId),
+}
+
+impl Incompatibility {
+    /// Whether dependent modules which reference public functions or structs in the module may
+    /// fail to link.
+    pub fn breaks_struct_and_pub_function_linking(&self) -> bool {
+        match self {
+            Incompatibility::ModuleIdChanged
+            | Incompatibility::StructRemoved(_)
+            | Incompatibility::StructAbilitiesRemoved { .. }
+            | Incompatibility::StructTypeParametersChanged(_) => true,
+            Incompatibility::FunctionRemoved(_, visibility)
+            | Incompatibility::FunctionSignatureChanged(_, visibility) => {
+                !matches!(visibility, Visibility::Friend)
+            },
+            Incompatibility::StructLayoutChanged(_) | Incompatibility::FriendRemoved(_) => false,
+        }
+    }
+
+    /// Whether previously published values of structs of the module can no longer be read.
+    pub fn breaks_struct_layout(&self) -> bool {
+        // After removing a struct, it could be re-added later with a different layout
+        matches!(
+            self,
+            Incompatibility::StructRemoved(_) | Incompatibility::StructLayoutChanged(_)
+        )
+    }
+
+    /// Whether friend modules which reference friend functions of the module may fail to link.
+    pub fn breaks_friend_linking(&self) -> bool {
+        match self {
+            Incompatibility::FunctionRemoved(_, visibility)
+            | Incompatibility::FunctionSignatureChanged(_, visibility) => {
+                matches!(visibility, Visibility::Friend)
+            },
+            Incompatibility::FriendRemoved(_) => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let function_kind = |visibility: &Visibility| match visibility {
+            Visibility::Public => "public function",
+            Visibility::Friend => "friend function",
+            Visibility::Private => "entry function",
+        };
+        match self {
+            Incompatibility::ModuleIdChanged => write!(f, "address or name of the module changed"),
+            Incompatibility::StructRemoved(name) => write!(f, "struct `{}` was removed", name),
+            Incompatibility::StructAbilitiesRemoved { name, old, new } => write!(
+                f,
+                "abilities of struct `{}` were reduced from `{}` to `{}`",
+                name, old, new
+            ),
+            Incompatibility::StructTypeParametersChanged(name) => write!(
+                f,
+                "type parameters of struct `{}` changed incompatibly",
+                name
+            ),
+            Incompatibility::StructLayoutChanged(name) => {
+                write!(f, "fields of struct `{}` changed", name)
+            },
+            Incompatibility::FunctionRemoved(name, visibility) => {
+                write!(f, "{} `{}` was removed", function_kind(visibility), name)
+            },
+            Incompatibility::FunctionSignatureChanged(name, visibility) => write!(
+                f,
+                "signature of {} `{}` changed incompatibly",
+                function_kind(visibility),
+                name
+            ),
+            Incompatibility::FriendRemoved(module_id) => {
+                write!(f, "friend `{}` was removed", module_id.short_str_lossless())
+            },
+        }
+    }
+}
+
+/// Returns all changes of `new_module` relative to `old_module` which break any kind of
+/// compatibility.
+fn find_incompatibilities(old_module: &Module, new_module: &Module) -> Vec<Incompatibility> {
+    let mut incompatibilities = vec![];
+
+    // module's name and address are unchanged
+    if old_module.address != new_module.address || old_module.name != new_module.name {
+        incompatibilities.push(Incompatibility::ModuleIdChanged);
+    }
+
+    // old module's structs are a subset of the new module's structs
+    for (name, old_struct) in &old_module.structs {
+        let new_struct = match new_module.structs.get(name) {
+            Some(new_struct) => new_struct,
+            None => {
+                // Struct not present in new . Existing modules that depend on this struct will fail to link with the new version of the module.
+                // Also, struct layout cannot be guaranteed transitively, because after
+                // removing the struct, it could be re-added later with a different layout.
+                incompatibilities.push(Incompatibility::StructRemoved(name.clone()));
+                continue;
+            },
+        };
+
+        if !struct_abilities_compatibile(old_struct.abilities, new_struct.abilities) {
+            incompatibilities.push(Incompatibility::StructAbilitiesRemoved {
+                name: name.clone(),
+                old: old_struct.abilities,
+                new: new_struct.abilities,
+            });
+        }
+        if !struct_type_parameters_compatibile(
+            &old_struct.type_parameters,
+            &new_struct.type_parameters,
+        ) {
+            incompatibilities.push(Incompatibility::StructTypeParametersChanged(name.clone()));
+        }
+        if new_struct.fields != old_struct.fields {
+            // Fields changed. Code in this module will fail at runtime if it tries to
+            // read a previously published struct value
+            // TODO: this is a stricter definition than required. We could in principle
+            // choose that changing the name (but not position or type) of a field is
+            // compatible. The VM does not care about the name of a field
+            // (it's purely informational), but clients presumably do.
+            incompatibilities.push(Incompatibility::StructLayoutChanged(name.clone()));
+        }
+    }
+
+    // The modules are considered as compatible function-wise when all the conditions are met:
+    //
+    // - old module's public functions are a subset of the new module's public functions
+    //   (i.e. we cannot remove or change public functions)
+    // - old module's script functions are a subset of the new module's script functions
+    //   (i.e. we cannot remove or change script functions)
+    // - for any friend function that is removed or changed in the old module
+    //   - if the function visibility is upgraded to public, it is OK
+    //   - otherwise, it is considered as incompatible.
+    //
+    // NOTE: it is possible to relax the compatibility checking for a friend function, i.e.,
+    // we can remove/change a friend function if the function is not used by any module in the
+    // friend list. But for simplicity, we decided to go to the more restrictive form now and
+    // we may revisit this in the future.
+    for (name, old_func) in &old_module.exposed_functions {
+        let new_func = match new_module.exposed_functions.get(name) {
+            Some(new_func) => new_func,
+            None => {
+                incompatibilities.push(Incompatibility::FunctionRemoved(
+                    name.clone(),
+                    old_func.visibility,
+                ));
+                continue;
+            },
+        };
+        let is_vis_compatible = match (old_func.visibility, new_func.visibility) {
+            // public must remain public
+            (Visibility::Public, Visibility::Public) => true,
+            (Visibility::Public, _) => false,
+            // friend can become public or remain friend
+            (Visibility::Friend, Visibility::Public) | (Visibility::Friend, Visibility::Friend) => {
+                true
+            },
+            (Visibility::Friend, _) => false,
+            // private can become public or friend, or stay private
+            (Visibility::Private, _) => true,
+        };
+        let is_entry_compatible = if old_module.file_format_version < VERSION_5
+            && new_module.file_format_version < VERSION_5
+        {
+            // if it was public(script), it must remain pubic(script)
+            // if it was not public(script), it _cannot_ become public(script)
+            old_func.is_entry == new_func.is_entry
+        } else {
+            // If it was an entry function, it must remain one.
+            // If it was not an entry function, it is allowed to become one.
+            !old_func.is_entry || new_func.is_entry
+        };
+        if !is_vis_compatible
+            || !is_entry_compatible
+            || old_func.parameters != new_func.parameters
+            || old_func.return_ != new_func.return_
+            || !fun_type_parameters_compatibile(
+                &old_func.type_parameters,
+                &new_func.type_parameters,
+            )
+        {
+            incompatibilities.push(Incompatibility::FunctionSignatureChanged(
+                name.clone(),
+                old_func.visibility,
+            ));
+        }
+    }
+
+    // check friend declarations compatibility
+    //
+    // - additions to the list are allowed
+    // - removals are not allowed
+    //
+    let new_friend_module_ids: BTreeSet<_> = new_module.friends.iter().collect();
+    for friend in old_module.friends.iter().collect::<BTreeSet<_>>() {
+        if !new_friend_module_ids.contains(friend) {
+            incompatibilities.push(Incompatibility::FriendRemoved(friend.clone()));
+        }
+    }
+
+    incompatibilities
 }
 
 // When upgrading, the new abilities must be a superset of the old abilities.
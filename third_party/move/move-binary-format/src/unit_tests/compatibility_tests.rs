@@ -1,7 +1,11 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{compatibility::Compatibility, file_format::*, normalized};
+use crate::{
+    compatibility::{Compatibility, Incompatibility},
+    file_format::*,
+    normalized,
+};
 use move_core_types::{account_address::AccountAddress, identifier::Identifier};
 use std::convert::TryFrom;
 
@@ -115,3 +119,41 @@ fn deprecated_add_script_visibility() {
         .check(&friend_module, &script_module)
         .is_err());
 }
+
+#[test]
+fn incompatibilities_by_kind() {
+    let fn_name = Identifier::new("fn").unwrap();
+    let script_module = mk_module(Visibility::DEPRECATED_SCRIPT);
+    let public_module = mk_module(Visibility::Public as u8);
+    let friend_module = mk_module(Visibility::Friend as u8);
+    let private_module = mk_module(Visibility::Private as u8);
+    // private functions which are not entry functions are not exposed
+    assert_eq!(
+        Compatibility::full_check().incompatibilities(&script_module, &private_module),
+        vec![Incompatibility::FunctionRemoved(
+            fn_name.clone(),
+            Visibility::Public
+        )]
+    );
+    assert_eq!(
+        Compatibility::full_check().incompatibilities(&public_module, &friend_module),
+        vec![Incompatibility::FunctionSignatureChanged(
+            fn_name.clone(),
+            Visibility::Public
+        )]
+    );
+    // removing a friend function only breaks friend linking
+    assert_eq!(
+        Compatibility::full_check().incompatibilities(&friend_module, &private_module),
+        vec![Incompatibility::FunctionRemoved(
+            fn_name,
+            Visibility::Friend
+        )]
+    );
+    assert!(Compatibility::new(true, true, false)
+        .incompatibilities(&friend_module, &private_module)
+        .is_empty());
+    assert!(Compatibility::new(true, true, false)
+        .check(&friend_module, &private_module)
+        .is_ok());
+}
@@ -177,6 +177,11 @@ pub static EXPERIMENTS: Lazy<BTreeMap<String, Experiment>> = Lazy::new(|| {
                 .to_string(),
             default: Given(false),
         },
+        Experiment {
+            name: Experiment::LINT_CHECKS.to_string(),
+            description: "Whether to run the lints over the compilation targets.".to_string(),
+            default: Given(false),
+        },
         Experiment {
             name: Experiment::ATTACH_COMPILED_MODULE.to_string(),
             description: "Whether to attach the compiled module to the global env.".to_string(),
@@ -206,6 +211,7 @@ impl Experiment {
     pub const KEEP_INLINE_FUNS: &'static str = "keep-inline-funs";
     pub const KEEP_UNINIT_ANNOTATIONS: &'static str = "keep-uninit-annotations";
     pub const LAMBDA_LIFTING: &'static str = "lambda-lifting";
+    pub const LINT_CHECKS: &'static str = "lint-checks";
     pub const OPTIMIZE: &'static str = "optimize";
     pub const RECURSIVE_TYPE_CHECK: &'static str = "recursive-type-check";
     pub const REFERENCE_SAFETY: &'static str = "reference-safety";
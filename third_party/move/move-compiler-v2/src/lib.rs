@@ -12,6 +12,7 @@ mod file_format_generator;
 pub mod flow_insensitive_checkers;
 pub mod function_checker;
 pub mod inliner;
+pub mod lint;
 pub mod logging;
 pub mod options;
 pub mod pipeline;
@@ -24,6 +25,7 @@ use crate::{
         lambda_lifter, lambda_lifter::LambdaLiftingOptions, rewrite_target::RewritingScope,
         seqs_in_binop_checker, spec_checker, spec_rewriter, EnvProcessorPipeline,
     },
+    lint::BytecodeLintProcessor,
     pipeline::{
         ability_processor::AbilityProcessor, avail_copies_analysis::AvailCopiesAnalysisProcessor,
         copy_propagation::CopyPropagation, dead_store_elimination::DeadStoreElimination,
@@ -370,6 +372,14 @@ pub fn check_and_rewrite_pipeline<'a, 'b>(
         env_pipeline.add("specification rewriter", spec_rewriter::run_spec_rewriter);
     }
 
+    if !for_v1_model && options.experiment_on(Experiment::LINT_CHECKS) {
+        // The bytecode lints run later, as part of the bytecode pipeline.
+        let lint_config = options.lint_config.clone();
+        env_pipeline.add("lints", move |env: &mut GlobalEnv| {
+            lint::run_exp_lints(env, &lint_config)
+        });
+    }
+
     env_pipeline
 }
 
@@ -396,6 +406,14 @@ pub fn bytecode_pipeline(env: &GlobalEnv) -> FunctionTargetPipeline {
     pipeline.add_processor(Box::new(LiveVarAnalysisProcessor::new(false)));
     pipeline.add_processor(Box::new(ReferenceSafetyProcessor {}));
 
+    // Bytecode lints use the live variable annotations from above, and need to see the code
+    // before it is transformed.
+    if options.experiment_on(Experiment::LINT_CHECKS) {
+        pipeline.add_processor(Box::new(BytecodeLintProcessor::new(
+            options.lint_config.clone(),
+        )));
+    }
+
     if options.experiment_on(Experiment::ABILITY_CHECK) {
         pipeline.add_processor(Box::new(ExitStateAnalysisProcessor {}));
         pipeline.add_processor(Box::new(AbilityProcessor {}));
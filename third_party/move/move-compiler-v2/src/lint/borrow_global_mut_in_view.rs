@@ -0,0 +1,47 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Lint which flags `borrow_global_mut` in `#[view]` functions. View functions are executed
+//! without committing their effects, so a mutable borrow is either a mistake or a sign that the
+//! function is not meant to be a view function.
+
+use crate::lint::{ExpLint, LintReporter};
+use move_model::{
+    ast::{Exp, ExpData, Operation},
+    model::FunctionEnv,
+    ty::ReferenceKind,
+};
+
+const VIEW_FUN_ATTRIBUTE: &str = "view";
+
+pub struct BorrowGlobalMutInView;
+
+impl ExpLint for BorrowGlobalMutInView {
+    fn name(&self) -> &'static str {
+        "borrow_global_mut_in_view"
+    }
+
+    fn description(&self) -> &'static str {
+        "`borrow_global_mut` in a `#[view]` function"
+    }
+
+    fn check(&self, reporter: &LintReporter, fun: &FunctionEnv, body: &Exp) {
+        let env = reporter.env();
+        let is_view = fun.has_attribute(|attr| {
+            env.symbol_pool().string(attr.name()).as_str() == VIEW_FUN_ATTRIBUTE
+        });
+        if !is_view {
+            return;
+        }
+        body.visit_pre_order(&mut |e| {
+            if let ExpData::Call(id, Operation::BorrowGlobal(ReferenceKind::Mutable), _) = e {
+                reporter.report(
+                    &env.get_node_loc(*id),
+                    "mutable borrow of global storage in a view function, whose effects are \
+                     never committed",
+                );
+            }
+            true
+        });
+    }
+}
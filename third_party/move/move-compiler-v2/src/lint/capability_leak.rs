@@ -0,0 +1,75 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Lint which flags public functions returning a `signer`, or one of the capabilities of the
+//! Aptos framework which are derived from a signer. Anyone can call a public function, so
+//! returning such a value hands out the authority of the account it was derived from.
+
+use crate::lint::{ExpLint, LintReporter};
+use move_model::{
+    ast::Exp,
+    model::{FunctionEnv, Visibility},
+    ty::Type,
+};
+
+/// Framework types which grant the authority of the signer they were created from.
+const CAPABILITY_TYPES: &[&str] = &[
+    "account::SignerCapability",
+    "coin::BurnCapability",
+    "coin::FreezeCapability",
+    "coin::MintCapability",
+    "fungible_asset::BurnRef",
+    "fungible_asset::MintRef",
+    "fungible_asset::TransferRef",
+    "object::ConstructorRef",
+    "object::DeleteRef",
+    "object::ExtendRef",
+    "object::TransferRef",
+];
+
+pub struct CapabilityLeak;
+
+impl ExpLint for CapabilityLeak {
+    fn name(&self) -> &'static str {
+        "capability_leak"
+    }
+
+    fn description(&self) -> &'static str {
+        "public function returning a signer or a signer derived capability"
+    }
+
+    fn check(&self, reporter: &LintReporter, fun: &FunctionEnv, _body: &Exp) {
+        if fun.visibility() != Visibility::Public {
+            return;
+        }
+        let env = reporter.env();
+        let mut leaked = None;
+        fun.get_result_type().visit(&mut |ty| {
+            if leaked.is_some() {
+                return;
+            }
+            if ty.is_signer() {
+                leaked = Some("signer".to_string())
+            } else if let Type::Struct(..) = ty {
+                let (struct_env, _) = ty.get_struct(env).expect("struct type");
+                let name = struct_env.get_full_name_str();
+                if struct_env.module_env.get_name().addr() == &env.get_stdlib_address()
+                    && CAPABILITY_TYPES.contains(&name.as_str())
+                {
+                    leaked = Some(name)
+                }
+            }
+        });
+        if let Some(leaked) = leaked {
+            reporter.report(
+                &fun.get_id_loc(),
+                &format!(
+                    "public function `{}` returns a value of type `{}`, which lets any caller \
+                     act on behalf of the account it was derived from",
+                    fun.get_full_name_str(),
+                    leaked
+                ),
+            );
+        }
+    }
+}
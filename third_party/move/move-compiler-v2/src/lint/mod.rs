@@ -0,0 +1,335 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implements an extensible lint framework on top of the model and the stackless bytecode.
+//!
+//! Lints come in two flavors:
+//! * `ExpLint`s inspect the (checked and rewritten) AST of each function in the model. They run
+//!   at the end of the env processor pipeline.
+//! * `BytecodeLint`s inspect the stackless bytecode of each function. They run as the
+//!   `BytecodeLintProcessor` in the bytecode pipeline, after live variable analysis, so they can
+//!   use the `LiveVarAnnotation` of a function target.
+//!
+//! The compiler runs both when the `lint-checks` experiment is on, using the `LintConfig` of its
+//! `Options`.
+//!
+//! Each lint has a name and a default `LintLevel`, which can be overridden via a `LintConfig`.
+//! Lints only run on functions which are targets of the compilation, and can be suppressed for a
+//! function or a whole module with the `#[lint::skip(name, ..)]` attribute. Without arguments,
+//! `#[lint::skip]` suppresses all lints.
+
+mod borrow_global_mut_in_view;
+mod capability_leak;
+mod needless_copy;
+mod needless_deref;
+mod unbounded_vector_iteration;
+mod unchecked_fee_arithmetic;
+
+use anyhow::bail;
+use codespan_reporting::diagnostic::Severity;
+use move_model::{
+    ast::{Attribute, Exp},
+    model::{FunctionEnv, GlobalEnv, Loc},
+};
+use move_stackless_bytecode::{
+    function_target::{FunctionData, FunctionTarget},
+    function_target_pipeline::{FunctionTargetProcessor, FunctionTargetsHolder},
+};
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+/// The attribute used to suppress lints for a function or a module.
+pub const LINT_SKIP_ATTRIBUTE: &str = "lint::skip";
+
+/// The level at which a lint is reported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LintLevel {
+    /// The lint is not run.
+    Allow,
+    /// Violations are reported as warnings.
+    Warn,
+    /// Violations are reported as errors.
+    Deny,
+}
+
+impl LintLevel {
+    fn severity(self) -> Option<Severity> {
+        match self {
+            LintLevel::Allow => None,
+            LintLevel::Warn => Some(Severity::Warning),
+            LintLevel::Deny => Some(Severity::Error),
+        }
+    }
+}
+
+impl FromStr for LintLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "allow" => LintLevel::Allow,
+            "warn" => LintLevel::Warn,
+            "deny" => LintLevel::Deny,
+            _ => bail!(
+                "invalid lint level `{}`: must be `allow`, `warn` or `deny`",
+                s
+            ),
+        })
+    }
+}
+
+impl fmt::Display for LintLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LintLevel::Allow => "allow",
+            LintLevel::Warn => "warn",
+            LintLevel::Deny => "deny",
+        })
+    }
+}
+
+/// Overrides the default levels of lints.
+#[derive(Clone, Debug, Default)]
+pub struct LintConfig {
+    levels: BTreeMap<String, LintLevel>,
+}
+
+impl LintConfig {
+    /// Sets the level of the named lint, failing if there is no such lint.
+    pub fn set_level(&mut self, name: &str, level: LintLevel) -> anyhow::Result<()> {
+        if !all_lints().iter().any(|(lint, _, _)| *lint == name) {
+            bail!("unknown lint `{}`", name)
+        }
+        self.levels.insert(name.to_string(), level);
+        Ok(())
+    }
+
+    /// Returns the level of the named lint, given its default level.
+    pub fn level(&self, name: &str, default: LintLevel) -> LintLevel {
+        self.levels.get(name).copied().unwrap_or(default)
+    }
+}
+
+/// A lint over the AST of a function.
+pub trait ExpLint {
+    /// The name of the lint, as used in `LintConfig` and `#[lint::skip]`.
+    fn name(&self) -> &'static str;
+
+    /// A one line description of what the lint checks.
+    fn description(&self) -> &'static str;
+
+    fn default_level(&self) -> LintLevel {
+        LintLevel::Warn
+    }
+
+    /// Checks the function with the given body, reporting violations via `reporter`.
+    fn check(&self, reporter: &LintReporter, fun: &FunctionEnv, body: &Exp);
+}
+
+/// A lint over the stackless bytecode of a function. Live variable annotations are available.
+pub trait BytecodeLint {
+    /// The name of the lint, as used in `LintConfig` and `#[lint::skip]`.
+    fn name(&self) -> &'static str;
+
+    /// A one line description of what the lint checks.
+    fn description(&self) -> &'static str;
+
+    fn default_level(&self) -> LintLevel {
+        LintLevel::Warn
+    }
+
+    /// Checks the function target, reporting violations via `reporter`.
+    fn check(&self, reporter: &LintReporter, target: &FunctionTarget);
+}
+
+/// Reports violations of a lint in a function.
+pub struct LintReporter<'env> {
+    env: &'env GlobalEnv,
+    name: &'static str,
+    severity: Severity,
+}
+
+impl<'env> LintReporter<'env> {
+    /// Creates a reporter for the lint in the given function, or `None` if the lint is allowed
+    /// or skipped for this function.
+    fn new(
+        config: &LintConfig,
+        name: &'static str,
+        default_level: LintLevel,
+        fun: &FunctionEnv<'env>,
+    ) -> Option<Self> {
+        let severity = config.level(name, default_level).severity()?;
+        if is_skipped(fun, name) {
+            return None;
+        }
+        Some(Self {
+            env: fun.module_env.env,
+            name,
+            severity,
+        })
+    }
+
+    pub fn env(&self) -> &'env GlobalEnv {
+        self.env
+    }
+
+    /// Reports a violation at the given location.
+    pub fn report(&self, loc: &Loc, msg: &str) {
+        self.env
+            .diag_with_notes(self.severity, loc, msg, vec![format!(
+                "this is reported by lint `{}`, which can be suppressed with `#[{}({})]`",
+                self.name, LINT_SKIP_ATTRIBUTE, self.name
+            )]);
+    }
+}
+
+/// Returns the name, description and default level of all lints.
+pub fn all_lints() -> Vec<(&'static str, &'static str, LintLevel)> {
+    let exp_lints = exp_lints()
+        .into_iter()
+        .map(|l| (l.name(), l.description(), l.default_level()));
+    let bytecode_lints = bytecode_lints()
+        .into_iter()
+        .map(|l| (l.name(), l.description(), l.default_level()));
+    exp_lints.chain(bytecode_lints).collect()
+}
+
+fn exp_lints() -> Vec<Box<dyn ExpLint>> {
+    vec![
+        Box::new(borrow_global_mut_in_view::BorrowGlobalMutInView),
+        Box::new(capability_leak::CapabilityLeak),
+        Box::new(needless_deref::NeedlessDeref),
+        Box::new(unbounded_vector_iteration::UnboundedVectorIteration),
+        Box::new(unchecked_fee_arithmetic::UncheckedFeeArithmetic),
+    ]
+}
+
+fn bytecode_lints() -> Vec<Box<dyn BytecodeLint>> {
+    vec![Box::new(needless_copy::NeedlessCopy)]
+}
+
+/// Runs the AST lints on the target functions of the environment, adding diagnostics to it.
+/// The environment must be free of errors.
+pub fn run_exp_lints(env: &GlobalEnv, config: &LintConfig) {
+    let exp_lints = exp_lints();
+    for module in env.get_modules() {
+        if !module.is_target() {
+            continue;
+        }
+        for fun in module.get_functions() {
+            if fun.is_test_only() {
+                continue;
+            }
+            if let Some(body) = fun.get_def() {
+                for lint in &exp_lints {
+                    if let Some(reporter) =
+                        LintReporter::new(config, lint.name(), lint.default_level(), &fun)
+                    {
+                        lint.check(&reporter, &fun, body)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Runs the bytecode lints as part of a function target pipeline. Must run after
+/// `LiveVarAnalysisProcessor`, and before any processor which transforms the code.
+pub struct BytecodeLintProcessor {
+    config: LintConfig,
+    lints: Vec<Box<dyn BytecodeLint>>,
+}
+
+impl BytecodeLintProcessor {
+    pub fn new(config: LintConfig) -> Self {
+        Self {
+            config,
+            lints: bytecode_lints(),
+        }
+    }
+}
+
+impl FunctionTargetProcessor for BytecodeLintProcessor {
+    fn process(
+        &self,
+        _targets: &mut FunctionTargetsHolder,
+        func_env: &FunctionEnv,
+        data: FunctionData,
+        _scc_opt: Option<&[FunctionEnv]>,
+    ) -> FunctionData {
+        if func_env.is_native() || !func_env.module_env.is_target() || func_env.is_test_only() {
+            return data;
+        }
+        let target = FunctionTarget::new(func_env, &data);
+        for lint in &self.lints {
+            if let Some(reporter) =
+                LintReporter::new(&self.config, lint.name(), lint.default_level(), func_env)
+            {
+                lint.check(&reporter, &target)
+            }
+        }
+        data
+    }
+
+    fn name(&self) -> String {
+        "bytecode_lints".to_string()
+    }
+}
+
+/// Returns true if the lint is suppressed by a `#[lint::skip]` attribute on the function or
+/// its module.
+fn is_skipped(fun: &FunctionEnv, name: &str) -> bool {
+    let pool = fun.symbol_pool();
+    let skips = |attrs: &[Attribute]| {
+        attrs.iter().any(|attr| match attr {
+            Attribute::Apply(_, attr_name, args)
+                if pool.string(*attr_name).as_str() == LINT_SKIP_ATTRIBUTE =>
+            {
+                args.is_empty()
+                    || args
+                        .iter()
+                        .any(|arg| pool.string(arg.name()).as_str() == name)
+            },
+            _ => false,
+        })
+    };
+    skips(fun.get_attributes()) || skips(fun.module_env.get_attributes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint_level_from_str() {
+        assert_eq!("allow".parse::<LintLevel>().unwrap(), LintLevel::Allow);
+        assert_eq!("deny".parse::<LintLevel>().unwrap(), LintLevel::Deny);
+        assert!("error".parse::<LintLevel>().is_err());
+    }
+
+    #[test]
+    fn test_lint_config() {
+        let mut config = LintConfig::default();
+        config.set_level("needless_copy", LintLevel::Deny).unwrap();
+        assert!(config.set_level("no_such_lint", LintLevel::Deny).is_err());
+        assert_eq!(
+            config.level("needless_copy", LintLevel::Warn),
+            LintLevel::Deny
+        );
+        assert_eq!(
+            config.level("needless_deref", LintLevel::Warn),
+            LintLevel::Warn
+        );
+    }
+
+    #[test]
+    fn test_lint_names_are_unique() {
+        let mut names = all_lints()
+            .into_iter()
+            .map(|(name, _, _)| name)
+            .collect::<Vec<_>>();
+        let count = names.len();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), count);
+    }
+}
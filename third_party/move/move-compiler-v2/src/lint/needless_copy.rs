@@ -0,0 +1,48 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Lint which flags explicit `copy` of a local which is not used afterwards, and can therefore be
+//! moved instead. This relies on the live variable annotation of the function target.
+
+use crate::{
+    lint::{BytecodeLint, LintReporter},
+    pipeline::livevar_analysis_processor::LiveVarAnnotation,
+};
+use move_stackless_bytecode::{
+    function_target::FunctionTarget,
+    stackless_bytecode::{AssignKind, Bytecode},
+};
+
+pub struct NeedlessCopy;
+
+impl BytecodeLint for NeedlessCopy {
+    fn name(&self) -> &'static str {
+        "needless_copy"
+    }
+
+    fn description(&self) -> &'static str {
+        "explicit `copy` of a local which is not used afterwards"
+    }
+
+    fn check(&self, reporter: &LintReporter, target: &FunctionTarget) {
+        let Some(annotation) = target.get_annotations().get::<LiveVarAnnotation>() else {
+            return;
+        };
+        for (offset, bc) in target.get_bytecode().iter().enumerate() {
+            if let Bytecode::Assign(attr_id, _, src, AssignKind::Copy) = bc {
+                let live_after = annotation
+                    .get_live_var_info_at(offset as u16)
+                    .map_or(true, |info| info.after.contains_key(src));
+                if !live_after {
+                    reporter.report(
+                        &target.get_bytecode_loc(*attr_id),
+                        &format!(
+                            "{} is not used after this `copy`, so it can be moved instead",
+                            target.get_local_name_for_error_message(*src)
+                        ),
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Lint which flags needless dereferences: `*&e` and `*&mut e` are just `e`, and `&*r` is just
+//! `r`, except for a mutable `r` which is frozen.
+
+use crate::lint::{ExpLint, LintReporter};
+use move_model::{
+    ast::{Exp, ExpData, Operation},
+    model::{FunctionEnv, GlobalEnv, NodeId},
+};
+
+pub struct NeedlessDeref;
+
+impl ExpLint for NeedlessDeref {
+    fn name(&self) -> &'static str {
+        "needless_deref"
+    }
+
+    fn description(&self) -> &'static str {
+        "dereference of a borrow, or borrow of a dereference"
+    }
+
+    fn check(&self, reporter: &LintReporter, _fun: &FunctionEnv, body: &Exp) {
+        let env = reporter.env();
+        body.visit_pre_order(&mut |e| {
+            if let ExpData::Call(id, outer, args) = e {
+                let msg = match (outer, args.first().map(|arg| arg.as_ref())) {
+                    (Operation::Deref, Some(ExpData::Call(inner, Operation::Borrow(_), _)))
+                        if is_written(env, id, inner) =>
+                    {
+                        Some("needless dereference of a borrow: `*&e` can be written as `e`")
+                    },
+                    (Operation::Borrow(_), Some(ExpData::Call(inner, Operation::Deref, _)))
+                        if is_written(env, id, inner) =>
+                    {
+                        Some("needless borrow of a dereference: `&*e` can be written as `e`")
+                    },
+                    _ => None,
+                };
+                if let Some(msg) = msg {
+                    reporter.report(&env.get_node_loc(*id), msg);
+                }
+            }
+            true
+        });
+    }
+}
+
+/// Returns true if both the outer and the inner operation are written in the source, as opposed
+/// to being introduced by the compiler, in which case they share the same location.
+fn is_written(env: &GlobalEnv, outer: &NodeId, inner: &NodeId) -> bool {
+    let (outer, inner) = (env.get_node_loc(*outer), env.get_node_loc(*inner));
+    outer.file_id() == inner.file_id() && outer.span().start() < inner.span().start()
+}
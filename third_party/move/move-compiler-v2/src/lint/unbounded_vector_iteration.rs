@@ -0,0 +1,68 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Lint which flags loops in entry functions which are bounded by the length of a vector passed
+//! in as an argument. The caller controls the length of such a vector, and with it the gas
+//! consumed by the transaction, which can be abused to exhaust the gas of a sponsored transaction
+//! or to make the function fail on realistic inputs.
+
+use crate::lint::{ExpLint, LintReporter};
+use move_model::{
+    ast::{Exp, ExpData, Operation},
+    model::FunctionEnv,
+};
+use std::collections::BTreeSet;
+
+const VECTOR_LENGTH: &str = "vector::length";
+
+pub struct UnboundedVectorIteration;
+
+impl ExpLint for UnboundedVectorIteration {
+    fn name(&self) -> &'static str {
+        "unbounded_vector_iteration"
+    }
+
+    fn description(&self) -> &'static str {
+        "loop in an entry function bounded by the length of a vector argument"
+    }
+
+    fn check(&self, reporter: &LintReporter, fun: &FunctionEnv, body: &Exp) {
+        if !fun.is_entry() {
+            return;
+        }
+        let env = reporter.env();
+        let param_count = fun.get_parameter_count();
+        let param_names: BTreeSet<_> = fun
+            .get_parameters_ref()
+            .iter()
+            .map(|param| param.0)
+            .collect();
+        let uses_param = |e: &Exp| {
+            e.any(&mut |e| match e {
+                ExpData::Temporary(_, idx) => *idx < param_count,
+                ExpData::LocalVar(_, name) => param_names.contains(name),
+                _ => false,
+            })
+        };
+        body.visit_pre_order(&mut |e| {
+            if let ExpData::Loop(id, loop_body) = e {
+                let bounded_by_param = loop_body.any(&mut |e| match e {
+                    ExpData::Call(_, Operation::MoveFunction(mid, fid), args) => {
+                        env.get_function(mid.qualified(*fid))
+                            .is_well_known(VECTOR_LENGTH)
+                            && args.iter().any(uses_param)
+                    },
+                    _ => false,
+                });
+                if bounded_by_param {
+                    reporter.report(
+                        &env.get_node_loc(*id),
+                        "loop over a vector argument of an entry function; the number of \
+                         iterations, and with it the gas cost, is controlled by the caller",
+                    );
+                }
+            }
+            true
+        });
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Lint which flags error-prone arithmetic in fee computations, which are recognized by
+//! mentioning a function, local, or field whose name contains `fee`. Two patterns are flagged:
+//!
+//! * `a / b * c`: the division truncates before the multiplication, which loses precision and
+//!   can round a fee down to zero.
+//! * `a * b / c` on types narrower than `u128`: the intermediate product can overflow and abort,
+//!   although the final result fits. The operands should be widened to `u128` first.
+
+use crate::lint::{ExpLint, LintReporter};
+use move_model::{
+    ast::{Exp, ExpData, Operation},
+    model::{FunctionEnv, GlobalEnv},
+    ty::{PrimitiveType, Type},
+};
+
+const FEE: &str = "fee";
+
+pub struct UncheckedFeeArithmetic;
+
+impl ExpLint for UncheckedFeeArithmetic {
+    fn name(&self) -> &'static str {
+        "unchecked_fee_arithmetic"
+    }
+
+    fn description(&self) -> &'static str {
+        "precision loss or intermediate overflow in fee arithmetic"
+    }
+
+    fn check(&self, reporter: &LintReporter, fun: &FunctionEnv, body: &Exp) {
+        let env = reporter.env();
+        let in_fee_function = fun.get_name_str().to_lowercase().contains(FEE);
+        body.visit_pre_order(&mut |e| {
+            let ExpData::Call(id, op, args) = e else {
+                return true;
+            };
+            let msg = match (op, args.first().map(|arg| arg.as_ref())) {
+                (Operation::Mul, _) if args.iter().any(|arg| is_op(arg, &Operation::Div)) => {
+                    "division before multiplication truncates the intermediate result; \
+                     multiply first to avoid losing precision"
+                },
+                (Operation::Div, Some(ExpData::Call(mul, Operation::Mul, _)))
+                    if is_narrow_integer(&env.get_node_type(*mul)) =>
+                {
+                    "the intermediate product can overflow although the result fits; \
+                     widen the operands to `u128` before multiplying"
+                },
+                _ => return true,
+            };
+            if in_fee_function || mentions_fee(env, fun, e) {
+                reporter.report(&env.get_node_loc(*id), msg);
+            }
+            true
+        });
+    }
+}
+
+fn is_op(e: &Exp, op: &Operation) -> bool {
+    matches!(e.as_ref(), ExpData::Call(_, inner, _) if inner == op)
+}
+
+fn is_narrow_integer(ty: &Type) -> bool {
+    matches!(
+        ty,
+        Type::Primitive(
+            PrimitiveType::U8 | PrimitiveType::U16 | PrimitiveType::U32 | PrimitiveType::U64
+        )
+    )
+}
+
+/// Returns true if the expression refers to a local, parameter, field or function whose name
+/// contains `fee`.
+fn mentions_fee(env: &GlobalEnv, fun: &FunctionEnv, e: &ExpData) -> bool {
+    let is_fee = |name: String| name.to_lowercase().contains(FEE);
+    e.any(&mut |e| match e {
+        ExpData::LocalVar(_, sym) => is_fee(sym.display(env.symbol_pool()).to_string()),
+        ExpData::Temporary(_, idx) => fun.get_parameters_ref().get(*idx).map_or(false, |param| {
+            is_fee(param.0.display(env.symbol_pool()).to_string())
+        }),
+        ExpData::Call(_, Operation::Select(mid, sid, fid), _) => {
+            let struct_env = env.get_module(*mid).into_struct(*sid);
+            let field_name = struct_env.get_field(*fid).get_name();
+            is_fee(field_name.display(env.symbol_pool()).to_string())
+        },
+        ExpData::Call(_, Operation::MoveFunction(mid, fid), _) => {
+            is_fee(env.get_function(mid.qualified(*fid)).get_name_str())
+        },
+        _ => false,
+    })
+}
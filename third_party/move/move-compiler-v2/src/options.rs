@@ -2,7 +2,10 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::experiments::{DefaultValue, EXPERIMENTS};
+use crate::{
+    experiments::{DefaultValue, EXPERIMENTS},
+    lint::LintConfig,
+};
 use clap::Parser;
 use codespan_reporting::diagnostic::Severity;
 use itertools::Itertools;
@@ -81,6 +84,9 @@ pub struct Options {
     /// Whether to compile #[test] and #[test_only] code
     #[clap(skip)]
     pub compile_test_code: bool,
+    /// The lint levels to use if the `lint-checks` experiment is on.
+    #[clap(skip)]
+    pub lint_config: LintConfig,
 }
 
 impl Default for Options {
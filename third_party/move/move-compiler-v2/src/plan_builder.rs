@@ -159,13 +159,18 @@ fn build_test_info(
     }
 
     let test_annotation_params = parse_test_attribute(env, test_attribute, 0);
+    let is_fuzz_test = is_fuzz_test_attribute(env, test_attribute);
 
     let mut arguments = Vec::new();
+    let mut fuzz_arguments = Vec::new();
     for param in function.get_parameters_ref() {
         let Parameter(var, _ty, var_loc) = &param;
 
         match test_annotation_params.get(var) {
+            Some(value) if is_fuzz_test => fuzz_arguments.push(Some(value.clone())),
             Some(value) => arguments.push(value.clone()),
+            // Unassigned parameters of fuzz tests are generated by the test runner
+            None if is_fuzz_test => fuzz_arguments.push(None),
             None => {
                 let missing_param_msg = "Missing test parameter assignment in test. Expected a \
                                          parameter to be assigned in this attribute";
@@ -190,9 +195,23 @@ fn build_test_info(
         test_name: fn_name_str.to_string(),
         arguments,
         expected_failure,
+        fuzz_arguments: is_fuzz_test.then_some(fuzz_arguments),
     })
 }
 
+/// Returns true if the test attribute is of the form `#[test(fuzz, ...)]`.
+fn is_fuzz_test_attribute(env: &GlobalEnv, test_attribute: &Attribute) -> bool {
+    match test_attribute {
+        Attribute::Apply(_, _, attrs) => attrs.iter().any(|attr| match attr {
+            Attribute::Apply(_, sym, args) => {
+                args.is_empty() && env.symbol_pool().string(*sym).as_str() == TestingAttribute::FUZZ
+            },
+            Attribute::Assign(..) => false,
+        }),
+        Attribute::Assign(..) => false,
+    }
+}
+
 //***************************************************************************
 // Attribute parsers
 //***************************************************************************
@@ -203,6 +222,14 @@ fn parse_test_attribute(
     depth: usize,
 ) -> BTreeMap<Symbol, MoveValue> {
     match test_attribute {
+        // `fuzz` marks the test as a fuzz test, see `is_fuzz_test_attribute`
+        Attribute::Apply(_, sym, args)
+            if depth == 1
+                && args.is_empty()
+                && env.symbol_pool().string(*sym).as_str() == TestingAttribute::FUZZ =>
+        {
+            BTreeMap::new()
+        },
         Attribute::Apply(id, _, _) if depth > 0 => {
             let aloc = env.get_node_loc(*id);
             env.error(&aloc, "Unexpected nested attribute in test declaration");
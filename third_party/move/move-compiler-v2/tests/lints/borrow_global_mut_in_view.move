@@ -0,0 +1,25 @@
+module 0x42::m {
+    struct R has key { v: u64 }
+
+    #[view]
+    public fun get_mut(addr: address): u64 acquires R {
+        let r = borrow_global_mut<R>(addr);
+        r.v
+    }
+
+    #[view]
+    public fun get(addr: address): u64 acquires R {
+        borrow_global<R>(addr).v
+    }
+
+    public fun set(addr: address, v: u64) acquires R {
+        borrow_global_mut<R>(addr).v = v
+    }
+
+    #[view]
+    #[lint::skip(borrow_global_mut_in_view)]
+    public fun get_mut_skipped(addr: address): u64 acquires R {
+        let r = borrow_global_mut<R>(addr);
+        r.v
+    }
+}
@@ -0,0 +1,32 @@
+module 0x1::account {
+    struct SignerCapability has drop, store { account: address }
+
+    public fun create_signer_capability(account: address): SignerCapability {
+        SignerCapability { account }
+    }
+
+    public(friend) fun create_signer_capability_internal(account: address): SignerCapability {
+        SignerCapability { account }
+    }
+}
+
+module 0x42::m {
+    struct Capability has drop, store { account: address }
+
+    public fun leak(s: signer): signer {
+        s
+    }
+
+    fun keep(s: signer): signer {
+        s
+    }
+
+    public fun create_capability(account: address): Capability {
+        Capability { account }
+    }
+
+    #[lint::skip(capability_leak)]
+    public fun leak_skipped(s: signer): signer {
+        s
+    }
+}
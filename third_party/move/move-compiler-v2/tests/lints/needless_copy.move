@@ -0,0 +1,24 @@
+module 0x42::m {
+    use std::vector;
+
+    fun copied(v: vector<u64>): u64 {
+        let w = copy v;
+        vector::length(&w)
+    }
+
+    fun copied_and_used(v: vector<u64>): u64 {
+        let w = copy v;
+        vector::length(&w) + vector::length(&v)
+    }
+
+    fun moved(v: vector<u64>): u64 {
+        let w = move v;
+        vector::length(&w)
+    }
+
+    #[lint::skip(needless_copy)]
+    fun copied_skipped(v: vector<u64>): u64 {
+        let w = copy v;
+        vector::length(&w)
+    }
+}
@@ -0,0 +1,23 @@
+module 0x42::m {
+    fun deref_borrow(x: u64): u64 {
+        *&x
+    }
+
+    fun borrow_deref(r: &u64): u64 {
+        let r = &*r;
+        *r
+    }
+
+    fun deref(r: &u64): u64 {
+        *r
+    }
+
+    fun borrow(x: &u64): &u64 {
+        x
+    }
+
+    #[lint::skip(needless_deref)]
+    fun deref_borrow_skipped(x: u64): u64 {
+        *&x
+    }
+}
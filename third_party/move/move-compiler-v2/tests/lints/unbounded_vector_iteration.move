@@ -0,0 +1,29 @@
+module 0x42::m {
+    use std::vector;
+
+    public entry fun iterate(v: vector<u64>) {
+        let i = 0;
+        while (i < vector::length(&v)) { i = i + 1 };
+    }
+
+    public entry fun iterate_bounded() {
+        let v = vector[1, 2, 3];
+        let i = 0;
+        while (i < vector::length(&v)) { i = i + 1 };
+    }
+
+    public fun sum(v: &vector<u64>): u64 {
+        let (i, s) = (0, 0);
+        while (i < vector::length(v)) {
+            s = s + *vector::borrow(v, i);
+            i = i + 1
+        };
+        s
+    }
+
+    #[lint::skip(unbounded_vector_iteration)]
+    public entry fun iterate_skipped(v: vector<u64>) {
+        let i = 0;
+        while (i < vector::length(&v)) { i = i + 1 };
+    }
+}
@@ -0,0 +1,22 @@
+module 0x42::m {
+    fun fee_amount(amount: u64, bps: u64): u64 {
+        amount / 10000 * bps
+    }
+
+    fun charge(amount: u64, fee_bps: u64): u64 {
+        amount * fee_bps / 10000
+    }
+
+    fun fee_amount_wide(amount: u64, bps: u64): u64 {
+        (((amount as u128) * (bps as u128) / 10000) as u64)
+    }
+
+    fun scale(x: u64, y: u64): u64 {
+        x / 100 * y
+    }
+
+    #[lint::skip(unchecked_fee_arithmetic)]
+    fun fee_amount_skipped(amount: u64, bps: u64): u64 {
+        amount / 10000 * bps
+    }
+}
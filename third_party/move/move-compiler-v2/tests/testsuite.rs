@@ -462,6 +462,23 @@ const TEST_CONFIGS: Lazy<BTreeMap<&str, TestConfig>> = Lazy::new(|| {
             dump_bytecode: DumpLevel::AllStages,
             dump_bytecode_filter: None,
         },
+        // Lints, which only report diagnostics
+        TestConfig {
+            name: "lints",
+            runner: |p| run_test(p, get_config_by_name("lints")),
+            include: vec!["/lints/"],
+            exclude: vec![],
+            exp_suffix: None,
+            options: opts
+                .clone()
+                .set_experiment(Experiment::LINT_CHECKS, true)
+                // Lint tests use attributes of the Aptos dialect, like `#[view]`
+                .set_skip_attribute_checks(true),
+            stop_after: StopAfter::BytecodePipeline(Some("bytecode_lints")),
+            dump_ast: DumpLevel::None,
+            dump_bytecode: DumpLevel::None,
+            dump_bytecode_filter: None,
+        },
         // -- File Format Generation
         // Test without bytecode optimizations enabled
         TestConfig {
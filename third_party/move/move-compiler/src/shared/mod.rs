@@ -754,6 +754,7 @@ pub mod known_attributes {
         pub const ARITHMETIC_ERROR_NAME: &'static str = "arithmetic_error";
         pub const ERROR_LOCATION: &'static str = "location";
         pub const EXPECTED_FAILURE: &'static str = "expected_failure";
+        pub const FUZZ: &'static str = "fuzz";
         pub const MAJOR_STATUS_NAME: &'static str = "major_status";
         pub const MINOR_STATUS_NAME: &'static str = "minor_status";
         pub const OUT_OF_GAS_NAME: &'static str = "out_of_gas";
@@ -36,6 +36,9 @@ pub struct TestCase {
     pub test_name: TestName,
     pub arguments: Vec<MoveValue>,
     pub expected_failure: Option<ExpectedFailure>,
+    /// For `#[test(fuzz)]` tests, the value of each parameter if it is assigned in the attribute.
+    /// The test runner generates random values for the parameters which are not assigned.
+    pub fuzz_arguments: Option<Vec<Option<MoveValue>>>,
 }
 
 #[derive(Debug, Clone)]
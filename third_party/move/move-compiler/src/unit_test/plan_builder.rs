@@ -158,10 +158,15 @@ fn build_test_info<'func>(
     }
 
     let test_annotation_params = parse_test_attribute(context, test_attribute, 0);
+    let is_fuzz_test = is_fuzz_test_attribute(test_attribute);
     let mut arguments = Vec::new();
+    let mut fuzz_arguments = Vec::new();
     for (var, _) in &function.signature.parameters {
         match test_annotation_params.get(&var.value()) {
+            Some(value) if is_fuzz_test => fuzz_arguments.push(Some(value.clone())),
             Some(value) => arguments.push(value.clone()),
+            // Unassigned parameters of fuzz tests are generated by the test runner
+            None if is_fuzz_test => fuzz_arguments.push(None),
             None => {
                 let missing_param_msg = "Missing test parameter assignment in test. Expected a \
                                          parameter to be assigned in this attribute";
@@ -184,9 +189,24 @@ fn build_test_info<'func>(
         test_name: fn_name.to_string(),
         arguments,
         expected_failure,
+        fuzz_arguments: is_fuzz_test.then_some(fuzz_arguments),
     })
 }
 
+/// Returns true if the test attribute is of the form `#[test(fuzz, ...)]`.
+fn is_fuzz_test_attribute(sp!(_, test_attribute): &E::Attribute) -> bool {
+    use E::Attribute_ as EA;
+    match test_attribute {
+        EA::Parameterized(_, attributes) => {
+            attributes.iter().any(|(_, _, sp!(_, attr))| match attr {
+                EA::Name(nm) => nm.value.as_str() == TestingAttribute::FUZZ,
+                _ => false,
+            })
+        },
+        _ => false,
+    }
+}
+
 //***************************************************************************
 // Attribute parsers
 //***************************************************************************
@@ -199,6 +219,10 @@ fn parse_test_attribute(
     use E::Attribute_ as EA;
 
     match test_attribute {
+        // `fuzz` marks the test as a fuzz test, see `is_fuzz_test_attribute`
+        EA::Name(nm) if depth == 1 && nm.value.as_str() == TestingAttribute::FUZZ => {
+            BTreeMap::new()
+        },
         EA::Name(_) | EA::Parameterized(_, _) if depth > 0 => {
             context.env.add_diag(diag!(
                 Attributes::InvalidTest,
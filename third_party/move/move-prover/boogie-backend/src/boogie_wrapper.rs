@@ -10,6 +10,9 @@ use crate::{
     boogie_helpers::{boogie_inst_suffix, boogie_struct_name},
     options::{BoogieOptions, VectorTheory},
     prover_task_runner::{ProverTaskRunner, RunBoogieWithSeeds},
+    verification_cache::{
+        options_fingerprint, verification_keys, verified_procedures, VerificationCache,
+    },
 };
 use anyhow::anyhow;
 use codespan::{ByteIndex, ColumnIndex, LineIndex, Location, Span};
@@ -218,6 +221,85 @@ impl<'env> BoogieWrapper<'env> {
 
     /// Calls boogie and analyzes output.
     pub fn call_boogie_and_verify_output(&self, boogie_file: &str) -> anyhow::Result<()> {
+        if let Some(cache_dir) = &self.options.verification_cache_dir {
+            return self.call_boogie_and_verify_output_cached(boogie_file, cache_dir);
+        }
+        self.verify_output(boogie_file).map(|_| ())
+    }
+
+    /// Like `call_boogie_and_verify_output`, but only verifies the procedures which are not in
+    /// the verification cache, and adds those which verified to it.
+    fn call_boogie_and_verify_output_cached(
+        &self,
+        boogie_file: &str,
+        cache_dir: &str,
+    ) -> anyhow::Result<()> {
+        let cache = VerificationCache::new(cache_dir);
+        // The options fingerprint excludes the path of the Boogie executable and the file to
+        // verify, so that the cache can be shared between machines. Instead, it includes the
+        // versions of the tools.
+        let command = self.options.get_boogie_command(boogie_file)?;
+        let fingerprint = format!(
+            "{}\n{}",
+            self.options.get_tool_versions()?,
+            options_fingerprint(&command[1..command.len() - 1])
+        );
+        let keys = verification_keys(&fs::read_to_string(boogie_file)?, &fingerprint);
+        let (cached, pending): (Vec<_>, Vec<_>) = keys
+            .iter()
+            .partition(|(_, key)| cache.is_verified(key.as_str()));
+        info!(
+            "{} verification conditions cached, {} to verify",
+            cached.len(),
+            pending.len()
+        );
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let mut options = self.options.clone();
+        if !cached.is_empty() {
+            options
+                .boogie_flags
+                .extend(pending.iter().map(|(name, _)| format!("-proc:{}", name)));
+        }
+        // Let Boogie report the outcome of each procedure, so that the ones which verified can be
+        // cached even if others fail. With multiple instances, all of them would write to the
+        // same file, so only a run without errors is cached then.
+        let xml_file = format!("{}.xml", boogie_file);
+        let per_procedure = options.num_instances == 1;
+        if per_procedure {
+            fs::remove_file(&xml_file).unwrap_or_default();
+            options.boogie_flags.push(format!("-xml:{}", xml_file));
+        }
+        let wrapper = BoogieWrapper {
+            options: &options,
+            ..*self
+        };
+        let no_errors = wrapper.verify_output(boogie_file)?;
+        let verified = if no_errors || !per_procedure {
+            BTreeSet::new()
+        } else {
+            // A procedure which verified but whose inconsistency check did not fail is
+            // inconsistent, so the results of the inconsistency checks are never cached from a
+            // failed run. The check is verified again with the next run.
+            verified_procedures(&fs::read_to_string(&xml_file).unwrap_or_default())
+                .into_iter()
+                .filter(|name| !name.contains("$verify_inconsistency"))
+                .collect()
+        };
+        if per_procedure && !self.options.keep_artifacts {
+            fs::remove_file(&xml_file).unwrap_or_default();
+        }
+        for (name, key) in pending {
+            if no_errors || verified.contains(name) {
+                cache.record_verified(key, name)?
+            }
+        }
+        Ok(())
+    }
+
+    /// Calls boogie, reports the errors found, and returns true if there were none.
+    fn verify_output(&self, boogie_file: &str) -> anyhow::Result<bool> {
         let BoogieOutput { errors, all_output } = self.call_boogie(boogie_file)?;
         let boogie_log_file = self.options.get_boogie_log_file(boogie_file);
         let log_file_existed = std::path::Path::new(&boogie_log_file).exists();
@@ -232,7 +314,7 @@ impl<'env> BoogieWrapper<'env> {
             std::fs::remove_file(boogie_log_file).unwrap_or_default();
         }
 
-        Ok(())
+        Ok(errors.is_empty())
     }
 
     /// Helper to add a boogie error as a codespan Diagnostic.
@@ -58,6 +58,7 @@ pub mod bytecode_translator;
 pub mod options;
 mod prover_task_runner;
 mod spec_translator;
+pub mod verification_cache;
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
 struct TypeInfo {
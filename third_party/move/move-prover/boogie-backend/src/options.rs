@@ -131,6 +131,9 @@ pub struct BoogieOptions {
     pub global_timeout_overwrite: bool,
     /// Whether Boogie output and log should be saved.
     pub keep_artifacts: bool,
+    /// Directory of the verification result cache. If set, verification conditions which
+    /// verified before with the same dependencies and options are not verified again.
+    pub verification_cache_dir: Option<String>,
     /// Eager threshold for quantifier instantiation.
     pub eager_threshold: usize,
     /// Lazy threshold for quantifier instantiation.
@@ -181,6 +184,7 @@ impl Default for BoogieOptions {
             vc_timeout: 80,
             global_timeout_overwrite: true,
             keep_artifacts: false,
+            verification_cache_dir: None,
             eager_threshold: 100,
             lazy_threshold: 100,
             stable_test_output: false,
@@ -300,17 +304,10 @@ impl BoogieOptions {
     /// Checks whether the expected tool versions are installed in the environment.
     pub fn check_tool_versions(&self) -> anyhow::Result<()> {
         if !self.boogie_exe.is_empty() {
-            // On Mac, version arg is `/version`, not `-version`
-            let version_arg = if cfg!(target_os = "macos") {
-                &["/version"]
-            } else {
-                &["-version"]
-            };
-
             let version = Self::get_version(
                 "boogie",
                 &self.boogie_exe,
-                version_arg,
+                Self::boogie_version_arg(),
                 r"version ([0-9.]*)",
             )?;
             Self::check_version_is_compatible(
@@ -338,18 +335,46 @@ impl BoogieOptions {
         Ok(())
     }
 
-    fn get_version(tool: &str, prog: &str, args: &[&str], regex: &str) -> anyhow::Result<String> {
-        let out = match Command::new(prog).args(args).output() {
-            Ok(out) => String::from_utf8_lossy(&out.stdout).to_string(),
-            Err(msg) => {
-                return Err(anyhow!(
-                    "cannot execute `{}` to obtain version of `{}`: {}",
-                    prog,
-                    tool,
-                    msg.to_string()
-                ))
-            },
+    /// Returns the version outputs of the Boogie executable and the solver which are used for
+    /// verification, one per line.
+    pub fn get_tool_versions(&self) -> anyhow::Result<String> {
+        let boogie_exe = if self.use_exp_boogie {
+            read_env_var("EXP_BOOGIE_EXE")
+        } else {
+            self.boogie_exe.clone()
         };
+        let boogie = Self::get_version_output("boogie", &boogie_exe, Self::boogie_version_arg())?;
+        let solver = if self.use_cvc5 {
+            Self::get_version_output("cvc5", &self.cvc5_exe, &["--version"])?
+        } else {
+            Self::get_version_output("z3", &self.z3_exe, &["--version"])?
+        };
+        Ok(format!("{}\n{}", boogie.trim(), solver.trim()))
+    }
+
+    fn boogie_version_arg() -> &'static [&'static str] {
+        // On Mac, version arg is `/version`, not `-version`
+        if cfg!(target_os = "macos") {
+            &["/version"]
+        } else {
+            &["-version"]
+        }
+    }
+
+    fn get_version_output(tool: &str, prog: &str, args: &[&str]) -> anyhow::Result<String> {
+        match Command::new(prog).args(args).output() {
+            Ok(out) => Ok(String::from_utf8_lossy(&out.stdout).to_string()),
+            Err(msg) => Err(anyhow!(
+                "cannot execute `{}` to obtain version of `{}`: {}",
+                prog,
+                tool,
+                msg.to_string()
+            )),
+        }
+    }
+
+    fn get_version(tool: &str, prog: &str, args: &[&str], regex: &str) -> anyhow::Result<String> {
+        let out = Self::get_version_output(tool, prog, args)?;
         if let Some(cap) = Regex::new(regex).unwrap().captures(&out) {
             Ok(cap[1].to_string())
         } else {
@@ -0,0 +1,394 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A content-addressed cache of verification results.
+//!
+//! The generated Boogie program is split into its top-level declarations. Each verification
+//! procedure (`..$verify..`) is keyed by the hash of its own text, the text of all declarations
+//! it transitively depends on, and the options Boogie is called with. Procedures whose key is in
+//! the cache verified before and are skipped; only the remaining ones are passed to Boogie.
+//!
+//! Source locations embedded in the Boogie program (e.g. `$at(2,1032,1050)` in tracking
+//! attributes) are not part of the key, so that moving code within a file does not invalidate
+//! the cache. Only successful verification is cached: the locations are only used for reporting
+//! errors.
+//!
+//! The options fingerprint includes the versions of Boogie and the solver, but not their paths
+//! or the number of cores, so that a cache can be shared between machines. If some procedures
+//! fail, the ones which Boogie reports as verified in its XML output are still cached.
+
+use itertools::Itertools;
+use log::debug;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The version of the cache key format. Bump this to invalidate existing caches if the way
+/// keys are computed changes.
+const CACHE_FORMAT_VERSION: &str = "1";
+
+/// Keywords which start a top-level Boogie declaration when appearing at the start of a line.
+const DECLARATION_KEYWORDS: &[&str] = &[
+    "axiom",
+    "const",
+    "datatype",
+    "function",
+    "implementation",
+    "procedure",
+    "type",
+    "var",
+];
+
+/// Names which are not declared in a Boogie program.
+const BUILTIN_NAMES: &[&str] = &[
+    "bool", "int", "real", "true", "false", "old", "forall", "exists", "lambda", "if", "then",
+    "else", "returns", "requires", "ensures", "modifies", "free", "unique", "var", "const",
+];
+
+static IDENTIFIER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"[A-Za-z_$'#?^~.\\][A-Za-z0-9_$'#?^~.\\]*").unwrap());
+
+static ATTRIBUTE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{:[^}]*\}").unwrap());
+
+static STRING_LITERAL: Lazy<Regex> = Lazy::new(|| Regex::new(r#""[^"\n]*""#).unwrap());
+
+static LOCATION: Lazy<Regex> = Lazy::new(|| Regex::new(r"\(\d+,\d+,\d+\)").unwrap());
+
+/// Prefixes of Boogie arguments which do not influence verification results: the paths of the
+/// solvers and trace files, and the number of cores.
+const MACHINE_DEPENDENT_ARGUMENTS: &[&str] = &[
+    "-proverOpt:PROVER_PATH=",
+    "-proverOpt:O:trace_file_name=",
+    "-vcsCores:",
+];
+
+static XML_METHOD_NAME: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^name="([^"]*)""#).unwrap());
+
+static XML_OUTCOME: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"<conclusion [^>]*outcome="([^"]*)""#).unwrap());
+
+/// An on-disk cache of verification conditions which are known to verify.
+pub struct VerificationCache {
+    dir: PathBuf,
+}
+
+impl VerificationCache {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Returns true if the verification condition with the given key verified before.
+    pub fn is_verified(&self, key: &str) -> bool {
+        self.dir.join(key).is_file()
+    }
+
+    /// Records that the verification condition with the given key verified. The procedure name
+    /// is only stored for debugging purposes.
+    pub fn record_verified(&self, key: &str, procedure: &str) -> anyhow::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.dir.join(key), procedure)?;
+        Ok(())
+    }
+}
+
+/// A top-level declaration of a Boogie program.
+struct Declaration<'a> {
+    keyword: &'a str,
+    text: &'a str,
+    /// The names this declaration (possibly) declares. Over-approximating is safe, it only leads
+    /// to more declarations being part of the key of a verification condition.
+    declares: BTreeSet<&'a str>,
+    /// The names this declaration (possibly) uses.
+    uses: BTreeSet<&'a str>,
+}
+
+/// Computes the cache key of each verification procedure of the Boogie program. The
+/// `options_fingerprint` must capture all options which influence the verification result.
+pub fn verification_keys(boogie: &str, options_fingerprint: &str) -> BTreeMap<String, String> {
+    let decls = split_declarations(boogie);
+    let mut declared_by: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (idx, decl) in decls.iter().enumerate() {
+        for name in &decl.declares {
+            declared_by.entry(name).or_default().push(idx)
+        }
+    }
+    let axioms = decls
+        .iter()
+        .enumerate()
+        .filter(|(_, decl)| decl.keyword == "axiom")
+        .map(|(idx, _)| idx)
+        .collect_vec();
+
+    let mut keys = BTreeMap::new();
+    for (idx, decl) in decls.iter().enumerate() {
+        if decl.keyword != "procedure" {
+            continue;
+        }
+        let Some(name) = declared_name(decl.text, decl.keyword) else {
+            continue;
+        };
+        if !name.contains("$verify") {
+            continue;
+        }
+        let closure = dependency_closure(&decls, &declared_by, &axioms, idx);
+        let mut hasher = Sha256::new();
+        hasher.update(CACHE_FORMAT_VERSION);
+        hasher.update(options_fingerprint);
+        for dep in closure {
+            hasher.update([0u8]);
+            hasher.update(normalize(decls[dep].text));
+        }
+        let key = format!("{:x}", hasher.finalize());
+        debug!("verification cache key of `{}`: {}", name, key);
+        keys.insert(name.to_string(), key);
+    }
+    keys
+}
+
+/// Computes the fingerprint of the Boogie arguments for `verification_keys`, leaving out the
+/// arguments which differ between machines without influencing the verification results, so
+/// that the cache can be shared between them. The arguments must not include the Boogie
+/// executable and the file to verify.
+pub fn options_fingerprint(arguments: &[String]) -> String {
+    arguments
+        .iter()
+        .filter(|arg| {
+            !MACHINE_DEPENDENT_ARGUMENTS
+                .iter()
+                .any(|prefix| arg.starts_with(prefix))
+        })
+        .join(" ")
+}
+
+/// Returns the procedures which verified according to the XML output Boogie writes when called
+/// with `-xml:<file>`. Procedures which have no conclusion, e.g. because Boogie was interrupted,
+/// are not included.
+pub fn verified_procedures(xml: &str) -> BTreeSet<String> {
+    xml.split("<method ")
+        .skip(1)
+        .filter_map(|method| {
+            let name = XML_METHOD_NAME.captures(method)?.get(1)?.as_str();
+            let outcome = XML_OUTCOME.captures(method)?.get(1)?.as_str();
+            (outcome == "correct").then(|| name.to_string())
+        })
+        .collect()
+}
+
+/// Computes the indices of the declarations the given declaration transitively depends on,
+/// including itself. Axioms are added as soon as they constrain any of the declarations.
+fn dependency_closure(
+    decls: &[Declaration],
+    declared_by: &BTreeMap<&str, Vec<usize>>,
+    axioms: &[usize],
+    root: usize,
+) -> BTreeSet<usize> {
+    let mut closure = BTreeSet::new();
+    let mut names = BTreeSet::new();
+    let mut todo = vec![root];
+    loop {
+        while let Some(idx) = todo.pop() {
+            if !closure.insert(idx) {
+                continue;
+            }
+            names.extend(decls[idx].declares.iter().copied());
+            for name in &decls[idx].uses {
+                if let Some(deps) = declared_by.get(name) {
+                    todo.extend(deps.iter().filter(|dep| !closure.contains(*dep)))
+                }
+            }
+        }
+        todo.extend(
+            axioms.iter().filter(|axiom| {
+                !closure.contains(*axiom) && !decls[**axiom].uses.is_disjoint(&names)
+            }),
+        );
+        if todo.is_empty() {
+            return closure;
+        }
+    }
+}
+
+/// Splits a Boogie program into its top-level declarations. A declaration starts at a line
+/// beginning with a declaration keyword and extends to the start of the next declaration.
+/// Anything before the first declaration is ignored.
+fn split_declarations(boogie: &str) -> Vec<Declaration> {
+    let mut starts = vec![];
+    let mut offset = 0;
+    for line in boogie.split_inclusive('\n') {
+        if let Some(keyword) = DECLARATION_KEYWORDS.iter().find(|keyword| {
+            line.starts_with(*keyword)
+                && !line[keyword.len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_')
+        }) {
+            starts.push((offset, *keyword))
+        }
+        offset += line.len();
+    }
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, (start, keyword))| {
+            let end = starts.get(i + 1).map_or(boogie.len(), |(end, _)| *end);
+            let text = &boogie[*start..end];
+            let uses = identifiers(text).collect::<BTreeSet<_>>();
+            let declares = match *keyword {
+                "axiom" => BTreeSet::new(),
+                // Datatypes declare constructors and selectors besides the type
+                "datatype" | "var" | "const" => uses.clone(),
+                _ => declared_name(text, keyword).into_iter().collect(),
+            };
+            Declaration {
+                keyword,
+                text,
+                declares,
+                uses,
+            }
+        })
+        .collect()
+}
+
+/// Returns the name declared by a declaration of the form `<keyword> {:attr}* <name> ...`.
+fn declared_name<'a>(text: &'a str, keyword: &str) -> Option<&'a str> {
+    let mut rest = text[keyword.len()..].trim_start();
+    while rest.starts_with("{:") {
+        rest = rest[ATTRIBUTE.find(rest)?.end()..].trim_start();
+    }
+    IDENTIFIER
+        .find(rest)
+        .filter(|m| m.start() == 0)
+        .map(|m| m.as_str())
+}
+
+/// Returns the identifiers used in the text, excluding builtin names.
+fn identifiers(text: &str) -> impl Iterator<Item = &str> {
+    IDENTIFIER
+        .find_iter(text)
+        .map(|m| m.as_str())
+        .filter(|name| !BUILTIN_NAMES.contains(name))
+}
+
+/// Removes comments and source locations, which don't influence verification results.
+fn normalize(text: &str) -> String {
+    let text = text
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("//"))
+        .join("\n");
+    STRING_LITERAL
+        .replace_all(&text, |caps: &regex::Captures| {
+            LOCATION.replace_all(&caps[0], "()").into_owned()
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::BoogieOptions;
+
+    const PROGRAM: &str = r#"
+type {:datatype} Vec _;
+function {:inline} $IsValid'u8'(v: int): bool { v >= 0 && v <= 255 }
+function $f(x: int): int;
+axiom (forall x: int :: $f(x) > 0);
+function $g(x: int): int;
+axiom (forall x: int :: $g(x) > 1);
+// fun m::a [verification] at ./sources/m.move:3:5+10
+procedure {:timeLimit 40} $42_m_a$verify(x: int) returns ()
+{
+    assume {:print "$at(2,100,110)"} true;
+    assert {:msg "assert_failed(2,100,110): error"} $f(x) > 0;
+}
+procedure {:timeLimit 40} $42_m_b$verify(x: int) returns ()
+{
+    assert $g(x) > 0;
+}
+"#;
+
+    #[test]
+    fn test_keys_depend_on_dependencies_only() {
+        let keys = verification_keys(PROGRAM, "");
+        assert_eq!(keys.len(), 2);
+
+        // Changing an axiom of `$g` only affects `b`
+        let changed = verification_keys(&PROGRAM.replace("$g(x) > 1", "$g(x) > 2"), "");
+        assert_eq!(keys["$42_m_a$verify"], changed["$42_m_a$verify"]);
+        assert_ne!(keys["$42_m_b$verify"], changed["$42_m_b$verify"]);
+
+        // Moving code only changes source locations, which are not part of the keys
+        let moved = verification_keys(
+            &PROGRAM
+                .replace("(2,100,110)", "(2,200,210)")
+                .replace("m.move:3:5", "m.move:4:5"),
+            "",
+        );
+        assert_eq!(keys, moved);
+
+        // Options are part of all keys
+        let other_options = verification_keys(PROGRAM, "-loopUnroll:3");
+        assert_ne!(keys["$42_m_a$verify"], other_options["$42_m_a$verify"]);
+        assert_ne!(keys["$42_m_b$verify"], other_options["$42_m_b$verify"]);
+    }
+
+    #[test]
+    fn test_keys_ignore_tool_paths_and_cores() {
+        let keys = |options: &BoogieOptions| {
+            let command = options.get_boogie_command("output.bpl").unwrap();
+            verification_keys(
+                PROGRAM,
+                &options_fingerprint(&command[1..command.len() - 1]),
+            )
+        };
+        let options = BoogieOptions {
+            boogie_exe: "/usr/local/bin/boogie".to_string(),
+            z3_exe: "/usr/local/bin/z3".to_string(),
+            cvc5_exe: "/usr/local/bin/cvc5".to_string(),
+            proc_cores: 4,
+            ..BoogieOptions::default()
+        };
+        let other_machine = BoogieOptions {
+            boogie_exe: "/home/user/.local/bin/boogie".to_string(),
+            z3_exe: "/home/user/.local/bin/z3".to_string(),
+            cvc5_exe: "/home/user/.local/bin/cvc5".to_string(),
+            proc_cores: 16,
+            ..options.clone()
+        };
+        assert_eq!(keys(&options), keys(&other_machine));
+
+        let cvc5 = |options: &BoogieOptions| BoogieOptions {
+            use_cvc5: true,
+            ..options.clone()
+        };
+        assert_eq!(keys(&cvc5(&options)), keys(&cvc5(&other_machine)));
+
+        // The solver itself is part of the keys
+        assert_ne!(keys(&options), keys(&cvc5(&options)));
+    }
+
+    #[test]
+    fn test_verified_procedures() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<boogie version="3.0.1.0" commandLine="boogie -xml:out.xml out.bpl">
+  <file name="out.bpl">
+    <method name="$42_m_a$verify" startTime="2024-01-01T00:00:00.0000000Z">
+      <conclusion endTime="2024-01-01T00:00:01.0000000Z" duration="1" outcome="correct" />
+    </method>
+    <method name="$42_m_b$verify" startTime="2024-01-01T00:00:00.0000000Z">
+      <conclusion endTime="2024-01-01T00:00:01.0000000Z" duration="1" outcome="errors" />
+    </method>
+    <method name="$42_m_c$verify" startTime="2024-01-01T00:00:00.0000000Z">
+      <conclusion endTime="2024-01-01T00:00:40.0000000Z" duration="40" outcome="timeout" />
+    </method>
+    <method name="$42_m_d$verify" startTime="2024-01-01T00:00:00.0000000Z">
+"#;
+        assert_eq!(
+            verified_procedures(xml),
+            BTreeSet::from(["$42_m_a$verify".to_string()])
+        );
+    }
+}
@@ -245,6 +245,13 @@ impl Options {
                     .action(SetTrue)
                     .help("keeps intermediate artifacts of the backend around")
             )
+            .arg(
+                Arg::new("cache-dir")
+                    .long("cache-dir")
+                    .value_name("DIR")
+                    .help("directory of the verification result cache; verification conditions \
+                     which verified before and did not change are not verified again")
+            )
             .arg(
                 Arg::new("boogie-poly")
                     .long("boogie-poly")
@@ -742,6 +749,10 @@ impl Options {
         if matches.get_flag("keep") {
             options.backend.keep_artifacts = true;
         }
+        if matches.contains_id("cache-dir") {
+            options.backend.verification_cache_dir =
+                Some(matches.get_one::<String>("cache-dir").unwrap().to_string());
+        }
         if matches.get_flag("boogie-poly") {
             options.prover.boogie_poly = true;
         }
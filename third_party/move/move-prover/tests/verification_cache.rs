@@ -0,0 +1,124 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! End-to-end tests of the verification result cache. Like the test suite, these need Boogie and
+//! Z3 to be configured via `BOOGIE_EXE` and `Z3_EXE`, and are skipped otherwise.
+
+use codespan_reporting::term::termcolor::Buffer;
+use move_command_line_common::env::read_env_var;
+use move_prover::{cli::Options, run_move_prover};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::{collections::BTreeSet, fs, path::Path};
+use tempfile::TempDir;
+
+static XML_METHOD_NAME: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"<method name="\$42_m_(\w+)\$verify""#).unwrap());
+
+static PROCEDURE_NAME: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\$42_m_(\w+)\$verify$").unwrap());
+
+const SOURCE: &str = r#"
+module 0x42::m {
+    fun f(x: u64): u64 { x }
+    spec f { ensures result == x; }
+
+    fun g(x: u64): u64 { x }
+    spec g { ensures result == x + 1; }
+}
+"#;
+
+/// The outcome of a prover run.
+struct Run {
+    /// Whether verification succeeded.
+    success: bool,
+    /// The functions Boogie verified, or None if Boogie was not called.
+    verified_by_boogie: Option<BTreeSet<String>>,
+}
+
+/// Runs the prover on the given source, keeping the Boogie artifacts in `dir`.
+fn prove(dir: &Path, cache_dir: &Path, source: &str) -> Run {
+    let source_file = dir.join("m.move");
+    let boogie_file = dir.join("m.bpl");
+    let xml_file = dir.join("m.bpl.xml");
+    fs::write(&source_file, source).unwrap();
+    fs::remove_file(&xml_file).unwrap_or_default();
+    let args = vec![
+        "mvp_test".to_string(),
+        "--verbose=warn".to_string(),
+        "--keep".to_string(),
+        format!("--cache-dir={}", cache_dir.display()),
+        format!("--output={}", boogie_file.display()),
+        source_file.display().to_string(),
+    ];
+    let options = Options::create_from_args(&args).unwrap();
+    let mut error_writer = Buffer::no_color();
+    let success = run_move_prover(&mut error_writer, options).is_ok();
+    let verified_by_boogie = fs::read_to_string(&xml_file).ok().map(|xml| {
+        XML_METHOD_NAME
+            .captures_iter(&xml)
+            .map(|cap| cap[1].to_string())
+            .collect()
+    });
+    Run {
+        success,
+        verified_by_boogie,
+    }
+}
+
+/// Returns the names of the functions whose verification is cached, with duplicates if a
+/// function verified with different keys.
+fn cached(cache_dir: &Path) -> Vec<String> {
+    let mut names = fs::read_dir(cache_dir)
+        .unwrap()
+        .map(|entry| {
+            let procedure = fs::read_to_string(entry.unwrap().path()).unwrap();
+            PROCEDURE_NAME.captures(&procedure).unwrap()[1].to_string()
+        })
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+fn functions(names: &[&str]) -> Option<BTreeSet<String>> {
+    Some(names.iter().map(|name| name.to_string()).collect())
+}
+
+#[test]
+fn test_verification_cache() {
+    if read_env_var("BOOGIE_EXE").is_empty() || read_env_var("Z3_EXE").is_empty() {
+        eprintln!("Prover tools are not configured, skipping verification cache test");
+        return;
+    }
+    let dir = TempDir::new().unwrap();
+    let cache_dir = dir.path().join("cache");
+
+    // A partial failure caches the function which verified.
+    let run = prove(dir.path(), &cache_dir, SOURCE);
+    assert!(!run.success);
+    assert_eq!(run.verified_by_boogie, functions(&["f", "g"]));
+    assert_eq!(cached(&cache_dir), vec!["f"]);
+
+    // A cache hit: only the failing function is verified again.
+    let run = prove(dir.path(), &cache_dir, SOURCE);
+    assert!(!run.success);
+    assert_eq!(run.verified_by_boogie, functions(&["g"]));
+    assert_eq!(cached(&cache_dir), vec!["f"]);
+
+    // Changing the specification of `f` invalidates its entry.
+    let changed = SOURCE.replace("ensures result == x;", "ensures result >= x;");
+    let run = prove(dir.path(), &cache_dir, &changed);
+    assert!(!run.success);
+    assert_eq!(run.verified_by_boogie, functions(&["f", "g"]));
+    assert_eq!(cached(&cache_dir), vec!["f", "f"]);
+
+    // Fixing `g` only verifies `g`, after which everything is cached.
+    let fixed = changed.replace("ensures result == x + 1;", "ensures result == x;");
+    let run = prove(dir.path(), &cache_dir, &fixed);
+    assert!(run.success);
+    assert_eq!(run.verified_by_boogie, functions(&["g"]));
+    assert_eq!(cached(&cache_dir), vec!["f", "f", "g"]);
+
+    let run = prove(dir.path(), &cache_dir, &fixed);
+    assert!(run.success);
+    assert_eq!(run.verified_by_boogie, None);
+}
@@ -800,9 +800,11 @@ impl Interpreter {
             ty,
             addr,
         )?;
-        self.operand_stack.push(res.map_err(|err| {
+        let res = res.map_err(|err| {
             err.with_message(format!("Failed to borrow global resource from {:?}", addr))
-        })?)?;
+        })?;
+        gas_meter.charge_resource_access(addr, TypeWithLoader { ty, loader }, is_mut)?;
+        self.operand_stack.push(res)?;
         Ok(())
     }
 
@@ -845,6 +847,7 @@ impl Interpreter {
         let exists = gv.exists()?;
         gas_meter.charge_exists(is_generic, TypeWithLoader { ty, loader }, exists)?;
         self.check_access(loader, AccessKind::Reads, ty, addr)?;
+        gas_meter.charge_resource_access(addr, TypeWithLoader { ty, loader }, false)?;
         self.operand_stack.push(Value::bool(exists))?;
         Ok(())
     }
@@ -871,6 +874,7 @@ impl Interpreter {
                         Some(&resource),
                     )?;
                     self.check_access(loader, AccessKind::Writes, ty, addr)?;
+                    gas_meter.charge_resource_access(addr, TypeWithLoader { ty, loader }, true)?;
                     resource
                 },
                 Err(err) => {
@@ -909,6 +913,7 @@ impl Interpreter {
                     true,
                 )?;
                 self.check_access(loader, AccessKind::Writes, ty, addr)?;
+                gas_meter.charge_resource_access(addr, TypeWithLoader { ty, loader }, true)?;
                 Ok(())
             },
             Err((err, resource)) => {
@@ -2155,6 +2160,11 @@ impl Frame {
                     },
                     Bytecode::Ret => {
                         gas_meter.charge_simple_instr(S::Ret)?;
+                        gas_meter.charge_return_values(
+                            interpreter
+                                .operand_stack
+                                .last_n(self.function.return_tys().len())?,
+                        )?;
                         return Ok(ExitCode::Return);
                     },
                     Bytecode::BrTrue(offset) => {
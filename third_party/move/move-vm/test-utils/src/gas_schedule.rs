@@ -507,6 +507,22 @@ impl<'b> GasMeter for GasStatus<'b> {
         Ok(())
     }
 
+    fn charge_return_values(
+        &mut self,
+        _ret_vals: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        Ok(())
+    }
+
+    fn charge_resource_access(
+        &mut self,
+        _addr: AccountAddress,
+        _ty: impl TypeView,
+        _is_write: bool,
+    ) -> PartialVMResult<()> {
+        Ok(())
+    }
+
     fn charge_create_ty(&mut self, _num_nodes: NumTypeNodes) -> PartialVMResult<()> {
         Ok(())
     }
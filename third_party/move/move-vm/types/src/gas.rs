@@ -299,6 +299,25 @@ pub trait GasMeter {
         locals: impl Iterator<Item = impl ValueView> + Clone,
     ) -> PartialVMResult<()>;
 
+    /// Called when a function returns, with the values it returns.
+    ///
+    /// This is not used to charge gas, but lets gas meters wrapping others, such as profilers,
+    /// observe the results of calls.
+    fn charge_return_values(
+        &mut self,
+        ret_vals: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+    ) -> PartialVMResult<()>;
+
+    /// Called when a global storage operation succeeds, with the resource it accessed.
+    ///
+    /// Like `charge_return_values`, this is not used to charge gas.
+    fn charge_resource_access(
+        &mut self,
+        addr: AccountAddress,
+        ty: impl TypeView,
+        is_write: bool,
+    ) -> PartialVMResult<()>;
+
     fn charge_create_ty(&mut self, num_nodes: NumTypeNodes) -> PartialVMResult<()>;
 
     fn charge_dependency(
@@ -540,6 +559,22 @@ impl GasMeter for UnmeteredGasMeter {
         Ok(())
     }
 
+    fn charge_return_values(
+        &mut self,
+        _ret_vals: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        Ok(())
+    }
+
+    fn charge_resource_access(
+        &mut self,
+        _addr: AccountAddress,
+        _ty: impl TypeView,
+        _is_write: bool,
+    ) -> PartialVMResult<()> {
+        Ok(())
+    }
+
     fn charge_create_ty(&mut self, _num_nodes: NumTypeNodes) -> PartialVMResult<()> {
         Ok(())
     }
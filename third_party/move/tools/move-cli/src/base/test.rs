@@ -20,7 +20,9 @@ use move_package::{
     compilation::{build_plan::BuildPlan, compiled_package::build_and_report_v2_driver},
     BuildConfig,
 };
-use move_unit_test::UnitTestingConfig;
+use move_unit_test::{
+    fuzzer::DEFAULT_FUZZ_ITERATIONS, test_runner::TestGasMeterFactory, UnitTestingConfig,
+};
 use move_vm_runtime::tracing::{LOGGING_FILE_WRITER, TRACING_ENABLED};
 use move_vm_test_utils::gas_schedule::CostTable;
 // if unix
@@ -84,6 +86,37 @@ pub struct Test {
     /// Collect coverage information for later use with the various `move coverage` subcommands
     #[clap(long = "coverage")]
     pub compute_coverage: bool,
+    /// Number of times each `#[test(fuzz)]` test is run with random arguments
+    #[clap(
+        name = "fuzz_iterations",
+        long = "fuzz_iterations",
+        default_value_t = DEFAULT_FUZZ_ITERATIONS
+    )]
+    pub fuzz_iterations: u64,
+    /// Seed for generating the arguments of `#[test(fuzz)]` tests, to replay a failing test
+    #[clap(name = "fuzz_seed", long = "fuzz_seed")]
+    pub fuzz_seed: Option<u64>,
+    /// Compare the gas used by each test against the snapshot in this file, which is created if
+    /// it does not exist
+    #[clap(name = "gas_snapshot", long = "gas_snapshot")]
+    pub gas_snapshot: Option<PathBuf>,
+    /// Write the gas used by each test to the gas snapshot instead of comparing against it
+    #[clap(
+        name = "update_gas_snapshot",
+        long = "update_gas_snapshot",
+        requires = "gas_snapshot"
+    )]
+    pub update_gas_snapshot: bool,
+    /// The change of the gas used by a test, in percent, up to which it matches the gas snapshot
+    #[clap(
+        name = "gas_snapshot_threshold",
+        long = "gas_snapshot_threshold",
+        default_value_t = 0.0
+    )]
+    pub gas_snapshot_threshold: f64,
+    /// Only warn about gas snapshot mismatches instead of failing
+    #[clap(name = "gas_snapshot_warn_only", long = "gas_snapshot_warn_only")]
+    pub gas_snapshot_warn_only: bool,
 
     /// Use the EVM-based execution backend.
     /// Does not work with --stackless.
@@ -113,6 +146,12 @@ impl Test {
             check_stackless_vm,
             verbose_mode,
             compute_coverage,
+            fuzz_iterations,
+            fuzz_seed,
+            gas_snapshot,
+            update_gas_snapshot,
+            gas_snapshot_threshold,
+            gas_snapshot_warn_only,
             #[cfg(feature = "evm-backend")]
             evm,
         } = self;
@@ -126,6 +165,12 @@ impl Test {
             check_stackless_vm,
             verbose: verbose_mode,
             ignore_compile_warnings,
+            fuzz_iterations,
+            fuzz_seed,
+            gas_snapshot,
+            update_gas_snapshot,
+            gas_snapshot_threshold,
+            gas_snapshot_warn_only,
             #[cfg(feature = "evm-backend")]
             evm,
 
@@ -158,12 +203,37 @@ pub enum UnitTestResult {
 }
 
 pub fn run_move_unit_tests<W: Write + Send>(
+    pkg_path: &Path,
+    build_config: move_package::BuildConfig,
+    unit_test_config: UnitTestingConfig,
+    natives: Vec<NativeFunctionRecord>,
+    genesis: ChangeSet,
+    cost_table: Option<CostTable>,
+    compute_coverage: bool,
+    writer: &mut W,
+) -> Result<UnitTestResult> {
+    let gas_meter_factory = unit_test_config.cost_table_gas_meter_factory(cost_table);
+    run_move_unit_tests_with_gas_meter(
+        pkg_path,
+        build_config,
+        unit_test_config,
+        natives,
+        genesis,
+        gas_meter_factory,
+        compute_coverage,
+        writer,
+    )
+}
+
+/// Like `run_move_unit_tests`, but executes the tests with the gas meters created by
+/// `gas_meter_factory`.
+pub fn run_move_unit_tests_with_gas_meter<W: Write + Send>(
     pkg_path: &Path,
     mut build_config: move_package::BuildConfig,
     mut unit_test_config: UnitTestingConfig,
     natives: Vec<NativeFunctionRecord>,
     genesis: ChangeSet,
-    cost_table: Option<CostTable>,
+    gas_meter_factory: impl TestGasMeterFactory,
     compute_coverage: bool,
     writer: &mut W,
 ) -> Result<UnitTestResult> {
@@ -302,7 +372,13 @@ pub fn run_move_unit_tests<W: Write + Send>(
     // Run the tests. If any of the tests fail, then we don't produce a coverage report, so cleanup
     // the trace files.
     if !unit_test_config
-        .run_and_report_unit_tests(test_plan, Some(natives), Some(genesis), cost_table, writer)
+        .run_and_report_unit_tests_with_gas_meter(
+            test_plan,
+            Some(natives),
+            Some(genesis),
+            gas_meter_factory,
+            writer,
+        )
         .unwrap()
         .1
     {
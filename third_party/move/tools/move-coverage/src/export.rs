@@ -0,0 +1,529 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Exports source-level coverage in the LCOV and Cobertura XML formats, which are understood by
+//! CI dashboards and coverage services. The execution counts of bytecode instructions are mapped
+//! back to source lines and branches via the source maps of the modules.
+//!
+//! Branch coverage is derived from the execution counts of the two successors of each
+//! conditional branch instruction. It is exact unless a successor can also be reached through
+//! another jump, in which case it is an over-approximation capped by the count of the branch.
+
+#![forbid(unsafe_code)]
+
+use crate::coverage_map::ExecCoverageMap;
+use anyhow::{bail, Result};
+use codespan::Files;
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{Bytecode, CodeOffset, FunctionDefinitionIndex},
+    CompiledModule,
+};
+use move_bytecode_source_map::source_map::SourceMap;
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The coverage of a single source file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FileCoverage {
+    /// The name of the package the file belongs to
+    pub package_name: String,
+    /// The line of definition and execution count of each function, keyed by `module::function`
+    pub functions: BTreeMap<String, (u32, u64)>,
+    /// The execution count of each line containing code
+    pub lines: BTreeMap<u32, u64>,
+    /// The execution count of each outcome of a conditional branch, keyed by line, branch and
+    /// outcome. `None` if the branch itself was never executed.
+    pub branches: BTreeMap<(u32, u32, u32), Option<u64>>,
+}
+
+/// Source-level coverage of a set of files, possibly across multiple packages and test runs.
+#[derive(Clone, Debug, Default)]
+pub struct CoverageReport {
+    pub files: BTreeMap<PathBuf, FileCoverage>,
+}
+
+impl FileCoverage {
+    fn merge(&mut self, other: FileCoverage) {
+        for (name, (line, count)) in other.functions {
+            self.functions.entry(name).or_insert((line, 0)).1 += count;
+        }
+        for (line, count) in other.lines {
+            *self.lines.entry(line).or_insert(0) += count;
+        }
+        for (key, taken) in other.branches {
+            let entry = self.branches.entry(key).or_insert(None);
+            *entry = match (*entry, taken) {
+                (None, None) => None,
+                (c1, c2) => Some(c1.unwrap_or(0) + c2.unwrap_or(0)),
+            };
+        }
+    }
+
+    fn functions_hit(&self) -> usize {
+        self.functions
+            .values()
+            .filter(|(_, count)| *count > 0)
+            .count()
+    }
+
+    fn lines_hit(&self) -> usize {
+        self.lines.values().filter(|count| **count > 0).count()
+    }
+
+    fn branches_hit(&self) -> usize {
+        self.branches
+            .values()
+            .filter(|taken| matches!(taken, Some(count) if *count > 0))
+            .count()
+    }
+}
+
+impl CoverageReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the coverage of a module, whose source is at `source_path`, to the report. The path
+    /// is reported as given.
+    pub fn add_module(
+        &mut self,
+        package_name: &str,
+        module: &CompiledModule,
+        source_map: &SourceMap,
+        source_path: &Path,
+        coverage_map: &ExecCoverageMap,
+    ) -> Result<()> {
+        let file_contents = fs::read_to_string(source_path)?;
+        if !source_map.check(&file_contents) {
+            bail!(
+                "File contents of {} out of sync with source map",
+                source_path.display()
+            )
+        }
+        let mut files = Files::new();
+        let file_id = files.add(source_path.as_os_str().to_os_string(), file_contents);
+        let file_hash = source_map.definition_location.file_hash();
+        // Lines are reported 1-based
+        let line_of = |byte_index: u32| -> Result<u32> {
+            Ok(files.location(file_id, byte_index)?.line.0 + 1)
+        };
+
+        let module_id = module.self_id();
+        let module_map = coverage_map
+            .module_maps
+            .get(&(*module_id.address(), module_id.name().to_owned()));
+
+        let mut file_coverage = FileCoverage {
+            package_name: package_name.to_string(),
+            ..FileCoverage::default()
+        };
+        for (idx, function_def) in module.function_defs().iter().enumerate() {
+            let code = match &function_def.code {
+                Some(code) => code,
+                None => continue,
+            };
+            let function_def_idx = FunctionDefinitionIndex(idx as u16);
+            let fn_name =
+                module.identifier_at(module.function_handle_at(function_def.function).name);
+            let function_coverage =
+                module_map.and_then(|module_map| module_map.get_function_coverage(fn_name));
+            let count = |offset: usize| {
+                function_coverage
+                    .and_then(|coverage| coverage.get(&(offset as u64)))
+                    .copied()
+                    .unwrap_or(0)
+            };
+
+            let function_map = source_map.get_function_source_map(function_def_idx)?;
+            file_coverage.functions.insert(
+                format!("{}::{}", module_id.name(), fn_name),
+                (line_of(function_map.definition_location.start())?, count(0)),
+            );
+
+            for (offset, instr) in code.code.iter().enumerate() {
+                let loc = source_map.get_code_location(function_def_idx, offset as CodeOffset)?;
+                // Code may be attributed to other files, e.g. for inlined functions
+                if loc.file_hash() != file_hash {
+                    continue;
+                }
+                let line = line_of(loc.start())?;
+                // A line is as often executed as the most often executed instruction on it
+                let line_count = file_coverage.lines.entry(line).or_insert(0);
+                *line_count = (*line_count).max(count(offset));
+
+                if let Bytecode::BrTrue(target) | Bytecode::BrFalse(target) = instr {
+                    let executed = count(offset);
+                    let branch = ((idx as u32) << 16) | offset as u32;
+                    for (outcome, successor) in
+                        [*target as usize, offset + 1].into_iter().enumerate()
+                    {
+                        let taken = if executed == 0 {
+                            None
+                        } else {
+                            Some(count(successor).min(executed))
+                        };
+                        file_coverage
+                            .branches
+                            .insert((line, branch, outcome as u32), taken);
+                    }
+                }
+            }
+        }
+
+        self.add_file(source_path.to_path_buf(), file_coverage);
+        Ok(())
+    }
+
+    /// Merges another report into this one, e.g. the report of another package or test run.
+    /// Execution counts of files in both reports are added up.
+    pub fn merge(&mut self, other: CoverageReport) {
+        for (path, file_coverage) in other.files {
+            self.add_file(path, file_coverage)
+        }
+    }
+
+    fn add_file(&mut self, path: PathBuf, file_coverage: FileCoverage) {
+        match self.files.get_mut(&path) {
+            Some(existing) => existing.merge(file_coverage),
+            None => {
+                self.files.insert(path, file_coverage);
+            },
+        }
+    }
+
+    /// Writes the report in the LCOV tracefile format.
+    pub fn write_lcov<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        for (path, file) in &self.files {
+            writeln!(w, "TN:")?;
+            writeln!(w, "SF:{}", path.display())?;
+            for (name, (line, _)) in &file.functions {
+                writeln!(w, "FN:{},{}", line, name)?;
+            }
+            for (name, (_, count)) in &file.functions {
+                writeln!(w, "FNDA:{},{}", count, name)?;
+            }
+            writeln!(w, "FNF:{}", file.functions.len())?;
+            writeln!(w, "FNH:{}", file.functions_hit())?;
+            for ((line, branch, outcome), taken) in &file.branches {
+                match taken {
+                    Some(count) => writeln!(w, "BRDA:{},{},{},{}", line, branch, outcome, count)?,
+                    None => writeln!(w, "BRDA:{},{},{},-", line, branch, outcome)?,
+                }
+            }
+            writeln!(w, "BRF:{}", file.branches.len())?;
+            writeln!(w, "BRH:{}", file.branches_hit())?;
+            for (line, count) in &file.lines {
+                writeln!(w, "DA:{},{}", line, count)?;
+            }
+            writeln!(w, "LF:{}", file.lines.len())?;
+            writeln!(w, "LH:{}", file.lines_hit())?;
+            writeln!(w, "end_of_record")?;
+        }
+        Ok(())
+    }
+
+    /// Writes the report in the Cobertura XML format. Each package becomes a Cobertura package,
+    /// and each source file a class.
+    pub fn write_cobertura<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut packages: BTreeMap<&str, Vec<(&PathBuf, &FileCoverage)>> = BTreeMap::new();
+        for (path, file) in &self.files {
+            packages
+                .entry(file.package_name.as_str())
+                .or_default()
+                .push((path, file));
+        }
+        let totals = |files: &[(&PathBuf, &FileCoverage)]| {
+            files.iter().fold((0, 0, 0, 0), |acc, (_, file)| {
+                (
+                    acc.0 + file.lines_hit(),
+                    acc.1 + file.lines.len(),
+                    acc.2 + file.branches_hit(),
+                    acc.3 + file.branches.len(),
+                )
+            })
+        };
+        let all_files = self.files.iter().collect::<Vec<_>>();
+        let (lines_covered, lines_valid, branches_covered, branches_valid) = totals(&all_files);
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis())
+            .unwrap_or_default();
+
+        writeln!(w, r#"<?xml version="1.0" ?>"#)?;
+        writeln!(
+            w,
+            r#"<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">"#
+        )?;
+        writeln!(
+            w,
+            r#"<coverage line-rate="{}" branch-rate="{}" lines-covered="{}" lines-valid="{}" branches-covered="{}" branches-valid="{}" complexity="0" version="1.9" timestamp="{}">"#,
+            rate(lines_covered, lines_valid),
+            rate(branches_covered, branches_valid),
+            lines_covered,
+            lines_valid,
+            branches_covered,
+            branches_valid,
+            timestamp
+        )?;
+        writeln!(w, "  <sources>")?;
+        writeln!(w, "    <source>.</source>")?;
+        writeln!(w, "  </sources>")?;
+        writeln!(w, "  <packages>")?;
+        for (package_name, files) in &packages {
+            let (lines_covered, lines_valid, branches_covered, branches_valid) = totals(files);
+            writeln!(
+                w,
+                r#"    <package name="{}" line-rate="{}" branch-rate="{}" complexity="0">"#,
+                escape_xml(package_name),
+                rate(lines_covered, lines_valid),
+                rate(branches_covered, branches_valid),
+            )?;
+            writeln!(w, "      <classes>")?;
+            for (path, file) in files {
+                write_cobertura_class(w, path, file)?;
+            }
+            writeln!(w, "      </classes>")?;
+            writeln!(w, "    </package>")?;
+        }
+        writeln!(w, "  </packages>")?;
+        writeln!(w, "</coverage>")
+    }
+}
+
+fn write_cobertura_class<W: Write>(w: &mut W, path: &Path, file: &FileCoverage) -> io::Result<()> {
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    writeln!(
+        w,
+        r#"        <class name="{}" filename="{}" line-rate="{}" branch-rate="{}" complexity="0">"#,
+        escape_xml(&name),
+        escape_xml(&path.display().to_string()),
+        rate(file.lines_hit(), file.lines.len()),
+        rate(file.branches_hit(), file.branches.len()),
+    )?;
+    writeln!(w, "          <methods>")?;
+    for (name, (line, count)) in &file.functions {
+        writeln!(
+            w,
+            r#"            <method name="{}" signature="" line-rate="{}" branch-rate="1" complexity="0">"#,
+            escape_xml(name),
+            if *count > 0 { 1 } else { 0 },
+        )?;
+        writeln!(w, "              <lines>")?;
+        writeln!(
+            w,
+            r#"                <line number="{}" hits="{}"/>"#,
+            line, count
+        )?;
+        writeln!(w, "              </lines>")?;
+        writeln!(w, "            </method>")?;
+    }
+    writeln!(w, "          </methods>")?;
+    writeln!(w, "          <lines>")?;
+    for (line, count) in &file.lines {
+        let outcomes = file
+            .branches
+            .range((*line, 0, 0)..=(*line, u32::MAX, u32::MAX))
+            .map(|(_, taken)| matches!(taken, Some(count) if *count > 0))
+            .collect::<Vec<_>>();
+        if outcomes.is_empty() {
+            writeln!(
+                w,
+                r#"            <line number="{}" hits="{}" branch="false"/>"#,
+                line, count
+            )?;
+        } else {
+            let covered = outcomes.iter().filter(|taken| **taken).count();
+            writeln!(
+                w,
+                r#"            <line number="{}" hits="{}" branch="true" condition-coverage="{}% ({}/{})"/>"#,
+                line,
+                count,
+                covered * 100 / outcomes.len(),
+                covered,
+                outcomes.len()
+            )?;
+        }
+    }
+    writeln!(w, "          </lines>")?;
+    writeln!(w, "        </class>")
+}
+
+/// The rate of covered items, which is 1 if there are none.
+fn rate(covered: usize, valid: usize) -> String {
+    if valid == 0 {
+        "1".to_string()
+    } else {
+        format!("{:.4}", covered as f64 / valid as f64)
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coverage_map::CoverageMap;
+    use move_compiler::{
+        compiled_unit::{CompiledUnit, NamedCompiledModule},
+        shared::{known_attributes::KnownAttribute, Flags},
+        Compiler,
+    };
+    use std::collections::BTreeSet;
+
+    fn report() -> CoverageReport {
+        let mut report = CoverageReport::new();
+        report
+            .files
+            .insert(PathBuf::from("sources/coin.move"), FileCoverage {
+                package_name: "Coin".to_string(),
+                functions: [
+                    ("coin::mint".to_string(), (3, 2)),
+                    ("coin::burn".to_string(), (9, 0)),
+                ]
+                .into_iter()
+                .collect(),
+                lines: [(4, 2), (5, 2), (6, 1), (10, 0)].into_iter().collect(),
+                branches: [((5, 7, 0), Some(1)), ((5, 7, 1), Some(0))]
+                    .into_iter()
+                    .collect(),
+            });
+        report
+    }
+
+    #[test]
+    fn test_lcov() {
+        let mut out = vec![];
+        report().write_lcov(&mut out).unwrap();
+        let lcov = String::from_utf8(out).unwrap();
+        assert_eq!(
+            lcov,
+            "TN:\nSF:sources/coin.move\nFN:9,coin::burn\nFN:3,coin::mint\nFNDA:0,coin::burn\n\
+            FNDA:2,coin::mint\nFNF:2\nFNH:1\nBRDA:5,7,0,1\nBRDA:5,7,1,0\nBRF:2\nBRH:1\nDA:4,2\n\
+            DA:5,2\nDA:6,1\nDA:10,0\nLF:4\nLH:3\nend_of_record\n"
+        );
+    }
+
+    #[test]
+    fn test_cobertura() {
+        let mut out = vec![];
+        report().write_cobertura(&mut out).unwrap();
+        let xml = String::from_utf8(out).unwrap();
+        assert!(xml.contains(r#"lines-covered="3" lines-valid="4" branches-covered="1""#));
+        assert!(xml.contains(r#"<class name="coin" filename="sources/coin.move" line-rate="0.7500" branch-rate="0.5000""#));
+        assert!(xml.contains(
+            r#"<line number="5" hits="2" branch="true" condition-coverage="50% (1/2)"/>"#
+        ));
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut merged = report();
+        merged.merge(report());
+        let file = &merged.files[&PathBuf::from("sources/coin.move")];
+        assert_eq!(file.lines[&5], 4);
+        assert_eq!(file.functions["coin::burn"], (9, 0));
+        assert_eq!(file.branches[&(5, 7, 0)], Some(2));
+    }
+
+    const MODULE: &str = "module 0x42::m {
+    public fun f(x: u64): u64 {
+        let y = x + 1;
+        y * 2
+    }
+
+    public fun g(x: u64): u64 {
+        x - 1
+    }
+}
+";
+
+    #[test]
+    fn test_add_module_from_trace() {
+        let dir = tempfile::tempdir().unwrap();
+        let source_path = dir.path().join("m.move");
+        fs::write(&source_path, MODULE).unwrap();
+        let (_, units) = Compiler::from_files(
+            vec![source_path.to_string_lossy().to_string()],
+            vec![],
+            BTreeMap::new(),
+            Flags::empty(),
+            KnownAttribute::get_all_attribute_names(),
+        )
+        .build_and_report()
+        .unwrap();
+        let NamedCompiledModule {
+            module, source_map, ..
+        } = match units.into_iter().next().unwrap().into_compiled_unit() {
+            CompiledUnit::Module(module) => module,
+            CompiledUnit::Script(_) => panic!("expected a module"),
+        };
+
+        // Record the trace of a single call of `f`, which has no branches, so each of its
+        // instructions is executed once. `g` is never called.
+        let f_def = module
+            .function_defs()
+            .iter()
+            .find(|def| {
+                module
+                    .identifier_at(module.function_handle_at(def.function).name)
+                    .as_str()
+                    == "f"
+            })
+            .unwrap();
+        let trace = (0..f_def.code.as_ref().unwrap().code.len())
+            .map(|pc| format!("0x42::m::f,{}\n", pc))
+            .collect::<String>();
+        let trace_path = dir.path().join("trace");
+        fs::write(&trace_path, trace).unwrap();
+        let coverage_map = CoverageMap::from_trace_file(&trace_path).to_unified_exec_map();
+
+        let mut report = CoverageReport::new();
+        report
+            .add_module("Test", &module, &source_map, &source_path, &coverage_map)
+            .unwrap();
+        let file = &report.files[&source_path];
+        assert_eq!(file.functions["m::f"], (2, 1));
+        assert_eq!(file.functions["m::g"], (7, 0));
+        // The lines of `f` are hit once, those of `g` never
+        assert_eq!(file.lines[&3], 1);
+        assert_eq!(file.lines[&4], 1);
+        assert_eq!(file.lines[&8], 0);
+        let lines = file.lines.keys().copied().collect::<BTreeSet<_>>();
+        assert!(lines.iter().all(|line| (2..=9).contains(line)));
+        assert!(file
+            .lines
+            .iter()
+            .all(|(line, count)| *count == if *line < 7 { 1 } else { 0 }));
+        assert!(file.branches.is_empty());
+
+        let mut out = vec![];
+        report.write_lcov(&mut out).unwrap();
+        let lcov = String::from_utf8(out).unwrap();
+        assert!(lcov.contains("FNDA:1,m::f\n"));
+        assert!(lcov.contains("FNDA:0,m::g\n"));
+        assert!(lcov.contains("DA:3,1\n"));
+        assert!(lcov.contains("DA:8,0\n"));
+    }
+}
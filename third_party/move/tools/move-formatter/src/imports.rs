@@ -0,0 +1,165 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Sorting of `use` declarations.
+//!
+//! Consecutive `use` declarations, each on its own line, are sorted and deduplicated, and so are
+//! the members imported in a `{..}` group. A blank line or a comment ends a run of declarations,
+//! so that authors can keep related imports together. The comments on the lines directly above
+//! the first declaration of a run move along with it. Declarations containing comments are not
+//! touched.
+
+use crate::tokens::{GapItem, Token};
+use move_compiler::parser::lexer::Tok;
+
+pub(crate) fn sort_imports(tokens: &mut Vec<Token>) {
+    let mut pos = 0;
+    while pos < tokens.len() {
+        let mut items = vec![];
+        let mut end = pos;
+        while let Some(item_end) = use_decl_at(tokens, end, items.is_empty()) {
+            items.push(tokens[end..item_end].to_vec());
+            end = item_end;
+        }
+        if items.is_empty() {
+            pos += 1;
+            continue;
+        }
+        // The line breaks before each declaration stay in place, the declarations move along
+        // with the comments directly above them.
+        let mut gaps = vec![];
+        for item in &mut items {
+            let leading_comments = leading_comments(&item[0].gap);
+            gaps.push(item[0].gap.drain(..leading_comments).collect::<Vec<_>>());
+        }
+        let mut items = items.into_iter().map(sort_members).collect::<Vec<_>>();
+        items.sort_by_cached_key(|item| sort_key(item));
+        // A duplicate is only dropped if that loses no comments
+        items.dedup_by(|item, prev| sort_key(item) == sort_key(prev) && !item[0].has_comments());
+        let mut sorted = vec![];
+        for (mut item, mut gap) in items.into_iter().zip(gaps) {
+            gap.append(&mut item[0].gap);
+            item[0].gap = gap;
+            sorted.extend(item)
+        }
+        let len = sorted.len();
+        tokens.splice(pos..end, sorted);
+        pos += len;
+    }
+}
+
+/// Returns the end of the `use` declaration starting at `start`, if there is one which can be
+/// sorted. Declarations after the first one of a run must directly follow the previous one.
+fn use_decl_at(tokens: &[Token], start: usize, first: bool) -> Option<usize> {
+    let token = tokens.get(start)?;
+    let at_line_start = start == 0 || token.newlines() > 0;
+    if !at_line_start || (!first && (token.newlines() > 1 || token.has_comments())) {
+        return None;
+    }
+    let mut pos = start;
+    if token.tok == Tok::NumSign {
+        // A single attribute like `#[test_only]`
+        if tokens.get(pos + 1)?.tok != Tok::LBracket {
+            return None;
+        }
+        let mut depth = 0;
+        loop {
+            pos += 1;
+            match tokens.get(pos)?.tok {
+                Tok::LBracket => depth += 1,
+                Tok::RBracket => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                },
+                Tok::EOF => return None,
+                _ => {},
+            }
+        }
+        pos += 1;
+    }
+    if tokens.get(pos)?.tok != Tok::Use {
+        return None;
+    }
+    loop {
+        pos += 1;
+        let token = tokens.get(pos)?;
+        if token.has_comments() || token.tok == Tok::EOF {
+            return None;
+        }
+        if token.tok == Tok::Semicolon {
+            return Some(pos + 1);
+        }
+    }
+}
+
+/// Returns the start of the comments on the lines directly above a declaration in the gap
+/// before it. A comment following code on the previous line, or separated from the declaration by
+/// a blank line, is not part of them.
+fn leading_comments(gap: &[GapItem]) -> usize {
+    let after_first_line = gap
+        .iter()
+        .position(|item| *item == GapItem::Newline)
+        .map_or(gap.len(), |newline| newline + 1);
+    (after_first_line..gap.len())
+        .find(|idx| {
+            matches!(gap[*idx], GapItem::Comment(..))
+                && !gap[*idx..]
+                    .windows(2)
+                    .any(|items| items == [GapItem::Newline, GapItem::Newline])
+        })
+        .unwrap_or(gap.len())
+}
+
+/// Sorts the members of a `use a::m::{..}` declaration, putting `Self` first.
+fn sort_members(mut item: Vec<Token>) -> Vec<Token> {
+    let Some(open) = item.iter().position(|token| token.tok == Tok::LBrace) else {
+        return item;
+    };
+    let Some(close) = item.iter().rposition(|token| token.tok == Tok::RBrace) else {
+        return item;
+    };
+    let mut members = item[open + 1..close]
+        .split(|token| token.tok == Tok::Comma)
+        .filter(|member| !member.is_empty())
+        .map(|member| member.to_vec())
+        .collect::<Vec<_>>();
+    members.sort_by_cached_key(|member| (member[0].text != "Self", text(member)));
+    members.dedup_by_key(|member| text(member));
+    let mut group = vec![];
+    for (idx, member) in members.into_iter().enumerate() {
+        if idx > 0 {
+            group.push(Token {
+                tok: Tok::Comma,
+                text: ",",
+                gap: vec![],
+                spaced: false,
+            })
+        }
+        group.extend(member.into_iter().map(|token| Token {
+            gap: vec![],
+            ..token
+        }))
+    }
+    item[close].gap.clear();
+    item.splice(open + 1..close, group);
+    item
+}
+
+/// Orders declarations without attributes first, then by the imported path.
+fn sort_key(item: &[Token]) -> (String, String) {
+    let use_pos = item
+        .iter()
+        .position(|token| token.tok == Tok::Use)
+        .unwrap_or_default();
+    (text(&item[..use_pos]), text(&item[use_pos..]))
+}
+
+fn text(tokens: &[Token]) -> String {
+    tokens
+        .iter()
+        .map(|token| token.text)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,614 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Layout of the token stream into lines.
+//!
+//! The indentation of a line follows from the brackets which are open at its start: each line
+//! opening a bracket indents the lines up to the closing bracket by one level. Lines continuing a
+//! statement from the previous line are indented by one more level. The spacing between tokens is
+//! derived from the kinds of the tokens; where a token is ambiguous (e.g. `<` for type arguments
+//! or comparison) the spacing of the source decides.
+
+use crate::{
+    tokens::{GapItem, Token},
+    FormatConfig,
+};
+use move_compiler::parser::lexer::Tok;
+
+/// Identifiers which act as keywords and are separated by a space from a following `(`.
+const CONTEXTUAL_KEYWORDS: &[&str] = &[
+    "aborts_if",
+    "aborts_with",
+    "apply",
+    "assert",
+    "assume",
+    "decreases",
+    "emits",
+    "ensures",
+    "except",
+    "for",
+    "in",
+    "include",
+    "match",
+    "modifies",
+    "requires",
+    "succeeds_if",
+    "to",
+    "where",
+    "with",
+];
+
+/// The role of a token which can be used in several ways.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Plain,
+    /// A prefix operator: `&` and `*`
+    Unary,
+    /// `<` opening type arguments
+    TypeArgsOpen,
+    /// `>` or `>>` closing type arguments
+    TypeArgsClose,
+    /// `{` and `}` of an import group, which have no inner spaces
+    TightBrace,
+    /// `|` opening the parameters of a lambda or a function type
+    LambdaOpen,
+    /// `|` closing the parameters of a lambda or a function type
+    LambdaClose,
+}
+
+#[derive(Debug, Clone)]
+struct Fragment {
+    text: String,
+    tok: Option<Tok>,
+    role: Role,
+    space_before: bool,
+    /// For comments, the column they started at in the source.
+    source_column: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Line {
+    indent: usize,
+    fragments: Vec<Fragment>,
+}
+
+struct Bracket {
+    tok: Tok,
+    /// The indentation of the line the bracket was opened in.
+    indent: usize,
+    /// The indentation of the statement the bracket was opened in.
+    statement_indent: usize,
+    tight: bool,
+}
+
+struct Layout {
+    lines: Vec<Line>,
+    current: Option<Line>,
+    /// The number of line breaks since the last fragment.
+    newlines: usize,
+    brackets: Vec<Bracket>,
+    /// The indentation of the current line without continuation.
+    statement_indent: usize,
+    type_args_depth: usize,
+    /// Whether the parameters of a lambda are open.
+    lambda_params: bool,
+    last_code: Option<Tok>,
+}
+
+/// Lays out the tokens and renders the formatted source.
+pub(crate) fn layout(tokens: &[Token], config: &FormatConfig) -> String {
+    let mut layout = Layout {
+        lines: vec![],
+        current: None,
+        newlines: 0,
+        brackets: vec![],
+        statement_indent: 0,
+        type_args_depth: 0,
+        lambda_params: false,
+        last_code: None,
+    };
+    for (idx, token) in tokens.iter().enumerate() {
+        for item in &token.gap {
+            match item {
+                GapItem::Newline => layout.end_line(),
+                GapItem::Comment(text, column) => layout.push_comment(text, *column),
+            }
+        }
+        if token.tok == Tok::EOF {
+            layout.end_line();
+            break;
+        }
+        layout.push_token(token, tokens.get(idx + 1));
+    }
+    let mut lines = vec![];
+    for line in std::mem::take(&mut layout.lines) {
+        wrap(line, config, &mut lines)
+    }
+    render(&lines, config)
+}
+
+impl Layout {
+    fn end_line(&mut self) {
+        if let Some(line) = self.current.take() {
+            self.lines.push(line)
+        }
+        self.newlines += 1;
+    }
+
+    /// Starts a new line unless there is a current one, and returns it.
+    fn line(&mut self, first: Option<Tok>) -> &mut Line {
+        if self.current.is_none() {
+            let closes = first.map_or(false, is_closing);
+            // Keep at most one blank line, and none at the start of a block or the end of it.
+            let after_open = self.lines.last().map_or(true, |line| {
+                line.fragments
+                    .iter()
+                    .rev()
+                    .find_map(|fragment| fragment.tok)
+                    .map_or(false, is_opening)
+            });
+            if self.newlines > 1 && !after_open && !closes {
+                self.lines.push(Line::default())
+            }
+            let indent = if closes {
+                self.statement_indent = self.brackets.last().map_or(0, |bracket| bracket.indent);
+                self.statement_indent
+            } else {
+                let base = self.brackets.last().map_or(0, |bracket| bracket.indent + 1);
+                self.statement_indent = base;
+                if first.is_some() && first != Some(Tok::LBrace) && self.continues_statement() {
+                    base + 1
+                } else {
+                    base
+                }
+            };
+            self.current = Some(Line {
+                indent,
+                fragments: vec![],
+            })
+        }
+        self.newlines = 0;
+        self.current.as_mut().unwrap()
+    }
+
+    /// Returns true if a line starting now continues the statement of the previous line.
+    fn continues_statement(&self) -> bool {
+        let in_block = self
+            .brackets
+            .last()
+            .map_or(true, |bracket| bracket.tok == Tok::LBrace);
+        in_block
+            && !matches!(
+                self.last_code,
+                None | Some(
+                    Tok::Semicolon
+                        | Tok::Comma
+                        | Tok::LBrace
+                        | Tok::RBrace
+                        | Tok::RBracket
+                        | Tok::LParen
+                        | Tok::LBracket
+                )
+            )
+    }
+
+    fn push_comment(&mut self, text: &str, column: usize) {
+        let line = self.line(None);
+        line.fragments.push(Fragment {
+            text: text.to_string(),
+            tok: None,
+            role: Role::Plain,
+            space_before: true,
+            source_column: Some(column),
+        })
+    }
+
+    fn push_token(&mut self, token: &Token, next: Option<&Token>) {
+        let role = self.role(token, next);
+        if token.tok == Tok::GreaterEqual && role == Role::TypeArgsClose {
+            // `vector<u8>= x` is lexed as `>=`
+            self.push_fragment(token, ">", Tok::Greater, role);
+            self.push_fragment(token, "=", Tok::Equal, Role::Plain);
+        } else {
+            self.push_fragment(token, token.text, token.tok, role);
+        }
+
+        match token.tok {
+            Tok::LParen | Tok::LBracket | Tok::LBrace => {
+                // A block is indented relative to its statement, even if it is opened on a
+                // continuation line like `acquires R {`.
+                let indent = if token.tok == Tok::LBrace {
+                    self.statement_indent
+                } else {
+                    self.current.as_ref().map_or(0, |line| line.indent)
+                };
+                self.brackets.push(Bracket {
+                    tok: token.tok,
+                    indent,
+                    statement_indent: self.statement_indent,
+                    tight: role == Role::TightBrace,
+                })
+            },
+            Tok::RParen | Tok::RBracket | Tok::RBrace => {
+                if let Some(bracket) = self.brackets.pop() {
+                    self.statement_indent = bracket.statement_indent
+                }
+            },
+            _ => {},
+        }
+        self.type_args_depth = match (token.tok, role) {
+            (_, Role::TypeArgsOpen) => self.type_args_depth + 1,
+            (Tok::Greater | Tok::GreaterEqual, Role::TypeArgsClose) => self.type_args_depth - 1,
+            (Tok::GreaterGreater, Role::TypeArgsClose) => self.type_args_depth - 2,
+            (
+                Tok::Identifier
+                | Tok::NumValue
+                | Tok::ColonColon
+                | Tok::Colon
+                | Tok::Comma
+                | Tok::Plus
+                | Tok::Copy
+                | Tok::Amp
+                | Tok::AmpMut
+                | Tok::Pipe,
+                _,
+            ) => self.type_args_depth,
+            _ => 0,
+        };
+        match role {
+            Role::LambdaOpen => self.lambda_params = true,
+            Role::LambdaClose => self.lambda_params = false,
+            _ => {},
+        }
+        self.last_code = Some(token.tok);
+    }
+
+    fn push_fragment(&mut self, token: &Token, text: &str, tok: Tok, role: Role) {
+        let line = self.line(Some(tok));
+        let space_before = match line.fragments.last() {
+            None => false,
+            Some(prev) if prev.tok.is_none() => true,
+            Some(prev) => space_between(prev, tok, role, token.spaced),
+        };
+        line.fragments.push(Fragment {
+            text: text.to_string(),
+            tok: Some(tok),
+            role,
+            space_before,
+            source_column: None,
+        })
+    }
+
+    /// Determines how an ambiguous token is used.
+    fn role(&self, token: &Token, next: Option<&Token>) -> Role {
+        let prev = self
+            .current
+            .as_ref()
+            .and_then(|line| line.fragments.iter().rev().find(|f| f.tok.is_some()))
+            .map(|fragment| (fragment.tok.unwrap(), fragment.role))
+            .or_else(|| self.last_code.map(|tok| (tok, Role::Plain)));
+        match token.tok {
+            Tok::Amp | Tok::Star => {
+                let after_value = prev.map_or(false, |(tok, role)| ends_value(tok, role));
+                let next_spaced = next.map_or(false, |next| next.spaced);
+                if !after_value || (token.spaced && !next_spaced) {
+                    Role::Unary
+                } else {
+                    Role::Plain
+                }
+            },
+            // Type arguments of a name or of a pattern like `*<T>` in specs
+            Tok::Less
+                if !token.spaced && matches!(prev, Some((Tok::Identifier | Tok::Star, _))) =>
+            {
+                Role::TypeArgsOpen
+            },
+            Tok::Greater | Tok::GreaterEqual if self.type_args_depth > 0 => Role::TypeArgsClose,
+            Tok::GreaterGreater if self.type_args_depth > 1 => Role::TypeArgsClose,
+            Tok::LBrace if prev.map(|(tok, _)| tok) == Some(Tok::ColonColon) => Role::TightBrace,
+            Tok::RBrace if self.brackets.last().map_or(false, |bracket| bracket.tight) => {
+                Role::TightBrace
+            },
+            Tok::Pipe if self.lambda_params => Role::LambdaClose,
+            // A `|` which does not follow a value can't be a bitwise or
+            Tok::Pipe if !prev.map_or(false, |(tok, role)| ends_value(tok, role)) => {
+                Role::LambdaOpen
+            },
+            _ => Role::Plain,
+        }
+    }
+}
+
+/// Returns true if there is a space between the fragment and a following token.
+fn space_between(prev: &Fragment, tok: Tok, role: Role, spaced: bool) -> bool {
+    use Tok::*;
+    let prev_tok = prev.tok.unwrap();
+    match (prev_tok, tok) {
+        (_, Comma | Semicolon | RParen | RBracket | Period | ColonColon | Colon) => false,
+        (LBrace, RBrace) => false,
+        (_, RBrace) => role != Role::TightBrace,
+        (LParen | LBracket | Period | ColonColon | AtSign | NumSign | Exclaim, _) => false,
+        (LBrace, _) => prev.role != Role::TightBrace,
+        _ if prev.role == Role::Unary || prev.role == Role::TypeArgsOpen => false,
+        _ if role == Role::TypeArgsOpen || role == Role::TypeArgsClose => false,
+        _ if prev.role == Role::LambdaOpen || role == Role::LambdaClose => false,
+        _ if prev.role == Role::LambdaClose || role == Role::LambdaOpen => true,
+        (Identifier, Exclaim) => spaced,
+        (Identifier, LParen) => spaced && CONTEXTUAL_KEYWORDS.contains(&prev.text.as_str()),
+        (Public, LParen) => false,
+        (Abort | Return, LParen) => spaced,
+        (_, LParen) => prev.role != Role::TypeArgsClose,
+        (Identifier, LBracket) => spaced && CONTEXTUAL_KEYWORDS.contains(&prev.text.as_str()),
+        (RParen | RBracket, LBracket) => false,
+        (_, LBracket) => prev.role != Role::TypeArgsClose,
+        (Pipe | PeriodPeriod, _) | (_, Pipe | PeriodPeriod) => spaced,
+        _ if is_binary(prev_tok, prev.role) || is_binary(tok, role) => true,
+        (Comma | Semicolon | Colon | AmpMut, _) => true,
+        _ if ends_value(prev_tok, prev.role) && starts_term(tok) => true,
+        _ => spaced,
+    }
+}
+
+fn is_binary(tok: Tok, role: Role) -> bool {
+    use Tok::*;
+    match tok {
+        Equal
+        | EqualEqual
+        | ExclaimEqual
+        | Plus
+        | Minus
+        | Slash
+        | Percent
+        | AmpAmp
+        | PipePipe
+        | LessEqual
+        | GreaterEqual
+        | LessLess
+        | EqualGreater
+        | EqualEqualGreater
+        | LessEqualEqualGreater
+        | Caret => true,
+        Amp | Star | Less | Greater | GreaterGreater => role == Role::Plain,
+        _ => false,
+    }
+}
+
+/// Returns true if the token can end an expression, a type or a keyword.
+fn ends_value(tok: Tok, role: Role) -> bool {
+    use Tok::*;
+    match tok {
+        Identifier | NumValue | NumTypedValue | ByteStringValue | True | False | RParen
+        | RBracket | RBrace => true,
+        Greater | GreaterGreater => role == Role::TypeArgsClose,
+        _ => is_keyword(tok),
+    }
+}
+
+/// Returns true if the token can start a term or is a keyword.
+fn starts_term(tok: Tok) -> bool {
+    use Tok::*;
+    match tok {
+        Identifier | NumValue | NumTypedValue | ByteStringValue | True | False | LBrace
+        | AtSign | Exclaim | Amp | AmpMut | Star | NumSign => true,
+        _ => is_keyword(tok),
+    }
+}
+
+fn is_keyword(tok: Tok) -> bool {
+    use Tok::*;
+    matches!(
+        tok,
+        Abort
+            | Acquires
+            | As
+            | Break
+            | Continue
+            | Copy
+            | Else
+            | If
+            | Invariant
+            | Let
+            | Loop
+            | Module
+            | Move
+            | Native
+            | Public
+            | Return
+            | Spec
+            | Struct
+            | Use
+            | While
+            | Fun
+            | Script
+            | Const
+            | Friend
+            | Inline
+    )
+}
+
+fn is_opening(tok: Tok) -> bool {
+    matches!(tok, Tok::LParen | Tok::LBracket | Tok::LBrace)
+}
+
+fn is_closing(tok: Tok) -> bool {
+    matches!(tok, Tok::RParen | Tok::RBracket | Tok::RBrace)
+}
+
+/// Wraps a line exceeding the maximal width by putting the items of its outermost bracketed
+/// list on lines of their own, and then wraps the resulting lines.
+fn wrap(line: Line, config: &FormatConfig, lines: &mut Vec<Line>) {
+    if width(&line, config) <= config.max_width
+        || line.fragments.iter().any(|f| f.text.contains('\n'))
+    {
+        lines.push(line);
+        return;
+    }
+    let Some((open, close)) = outermost_list(&line) else {
+        lines.push(line);
+        return;
+    };
+    let indent = line.indent;
+    let mut fragments = line.fragments;
+    let tail = fragments.split_off(close);
+    let items = fragments.split_off(open + 1);
+    lines.push(Line { indent, fragments });
+    let mut item = vec![];
+    for fragment in items {
+        let is_separator = fragment.tok == Some(Tok::Comma) && item_depth(&item) == 0;
+        item.push(fragment);
+        if is_separator {
+            wrap(
+                Line {
+                    indent: indent + 1,
+                    fragments: std::mem::take(&mut item),
+                },
+                config,
+                lines,
+            )
+        }
+    }
+    if !item.is_empty() {
+        wrap(
+            Line {
+                indent: indent + 1,
+                fragments: item,
+            },
+            config,
+            lines,
+        )
+    }
+    wrap(
+        Line {
+            indent,
+            fragments: tail,
+        },
+        config,
+        lines,
+    )
+}
+
+/// Finds the non-empty parenthesized list or import group to wrap: the first one with several
+/// items, or else the last one on the line if it ends the line.
+fn outermost_list(line: &Line) -> Option<(usize, usize)> {
+    let mut depth = 0;
+    let mut open = None;
+    let mut lists = vec![];
+    let mut last_open = None;
+    for (idx, fragment) in line.fragments.iter().enumerate() {
+        let Some(tok) = fragment.tok else { continue };
+        if is_opening(tok) {
+            // `public(friend)` is not a list
+            let after_public = idx > 0 && line.fragments[idx - 1].tok == Some(Tok::Public);
+            let is_list = (tok == Tok::LParen && !after_public)
+                || (tok == Tok::LBrace && fragment.role == Role::TightBrace);
+            if depth == 0 {
+                last_open = Some(idx);
+                open = is_list.then_some((idx, false));
+            }
+            depth += 1;
+        } else if is_closing(tok) {
+            if depth == 0 {
+                // Closes a bracket opened on a previous line
+                open = None;
+                continue;
+            }
+            depth -= 1;
+            if depth == 0 {
+                if let Some((open, has_comma)) = open.take() {
+                    if idx > open + 1 {
+                        lists.push((open, idx, has_comma))
+                    }
+                }
+            }
+        } else if tok == Tok::Comma && depth == 1 {
+            if let Some((_, has_comma)) = &mut open {
+                *has_comma = true
+            }
+        }
+    }
+    lists
+        .iter()
+        .find(|(_, _, has_comma)| *has_comma)
+        .or_else(|| {
+            // Only wrap a single item if nothing but punctuation follows it
+            lists.last().filter(|(open, close, _)| {
+                Some(*open) == last_open
+                    && line.fragments[close + 1..].iter().all(|fragment| {
+                        matches!(
+                            fragment.tok,
+                            None | Some(Tok::Semicolon | Tok::Comma | Tok::RParen | Tok::LBrace)
+                        )
+                    })
+            })
+        })
+        .map(|(open, close, _)| (*open, *close))
+}
+
+/// Returns the nesting depth of brackets at the end of the fragments.
+fn item_depth(fragments: &[Fragment]) -> usize {
+    fragments
+        .iter()
+        .fold(0, |depth, fragment| match fragment.tok {
+            Some(tok) if is_opening(tok) => depth + 1,
+            Some(tok) if is_closing(tok) => depth.saturating_sub(1),
+            _ => depth,
+        })
+}
+
+fn width(line: &Line, config: &FormatConfig) -> usize {
+    line.indent * config.indent_size
+        + line
+            .fragments
+            .iter()
+            .enumerate()
+            .map(|(idx, f)| f.text.len() + usize::from(idx > 0 && f.space_before))
+            .sum::<usize>()
+}
+
+fn render(lines: &[Line], config: &FormatConfig) -> String {
+    let mut out = String::new();
+    for line in lines {
+        let mut text = " ".repeat(line.indent * config.indent_size);
+        if line.fragments.is_empty() {
+            text.clear()
+        }
+        for (idx, fragment) in line.fragments.iter().enumerate() {
+            if idx > 0 && fragment.space_before {
+                text.push(' ')
+            }
+            match fragment.source_column {
+                Some(column) if fragment.text.contains('\n') => {
+                    // Shift the lines of a block comment along with its first line
+                    let new_column = text.len();
+                    for (line_idx, comment_line) in fragment.text.split('\n').enumerate() {
+                        if line_idx == 0 {
+                            text.push_str(comment_line);
+                            continue;
+                        }
+                        text.push('\n');
+                        if new_column >= column {
+                            if !comment_line.trim().is_empty() {
+                                text.push_str(&" ".repeat(new_column - column))
+                            }
+                            text.push_str(comment_line)
+                        } else {
+                            let removable = comment_line.len() - comment_line.trim_start().len();
+                            text.push_str(&comment_line[removable.min(column - new_column)..])
+                        }
+                    }
+                },
+                _ => text.push_str(&fragment.text),
+            }
+        }
+        for text_line in text.split('\n') {
+            out.push_str(text_line.trim_end());
+            out.push('\n');
+        }
+    }
+    let trimmed = out.trim_matches('\n');
+    if trimmed.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", trimmed)
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A formatter for Move source code.
+//!
+//! The formatter works on the token stream of the move-compiler lexer, so that all comments are
+//! preserved. It normalizes indentation and the spacing between tokens, collapses runs of blank
+//! lines, sorts `use` declarations, and wraps lines which exceed the maximal width at their
+//! outermost argument list. Line breaks chosen by the author are otherwise kept.
+//!
+//! Only sources which parse are formatted. Before the formatted source is returned, it is parsed
+//! again and its tokens are compared with the ones of the source (with imports sorted), ignoring
+//! whitespace and comments, so that formatting never produces broken or different code.
+
+mod imports;
+mod layout;
+mod tokens;
+
+use anyhow::{bail, Context};
+use move_command_line_common::files::FileHash;
+use move_compiler::{
+    diagnostics::{report_diagnostics_to_buffer, FilesSourceText},
+    parser::{comments::verify_string, syntax::parse_file_string},
+    shared::{CompilationEnv, Flags},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, fs, path::Path};
+use tokens::Token;
+
+/// The name of the formatter configuration file in the package root.
+pub const CONFIG_FILE_NAME: &str = "movefmt.toml";
+
+/// The configuration of the formatter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FormatConfig {
+    /// The maximal width of a line. Longer lines are wrapped where possible.
+    pub max_width: usize,
+    /// The number of spaces per indentation level.
+    pub indent_size: usize,
+    /// Whether to sort consecutive `use` declarations and the members imported by them.
+    pub reorder_imports: bool,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self {
+            max_width: 100,
+            indent_size: 4,
+            reorder_imports: true,
+        }
+    }
+}
+
+impl FormatConfig {
+    /// Reads the configuration from the package at `package_path`, using defaults if it has no
+    /// configuration file.
+    pub fn load(package_path: &Path) -> anyhow::Result<Self> {
+        let path = package_path.join(CONFIG_FILE_NAME);
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)?;
+        toml::from_str(&contents)
+            .with_context(|| format!("Unable to parse formatter configuration {}", path.display()))
+    }
+}
+
+/// Formats the Move source. `file_name` is only used in error messages.
+pub fn format_source(
+    file_name: &str,
+    source: &str,
+    config: &FormatConfig,
+) -> anyhow::Result<String> {
+    check_parses(file_name, source)?;
+    let mut tokens = tokens::tokenize(source)?;
+    if config.reorder_imports {
+        imports::sort_imports(&mut tokens);
+    }
+    let formatted = layout::layout(&tokens, config);
+    if check_parses(file_name, &formatted).is_err() || !same_tokens(&tokens, &formatted) {
+        bail!(
+            "formatting `{}` would produce invalid code; please report this as a bug",
+            file_name
+        )
+    }
+    Ok(formatted)
+}
+
+/// Checks that the formatted source consists of the given tokens, ignoring whitespace and
+/// comments.
+fn same_tokens(tokens: &[Token], formatted: &str) -> bool {
+    let Ok(formatted_tokens) = tokens::tokenize(formatted) else {
+        return false;
+    };
+    let key = |token: &Token| (token.tok, token.text.to_string());
+    tokens.iter().map(key).eq(formatted_tokens.iter().map(key))
+}
+
+/// Checks that the source parses, returning the rendered diagnostics otherwise.
+fn check_parses(file_name: &str, source: &str) -> anyhow::Result<()> {
+    let file_hash = FileHash::new(source);
+    let mut env = CompilationEnv::new(Flags::model_compilation(), BTreeSet::new());
+    let result = verify_string(file_hash, source)
+        .and_then(|_| parse_file_string(&mut env, file_hash, source));
+    if let Err(diags) = result {
+        let mut files = FilesSourceText::new();
+        files.insert(file_hash, (file_name.into(), source.to_string()));
+        bail!(
+            "{}",
+            String::from_utf8_lossy(&report_diagnostics_to_buffer(&files, diags))
+        )
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_tokens() {
+        let tokens = tokens::tokenize("let r = &mut x; // comment\nf(r)").unwrap();
+        assert!(same_tokens(
+            &tokens,
+            "let r = &mut   x;\n/* other */ f( r )"
+        ));
+        assert!(!same_tokens(&tokens, "let r = &mut x; f(r, r)"));
+        assert!(!same_tokens(&tokens, "let r = &mut y; f(r)"));
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tokenization of Move source which, unlike the lexer, keeps comments and line breaks.
+
+use anyhow::anyhow;
+use move_command_line_common::files::FileHash;
+use move_compiler::parser::lexer::{Lexer, Tok};
+
+/// A token together with the comments and line breaks preceding it.
+#[derive(Debug, Clone)]
+pub(crate) struct Token<'a> {
+    pub tok: Tok,
+    pub text: &'a str,
+    /// The line breaks and comments between the previous token and this one.
+    pub gap: Vec<GapItem<'a>>,
+    /// Whether this token was separated from the previous one in the source.
+    pub spaced: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum GapItem<'a> {
+    Newline,
+    /// A line or block comment and the column it starts at.
+    Comment(&'a str, usize),
+}
+
+impl<'a> Token<'a> {
+    pub fn has_comments(&self) -> bool {
+        self.gap
+            .iter()
+            .any(|item| matches!(item, GapItem::Comment(..)))
+    }
+
+    pub fn newlines(&self) -> usize {
+        self.gap
+            .iter()
+            .filter(|item| matches!(item, GapItem::Newline))
+            .count()
+    }
+}
+
+/// Splits the source into tokens. The last token is always `Tok::EOF`, which carries the
+/// comments at the end of the file.
+pub(crate) fn tokenize(source: &str) -> anyhow::Result<Vec<Token<'_>>> {
+    let mut lexer = Lexer::new(source, FileHash::new(source));
+    let mut tokens = vec![];
+    loop {
+        lexer
+            .advance()
+            .map_err(|diag| anyhow!("unable to tokenize source: {:?}", diag))?;
+        let start = lexer.start_loc();
+        let prev_end = lexer.previous_end_loc();
+        let tok = lexer.peek();
+        tokens.push(Token {
+            tok,
+            // `&mut` includes the whitespace following it
+            text: lexer.content().trim_end(),
+            gap: scan_gap(source, prev_end, start),
+            spaced: prev_end < start,
+        });
+        if tok == Tok::EOF {
+            return Ok(tokens);
+        }
+    }
+}
+
+/// Finds the line breaks and comments in the whitespace between two tokens. Block comments can
+/// be nested.
+fn scan_gap(source: &str, start: usize, end: usize) -> Vec<GapItem<'_>> {
+    let mut items = vec![];
+    let mut pos = start;
+    while pos < end {
+        let rest = &source[pos..end];
+        if rest.starts_with("//") {
+            let len = rest.find('\n').unwrap_or(rest.len());
+            items.push(GapItem::Comment(
+                rest[..len].trim_end(),
+                column(source, pos),
+            ));
+            pos += len;
+        } else if rest.starts_with("/*") {
+            let mut depth = 0;
+            let mut len = 0;
+            while len < rest.len() {
+                if rest[len..].starts_with("/*") {
+                    depth += 1;
+                    len += 2;
+                } else if rest[len..].starts_with("*/") {
+                    depth -= 1;
+                    len += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    len += rest[len..].chars().next().map_or(1, char::len_utf8);
+                }
+            }
+            items.push(GapItem::Comment(&rest[..len], column(source, pos)));
+            pos += len;
+        } else {
+            if rest.starts_with('\n') {
+                items.push(GapItem::Newline)
+            }
+            pos += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    items
+}
+
+fn column(source: &str, pos: usize) -> usize {
+    pos - source[..pos].rfind('\n').map_or(0, |newline| newline + 1)
+}
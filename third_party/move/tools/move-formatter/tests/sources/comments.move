@@ -0,0 +1,25 @@
+/// A module with comments in all kinds of places.
+module 0x42::comments {
+  /// Documentation of a struct.
+  struct S has copy,drop {
+      // The first field
+      a: u64,   // trailing comment
+      /* a block
+         comment */
+      b: bool
+  }
+
+
+
+  fun f( x : u64 ) : u64 { // after the brace
+      /* inline */ let y = x+1; // after a statement
+      if(y>10){
+              y*2
+      }else{
+          // nothing to do
+          y
+      }
+  }
+
+  /* /* nested */ block comment at the end */
+}
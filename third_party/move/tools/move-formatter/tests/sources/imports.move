@@ -0,0 +1,20 @@
+module 0x42::imports {
+    use std::vector;
+    use std::signer::{Self,address_of};
+    use 0x1::option::{some, Self, none, some};
+    use std::signer::{Self,address_of};
+    #[test_only]
+    use std::debug;
+
+    // A comment ends a run of imports and moves with the declaration below it
+    use std::string;
+    use std::bcs;
+
+    fun f(s: &signer): vector<u8> {
+        let v = vector::empty<u8>();
+        vector::append(&mut v, bcs::to_bytes(&address_of(s)));
+        let _ = string::utf8(v);
+        let _ = option::is_some(&some(1)) || option::is_none(&none<u64>());
+        v
+    }
+}
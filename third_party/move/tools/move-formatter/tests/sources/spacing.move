@@ -0,0 +1,32 @@
+module 0x42::spacing {
+    use std::vector;
+
+    struct Pair<T1:copy+drop, T2:store> has copy,drop { first:T1, second:T2 }
+
+    public(friend) fun generic<T:copy+drop>(x:&T, y:&mut T):vector<T> {
+        *y=*x;
+        let v=vector[*x,*y];
+        let w=vector::empty<vector<T>>();
+        vector::push_back(&mut w,v);
+        let Pair{first:_, second:_}=Pair<u8,u64>{first:1, second:2};
+        if (!(1<2) && 3>=2 || (4 as u64)>>1==2) abort(1);
+        *vector::borrow(&w,0)
+    }
+
+    fun lambda(v: vector<u64>): u64 {
+        let sum=0;
+        vector::for_each(v,|x|sum=sum+x);
+        sum
+    }
+
+    inline fun apply(f:|u64|u64, x: u64): u64 { f(x) }
+
+    fun call_with_a_long_list_of_arguments(first_argument: u64, second_argument: u64, third_argument: u64): u64 {
+        call_with_a_long_list_of_arguments(first_argument + 1, second_argument + 2, third_argument + 3)
+    }
+
+    spec generic {
+        pragma verify=false;
+        ensures result==vector[old(x)];
+    }
+}
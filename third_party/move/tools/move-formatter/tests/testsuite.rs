@@ -0,0 +1,21 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use move_command_line_common::testing::EXP_EXT;
+use move_formatter::{format_source, FormatConfig};
+use move_prover_test_utils::baseline_test::verify_or_update_baseline;
+use std::{fs, path::Path};
+
+fn test_runner(path: &Path) -> datatest_stable::Result<()> {
+    let config = FormatConfig::default();
+    let file_name = path.to_string_lossy();
+    let source = fs::read_to_string(path)?;
+    let formatted = format_source(&file_name, &source, &config)?;
+    if format_source(&file_name, &formatted, &config)? != formatted {
+        return Err(format!("formatting `{}` is not idempotent", file_name).into());
+    }
+    verify_or_update_baseline(&path.with_extension(EXP_EXT), &formatted)?;
+    Ok(())
+}
+
+datatest_stable::harness!(test_runner, "tests/sources", r".*\.move$");
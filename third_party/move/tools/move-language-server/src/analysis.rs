@@ -0,0 +1,382 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Analysis of a Move package, answering the queries of the language server.
+
+use crate::symbols::{Occurrence, Symbols, Target};
+use codespan::{FileId, Span};
+use codespan_reporting::{
+    diagnostic::{Diagnostic, LabelStyle, Severity},
+    term::termcolor::NoColor,
+};
+use lsp_types::{
+    DiagnosticRelatedInformation, DiagnosticSeverity, Hover, HoverContents, Location,
+    MarkupContent, MarkupKind, Position, Range, Url,
+};
+use move_compiler_v2::{
+    bytecode_pipeline, check_and_rewrite_pipeline, env_pipeline::rewrite_target::RewritingScope,
+    run_bytecode_gen, run_checker,
+};
+use move_model::{metadata::CompilerVersion, model::GlobalEnv};
+use move_package::{
+    compilation::model_builder::ModelBuilder, resolution::resolution_graph::ResolvedGraph,
+    BuildConfig, ModelConfig,
+};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// The result of analyzing a package.
+pub struct PackageAnalysis {
+    root: PathBuf,
+    pub(crate) env: GlobalEnv,
+    pub(crate) symbols: Symbols,
+    /// The files of the model by their canonical path.
+    files: BTreeMap<PathBuf, FileId>,
+}
+
+impl PackageAnalysis {
+    /// Analyzes the package at `root` with the v2 compiler. Errors in the Move code are reported
+    /// as diagnostics; only errors in resolving the package and its dependencies fail.
+    pub fn new(build_config: &BuildConfig, root: &Path) -> anyhow::Result<Self> {
+        Self::from_resolved_graph(resolve(build_config, root)?)
+    }
+
+    /// Like `new`, but reuses a resolution of the package. The sources of the packages are read
+    /// again, so the resolution stays valid as long as the manifests do not change.
+    pub fn from_resolved_graph(resolved_graph: ResolvedGraph) -> anyhow::Result<Self> {
+        let root = resolved_graph.root_package_path.canonicalize()?;
+        let language_version = resolved_graph
+            .build_options
+            .compiler_config
+            .language_version
+            .unwrap_or_default();
+        let options = ModelBuilder::create(resolved_graph, ModelConfig {
+            all_files_as_targets: false,
+            target_filter: None,
+            compiler_version: CompilerVersion::V2_0,
+            language_version,
+        })
+        .compiler_v2_options()?;
+
+        let mut env = run_checker(options.clone())?;
+        let symbols = Symbols::new(&env);
+        // Run the remaining checks of the compiler for their diagnostics, as long as the previous
+        // ones succeeded.
+        if !env.has_errors() {
+            check_and_rewrite_pipeline(&options, false, RewritingScope::CompilationTarget)
+                .run(&mut env);
+        }
+        if !env.has_errors() {
+            let mut targets = run_bytecode_gen(&env);
+            bytecode_pipeline(&env).run(&env, &mut targets);
+        }
+
+        let files = env
+            .get_source_file_ids()
+            .into_iter()
+            .filter_map(|file_id| {
+                let path = Path::new(env.get_file(file_id)).canonicalize().ok()?;
+                Some((path, file_id))
+            })
+            .collect();
+        Ok(Self {
+            root,
+            env,
+            symbols,
+            files,
+        })
+    }
+
+    /// Returns the canonical path of the package root.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the diagnostics for each source file of the package. Files without diagnostics
+    /// are included, so that diagnostics reported before can be cleared.
+    pub fn diagnostics(&self) -> BTreeMap<PathBuf, Vec<lsp_types::Diagnostic>> {
+        let mut result: BTreeMap<PathBuf, Vec<lsp_types::Diagnostic>> = self
+            .files
+            .keys()
+            .filter(|path| path.starts_with(&self.root))
+            .map(|path| (path.clone(), vec![]))
+            .collect();
+        let mut diags = vec![];
+        self.env
+            .report_diag_with_filter(&mut NoColor::new(vec![]), |diag| {
+                diags.push(diag.clone());
+                false
+            });
+        for diag in diags {
+            let Some(label) = diag
+                .labels
+                .iter()
+                .find(|label| label.style == LabelStyle::Primary)
+                .or_else(|| diag.labels.first())
+            else {
+                continue;
+            };
+            let path = Path::new(self.env.get_file(label.file_id));
+            let Some(file_diags) = path
+                .canonicalize()
+                .ok()
+                .and_then(|path| result.get_mut(&path))
+            else {
+                continue;
+            };
+            file_diags.push(self.to_lsp_diagnostic(&diag, label.file_id, &label.range))
+        }
+        result
+    }
+
+    /// Returns the location of the declaration of the name at the position.
+    pub fn definition(&self, path: &Path, position: Position) -> Option<Location> {
+        let occ = self.occurrence_at(path, position)?;
+        let (file_id, decl) = self
+            .symbols
+            .occurrences_of(occ.target)
+            .find(|(_, occ)| occ.is_declaration)?;
+        self.location(file_id, decl.span)
+    }
+
+    /// Returns the locations of all occurrences of the name at the position.
+    pub fn references(
+        &self,
+        path: &Path,
+        position: Position,
+        include_declaration: bool,
+    ) -> Vec<Location> {
+        let Some(occ) = self.occurrence_at(path, position) else {
+            return vec![];
+        };
+        self.symbols
+            .occurrences_of(occ.target)
+            .filter(|(_, occ)| include_declaration || !occ.is_declaration)
+            .filter_map(|(file_id, occ)| self.location(file_id, occ.span))
+            .collect()
+    }
+
+    /// Returns the signature or type and the documentation of the name at the position.
+    pub fn hover(&self, path: &Path, position: Position) -> Option<Hover> {
+        let file_id = self.file_id(path)?;
+        let occ = self.occurrence_at(path, position)?;
+        let (signature, doc) = self.describe(occ);
+        let mut value = format!("```move\n{}\n```", signature);
+        if !doc.is_empty() {
+            value.push_str("\n\n");
+            value.push_str(doc.trim());
+        }
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: Some(self.range(file_id, occ.span)),
+        })
+    }
+
+    /// Returns the signature or type and the documentation of the target of the occurrence.
+    fn describe(&self, occ: &Occurrence) -> (String, String) {
+        let env = &self.env;
+        let display_ctx = match occ.fun {
+            Some(fun_id) => env.get_function(fun_id).get_type_display_ctx(),
+            None => env.get_type_display_ctx(),
+        };
+        match occ.target {
+            Target::Function(fun_id) => {
+                let fun = env.get_function(fun_id);
+                let signature = format!(
+                    "{}\n{}",
+                    fun.module_env.get_full_name_str(),
+                    fun.get_header_string()
+                );
+                (signature, fun.get_doc().to_string())
+            },
+            Target::Struct(struct_id) => {
+                let struct_env = env.get_struct(struct_id);
+                let display_ctx = struct_env.get_type_display_ctx();
+                let mut signature = format!(
+                    "{}\nstruct {}",
+                    struct_env.module_env.get_full_name_str(),
+                    struct_env.get_name().display(env.symbol_pool())
+                );
+                let type_params = struct_env.get_type_parameters();
+                if !type_params.is_empty() {
+                    let names = type_params
+                        .iter()
+                        .map(|param| param.0.display(env.symbol_pool()).to_string())
+                        .collect::<Vec<_>>();
+                    signature.push_str(&format!("<{}>", names.join(", ")));
+                }
+                signature.push_str(" {");
+                for field in struct_env.get_fields() {
+                    signature.push_str(&format!(
+                        "\n    {}: {},",
+                        field.get_name().display(env.symbol_pool()),
+                        field.get_type().display(&display_ctx)
+                    ));
+                }
+                signature.push_str("\n}");
+                (signature, struct_env.get_doc().to_string())
+            },
+            Target::Field(struct_id, field_id) => {
+                let struct_env = env.get_struct(struct_id);
+                let field = struct_env.get_field(field_id);
+                let signature = format!(
+                    "{}\n{}: {}",
+                    struct_env.get_full_name_str(),
+                    field.get_name().display(env.symbol_pool()),
+                    field.get_type().display(&struct_env.get_type_display_ctx())
+                );
+                (signature, field.get_doc().to_string())
+            },
+            Target::Constant(constant_id) => {
+                let module = env.get_module(constant_id.module_id);
+                let constant = module.get_named_constant(constant_id.id);
+                let signature = format!(
+                    "{}\nconst {}: {}",
+                    module.get_full_name_str(),
+                    constant.get_name().display(env.symbol_pool()),
+                    constant.get_type().display(&display_ctx)
+                );
+                (signature, constant.get_doc().to_string())
+            },
+            Target::Local(file_id, span) => {
+                let name = self.text(file_id, span);
+                let signature = match &occ.ty {
+                    Some(ty) => format!("{}: {}", name, ty.display(&display_ctx)),
+                    None => name.to_string(),
+                };
+                (signature, String::new())
+            },
+        }
+    }
+
+    pub(crate) fn file_id(&self, path: &Path) -> Option<FileId> {
+        self.files.get(&path.canonicalize().ok()?).copied()
+    }
+
+    fn occurrence_at(&self, path: &Path, position: Position) -> Option<&Occurrence> {
+        let file_id = self.file_id(path)?;
+        let offset = position_to_offset(self.env.get_file_source(file_id), position);
+        self.symbols.occurrence_at(file_id, offset)
+    }
+
+    pub(crate) fn text(&self, file_id: FileId, span: Span) -> &str {
+        &self.env.get_file_source(file_id)[span.start().to_usize()..span.end().to_usize()]
+    }
+
+    fn range(&self, file_id: FileId, span: Span) -> Range {
+        let source = self.env.get_file_source(file_id);
+        Range::new(
+            offset_to_position(source, span.start().to_usize()),
+            offset_to_position(source, span.end().to_usize()),
+        )
+    }
+
+    fn location(&self, file_id: FileId, span: Span) -> Option<Location> {
+        let path = Path::new(self.env.get_file(file_id)).canonicalize().ok()?;
+        Some(Location::new(
+            Url::from_file_path(path).ok()?,
+            self.range(file_id, span),
+        ))
+    }
+
+    fn to_lsp_diagnostic(
+        &self,
+        diag: &Diagnostic<FileId>,
+        file_id: FileId,
+        range: &std::ops::Range<usize>,
+    ) -> lsp_types::Diagnostic {
+        let severity = match diag.severity {
+            Severity::Bug | Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+            Severity::Note => DiagnosticSeverity::INFORMATION,
+            Severity::Help => DiagnosticSeverity::HINT,
+        };
+        let mut message = diag.message.clone();
+        for note in &diag.notes {
+            message.push('\n');
+            message.push_str(note);
+        }
+        let related_information = diag
+            .labels
+            .iter()
+            .filter(|label| label.style == LabelStyle::Secondary)
+            .filter_map(|label| {
+                let span = Span::new(label.range.start as u32, label.range.end as u32);
+                Some(DiagnosticRelatedInformation {
+                    location: self.location(label.file_id, span)?,
+                    message: label.message.clone(),
+                })
+            })
+            .collect::<Vec<_>>();
+        lsp_types::Diagnostic {
+            range: self.range(file_id, Span::new(range.start as u32, range.end as u32)),
+            severity: Some(severity),
+            source: Some("move".to_string()),
+            message,
+            related_information: (!related_information.is_empty()).then_some(related_information),
+            ..Default::default()
+        }
+    }
+}
+
+/// Converts a byte offset into a position, whose character is counted in UTF-16 code units as
+/// required by the protocol.
+pub(crate) fn offset_to_position(source: &str, offset: usize) -> Position {
+    let before = &source[..offset.min(source.len())];
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+    Position::new(
+        before.matches('\n').count() as u32,
+        before[line_start..].encode_utf16().count() as u32,
+    )
+}
+
+/// Converts a position into a byte offset, clamping it to the end of its line.
+pub(crate) fn position_to_offset(source: &str, position: Position) -> usize {
+    let line_start = match position.line {
+        0 => 0,
+        line => match source.match_indices('\n').nth(line as usize - 1) {
+            Some((idx, _)) => idx + 1,
+            None => return source.len(),
+        },
+    };
+    let line = source[line_start..].split('\n').next().unwrap_or_default();
+    let mut units = 0;
+    for (idx, c) in line.char_indices() {
+        if units >= position.character as usize {
+            return line_start + idx;
+        }
+        units += c.len_utf16();
+    }
+    line_start + line.len()
+}
+
+/// Resolves the package at `root` and its dependencies. The latest versions of git dependencies
+/// are not fetched, so that analysis works offline once the dependencies were downloaded.
+pub(crate) fn resolve(build_config: &BuildConfig, root: &Path) -> anyhow::Result<ResolvedGraph> {
+    let mut build_config = build_config.clone();
+    build_config.skip_fetch_latest_git_deps = true;
+    build_config.resolution_graph_for_package(&root.canonicalize()?, &mut Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_conversion() {
+        let source = "module 0x1::m {\n    // ä𝄞 x\n}\n";
+        for offset in [0, 7, 16, 20, 23, 25, 29, 32, source.len()] {
+            let position = offset_to_position(source, offset);
+            assert_eq!(position_to_offset(source, position), offset);
+        }
+        // `𝄞` takes two UTF-16 code units
+        assert_eq!(offset_to_position(source, 29), Position::new(1, 10));
+        // Positions past the end of a line are clamped
+        assert_eq!(position_to_offset(source, Position::new(0, 100)), 15);
+    }
+}
@@ -0,0 +1,142 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Completion of module members and struct fields.
+//!
+//! Completions are computed while the user types, so the text of the file generally differs from
+//! the one the package was last analyzed with. The text before the cursor decides what is
+//! completed: module members after `m::`, fields after `x.`, and otherwise the members of the
+//! modules in the file.
+
+use crate::{analysis::PackageAnalysis, symbols::Target};
+use lsp_types::{CompletionItem, CompletionItemKind, Documentation, Position};
+use move_model::model::{ModuleEnv, StructEnv, Visibility};
+use std::path::Path;
+
+impl PackageAnalysis {
+    /// Returns the completions at the position of the current text of the file.
+    pub fn completions(&self, path: &Path, text: &str, position: Position) -> Vec<CompletionItem> {
+        let offset = crate::analysis::position_to_offset(text, position);
+        // The text before the identifier being typed
+        let before = text[..offset].trim_end_matches(is_ident_char);
+        if let Some(qualifier) = before.strip_suffix("::") {
+            let module_name = trailing_ident(qualifier);
+            return self
+                .env
+                .get_modules()
+                .filter(|module| module.get_name().display(&self.env).to_string() == module_name)
+                .flat_map(|module| self.module_members(&module, false))
+                .collect();
+        }
+        if let Some(receiver) = before.strip_suffix('.') {
+            return self.field_completions(path, trailing_ident(receiver), before.len());
+        }
+        self.file_modules(path)
+            .flat_map(|module| self.module_members(&module, true))
+            .collect()
+    }
+
+    /// Completes the fields of the type of the named local, using the type of its last
+    /// occurrence before the offset. Falls back to the fields of all structs in the file.
+    fn field_completions(&self, path: &Path, name: &str, offset: usize) -> Vec<CompletionItem> {
+        let struct_env = self.file_id(path).and_then(|file_id| {
+            let occ = self
+                .symbols
+                .occurrences_in(file_id)
+                .iter()
+                .filter(|occ| {
+                    matches!(occ.target, Target::Local(..))
+                        && occ.span.start().to_usize() < offset
+                        && self.text(file_id, occ.span) == name
+                })
+                .max_by_key(|occ| occ.span.start())?;
+            let (struct_env, _) = occ.ty.as_ref()?.skip_reference().get_struct(&self.env)?;
+            Some(struct_env)
+        });
+        match struct_env {
+            Some(struct_env) => self.fields(&struct_env),
+            None => self
+                .file_modules(path)
+                .flat_map(|module| {
+                    module
+                        .into_structs()
+                        .flat_map(|struct_env| self.fields(&struct_env))
+                        .collect::<Vec<_>>()
+                })
+                .collect(),
+        }
+    }
+
+    fn fields(&self, struct_env: &StructEnv) -> Vec<CompletionItem> {
+        let display_ctx = struct_env.get_type_display_ctx();
+        struct_env
+            .get_fields()
+            .map(|field| CompletionItem {
+                label: field.get_name().display(self.env.symbol_pool()).to_string(),
+                kind: Some(CompletionItemKind::FIELD),
+                detail: Some(field.get_type().display(&display_ctx).to_string()),
+                documentation: documentation(field.get_doc()),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    /// Returns the functions, structs and constants of the module. Private functions are only
+    /// included if the completion is within the module.
+    fn module_members(&self, module: &ModuleEnv, within_module: bool) -> Vec<CompletionItem> {
+        let pool = self.env.symbol_pool();
+        let functions = module
+            .get_functions()
+            .filter(|fun| within_module || fun.visibility() != Visibility::Private)
+            .map(|fun| CompletionItem {
+                label: fun.get_name().display(pool).to_string(),
+                kind: Some(CompletionItemKind::FUNCTION),
+                detail: Some(fun.get_header_string()),
+                documentation: documentation(fun.get_doc()),
+                ..Default::default()
+            });
+        let structs = module.get_structs().map(|struct_env| CompletionItem {
+            label: struct_env.get_name().display(pool).to_string(),
+            kind: Some(CompletionItemKind::STRUCT),
+            documentation: documentation(struct_env.get_doc()),
+            ..Default::default()
+        });
+        let constants = module
+            .get_named_constants()
+            .filter(|_| within_module)
+            .map(|constant| CompletionItem {
+                label: constant.get_name().display(pool).to_string(),
+                kind: Some(CompletionItemKind::CONSTANT),
+                detail: Some(
+                    constant
+                        .get_type()
+                        .display(&self.env.get_type_display_ctx())
+                        .to_string(),
+                ),
+                documentation: documentation(constant.get_doc()),
+                ..Default::default()
+            });
+        functions.chain(structs).chain(constants).collect()
+    }
+
+    /// Returns the modules declared in the file.
+    fn file_modules(&self, path: &Path) -> impl Iterator<Item = ModuleEnv<'_>> {
+        let file_id = self.file_id(path);
+        self.env
+            .get_modules()
+            .filter(move |module| Some(module.get_loc().file_id()) == file_id)
+    }
+}
+
+fn documentation(doc: &str) -> Option<Documentation> {
+    let doc = doc.trim();
+    (!doc.is_empty()).then(|| Documentation::String(doc.to_string()))
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn trailing_ident(text: &str) -> &str {
+    &text[text.trim_end_matches(is_ident_char).len()..]
+}
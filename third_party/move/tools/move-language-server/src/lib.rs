@@ -0,0 +1,17 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A language server for Move.
+//!
+//! Packages are resolved with `move-package` and checked with the v2 compiler, whose diagnostics
+//! are published to the client. Go-to-definition, find references and hover are answered from an
+//! index of the names in the model of the package, and completion offers module members and
+//! struct fields.
+
+mod analysis;
+mod completion;
+mod server;
+mod symbols;
+
+pub use analysis::PackageAnalysis;
+pub use server::run;
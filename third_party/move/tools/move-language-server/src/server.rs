@@ -0,0 +1,255 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The language server, communicating with the client over stdin and stdout.
+//!
+//! Packages are analyzed when one of their files is opened or saved. Requests are answered from
+//! the last analysis, except for completions, which also take the unsaved text of the file into
+//! account. A package is only resolved again if its manifest changed.
+
+use crate::analysis::{resolve, PackageAnalysis};
+use anyhow::bail;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+        Notification as _, PublishDiagnostics, ShowMessage,
+    },
+    request::{Completion, GotoDefinition, HoverRequest, References, Request as _},
+    CompletionOptions, CompletionParams, CompletionResponse, DidChangeTextDocumentParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, DidSaveTextDocumentParams,
+    GotoDefinitionParams, GotoDefinitionResponse, HoverParams, HoverProviderCapability,
+    MessageType, OneOf, PublishDiagnosticsParams, ReferenceParams, ServerCapabilities,
+    ShowMessageParams, TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+};
+use move_package::{
+    resolution::resolution_graph::ResolvedGraph, source_package::layout::SourcePackageLayout,
+    BuildConfig,
+};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Runs the language server until the client shuts it down. Packages are built with the given
+/// configuration.
+pub fn run(build_config: BuildConfig) -> anyhow::Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![":".to_string(), ".".to_string()]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+    Server {
+        connection: &connection,
+        build_config,
+        documents: BTreeMap::new(),
+        resolutions: BTreeMap::new(),
+        packages: BTreeMap::new(),
+    }
+    .main_loop()?;
+    io_threads.join()?;
+    Ok(())
+}
+
+struct Server<'a> {
+    connection: &'a Connection,
+    build_config: BuildConfig,
+    /// The current text of the open files.
+    documents: BTreeMap<PathBuf, String>,
+    /// The manifest and the resolution of each package by its root.
+    resolutions: BTreeMap<PathBuf, (String, ResolvedGraph)>,
+    /// The last analysis of each package by its root.
+    packages: BTreeMap<PathBuf, PackageAnalysis>,
+}
+
+impl Server<'_> {
+    fn main_loop(&mut self) -> anyhow::Result<()> {
+        let connection = self.connection;
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    let id = request.id.clone();
+                    let response = match self.handle_request(request) {
+                        Ok(result) => Response::new_ok(id, result),
+                        Err(err) => {
+                            Response::new_err(id, ErrorCode::InvalidParams as i32, err.to_string())
+                        },
+                    };
+                    connection.sender.send(response.into())?
+                },
+                Message::Notification(notification) => self.handle_notification(notification)?,
+                Message::Response(_) => {},
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> anyhow::Result<Value> {
+        Ok(match request.method.as_str() {
+            GotoDefinition::METHOD => {
+                let params: GotoDefinitionParams = serde_json::from_value(request.params)?;
+                let position = params.text_document_position_params;
+                let path = to_path(&position.text_document.uri)?;
+                let location = self
+                    .package_of(&path)
+                    .and_then(|package| package.definition(&path, position.position));
+                serde_json::to_value(location.map(GotoDefinitionResponse::Scalar))?
+            },
+            References::METHOD => {
+                let params: ReferenceParams = serde_json::from_value(request.params)?;
+                let position = params.text_document_position;
+                let path = to_path(&position.text_document.uri)?;
+                let locations = self.package_of(&path).map(|package| {
+                    package.references(&path, position.position, params.context.include_declaration)
+                });
+                serde_json::to_value(locations)?
+            },
+            HoverRequest::METHOD => {
+                let params: HoverParams = serde_json::from_value(request.params)?;
+                let position = params.text_document_position_params;
+                let path = to_path(&position.text_document.uri)?;
+                let hover = self
+                    .package_of(&path)
+                    .and_then(|package| package.hover(&path, position.position));
+                serde_json::to_value(hover)?
+            },
+            Completion::METHOD => {
+                let params: CompletionParams = serde_json::from_value(request.params)?;
+                let position = params.text_document_position;
+                let path = to_path(&position.text_document.uri)?;
+                let items = match (self.package_of(&path), self.documents.get(&path)) {
+                    (Some(package), Some(text)) => {
+                        package.completions(&path, text, position.position)
+                    },
+                    _ => vec![],
+                };
+                serde_json::to_value(CompletionResponse::Array(items))?
+            },
+            method => bail!("unsupported request `{}`", method),
+        })
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> anyhow::Result<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let path = to_path(&params.text_document.uri)?;
+                self.documents
+                    .insert(path.clone(), params.text_document.text);
+                if self.package_of(&path).is_none() {
+                    self.analyze(&path)?
+                }
+            },
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let path = to_path(&params.text_document.uri)?;
+                // With full synchronization, the last change contains the whole text
+                if let Some(change) = params.content_changes.into_iter().last() {
+                    self.documents.insert(path, change.text);
+                }
+            },
+            DidSaveTextDocument::METHOD => {
+                let params: DidSaveTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                self.analyze(&to_path(&params.text_document.uri)?)?
+            },
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                self.documents.remove(&to_path(&params.text_document.uri)?);
+            },
+            _ => {},
+        }
+        Ok(())
+    }
+
+    /// Analyzes the package containing the file and publishes its diagnostics. Errors building
+    /// the package are shown to the user.
+    fn analyze(&mut self, path: &Path) -> anyhow::Result<()> {
+        let Ok(root) = SourcePackageLayout::try_find_root(path) else {
+            return Ok(());
+        };
+        let package = match self
+            .resolution_of(&root)
+            .and_then(PackageAnalysis::from_resolved_graph)
+        {
+            Ok(package) => package,
+            Err(err) => {
+                return self.notify::<ShowMessage>(ShowMessageParams {
+                    typ: MessageType::ERROR,
+                    message: format!("Unable to build package {}: {:#}", root.display(), err),
+                })
+            },
+        };
+        for (path, diagnostics) in package.diagnostics() {
+            let Ok(uri) = Url::from_file_path(&path) else {
+                continue;
+            };
+            self.notify::<PublishDiagnostics>(PublishDiagnosticsParams::new(
+                uri,
+                diagnostics,
+                None,
+            ))?
+        }
+        self.packages.insert(package.root().to_path_buf(), package);
+        Ok(())
+    }
+
+    /// Returns the resolution of the package at `root`, resolving it only if this did not happen
+    /// yet or its manifest changed since.
+    fn resolution_of(&mut self, root: &Path) -> anyhow::Result<ResolvedGraph> {
+        let root = root.canonicalize()?;
+        let manifest = fs::read_to_string(root.join(SourcePackageLayout::Manifest.path()))?;
+        if let Some((resolved_manifest, graph)) = self.resolutions.get(&root) {
+            if *resolved_manifest == manifest {
+                return Ok(graph.clone());
+            }
+        }
+        let graph = resolve(&self.build_config, &root)?;
+        self.resolutions.insert(root, (manifest, graph.clone()));
+        Ok(graph)
+    }
+
+    /// Returns the analysis of the package containing the file.
+    fn package_of(&self, path: &Path) -> Option<&PackageAnalysis> {
+        let path = path.canonicalize().ok()?;
+        self.packages
+            .iter()
+            .filter(|(root, _)| path.starts_with(root))
+            // The innermost package, in case packages are nested
+            .max_by_key(|(root, _)| root.components().count())
+            .map(|(_, package)| package)
+    }
+
+    fn notify<N: lsp_types::notification::Notification>(
+        &self,
+        params: N::Params,
+    ) -> anyhow::Result<()> {
+        self.connection
+            .sender
+            .send(Notification::new(N::METHOD.to_string(), params).into())?;
+        Ok(())
+    }
+}
+
+fn to_path(uri: &Url) -> anyhow::Result<PathBuf> {
+    match uri.to_file_path() {
+        Ok(path) => Ok(path),
+        Err(()) => bail!("`{}` is not a file", uri),
+    }
+}
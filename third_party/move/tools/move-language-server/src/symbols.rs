@@ -0,0 +1,398 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! An index of the names occurring in a Move program and the declarations they refer to.
+//!
+//! The index is built from the model right after type checking, before any rewriting of function
+//! bodies (like inlining) takes place, so that it reflects the code as written.
+
+use codespan::{FileId, Span};
+use move_model::{
+    ast::{ExpData, Operation, Pattern},
+    model::{
+        FieldId, FunId, FunctionEnv, GlobalEnv, Loc, NamedConstantId, NodeId, Parameter,
+        QualifiedId, StructId,
+    },
+    symbol::Symbol,
+    ty::Type,
+};
+use std::collections::BTreeMap;
+
+/// What a name refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Target {
+    Function(QualifiedId<FunId>),
+    Struct(QualifiedId<StructId>),
+    Field(QualifiedId<StructId>, FieldId),
+    Constant(QualifiedId<NamedConstantId>),
+    /// A local or parameter, identified by the span of its declaration.
+    Local(FileId, Span),
+}
+
+/// An occurrence of a name in the source.
+#[derive(Debug, Clone)]
+pub(crate) struct Occurrence {
+    pub span: Span,
+    pub target: Target,
+    pub is_declaration: bool,
+    /// The type of the named entity, if it has one.
+    pub ty: Option<Type>,
+    /// The function the occurrence is in, which determines how type parameters are displayed.
+    pub fun: Option<QualifiedId<FunId>>,
+}
+
+/// A local declaration and the range of offsets it is in scope in.
+struct LocalDecl {
+    name: Symbol,
+    file_id: FileId,
+    span: Span,
+    scope: (usize, usize),
+}
+
+#[derive(Default)]
+pub(crate) struct Symbols {
+    occurrences: BTreeMap<FileId, Vec<Occurrence>>,
+}
+
+impl Symbols {
+    pub fn new(env: &GlobalEnv) -> Self {
+        let mut symbols = Self::default();
+        for module in env.get_modules() {
+            for struct_env in module.get_structs() {
+                let struct_id = struct_env.get_qualified_id();
+                symbols.add_declaration(
+                    env,
+                    &struct_env.get_loc(),
+                    struct_env.get_name(),
+                    Target::Struct(struct_id),
+                    None,
+                );
+                for field in struct_env.get_fields() {
+                    symbols.add_declaration(
+                        env,
+                        field.get_loc(),
+                        field.get_name(),
+                        Target::Field(struct_id, field.get_id()),
+                        Some(field.get_type()),
+                    );
+                }
+            }
+            for constant in module.get_named_constants() {
+                symbols.add_declaration(
+                    env,
+                    &constant.get_loc(),
+                    constant.get_name(),
+                    Target::Constant(module.get_id().qualified(constant.get_id())),
+                    Some(constant.get_type()),
+                );
+            }
+            for fun in module.get_functions() {
+                symbols.add_declaration(
+                    env,
+                    &fun.get_id_loc(),
+                    fun.get_name(),
+                    Target::Function(fun.get_qualified_id()),
+                    None,
+                );
+                symbols.add_function_body(env, &fun);
+            }
+        }
+        symbols
+    }
+
+    /// Returns the innermost occurrence at the byte offset of the file.
+    pub fn occurrence_at(&self, file_id: FileId, offset: usize) -> Option<&Occurrence> {
+        self.occurrences
+            .get(&file_id)?
+            .iter()
+            .filter(|occ| {
+                occ.span.start().to_usize() <= offset && offset <= occ.span.end().to_usize()
+            })
+            .min_by_key(|occ| occ.span.end().to_usize() - occ.span.start().to_usize())
+    }
+
+    /// Returns all occurrences in the file.
+    pub fn occurrences_in(&self, file_id: FileId) -> &[Occurrence] {
+        self.occurrences
+            .get(&file_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Returns all occurrences of the target, including its declaration.
+    pub fn occurrences_of(
+        &self,
+        target: Target,
+    ) -> impl Iterator<Item = (FileId, &Occurrence)> + '_ {
+        self.occurrences.iter().flat_map(move |(file_id, occs)| {
+            occs.iter()
+                .filter(move |occ| occ.target == target)
+                .map(move |occ| (*file_id, occ))
+        })
+    }
+
+    fn add(&mut self, file_id: FileId, occurrence: Occurrence) {
+        self.occurrences
+            .entry(file_id)
+            .or_default()
+            .push(occurrence)
+    }
+
+    fn add_declaration(
+        &mut self,
+        env: &GlobalEnv,
+        loc: &Loc,
+        name: Symbol,
+        target: Target,
+        ty: Option<Type>,
+    ) {
+        let span = name_span(env, loc, name, false);
+        self.add(loc.file_id(), Occurrence {
+            span,
+            target,
+            is_declaration: true,
+            ty,
+            fun: None,
+        })
+    }
+
+    fn add_function_body(&mut self, env: &GlobalEnv, fun: &FunctionEnv) {
+        let fun_id = Some(fun.get_qualified_id());
+        let mut locals = vec![];
+        for Parameter(name, ty, loc) in fun.get_parameters() {
+            let decl = self.add_local(env, &loc, name, Some(ty), fun_id, (0, usize::MAX));
+            locals.push(decl);
+        }
+        let Some(body) = fun.get_def() else {
+            return;
+        };
+
+        // Uses of locals are resolved once all declarations are known.
+        let mut uses = vec![];
+        body.visit_pre_order(&mut |exp| {
+            match exp {
+                ExpData::LocalVar(id, name) => uses.push((*id, *name)),
+                ExpData::Call(id, op, _) => self.add_operation(env, *id, op, fun_id),
+                // The locals declared by a block are in scope in its body only
+                ExpData::Block(_, pat, _, body) | ExpData::Lambda(_, pat, body) => {
+                    let scope = span_range(&env.get_node_loc(body.node_id()));
+                    self.add_pattern(env, pat, Some(scope), fun_id, &mut locals, &mut uses)
+                },
+                ExpData::Quant(id, _, ranges, ..) => {
+                    let scope = span_range(&env.get_node_loc(*id));
+                    for (pat, _) in ranges {
+                        self.add_pattern(env, pat, Some(scope), fun_id, &mut locals, &mut uses)
+                    }
+                },
+                ExpData::Match(_, _, arms) => {
+                    for arm in arms {
+                        let scope = span_range(&arm.loc);
+                        self.add_pattern(
+                            env,
+                            &arm.pattern,
+                            Some(scope),
+                            fun_id,
+                            &mut locals,
+                            &mut uses,
+                        )
+                    }
+                },
+                ExpData::Assign(_, pat, _) => {
+                    self.add_pattern(env, pat, None, fun_id, &mut locals, &mut uses)
+                },
+                _ => {},
+            }
+            true
+        });
+
+        for (id, name) in uses {
+            let loc = env.get_node_loc(id);
+            let span = name_span(env, &loc, name, false);
+            // The innermost declaration of the name in scope
+            let offset = span.start().to_usize();
+            let decl = locals
+                .iter()
+                .filter(|decl| {
+                    decl.name == name
+                        && decl.file_id == loc.file_id()
+                        && decl.scope.0 <= offset
+                        && offset <= decl.scope.1
+                })
+                .max_by_key(|decl| decl.span.start());
+            if let Some(decl) = decl {
+                self.add(loc.file_id(), Occurrence {
+                    span,
+                    target: Target::Local(decl.file_id, decl.span),
+                    is_declaration: false,
+                    ty: env.get_node_type_opt(id),
+                    fun: fun_id,
+                })
+            }
+        }
+    }
+
+    /// Adds the names in a pattern. If `scope` is set, the pattern declares locals which are in
+    /// scope in that range of offsets, otherwise it assigns to existing ones.
+    fn add_pattern(
+        &mut self,
+        env: &GlobalEnv,
+        pat: &Pattern,
+        scope: Option<(usize, usize)>,
+        fun: Option<QualifiedId<FunId>>,
+        locals: &mut Vec<LocalDecl>,
+        uses: &mut Vec<(NodeId, Symbol)>,
+    ) {
+        match pat {
+            Pattern::Var(id, name) => match scope {
+                Some(scope) => {
+                    let loc = env.get_node_loc(*id);
+                    let ty = env.get_node_type_opt(*id);
+                    let decl = self.add_local(env, &loc, *name, ty, fun, scope);
+                    locals.push(decl)
+                },
+                None => uses.push((*id, *name)),
+            },
+            Pattern::Tuple(_, pats) => {
+                for pat in pats {
+                    self.add_pattern(env, pat, scope, fun, locals, uses)
+                }
+            },
+            Pattern::Struct(id, struct_id, _, pats) => {
+                let struct_id = struct_id.to_qualified_id();
+                let name = env.get_struct(struct_id).get_name();
+                self.add_reference(env, *id, Target::Struct(struct_id), name, false, fun);
+                for pat in pats {
+                    self.add_pattern(env, pat, scope, fun, locals, uses)
+                }
+            },
+            Pattern::Wildcard(_) | Pattern::Error(_) => {},
+        }
+    }
+
+    fn add_local(
+        &mut self,
+        env: &GlobalEnv,
+        loc: &Loc,
+        name: Symbol,
+        ty: Option<Type>,
+        fun: Option<QualifiedId<FunId>>,
+        scope: (usize, usize),
+    ) -> LocalDecl {
+        let span = name_span(env, loc, name, false);
+        self.add(loc.file_id(), Occurrence {
+            span,
+            target: Target::Local(loc.file_id(), span),
+            is_declaration: true,
+            ty,
+            fun,
+        });
+        LocalDecl {
+            name,
+            file_id: loc.file_id(),
+            span,
+            scope,
+        }
+    }
+
+    fn add_operation(
+        &mut self,
+        env: &GlobalEnv,
+        id: NodeId,
+        op: &Operation,
+        fun: Option<QualifiedId<FunId>>,
+    ) {
+        let (target, name, last) = match op {
+            Operation::MoveFunction(mid, fid) | Operation::Closure(mid, fid) => {
+                let fun_id = mid.qualified(*fid);
+                (
+                    Target::Function(fun_id),
+                    env.get_function(fun_id).get_name(),
+                    false,
+                )
+            },
+            Operation::Pack(mid, sid, _) => {
+                let struct_id = mid.qualified(*sid);
+                (
+                    Target::Struct(struct_id),
+                    env.get_struct(struct_id).get_name(),
+                    false,
+                )
+            },
+            Operation::Select(mid, sid, fid) | Operation::UpdateField(mid, sid, fid) => {
+                let struct_id = mid.qualified(*sid);
+                // The field name comes last in `e.f`
+                (
+                    Target::Field(struct_id, *fid),
+                    env.get_struct(struct_id).get_field(*fid).get_name(),
+                    true,
+                )
+            },
+            Operation::Exists(_)
+            | Operation::BorrowGlobal(_)
+            | Operation::MoveTo
+            | Operation::MoveFrom => {
+                let Some(Type::Struct(mid, sid, _)) = env
+                    .get_node_instantiation_opt(id)
+                    .and_then(|inst| inst.into_iter().next())
+                else {
+                    return;
+                };
+                let struct_id = mid.qualified(sid);
+                (
+                    Target::Struct(struct_id),
+                    env.get_struct(struct_id).get_name(),
+                    false,
+                )
+            },
+            _ => return,
+        };
+        self.add_reference(env, id, target, name, last, fun)
+    }
+
+    fn add_reference(
+        &mut self,
+        env: &GlobalEnv,
+        id: NodeId,
+        target: Target,
+        name: Symbol,
+        last: bool,
+        fun: Option<QualifiedId<FunId>>,
+    ) {
+        let loc = env.get_node_loc(id);
+        let span = name_span(env, &loc, name, last);
+        self.add(loc.file_id(), Occurrence {
+            span,
+            target,
+            is_declaration: false,
+            ty: env.get_node_type_opt(id),
+            fun,
+        })
+    }
+}
+
+fn span_range(loc: &Loc) -> (usize, usize) {
+    (loc.span().start().to_usize(), loc.span().end().to_usize())
+}
+
+/// Finds the span of the name in the source of `loc`, which may cover more than the name, like a
+/// whole call or declaration. Takes the first occurrence of the name, or the last one if `last`
+/// is set, and falls back to the span of `loc`.
+fn name_span(env: &GlobalEnv, loc: &Loc, name: Symbol, last: bool) -> Span {
+    let name = name.display(env.symbol_pool()).to_string();
+    let Ok(source) = env.get_source(loc) else {
+        return loc.span();
+    };
+    let is_ident_char = |c: char| c.is_alphanumeric() || c == '_';
+    let mut matches = source.match_indices(name.as_str()).filter(|(idx, _)| {
+        !source[..*idx].ends_with(is_ident_char)
+            && !source[idx + name.len()..].starts_with(is_ident_char)
+    });
+    let found = if last { matches.last() } else { matches.next() };
+    match found {
+        Some((idx, _)) => {
+            let start = loc.span().start().to_usize() + idx;
+            Span::new(start as u32, (start + name.len()) as u32)
+        },
+        None => loc.span(),
+    }
+}
@@ -0,0 +1,23 @@
+module basic::coin {
+    /// A coin with a value.
+    struct Coin has store, drop {
+        /// The value of the coin.
+        value: u64,
+    }
+
+    const E_ZERO: u64 = 1;
+
+    /// Creates a coin.
+    public fun mint(value: u64): Coin {
+        assert!(value > 0, E_ZERO);
+        Coin { value }
+    }
+
+    public fun value(coin: &Coin): u64 {
+        coin.value
+    }
+
+    fun burn(coin: Coin) {
+        let Coin { value: _ } = coin;
+    }
+}
@@ -0,0 +1,8 @@
+module basic::wallet {
+    use basic::coin;
+
+    public fun double(): u64 {
+        let c = coin::mint(1);
+        coin::value(&c) + coin::value(&c)
+    }
+}
@@ -0,0 +1,5 @@
+module errors::errors {
+    fun f(): u64 {
+        true
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use lsp_types::{DiagnosticSeverity, HoverContents, Position};
+use move_language_server::PackageAnalysis;
+use move_package::BuildConfig;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tempfile::tempdir;
+
+fn analyze(package: &str) -> (PackageAnalysis, PathBuf) {
+    let root = Path::new("tests/packages").join(package);
+    let analysis = PackageAnalysis::new(
+        &BuildConfig {
+            install_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            ..Default::default()
+        },
+        &root,
+    )
+    .unwrap();
+    (analysis, root.join("sources"))
+}
+
+/// Returns the position of the `nth` occurrence of `pattern` in the text, plus `delta`
+/// characters.
+fn position_of(text: &str, pattern: &str, nth: usize, delta: u32) -> Position {
+    let (offset, _) = text.match_indices(pattern).nth(nth).unwrap();
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+    Position::new(
+        before.matches('\n').count() as u32,
+        (offset - line_start) as u32 + delta,
+    )
+}
+
+#[test]
+fn test_definition() {
+    let (analysis, sources) = analyze("basic");
+    let wallet = sources.join("wallet.move");
+    let text = fs::read_to_string(&wallet).unwrap();
+    let coin_text = fs::read_to_string(sources.join("coin.move")).unwrap();
+
+    let location = analysis
+        .definition(&wallet, position_of(&text, "mint", 0, 1))
+        .unwrap();
+    assert!(location.uri.path().ends_with("coin.move"));
+    assert_eq!(location.range.start, position_of(&coin_text, "mint", 0, 0));
+
+    // A local resolves to its declaration
+    let location = analysis
+        .definition(&wallet, position_of(&text, "&c", 1, 1))
+        .unwrap();
+    assert!(location.uri.path().ends_with("wallet.move"));
+    assert_eq!(location.range.start, position_of(&text, "c =", 0, 0));
+}
+
+#[test]
+fn test_references() {
+    let (analysis, sources) = analyze("basic");
+    let coin = sources.join("coin.move");
+    let text = fs::read_to_string(&coin).unwrap();
+
+    // The function `value` is declared in `coin` and called twice in `wallet`
+    let position = position_of(&text, "fun value", 0, 4);
+    assert_eq!(analysis.references(&coin, position, true).len(), 3);
+    assert_eq!(analysis.references(&coin, position, false).len(), 2);
+
+    // The struct `Coin` is referenced in signatures, by the pack in `mint` and the unpack in
+    // `burn`
+    let references = analysis.references(&coin, position_of(&text, "Coin", 0, 0), false);
+    assert!(references.len() >= 2);
+}
+
+#[test]
+fn test_hover() {
+    let (analysis, sources) = analyze("basic");
+    let wallet = sources.join("wallet.move");
+    let text = fs::read_to_string(&wallet).unwrap();
+
+    let hover_text = |position| match analysis.hover(&wallet, position).unwrap().contents {
+        HoverContents::Markup(content) => content.value,
+        contents => panic!("unexpected hover contents {:?}", contents),
+    };
+    let mint = hover_text(position_of(&text, "mint", 0, 0));
+    assert!(mint.contains("public fun mint(value: u64): "));
+    assert!(mint.contains("Creates a coin."));
+    let local = hover_text(position_of(&text, "c =", 0, 0));
+    assert!(local.contains("c: ") && local.contains("Coin"));
+}
+
+#[test]
+fn test_completion() {
+    let (analysis, sources) = analyze("basic");
+    let wallet = sources.join("wallet.move");
+    let text = fs::read_to_string(&wallet).unwrap();
+    let labels = |text: &str, position| {
+        analysis
+            .completions(&wallet, text, position)
+            .into_iter()
+            .map(|item| item.label)
+            .collect::<Vec<_>>()
+    };
+
+    // Module members, without private functions
+    let edited = text.replacen("coin::value(&c) + ", "coin::m", 1);
+    let members = labels(&edited, position_of(&edited, "coin::m", 1, 7));
+    assert!(members.contains(&"mint".to_string()));
+    assert!(members.contains(&"Coin".to_string()));
+    assert!(!members.contains(&"burn".to_string()));
+
+    // Fields of the type of a local
+    let edited = text.replacen("coin::value(&c) + ", "c.", 1);
+    let fields = labels(&edited, position_of(&edited, "c.", 0, 2));
+    assert_eq!(fields, vec!["value".to_string()]);
+}
+
+#[test]
+fn test_diagnostics() {
+    let (analysis, sources) = analyze("errors");
+    let diagnostics = analysis.diagnostics();
+    let path = sources.join("errors.move").canonicalize().unwrap();
+    let errors = diagnostics[&path]
+        .iter()
+        .filter(|diag| diag.severity == Some(DiagnosticSeverity::ERROR))
+        .collect::<Vec<_>>();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].range.start.line, 2);
+}
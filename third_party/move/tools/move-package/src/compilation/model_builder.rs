@@ -33,6 +33,58 @@ impl ModelBuilder {
     // TODO: In the future we will need a better way to do this to support renaming in packages
     // where we want to support building a Move model.
     pub fn build_model(&self) -> Result<GlobalEnv> {
+        let (all_targets, all_deps) = self.package_paths()?;
+        let skip_attribute_checks = self
+            .resolution_graph
+            .build_options
+            .compiler_config
+            .skip_attribute_checks;
+        let known_attributes = &self
+            .resolution_graph
+            .build_options
+            .compiler_config
+            .known_attributes;
+        match self.model_config.compiler_version {
+            CompilerVersion::V1 => run_model_builder_with_options(
+                all_targets,
+                vec![],
+                all_deps,
+                ModelBuilderOptions::default(),
+                skip_attribute_checks,
+                known_attributes,
+            ),
+            CompilerVersion::V2_0 => {
+                let options = self.compiler_v2_options_for(all_targets, all_deps);
+                let mut error_writer = StandardStream::stderr(ColorChoice::Auto);
+                move_compiler_v2::run_move_compiler_for_analysis(&mut error_writer, options)
+            },
+        }
+    }
+
+    /// Returns the options for running the v2 compiler on the package, for clients which want to
+    /// drive the compiler themselves, e.g. to obtain a model even if the package has errors.
+    pub fn compiler_v2_options(&self) -> Result<Options> {
+        let (all_targets, all_deps) = self.package_paths()?;
+        Ok(self.compiler_v2_options_for(all_targets, all_deps))
+    }
+
+    fn compiler_v2_options_for(
+        &self,
+        targets: Vec<PackagePaths>,
+        deps: Vec<PackagePaths>,
+    ) -> Options {
+        let compiler_config = &self.resolution_graph.build_options.compiler_config;
+        let mut options = make_options_for_v2_compiler(targets, deps);
+        options.language_version = compiler_config.language_version;
+        options
+            .known_attributes
+            .clone_from(&compiler_config.known_attributes);
+        options.skip_attribute_checks = compiler_config.skip_attribute_checks;
+        options
+    }
+
+    /// Returns the source paths of the targets and of the dependencies of the model.
+    fn package_paths(&self) -> Result<(Vec<PackagePaths>, Vec<PackagePaths>)> {
         // Make sure no renamings have been performed
         if let Some(pkg_name) = self.resolution_graph.contains_renaming() {
             anyhow::bail!(
@@ -117,38 +169,7 @@ impl ModelBuilder {
             ),
         };
 
-        let skip_attribute_checks = self
-            .resolution_graph
-            .build_options
-            .compiler_config
-            .skip_attribute_checks;
-        let known_attributes = &self
-            .resolution_graph
-            .build_options
-            .compiler_config
-            .known_attributes;
-        match self.model_config.compiler_version {
-            CompilerVersion::V1 => run_model_builder_with_options(
-                all_targets,
-                vec![],
-                all_deps,
-                ModelBuilderOptions::default(),
-                skip_attribute_checks,
-                known_attributes,
-            ),
-            CompilerVersion::V2_0 => {
-                let mut options = make_options_for_v2_compiler(all_targets, all_deps);
-                options.language_version = self
-                    .resolution_graph
-                    .build_options
-                    .compiler_config
-                    .language_version;
-                options.known_attributes.clone_from(known_attributes);
-                options.skip_attribute_checks = skip_attribute_checks;
-                let mut error_writer = StandardStream::stderr(ColorChoice::Auto);
-                move_compiler_v2::run_move_compiler_for_analysis(&mut error_writer, options)
-            },
-        }
+        Ok((all_targets, all_deps))
     }
 }
 
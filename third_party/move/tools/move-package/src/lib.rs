@@ -14,7 +14,10 @@ use crate::{
         build_plan::BuildPlan, compiled_package::CompiledPackage, model_builder::ModelBuilder,
     },
     package_lock::PackageLock,
-    resolution::resolution_graph::{ResolutionGraph, ResolvedGraph},
+    resolution::{
+        lockfile::Lockfile,
+        resolution_graph::{ResolutionGraph, ResolvedGraph},
+    },
     source_package::manifest_parser,
 };
 use anyhow::{bail, Result};
@@ -152,6 +155,22 @@ pub struct BuildConfig {
     #[clap(long = "skip-fetch-latest-git-deps", global = true)]
     pub skip_fetch_latest_git_deps: bool,
 
+    /// Fail instead of updating the Move.lock file if it is missing or out of date
+    #[clap(long = "locked", global = true)]
+    #[serde(default)]
+    pub locked: bool,
+
+    /// Like --locked, and additionally never fetch git dependencies from the network
+    #[clap(long = "frozen", global = true)]
+    #[serde(default)]
+    pub frozen: bool,
+
+    /// Ignore the Move.lock file and resolve git dependencies to the latest commit of their
+    /// revision, rewriting the lockfile. Used programmatically only.
+    #[clap(skip)]
+    #[serde(default)]
+    pub update_lockfile: bool,
+
     #[clap(flatten)]
     pub compiler_config: CompilerConfig,
 }
@@ -201,6 +220,7 @@ impl BuildConfig {
         let config = self.compiler_config.clone(); // Need clone because of mut self
         let resolved_graph = self.resolution_graph_for_package(path, writer)?;
         let mutx = PackageLock::lock();
+        resolved_graph.update_lockfile()?;
         let ret = BuildPlan::create(resolved_graph)?.compile(&config, writer);
         mutx.unlock();
         ret
@@ -216,6 +236,7 @@ impl BuildConfig {
         let config = self.compiler_config.clone(); // Need clone because of mut self
         let resolved_graph = self.resolution_graph_for_package(path, writer)?;
         let mutx = PackageLock::lock();
+        resolved_graph.update_lockfile()?;
         let ret = BuildPlan::create(resolved_graph)?.compile_no_exit(&config, writer);
         mutx.unlock();
         ret
@@ -227,6 +248,7 @@ impl BuildConfig {
         // vector as the writer
         let resolved_graph = self.resolution_graph_for_package(path, &mut Vec::new())?;
         let mutx = PackageLock::lock();
+        resolved_graph.update_lockfile()?;
         let ret = BuildPlan::create(resolved_graph)?.compile_evm(writer);
         mutx.unlock();
         ret
@@ -259,11 +281,24 @@ impl BuildConfig {
         // This should be locked as it inspects the environment for `MOVE_HOME` which could
         // possibly be set by a different process in parallel.
         let manifest = manifest_parser::parse_source_manifest(toml_manifest)?;
-        ResolutionGraph::download_dependency_repos(&manifest, self, &path, writer)?;
+        let lockfile = if self.update_lockfile {
+            None
+        } else {
+            Lockfile::read(&path)?
+        };
+        ResolutionGraph::download_dependency_repos(
+            &manifest,
+            self,
+            &path,
+            lockfile.as_ref(),
+            writer,
+        )?;
         mutx.unlock();
         Ok(())
     }
 
+    /// Resolves the package graph at `path`. The Move.lock file is only written if
+    /// `update_lockfile` is set; builds write it via `ResolvedGraph::update_lockfile`.
     pub fn resolution_graph_for_package<W: Write>(
         mut self,
         path: &Path,
@@ -280,7 +315,14 @@ impl BuildConfig {
         // possibly be set by a different process in parallel.
         let manifest = manifest_parser::parse_source_manifest(toml_manifest)?;
         let resolution_graph = ResolutionGraph::new(manifest, path, self, writer)?;
-        let ret = resolution_graph.resolve();
+        let ret = resolution_graph.resolve().and_then(|resolved_graph| {
+            if resolved_graph.build_options.update_lockfile {
+                resolved_graph.update_lockfile()?
+            } else {
+                resolved_graph.check_lockfile()?
+            }
+            Ok(resolved_graph)
+        });
         mutx.unlock();
         ret
     }
@@ -0,0 +1,274 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The `Move.lock` file of a package records the exact state of all its transitive dependencies
+//! at the time they were resolved: the commit a git dependency was checked out at, the digest of
+//! each dependency's sources, and the named addresses each dependency assigns. Builds check out
+//! the locked commits instead of the latest commit of the branch or tag given in `Move.toml`, so
+//! that they are reproducible until the lockfile is updated.
+
+use crate::{
+    resolution::resolution_graph::{run_git, ResolvedGraph, ResolvingPackage},
+    source_package::{
+        layout::SourcePackageLayout,
+        parsed_manifest::{Dependency, GitInfo, PackageName},
+        std_lib::StdLib,
+    },
+    BuildConfig,
+};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+
+/// The version of the lockfile format.
+pub const LOCKFILE_VERSION: u64 = 1;
+
+const LOCKFILE_HEADER: &str =
+    "# This file is generated by the Move package system. Do not edit it by hand.\n\n";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lockfile {
+    pub version: u64,
+    /// The locked dependencies, ordered by package name
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    pub name: String,
+    /// The digest of the manifest and sources of the package, excluding tests and examples
+    pub digest: String,
+    /// For git dependencies, the url of the repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<String>,
+    /// For git dependencies, the revision given in the manifest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+    /// For git dependencies, the directory of the package in the repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subdir: Option<String>,
+    /// For git dependencies, the commit the revision was resolved to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// The named addresses the package assigns a value to
+    #[serde(default)]
+    pub addresses: BTreeMap<String, String>,
+    /// Whether the package is only reachable via dev dependencies
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dev: bool,
+}
+
+impl Lockfile {
+    /// Reads the lockfile of the package at `root_path`, if there is one.
+    pub fn read(root_path: &Path) -> Result<Option<Lockfile>> {
+        let path = root_path.join(SourcePackageLayout::Lockfile.path());
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(&path)?;
+        let lockfile: Lockfile = toml::from_str(&contents)
+            .with_context(|| format!("Unable to parse lockfile {}", path.display()))?;
+        if lockfile.version != LOCKFILE_VERSION {
+            bail!(
+                "Unsupported version {} of lockfile {}, expected version {}",
+                lockfile.version,
+                path.display(),
+                LOCKFILE_VERSION
+            )
+        }
+        Ok(Some(lockfile))
+    }
+
+    /// Writes the lockfile of the package at `root_path`.
+    pub fn write(&self, root_path: &Path) -> Result<()> {
+        let path = root_path.join(SourcePackageLayout::Lockfile.path());
+        let contents = format!("{}{}", LOCKFILE_HEADER, toml::to_string(self)?);
+        fs::write(&path, contents)
+            .with_context(|| format!("Unable to write lockfile {}", path.display()))
+    }
+
+    /// Creates the lockfile for the dependencies of the root package in the resolved graph.
+    pub fn from_resolved_graph(graph: &ResolvedGraph) -> Result<Lockfile> {
+        let root_name = graph.root_package.package.name;
+        let non_dev_packages = non_dev_packages(graph);
+        let mut packages = vec![];
+        for (name, package) in &graph.package_table {
+            if *name == root_name {
+                continue;
+            }
+            // The digest must not depend on whether we are building in dev mode.
+            let digest = ResolvingPackage::get_package_digest_for_config(
+                &package.package_path,
+                &BuildConfig::default(),
+            )?;
+            let mut locked = LockedPackage {
+                name: name.to_string(),
+                digest: digest.to_string(),
+                git: None,
+                rev: None,
+                subdir: None,
+                commit: None,
+                addresses: package
+                    .source_package
+                    .addresses
+                    .iter()
+                    .flatten()
+                    .filter_map(|(name, addr)| {
+                        addr.map(|addr| (name.to_string(), addr.to_hex_literal()))
+                    })
+                    .collect(),
+                dev: !non_dev_packages.contains(name),
+            };
+            if let Some(git_info) = find_dependency(graph, *name).and_then(|dep| dep.git_info) {
+                let commit = run_git(&[
+                    "-C",
+                    &git_info.download_to.display().to_string(),
+                    "rev-parse",
+                    "HEAD",
+                ])
+                .with_context(|| format!("Unable to find the commit of package '{}'", name))?;
+                locked.git = Some(git_info.git_url.to_string());
+                locked.rev = Some(git_info.git_rev.to_string());
+                locked.subdir = Some(git_info.subdir.display().to_string());
+                locked.commit = Some(commit);
+            }
+            packages.push(locked);
+        }
+        Ok(Lockfile {
+            version: LOCKFILE_VERSION,
+            packages,
+        })
+    }
+
+    /// Returns the locked commit of the git dependency, if the lockfile has an entry for it and
+    /// the dependency has not changed in the manifest since.
+    pub fn locked_commit(&self, name: PackageName, git_info: &GitInfo) -> Option<&str> {
+        self.packages
+            .iter()
+            .find(|package| {
+                package.name == name.as_str()
+                    && package.git.as_deref() == Some(git_info.git_url.as_str())
+                    && package.rev.as_deref() == Some(git_info.git_rev.as_str())
+                    && package.subdir.as_deref() == Some(&git_info.subdir.display().to_string())
+            })
+            .and_then(|package| package.commit.as_deref())
+    }
+
+    /// Returns true if any of the locked packages is a git dependency.
+    pub fn has_git_dependencies(&self) -> bool {
+        self.packages.iter().any(|package| package.commit.is_some())
+    }
+
+    /// Adds the dev-only packages of `other` which are not in this lockfile.
+    pub fn extend_dev_only(&mut self, other: &Lockfile) {
+        for package in other.packages.iter().filter(|package| package.dev) {
+            if !self.packages.iter().any(|p| p.name == package.name) {
+                self.packages.push(package.clone())
+            }
+        }
+        self.packages.sort_by(|p1, p2| p1.name.cmp(&p2.name))
+    }
+}
+
+impl ResolvedGraph {
+    /// Brings the lockfile of the root package up to date with this graph. Fails if the lockfile
+    /// needs to change but the build is `--locked` or `--frozen`. Only builds and explicit updates
+    /// write the lockfile; everything else merely resolves the graph, see `check_lockfile`.
+    ///
+    /// Packages without git dependencies have nothing to pin, so no lockfile is created for them.
+    pub fn update_lockfile(&self) -> Result<()> {
+        match self.updated_lockfile()? {
+            Some(lockfile) => {
+                self.ensure_not_locked()?;
+                lockfile.write(&self.root_package_path)
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// Fails if the lockfile of the root package is out of date with this graph and the build is
+    /// `--locked` or `--frozen`.
+    pub fn check_lockfile(&self) -> Result<()> {
+        if (self.build_options.locked || self.build_options.frozen)
+            && self.updated_lockfile()?.is_some()
+        {
+            self.ensure_not_locked()?
+        }
+        Ok(())
+    }
+
+    /// Returns the lockfile for this graph, or `None` if the current lockfile is up to date.
+    ///
+    /// Entries for packages which are no longer in the graph are dropped. The exception are
+    /// dev-only packages outside of dev mode, as they are only part of the graph in dev mode;
+    /// those are dropped by the next resolution in dev mode, e.g. by `update-deps`.
+    fn updated_lockfile(&self) -> Result<Option<Lockfile>> {
+        let mut lockfile = Lockfile::from_resolved_graph(self)?;
+        let previous = if self.build_options.update_lockfile {
+            Lockfile::read(&self.root_package_path)?
+        } else {
+            self.lockfile.clone()
+        };
+        if let Some(previous) = previous {
+            if !self.build_options.dev_mode {
+                lockfile.extend_dev_only(&previous)
+            }
+            if previous == lockfile {
+                return Ok(None);
+            }
+        } else if !lockfile.has_git_dependencies() {
+            return Ok(None);
+        }
+        Ok(Some(lockfile))
+    }
+
+    fn ensure_not_locked(&self) -> Result<()> {
+        if self.build_options.locked || self.build_options.frozen {
+            bail!(
+                "{} of package '{}' needs to be updated, but --locked or --frozen was passed. \
+                Run `update-deps` to update it",
+                SourcePackageLayout::Lockfile.location_str(),
+                self.root_package.package.name
+            )
+        }
+        Ok(())
+    }
+}
+
+/// Returns the packages of the graph which are reachable from the root package without going
+/// through a dev dependency.
+fn non_dev_packages(graph: &ResolvedGraph) -> BTreeSet<PackageName> {
+    let mut reachable = BTreeSet::new();
+    let mut todo = vec![graph.root_package.package.name];
+    while let Some(name) = todo.pop() {
+        if !reachable.insert(name) {
+            continue;
+        }
+        if let Some(package) = graph.package_table.get(&name) {
+            todo.extend(package.source_package.dependencies.keys().copied())
+        }
+    }
+    reachable
+}
+
+/// Finds the dependency declaration of the named package in the graph.
+fn find_dependency(graph: &ResolvedGraph, name: PackageName) -> Option<Dependency> {
+    if let Some(std_version) = &graph.build_options.override_std {
+        if let Some(std_lib) = StdLib::from_package_name(name) {
+            return Some(std_lib.dependency(std_version));
+        }
+    }
+    graph.package_table.values().find_map(|package| {
+        package
+            .source_package
+            .dependencies
+            .get(&name)
+            .or_else(|| package.source_package.dev_dependencies.get(&name))
+            .cloned()
+    })
+}
@@ -4,12 +4,12 @@
 
 use crate::{
     package_hooks,
-    resolution::digest::compute_digest,
+    resolution::{digest::compute_digest, lockfile::Lockfile},
     source_package::{
         layout::SourcePackageLayout,
         manifest_parser::{parse_move_manifest_string, parse_source_manifest},
         parsed_manifest::{
-            Dependencies, Dependency, FileName, NamedAddress, PackageDigest, PackageName,
+            Dependencies, Dependency, FileName, GitInfo, NamedAddress, PackageDigest, PackageName,
             SourceManifest, SubstOrRename,
         },
         std_lib::{StdLib, StdVersion},
@@ -76,6 +76,8 @@ pub struct ResolutionGraph<T> {
     pub graph: DiGraphMap<PackageName, ()>,
     /// A mapping of package name to its resolution
     pub package_table: BTreeMap<PackageName, ResolutionPackage<T>>,
+    /// The lockfile of the root package the git dependencies were checked out from, if any
+    pub lockfile: Option<Lockfile>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -106,12 +108,18 @@ impl ResolvingGraph {
                 build_options.architecture = info.architecture;
             }
         }
+        let lockfile = if build_options.update_lockfile {
+            None
+        } else {
+            Lockfile::read(&root_package_path)?
+        };
         let mut resolution_graph = Self {
             root_package_path: root_package_path.clone(),
             build_options: build_options.clone(),
             root_package: root_package.clone(),
             graph: DiGraphMap::new(),
             package_table: BTreeMap::new(),
+            lockfile,
         };
 
         let override_std = &build_options.override_std;
@@ -140,6 +148,7 @@ impl ResolvingGraph {
             root_package,
             graph,
             package_table,
+            lockfile,
         } = self;
 
         let mut unresolved_addresses = Vec::new();
@@ -200,6 +209,7 @@ impl ResolvingGraph {
             root_package,
             graph,
             package_table: resolved_package_table,
+            lockfile,
         })
     }
 
@@ -415,6 +425,8 @@ impl ResolvingGraph {
             dep_name_in_pkg,
             &dep,
             self.build_options.skip_fetch_latest_git_deps,
+            self.lockfile.as_ref(),
+            self.build_options.frozen,
             writer,
         )?;
         let (dep_package, dep_package_dir) =
@@ -547,6 +559,7 @@ impl ResolvingGraph {
         manifest: &SourceManifest,
         build_options: &BuildConfig,
         root_path: &Path,
+        lockfile: Option<&Lockfile>,
         writer: &mut W,
     ) -> Result<()> {
         // include dev dependencies if in dev mode
@@ -563,6 +576,8 @@ impl ResolvingGraph {
                 *dep_name,
                 dep,
                 build_options.skip_fetch_latest_git_deps,
+                lockfile,
+                build_options.frozen,
                 writer,
             )?;
 
@@ -570,7 +585,13 @@ impl ResolvingGraph {
                 Self::parse_package_manifest(dep, dep_name, root_path.to_path_buf())
                     .with_context(|| format!("While processing dependency '{}'", *dep_name))?;
             // download dependencies of dependencies
-            Self::download_dependency_repos(&dep_manifest, build_options, root_path, writer)?;
+            Self::download_dependency_repos(
+                &dep_manifest,
+                build_options,
+                root_path,
+                lockfile,
+                writer,
+            )?;
         }
         Ok(())
     }
@@ -579,9 +600,21 @@ impl ResolvingGraph {
         dep_name: PackageName,
         dep: &Dependency,
         skip_fetch_latest_git_deps: bool,
+        lockfile: Option<&Lockfile>,
+        frozen: bool,
         writer: &mut W,
     ) -> Result<()> {
         if let Some(git_info) = &dep.git_info {
+            if let Some(commit) = lockfile.and_then(|l| l.locked_commit(dep_name, git_info)) {
+                return Self::checkout_locked_commit(dep_name, git_info, commit, frozen, writer);
+            }
+            if frozen {
+                bail!(
+                    "Git dependency '{}' is not locked in {}, which is required with --frozen",
+                    dep_name,
+                    SourcePackageLayout::Lockfile.location_str()
+                )
+            }
             let git_url = git_info.git_url.as_str();
             let git_rev = git_info.git_rev.as_str();
             let git_path = &git_info.download_to.display().to_string();
@@ -714,6 +747,82 @@ impl ResolvingGraph {
         }
         Ok(())
     }
+
+    /// Checks out the commit of a git dependency recorded in the lockfile. The repository is only
+    /// cloned or fetched if the commit is not available locally.
+    fn checkout_locked_commit<W: Write>(
+        dep_name: PackageName,
+        git_info: &GitInfo,
+        commit: &str,
+        frozen: bool,
+        writer: &mut W,
+    ) -> Result<()> {
+        let git_url = git_info.git_url.as_str();
+        let git_path = &git_info.download_to.display().to_string();
+        confirm_git_available()?;
+
+        if !git_info.download_to.exists() {
+            if frozen {
+                bail!(
+                    "Git dependency '{}' has not been downloaded, which is required with --frozen",
+                    dep_name
+                )
+            }
+            writeln!(
+                writer,
+                "{} {}",
+                "FETCHING GIT DEPENDENCY".bold().green(),
+                git_url,
+            )?;
+            run_git(&["clone", git_url, git_path]).with_context(|| {
+                format!("Failed to clone Git repository for package '{}'", dep_name)
+            })?;
+        } else if run_git(&["-C", git_path, "rev-parse", "HEAD"])
+            .ok()
+            .as_deref()
+            == Some(commit)
+        {
+            return Ok(());
+        }
+
+        if run_git(&[
+            "-C",
+            git_path,
+            "cat-file",
+            "-e",
+            &format!("{}^{{commit}}", commit),
+        ])
+        .is_err()
+        {
+            if frozen {
+                bail!(
+                    "Locked commit '{}' of git dependency '{}' has not been downloaded, which is \
+                    required with --frozen",
+                    commit,
+                    dep_name
+                )
+            }
+            writeln!(
+                writer,
+                "{} {}",
+                "UPDATING GIT DEPENDENCY".bold().green(),
+                git_url,
+            )?;
+            run_git(&["-C", git_path, "fetch", "origin"]).with_context(|| {
+                format!(
+                    "Failed to fetch latest Git state for package '{}'",
+                    dep_name
+                )
+            })?;
+        }
+        run_git(&["-C", git_path, "checkout", "--force", commit]).with_context(|| {
+            format!(
+                "Failed to checkout locked commit '{}' for package '{}'",
+                commit, dep_name
+            )
+        })?;
+        Ok(())
+    }
 }
 
 impl ResolvingPackage {
@@ -809,7 +918,7 @@ impl ResolvingPackage {
         Ok(places_to_look)
     }
 
-    fn get_package_digest_for_config(
+    pub(crate) fn get_package_digest_for_config(
         package_path: &Path,
         config: &BuildConfig,
     ) -> Result<PackageDigest> {
@@ -984,3 +1093,20 @@ fn confirm_git_available() -> Result<()> {
         },
     }
 }
+
+/// Runs git with the given arguments, failing if it does not exit successfully. Returns the
+/// trimmed standard output.
+pub(crate) fn run_git(args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .stdin(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        bail!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
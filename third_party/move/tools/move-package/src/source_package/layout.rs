@@ -16,6 +16,7 @@ pub enum SourcePackageLayout {
     Scripts,
     Examples,
     Manifest,
+    Lockfile,
     DocTemplates,
     Build,
 }
@@ -24,6 +25,7 @@ impl SourcePackageLayout {
     /// A Move source package is laid out on-disk as
     /// a_move_package
     /// ├── Move.toml      (required)
+    /// ├── Move.lock      (generated for packages with git dependencies)
     /// ├── sources        (required)
     /// ├── examples       (optional, dev mode)
     /// ├── scripts        (optional)
@@ -54,6 +56,7 @@ impl SourcePackageLayout {
         match self {
             Self::Sources => "sources",
             Self::Manifest => "Move.toml",
+            Self::Lockfile => "Move.lock",
             Self::Tests => "tests",
             Self::Scripts => "scripts",
             Self::Examples => "examples",
@@ -66,7 +69,8 @@ impl SourcePackageLayout {
     pub fn is_optional(&self) -> bool {
         match self {
             Self::Sources | Self::Manifest => false,
-            Self::Tests
+            Self::Lockfile
+            | Self::Tests
             | Self::Scripts
             | Self::Examples
             | Self::Specifications
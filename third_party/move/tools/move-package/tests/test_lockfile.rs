@@ -0,0 +1,216 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use move_package::{
+    resolution::{
+        lockfile::{LockedPackage, Lockfile, LOCKFILE_VERSION},
+        resolution_graph as RG,
+    },
+    source_package::{manifest_parser as MP, parsed_manifest::GitInfo},
+    BuildConfig,
+};
+use move_symbol_pool::Symbol;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tempfile::tempdir;
+use walkdir::WalkDir;
+
+fn locked_package(name: &str, dev: bool) -> LockedPackage {
+    LockedPackage {
+        name: name.to_string(),
+        digest: "0D4C3A".to_string(),
+        git: None,
+        rev: None,
+        subdir: None,
+        commit: None,
+        addresses: Default::default(),
+        dev,
+    }
+}
+
+/// Copies the package at `path` to a temporary directory, so that its lockfile can be written.
+fn copy_package(path: &Path, to: &Path) {
+    for entry in WalkDir::new(path) {
+        let entry = entry.unwrap();
+        let target = to.join(entry.path().strip_prefix(path).unwrap());
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(target).unwrap();
+        } else {
+            fs::copy(entry.path(), target).unwrap();
+        }
+    }
+}
+
+#[test]
+fn test_lockfile_of_local_dependencies() {
+    let path = Path::new("tests/test_sources/resolution/dep_good_digest");
+    let pm = MP::parse_move_manifest_from_file(path).unwrap();
+    let resolved_graph = RG::ResolutionGraph::new(
+        pm,
+        path.to_path_buf(),
+        BuildConfig {
+            install_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            ..Default::default()
+        },
+        &mut Vec::new(), /* empty writer as no diags needed */
+    )
+    .unwrap()
+    .resolve()
+    .unwrap();
+
+    let lockfile = Lockfile::from_resolved_graph(&resolved_graph).unwrap();
+    assert!(!lockfile.has_git_dependencies());
+    assert_eq!(lockfile.packages, vec![LockedPackage {
+        name: "OtherDep".to_string(),
+        digest: "6A88B7888D6049EB0121900E22B6FA2C0E702F042C8C8D4FD62AD5C990B9F9A8".to_string(),
+        git: None,
+        rev: None,
+        subdir: None,
+        commit: None,
+        // `B` is assigned by the root package, not by the dependency itself
+        addresses: Default::default(),
+        dev: false,
+    }]);
+}
+
+#[test]
+fn test_lockfile_roundtrip_and_locked_commit() {
+    let url = "https://github.com/aptos-labs/aptos-core.git";
+    let subdir = "aptos-move/framework/aptos-framework";
+    let lockfile = Lockfile {
+        version: LOCKFILE_VERSION,
+        packages: vec![LockedPackage {
+            name: "AptosFramework".to_string(),
+            digest: "0D4C3A".to_string(),
+            git: Some(url.to_string()),
+            rev: Some("mainnet".to_string()),
+            subdir: Some(subdir.to_string()),
+            commit: Some("d3adb33f".to_string()),
+            addresses: [("aptos_framework".to_string(), "0x1".to_string())]
+                .into_iter()
+                .collect(),
+            dev: false,
+        }],
+    };
+    let dir = tempdir().unwrap();
+    lockfile.write(dir.path()).unwrap();
+    let read = Lockfile::read(dir.path()).unwrap().unwrap();
+    assert_eq!(read, lockfile);
+    assert!(read.has_git_dependencies());
+
+    let git_info = GitInfo {
+        git_url: Symbol::from(url),
+        git_rev: Symbol::from("mainnet"),
+        subdir: PathBuf::from(subdir),
+        download_to: PathBuf::new(),
+    };
+    let name = Symbol::from("AptosFramework");
+    assert_eq!(read.locked_commit(name, &git_info), Some("d3adb33f"));
+
+    // Changing the revision in the manifest invalidates the locked commit
+    let git_info = GitInfo {
+        git_rev: Symbol::from("testnet"),
+        ..git_info
+    };
+    assert_eq!(read.locked_commit(name, &git_info), None);
+}
+
+#[test]
+fn test_missing_lockfile() {
+    let dir = tempdir().unwrap();
+    assert!(Lockfile::read(dir.path()).unwrap().is_none());
+}
+
+#[test]
+fn test_stale_entries_are_dropped() {
+    let path = Path::new("tests/test_sources/resolution/dep_good_digest");
+    let pm = MP::parse_move_manifest_from_file(path).unwrap();
+    let resolved_graph = RG::ResolutionGraph::new(
+        pm,
+        path.to_path_buf(),
+        BuildConfig {
+            install_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            ..Default::default()
+        },
+        &mut Vec::new(), /* empty writer as no diags needed */
+    )
+    .unwrap()
+    .resolve()
+    .unwrap();
+
+    // Outside of dev mode, entries of dev-only packages are kept, but not those of packages which
+    // are no longer dependencies.
+    let previous = Lockfile {
+        version: LOCKFILE_VERSION,
+        packages: vec![
+            locked_package("DevDep", true),
+            locked_package("OtherDep", false),
+            locked_package("RemovedDep", false),
+        ],
+    };
+    let mut lockfile = Lockfile::from_resolved_graph(&resolved_graph).unwrap();
+    lockfile.extend_dev_only(&previous);
+    assert_eq!(
+        lockfile
+            .packages
+            .iter()
+            .map(|package| (package.name.as_str(), package.dev))
+            .collect::<Vec<_>>(),
+        vec![("DevDep", true), ("OtherDep", false)]
+    );
+}
+
+#[test]
+fn test_only_builds_and_updates_write_lockfile() {
+    let dir = tempdir().unwrap();
+    copy_package(
+        Path::new("tests/test_sources/resolution/dep_good_digest"),
+        dir.path(),
+    );
+    let stale = Lockfile {
+        version: LOCKFILE_VERSION,
+        packages: vec![locked_package("RemovedDep", false)],
+    };
+    stale.write(dir.path()).unwrap();
+    let config = BuildConfig {
+        install_dir: Some(dir.path().join("build")),
+        ..Default::default()
+    };
+
+    // Resolving the graph leaves the lockfile alone...
+    config
+        .clone()
+        .resolution_graph_for_package(dir.path(), &mut Vec::new())
+        .unwrap();
+    assert_eq!(Lockfile::read(dir.path()).unwrap().unwrap(), stale);
+
+    // ...but fails if it is out of date and the build is locked
+    let locked_config = BuildConfig {
+        locked: true,
+        ..config.clone()
+    };
+    assert!(locked_config
+        .resolution_graph_for_package(dir.path(), &mut Vec::new())
+        .is_err());
+    assert_eq!(Lockfile::read(dir.path()).unwrap().unwrap(), stale);
+
+    // An explicit update rewrites it, dropping the stale entry
+    let update_config = BuildConfig {
+        update_lockfile: true,
+        ..config
+    };
+    update_config
+        .resolution_graph_for_package(dir.path(), &mut Vec::new())
+        .unwrap();
+    let lockfile = Lockfile::read(dir.path()).unwrap().unwrap();
+    assert_eq!(
+        lockfile
+            .packages
+            .iter()
+            .map(|package| package.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["OtherDep"]
+    );
+}
@@ -0,0 +1,535 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generation and shrinking of the arguments of `#[test(fuzz)]` tests.
+//!
+//! The types of the parameters of a fuzz test are read from the compiled test module. Values are
+//! generated randomly, biased towards the edge cases of each type. When a test fails, its
+//! arguments are shrunk step by step towards smaller values for as long as the test keeps failing.
+
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{FunctionDefinitionIndex, SignatureToken, StructFieldInformation},
+    CompiledModule,
+};
+use move_compiler::compiled_unit::NamedCompiledModule;
+use move_core_types::{
+    account_address::AccountAddress,
+    identifier::IdentStr,
+    language_storage::ModuleId,
+    u256::U256,
+    value::{MoveStruct, MoveValue},
+};
+use rand::{distributions::Alphanumeric, Rng};
+use std::collections::BTreeMap;
+
+/// The default number of times a fuzz test is run with random arguments.
+pub const DEFAULT_FUZZ_ITERATIONS: u64 = 100;
+
+/// The maximal length of generated vectors and strings.
+const MAX_GENERATED_LENGTH: usize = 16;
+
+/// The probability with which an edge case of a type is generated instead of a random value.
+const EDGE_CASE_PROBABILITY: f64 = 0.25;
+
+/// Configuration of the execution of fuzz tests.
+#[derive(Debug, Clone)]
+pub struct FuzzConfig {
+    /// The number of times each fuzz test is run with random arguments
+    pub iterations: u64,
+    /// The seed for generating arguments, to replay a failure. A random seed is chosen for each
+    /// test if none is given.
+    pub seed: Option<u64>,
+}
+
+impl Default for FuzzConfig {
+    fn default() -> Self {
+        Self {
+            iterations: DEFAULT_FUZZ_ITERATIONS,
+            seed: None,
+        }
+    }
+}
+
+/// The type of a parameter of a fuzz test, as far as generating values for it is concerned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FuzzType {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+    Signer,
+    Vector(Box<FuzzType>),
+    /// `std::string::String`, which is generated as valid UTF-8
+    String,
+    Struct {
+        name: String,
+        fields: Vec<(String, FuzzType)>,
+    },
+}
+
+/// A parameter of a fuzz test.
+#[derive(Debug, Clone)]
+pub struct FuzzParameter {
+    pub name: String,
+    pub ty: FuzzType,
+}
+
+/// Returns the parameters of the named test function, or a message describing why values can't
+/// be generated for them.
+pub fn fuzz_parameters(
+    modules: &BTreeMap<ModuleId, NamedCompiledModule>,
+    module_id: &ModuleId,
+    function_name: &str,
+) -> Result<Vec<FuzzParameter>, String> {
+    let named_module = modules
+        .get(module_id)
+        .ok_or_else(|| format!("Unable to find the compiled module {}", module_id))?;
+    let module = &named_module.module;
+    let (fdef_idx, handle) = module
+        .function_defs()
+        .iter()
+        .enumerate()
+        .map(|(idx, def)| (idx, module.function_handle_at(def.function)))
+        .find(|(_, handle)| module.identifier_at(handle.name).as_str() == function_name)
+        .ok_or_else(|| format!("Unable to find the test function {}", function_name))?;
+    if !handle.type_parameters.is_empty() {
+        return Err("Generic functions cannot be fuzz tests".to_string());
+    }
+    let parameter_names = named_module
+        .source_map
+        .get_function_source_map(FunctionDefinitionIndex::new(fdef_idx as u16))
+        .map(|source_map| source_map.parameters.clone())
+        .unwrap_or_default();
+    module
+        .signature_at(handle.parameters)
+        .0
+        .iter()
+        .enumerate()
+        .map(|(idx, token)| {
+            let name = parameter_names
+                .get(idx)
+                .map(|(name, _)| name.clone())
+                .unwrap_or_else(|| format!("arg{}", idx));
+            let ty = FuzzType::from_signature_token(modules, module, token).map_err(|err| {
+                format!(
+                    "Unable to generate values for parameter `{}`: {}",
+                    name, err
+                )
+            })?;
+            Ok(FuzzParameter { name, ty })
+        })
+        .collect()
+}
+
+impl FuzzType {
+    fn from_signature_token(
+        modules: &BTreeMap<ModuleId, NamedCompiledModule>,
+        module: &CompiledModule,
+        token: &SignatureToken,
+    ) -> Result<Self, String> {
+        use SignatureToken as S;
+        Ok(match token {
+            S::Bool => FuzzType::Bool,
+            S::U8 => FuzzType::U8,
+            S::U16 => FuzzType::U16,
+            S::U32 => FuzzType::U32,
+            S::U64 => FuzzType::U64,
+            S::U128 => FuzzType::U128,
+            S::U256 => FuzzType::U256,
+            S::Address => FuzzType::Address,
+            S::Signer => FuzzType::Signer,
+            S::Vector(elem) => {
+                FuzzType::Vector(Box::new(Self::from_signature_token(modules, module, elem)?))
+            },
+            S::Struct(idx) => {
+                let handle = module.struct_handle_at(*idx);
+                let struct_module_id =
+                    module.module_id_for_handle(module.module_handle_at(handle.module));
+                let struct_name = module.identifier_at(handle.name);
+                Self::from_struct(modules, &struct_module_id, struct_name)?
+            },
+            S::StructInstantiation(..) => {
+                return Err("generic structs are not supported".to_string())
+            },
+            // The VM passes references to arguments to functions taking references
+            S::Reference(inner) | S::MutableReference(inner) => {
+                Self::from_signature_token(modules, module, inner)?
+            },
+            S::TypeParameter(_) => return Err("type parameters are not supported".to_string()),
+        })
+    }
+
+    fn from_struct(
+        modules: &BTreeMap<ModuleId, NamedCompiledModule>,
+        module_id: &ModuleId,
+        struct_name: &IdentStr,
+    ) -> Result<Self, String> {
+        if module_id.address() == &AccountAddress::ONE
+            && module_id.name().as_str() == "string"
+            && struct_name.as_str() == "String"
+        {
+            return Ok(FuzzType::String);
+        }
+        let qualified_name = format!("{}::{}", module_id.name(), struct_name);
+        let module = &modules
+            .get(module_id)
+            .ok_or_else(|| format!("unable to find the module of struct `{}`", qualified_name))?
+            .module;
+        let def = module
+            .struct_defs()
+            .iter()
+            .find(|def| {
+                module.identifier_at(module.struct_handle_at(def.struct_handle).name) == struct_name
+            })
+            .ok_or_else(|| format!("unable to find struct `{}`", qualified_name))?;
+        match &def.field_information {
+            StructFieldInformation::Native => Err(format!(
+                "native struct `{}` is not supported",
+                qualified_name
+            )),
+            StructFieldInformation::Declared(fields) => Ok(FuzzType::Struct {
+                name: qualified_name,
+                fields: fields
+                    .iter()
+                    .map(|field| {
+                        Ok((
+                            module.identifier_at(field.name).to_string(),
+                            Self::from_signature_token(modules, module, &field.signature.0)?,
+                        ))
+                    })
+                    .collect::<Result<_, String>>()?,
+            }),
+        }
+    }
+
+    /// Generates a random value of this type.
+    pub fn generate(&self, rng: &mut impl Rng) -> MoveValue {
+        match self {
+            FuzzType::Bool => MoveValue::Bool(rng.gen()),
+            FuzzType::U8 => MoveValue::U8(pick(rng, [0, 1, u8::MAX], |rng| rng.gen())),
+            FuzzType::U16 => MoveValue::U16(pick(rng, [0, 1, u16::MAX], |rng| rng.gen())),
+            FuzzType::U32 => MoveValue::U32(pick(rng, [0, 1, u32::MAX], |rng| rng.gen())),
+            FuzzType::U64 => MoveValue::U64(pick(rng, [0, 1, u64::MAX], |rng| rng.gen())),
+            FuzzType::U128 => MoveValue::U128(pick(rng, [0, 1, u128::MAX], |rng| rng.gen())),
+            FuzzType::U256 => MoveValue::U256(pick(
+                rng,
+                [U256::zero(), U256::one(), U256::max_value()],
+                |rng| U256::from_le_bytes(&rng.gen()),
+            )),
+            FuzzType::Address => MoveValue::Address(generate_address(rng)),
+            FuzzType::Signer => MoveValue::Signer(generate_address(rng)),
+            FuzzType::Vector(elem) => {
+                let len = rng.gen_range(0, MAX_GENERATED_LENGTH + 1);
+                MoveValue::Vector((0..len).map(|_| elem.generate(rng)).collect())
+            },
+            FuzzType::String => {
+                let len = rng.gen_range(0, MAX_GENERATED_LENGTH + 1);
+                let s = (0..len)
+                    .map(|_| {
+                        if rng.gen_bool(EDGE_CASE_PROBABILITY) {
+                            rng.gen::<char>()
+                        } else {
+                            rng.sample(Alphanumeric)
+                        }
+                    })
+                    .collect();
+                string_value(s)
+            },
+            FuzzType::Struct { fields, .. } => MoveValue::Struct(MoveStruct::Runtime(
+                fields.iter().map(|(_, ty)| ty.generate(rng)).collect(),
+            )),
+        }
+    }
+
+    /// Returns values of this type which are simpler than `value`, most aggressive shrinks first.
+    pub fn shrink(&self, value: &MoveValue) -> Vec<MoveValue> {
+        match (self, value) {
+            (FuzzType::Bool, MoveValue::Bool(true)) => vec![MoveValue::Bool(false)],
+            (FuzzType::U8, MoveValue::U8(v)) => shrink_int((*v).into(), U256::unchecked_as_u8)
+                .into_iter()
+                .map(MoveValue::U8)
+                .collect(),
+            (FuzzType::U16, MoveValue::U16(v)) => shrink_int((*v).into(), U256::unchecked_as_u16)
+                .into_iter()
+                .map(MoveValue::U16)
+                .collect(),
+            (FuzzType::U32, MoveValue::U32(v)) => shrink_int((*v).into(), U256::unchecked_as_u32)
+                .into_iter()
+                .map(MoveValue::U32)
+                .collect(),
+            (FuzzType::U64, MoveValue::U64(v)) => shrink_int((*v).into(), U256::unchecked_as_u64)
+                .into_iter()
+                .map(MoveValue::U64)
+                .collect(),
+            (FuzzType::U128, MoveValue::U128(v)) => {
+                shrink_int((*v).into(), U256::unchecked_as_u128)
+                    .into_iter()
+                    .map(MoveValue::U128)
+                    .collect()
+            },
+            (FuzzType::U256, MoveValue::U256(v)) => shrink_int(*v, |v| *v)
+                .into_iter()
+                .map(MoveValue::U256)
+                .collect(),
+            (FuzzType::Address, MoveValue::Address(addr)) if addr != &AccountAddress::ZERO => {
+                vec![MoveValue::Address(AccountAddress::ZERO)]
+            },
+            (FuzzType::Signer, MoveValue::Signer(addr)) if addr != &AccountAddress::ZERO => {
+                vec![MoveValue::Signer(AccountAddress::ZERO)]
+            },
+            (FuzzType::Vector(elem), MoveValue::Vector(values)) => {
+                shrink_sequence(values, |value| elem.shrink(value))
+                    .into_iter()
+                    .map(MoveValue::Vector)
+                    .collect()
+            },
+            (FuzzType::String, value) => {
+                let chars = string_of_value(value).chars().collect::<Vec<_>>();
+                shrink_sequence(&chars, |c| {
+                    if c.is_ascii_lowercase() {
+                        vec![]
+                    } else {
+                        vec!['a']
+                    }
+                })
+                .into_iter()
+                .map(|chars| string_value(chars.into_iter().collect()))
+                .collect()
+            },
+            (FuzzType::Struct { fields, .. }, MoveValue::Struct(MoveStruct::Runtime(values))) => {
+                let mut shrunk = vec![];
+                for (idx, ((_, ty), value)) in fields.iter().zip(values).enumerate() {
+                    for field_value in ty.shrink(value) {
+                        let mut values = values.clone();
+                        values[idx] = field_value;
+                        shrunk.push(MoveValue::Struct(MoveStruct::Runtime(values)))
+                    }
+                }
+                shrunk
+            },
+            _ => vec![],
+        }
+    }
+
+    /// Formats a value of this type for display in test failures.
+    pub fn format_value(&self, value: &MoveValue) -> String {
+        match (self, value) {
+            (FuzzType::Vector(elem), MoveValue::Vector(values)) => format!(
+                "vector[{}]",
+                values
+                    .iter()
+                    .map(|value| elem.format_value(value))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            (FuzzType::String, value) => format!("{:?}", string_of_value(value)),
+            (FuzzType::Struct { name, fields }, MoveValue::Struct(MoveStruct::Runtime(values))) => {
+                format!(
+                    "{} {{ {} }}",
+                    name,
+                    fields
+                        .iter()
+                        .zip(values)
+                        .map(|((field, ty), value)| format!(
+                            "{}: {}",
+                            field,
+                            ty.format_value(value)
+                        ))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            },
+            (_, value) => value.to_string(),
+        }
+    }
+}
+
+/// Picks one of the edge cases with probability `EDGE_CASE_PROBABILITY`, otherwise generates a
+/// random value.
+fn pick<R: Rng, T: Copy>(rng: &mut R, edge_cases: [T; 3], random: impl FnOnce(&mut R) -> T) -> T {
+    if rng.gen_bool(EDGE_CASE_PROBABILITY) {
+        edge_cases[rng.gen_range(0, edge_cases.len())]
+    } else {
+        random(rng)
+    }
+}
+
+fn generate_address(rng: &mut impl Rng) -> AccountAddress {
+    pick(
+        rng,
+        [
+            AccountAddress::ZERO,
+            AccountAddress::ONE,
+            AccountAddress::TWO,
+        ],
+        |rng| AccountAddress::new(rng.gen()),
+    )
+}
+
+fn shrink_int<T>(value: U256, convert: impl Fn(&U256) -> T) -> Vec<T> {
+    let mut candidates = vec![];
+    for candidate in [
+        U256::zero(),
+        value / U256::from(2u8),
+        value.wrapping_sub(U256::one()),
+    ] {
+        if candidate < value && !candidates.contains(&candidate) {
+            candidates.push(candidate)
+        }
+    }
+    candidates.iter().map(convert).collect()
+}
+
+/// Shrinks a sequence by removing elements, then by shrinking single elements.
+fn shrink_sequence<T: Clone>(values: &[T], shrink_elem: impl Fn(&T) -> Vec<T>) -> Vec<Vec<T>> {
+    let mut shrunk = vec![];
+    if values.is_empty() {
+        return shrunk;
+    }
+    shrunk.push(vec![]);
+    if values.len() > 1 {
+        shrunk.push(values[..values.len() / 2].to_vec());
+    }
+    for idx in 0..values.len() {
+        let mut values = values.to_vec();
+        values.remove(idx);
+        shrunk.push(values)
+    }
+    for (idx, value) in values.iter().enumerate() {
+        for elem in shrink_elem(value) {
+            let mut values = values.to_vec();
+            values[idx] = elem;
+            shrunk.push(values)
+        }
+    }
+    shrunk
+}
+
+fn string_value(s: String) -> MoveValue {
+    MoveValue::Struct(MoveStruct::Runtime(vec![MoveValue::vector_u8(
+        s.into_bytes(),
+    )]))
+}
+
+fn string_of_value(value: &MoveValue) -> String {
+    let bytes = match value {
+        MoveValue::Struct(MoveStruct::Runtime(fields)) => match fields.as_slice() {
+            [MoveValue::Vector(bytes)] => bytes
+                .iter()
+                .filter_map(|byte| match byte {
+                    MoveValue::U8(byte) => Some(*byte),
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        },
+        _ => vec![],
+    };
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn struct_type() -> FuzzType {
+        FuzzType::Struct {
+            name: "m::S".to_string(),
+            fields: vec![
+                ("flag".to_string(), FuzzType::Bool),
+                ("name".to_string(), FuzzType::String),
+                (
+                    "values".to_string(),
+                    FuzzType::Vector(Box::new(FuzzType::U64)),
+                ),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_generate_is_deterministic() {
+        let ty = FuzzType::Vector(Box::new(struct_type()));
+        let values = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..10).map(|_| ty.generate(&mut rng)).collect::<Vec<_>>()
+        };
+        assert_eq!(values(42), values(42));
+        assert_ne!(values(42), values(43));
+    }
+
+    #[test]
+    fn test_generated_strings_are_utf8() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..100 {
+            let value = FuzzType::String.generate(&mut rng);
+            let MoveValue::Struct(MoveStruct::Runtime(fields)) = &value else {
+                panic!("expected a string struct")
+            };
+            let MoveValue::Vector(bytes) = &fields[0] else {
+                panic!("expected a byte vector")
+            };
+            let bytes = bytes
+                .iter()
+                .map(|byte| match byte {
+                    MoveValue::U8(byte) => *byte,
+                    _ => panic!("expected a byte"),
+                })
+                .collect::<Vec<_>>();
+            assert!(String::from_utf8(bytes).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_shrink_int() {
+        assert_eq!(FuzzType::U8.shrink(&MoveValue::U8(0)), vec![]);
+        assert_eq!(FuzzType::U8.shrink(&MoveValue::U8(1)), vec![MoveValue::U8(
+            0
+        )]);
+        assert_eq!(FuzzType::U64.shrink(&MoveValue::U64(10)), vec![
+            MoveValue::U64(0),
+            MoveValue::U64(5),
+            MoveValue::U64(9)
+        ]);
+    }
+
+    #[test]
+    fn test_shrink_to_minimal_failing_value() {
+        // Shrink greedily as a test runner does, with a "test" failing for vectors containing an
+        // element greater than 100.
+        let ty = FuzzType::Vector(Box::new(FuzzType::U64));
+        let fails = |value: &MoveValue| match value {
+            MoveValue::Vector(values) => values
+                .iter()
+                .any(|value| matches!(value, MoveValue::U64(v) if *v > 100)),
+            _ => false,
+        };
+        let mut value = MoveValue::Vector(vec![
+            MoveValue::U64(3),
+            MoveValue::U64(5000),
+            MoveValue::U64(7),
+        ]);
+        while let Some(shrunk) = ty.shrink(&value).into_iter().find(fails) {
+            value = shrunk
+        }
+        assert_eq!(value, MoveValue::Vector(vec![MoveValue::U64(101)]));
+    }
+
+    #[test]
+    fn test_format_value() {
+        let value = MoveValue::Struct(MoveStruct::Runtime(vec![
+            MoveValue::Bool(true),
+            string_value("a\"b".to_string()),
+            MoveValue::Vector(vec![MoveValue::U64(1), MoveValue::U64(2)]),
+        ]));
+        assert_eq!(
+            struct_type().format_value(&value),
+            r#"m::S { flag: true, name: "a\"b", values: vector[1u64, 2u64] }"#
+        );
+    }
+}
@@ -0,0 +1,165 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Gas snapshots record the gas used by each test in a file which is committed together with the
+//! tests. Comparing a test run against the snapshot reveals changes of the gas costs of the code
+//! under test.
+//!
+//! The snapshot has one line per test, consisting of the qualified name of the test and its gas
+//! usage, e.g. `0x1::coin::test_mint: 1234`.
+
+use colored::Colorize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs,
+    io::{Error, ErrorKind, Result, Write},
+    path::Path,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GasSnapshot {
+    gas_usage: BTreeMap<String, u64>,
+}
+
+/// The differences between a gas snapshot and the gas usage of a test run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GasSnapshotDiff {
+    /// The tests whose gas usage changed, with the old and the new gas usage
+    pub changed: Vec<(String, u64, u64)>,
+    /// The tests which are not in the snapshot yet
+    pub added: Vec<(String, u64)>,
+    /// The tests in the snapshot which do not exist anymore
+    pub removed: Vec<(String, u64)>,
+}
+
+impl GasSnapshot {
+    pub fn new(gas_usage: BTreeMap<String, u64>) -> Self {
+        Self { gas_usage }
+    }
+
+    /// Reads the snapshot from the file, returns `None` if it does not exist.
+    pub fn read(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        fs::read_to_string(path)?.parse().map(Some)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    /// Returns the snapshot updated with the gas usage of a test run. Tests which are not part of
+    /// `tests` are dropped, while tests which did not run, e.g. because they were filtered out,
+    /// keep their entry.
+    pub fn update(&self, gas_usage: &BTreeMap<String, u64>, tests: &BTreeSet<String>) -> Self {
+        let mut updated: BTreeMap<_, _> = self
+            .gas_usage
+            .iter()
+            .filter(|(name, _)| tests.contains(*name))
+            .map(|(name, gas)| (name.clone(), *gas))
+            .collect();
+        updated.extend(gas_usage.iter().map(|(name, gas)| (name.clone(), *gas)));
+        Self::new(updated)
+    }
+
+    /// Compares the gas usage of a test run against the snapshot. `tests` are the names of all
+    /// tests, including the ones which did not run.
+    pub fn diff(
+        &self,
+        gas_usage: &BTreeMap<String, u64>,
+        tests: &BTreeSet<String>,
+    ) -> GasSnapshotDiff {
+        let mut diff = GasSnapshotDiff::default();
+        for (name, new) in gas_usage {
+            match self.gas_usage.get(name) {
+                Some(old) if old != new => diff.changed.push((name.clone(), *old, *new)),
+                Some(_) => {},
+                None => diff.added.push((name.clone(), *new)),
+            }
+        }
+        for (name, old) in &self.gas_usage {
+            if !tests.contains(name) {
+                diff.removed.push((name.clone(), *old))
+            }
+        }
+        diff
+    }
+}
+
+impl GasSnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// Returns the changed tests whose gas usage changed by more than `threshold` percent.
+    pub fn exceeding(&self, threshold: f64) -> Vec<&str> {
+        self.changed
+            .iter()
+            .filter(|(_, old, new)| percent_change(*old, *new).abs() > threshold)
+            .map(|(name, _, _)| name.as_str())
+            .collect()
+    }
+
+    /// Prints the differences, highlighting the changes above `threshold` percent.
+    pub fn report<W: Write>(&self, writer: &mut W, threshold: f64) -> Result<()> {
+        for (name, old, new) in &self.changed {
+            let change = percent_change(*old, *new);
+            let line = format!("~ {}: {} -> {} ({:+.2}%)", name, old, new, change);
+            if change.abs() > threshold {
+                writeln!(writer, "{}", line.bold().bright_red())?
+            } else {
+                writeln!(writer, "{}", line)?
+            }
+        }
+        for (name, gas) in &self.added {
+            writeln!(writer, "{}", format!("+ {}: {}", name, gas).green())?
+        }
+        for (name, gas) in &self.removed {
+            writeln!(writer, "{}", format!("- {}: {}", name, gas).yellow())?
+        }
+        Ok(())
+    }
+}
+
+fn percent_change(old: u64, new: u64) -> f64 {
+    if old == 0 {
+        return if new == 0 { 0.0 } else { f64::INFINITY };
+    }
+    (new as f64 - old as f64) / old as f64 * 100.0
+}
+
+impl std::str::FromStr for GasSnapshot {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut gas_usage = BTreeMap::new();
+        for (idx, line) in s.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = line
+                .rsplit_once(':')
+                .and_then(|(name, gas)| Some((name.trim(), gas.trim().parse::<u64>().ok()?)));
+            match entry {
+                Some((name, gas)) => gas_usage.insert(name.to_string(), gas),
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("invalid gas snapshot entry in line {}: `{}`", idx + 1, line),
+                    ))
+                },
+            };
+        }
+        Ok(Self::new(gas_usage))
+    }
+}
+
+impl fmt::Display for GasSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, gas) in &self.gas_usage {
+            writeln!(f, "{}: {}", name, gas)?
+        }
+        Ok(())
+    }
+}
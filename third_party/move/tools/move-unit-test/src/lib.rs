@@ -4,10 +4,16 @@
 
 pub mod cargo_runner;
 pub mod extensions;
+pub mod fuzzer;
+pub mod gas_snapshot;
 pub mod test_reporter;
 pub mod test_runner;
 
-use crate::test_runner::TestRunner;
+use crate::{
+    fuzzer::{FuzzConfig, DEFAULT_FUZZ_ITERATIONS},
+    gas_snapshot::GasSnapshot,
+    test_runner::{CostTableGasMeterFactory, TestGasMeterFactory, TestRunner},
+};
 use clap::*;
 use move_command_line_common::files::verify_and_create_named_address_mapping;
 use move_compiler::{
@@ -21,9 +27,10 @@ use move_core_types::{effects::ChangeSet, language_storage::ModuleId};
 use move_vm_runtime::native_functions::NativeFunctionTable;
 use move_vm_test_utils::gas_schedule::CostTable;
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     io::{Result, Write},
     marker::Send,
+    path::{Path, PathBuf},
     sync::Mutex,
 };
 
@@ -107,6 +114,44 @@ pub struct UnitTestingConfig {
     #[clap(short = 'v', long = "verbose")]
     pub verbose: bool,
 
+    /// Number of times each `#[test(fuzz)]` test is run with random arguments
+    #[clap(
+        name = "fuzz_iterations",
+        long = "fuzz_iterations",
+        default_value_t = DEFAULT_FUZZ_ITERATIONS
+    )]
+    pub fuzz_iterations: u64,
+
+    /// Seed for generating the arguments of `#[test(fuzz)]` tests. Use the seed reported by a
+    /// failing fuzz test to replay it.
+    #[clap(name = "fuzz_seed", long = "fuzz_seed")]
+    pub fuzz_seed: Option<u64>,
+
+    /// Compare the gas used by each test against the snapshot in this file, which is created if
+    /// it does not exist. Changes are printed, and above `gas_snapshot_threshold` fail the run.
+    #[clap(name = "gas_snapshot", long = "gas_snapshot")]
+    pub gas_snapshot: Option<PathBuf>,
+
+    /// Write the gas used by each test to the gas snapshot instead of comparing against it
+    #[clap(
+        name = "update_gas_snapshot",
+        long = "update_gas_snapshot",
+        requires = "gas_snapshot"
+    )]
+    pub update_gas_snapshot: bool,
+
+    /// The change of the gas used by a test, in percent, up to which it matches the gas snapshot
+    #[clap(
+        name = "gas_snapshot_threshold",
+        long = "gas_snapshot_threshold",
+        default_value_t = 0.0
+    )]
+    pub gas_snapshot_threshold: f64,
+
+    /// Only warn about gas snapshot mismatches instead of failing
+    #[clap(name = "gas_snapshot_warn_only", long = "gas_snapshot_warn_only")]
+    pub gas_snapshot_warn_only: bool,
+
     /// Use the EVM-based execution backend.
     /// Does not work with --stackless.
     #[cfg(feature = "evm-backend")]
@@ -139,6 +184,12 @@ impl UnitTestingConfig {
             verbose: false,
             list: false,
             named_address_values: vec![],
+            fuzz_iterations: DEFAULT_FUZZ_ITERATIONS,
+            fuzz_seed: None,
+            gas_snapshot: None,
+            update_gas_snapshot: false,
+            gas_snapshot_threshold: 0.0,
+            gas_snapshot_warn_only: false,
 
             #[cfg(feature = "evm-backend")]
             evm: false,
@@ -209,6 +260,18 @@ impl UnitTestingConfig {
         Some(test_plan)
     }
 
+    /// Returns the factory for gas meters using the cost table, or a unit cost table if none is
+    /// given, bounded by the gas limit of the config.
+    pub fn cost_table_gas_meter_factory(
+        &self,
+        cost_table: Option<CostTable>,
+    ) -> CostTableGasMeterFactory {
+        CostTableGasMeterFactory::new(
+            cost_table,
+            self.gas_limit.unwrap_or(DEFAULT_EXECUTION_BOUND),
+        )
+    }
+
     /// Public entry point to Move unit testing as a library
     /// Returns `true` if all unit tests passed. Otherwise, returns `false`.
     pub fn run_and_report_unit_tests<W: Write + Send>(
@@ -218,6 +281,25 @@ impl UnitTestingConfig {
         genesis_state: Option<ChangeSet>,
         cost_table: Option<CostTable>,
         writer: W,
+    ) -> Result<(W, bool)> {
+        self.run_and_report_unit_tests_with_gas_meter(
+            test_plan,
+            native_function_table,
+            genesis_state,
+            self.cost_table_gas_meter_factory(cost_table),
+            writer,
+        )
+    }
+
+    /// Like `run_and_report_unit_tests`, but executes the tests with the gas meters created by
+    /// `gas_meter_factory`.
+    pub fn run_and_report_unit_tests_with_gas_meter<W: Write + Send>(
+        &self,
+        test_plan: TestPlan,
+        native_function_table: Option<NativeFunctionTable>,
+        genesis_state: Option<ChangeSet>,
+        gas_meter_factory: impl TestGasMeterFactory,
+        writer: W,
     ) -> Result<(W, bool)> {
         let shared_writer = Mutex::new(writer);
 
@@ -235,17 +317,32 @@ impl UnitTestingConfig {
             return Ok((shared_writer.into_inner().unwrap(), true));
         }
 
+        // The names of all tests, before filtering, to tell removed tests from filtered ones
+        let test_names: BTreeSet<_> = test_plan
+            .module_tests
+            .iter()
+            .flat_map(|(module_id, test_plan)| {
+                test_plan
+                    .tests
+                    .keys()
+                    .map(move |test_name| format!("{}::{}", format_module_id(module_id), test_name))
+            })
+            .collect();
+
         writeln!(shared_writer.lock().unwrap(), "Running Move unit tests")?;
         let mut test_runner = TestRunner::new(
-            self.gas_limit.unwrap_or(DEFAULT_EXECUTION_BOUND),
             self.num_threads,
             self.report_storage_on_error,
             self.report_stacktrace_on_abort,
             test_plan,
             native_function_table,
             genesis_state,
-            cost_table,
+            gas_meter_factory,
             self.verbose,
+            FuzzConfig {
+                iterations: self.fuzz_iterations,
+                seed: self.fuzz_seed,
+            },
             #[cfg(feature = "evm-backend")]
             self.evm,
         )
@@ -264,11 +361,66 @@ impl UnitTestingConfig {
             test_results.report_goldens(&shared_writer)?;
         }
 
-        let ok = test_results.summarize(&shared_writer)?;
+        let gas_usage = test_results.gas_usage();
+        let mut ok = test_results.summarize(&shared_writer)?;
 
-        let writer = shared_writer.into_inner().unwrap();
+        let mut writer = shared_writer.into_inner().unwrap();
+        if let Some(path) = &self.gas_snapshot {
+            ok &= self.check_gas_snapshot(path, &gas_usage, &test_names, &mut writer)?;
+        }
         Ok((writer, ok))
     }
+
+    /// Compares the gas usage of the tests against the gas snapshot, or writes it if the
+    /// snapshot does not exist or is to be updated. Returns `false` if the gas usage of a test
+    /// changed by more than the threshold.
+    fn check_gas_snapshot<W: Write>(
+        &self,
+        path: &Path,
+        gas_usage: &BTreeMap<String, u64>,
+        test_names: &BTreeSet<String>,
+        writer: &mut W,
+    ) -> Result<bool> {
+        let snapshot = match GasSnapshot::read(path)? {
+            Some(snapshot) if !self.update_gas_snapshot => snapshot,
+            snapshot => {
+                snapshot
+                    .unwrap_or_default()
+                    .update(gas_usage, test_names)
+                    .write(path)?;
+                writeln!(writer, "Wrote gas snapshot to {}", path.display())?;
+                return Ok(true);
+            },
+        };
+        let diff = snapshot.diff(gas_usage, test_names);
+        if diff.is_empty() {
+            return Ok(true);
+        }
+        writeln!(writer, "\nGas snapshot differences ({}):\n", path.display())?;
+        diff.report(writer, self.gas_snapshot_threshold)?;
+        let exceeding = diff.exceeding(self.gas_snapshot_threshold);
+        if exceeding.is_empty() {
+            writeln!(
+                writer,
+                "\nNo gas usage changed by more than {}%",
+                self.gas_snapshot_threshold
+            )?;
+            return Ok(true);
+        }
+        writeln!(
+            writer,
+            "\n{}: the gas usage of {} test(s) changed by more than {}%. Update the \
+            gas snapshot to accept the changes",
+            if self.gas_snapshot_warn_only {
+                "Warning"
+            } else {
+                "Gas snapshot mismatch"
+            },
+            exceeding.len(),
+            self.gas_snapshot_threshold,
+        )?;
+        Ok(self.gas_snapshot_warn_only)
+    }
 }
 
 #[test]
@@ -59,6 +59,20 @@ pub struct TestFailure {
     pub vm_error: Option<VMError>,
     pub failure_reason: FailureReason,
     pub storage_state: Option<String>,
+    pub fuzz_input: Option<FuzzInput>,
+}
+
+/// The input of a failing `#[test(fuzz)]` test.
+#[derive(Debug, Clone, Ord, PartialOrd, PartialEq, Eq)]
+pub struct FuzzInput {
+    /// The seed the arguments were generated with
+    pub seed: u64,
+    /// The number of runs until the test failed
+    pub runs: u64,
+    /// The number of steps in which the failing arguments were shrunk
+    pub shrink_steps: u64,
+    /// The name and value of each argument of the failing run, after shrinking
+    pub arguments: Vec<(String, String)>,
 }
 
 #[derive(Debug, Clone, Ord, PartialOrd, PartialEq, Eq)]
@@ -156,9 +170,15 @@ impl TestFailure {
             vm_error,
             failure_reason,
             storage_state,
+            fuzz_input: None,
         }
     }
 
+    pub fn with_fuzz_input(mut self, fuzz_input: FuzzInput) -> Self {
+        self.fuzz_input = Some(fuzz_input);
+        self
+    }
+
     pub fn render_error(&self, test_plan: &TestPlan) -> String {
         let error_string = match &self.failure_reason {
             FailureReason::NoError(message) => message.to_string(),
@@ -235,6 +255,26 @@ impl TestFailure {
             },
         };
 
+        let error_string = match &self.fuzz_input {
+            None => error_string,
+            Some(fuzz_input) => {
+                format!(
+                    "{}\n────── Fuzz test input ──────\n\
+                    Failed after {} run(s) with seed {}, shrunk in {} step(s) to:\n{}",
+                    error_string,
+                    fuzz_input.runs,
+                    fuzz_input.seed,
+                    fuzz_input.shrink_steps,
+                    fuzz_input
+                        .arguments
+                        .iter()
+                        .map(|(name, value)| format!("    {} = {}", name, value))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                )
+            },
+        };
+
         match &self.storage_state {
             None => error_string,
             Some(storage_state) => {
@@ -467,6 +507,28 @@ impl TestResults {
         Ok(())
     }
 
+    /// Returns the gas used by each passed test by its qualified name. `#[test(fuzz)]` tests are
+    /// left out, as their gas usage depends on the generated arguments.
+    pub fn gas_usage(&self) -> BTreeMap<String, u64> {
+        let mut gas_usage = BTreeMap::new();
+        for (module_id, test_results) in &self.final_statistics.passed {
+            let tests = &self.test_plan.module_tests[module_id].tests;
+            for test_result in test_results {
+                if tests[&test_result.function_ident].fuzz_arguments.is_none() {
+                    gas_usage.insert(
+                        format!(
+                            "{}::{}",
+                            format_module_id(module_id),
+                            test_result.function_ident
+                        ),
+                        test_result.instructions_executed,
+                    );
+                }
+            }
+        }
+        gas_usage
+    }
+
     pub fn report_statistics<W: Write>(&self, writer: &Mutex<W>) -> Result<()> {
         writeln!(writer.lock().unwrap(), "\nTest Statistics:\n")?;
 
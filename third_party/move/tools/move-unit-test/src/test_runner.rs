@@ -4,20 +4,25 @@
 
 use crate::{
     extensions, format_module_id,
+    fuzzer::{self, FuzzConfig, FuzzParameter},
     test_reporter::{
-        FailureReason, MoveError, TestFailure, TestResults, TestRunInfo, TestStatistics,
+        FailureReason, FuzzInput, MoveError, TestFailure, TestResults, TestRunInfo, TestStatistics,
     },
 };
 use anyhow::Result;
 use colored::*;
 use move_binary_format::{errors::VMResult, file_format::CompiledModule};
 use move_bytecode_utils::Modules;
-use move_compiler::unit_test::{ExpectedFailure, ModuleTestPlan, TestCase, TestPlan};
+use move_compiler::{
+    compiled_unit::NamedCompiledModule,
+    unit_test::{ExpectedFailure, ModuleTestPlan, TestCase, TestPlan},
+};
 use move_core_types::{
     account_address::AccountAddress,
     effects::{ChangeSet, Op},
     identifier::IdentStr,
-    value::serialize_values,
+    language_storage::ModuleId,
+    value::{serialize_values, MoveValue},
     vm_status::StatusCode,
 };
 use move_resource_viewer::MoveValueAnnotator;
@@ -32,8 +37,16 @@ use move_vm_test_utils::{
     gas_schedule::{zero_cost_schedule, CostTable, Gas, GasCost, GasStatus},
     InMemoryStorage,
 };
+use move_vm_types::gas::GasMeter;
+use rand::{rngs::StdRng, SeedableRng};
 use rayon::prelude::*;
-use std::{io::Write, marker::Send, sync::Mutex, time::Instant};
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    marker::Send,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 #[cfg(feature = "evm-backend")]
 use {
     evm::{backend::MemoryVicinity, ExitReason},
@@ -41,31 +54,85 @@ use {
     move_to_yul,
     primitive_types::{H160, U256},
     std::convert::TryInto,
-    std::time::Duration,
 };
 
+/// The maximal number of runs spent on shrinking the arguments of a failing fuzz test.
+const MAX_SHRINK_RUNS: u64 = 1000;
+
 /// Test state common to all tests
-pub struct SharedTestingConfig {
+pub struct SharedTestingConfig<F> {
     save_storage_state_on_failure: bool,
     report_stacktrace_on_abort: bool,
-    execution_bound: u64,
-    cost_table: CostTable,
+    gas_meter_factory: F,
     native_function_table: NativeFunctionTable,
     starting_storage_state: InMemoryStorage,
     #[allow(dead_code)] // used by some features
     source_files: Vec<String>,
     record_writeset: bool,
+    fuzz_config: FuzzConfig,
 
     #[cfg(feature = "evm-backend")]
     evm: bool,
 }
 
-pub struct TestRunner {
+pub struct TestRunner<F> {
     num_threads: usize,
-    testing_config: SharedTestingConfig,
+    testing_config: SharedTestingConfig<F>,
     tests: TestPlan,
 }
 
+/// Creates the gas meter each test is executed with. The gas used by a test is the one reported
+/// in the test statistics and recorded in gas snapshots.
+pub trait TestGasMeterFactory: Send + Sync {
+    type GasMeter<'a>: GasMeter
+    where
+        Self: 'a;
+
+    /// Returns the gas meter for a single run of a test. Running out of gas is reported as a test
+    /// timeout.
+    fn new_gas_meter(&self) -> Self::GasMeter<'_>;
+
+    /// Returns the gas consumed by the run the meter was used for.
+    fn gas_used(&self, gas_meter: &Self::GasMeter<'_>) -> u64;
+}
+
+/// Meters tests with a `CostTable`, bounding the gas of each test run.
+pub struct CostTableGasMeterFactory {
+    cost_table: CostTable,
+    execution_bound: u64,
+}
+
+impl CostTableGasMeterFactory {
+    pub fn new(cost_table: Option<CostTable>, execution_bound: u64) -> Self {
+        Self {
+            // TODO: our current implementation uses a unit cost table to prevent programs from
+            // running indefinitely. This should probably be done in a different way, like halting
+            // after executing a certain number of instructions or setting a timer.
+            //
+            // From the API standpoint, we should let the client specify the cost table.
+            cost_table: cost_table.unwrap_or_else(unit_cost_table),
+            execution_bound,
+        }
+    }
+}
+
+impl TestGasMeterFactory for CostTableGasMeterFactory {
+    type GasMeter<'a> = GasStatus<'a>;
+
+    fn new_gas_meter(&self) -> GasStatus<'_> {
+        GasStatus::new(&self.cost_table, Gas::new(self.execution_bound))
+    }
+
+    fn gas_used(&self, gas_meter: &GasStatus<'_>) -> u64 {
+        // TODO(Gas): This doesn't look quite right...
+        //            We're not computing the number of instructions executed even with a unit gas schedule.
+        Gas::new(self.execution_bound)
+            .checked_sub(gas_meter.remaining_gas())
+            .unwrap()
+            .into()
+    }
+}
+
 /// A gas schedule where every instruction has a cost of "1". This is used to bound execution of a
 /// test to a certain number of ticks.
 fn unit_cost_table() -> CostTable {
@@ -123,9 +190,8 @@ fn print_resources_and_extensions(
     Ok(buf)
 }
 
-impl TestRunner {
+impl<F: TestGasMeterFactory> TestRunner<F> {
     pub fn new(
-        execution_bound: u64,
         num_threads: usize,
         save_storage_state_on_failure: bool,
         report_stacktrace_on_abort: bool,
@@ -134,8 +200,9 @@ impl TestRunner {
         // we don't have to make assumptions about their gas parameters.
         native_function_table: Option<NativeFunctionTable>,
         genesis_state: Option<ChangeSet>,
-        cost_table: Option<CostTable>,
+        gas_meter_factory: F,
         record_writeset: bool,
+        fuzz_config: FuzzConfig,
         #[cfg(feature = "evm-backend")] evm: bool,
     ) -> Result<Self> {
         let source_files = tests
@@ -159,16 +226,11 @@ impl TestRunner {
                 save_storage_state_on_failure,
                 report_stacktrace_on_abort,
                 starting_storage_state,
-                execution_bound,
                 native_function_table,
-                // TODO: our current implementation uses a unit cost table to prevent programs from
-                // running indefinitely. This should probably be done in a different way, like halting
-                // after executing a certain number of instructions or setting a timer.
-                //
-                // From the API standpoint, we should let the client specify the cost table.
-                cost_table: cost_table.unwrap_or_else(unit_cost_table),
+                gas_meter_factory,
                 source_files,
                 record_writeset,
+                fuzz_config,
                 #[cfg(feature = "evm-backend")]
                 evm,
             },
@@ -187,7 +249,13 @@ impl TestRunner {
                     .tests
                     .module_tests
                     .par_iter()
-                    .map(|(_, test_plan)| self.testing_config.exec_module_tests(test_plan, writer))
+                    .map(|(_, test_plan)| {
+                        self.testing_config.exec_module_tests(
+                            test_plan,
+                            &self.tests.module_info,
+                            writer,
+                        )
+                    })
                     .reduce(TestStatistics::new, |acc, stats| acc.combine(stats));
 
                 Ok(TestResults::new(final_statistics, self.tests))
@@ -254,13 +322,13 @@ impl<'a, 'b, W: Write> TestOutput<'a, 'b, W> {
     }
 }
 
-impl SharedTestingConfig {
+impl<F: TestGasMeterFactory> SharedTestingConfig<F> {
     #[allow(clippy::field_reassign_with_default)]
     fn execute_via_move_vm(
         &self,
         test_plan: &ModuleTestPlan,
         function_name: &str,
-        test_info: &TestCase,
+        arguments: &[MoveValue],
     ) -> (
         VMResult<ChangeSet>,
         VMResult<NativeContextExtensions>,
@@ -274,7 +342,7 @@ impl SharedTestingConfig {
         let extensions = extensions::new_extensions();
         let mut session =
             move_vm.new_session_with_extensions(&self.starting_storage_state, extensions);
-        let mut gas_meter = GasStatus::new(&self.cost_table, Gas::new(self.execution_bound));
+        let mut gas_meter = self.gas_meter_factory.new_gas_meter();
         // TODO: collect VM logs if the verbose flag (i.e, `self.verbose`) is set
 
         let now = Instant::now();
@@ -283,7 +351,7 @@ impl SharedTestingConfig {
             &test_plan.module_id,
             IdentStr::new(function_name).unwrap(),
             vec![], // no ty args, at least for now
-            serialize_values(test_info.arguments.iter()),
+            serialize_values(arguments.iter()),
             &mut gas_meter,
             &mut TraversalContext::new(&storage),
         );
@@ -301,12 +369,7 @@ impl SharedTestingConfig {
         let test_run_info = TestRunInfo::new(
             function_name.to_string(),
             now.elapsed(),
-            // TODO(Gas): This doesn't look quite right...
-            //            We're not computing the number of instructions executed even with a unit gas schedule.
-            Gas::new(self.execution_bound)
-                .checked_sub(gas_meter.remaining_gas())
-                .unwrap()
-                .into(),
+            self.gas_meter_factory.gas_used(&gas_meter),
         );
         match session.finish_with_extensions() {
             Ok((cs, extensions)) => (Ok(cs), Ok(extensions), return_result, test_run_info),
@@ -314,142 +377,264 @@ impl SharedTestingConfig {
         }
     }
 
+    /// Runs the test with the given arguments. Returns the failure if the test did not end the
+    /// way it is expected to.
+    fn run_test(
+        &self,
+        test_plan: &ModuleTestPlan,
+        function_name: &str,
+        test_info: &TestCase,
+        arguments: &[MoveValue],
+        stats: &mut TestStatistics,
+    ) -> Result<TestRunInfo, TestFailure> {
+        let (cs_result, ext_result, exec_result, test_run_info) =
+            self.execute_via_move_vm(test_plan, function_name, arguments);
+
+        if self.record_writeset {
+            stats.test_output(
+                function_name.to_string(),
+                test_plan,
+                format!("{:?}", cs_result),
+            );
+        }
+
+        let save_session_state = || {
+            if self.save_storage_state_on_failure {
+                cs_result.ok().and_then(|changeset| {
+                    ext_result.ok().and_then(|extensions| {
+                        print_resources_and_extensions(
+                            &changeset,
+                            extensions,
+                            &self.starting_storage_state,
+                        )
+                        .ok()
+                    })
+                })
+            } else {
+                None
+            }
+        };
+        match exec_result {
+            Err(err) => {
+                let actual_err = MoveError(
+                    err.major_status(),
+                    err.sub_status(),
+                    err.location().clone(),
+                    err.message().cloned(),
+                );
+                assert!(err.major_status() != StatusCode::EXECUTED);
+                let failure_reason = match test_info.expected_failure.as_ref() {
+                    Some(ExpectedFailure::Expected) => return Ok(test_run_info),
+                    Some(ExpectedFailure::ExpectedWithError(expected_err))
+                        if expected_err == &actual_err =>
+                    {
+                        return Ok(test_run_info)
+                    },
+                    Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(code))
+                        if actual_err.0 == StatusCode::ABORTED
+                            && actual_err.1.is_some()
+                            && actual_err.1.unwrap() == *code =>
+                    {
+                        return Ok(test_run_info)
+                    },
+                    // incorrect cases
+                    Some(ExpectedFailure::ExpectedWithError(expected_err)) => {
+                        FailureReason::wrong_error(expected_err.clone(), actual_err)
+                    },
+                    Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(expected_code)) => {
+                        FailureReason::wrong_abort_deprecated(*expected_code, actual_err)
+                    },
+                    // Ran out of ticks, report a test timeout
+                    None if matches!(
+                        err.major_status(),
+                        StatusCode::OUT_OF_GAS | StatusCode::EXECUTION_LIMIT_REACHED
+                    ) =>
+                    {
+                        FailureReason::timeout()
+                    },
+                    None => FailureReason::unexpected_error(actual_err),
+                };
+                Err(TestFailure::new(
+                    failure_reason,
+                    test_run_info,
+                    Some(err),
+                    save_session_state(),
+                ))
+            },
+            Ok(_) => {
+                // Expected the test to fail, but it executed
+                if test_info.expected_failure.is_some() {
+                    Err(TestFailure::new(
+                        FailureReason::no_error(),
+                        test_run_info,
+                        None,
+                        save_session_state(),
+                    ))
+                } else {
+                    // Expected the test to execute fully and it did
+                    Ok(test_run_info)
+                }
+            },
+        }
+    }
+
+    /// Runs a `#[test(fuzz)]` test with random values for the parameters which are not assigned
+    /// in its attribute. The arguments of the first failing run are shrunk before reporting them.
+    fn run_fuzz_test(
+        &self,
+        test_plan: &ModuleTestPlan,
+        module_info: &BTreeMap<ModuleId, NamedCompiledModule>,
+        function_name: &str,
+        test_info: &TestCase,
+        fuzz_arguments: &[Option<MoveValue>],
+        stats: &mut TestStatistics,
+    ) -> Result<TestRunInfo, TestFailure> {
+        let parameters = fuzzer::fuzz_parameters(module_info, &test_plan.module_id, function_name)
+            .map_err(|message| {
+                TestFailure::new(
+                    FailureReason::property(message),
+                    TestRunInfo::new(function_name.to_string(), Duration::ZERO, 0),
+                    None,
+                    None,
+                )
+            })?;
+        let seed = self.fuzz_config.seed.unwrap_or_else(rand::random);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut elapsed_time = Duration::ZERO;
+        let mut instructions_executed = 0;
+        for runs in 1..=self.fuzz_config.iterations {
+            let arguments: Vec<_> = parameters
+                .iter()
+                .zip(fuzz_arguments)
+                .map(|(parameter, assigned)| {
+                    assigned
+                        .clone()
+                        .unwrap_or_else(|| parameter.ty.generate(&mut rng))
+                })
+                .collect();
+            match self.run_test(test_plan, function_name, test_info, &arguments, stats) {
+                Ok(test_run_info) => {
+                    elapsed_time += test_run_info.elapsed_time;
+                    instructions_executed += test_run_info.instructions_executed;
+                },
+                Err(failure) => {
+                    let (failure, arguments, shrink_steps) = self.shrink_fuzz_failure(
+                        test_plan,
+                        function_name,
+                        test_info,
+                        &parameters,
+                        fuzz_arguments,
+                        failure,
+                        arguments,
+                        stats,
+                    );
+                    return Err(failure.with_fuzz_input(FuzzInput {
+                        seed,
+                        runs,
+                        shrink_steps,
+                        arguments: parameters
+                            .iter()
+                            .zip(&arguments)
+                            .map(|(parameter, value)| {
+                                (parameter.name.clone(), parameter.ty.format_value(value))
+                            })
+                            .collect(),
+                    }));
+                },
+            }
+        }
+        Ok(TestRunInfo::new(
+            function_name.to_string(),
+            elapsed_time,
+            instructions_executed,
+        ))
+    }
+
+    /// Shrinks the generated arguments of a failing fuzz test for as long as the test keeps
+    /// failing. Returns the last failure, its arguments and the number of shrink steps taken.
+    fn shrink_fuzz_failure(
+        &self,
+        test_plan: &ModuleTestPlan,
+        function_name: &str,
+        test_info: &TestCase,
+        parameters: &[FuzzParameter],
+        fuzz_arguments: &[Option<MoveValue>],
+        mut failure: TestFailure,
+        mut arguments: Vec<MoveValue>,
+        stats: &mut TestStatistics,
+    ) -> (TestFailure, Vec<MoveValue>, u64) {
+        let mut shrink_steps = 0;
+        let mut shrink_runs = 0;
+        'shrink: loop {
+            for (idx, parameter) in parameters.iter().enumerate() {
+                // Arguments assigned in the test attribute are kept as they are
+                if fuzz_arguments[idx].is_some() {
+                    continue;
+                }
+                for candidate in parameter.ty.shrink(&arguments[idx]) {
+                    if shrink_runs == MAX_SHRINK_RUNS {
+                        break 'shrink;
+                    }
+                    shrink_runs += 1;
+                    let mut shrunk_arguments = arguments.clone();
+                    shrunk_arguments[idx] = candidate;
+                    if let Err(shrunk_failure) = self.run_test(
+                        test_plan,
+                        function_name,
+                        test_info,
+                        &shrunk_arguments,
+                        stats,
+                    ) {
+                        failure = shrunk_failure;
+                        arguments = shrunk_arguments;
+                        shrink_steps += 1;
+                        continue 'shrink;
+                    }
+                }
+            }
+            break;
+        }
+        (failure, arguments, shrink_steps)
+    }
+
     fn exec_module_tests_move_vm_and_stackless_vm(
         &self,
         test_plan: &ModuleTestPlan,
+        module_info: &BTreeMap<ModuleId, NamedCompiledModule>,
         output: &TestOutput<impl Write>,
     ) -> TestStatistics {
         let mut stats = TestStatistics::new();
 
         for (function_name, test_info) in &test_plan.tests {
-            let (cs_result, ext_result, exec_result, test_run_info) =
-                self.execute_via_move_vm(test_plan, function_name, test_info);
-
-            if self.record_writeset {
-                stats.test_output(
-                    function_name.to_string(),
+            let result = match &test_info.fuzz_arguments {
+                None => self.run_test(
                     test_plan,
-                    format!("{:?}", cs_result),
-                );
-            }
-
-            let save_session_state = || {
-                if self.save_storage_state_on_failure {
-                    cs_result.ok().and_then(|changeset| {
-                        ext_result.ok().and_then(|extensions| {
-                            print_resources_and_extensions(
-                                &changeset,
-                                extensions,
-                                &self.starting_storage_state,
-                            )
-                            .ok()
-                        })
-                    })
-                } else {
-                    None
-                }
+                    function_name,
+                    test_info,
+                    &test_info.arguments,
+                    &mut stats,
+                ),
+                Some(fuzz_arguments) => self.run_fuzz_test(
+                    test_plan,
+                    module_info,
+                    function_name,
+                    test_info,
+                    fuzz_arguments,
+                    &mut stats,
+                ),
             };
-            match exec_result {
-                Err(err) => {
-                    let actual_err = MoveError(
-                        err.major_status(),
-                        err.sub_status(),
-                        err.location().clone(),
-                        err.message().cloned(),
-                    );
-                    assert!(err.major_status() != StatusCode::EXECUTED);
-                    match test_info.expected_failure.as_ref() {
-                        Some(ExpectedFailure::Expected) => {
-                            output.pass(function_name);
-                            stats.test_success(test_run_info, test_plan);
-                        },
-                        Some(ExpectedFailure::ExpectedWithError(expected_err))
-                            if expected_err == &actual_err =>
-                        {
-                            output.pass(function_name);
-                            stats.test_success(test_run_info, test_plan);
-                        },
-                        Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(code))
-                            if actual_err.0 == StatusCode::ABORTED
-                                && actual_err.1.is_some()
-                                && actual_err.1.unwrap() == *code =>
-                        {
-                            output.pass(function_name);
-                            stats.test_success(test_run_info, test_plan);
-                        },
-                        // incorrect cases
-                        Some(ExpectedFailure::ExpectedWithError(expected_err)) => {
-                            output.fail(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::wrong_error(expected_err.clone(), actual_err),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        },
-                        Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(expected_code)) => {
-                            output.fail(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::wrong_abort_deprecated(
-                                        *expected_code,
-                                        actual_err,
-                                    ),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        },
-                        None if err.major_status() == StatusCode::OUT_OF_GAS => {
-                            // Ran out of ticks, report a test timeout and log a test failure
-                            output.timeout(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::timeout(),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        },
-                        None => {
-                            output.fail(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::unexpected_error(actual_err),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        },
-                    }
+            match result {
+                Ok(test_run_info) => {
+                    output.pass(function_name);
+                    stats.test_success(test_run_info, test_plan);
                 },
-                Ok(_) => {
-                    // Expected the test to fail, but it executed
-                    if test_info.expected_failure.is_some() {
-                        output.fail(function_name);
-                        stats.test_failure(
-                            TestFailure::new(
-                                FailureReason::no_error(),
-                                test_run_info,
-                                None,
-                                save_session_state(),
-                            ),
-                            test_plan,
-                        )
+                Err(test_failure) => {
+                    if let FailureReason::Timeout(_) = &test_failure.failure_reason {
+                        output.timeout(function_name)
                     } else {
-                        // Expected the test to execute fully and it did
-                        output.pass(function_name);
-                        stats.test_success(test_run_info, test_plan);
+                        output.fail(function_name)
                     }
+                    stats.test_failure(test_failure, test_plan)
                 },
             }
         }
@@ -677,6 +862,7 @@ impl SharedTestingConfig {
     fn exec_module_tests(
         &self,
         test_plan: &ModuleTestPlan,
+        module_info: &BTreeMap<ModuleId, NamedCompiledModule>,
         writer: &Mutex<impl Write>,
     ) -> TestStatistics {
         let output = TestOutput { test_plan, writer };
@@ -686,6 +872,6 @@ impl SharedTestingConfig {
             return self.exec_module_tests_evm(test_plan, &output);
         }
 
-        self.exec_module_tests_move_vm_and_stackless_vm(test_plan, &output)
+        self.exec_module_tests_move_vm_and_stackless_vm(test_plan, module_info, &output)
     }
 }
@@ -0,0 +1,112 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use move_unit_test::{gas_snapshot::GasSnapshot, UnitTestingConfig};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tempfile::tempdir;
+
+fn testing_config(snapshot: &Path) -> UnitTestingConfig {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/test_sources");
+    UnitTestingConfig {
+        source_files: vec![path
+            .join("construct_data.move")
+            .to_string_lossy()
+            .to_string()],
+        dep_files: move_stdlib::move_stdlib_files(),
+        gas_snapshot: Some(snapshot.to_path_buf()),
+        ..UnitTestingConfig::default_with_bound(None)
+    }
+    .with_named_addresses(move_stdlib::move_stdlib_named_addresses())
+}
+
+/// Runs the tests, returns whether they passed and the output.
+fn run(config: &UnitTestingConfig) -> (bool, String) {
+    let test_plan = config.build_test_plan().unwrap();
+    let (output, ok) = config
+        .run_and_report_unit_tests(test_plan, None, None, None, Vec::new())
+        .unwrap();
+    (ok, String::from_utf8(output).unwrap())
+}
+
+/// Returns the snapshot with the gas usage of the test scaled by `factor`.
+fn scale_entry(snapshot: &str, test: &str, factor: f64) -> String {
+    snapshot
+        .lines()
+        .map(|line| match line.strip_prefix(test) {
+            Some(gas) => {
+                let gas: u64 = gas.trim_start_matches(": ").parse().unwrap();
+                format!("{}: {}\n", test, (gas as f64 * factor) as u64)
+            },
+            None => format!("{}\n", line),
+        })
+        .collect()
+}
+
+#[test]
+fn test_gas_snapshot_mismatch() {
+    std::env::set_var("NO_COLOR", "1");
+    let dir = tempdir().unwrap();
+    let snapshot_path = dir.path().join(".gas-snapshot");
+    let config = testing_config(&snapshot_path);
+
+    // The first run records the snapshot, the second one matches it
+    let (ok, output) = run(&config);
+    assert!(ok && output.contains("Wrote gas snapshot"));
+    let snapshot = fs::read_to_string(&snapshot_path).unwrap();
+    assert!(snapshot.contains("0x1::M::make_sure_number_matches: "));
+    assert!(run(&config).0);
+
+    fs::write(
+        &snapshot_path,
+        scale_entry(&snapshot, "0x1::M::make_sure_number_matches", 0.5),
+    )
+    .unwrap();
+    let (ok, output) = run(&config);
+    assert!(!ok);
+    assert!(output.contains("~ 0x1::M::make_sure_number_matches: "));
+    assert!(output.contains("Gas snapshot mismatch: the gas usage of 1 test(s) changed"));
+
+    // The change is tolerated with a sufficient threshold or in warn only mode
+    let (ok, _) = run(&UnitTestingConfig {
+        gas_snapshot_threshold: 150.0,
+        ..config.clone()
+    });
+    assert!(ok);
+    let (ok, output) = run(&UnitTestingConfig {
+        gas_snapshot_warn_only: true,
+        ..config.clone()
+    });
+    assert!(ok && output.contains("Warning: the gas usage of 1 test(s) changed"));
+
+    // Updating restores the snapshot of the run
+    run(&UnitTestingConfig {
+        update_gas_snapshot: true,
+        ..config
+    });
+    assert_eq!(fs::read_to_string(&snapshot_path).unwrap(), snapshot);
+}
+
+#[test]
+fn test_gas_snapshot_filter() {
+    let dir = tempdir().unwrap();
+    let snapshot_path = dir.path().join(".gas-snapshot");
+    let config = testing_config(&snapshot_path);
+    run(&config);
+    let snapshot = GasSnapshot::read(&snapshot_path).unwrap().unwrap();
+
+    // Tests which did not run keep their entry, tests which do not exist anymore are dropped
+    fs::write(&snapshot_path, format!("{}0x1::M::removed: 10\n", snapshot)).unwrap();
+    let (ok, output) = run(&UnitTestingConfig {
+        filter: Some("number_matches".to_string()),
+        update_gas_snapshot: true,
+        ..config
+    });
+    assert!(ok && output.contains("Wrote gas snapshot"));
+    assert_eq!(
+        GasSnapshot::read(&snapshot_path).unwrap().unwrap(),
+        snapshot
+    );
+}
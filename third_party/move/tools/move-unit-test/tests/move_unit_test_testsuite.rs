@@ -97,6 +97,8 @@ fn run_test_impl(path: &Path) -> anyhow::Result<()> {
             .collect(),
         verbose: true,
         report_stacktrace_on_abort: true,
+        // A fixed seed keeps the arguments reported by failing fuzz tests stable
+        fuzz_seed: Some(0),
 
         ..UnitTestingConfig::default_with_bound(None)
     };
@@ -0,0 +1,49 @@
+module 0x1::M {
+    use std::signer;
+    use std::string::{Self, String};
+    use std::vector;
+
+    struct Pair has drop {
+        a: u64,
+        b: bool,
+    }
+
+    #[test(fuzz)]
+    fun add_commutes(x: u32, y: u32) {
+        assert!((x as u64) + (y as u64) == (y as u64) + (x as u64), 0);
+    }
+
+    #[test(fuzz)]
+    fun reverse_twice(v: vector<u8>) {
+        let w = v;
+        vector::reverse(&mut w);
+        vector::reverse(&mut w);
+        assert!(w == v, 0);
+    }
+
+    #[test(fuzz)]
+    fun valid_strings(s: String) {
+        // Aborts if the bytes are not valid UTF-8
+        let t = string::utf8(*string::bytes(&s));
+        assert!(string::length(&t) == string::length(&s), 0);
+    }
+
+    #[test(fuzz)]
+    fun structs(p: Pair) {
+        let Pair { a, b } = p;
+        assert!(a / 2 <= a, 0);
+        assert!(b || !b, 1);
+    }
+
+    #[test(fuzz, s = @0x42)]
+    fun assigned_signer(s: signer, a: address) {
+        assert!(signer::address_of(&s) == @0x42, 0);
+        assert!(vector::length(&std::bcs::to_bytes(&a)) == 32, 1);
+    }
+
+    #[test(fuzz)]
+    #[expected_failure(arithmetic_error, location = Self)]
+    fun always_overflows(x: u8) {
+        let _y = x + 255 + 1;
+    }
+}
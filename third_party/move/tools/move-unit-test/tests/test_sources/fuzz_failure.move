@@ -0,0 +1,7 @@
+module 0x1::M {
+    // Fails for every argument but 0, so the first failing argument is shrunk to 1
+    #[test(fuzz)]
+    fun nonzero_fails(x: u64) {
+        if (x != 0) abort 0
+    }
+}
@@ -0,0 +1,57 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use move_core_types::{
+    account_address::AccountAddress,
+    ident_str,
+    identifier::IdentStr,
+    language_storage::{StructTag, CORE_CODE_ADDRESS},
+    move_resource::MoveStructType,
+};
+use serde::{Deserialize, Serialize};
+
+/// Struct that represents a TransferEvent, emitted when the owner of an object changes.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ObjectTransferEvent {
+    object: AccountAddress,
+    from: AccountAddress,
+    to: AccountAddress,
+}
+
+impl ObjectTransferEvent {
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self> {
+        bcs::from_bytes(bytes).map_err(Into::into)
+    }
+
+    /// The struct tag of Transfer, the module event with the same layout emitted in place of
+    /// TransferEvent.
+    pub fn module_event_struct_tag() -> StructTag {
+        StructTag {
+            address: CORE_CODE_ADDRESS,
+            module: Self::module_identifier(),
+            name: ident_str!("Transfer").to_owned(),
+            type_args: vec![],
+        }
+    }
+
+    /// Get the address of the transferred object
+    pub fn object(&self) -> AccountAddress {
+        self.object
+    }
+
+    /// Get the previous owner
+    pub fn from(&self) -> AccountAddress {
+        self.from
+    }
+
+    /// Get the new owner
+    pub fn to(&self) -> AccountAddress {
+        self.to
+    }
+}
+
+impl MoveStructType for ObjectTransferEvent {
+    const MODULE_NAME: &'static IdentStr = ident_str!("object");
+    const STRUCT_NAME: &'static IdentStr = ident_str!("TransferEvent");
+}
@@ -3,7 +3,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    account_config::{DepositEvent, NewBlockEvent, NewEpochEvent, WithdrawEvent},
+    account_config::{
+        DepositEvent, NewBlockEvent, NewEpochEvent, ObjectTransferEvent, WithdrawEvent,
+    },
     dkg::DKGStartEvent,
     event::EventKey,
     jwks::ObservedJWKsUpdated,
@@ -365,6 +367,21 @@ impl TryFrom<&ContractEvent> for DepositEvent {
     }
 }
 
+impl TryFrom<&ContractEvent> for ObjectTransferEvent {
+    type Error = Error;
+
+    fn try_from(event: &ContractEvent) -> Result<Self> {
+        let expected = match event {
+            ContractEvent::V1(_) => Self::struct_tag(),
+            ContractEvent::V2(_) => Self::module_event_struct_tag(),
+        };
+        if event.type_tag() != &TypeTag::Struct(Box::new(expected)) {
+            bail!("Expected object transfer event")
+        }
+        Self::try_from_bytes(event.event_data())
+    }
+}
+
 impl TryFrom<&ContractEvent> for ObservedJWKsUpdated {
     type Error = Error;
 
@@ -9,6 +9,7 @@ use crate::{
     transaction::{AccountTransactionsWithProof, Version},
 };
 use anyhow::Result;
+use move_core_types::language_storage::StructTag;
 
 #[derive(Clone, Copy, Eq, PartialEq)]
 pub enum Order {
@@ -45,4 +46,35 @@ pub trait IndexerReader: Send + Sync {
         include_events: bool,
         ledger_version: Version,
     ) -> Result<AccountTransactionsWithProof>;
+
+    fn get_account_activity_start_version(&self) -> Result<Version>;
+
+    fn get_account_activity(
+        &self,
+        address: AccountAddress,
+        start_version: Version,
+        order: Order,
+        limit: u64,
+        include_events: bool,
+        ledger_version: Version,
+    ) -> Result<AccountTransactionsWithProof>;
+
+    fn get_object_ownership_start_version(&self) -> Result<Version>;
+
+    fn get_owned_objects(
+        &self,
+        owner: AccountAddress,
+        resource_type: Option<&StructTag>,
+        start_object: AccountAddress,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<(Vec<(AccountAddress, Version)>, Option<AccountAddress>)>;
+
+    fn get_object_owner_history(
+        &self,
+        object: AccountAddress,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<(Version, Option<AccountAddress>)>>;
 }